@@ -16,6 +16,7 @@ export default [
       'src-tauri/target/**',
       '**/*.min.js',
       '**/coverage/**',
+      'src/lib/api-types.generated.ts',
       '**/.nuxt/**',
       '**/.output/**',
       '**/.vite/**',
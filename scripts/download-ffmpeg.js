@@ -4,11 +4,14 @@
 /**
  * FFmpeg Bundling Setup for Honeymelon (Apple Silicon only)
  * - Downloads arm64 ffmpeg/ffprobe zips from OSXExperts.NET
+ * - Checks each zip and the binary in it against the SHA-256s pinned in
+ *   managed-ffmpeg.json, or records them there when run with --pin
  * - Extracts and installs into src-tauri/bin
  * - Verifies architecture
  * - Ad-hoc codesigns the binaries (best-effort)
  */
 
+import crypto from 'node:crypto';
 import fs from 'node:fs';
 import path from 'node:path';
 import os from 'node:os';
@@ -21,15 +24,24 @@ const GREEN = '\x1b[32m';
 const YELLOW = '\x1b[33m';
 const NC = '\x1b[0m';
 
-// Config (Apple Silicon builds from OSXExperts.NET)
-const FFMPEG_VERSION = '7.1.1';
-const FFMPEG_URL = 'https://www.osxexperts.net/ffmpeg711arm.zip';
-const FFPROBE_URL = 'https://www.osxexperts.net/ffprobe711arm.zip';
-
 const SCRIPT_DIR = path.dirname(fileURLToPath(import.meta.url));
 const PROJECT_ROOT = path.resolve(SCRIPT_DIR, '..');
 const BIN_DIR = path.join(PROJECT_ROOT, 'src-tauri', 'bin');
 
+// Config (Apple Silicon builds from OSXExperts.NET), shared with the app's
+// managed FFmpeg download so both use the same pinned archives
+const MANIFEST_PATH = path.join(
+  PROJECT_ROOT,
+  'src-tauri',
+  'src',
+  'binary_resolver',
+  'managed-ffmpeg.json',
+);
+const MANIFEST = JSON.parse(fs.readFileSync(MANIFEST_PATH, 'utf8'));
+const BUILD = MANIFEST.builds.find((build) => build.os === 'macos' && build.arch === 'aarch64');
+const FFMPEG_VERSION = BUILD.version;
+const PIN = process.argv.includes('--pin');
+
 // -----------------------------------------------
 
 function banner() {
@@ -126,7 +138,31 @@ function findExecutable(rootDir, want) {
   return null;
 }
 
-function fetchZipExtractOne(url, want, outPath) {
+function sha256(file) {
+  return crypto.createHash('sha256').update(fs.readFileSync(file)).digest('hex');
+}
+
+// Pins `file`'s digest under `archive[key]` with --pin; otherwise refuses
+// files that don't match the pinned digest.
+function checkPin(archive, key, file, label) {
+  const digest = sha256(file);
+  if (PIN) {
+    archive[key] = digest;
+    fs.writeFileSync(MANIFEST_PATH, `${JSON.stringify(MANIFEST, null, 2)}\n`);
+    console.log(`${GREEN}Pinned ${label}:${NC} ${digest}`);
+    return;
+  }
+  if (!archive[key]) {
+    console.log(`${YELLOW}No digest pinned for ${label}; run with --pin to record it.${NC}`);
+    return;
+  }
+  if (archive[key] !== digest) {
+    fail(`${label} digest ${digest} doesn't match the pinned ${archive[key]}.`);
+  }
+}
+
+function fetchZipExtractOne(archive, want, outPath) {
+  const url = archive.url;
   const tmp = fs.mkdtempSync(path.join(os.tmpdir(), 'hm-'));
   const zip = path.join(tmp, 'pkg.zip');
 
@@ -152,6 +188,7 @@ function fetchZipExtractOne(url, want, outPath) {
     ],
     { cwd: tmp },
   );
+  checkPin(archive, 'sha256', zip, `${want} archive`);
 
   console.log(`${GREEN}Extracting ${want}...${NC}`);
   needCmd('unzip');
@@ -163,6 +200,9 @@ function fetchZipExtractOne(url, want, outPath) {
     fail(`Error: could not locate '${want}' in downloaded archive.`);
   }
 
+  // Before code signing, which rewrites the binary
+  checkPin(archive, 'binarySha256', found, `${want} binary`);
+
   const arch = binaryArch(found);
   console.log(`${GREEN}${want} detected arch:${NC} ${arch}`);
   if (arch !== 'arm64') {
@@ -237,10 +277,10 @@ function main() {
   } catch {}
 
   console.log(`${GREEN}Step 1: Downloading FFmpeg (arm64)...${NC}`);
-  fetchZipExtractOne(FFMPEG_URL, 'ffmpeg', path.join(BIN_DIR, 'ffmpeg'));
+  fetchZipExtractOne(BUILD.ffmpeg, 'ffmpeg', path.join(BIN_DIR, 'ffmpeg'));
 
   console.log(`${GREEN}Step 2: Downloading FFprobe (arm64)...${NC}`);
-  fetchZipExtractOne(FFPROBE_URL, 'ffprobe', path.join(BIN_DIR, 'ffprobe'));
+  fetchZipExtractOne(BUILD.ffprobe, 'ffprobe', path.join(BIN_DIR, 'ffprobe'));
 
   console.log(`${GREEN}Step 3: Final architecture check...${NC}`);
   const FFMPEG_ARCH = binaryArch(path.join(BIN_DIR, 'ffmpeg'));
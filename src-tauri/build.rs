@@ -1,3 +1,4 @@
+use sha2::{Digest, Sha256};
 use std::{env, fs, os::unix::fs::PermissionsExt, path::Path, process::Command};
 
 fn ensure_exec(path: &Path) -> std::io::Result<()> {
@@ -28,6 +29,22 @@ fn require_sidecar(rel: &str) {
     // Best-effort: ensure executable bit
     let _ = ensure_exec(&p);
 
+    // Record the digest the runtime checks the bundled binary against
+    let bytes = fs::read(&p).unwrap_or_else(|err| {
+        eprintln!("error: cannot read sidecar {}: {err}", p.display());
+        std::process::exit(1);
+    });
+    let digest: String = Sha256::digest(&bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let name = Path::new(rel)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_uppercase();
+    println!("cargo:rustc-env=HONEYMELON_SIDECAR_SHA256_{name}={digest}");
+
     // Optional: sanity print first line of `-version` in local macOS builds
     #[cfg(target_os = "macos")]
     {
@@ -39,6 +56,55 @@ fn require_sidecar(rel: &str) {
     }
 }
 
+/// Release builds must not ship a managed FFmpeg download without pinned
+/// digests; `managed.rs` refuses unpinned entries at runtime anyway.
+fn require_pinned_managed_ffmpeg() {
+    let rel = "src/binary_resolver/managed-ffmpeg.json";
+    println!("cargo:rerun-if-changed={rel}");
+    if env::var("PROFILE").as_deref() != Ok("release") {
+        return;
+    }
+
+    let manifest = fs::read_to_string(rel).unwrap_or_else(|err| {
+        eprintln!("error: cannot read {rel}: {err}");
+        std::process::exit(1);
+    });
+    let unpinned = ["\"sha256\"", "\"binarySha256\""].iter().any(|key| {
+        manifest.split(key).skip(1).any(|rest| {
+            let digest = rest.split('"').nth(1).unwrap_or_default();
+            digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    });
+    if unpinned {
+        eprintln!(
+            "error: {rel} has archives or binaries without a SHA-256 pin\n\
+             hint: run `npm run download-ffmpeg -- --pin` and commit the manifest"
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Forwards the secret trial records are signed with. Release builds must
+/// set one; anything compiled in from the repo would let anyone forge a
+/// trial.
+fn forward_trial_signing_secret() {
+    println!("cargo:rerun-if-env-changed=TRIAL_SIGNING_SECRET");
+    let secret = env::var("TRIAL_SIGNING_SECRET").unwrap_or_default();
+    let secret = if !secret.trim().is_empty() {
+        secret
+    } else if env::var("PROFILE").as_deref() == Ok("release") {
+        eprintln!(
+            "error: TRIAL_SIGNING_SECRET is not set\n\
+             hint: release builds sign trial records with it; set it in .env or the environment"
+        );
+        std::process::exit(1);
+    } else {
+        println!("cargo:warning=TRIAL_SIGNING_SECRET is not set; using a development secret");
+        "honeymelon-dev-trial".to_string()
+    };
+    println!("cargo:rustc-env=TRIAL_SIGNING_SECRET={secret}");
+}
+
 fn main() {
     // Load .env (kept from your original)
     let _ = dotenvy::dotenv();
@@ -50,9 +116,19 @@ fn main() {
         println!("cargo:rustc-env=LICENSE_SIGNING_PUBLIC_KEY={}", key);
     }
 
+    // Forward the optional license server settings
+    for name in ["LICENSE_SERVER_URL", "LICENSE_OFFLINE_GRACE_DAYS"] {
+        if let Ok(value) = std::env::var(name) {
+            println!("cargo:rustc-env={name}={value}");
+        }
+    }
+
+    forward_trial_signing_secret();
+
     // New: enforce the *new* paths
     require_sidecar("bin/ffmpeg");
     require_sidecar("bin/ffprobe");
+    require_pinned_managed_ffmpeg();
 
     tauri_build::build();
 }
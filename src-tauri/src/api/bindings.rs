@@ -0,0 +1,209 @@
+//! TypeScript declarations for the [`super`] types, generated by ts-rs
+//! from the Rust definitions and their serde attributes.
+//!
+//! The test renders every type into the checked-in bindings file and fails
+//! when the file has drifted, so a field added, renamed or retyped in Rust
+//! fails the suite until the bindings are regenerated with
+//! `UPDATE_API_BINDINGS=1 cargo test api::`.
+
+use std::path::PathBuf;
+use ts_rs::TS;
+
+use super::*;
+
+const HEADER: &str = "\
+// This file is generated from src-tauri/src/api. Do not edit it by hand;
+// run `UPDATE_API_BINDINGS=1 cargo test api::` in src-tauri instead.
+";
+
+fn declarations() -> Vec<String> {
+    vec![
+        JobSpec::decl(),
+        FfmpegCommand::decl(),
+        CommandInput::decl(),
+        StreamCodec::decl(),
+        StreamFilter::decl(),
+        MetadataEntry::decl(),
+        CommandOption::decl(),
+        OverwritePolicy::decl(),
+        PlanRequest::decl(),
+        Preset::decl(),
+        PresetVideo::decl(),
+        PresetAudio::decl(),
+        PresetSubs::decl(),
+        VideoTier::decl(),
+        AudioTier::decl(),
+        NativePath::decl(),
+    ]
+}
+
+fn render(decls: &[String]) -> String {
+    let mut out = String::from(HEADER);
+    for decl in decls {
+        out.push_str(&format!("\nexport {decl}\n"));
+    }
+    out
+}
+
+fn bindings_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../src/lib/api-types.generated.ts")
+}
+
+fn full_spec() -> JobSpec {
+    let mut video_tiers = BTreeMap::new();
+    video_tiers.insert(
+        "high".to_string(),
+        VideoTier {
+            bitrate_k: Some(8000),
+            maxrate_k: Some(12000),
+            bufsize_k: Some(16000),
+            crf: Some(18.0),
+            profile: Some("high".into()),
+        },
+    );
+    let mut audio_tiers = BTreeMap::new();
+    audio_tiers.insert(
+        "fast".to_string(),
+        AudioTier {
+            bitrate_k: Some(128),
+            quality: Some(2.0),
+        },
+    );
+
+    JobSpec {
+        job_id: "job-1".into(),
+        args: vec!["-i".into(), "in.mov".into()],
+        command: Some(FfmpegCommand {
+            inputs: vec![CommandInput {
+                path: NativePath::Raw {
+                    bytes: b"in\xff.mov".to_vec(),
+                },
+                options: vec![CommandOption {
+                    name: "ss".into(),
+                    value: Some("5".into()),
+                }],
+            }],
+            maps: vec!["0:v:0?".into()],
+            codecs: vec![StreamCodec {
+                stream: "v".into(),
+                codec: "libx264".into(),
+            }],
+            filters: vec![StreamFilter {
+                stream: "v".into(),
+                graph: "scale=1280:-2".into(),
+            }],
+            metadata: vec![MetadataEntry {
+                stream: Some("a:0".into()),
+                key: "language".into(),
+                value: "eng".into(),
+            }],
+            options: vec![CommandOption {
+                name: "crf".into(),
+                value: Some("23".into()),
+            }],
+            format: Some("mp4".into()),
+        }),
+        output_path: "/out/in.mp4".into(),
+        exclusive: true,
+        low_priority: Some(true),
+        depends_on: vec!["job-0".into()],
+        overwrite: Some(OverwritePolicy::Rename),
+        duration_sec: Some(60.0),
+        segmented: true,
+        threads: Some(4),
+        trash_source: true,
+        preserve_timestamps: true,
+        metadata: JobMetadata::from([("batch".to_string(), "batch-1".to_string())]),
+        ffmpeg_installation: Some("nightly".into()),
+        plan: Some(PlanRequest {
+            preset: Preset {
+                id: "video-to-mp4".into(),
+                label: "MP4".into(),
+                container: "mp4".into(),
+                media_kind: "video".into(),
+                source_containers: vec!["mov".into()],
+                description: Some("Remux or transcode to MP4".into()),
+                video: PresetVideo {
+                    codec: "h264".into(),
+                    tiers: Some(video_tiers),
+                    copy_color_metadata: Some(true),
+                },
+                audio: PresetAudio {
+                    codec: "aac".into(),
+                    bitrate_k: Some(192),
+                    tiers: Some(audio_tiers),
+                    stereo_only: Some(false),
+                },
+                subs: Some(PresetSubs {
+                    mode: "convert".into(),
+                    burn_in_available: Some(true),
+                    notes: Some("mov_text only".into()),
+                }),
+                remux_only: Some(false),
+                experimental: Some(false),
+                output_extension: Some("mp4".into()),
+                tags: Some(vec!["video".into()]),
+            },
+            tier: Some("high".into()),
+        }),
+    }
+}
+
+#[test]
+fn bindings_file_is_up_to_date() {
+    let rendered = render(&declarations());
+    let path = bindings_path();
+
+    if std::env::var_os("UPDATE_API_BINDINGS").is_some() {
+        std::fs::write(&path, &rendered).expect("write api bindings");
+        return;
+    }
+
+    let current = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        current == rendered,
+        "{} is stale; run `UPDATE_API_BINDINGS=1 cargo test api::`",
+        path.display()
+    );
+}
+
+#[test]
+fn frontend_payloads_deserialize() {
+    let spec: JobSpec = serde_json::from_value(serde_json::json!({
+        "jobId": "job-1",
+        "args": ["-i", "in.mov"],
+        "outputPath": "/out/in.mp4"
+    }))
+    .unwrap();
+
+    assert!(!spec.exclusive);
+    assert_eq!(spec.preset_id(), None);
+    assert_eq!(full_spec().preset_id(), Some("video-to-mp4"));
+
+    // 60 s at the high tier's 12000k peak plus 192k audio.
+    assert_eq!(full_spec().estimated_output_bytes(), Some(91_440_000));
+    assert_eq!(spec.estimated_output_bytes(), None);
+
+    let round_trip: JobSpec =
+        serde_json::from_value(serde_json::to_value(full_spec()).unwrap()).unwrap();
+    assert_eq!(round_trip, full_spec());
+}
+
+#[cfg(unix)]
+#[test]
+fn paths_that_arent_unicode_travel_as_bytes() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let name = OsStr::from_bytes(b"clip\xff.mov");
+    let wire = serde_json::to_value(NativePath::from(name)).unwrap();
+    assert_eq!(
+        wire,
+        serde_json::json!({ "bytes": [99, 108, 105, 112, 255, 46, 109, 111, 118] })
+    );
+    let back: NativePath = serde_json::from_value(wire).unwrap();
+    assert_eq!(back.to_string_lossy(), "clip\u{fffd}.mov");
+    assert_eq!(back.into_os_string(), name);
+
+    let text = serde_json::to_value(NativePath::from("clip.mov")).unwrap();
+    assert_eq!(text, serde_json::json!("clip.mov"));
+}
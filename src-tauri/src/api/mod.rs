@@ -0,0 +1,437 @@
+//! Typed contracts shared by commands and the frontend.
+//!
+//! Commands that used to take loose positional strings accept these types
+//! instead. TypeScript declarations for them are derived with ts-rs into
+//! `src/lib/api-types.generated.ts` by the tests in [`bindings`], which
+//! fail whenever the checked-in file drifts from the Rust definitions.
+
+#[cfg(test)]
+mod bindings;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Free-form labels a caller attaches to a job, such as a batch id or a
+/// display name. The runner doesn't interpret them.
+pub type JobMetadata = BTreeMap<String, String>;
+
+/// A file system path on the wire: a plain string when it is valid
+/// Unicode, and `{ "bytes": [...] }` with the raw path otherwise, as
+/// UTF-16LE code units on Windows. Names that aren't valid Unicode occur
+/// on Linux and old network shares and can't survive a JSON string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(untagged)]
+pub enum NativePath {
+    Text(String),
+    Raw { bytes: Vec<u8> },
+}
+
+impl NativePath {
+    pub fn into_os_string(self) -> OsString {
+        match self {
+            Self::Text(text) => text.into(),
+            Self::Raw { bytes } => os_string_from_bytes(bytes),
+        }
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        self.into_os_string().into()
+    }
+
+    /// The path as text, with unrepresentable parts replaced, for messages
+    /// and display.
+    pub fn to_string_lossy(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Raw { bytes } => os_string_from_bytes(bytes.clone())
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+
+    /// Length in bytes of the encoded path.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Text(text) => text.len(),
+            Self::Raw { bytes } => bytes.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for NativePath {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl fmt::Display for NativePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+impl From<&str> for NativePath {
+    fn from(path: &str) -> Self {
+        Self::Text(path.to_string())
+    }
+}
+
+impl From<String> for NativePath {
+    fn from(path: String) -> Self {
+        Self::Text(path)
+    }
+}
+
+impl From<&OsStr> for NativePath {
+    fn from(path: &OsStr) -> Self {
+        match path.to_str() {
+            Some(text) => Self::Text(text.to_string()),
+            None => Self::Raw {
+                bytes: os_str_to_bytes(path),
+            },
+        }
+    }
+}
+
+impl From<&Path> for NativePath {
+    fn from(path: &Path) -> Self {
+        path.as_os_str().into()
+    }
+}
+
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+#[cfg(unix)]
+fn os_str_to_bytes(path: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    OsString::from_wide(&units)
+}
+
+#[cfg(windows)]
+fn os_str_to_bytes(path: &OsStr) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    path.encode_wide().flat_map(u16::to_le_bytes).collect()
+}
+
+/// Everything `start_job` needs to launch one conversion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct JobSpec {
+    pub job_id: String,
+    /// Raw ffmpeg arguments; empty when `command` is given instead.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Structured alternative to `args`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<FfmpegCommand>,
+    pub output_path: NativePath,
+    #[serde(default)]
+    #[cfg_attr(test, ts(as = "Option<bool>", optional))]
+    pub exclusive: bool,
+    /// Run ffmpeg at background priority; unset follows the global
+    /// preference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_priority: Option<bool>,
+    /// Jobs that must succeed before this one starts; if one fails, this
+    /// job completes as `job_skipped`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// What to do when the output file already exists; unset overwrites it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<OverwritePolicy>,
+    /// Source duration from the probe. With the plan's target bitrates it
+    /// sizes the free-space check made before ffmpeg starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_sec: Option<f64>,
+    /// Encode long sources as keyframe-aligned segments in parallel
+    /// ffmpeg processes; sources that don't qualify run as usual.
+    #[serde(default)]
+    #[cfg_attr(test, ts(as = "Option<bool>", optional))]
+    pub segmented: bool,
+    /// Most threads the encoder may use, leaving the rest of the machine
+    /// to other work; unset follows the global default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    /// Move the source to the trash once the output is finalized and
+    /// verified against it.
+    #[serde(default)]
+    #[cfg_attr(test, ts(as = "Option<bool>", optional))]
+    pub trash_source: bool,
+    /// Give the output the source's timestamps (and Finder tags on macOS).
+    #[serde(default)]
+    #[cfg_attr(test, ts(as = "Option<bool>", optional))]
+    pub preserve_timestamps: bool,
+    /// Echoed back in the job's progress and completion events.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: JobMetadata,
+    /// Id of the registered FFmpeg installation to run; unset uses the
+    /// preferred FFmpeg.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ffmpeg_installation: Option<String>,
+    /// The plan that produced `args`, reported back in failure telemetry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<PlanRequest>,
+}
+
+/// A typed ffmpeg invocation. The runner serializes it itself, so paths
+/// and filtergraphs need no escaping and aren't held to the filter applied
+/// to raw arguments.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCommand {
+    pub inputs: Vec<CommandInput>,
+    /// Stream selections such as `0:v:0?` or `-0:s`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maps: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codecs: Vec<StreamCodec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<StreamFilter>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<MetadataEntry>,
+    /// Other output options, e.g. `crf` with value `23`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<CommandOption>,
+    /// Output muxer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct CommandInput {
+    pub path: NativePath,
+    /// Options applying to this input, such as `ss`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<CommandOption>,
+}
+
+/// Encoder for the streams matching `stream` (`v`, `a:1`; empty for all).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct StreamCodec {
+    pub stream: String,
+    pub codec: String,
+}
+
+/// Simple filtergraph for the streams matching `stream`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct StreamFilter {
+    pub stream: String,
+    pub graph: String,
+}
+
+/// Metadata tag of the output, or of its streams matching `stream`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<String>,
+    pub key: String,
+    pub value: String,
+}
+
+/// One ffmpeg option, named without its leading dash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOption {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Handling of an output file that already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub enum OverwritePolicy {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Leave the existing file alone and fail the job as `job_output_exists`.
+    Skip,
+    /// Write to the first free `name (n).ext` instead.
+    Rename,
+}
+
+/// Preset and tier selection a job was planned from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct PlanRequest {
+    pub preset: Preset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
+}
+
+/// Conversion preset, mirroring the frontend preset catalog.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    pub id: String,
+    pub label: String,
+    pub container: String,
+    pub media_kind: String,
+    pub source_containers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub video: PresetVideo,
+    pub audio: PresetAudio,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subs: Option<PresetSubs>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remux_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_extension: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct PresetVideo {
+    pub codec: String,
+    /// Overrides keyed by tier name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiers: Option<BTreeMap<String, VideoTier>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy_color_metadata: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct PresetAudio {
+    pub codec: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiers: Option<BTreeMap<String, AudioTier>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stereo_only: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct PresetSubs {
+    pub mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burn_in_available: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct VideoTier {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maxrate_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bufsize_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crf: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[serde(rename_all = "camelCase")]
+pub struct AudioTier {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate_k: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<f64>,
+}
+
+impl JobSpec {
+    pub fn preset_id(&self) -> Option<&str> {
+        self.plan.as_ref().map(|plan| plan.preset.id.as_str())
+    }
+
+    /// Expected output size from the source duration and the planned
+    /// bitrates. `None` when a stream is copied or encoded by quality
+    /// alone, since its size can't be predicted.
+    pub fn estimated_output_bytes(&self) -> Option<u64> {
+        let duration = self.duration_sec.filter(|duration| *duration > 0.0)?;
+        let plan = self.plan.as_ref()?;
+        let tier = plan.tier.as_deref();
+        let preset = &plan.preset;
+
+        let video_kbps = if preset.media_kind == "audio" {
+            0
+        } else {
+            preset.video.target_kbps(tier)?
+        };
+        let total_kbps = video_kbps + preset.audio.target_kbps(tier)?;
+        if total_kbps == 0 {
+            return None;
+        }
+        Some((duration * f64::from(total_kbps) * 1000.0 / 8.0) as u64)
+    }
+}
+
+impl PresetVideo {
+    /// Peak bitrate for `tier`, 0 when video is dropped.
+    fn target_kbps(&self, tier: Option<&str>) -> Option<u32> {
+        match self.codec.as_str() {
+            "none" => Some(0),
+            "copy" => None,
+            _ => {
+                let tier = self.tiers.as_ref()?.get(tier?)?;
+                tier.maxrate_k.or(tier.bitrate_k)
+            },
+        }
+    }
+}
+
+impl PresetAudio {
+    /// Bitrate for `tier`, 0 when audio is dropped.
+    fn target_kbps(&self, tier: Option<&str>) -> Option<u32> {
+        match self.codec.as_str() {
+            "none" => Some(0),
+            "copy" => None,
+            _ => tier
+                .and_then(|tier| self.tiers.as_ref()?.get(tier)?.bitrate_k)
+                .or(self.bitrate_k),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Application-wide run modes selected at launch.
+//!
+//! Read-only mode turns Honeymelon into a pure media inspector for demos and
+//! kiosks: probing and planning keep working, while every command that writes
+//! files, licenses, or configuration is refused with `read_only_mode`.
+
+use serde::Serialize;
+
+use crate::error::AppError;
+
+pub const READ_ONLY_FLAG: &str = "--read-only";
+pub const READ_ONLY_ENV: &str = "HONEYMELON_READ_ONLY";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMode {
+    pub read_only: bool,
+}
+
+impl AppMode {
+    /// Reads the mode from the command line and environment.
+    pub fn detect() -> Self {
+        Self::from_sources(std::env::args(), std::env::var(READ_ONLY_ENV).ok())
+    }
+
+    fn from_sources(args: impl IntoIterator<Item = String>, env: Option<String>) -> Self {
+        let flag = args.into_iter().any(|arg| arg == READ_ONLY_FLAG);
+        let env = env.is_some_and(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+
+        Self {
+            read_only: flag || env,
+        }
+    }
+
+    /// Fails with `read_only_mode` when mutating commands are disabled.
+    pub fn ensure_writable(&self, action: &str) -> Result<(), AppError> {
+        if self.read_only {
+            return Err(AppError::new(
+                "read_only_mode",
+                format!("Cannot {action} while Honeymelon runs in read-only mode."),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_flag_and_environment() {
+        assert!(!AppMode::from_sources(args(&["honeymelon"]), None).read_only);
+        assert!(AppMode::from_sources(args(&["honeymelon", "--read-only"]), None).read_only);
+        assert!(AppMode::from_sources(args(&["honeymelon"]), Some("TRUE".into())).read_only);
+        assert!(!AppMode::from_sources(args(&["honeymelon"]), Some("0".into())).read_only);
+    }
+
+    #[test]
+    fn read_only_mode_rejects_writes() {
+        assert!(AppMode::default().ensure_writable("start jobs").is_ok());
+
+        let err = AppMode { read_only: true }
+            .ensure_writable("start jobs")
+            .unwrap_err();
+        assert_eq!(err.code, "read_only_mode");
+        assert!(err.message.contains("start jobs"));
+    }
+}
@@ -1,6 +1,8 @@
+use crate::app_mode::AppMode;
 use crate::services::ServiceRegistry;
+use serde::Serialize;
 use tauri::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
-use tauri::{App, Builder, Emitter, Manager, Wry};
+use tauri::{App, AppHandle, Builder, Emitter, Manager, RunEvent, WindowEvent, Wry};
 
 type AppRuntime = Wry;
 
@@ -11,27 +13,145 @@ pub fn build_app() -> Builder<AppRuntime> {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_window_state::Builder::default().build())
         .plugin(tauri_plugin_store::Builder::default().build())
-        .manage(ServiceRegistry::default())
+        .manage(ServiceRegistry {
+            mode: AppMode::detect(),
+            ..ServiceRegistry::default()
+        })
         .invoke_handler(tauri::generate_handler![
+            crate::commands::app::app_mode,
+            crate::commands::app::command_metrics,
+            crate::commands::app::set_command_timeout,
+            crate::commands::app::confirm_quit,
+            crate::commands::binaries::install_ffmpeg,
+            crate::commands::binaries::managed_ffmpeg,
+            crate::commands::binaries::set_ffmpeg_path,
+            crate::commands::binaries::get_binary_info,
+            crate::commands::binaries::diagnose_binaries,
+            crate::commands::binaries::list_ffmpeg_installations,
+            crate::commands::binaries::register_ffmpeg_installation,
+            crate::commands::binaries::remove_ffmpeg_installation,
             crate::commands::media::load_capabilities,
+            crate::commands::media::refresh_capabilities,
+            crate::commands::media::get_encoder_options,
+            crate::commands::media::validate_preset,
             crate::commands::media::probe_media,
+            crate::commands::media::export_probe_report,
+            crate::commands::media::probe_media_batch,
+            crate::commands::media::cancel_probe_batch,
+            crate::commands::media::generate_contact_sheet,
+            crate::commands::media::generate_waveform,
             crate::commands::jobs::start_job,
             crate::commands::jobs::cancel_job,
+            crate::commands::jobs::cancel_all_jobs,
+            crate::commands::jobs::pause_all_jobs,
+            crate::commands::jobs::resume_all_jobs,
+            crate::commands::jobs::restore_jobs,
+            crate::commands::jobs::get_history,
+            crate::commands::jobs::clear_history,
+            crate::commands::jobs::get_job_log_path,
+            crate::commands::jobs::export_job_logs,
             crate::commands::jobs::set_max_concurrency,
+            crate::commands::jobs::set_retry_policy,
+            crate::commands::jobs::set_post_job_hooks,
+            crate::commands::jobs::set_stall_timeout,
+            crate::commands::jobs::set_prevent_sleep,
+            crate::commands::jobs::set_battery_policy,
+            crate::commands::jobs::set_progress_interval,
+            crate::commands::jobs::set_low_priority,
+            crate::commands::jobs::set_default_threads,
+            crate::commands::jobs::start_verification,
+            crate::commands::jobs::set_verification_concurrency,
+            crate::commands::jobs::negotiate_event_encoding,
             crate::commands::media::expand_media_paths,
             crate::commands::dialogs::pick_media_files,
             crate::commands::dialogs::choose_output_directory,
             crate::commands::licensing::verify_license_key,
             crate::commands::licensing::activate_license,
             crate::commands::licensing::current_license,
-            crate::commands::licensing::remove_license
+            crate::commands::licensing::remove_license,
+            crate::commands::licensing::deactivate_license,
+            crate::commands::licensing::trial_status,
+            crate::commands::licensing::has_entitlement,
+            crate::commands::planning::build_video_filters,
+            crate::commands::planning::diff_plans,
+            crate::commands::planning::resolve_output_container,
+            crate::commands::planning::plan_trim,
+            crate::commands::planning::apply_remediation,
+            crate::commands::planning::plan_album,
+            crate::commands::planning::finish_album,
+            crate::commands::planning::preview_stream_selection,
+            crate::commands::planning::estimate_output_size,
+            crate::commands::watch_folders::list_watch_folders,
+            crate::commands::watch_folders::save_watch_folder,
+            crate::commands::watch_folders::remove_watch_folder,
+            crate::commands::watch_folders::poll_watch_folder,
+            crate::commands::watch_folders::resolve_watch_output,
+            crate::commands::catalog::catalog_libraries,
+            crate::commands::catalog::set_catalog_libraries,
+            crate::commands::catalog::scan_catalog,
+            crate::commands::catalog::query_catalog,
+            crate::commands::catalog::conversion_rules,
+            crate::commands::catalog::save_conversion_rule,
+            crate::commands::catalog::remove_conversion_rule,
+            crate::commands::catalog::plan_rule_batch,
+            crate::commands::loudness::analyze_loudness,
+            crate::commands::loudness::write_gain_tags
         ])
         .setup(|app| {
+            crate::fonts::configure_fontconfig(app.handle());
             configure_menus(app)?;
+            app.state::<ServiceRegistry>()
+                .jobs
+                .load_post_job_hooks(app.handle().clone());
+            let services = app.state::<ServiceRegistry>();
+            services
+                .licensing
+                .migrate_storage(app.handle(), services.mode);
+            services.licensing.start_revalidation(app.handle().clone());
+            services
+                .catalog
+                .start_scheduler(app.handle().clone(), services.mode);
             Ok(())
         })
 }
 
+/// Sent instead of closing or quitting while jobs run; the frontend asks the
+/// user and calls `confirm_quit` to go ahead.
+const QUIT_REQUESTED_EVENT: &str = "app:quit-requested";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuitRequestPayload {
+    active_jobs: usize,
+}
+
+/// Holds closing the window or quitting while jobs run until the user
+/// confirms, and kills running conversions when the app exits so no ffmpeg
+/// tree outlives it.
+pub fn handle_run_event(app: &AppHandle<AppRuntime>, event: RunEvent) {
+    match event {
+        RunEvent::ExitRequested { api, .. } if request_quit_confirmation(app) => {
+            api.prevent_exit();
+        },
+        RunEvent::WindowEvent {
+            event: WindowEvent::CloseRequested { api, .. },
+            ..
+        } if request_quit_confirmation(app) => api.prevent_close(),
+        RunEvent::Exit => app.state::<ServiceRegistry>().jobs.shutdown(),
+        _ => {},
+    }
+}
+
+/// Emits the confirmation request when jobs would be interrupted.
+fn request_quit_confirmation(app: &AppHandle<AppRuntime>) -> bool {
+    let active_jobs = app.state::<ServiceRegistry>().jobs.active_job_count();
+    if active_jobs == 0 {
+        return false;
+    }
+    let _ = app.emit(QUIT_REQUESTED_EVENT, QuitRequestPayload { active_jobs });
+    true
+}
+
 #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 fn configure_menus(app: &App<AppRuntime>) -> tauri::Result<()> {
     let menu = build_desktop_menu(app)?;
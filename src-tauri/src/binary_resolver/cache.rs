@@ -0,0 +1,146 @@
+//! Resolved candidates remembered between calls, so job starts, probes and
+//! capability queries don't each re-read the preferences and re-check every
+//! candidate.
+//!
+//! A cached result stays in use while the environment override is unchanged
+//! and every file candidate keeps the modification time it had, or stays
+//! missing. Changes the resolver makes itself, such as a new preferred
+//! binary or a managed install, drop the cache explicitly.
+
+use std::{
+    collections::HashMap,
+    env,
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::SystemTime,
+};
+
+use super::{BinarySource, BinaryType};
+
+/// What one resolution found and the state it was based on.
+struct CachedResolution {
+    env_override: Option<OsString>,
+    /// Every file candidate with its modification time; `None` when it
+    /// was missing.
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    resolved: Vec<(BinarySource, OsString)>,
+}
+
+impl CachedResolution {
+    fn new(
+        env_override: Option<OsString>,
+        watched: Vec<PathBuf>,
+        resolved: Vec<(BinarySource, OsString)>,
+    ) -> Self {
+        let files = watched
+            .into_iter()
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+        Self {
+            env_override,
+            files,
+            resolved,
+        }
+    }
+
+    fn is_current(&self, env_override: Option<&OsStr>) -> bool {
+        self.env_override.as_deref() == env_override
+            && self
+                .files
+                .iter()
+                .all(|(path, recorded)| modified(path) == *recorded)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn resolutions() -> &'static Mutex<HashMap<BinaryType, CachedResolution>> {
+    static RESOLUTIONS: OnceLock<Mutex<HashMap<BinaryType, CachedResolution>>> = OnceLock::new();
+    RESOLUTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The cached resolution for `binary_type`, or the result of `resolve`,
+/// which returns the file candidates it looked at and the candidates it
+/// accepted.
+pub(super) fn cached(
+    binary_type: BinaryType,
+    resolve: impl FnOnce() -> (Vec<PathBuf>, Vec<(BinarySource, OsString)>),
+) -> Vec<(BinarySource, OsString)> {
+    let env_override = env::var_os(binary_type.env_var_name());
+    if let Ok(cache) = resolutions().lock() {
+        if let Some(entry) = cache.get(&binary_type) {
+            if entry.is_current(env_override.as_deref()) {
+                return entry.resolved.clone();
+            }
+        }
+    }
+
+    let (watched, resolved) = resolve();
+    if let Ok(mut cache) = resolutions().lock() {
+        cache.insert(
+            binary_type,
+            CachedResolution::new(env_override, watched, resolved.clone()),
+        );
+    }
+    resolved
+}
+
+/// Drops every cached resolution, so the next lookup walks the candidates
+/// again.
+pub fn invalidate_resolution_cache() {
+    if let Ok(mut cache) = resolutions().lock() {
+        cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn resolution(env_override: Option<&str>, watched: Vec<PathBuf>) -> CachedResolution {
+        CachedResolution::new(
+            env_override.map(OsString::from),
+            watched,
+            vec![(BinarySource::SystemPath, OsString::from("ffmpeg"))],
+        )
+    }
+
+    #[test]
+    fn stays_current_until_the_environment_changes() {
+        let entry = resolution(Some("/opt/ffmpeg"), Vec::new());
+        assert!(entry.is_current(Some(OsStr::new("/opt/ffmpeg"))));
+        assert!(!entry.is_current(Some(OsStr::new("/usr/bin/ffmpeg"))));
+        assert!(!entry.is_current(None));
+    }
+
+    #[test]
+    fn notices_changed_and_new_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let present = dir.path().join("ffmpeg");
+        let absent = dir.path().join("bin").join("ffmpeg");
+        fs::write(&present, "").unwrap();
+
+        let entry = resolution(None, vec![present.clone(), absent.clone()]);
+        assert!(entry.is_current(None));
+
+        let file = fs::File::options().write(true).open(&present).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert!(!entry.is_current(None));
+
+        let entry = resolution(None, vec![present, absent.clone()]);
+        fs::create_dir(dir.path().join("bin")).unwrap();
+        fs::write(&absent, "").unwrap();
+        assert!(!entry.is_current(None));
+    }
+}
@@ -0,0 +1,209 @@
+//! A report of every place the resolver looks for FFmpeg and FFprobe and
+//! what it found there, so support can see why "ffmpeg not found" happens
+//! on a user's machine.
+
+use serde::Serialize;
+use std::{env, ffi::OsStr, path::PathBuf};
+use tauri::AppHandle;
+
+use super::{
+    file_candidates, is_valid_binary, preferences::read_version, BinarySource, BinaryType,
+    Candidate, Check,
+};
+
+/// Why a candidate is used or skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CandidateStatus {
+    /// Nothing exists at the path.
+    Missing,
+    /// Something exists there, but not an executable file.
+    NotExecutable,
+    /// A bundled or managed binary whose SHA-256 doesn't match its record.
+    ChecksumMismatch,
+    /// Accepted by the resolver, but `-version` fails or reports no version.
+    NoVersion,
+    /// Runs and reports a version.
+    Ok,
+}
+
+impl CandidateStatus {
+    /// Whether the resolver accepts a candidate in this state.
+    fn is_accepted(self) -> bool {
+        matches!(self, Self::NoVersion | Self::Ok)
+    }
+}
+
+/// One candidate path and what was found there.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateDiagnosis {
+    pub source: BinarySource,
+    /// Path checked. For the PATH tier, where the lookup found the binary,
+    /// or the bare name when it found nothing.
+    pub path: String,
+    pub status: CandidateStatus,
+    pub version: Option<String>,
+    /// Whether this is the candidate the resolver picks.
+    pub selected: bool,
+}
+
+/// Every candidate for both tools, in the resolver's priority order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryDiagnostics {
+    pub ffmpeg: Vec<CandidateDiagnosis>,
+    pub ffprobe: Vec<CandidateDiagnosis>,
+    /// The PATH searched for the last tier.
+    pub search_path: Option<String>,
+}
+
+/// Checks every candidate the resolver would consider. Accepted candidates
+/// are run with `-version`, so this takes a moment.
+pub fn diagnose_binaries(app: &AppHandle) -> BinaryDiagnostics {
+    let search_path = env::var_os("PATH");
+    BinaryDiagnostics {
+        ffmpeg: diagnose(app, BinaryType::FFmpeg, search_path.as_deref()),
+        ffprobe: diagnose(app, BinaryType::FFprobe, search_path.as_deref()),
+        search_path: search_path.map(|path| path.to_string_lossy().into_owned()),
+    }
+}
+
+fn diagnose(
+    app: &AppHandle,
+    binary_type: BinaryType,
+    search_path: Option<&OsStr>,
+) -> Vec<CandidateDiagnosis> {
+    let name = binary_type.binary_name();
+    let mut report: Vec<_> = file_candidates(binary_type, app)
+        .iter()
+        .map(|candidate| diagnose_candidate(candidate, name))
+        .collect();
+
+    let on_path =
+        search_path.and_then(|search_path| find_on_path(search_path, &binary_type.file_name()));
+    report.push(match on_path {
+        Some(path) => diagnose_candidate(
+            &Candidate::new(BinarySource::SystemPath, path, Check::None),
+            name,
+        ),
+        None => CandidateDiagnosis {
+            source: BinarySource::SystemPath,
+            path: name.to_string(),
+            status: CandidateStatus::Missing,
+            version: None,
+            selected: false,
+        },
+    });
+
+    // The resolver falls back to the bare name even when the PATH has no
+    // such binary, so the last entry is selected when nothing else is
+    let selected = report
+        .iter()
+        .position(|diagnosis| diagnosis.status.is_accepted())
+        .unwrap_or(report.len() - 1);
+    report[selected].selected = true;
+    report
+}
+
+fn diagnose_candidate(candidate: &Candidate, name: &str) -> CandidateDiagnosis {
+    let path = &candidate.path;
+    let mut version = None;
+    let status = if !path.exists() {
+        CandidateStatus::Missing
+    } else if !is_valid_binary(path) {
+        CandidateStatus::NotExecutable
+    } else if !candidate.passes_check() {
+        CandidateStatus::ChecksumMismatch
+    } else {
+        version = read_version(path.as_os_str(), name);
+        if version.is_some() {
+            CandidateStatus::Ok
+        } else {
+            CandidateStatus::NoVersion
+        }
+    };
+    CandidateDiagnosis {
+        source: candidate.source,
+        path: path.to_string_lossy().into_owned(),
+        status,
+        version,
+        selected: false,
+    }
+}
+
+/// The first executable named `file_name` in the directories of
+/// `search_path`.
+fn find_on_path(search_path: &OsStr, file_name: &str) -> Option<PathBuf> {
+    env::split_paths(search_path)
+        .map(|dir| dir.join(file_name))
+        .find(is_valid_binary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    #[cfg(unix)]
+    fn write_executable(path: &Path, script: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::write(path, script).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classifies_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = |name: &str, check: Check| {
+            let candidate = Candidate::new(BinarySource::Environment, dir.path().join(name), check);
+            let diagnosis = diagnose_candidate(&candidate, "ffmpeg");
+            (diagnosis.status, diagnosis.version)
+        };
+
+        fs::write(dir.path().join("plain"), "").unwrap();
+        write_executable(
+            &dir.path().join("ffmpeg"),
+            "#!/bin/sh\necho 'ffmpeg version 7.1.1 Copyright (c) 2000-2025'\n",
+        );
+        write_executable(&dir.path().join("broken"), "#!/bin/sh\nexit 1\n");
+
+        assert_eq!(
+            check("missing", Check::None),
+            (CandidateStatus::Missing, None)
+        );
+        assert_eq!(
+            check("plain", Check::None),
+            (CandidateStatus::NotExecutable, None)
+        );
+        assert_eq!(
+            check("ffmpeg", Check::Digest(None)),
+            (CandidateStatus::ChecksumMismatch, None)
+        );
+        assert_eq!(
+            check("broken", Check::None),
+            (CandidateStatus::NoVersion, None)
+        );
+        assert_eq!(
+            check("ffmpeg", Check::None),
+            (CandidateStatus::Ok, Some("7.1.1".to_string()))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_binaries_on_the_path() {
+        let empty = tempfile::tempdir().unwrap();
+        let tools = tempfile::tempdir().unwrap();
+        write_executable(&tools.path().join("ffmpeg"), "#!/bin/sh\n");
+        fs::create_dir(empty.path().join("ffprobe")).unwrap();
+
+        let search_path = env::join_paths([empty.path(), tools.path()]).unwrap();
+        assert_eq!(
+            find_on_path(&search_path, "ffmpeg"),
+            Some(tools.path().join("ffmpeg"))
+        );
+        assert_eq!(find_on_path(&search_path, "ffprobe"), None);
+    }
+}
@@ -0,0 +1,229 @@
+//! FFmpeg builds registered side by side (a full GPL build, an LGPL one, a
+//! nightly), which a job can name by id to run instead of the preferred
+//! FFmpeg.
+//!
+//! Each build is checked when it is registered and again when a job
+//! launches with it; jobs that name none keep following the resolver.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Manager};
+
+use super::{is_valid_binary, preferences, BinaryType};
+use crate::{
+    error::AppError,
+    persistence::{self, DocumentSchema},
+};
+
+const INSTALLATIONS_FILE_NAME: &str = "ffmpeg-installations.json";
+const INSTALLATIONS_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "FFmpeg installations",
+    migrations: &[],
+};
+
+/// One registered FFmpeg build.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegInstallation {
+    pub id: String,
+    pub label: String,
+    pub ffmpeg_path: PathBuf,
+    /// Version reported when the build was registered.
+    pub version: String,
+}
+
+/// The registered builds and which of them is the preferred FFmpeg.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegInstallations {
+    /// Id of the build whose path was chosen with `set_ffmpeg_path`.
+    pub preferred_id: Option<String>,
+    pub installations: Vec<FfmpegInstallation>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallationRegistry {
+    #[serde(default)]
+    installations: Vec<FfmpegInstallation>,
+}
+
+impl InstallationRegistry {
+    /// Adds a build, or relabels the one already registered for `path`.
+    fn upsert(&mut self, label: &str, path: &Path, version: String) -> FfmpegInstallation {
+        if let Some(existing) = self
+            .installations
+            .iter_mut()
+            .find(|installation| installation.ffmpeg_path == path)
+        {
+            existing.label = label.to_string();
+            existing.version = version;
+            return existing.clone();
+        }
+        let installation = FfmpegInstallation {
+            id: uuid::Uuid::new_v4().to_string(),
+            label: label.to_string(),
+            ffmpeg_path: path.to_path_buf(),
+            version,
+        };
+        self.installations.push(installation.clone());
+        installation
+    }
+
+    fn remove(&mut self, id: &str) -> bool {
+        let before = self.installations.len();
+        self.installations
+            .retain(|installation| installation.id != id);
+        self.installations.len() != before
+    }
+
+    fn find(&self, id: &str) -> Option<&FfmpegInstallation> {
+        self.installations
+            .iter()
+            .find(|installation| installation.id == id)
+    }
+
+    fn listing(self, preferred: Option<&Path>) -> FfmpegInstallations {
+        FfmpegInstallations {
+            preferred_id: preferred.and_then(|preferred| {
+                self.installations
+                    .iter()
+                    .find(|installation| installation.ffmpeg_path == preferred)
+                    .map(|installation| installation.id.clone())
+            }),
+            installations: self.installations,
+        }
+    }
+}
+
+fn store_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(INSTALLATIONS_FILE_NAME))
+        .map_err(|err| AppError::new("binary_store_path", err.to_string()))
+}
+
+fn load(app: &AppHandle) -> InstallationRegistry {
+    store_path(app)
+        .and_then(|path| persistence::load(&path, &INSTALLATIONS_SCHEMA))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, registry: &InstallationRegistry) -> Result<(), AppError> {
+    persistence::persist(&store_path(app)?, &INSTALLATIONS_SCHEMA, registry)
+}
+
+/// Lists the registered builds.
+pub fn ffmpeg_installations(app: &AppHandle) -> FfmpegInstallations {
+    load(app).listing(preferences::preferred_binary(app, BinaryType::FFmpeg).as_deref())
+}
+
+/// Registers the FFmpeg at `path` under `label`; registering a path again
+/// updates its label and version.
+///
+/// # Errors
+///
+/// Returns `binary_invalid` when `path` isn't an executable that reports an
+/// FFmpeg version; nothing is stored in that case.
+pub fn register_ffmpeg_installation(
+    app: &AppHandle,
+    label: &str,
+    path: &Path,
+) -> Result<FfmpegInstallation, AppError> {
+    let version = preferences::ffmpeg_version(path)?;
+    let mut registry = load(app);
+    let installation = registry.upsert(label, path, version);
+    save(app, &registry)?;
+    Ok(installation)
+}
+
+/// Forgets a registered build. The binary itself is left alone.
+///
+/// # Errors
+///
+/// Returns `installation_not_found` for an unknown `id`.
+pub fn remove_ffmpeg_installation(app: &AppHandle, id: &str) -> Result<(), AppError> {
+    let mut registry = load(app);
+    if !registry.remove(id) {
+        return Err(AppError::new(
+            "installation_not_found",
+            format!("No FFmpeg installation is registered as {id}."),
+        ));
+    }
+    save(app, &registry)
+}
+
+/// The ffmpeg path of the build registered as `id`.
+///
+/// # Errors
+///
+/// Returns `job_installation_unknown` for an unknown `id` and
+/// `job_installation_missing` when its binary is gone or not executable.
+pub fn installation_ffmpeg(app: &AppHandle, id: &str) -> Result<OsString, AppError> {
+    let registry = load(app);
+    let installation = registry.find(id).ok_or_else(|| {
+        AppError::new(
+            "job_installation_unknown",
+            format!("No FFmpeg installation is registered as {id}."),
+        )
+    })?;
+    if !is_valid_binary(&installation.ffmpeg_path) {
+        return Err(AppError::new(
+            "job_installation_missing",
+            format!(
+                "{} ({}) is no longer an executable file.",
+                installation.label,
+                installation.ffmpeg_path.display()
+            ),
+        ));
+    }
+    Ok(installation.ffmpeg_path.clone().into_os_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_builds_once_per_path() {
+        let mut registry = InstallationRegistry::default();
+        let full = registry.upsert("Full", Path::new("/opt/full/ffmpeg"), "7.1".into());
+        let nightly = registry.upsert("Nightly", Path::new("/opt/git/ffmpeg"), "N-1".into());
+        assert_ne!(full.id, nightly.id);
+
+        let relabeled = registry.upsert("GPL", Path::new("/opt/full/ffmpeg"), "7.1.1".into());
+        assert_eq!(relabeled.id, full.id);
+        assert_eq!(registry.installations.len(), 2);
+        assert_eq!(registry.find(&full.id).unwrap().label, "GPL");
+        assert_eq!(registry.find(&full.id).unwrap().version, "7.1.1");
+
+        assert!(registry.remove(&nightly.id));
+        assert!(!registry.remove(&nightly.id));
+        assert!(registry.find(&nightly.id).is_none());
+    }
+
+    #[test]
+    fn listing_marks_the_preferred_build() {
+        let mut registry = InstallationRegistry::default();
+        let full = registry.upsert("Full", Path::new("/opt/full/ffmpeg"), "7.1".into());
+        registry.upsert("LGPL", Path::new("/opt/lgpl/ffmpeg"), "7.1".into());
+
+        let listing = registry
+            .clone()
+            .listing(Some(Path::new("/opt/full/ffmpeg")));
+        assert_eq!(listing.preferred_id, Some(full.id));
+        assert_eq!(listing.installations.len(), 2);
+        assert_eq!(registry.clone().listing(None).preferred_id, None);
+        assert_eq!(
+            registry
+                .listing(Some(Path::new("/usr/bin/ffmpeg")))
+                .preferred_id,
+            None
+        );
+    }
+}
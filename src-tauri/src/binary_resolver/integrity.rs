@@ -0,0 +1,118 @@
+//! SHA-256 checks the resolver runs before accepting a bundled or managed
+//! binary, so a tampered or truncated FFmpeg is never executed.
+//!
+//! Bundled sidecars are checked against the digests `build.rs` recorded
+//! when the app was built; managed binaries against the digests pinned in
+//! `managed-ffmpeg.json`, which is compiled into the app. Hashing a full FFmpeg takes a while, so
+//! digests are remembered per file for as long as its size and
+//! modification time stay the same.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::SystemTime,
+};
+
+use super::BinaryType;
+use crate::runner::verification::sha256_hex;
+
+/// A file's digest and the state it was computed for.
+struct CachedDigest {
+    size: u64,
+    modified: SystemTime,
+    sha256: String,
+}
+
+fn digests() -> &'static Mutex<HashMap<PathBuf, CachedDigest>> {
+    static DIGESTS: OnceLock<Mutex<HashMap<PathBuf, CachedDigest>>> = OnceLock::new();
+    DIGESTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Digest of the sidecar in `src-tauri/bin` the app was built with.
+pub(super) fn bundled_digest(binary_type: BinaryType) -> Option<&'static str> {
+    match binary_type {
+        BinaryType::FFmpeg => option_env!("HONEYMELON_SIDECAR_SHA256_FFMPEG"),
+        BinaryType::FFprobe => option_env!("HONEYMELON_SIDECAR_SHA256_FFPROBE"),
+    }
+}
+
+/// Whether the file at `path` has the SHA-256 `expected`. Unreadable files
+/// and a missing expectation both fail.
+pub(super) fn matches_digest(path: &Path, expected: Option<&str>) -> bool {
+    let Some(expected) = expected else {
+        return false;
+    };
+    let matches = digest(path).is_some_and(|actual| actual.eq_ignore_ascii_case(expected));
+    if !matches {
+        eprintln!(
+            "[binaries] rejecting {}: its SHA-256 doesn't match the manifest",
+            path.display()
+        );
+    }
+    matches
+}
+
+fn digest(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    let (size, modified) = (metadata.len(), metadata.modified().ok()?);
+
+    let mut cache = digests().lock().ok()?;
+    if let Some(cached) = cache.get(path) {
+        if cached.size == size && cached.modified == modified {
+            return Some(cached.sha256.clone());
+        }
+    }
+    let sha256 = sha256_hex(path).ok()?;
+    cache.insert(
+        path.to_path_buf(),
+        CachedDigest {
+            size,
+            modified,
+            sha256: sha256.clone(),
+        },
+    );
+    Some(sha256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_DIGEST: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn accepts_only_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ffmpeg");
+        fs::write(&path, "test").unwrap();
+
+        assert!(matches_digest(&path, Some(TEST_DIGEST)));
+        assert!(matches_digest(&path, Some(&TEST_DIGEST.to_uppercase())));
+        assert!(!matches_digest(&path, None));
+        assert!(!matches_digest(
+            &dir.path().join("missing"),
+            Some(TEST_DIGEST)
+        ));
+    }
+
+    #[test]
+    fn notices_a_truncated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ffprobe");
+        fs::write(&path, "test").unwrap();
+        assert!(matches_digest(&path, Some(TEST_DIGEST)));
+
+        fs::write(&path, "tes").unwrap();
+        assert!(!matches_digest(&path, Some(TEST_DIGEST)));
+    }
+
+    #[test]
+    fn build_records_the_sidecar_digests() {
+        for binary_type in [BinaryType::FFmpeg, BinaryType::FFprobe] {
+            let digest = bundled_digest(binary_type).expect("digest recorded by build.rs");
+            assert_eq!(digest.len(), 64);
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Managed FFmpeg: a pinned static build downloaded into app data for users
+//! without an FFmpeg of their own.
+//!
+//! Builds are pinned per platform in `managed-ffmpeg.json`, each archive
+//! with its SHA-256 and that of the binary inside it. An archive is only
+//! unpacked once its digest matches the pin, and the binaries are moved
+//! into place only after both have been verified, so an interrupted
+//! install never leaves a half-written binary for the resolver to adopt.
+//! The manifest is compiled into the app, and the resolver checks the
+//! installed binaries against it rather than against `install.json`, which
+//! lives in the same writable directory as the binaries.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+use tauri::{AppHandle, Manager};
+
+use super::BinaryType;
+use crate::{error::AppError, runner::verification::sha256_hex};
+
+/// Emitted with a `DownloadProgress` while a managed build downloads.
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "ffmpeg://download-progress";
+
+const MANIFEST: &str = include_str!("managed-ffmpeg.json");
+/// Platforms the app ships for, each of which needs a pinned build.
+/// `npm run download-ffmpeg -- --pin` records their digests, and release
+/// builds refuse to compile while one is missing.
+#[cfg_attr(not(test), allow(dead_code))]
+const SUPPORTED_TARGETS: [(&str, &str); 1] = [("macos", "aarch64")];
+/// Records the installed build next to the binaries.
+const INSTALL_FILE: &str = "install.json";
+/// How often the partial download is measured for progress events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    builds: Vec<PinnedBuild>,
+}
+
+/// One platform's pinned FFmpeg release.
+#[derive(Debug, Clone, Deserialize)]
+struct PinnedBuild {
+    /// `std::env::consts::OS`, e.g. "macos".
+    os: String,
+    /// `std::env::consts::ARCH`, e.g. "aarch64".
+    arch: String,
+    version: String,
+    ffmpeg: PinnedArchive,
+    ffprobe: PinnedArchive,
+}
+
+/// A zip archive holding one binary, and the digests both must have.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinnedArchive {
+    url: String,
+    sha256: String,
+    /// SHA-256 of the unpacked binary, before any code signing.
+    #[serde(default)]
+    binary_sha256: String,
+}
+
+/// Bytes received so far for one binary's archive.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    /// "ffmpeg" or "ffprobe".
+    pub binary: String,
+    pub downloaded_bytes: u64,
+    /// Archive size, when the server reports one.
+    pub total_bytes: Option<u64>,
+}
+
+/// The build installed into app data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedInstall {
+    pub version: String,
+    pub ffmpeg_path: PathBuf,
+    pub ffprobe_path: PathBuf,
+    /// SHA-256 of the installed binaries. The resolver checks the pins
+    /// compiled into the app, not these.
+    pub ffmpeg_sha256: String,
+    pub ffprobe_sha256: String,
+}
+
+/// Directory managed binaries are installed into.
+pub fn managed_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("ffmpeg"))
+}
+
+/// Path of the managed `binary_type` and the digest pinned for it in the
+/// manifest compiled into the app.
+pub(super) fn managed_binary(
+    app: &AppHandle,
+    binary_type: BinaryType,
+) -> Option<(PathBuf, String)> {
+    let install = managed_install(app)?;
+    let build = pinned_build(MANIFEST, std::env::consts::OS, std::env::consts::ARCH).ok()?;
+    Some(match binary_type {
+        BinaryType::FFmpeg => (install.ffmpeg_path, build.ffmpeg.binary_sha256),
+        BinaryType::FFprobe => (install.ffprobe_path, build.ffprobe.binary_sha256),
+    })
+}
+
+/// The installed managed build, if any.
+pub fn managed_install(app: &AppHandle) -> Option<ManagedInstall> {
+    let contents = fs::read_to_string(managed_dir(app)?.join(INSTALL_FILE)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Downloads, verifies and installs the build pinned for this platform,
+/// replacing any earlier managed install. The resolver adopts it on its
+/// next lookup.
+///
+/// # Errors
+///
+/// `ffmpeg_download_unsupported` when nothing is pinned for this platform,
+/// `ffmpeg_download_failed` when a download or unpacking fails,
+/// `ffmpeg_download_checksum` when an archive doesn't match its pin, and
+/// `ffmpeg_install_failed` when the binaries can't be put in place.
+pub fn install_managed_ffmpeg(
+    app: &AppHandle,
+    on_progress: &mut dyn FnMut(DownloadProgress),
+) -> Result<ManagedInstall, AppError> {
+    let build = pinned_build(MANIFEST, std::env::consts::OS, std::env::consts::ARCH)?;
+    let dir = managed_dir(app).ok_or_else(|| {
+        AppError::new(
+            "ffmpeg_install_failed",
+            "The app data directory is unavailable.",
+        )
+    })?;
+    let staging = dir.join(".staging");
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(install_error)?;
+
+    let result = stage_and_install(&build, &dir, &staging, on_progress);
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn stage_and_install(
+    build: &PinnedBuild,
+    dir: &Path,
+    staging: &Path,
+    on_progress: &mut dyn FnMut(DownloadProgress),
+) -> Result<ManagedInstall, AppError> {
+    let mut staged = Vec::new();
+    for (binary_type, archive) in [
+        (BinaryType::FFmpeg, &build.ffmpeg),
+        (BinaryType::FFprobe, &build.ffprobe),
+    ] {
+        let name = binary_type.binary_name();
+        let zip = staging.join(format!("{name}.zip"));
+        download(&archive.url, &zip, &mut |downloaded_bytes, total_bytes| {
+            on_progress(DownloadProgress {
+                binary: name.to_string(),
+                downloaded_bytes,
+                total_bytes,
+            })
+        })?;
+        verify_checksum(&zip, &archive.sha256, &format!("{name} archive"))?;
+        let binary = unpack(&zip, &staging.join(name), name)?;
+        verify_checksum(&binary, &archive.binary_sha256, name)?;
+        staged.push((name, binary));
+    }
+
+    for (name, binary) in &staged {
+        fs::rename(binary, dir.join(name)).map_err(install_error)?;
+    }
+    let ffmpeg_path = dir.join(BinaryType::FFmpeg.binary_name());
+    let ffprobe_path = dir.join(BinaryType::FFprobe.binary_name());
+    let install = ManagedInstall {
+        version: build.version.clone(),
+        ffmpeg_sha256: build.ffmpeg.binary_sha256.clone(),
+        ffprobe_sha256: build.ffprobe.binary_sha256.clone(),
+        ffmpeg_path,
+        ffprobe_path,
+    };
+    let serialized = serde_json::to_string_pretty(&install).map_err(install_error)?;
+    fs::write(dir.join(INSTALL_FILE), serialized).map_err(install_error)?;
+    super::invalidate_resolution_cache();
+    Ok(install)
+}
+
+/// Picks the build pinned for `os`/`arch`. Entries without a well-formed
+/// digest are ignored, so an unpinned platform is never downloaded.
+fn pinned_build(manifest: &str, os: &str, arch: &str) -> Result<PinnedBuild, AppError> {
+    let manifest: Manifest = serde_json::from_str(manifest).map_err(|error| {
+        AppError::new(
+            "ffmpeg_download_unsupported",
+            format!("The pinned FFmpeg manifest is malformed: {error}"),
+        )
+    })?;
+    manifest
+        .builds
+        .into_iter()
+        .find(|build| {
+            build.os == os
+                && build.arch == arch
+                && [&build.ffmpeg, &build.ffprobe]
+                    .iter()
+                    .all(|archive| is_sha256(&archive.sha256) && is_sha256(&archive.binary_sha256))
+        })
+        .ok_or_else(|| {
+            AppError::new(
+                "ffmpeg_download_unsupported",
+                format!("No verified FFmpeg build is pinned for {os}/{arch}."),
+            )
+        })
+}
+
+fn is_sha256(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Downloads `url` to `dest` with curl, reporting the bytes written so far
+/// while it runs.
+fn download(
+    url: &str,
+    dest: &Path,
+    on_progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<(), AppError> {
+    let total = content_length(url);
+    let mut child = Command::new("curl")
+        .args([
+            "--fail",
+            "--location",
+            "--silent",
+            "--show-error",
+            "--retry",
+            "3",
+            "--connect-timeout",
+            "10",
+            "--output",
+        ])
+        .arg(dest)
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| download_error(format!("Failed to start curl: {error}")))?;
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {
+                let downloaded = fs::metadata(dest).map(|meta| meta.len()).unwrap_or(0);
+                on_progress(downloaded, total);
+                thread::sleep(PROGRESS_INTERVAL);
+            },
+            Err(error) => return Err(download_error(error.to_string())),
+        }
+    };
+    let output = child
+        .wait_with_output()
+        .map_err(|error| download_error(error.to_string()))?;
+    if !status.success() {
+        return Err(download_error(format!(
+            "Downloading {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let downloaded = fs::metadata(dest).map(|meta| meta.len()).unwrap_or(0);
+    on_progress(downloaded, total.or(Some(downloaded)));
+    Ok(())
+}
+
+/// Size the server reports for `url`, following redirects.
+fn content_length(url: &str) -> Option<u64> {
+    let output = Command::new("curl")
+        .args([
+            "--head",
+            "--location",
+            "--silent",
+            "--connect-timeout",
+            "10",
+        ])
+        .arg(url)
+        .output()
+        .ok()?;
+    parse_content_length(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The last `Content-Length` of a (possibly redirected) header dump.
+fn parse_content_length(headers: &str) -> Option<u64> {
+    headers
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .filter_map(|(_, value)| value.trim().parse().ok())
+        .next_back()
+}
+
+fn verify_checksum(path: &Path, expected: &str, name: &str) -> Result<(), AppError> {
+    let actual = sha256_hex(path).map_err(|error| download_error(error.to_string()))?;
+    if actual.eq_ignore_ascii_case(expected) {
+        return Ok(());
+    }
+    let _ = fs::remove_file(path);
+    Err(AppError::new(
+        "ffmpeg_download_checksum",
+        format!("The downloaded {name} doesn't match its pinned checksum."),
+    ))
+}
+
+/// Unzips `zip` into `dest` and returns the executable named `name` in it.
+fn unpack(zip: &Path, dest: &Path, name: &str) -> Result<PathBuf, AppError> {
+    let output = Command::new("unzip")
+        .args(["-q", "-o"])
+        .arg(zip)
+        .arg("-d")
+        .arg(dest)
+        .output()
+        .map_err(|error| download_error(format!("Failed to start unzip: {error}")))?;
+    if !output.status.success() {
+        return Err(download_error(format!(
+            "Unpacking {name} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let binary = find_file(dest, name, 3).ok_or_else(|| {
+        download_error(format!("The downloaded archive contains no {name} binary."))
+    })?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).map_err(install_error)?;
+    }
+    Ok(binary)
+}
+
+/// Finds a file named `name` at most `depth` directories below `dir`.
+fn find_file(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    let entries: Vec<_> = fs::read_dir(dir).ok()?.flatten().collect();
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.file_name() == name && entry.path().is_file())
+    {
+        return Some(entry.path());
+    }
+    if depth == 0 {
+        return None;
+    }
+    entries
+        .iter()
+        .filter(|entry| entry.path().is_dir())
+        .find_map(|entry| find_file(&entry.path(), name, depth - 1))
+}
+
+fn download_error(message: impl Into<String>) -> AppError {
+    AppError::new("ffmpeg_download_failed", message)
+}
+
+fn install_error(error: impl std::fmt::Display) -> AppError {
+    AppError::new(
+        "ffmpeg_install_failed",
+        format!("Failed to install FFmpeg: {error}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    fn manifest(sha256: &str) -> String {
+        format!(
+            r#"{{"builds": [{{
+                "os": "macos", "arch": "aarch64", "version": "7.1.1",
+                "ffmpeg": {{"url": "https://example.com/ffmpeg.zip", "sha256": "{sha256}", "binarySha256": "{sha256}"}},
+                "ffprobe": {{"url": "https://example.com/ffprobe.zip", "sha256": "{sha256}", "binarySha256": "{sha256}"}}
+            }}]}}"#
+        )
+    }
+
+    #[test]
+    fn picks_the_build_pinned_for_the_platform() {
+        let build = pinned_build(&manifest(DIGEST), "macos", "aarch64").unwrap();
+        assert_eq!(build.version, "7.1.1");
+        assert_eq!(build.ffprobe.url, "https://example.com/ffprobe.zip");
+
+        let error = pinned_build(&manifest(DIGEST), "linux", "x86_64").unwrap_err();
+        assert_eq!(error.code, "ffmpeg_download_unsupported");
+        assert!(pinned_build(&manifest(""), "macos", "aarch64").is_err());
+
+        // An archive pin alone can't vouch for the binary the resolver runs.
+        let archive_only =
+            manifest(DIGEST).replace(&format!(r#", "binarySha256": "{DIGEST}""#), "");
+        assert!(pinned_build(&archive_only, "macos", "aarch64").is_err());
+    }
+
+    #[test]
+    fn the_shipped_manifest_lists_every_supported_target() {
+        let manifest: Manifest = serde_json::from_str(MANIFEST).unwrap();
+        for (os, arch) in SUPPORTED_TARGETS {
+            let builds: Vec<_> = manifest
+                .builds
+                .iter()
+                .filter(|build| build.os == os && build.arch == arch)
+                .collect();
+            assert_eq!(builds.len(), 1, "{os}/{arch}");
+            for archive in [&builds[0].ffmpeg, &builds[0].ffprobe] {
+                assert!(archive.url.starts_with("https://"), "{}", archive.url);
+                for digest in [&archive.sha256, &archive.binary_sha256] {
+                    assert!(digest.is_empty() || is_sha256(digest));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "digests are pinned with `npm run download-ffmpeg -- --pin` before a release"]
+    fn the_shipped_manifest_pins_every_supported_target() {
+        for (os, arch) in SUPPORTED_TARGETS {
+            assert!(pinned_build(MANIFEST, os, arch).is_ok(), "{os}/{arch}");
+        }
+    }
+
+    #[test]
+    fn rejects_archives_that_miss_their_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ffmpeg.zip");
+        fs::write(&path, "test").unwrap();
+        assert!(verify_checksum(&path, DIGEST, "ffmpeg").is_ok());
+
+        fs::write(&path, "tampered").unwrap();
+        let error = verify_checksum(&path, DIGEST, "ffmpeg").unwrap_err();
+        assert_eq!(error.code, "ffmpeg_download_checksum");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn reads_the_final_content_length() {
+        let headers = "HTTP/1.1 302 Found\r\nContent-Length: 0\r\n\r\n\
+                       HTTP/1.1 200 OK\r\ncontent-length: 24117248\r\n\r\n";
+        assert_eq!(parse_content_length(headers), Some(24_117_248));
+        assert_eq!(parse_content_length(""), None);
+    }
+
+    #[test]
+    fn finds_binaries_in_nested_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("ffmpeg-7.1.1").join("bin");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("ffprobe"), "").unwrap();
+
+        assert_eq!(
+            find_file(dir.path(), "ffprobe", 3),
+            Some(nested.join("ffprobe"))
+        );
+        assert_eq!(find_file(dir.path(), "ffprobe", 1), None);
+    }
+}
@@ -0,0 +1,393 @@
+//! Binary resolution utilities for locating FFmpeg and FFprobe binaries.
+//!
+//! This module provides a centralized, DRY approach to resolving FFmpeg/FFprobe paths
+//! using a 6-tier fallback strategy:
+//! 1. Binary chosen by the user (see [`preferences`])
+//! 2. Environment variable override (HONEYMELON_FFMPEG_PATH / HONEYMELON_FFPROBE_PATH)
+//! 3. Development bundled binary (src-tauri/bin/)
+//! 4. Production bundled binary (app.app/Contents/Resources/bin/)
+//! 5. Managed build downloaded into app data (see [`managed`])
+//! 6. System PATH fallback
+//!
+//! Jobs can also name one of several registered builds (see
+//! [`installations`]) to bypass these tiers.
+//!
+//! This eliminates code duplication across ffmpeg_probe.rs, ffmpeg_capabilities.rs,
+//! and the runner modules under `src-tauri/src/runner`.
+
+mod cache;
+mod diagnostics;
+mod installations;
+mod integrity;
+mod managed;
+mod preferences;
+
+pub use cache::invalidate_resolution_cache;
+pub use diagnostics::{diagnose_binaries, BinaryDiagnostics};
+pub use installations::{
+    ffmpeg_installations, installation_ffmpeg, register_ffmpeg_installation,
+    remove_ffmpeg_installation, FfmpegInstallation, FfmpegInstallations,
+};
+pub use managed::{
+    install_managed_ffmpeg, managed_install, ManagedInstall, DOWNLOAD_PROGRESS_EVENT,
+};
+pub use preferences::{binary_info, set_preferred_ffmpeg, BinaryInfo};
+
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Represents the type of binary to resolve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryType {
+    /// FFmpeg binary for media conversion
+    FFmpeg,
+    /// FFprobe binary for media inspection
+    FFprobe,
+}
+
+impl BinaryType {
+    /// Returns the environment variable name for this binary type
+    fn env_var_name(&self) -> &'static str {
+        match self {
+            BinaryType::FFmpeg => "HONEYMELON_FFMPEG_PATH",
+            BinaryType::FFprobe => "HONEYMELON_FFPROBE_PATH",
+        }
+    }
+
+    /// Returns the binary filename for this binary type
+    fn binary_name(&self) -> &'static str {
+        match self {
+            BinaryType::FFmpeg => "ffmpeg",
+            BinaryType::FFprobe => "ffprobe",
+        }
+    }
+
+    /// Returns the executable's filename on the platform the app runs on,
+    /// e.g. `ffmpeg.exe` on Windows
+    fn file_name(&self) -> String {
+        self.file_name_for(std::env::consts::OS)
+    }
+
+    /// Returns the executable's filename on `os`, a `std::env::consts::OS`
+    /// value
+    fn file_name_for(&self, os: &str) -> String {
+        let suffix = if os == "windows" { ".exe" } else { "" };
+        format!("{}{suffix}", self.binary_name())
+    }
+}
+
+/// Where a resolved binary came from, in priority order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BinarySource {
+    /// Path the user chose with `set_ffmpeg_path`
+    Preference,
+    /// HONEYMELON_FFMPEG_PATH / HONEYMELON_FFPROBE_PATH
+    Environment,
+    /// src-tauri/bin/, for `tauri dev`
+    Development,
+    /// The app bundle's resources
+    Bundled,
+    /// The build downloaded into app data
+    Managed,
+    /// Looked up on the system PATH
+    SystemPath,
+}
+
+/// Resolves candidate paths for a given binary type using the 6-tier fallback strategy.
+///
+/// # Arguments
+/// * `binary_type` - The type of binary to resolve (FFmpeg or FFprobe)
+/// * `app` - Tauri AppHandle for accessing resource directories
+///
+/// # Returns
+/// A vector of candidate paths in priority order. The first valid path should be used.
+///
+/// # Example
+/// ```ignore
+/// use honeymelon_lib::binary_resolver::{BinaryType, resolve_binary_paths};
+/// use std::process::Command;
+/// # use tauri::AppHandle;
+/// # fn example(app: &AppHandle) {
+/// let candidates = resolve_binary_paths(BinaryType::FFmpeg, app);
+/// // Try each candidate until one works
+/// for path in candidates {
+///     if let Ok(output) = Command::new(&path).arg("-version").output() {
+///         // Found working FFmpeg
+///         break;
+///     }
+/// }
+/// # }
+/// ```
+pub fn resolve_binary_paths(binary_type: BinaryType, app: &AppHandle) -> Vec<OsString> {
+    resolve_binary_candidates(binary_type, app)
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Like `resolve_binary_paths`, with the source of each candidate.
+///
+/// The result is cached (see [`cache`]) until the environment override or
+/// one of the candidate files changes.
+pub fn resolve_binary_candidates(
+    binary_type: BinaryType,
+    app: &AppHandle,
+) -> Vec<(BinarySource, OsString)> {
+    cache::cached(binary_type, || {
+        let files = file_candidates(binary_type, app);
+        let watched = files
+            .iter()
+            .map(|candidate| candidate.path.clone())
+            .collect();
+        let mut candidates: Vec<_> = files
+            .into_iter()
+            .filter(Candidate::is_usable)
+            .map(|candidate| (candidate.source, candidate.path.into_os_string()))
+            .collect();
+
+        // Priority 6: System PATH fallback for standard installations; the
+        // bare name is enough, as Windows adds `.exe` when searching the PATH
+        candidates.push((
+            BinarySource::SystemPath,
+            OsString::from(binary_type.binary_name()),
+        ));
+
+        (watched, candidates)
+    })
+}
+
+/// A file the resolver considers, before it is checked.
+struct Candidate {
+    source: BinarySource,
+    path: PathBuf,
+    check: Check,
+}
+
+/// What a candidate must pass besides being an executable file.
+enum Check {
+    /// Nothing more; the user or environment chose it.
+    None,
+    /// The file's SHA-256 must equal this digest; without one it fails.
+    Digest(Option<String>),
+}
+
+impl Candidate {
+    fn new(source: BinarySource, path: PathBuf, check: Check) -> Self {
+        Self {
+            source,
+            path,
+            check,
+        }
+    }
+
+    fn is_usable(&self) -> bool {
+        is_valid_binary(&self.path) && self.passes_check()
+    }
+
+    fn passes_check(&self) -> bool {
+        match &self.check {
+            Check::None => true,
+            Check::Digest(expected) => integrity::matches_digest(&self.path, expected.as_deref()),
+        }
+    }
+}
+
+/// The file candidates of tiers 1 to 5 in priority order, whether or not
+/// they exist.
+fn file_candidates(binary_type: BinaryType, app: &AppHandle) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+
+    // Priority 1: Binary the user chose in the app
+    if let Some(preferred) = preferences::preferred_binary(app, binary_type) {
+        candidates.push(Candidate::new(
+            BinarySource::Preference,
+            preferred,
+            Check::None,
+        ));
+    }
+
+    // Priority 2: Environment variable override for custom installations
+    if let Ok(override_path) = std::env::var(binary_type.env_var_name()) {
+        candidates.push(Candidate::new(
+            BinarySource::Environment,
+            PathBuf::from(override_path),
+            Check::None,
+        ));
+    }
+
+    // Bundled and managed binaries are only accepted while they match the
+    // digests recorded for them, so a tampered or truncated copy is skipped
+    let bundled_digest =
+        || Check::Digest(integrity::bundled_digest(binary_type).map(str::to_string));
+
+    // Priority 3: Development-bundled binary for local development (`tauri dev`)
+    candidates.push(Candidate::new(
+        BinarySource::Development,
+        bundled_binary_path(
+            Path::new(env!("CARGO_MANIFEST_DIR")),
+            binary_type,
+            std::env::consts::OS,
+        ),
+        bundled_digest(),
+    ));
+
+    // Priority 4: Application-bundled binary for packaged distributions
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        candidates.push(Candidate::new(
+            BinarySource::Bundled,
+            bundled_binary_path(&resource_dir, binary_type, std::env::consts::OS),
+            bundled_digest(),
+        ));
+    }
+
+    // Priority 5: Managed build, for users without FFmpeg of their own
+    if let Some((managed, digest)) = managed::managed_binary(app, binary_type) {
+        candidates.push(Candidate::new(
+            BinarySource::Managed,
+            managed,
+            Check::Digest(Some(digest)),
+        ));
+    }
+
+    candidates
+}
+
+/// Path of a sidecar under `dir/bin`, named as executables are on `os`.
+fn bundled_binary_path(dir: &Path, binary_type: BinaryType, os: &str) -> PathBuf {
+    dir.join("bin").join(binary_type.file_name_for(os))
+}
+
+/// Convenience function to resolve FFmpeg paths
+pub fn resolve_ffmpeg_paths(app: &AppHandle) -> Vec<OsString> {
+    resolve_binary_paths(BinaryType::FFmpeg, app)
+}
+
+/// Convenience function to resolve FFprobe paths
+pub fn resolve_ffprobe_paths(app: &AppHandle) -> Vec<OsString> {
+    resolve_binary_paths(BinaryType::FFprobe, app)
+}
+
+/// Checks if a given path points to a valid executable binary.
+///
+/// # Arguments
+/// * `path` - The path to check
+///
+/// # Returns
+/// `true` if the path exists and is executable, `false` otherwise
+fn is_valid_binary(path: &PathBuf) -> bool {
+    if !path.exists() {
+        return false;
+    }
+
+    let metadata = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+
+    if !metadata.is_file() {
+        return false;
+    }
+
+    // On Unix systems, check executable permission
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = metadata.permissions();
+        let mode = permissions.mode();
+        // Check if any execute bit is set (user, group, or other)
+        if mode & 0o111 == 0 {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_type_env_var_names() {
+        assert_eq!(BinaryType::FFmpeg.env_var_name(), "HONEYMELON_FFMPEG_PATH");
+        assert_eq!(
+            BinaryType::FFprobe.env_var_name(),
+            "HONEYMELON_FFPROBE_PATH"
+        );
+    }
+
+    #[test]
+    fn test_binary_type_binary_names() {
+        assert_eq!(BinaryType::FFmpeg.binary_name(), "ffmpeg");
+        assert_eq!(BinaryType::FFprobe.binary_name(), "ffprobe");
+    }
+
+    #[test]
+    fn test_binary_type_file_names() {
+        assert_eq!(BinaryType::FFmpeg.file_name_for("windows"), "ffmpeg.exe");
+        assert_eq!(BinaryType::FFprobe.file_name_for("windows"), "ffprobe.exe");
+        assert_eq!(BinaryType::FFmpeg.file_name_for("macos"), "ffmpeg");
+        assert_eq!(BinaryType::FFprobe.file_name_for("linux"), "ffprobe");
+    }
+
+    #[test]
+    fn test_bundled_binary_paths() {
+        let resources = Path::new("/Applications/Honeymelon.app/Contents/Resources");
+        assert_eq!(
+            bundled_binary_path(resources, BinaryType::FFmpeg, "macos"),
+            resources.join("bin").join("ffmpeg")
+        );
+        assert_eq!(
+            bundled_binary_path(resources, BinaryType::FFprobe, "windows"),
+            resources.join("bin").join("ffprobe.exe")
+        );
+    }
+
+    #[test]
+    fn test_windows_candidates_are_found_with_their_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("bin")).unwrap();
+        for name in ["ffmpeg.exe", "ffprobe.exe"] {
+            let path = dir.path().join("bin").join(name);
+            fs::write(&path, "").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            }
+        }
+
+        for binary_type in [BinaryType::FFmpeg, BinaryType::FFprobe] {
+            assert!(is_valid_binary(&bundled_binary_path(
+                dir.path(),
+                binary_type,
+                "windows"
+            )));
+            assert!(!is_valid_binary(&bundled_binary_path(
+                dir.path(),
+                binary_type,
+                "macos"
+            )));
+        }
+    }
+
+    #[test]
+    fn test_is_valid_binary_nonexistent() {
+        let path = PathBuf::from("/nonexistent/path/ffmpeg");
+        assert!(!is_valid_binary(&path));
+    }
+
+    #[test]
+    fn test_is_valid_binary_directory() {
+        // Use a known directory (current directory)
+        let path = PathBuf::from(".");
+        assert!(!is_valid_binary(&path));
+    }
+
+    // Note: More comprehensive tests would require mocking the filesystem
+    // or using a test fixture directory with actual binary files
+}
@@ -0,0 +1,196 @@
+//! The FFmpeg binary the user chose in the app, which the resolver tries
+//! before every other candidate.
+//!
+//! Only the ffmpeg path is stored; ffprobe is taken from the same
+//! directory when one sits next to it, as it does in every FFmpeg
+//! distribution.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tauri::{AppHandle, Manager};
+
+use super::{is_valid_binary, resolve_binary_candidates, BinarySource, BinaryType};
+use crate::{
+    error::AppError,
+    persistence::{self, DocumentSchema},
+};
+
+const PREFERENCES_FILE_NAME: &str = "binaries.json";
+const PREFERENCES_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "binary preferences",
+    migrations: &[],
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinaryPreferences {
+    #[serde(default)]
+    ffmpeg_path: Option<PathBuf>,
+}
+
+/// The binary the resolver currently picks for one tool.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedBinary {
+    /// Path, or the bare name when it is looked up on the PATH.
+    pub path: String,
+    pub source: BinarySource,
+    /// Version from `-version`; `None` when the binary doesn't run.
+    pub version: Option<String>,
+}
+
+/// Which FFmpeg and FFprobe are in use, and why.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryInfo {
+    /// Path chosen with `set_ffmpeg_path`, if any.
+    pub preferred_path: Option<PathBuf>,
+    pub ffmpeg: ResolvedBinary,
+    pub ffprobe: ResolvedBinary,
+}
+
+fn store_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(PREFERENCES_FILE_NAME))
+        .map_err(|err| AppError::new("binary_store_path", err.to_string()))
+}
+
+fn load(app: &AppHandle) -> BinaryPreferences {
+    store_path(app)
+        .and_then(|path| persistence::load(&path, &PREFERENCES_SCHEMA))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// The user's choice for `binary_type`, which may not exist any more.
+pub(super) fn preferred_binary(app: &AppHandle, binary_type: BinaryType) -> Option<PathBuf> {
+    let ffmpeg = load(app).ffmpeg_path?;
+    Some(match binary_type {
+        BinaryType::FFmpeg => ffmpeg,
+        BinaryType::FFprobe => ffmpeg.with_file_name(BinaryType::FFprobe.file_name()),
+    })
+}
+
+/// Stores `path` as the FFmpeg to use, or clears the choice with `None`.
+///
+/// # Errors
+///
+/// Returns `binary_invalid` when `path` isn't an executable that reports an
+/// FFmpeg version; nothing is stored in that case.
+pub fn set_preferred_ffmpeg(app: &AppHandle, path: Option<&Path>) -> Result<BinaryInfo, AppError> {
+    if let Some(path) = path {
+        ffmpeg_version(path)?;
+    }
+
+    let preferences = BinaryPreferences {
+        ffmpeg_path: path.map(Path::to_path_buf),
+    };
+    persistence::persist(&store_path(app)?, &PREFERENCES_SCHEMA, &preferences)?;
+    super::invalidate_resolution_cache();
+    Ok(binary_info(app))
+}
+
+/// Reports the binaries the resolver picks right now.
+pub fn binary_info(app: &AppHandle) -> BinaryInfo {
+    BinaryInfo {
+        preferred_path: load(app).ffmpeg_path,
+        ffmpeg: resolved(app, BinaryType::FFmpeg),
+        ffprobe: resolved(app, BinaryType::FFprobe),
+    }
+}
+
+fn resolved(app: &AppHandle, binary_type: BinaryType) -> ResolvedBinary {
+    let name = binary_type.binary_name();
+    let (source, path) = resolve_binary_candidates(binary_type, app)
+        .into_iter()
+        .next()
+        .unwrap_or((BinarySource::SystemPath, name.into()));
+    ResolvedBinary {
+        version: read_version(&path, name),
+        path: path.to_string_lossy().into_owned(),
+        source,
+    }
+}
+
+/// Version of the FFmpeg at `path`.
+///
+/// # Errors
+///
+/// Returns `binary_invalid` when `path` isn't an executable that reports an
+/// FFmpeg version.
+pub(super) fn ffmpeg_version(path: &Path) -> Result<String, AppError> {
+    if !is_valid_binary(&path.to_path_buf()) {
+        return Err(AppError::new(
+            "binary_invalid",
+            format!("{} is not an executable file.", path.display()),
+        ));
+    }
+    read_version(path.as_os_str(), BinaryType::FFmpeg.binary_name()).ok_or_else(|| {
+        AppError::new(
+            "binary_invalid",
+            format!("{} did not report an FFmpeg version.", path.display()),
+        )
+    })
+}
+
+pub(super) fn read_version(path: &OsStr, name: &str) -> Option<String> {
+    let output = Command::new(path).arg("-version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_version_line(&String::from_utf8_lossy(&output.stdout), name)
+}
+
+/// Version from the first line of `-version`, e.g. "7.1.1" from
+/// `ffmpeg version 7.1.1 Copyright (c) 2000-2025 the FFmpeg developers`.
+fn parse_version_line(output: &str, name: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .strip_prefix(name)?
+        .strip_prefix(" version ")?
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_version_line() {
+        let output = "ffmpeg version 7.1.1 Copyright (c) 2000-2025 the FFmpeg developers\n\
+                      built with Apple clang version 16.0.0";
+        assert_eq!(
+            parse_version_line(output, "ffmpeg").as_deref(),
+            Some("7.1.1")
+        );
+        assert_eq!(
+            parse_version_line("ffprobe version n6.1 Copyright", "ffprobe").as_deref(),
+            Some("n6.1")
+        );
+        assert_eq!(parse_version_line(output, "ffprobe"), None);
+        assert_eq!(parse_version_line("usage: tool", "ffmpeg"), None);
+    }
+
+    #[test]
+    fn preferences_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("config").join(PREFERENCES_FILE_NAME);
+        let preferences = BinaryPreferences {
+            ffmpeg_path: Some("/opt/homebrew/bin/ffmpeg".into()),
+        };
+        persistence::persist(&path, &PREFERENCES_SCHEMA, &preferences).unwrap();
+        assert_eq!(
+            persistence::load::<BinaryPreferences>(&path, &PREFERENCES_SCHEMA).unwrap(),
+            Some(preferences)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Media library catalog.
+//!
+//! Library folders are scanned incrementally: files are re-probed only when
+//! their size or modification time changed, and entries for deleted files are
+//! dropped. The stored summaries back query commands such as "all non-HEVC
+//! 1080p files" that feed batch conversions, and user rules turn matching
+//! entries into reviewable batch plans, on demand or on each rule's
+//! schedule.
+
+mod rules;
+mod scan;
+mod storage;
+
+pub use rules::{due_rules, plan_batch, BatchPlan, ConversionRule};
+pub use scan::{scan, ScanSummary};
+pub use storage::{load, persist, store_path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ffmpeg_probe::ProbeSummary;
+
+/// Transfer characteristics that mark HDR content (PQ and HLG).
+const HDR_TRANSFERS: &[&str] = &["smpte2084", "arib-std-b67"];
+
+/// Probe data kept per catalog entry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CatalogMedia {
+    pub duration_sec: f64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    pub vcodec: Option<String>,
+    pub acodec: Option<String>,
+    pub channels: Option<u32>,
+    pub hdr: bool,
+}
+
+impl From<&ProbeSummary> for CatalogMedia {
+    fn from(summary: &ProbeSummary) -> Self {
+        let hdr = summary
+            .color
+            .as_ref()
+            .and_then(|color| color.trc.as_deref())
+            .is_some_and(|trc| HDR_TRANSFERS.contains(&trc));
+
+        Self {
+            duration_sec: summary.duration_sec,
+            width: summary.width,
+            height: summary.height,
+            fps: summary.fps,
+            vcodec: summary.vcodec.clone(),
+            acodec: summary.acodec.clone(),
+            channels: summary.channels,
+            hdr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub path: String,
+    pub size: u64,
+    /// Modification time in seconds since the Unix epoch.
+    pub modified_secs: u64,
+    pub media: CatalogMedia,
+    /// Set when the file changed but re-probing it failed; `media` still
+    /// describes the last successful probe.
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// Persisted catalog: library folders, their scanned files, and the
+/// auto-conversion rules evaluated over them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Catalog {
+    pub libraries: Vec<String>,
+    pub entries: Vec<CatalogEntry>,
+    pub rules: Vec<ConversionRule>,
+}
+
+/// Filters applied by `query_catalog`; unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CatalogQuery {
+    /// Only include these video codecs (case-insensitive).
+    pub video_codecs: Vec<String>,
+    /// Exclude these video codecs (case-insensitive).
+    pub exclude_video_codecs: Vec<String>,
+    pub audio_codecs: Vec<String>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+    pub hdr: Option<bool>,
+    pub min_size_bytes: Option<u64>,
+    pub max_size_bytes: Option<u64>,
+}
+
+impl CatalogQuery {
+    pub fn is_unfiltered(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, entry: &CatalogEntry) -> bool {
+        let media = &entry.media;
+        let vcodec = media.vcodec.as_deref().unwrap_or_default();
+        let acodec = media.acodec.as_deref().unwrap_or_default();
+
+        if !self.video_codecs.is_empty() && !contains_ignore_case(&self.video_codecs, vcodec) {
+            return false;
+        }
+        if contains_ignore_case(&self.exclude_video_codecs, vcodec) {
+            return false;
+        }
+        if !self.audio_codecs.is_empty() && !contains_ignore_case(&self.audio_codecs, acodec) {
+            return false;
+        }
+
+        let height = media.height.unwrap_or_default();
+        if self.min_height.is_some_and(|min| height < min)
+            || self.max_height.is_some_and(|max| height > max)
+        {
+            return false;
+        }
+
+        if self.hdr.is_some_and(|hdr| hdr != media.hdr) {
+            return false;
+        }
+
+        !(self.min_size_bytes.is_some_and(|min| entry.size < min)
+            || self.max_size_bytes.is_some_and(|max| entry.size > max))
+    }
+}
+
+fn contains_ignore_case(values: &[String], needle: &str) -> bool {
+    !needle.is_empty()
+        && values
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(needle))
+}
+
+impl Catalog {
+    pub fn query(&self, query: &CatalogQuery) -> Vec<CatalogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffmpeg_probe::ProbeColor;
+
+    fn entry(path: &str, vcodec: &str, height: u32, size: u64, hdr: bool) -> CatalogEntry {
+        CatalogEntry {
+            path: path.into(),
+            size,
+            modified_secs: 0,
+            media: CatalogMedia {
+                vcodec: Some(vcodec.into()),
+                acodec: Some("aac".into()),
+                height: Some(height),
+                hdr,
+                ..CatalogMedia::default()
+            },
+            stale: false,
+        }
+    }
+
+    fn catalog() -> Catalog {
+        Catalog {
+            entries: vec![
+                entry("/lib/a.mp4", "h264", 1080, 6_000_000_000, false),
+                entry("/lib/b.mkv", "hevc", 1080, 2_000_000_000, false),
+                entry("/lib/c.mov", "h264", 720, 1_000_000_000, false),
+                entry("/lib/d.mkv", "hevc", 2160, 9_000_000_000, true),
+            ],
+            ..Catalog::default()
+        }
+    }
+
+    #[test]
+    fn finds_non_hevc_1080p_files() {
+        let query = CatalogQuery {
+            exclude_video_codecs: vec!["HEVC".into()],
+            min_height: Some(1080),
+            max_height: Some(1080),
+            ..CatalogQuery::default()
+        };
+
+        let results = catalog().query(&query);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "/lib/a.mp4");
+    }
+
+    #[test]
+    fn filters_by_hdr_and_size() {
+        let query = CatalogQuery {
+            hdr: Some(true),
+            min_size_bytes: Some(5_000_000_000),
+            ..CatalogQuery::default()
+        };
+        assert_eq!(catalog().query(&query)[0].path, "/lib/d.mkv");
+
+        let small = CatalogQuery {
+            video_codecs: vec!["h264".into()],
+            max_size_bytes: Some(1_500_000_000),
+            ..CatalogQuery::default()
+        };
+        assert_eq!(catalog().query(&small)[0].path, "/lib/c.mov");
+    }
+
+    #[test]
+    fn detects_hdr_from_transfer_characteristics() {
+        let summary = ProbeSummary {
+            duration_sec: 10.0,
+            width: Some(3840),
+            height: Some(2160),
+            fps: Some(24.0),
+            vcodec: Some("hevc".into()),
+            acodec: None,
+            has_text_subs: false,
+            has_image_subs: false,
+            channels: None,
+            color: Some(ProbeColor {
+                primaries: Some("bt2020".into()),
+                trc: Some("smpte2084".into()),
+                space: Some("bt2020nc".into()),
+                ..Default::default()
+            }),
+            pix_fmt: Some("yuv420p10le".into()),
+            bit_depth: Some(10),
+            profile: Some("Main 10".into()),
+            level: Some(150),
+            bit_rate: None,
+            video_bit_rate: None,
+            audio_bit_rate: None,
+            has_cover_art: false,
+            attachments: Vec::new(),
+            streams: Vec::new(),
+            interlacing: None,
+        };
+
+        assert!(CatalogMedia::from(&summary).hdr);
+    }
+}
@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::{Catalog, CatalogQuery};
+use crate::error::AppError;
+
+/// User-defined auto-conversion rule evaluated against the catalog, e.g.
+/// "anything H.264 over 5 GB → HEVC preset into the same folder".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub query: CatalogQuery,
+    pub preset_id: String,
+    /// Destination folder; `None` writes next to the source file.
+    #[serde(default)]
+    pub output_directory: Option<String>,
+    /// Replace the original once the converted output has been verified.
+    #[serde(default)]
+    pub replace_original: bool,
+    /// Re-scans the libraries and plans this rule every this many hours;
+    /// `None` evaluates it on demand only.
+    #[serde(default)]
+    pub schedule_hours: Option<u32>,
+    /// When the schedule last evaluated the rule, in seconds since the Unix
+    /// epoch.
+    #[serde(default)]
+    pub last_scheduled_secs: Option<u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ConversionRule {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.id.trim().is_empty() || self.preset_id.trim().is_empty() {
+            return Err(AppError::new(
+                "catalog_rule_invalid",
+                "Conversion rules need an id and a preset.",
+            ));
+        }
+
+        if let Some(dir) = self.output_directory.as_deref() {
+            if !Path::new(dir).is_dir() {
+                return Err(AppError::new(
+                    "catalog_rule_invalid",
+                    format!("Output folder {dir} is not a directory."),
+                ));
+            }
+        }
+
+        if self.schedule_hours == Some(0) {
+            return Err(AppError::new(
+                "catalog_rule_invalid",
+                "Scheduled rules must run at most once an hour.",
+            ));
+        }
+
+        if self.replace_original && self.query.is_unfiltered() {
+            return Err(AppError::new(
+                "catalog_rule_invalid",
+                "Rules that replace originals must filter the catalog.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A single conversion proposed by a rule, awaiting user review.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedConversion {
+    pub rule_id: String,
+    pub source: String,
+    pub preset_id: String,
+    pub output_directory: String,
+    pub replace_original: bool,
+    pub size: u64,
+}
+
+/// Reviewable batch generated from the enabled rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchPlan {
+    pub items: Vec<PlannedConversion>,
+    pub total_bytes: u64,
+}
+
+/// Ids of the enabled, scheduled rules whose interval has passed at `now_secs`.
+pub fn due_rules(catalog: &Catalog, now_secs: u64) -> Vec<String> {
+    catalog
+        .rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter(|rule| {
+            rule.schedule_hours.is_some_and(|hours| {
+                let interval = u64::from(hours) * 60 * 60;
+                rule.last_scheduled_secs
+                    .is_none_or(|last| now_secs.saturating_sub(last) >= interval)
+            })
+        })
+        .map(|rule| rule.id.clone())
+        .collect()
+}
+
+/// Evaluates rules in order; a file claimed by an earlier rule is not planned
+/// again by later ones. `rule_ids` restricts evaluation to specific rules.
+pub fn plan_batch(catalog: &Catalog, rule_ids: Option<&[String]>) -> BatchPlan {
+    let mut claimed: HashSet<&str> = HashSet::new();
+    let mut plan = BatchPlan::default();
+
+    let rules = catalog.rules.iter().filter(|rule| match rule_ids {
+        Some(ids) => ids.contains(&rule.id),
+        None => rule.enabled,
+    });
+
+    for rule in rules {
+        for entry in &catalog.entries {
+            if !rule.query.matches(entry) || !claimed.insert(entry.path.as_str()) {
+                continue;
+            }
+
+            let output_directory = rule.output_directory.clone().unwrap_or_else(|| {
+                Path::new(&entry.path)
+                    .parent()
+                    .map(|parent| parent.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+
+            plan.total_bytes += entry.size;
+            plan.items.push(PlannedConversion {
+                rule_id: rule.id.clone(),
+                source: entry.path.clone(),
+                preset_id: rule.preset_id.clone(),
+                output_directory,
+                replace_original: rule.replace_original,
+                size: entry.size,
+            });
+        }
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{CatalogEntry, CatalogMedia};
+
+    fn entry(path: &str, vcodec: &str, size: u64) -> CatalogEntry {
+        CatalogEntry {
+            path: path.into(),
+            size,
+            modified_secs: 0,
+            media: CatalogMedia {
+                vcodec: Some(vcodec.into()),
+                height: Some(1080),
+                ..CatalogMedia::default()
+            },
+            stale: false,
+        }
+    }
+
+    fn rule(id: &str, query: CatalogQuery) -> ConversionRule {
+        ConversionRule {
+            id: id.into(),
+            name: id.into(),
+            enabled: true,
+            query,
+            preset_id: "video-to-mp4-hevc".into(),
+            output_directory: None,
+            replace_original: false,
+            schedule_hours: None,
+            last_scheduled_secs: None,
+        }
+    }
+
+    fn large_h264() -> CatalogQuery {
+        CatalogQuery {
+            video_codecs: vec!["h264".into()],
+            min_size_bytes: Some(5_000_000_000),
+            ..CatalogQuery::default()
+        }
+    }
+
+    #[test]
+    fn plans_matching_files_into_their_own_folder() {
+        let catalog = Catalog {
+            entries: vec![
+                entry("/lib/show/a.mkv", "h264", 6_000_000_000),
+                entry("/lib/show/b.mkv", "h264", 1_000_000_000),
+                entry("/lib/show/c.mkv", "hevc", 8_000_000_000),
+            ],
+            rules: vec![rule("big-h264", large_h264())],
+            ..Catalog::default()
+        };
+
+        let plan = plan_batch(&catalog, None);
+        assert_eq!(plan.items.len(), 1);
+        assert_eq!(plan.items[0].source, "/lib/show/a.mkv");
+        assert_eq!(plan.items[0].output_directory, "/lib/show");
+        assert_eq!(plan.total_bytes, 6_000_000_000);
+    }
+
+    #[test]
+    fn earlier_rules_claim_files_and_disabled_rules_are_skipped() {
+        let mut disabled = rule("disabled", CatalogQuery::default());
+        disabled.enabled = false;
+        let catalog = Catalog {
+            entries: vec![entry("/lib/a.mkv", "h264", 6_000_000_000)],
+            rules: vec![
+                disabled,
+                rule("first", large_h264()),
+                rule("second", large_h264()),
+            ],
+            ..Catalog::default()
+        };
+
+        let plan = plan_batch(&catalog, None);
+        assert_eq!(plan.items.len(), 1);
+        assert_eq!(plan.items[0].rule_id, "first");
+
+        let only_second = plan_batch(&catalog, Some(&["second".to_string()]));
+        assert_eq!(only_second.items[0].rule_id, "second");
+    }
+
+    #[test]
+    fn scheduled_rules_are_due_once_their_interval_passed() {
+        let mut daily = rule("daily", large_h264());
+        daily.schedule_hours = Some(24);
+        let mut ran_recently = daily.clone();
+        ran_recently.id = "recent".into();
+        ran_recently.last_scheduled_secs = Some(100_000 - 3_600);
+        let mut ran_yesterday = ran_recently.clone();
+        ran_yesterday.id = "yesterday".into();
+        ran_yesterday.last_scheduled_secs = Some(100_000 - 24 * 3_600);
+        let mut disabled = daily.clone();
+        disabled.id = "disabled".into();
+        disabled.enabled = false;
+        let catalog = Catalog {
+            rules: vec![
+                rule("on-demand", large_h264()),
+                daily,
+                ran_recently,
+                ran_yesterday,
+                disabled,
+            ],
+            ..Catalog::default()
+        };
+
+        assert_eq!(due_rules(&catalog, 100_000), vec!["daily", "yesterday"]);
+    }
+
+    #[test]
+    fn replacing_rules_must_filter_the_catalog() {
+        let mut unsafe_rule = rule("all", CatalogQuery::default());
+        unsafe_rule.replace_original = true;
+        assert_eq!(
+            unsafe_rule.validate().unwrap_err().code,
+            "catalog_rule_invalid"
+        );
+
+        unsafe_rule.query = large_h264();
+        assert!(unsafe_rule.validate().is_ok());
+    }
+}
@@ -0,0 +1,215 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use super::{Catalog, CatalogEntry, CatalogMedia};
+use crate::{error::AppError, fs_utils, services::MediaFilter};
+
+/// Counts of what changed during an incremental scan.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    pub failed: Vec<ScanFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanFailure {
+    pub path: String,
+    pub message: String,
+}
+
+/// Rescans the catalog's library folders, probing only new or changed files.
+pub fn scan(
+    catalog: &mut Catalog,
+    mut probe: impl FnMut(&str) -> Result<CatalogMedia, AppError>,
+) -> Result<ScanSummary, AppError> {
+    let files = fs_utils::expand_media_paths(catalog.libraries.clone())?;
+    let mut previous: HashMap<String, CatalogEntry> = catalog
+        .entries
+        .drain(..)
+        .map(|entry| (entry.path.clone(), entry))
+        .collect();
+
+    let mut summary = ScanSummary::default();
+    let mut entries = Vec::new();
+
+    for path in files.into_iter().filter(|path| is_media_file(path)) {
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let size = metadata.len();
+        let modified_secs = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let known = previous.remove(&path);
+        if let Some(entry) = known
+            .as_ref()
+            .filter(|entry| entry.size == size && entry.modified_secs == modified_secs)
+        {
+            entries.push(entry.clone());
+            summary.unchanged += 1;
+            continue;
+        }
+
+        match probe(&path) {
+            Ok(media) => {
+                if known.is_some() {
+                    summary.updated += 1;
+                } else {
+                    summary.added += 1;
+                }
+                entries.push(CatalogEntry {
+                    path,
+                    size,
+                    modified_secs,
+                    media,
+                    stale: false,
+                });
+            },
+            Err(err) => {
+                // Keep what was known about a changed file; its old size and
+                // time make the next scan probe it again.
+                if let Some(entry) = known {
+                    entries.push(CatalogEntry {
+                        stale: true,
+                        ..entry
+                    });
+                }
+                summary.failed.push(ScanFailure {
+                    path,
+                    message: err.message,
+                });
+            },
+        }
+    }
+
+    summary.removed = previous.len();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    catalog.entries = entries;
+    Ok(summary)
+}
+
+fn is_media_file(path: &str) -> bool {
+    let Some(extension) = Path::new(path).extension().and_then(|ext| ext.to_str()) else {
+        return false;
+    };
+    [MediaFilter::Video, MediaFilter::Audio]
+        .iter()
+        .flat_map(|filter| filter.extensions())
+        .any(|known| known.eq_ignore_ascii_case(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn media(vcodec: &str) -> CatalogMedia {
+        CatalogMedia {
+            vcodec: Some(vcodec.into()),
+            ..CatalogMedia::default()
+        }
+    }
+
+    #[test]
+    fn scan_probes_only_new_and_changed_files() {
+        let temp = TempDir::new().unwrap();
+        let first = temp.path().join("a.mp4");
+        let second = temp.path().join("b.MKV");
+        fs::write(&first, b"one").unwrap();
+        fs::write(&second, b"two").unwrap();
+        fs::write(temp.path().join("notes.txt"), b"ignored").unwrap();
+
+        let mut catalog = Catalog {
+            libraries: vec![temp.path().to_string_lossy().into_owned()],
+            ..Catalog::default()
+        };
+
+        let mut probed = Vec::new();
+        let summary = scan(&mut catalog, |path| {
+            probed.push(path.to_string());
+            Ok(media("h264"))
+        })
+        .unwrap();
+        assert_eq!(summary.added, 2);
+        assert_eq!(probed.len(), 2);
+
+        fs::write(&second, b"longer content").unwrap();
+        fs::remove_file(&first).unwrap();
+
+        let mut reprobed = Vec::new();
+        let summary = scan(&mut catalog, |path| {
+            reprobed.push(path.to_string());
+            Ok(media("hevc"))
+        })
+        .unwrap();
+
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.removed, 1);
+        assert_eq!(summary.unchanged, 0);
+        assert_eq!(reprobed, vec![second.to_string_lossy().into_owned()]);
+        assert_eq!(catalog.entries.len(), 1);
+        assert_eq!(catalog.entries[0].media.vcodec.as_deref(), Some("hevc"));
+
+        let summary = scan(&mut catalog, |_| panic!("unchanged files are not probed")).unwrap();
+        assert_eq!(summary.unchanged, 1);
+    }
+
+    #[test]
+    fn probe_failures_are_reported_without_aborting() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("broken.mov"), b"x").unwrap();
+        let mut catalog = Catalog {
+            libraries: vec![temp.path().to_string_lossy().into_owned()],
+            ..Catalog::default()
+        };
+
+        let summary = scan(&mut catalog, |_| {
+            Err(AppError::new("probe_ffprobe_exec", "ffprobe failed"))
+        })
+        .unwrap();
+
+        assert_eq!(summary.failed.len(), 1);
+        assert!(catalog.entries.is_empty());
+    }
+
+    #[test]
+    fn failed_reprobes_keep_the_known_entry_as_stale() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("a.mp4");
+        fs::write(&file, b"one").unwrap();
+        let mut catalog = Catalog {
+            libraries: vec![temp.path().to_string_lossy().into_owned()],
+            ..Catalog::default()
+        };
+        scan(&mut catalog, |_| Ok(media("h264"))).unwrap();
+
+        fs::write(&file, b"rewritten").unwrap();
+        let summary = scan(&mut catalog, |_| {
+            Err(AppError::new("probe_ffprobe_exec", "ffprobe failed"))
+        })
+        .unwrap();
+
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.removed, 0);
+        assert_eq!(catalog.entries.len(), 1);
+        assert!(catalog.entries[0].stale);
+        assert_eq!(catalog.entries[0].media.vcodec.as_deref(), Some("h264"));
+
+        let summary = scan(&mut catalog, |_| Ok(media("hevc"))).unwrap();
+        assert_eq!(summary.updated, 1);
+        assert!(!catalog.entries[0].stale);
+        assert_eq!(catalog.entries[0].media.vcodec.as_deref(), Some("hevc"));
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use super::Catalog;
+use crate::{
+    error::AppError,
+    persistence::{self, DocumentSchema},
+};
+
+const CATALOG_FILE_NAME: &str = "catalog.json";
+const CATALOG_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "catalog",
+    migrations: &[],
+};
+
+pub fn store_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(CATALOG_FILE_NAME))
+        .map_err(|err| AppError::new("catalog_store_path", err.to_string()))
+}
+
+pub fn load(path: &Path) -> Result<Catalog, AppError> {
+    Ok(persistence::load(path, &CATALOG_SCHEMA)?.unwrap_or_default())
+}
+
+pub fn persist(path: &Path, catalog: &Catalog) -> Result<(), AppError> {
+    persistence::persist(path, &CATALOG_SCHEMA, catalog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn missing_store_loads_empty_catalog() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("data").join(CATALOG_FILE_NAME);
+        assert_eq!(load(&path).unwrap(), Catalog::default());
+
+        let catalog = Catalog {
+            libraries: vec!["/Volumes/Media".into()],
+            ..Catalog::default()
+        };
+        persist(&path, &catalog).unwrap();
+        assert_eq!(load(&path).unwrap(), catalog);
+    }
+}
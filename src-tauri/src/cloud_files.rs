@@ -0,0 +1,135 @@
+//! Detection of cloud-synced files that aren't on this device yet.
+//!
+//! iCloud Drive, OneDrive and Dropbox can keep files online only, leaving a
+//! placeholder that is fetched when it is read. ffmpeg reading one stalls
+//! on the download or fails halfway through, so probes and jobs refuse such
+//! inputs up front with `input_not_downloaded`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{error::AppError, fs_utils::long_path};
+
+pub const INPUT_NOT_DOWNLOADED: &str = "input_not_downloaded";
+
+/// Smallest file whose block count is trusted; tiny files may be stored
+/// inline in their inode and report no blocks.
+#[cfg(unix)]
+const MIN_SPARSE_CHECK_LEN: u64 = 64 * 1024;
+
+/// Suffix of the stubs older iCloud Drive versions leave in place of a
+/// file, named `.<name>.icloud`.
+const ICLOUD_STUB_SUFFIX: &str = ".icloud";
+
+/// Fails with `input_not_downloaded` when `path` is only stored online.
+pub fn ensure_downloaded(path: &Path) -> Result<(), AppError> {
+    if !is_online_only(path) {
+        return Ok(());
+    }
+    Err(AppError::new(
+        INPUT_NOT_DOWNLOADED,
+        format!(
+            "{} is only stored online. Download it to this device and try again.",
+            path.display()
+        ),
+    ))
+}
+
+/// Whether `path` is a cloud placeholder, or is missing with an iCloud
+/// stub in its place.
+pub fn is_online_only(path: &Path) -> bool {
+    match fs::metadata(long_path(path)) {
+        Ok(metadata) => metadata.is_file() && is_placeholder(&metadata),
+        Err(_) => icloud_stub(path).is_some_and(|stub| long_path(&stub).is_file()),
+    }
+}
+
+/// The file an iCloud stub such as `.clip.mov.icloud` stands in for.
+pub fn stub_target(stub: &Path) -> Option<PathBuf> {
+    let name = stub.file_name()?.to_str()?;
+    let target = name.strip_prefix('.')?.strip_suffix(ICLOUD_STUB_SUFFIX)?;
+    (!target.is_empty()).then(|| stub.with_file_name(target))
+}
+
+fn icloud_stub(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    Some(path.with_file_name(format!(".{name}{ICLOUD_STUB_SUFFIX}")))
+}
+
+/// A sizeable file without allocated blocks is a placeholder on every
+/// provider; macOS also flags File Provider placeholders as dataless.
+#[cfg(unix)]
+fn is_placeholder(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.len() >= MIN_SPARSE_CHECK_LEN && metadata.blocks() == 0) || is_dataless(metadata)
+}
+
+#[cfg(target_os = "macos")]
+fn is_dataless(metadata: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x4000_0000;
+    metadata.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_dataless(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Cloud Files placeholders (OneDrive, Dropbox) carry recall attributes.
+#[cfg(windows)]
+fn is_placeholder(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x0000_1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+    metadata.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN
+            | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_placeholder(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icloud_stubs_stand_in_for_their_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let clip = dir.path().join("clip.mov");
+        fs::write(dir.path().join(".clip.mov.icloud"), b"plist").unwrap();
+
+        assert_eq!(
+            stub_target(&dir.path().join(".clip.mov.icloud")),
+            Some(clip.clone())
+        );
+        assert_eq!(stub_target(&dir.path().join("clip.icloud")), None);
+        let err = ensure_downloaded(&clip).unwrap_err();
+        assert_eq!(err.code, INPUT_NOT_DOWNLOADED);
+        assert!(ensure_downloaded(&dir.path().join("other.mov")).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sparse_files_are_online_only() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let sparse = dir.path().join("sparse.mov");
+        let local = dir.path().join("local.mov");
+        fs::File::create(&sparse)
+            .unwrap()
+            .set_len(MIN_SPARSE_CHECK_LEN * 4)
+            .unwrap();
+        fs::write(&local, vec![1u8; MIN_SPARSE_CHECK_LEN as usize]).unwrap();
+
+        assert!(is_online_only(&sparse));
+        assert!(!is_online_only(&local));
+    }
+}
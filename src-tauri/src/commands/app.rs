@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use super::validation::Validator;
+use crate::{
+    app_mode::AppMode,
+    error::AppError,
+    metrics::{CommandStats, MUTATING_COMMANDS},
+    services::ServiceRegistry,
+};
+
+#[tauri::command]
+pub async fn app_mode(services: State<'_, ServiceRegistry>) -> Result<AppMode, AppError> {
+    Ok(services.inner().mode)
+}
+
+/// Quits after the user confirmed interrupting active jobs: running jobs
+/// are killed and their partial output removed, and the queue stays on disk
+/// to be restored next launch.
+#[tauri::command]
+pub async fn confirm_quit(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<(), AppError> {
+    services.inner().jobs.shutdown();
+    app.exit(0);
+    Ok(())
+}
+
+/// Per-command call counts, durations, slow calls and timeouts.
+#[tauri::command]
+pub async fn command_metrics(
+    services: State<'_, ServiceRegistry>,
+) -> Result<BTreeMap<String, CommandStats>, AppError> {
+    Ok(services.inner().metrics.snapshot())
+}
+
+/// Overrides a command's timeout; `null` lets it run without one.
+/// Commands that write files or settings can't be given one.
+#[tauri::command]
+pub async fn set_command_timeout(
+    services: State<'_, ServiceRegistry>,
+    mut command: String,
+    timeout_ms: Option<u64>,
+) -> Result<(), AppError> {
+    Validator::new().id("command", &mut command).finish()?;
+    if MUTATING_COMMANDS.contains(&command.as_str()) {
+        return Err(AppError::new(
+            "command_timeout_unsupported",
+            format!("{command} changes files or settings and always runs to completion"),
+        ));
+    }
+    services
+        .inner()
+        .timeouts
+        .set(&command, timeout_ms.map(Duration::from_millis));
+    Ok(())
+}
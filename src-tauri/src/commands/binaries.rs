@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use super::{timeout::run_blocking, validation::Validator};
+use crate::{
+    binary_resolver::{
+        BinaryDiagnostics, BinaryInfo, FfmpegInstallation, FfmpegInstallations, ManagedInstall,
+    },
+    error::AppError,
+    services::ServiceRegistry,
+};
+
+/// Downloads and installs the pinned FFmpeg build, reporting progress as
+/// `ffmpeg://download-progress` events. The resolver adopts it right away.
+#[tauri::command]
+pub async fn install_ffmpeg(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<ManagedInstall, AppError> {
+    services.inner().mode.ensure_writable("install FFmpeg")?;
+    let binary_service = services.inner().binaries.clone();
+    run_blocking(
+        services.inner(),
+        "install_ffmpeg",
+        "binary_thread_join",
+        move || binary_service.install_managed(&app),
+    )
+    .await
+}
+
+/// The installed managed FFmpeg build, if any.
+#[tauri::command]
+pub async fn managed_ffmpeg(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<Option<ManagedInstall>, AppError> {
+    Ok(services.inner().binaries.managed(&app))
+}
+
+/// Makes `path` the FFmpeg to use ahead of every other candidate, after
+/// checking it runs; `null` goes back to automatic resolution.
+#[tauri::command]
+pub async fn set_ffmpeg_path(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    path: Option<String>,
+) -> Result<BinaryInfo, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("change the FFmpeg path")?;
+    if let Some(path) = &path {
+        Validator::new().existing_path("path", path).finish()?;
+    }
+    let binary_service = services.inner().binaries.clone();
+    run_blocking(
+        services.inner(),
+        "set_ffmpeg_path",
+        "binary_thread_join",
+        move || binary_service.set_preferred_ffmpeg(&app, path.map(PathBuf::from).as_deref()),
+    )
+    .await
+}
+
+/// The FFmpeg and FFprobe in use, where they were found and their versions.
+#[tauri::command]
+pub async fn get_binary_info(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<BinaryInfo, AppError> {
+    let binary_service = services.inner().binaries.clone();
+    run_blocking(
+        services.inner(),
+        "get_binary_info",
+        "binary_thread_join",
+        move || Ok(binary_service.info(&app)),
+    )
+    .await
+}
+
+/// Every path searched for FFmpeg and FFprobe, with what was found there
+/// and which one is used, for working out why a binary isn't found.
+#[tauri::command]
+pub async fn diagnose_binaries(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<BinaryDiagnostics, AppError> {
+    let binary_service = services.inner().binaries.clone();
+    run_blocking(
+        services.inner(),
+        "diagnose_binaries",
+        "binary_thread_join",
+        move || Ok(binary_service.diagnose(&app)),
+    )
+    .await
+}
+
+/// The registered FFmpeg builds, with the preferred one marked.
+#[tauri::command]
+pub async fn list_ffmpeg_installations(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<FfmpegInstallations, AppError> {
+    Ok(services.inner().binaries.installations(&app))
+}
+
+/// Registers the FFmpeg at `path` as a build jobs can select by id, after
+/// checking it runs.
+#[tauri::command]
+pub async fn register_ffmpeg_installation(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut label: String,
+    path: String,
+) -> Result<FfmpegInstallation, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("register FFmpeg installations")?;
+    Validator::new()
+        .id("label", &mut label)
+        .existing_path("path", &path)
+        .finish()?;
+    let binary_service = services.inner().binaries.clone();
+    run_blocking(
+        services.inner(),
+        "register_ffmpeg_installation",
+        "binary_thread_join",
+        move || binary_service.register_installation(&app, &label, Path::new(&path)),
+    )
+    .await
+}
+
+/// Forgets a registered FFmpeg build; the binary stays on disk.
+#[tauri::command]
+pub async fn remove_ffmpeg_installation(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut id: String,
+) -> Result<(), AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("remove FFmpeg installations")?;
+    Validator::new().id("id", &mut id).finish()?;
+    services.inner().binaries.remove_installation(&app, &id)
+}
@@ -0,0 +1,117 @@
+use tauri::{AppHandle, State};
+
+use super::{timeout::run_blocking, validation::Validator};
+use crate::{
+    catalog::{BatchPlan, CatalogEntry, CatalogQuery, ConversionRule, ScanSummary},
+    error::AppError,
+    services::ServiceRegistry,
+};
+
+#[tauri::command]
+pub async fn catalog_libraries(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<String>, AppError> {
+    let catalog = services.inner().catalog.clone();
+    catalog.libraries(&app)
+}
+
+#[tauri::command]
+pub async fn set_catalog_libraries(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    libraries: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("change library folders")?;
+    let catalog = services.inner().catalog.clone();
+    catalog.set_libraries(&app, libraries)
+}
+
+#[tauri::command]
+pub async fn scan_catalog(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<ScanSummary, AppError> {
+    services.inner().mode.ensure_writable("scan libraries")?;
+    let catalog = services.inner().catalog.clone();
+    run_blocking(
+        services.inner(),
+        "scan_catalog",
+        "catalog_thread_join",
+        move || catalog.scan(&app),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn query_catalog(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    query: CatalogQuery,
+) -> Result<Vec<CatalogEntry>, AppError> {
+    let catalog = services.inner().catalog.clone();
+    run_blocking(
+        services.inner(),
+        "query_catalog",
+        "catalog_thread_join",
+        move || catalog.query(&app, &query),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn conversion_rules(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<ConversionRule>, AppError> {
+    let catalog = services.inner().catalog.clone();
+    catalog.rules(&app)
+}
+
+#[tauri::command]
+pub async fn save_conversion_rule(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    rule: ConversionRule,
+) -> Result<Vec<ConversionRule>, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("save conversion rules")?;
+    let catalog = services.inner().catalog.clone();
+    catalog.save_rule(&app, rule)
+}
+
+#[tauri::command]
+pub async fn remove_conversion_rule(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut id: String,
+) -> Result<Vec<ConversionRule>, AppError> {
+    Validator::new().id("id", &mut id).finish()?;
+    services
+        .inner()
+        .mode
+        .ensure_writable("remove conversion rules")?;
+    let catalog = services.inner().catalog.clone();
+    catalog.remove_rule(&app, &id)
+}
+
+#[tauri::command]
+pub async fn plan_rule_batch(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    rule_ids: Option<Vec<String>>,
+) -> Result<BatchPlan, AppError> {
+    let catalog = services.inner().catalog.clone();
+    run_blocking(
+        services.inner(),
+        "plan_rule_batch",
+        "catalog_thread_join",
+        move || catalog.plan_batch(&app, rule_ids),
+    )
+    .await
+}
@@ -1,5 +1,6 @@
 use tauri::State;
 
+use super::timeout::run_blocking;
 use crate::{
     error::AppError,
     services::{MediaFilter, ServiceRegistry},
@@ -12,7 +13,13 @@ pub async fn pick_media_files(
 ) -> Result<Vec<String>, AppError> {
     let dialog_service = services.inner().dialogs.clone();
     let filter = MediaFilter::from_kind(media_kind.as_deref());
-    spawn_dialog(move || dialog_service.pick_media_files(filter)).await
+    run_blocking(
+        services.inner(),
+        "pick_media_files",
+        "dialog_thread_join",
+        move || dialog_service.pick_media_files(filter),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -21,16 +28,11 @@ pub async fn choose_output_directory(
     default_path: Option<String>,
 ) -> Result<Option<String>, AppError> {
     let dialog_service = services.inner().dialogs.clone();
-    spawn_dialog(move || dialog_service.choose_output_directory(default_path)).await
-}
-
-async fn spawn_dialog<T>(
-    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
-) -> Result<T, AppError>
-where
-    T: Send + 'static,
-{
-    tauri::async_runtime::spawn_blocking(task)
-        .await
-        .map_err(|err| AppError::new("dialog_thread_join", err.to_string()))?
+    run_blocking(
+        services.inner(),
+        "choose_output_directory",
+        "dialog_thread_join",
+        move || dialog_service.choose_output_directory(default_path),
+    )
+    .await
 }
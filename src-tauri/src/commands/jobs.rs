@@ -1,39 +1,79 @@
 #[cfg(test)]
 use once_cell::sync::Lazy;
 use serde::Serialize;
+use std::ffi::OsString;
 #[cfg(test)]
 use std::sync::Mutex;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, State};
 
-use crate::{error::AppError, services::ServiceRegistry};
+use super::validation::{validated, Validator};
+use crate::{
+    api::JobSpec,
+    error::AppError,
+    runner::{
+        battery::BatteryPolicy,
+        command_builder::FfmpegCommandBuilder,
+        coordinator::{JobActionResult, JobOptions, RestoredJob},
+        events::EventEncoding,
+        history::HistoryEntry,
+        hooks::PostJobHook,
+        retry::RetryPolicy,
+    },
+    services::ServiceRegistry,
+};
 
 #[tauri::command]
 pub async fn start_job(
     app: AppHandle,
     services: State<'_, ServiceRegistry>,
-    job_id: String,
-    args: Vec<String>,
-    output_path: String,
-    exclusive: bool,
+    spec: JobSpec,
 ) -> Result<(), AppError> {
+    services.inner().mode.ensure_writable("start jobs")?;
+    let spec = validated("spec", spec)?;
     let jobs = services.inner().jobs.clone();
+    let preset_id = spec.preset_id().map(str::to_string);
+    let estimated_size = spec.estimated_output_bytes();
+    let args: Vec<OsString> = match &spec.command {
+        Some(command) => FfmpegCommandBuilder::from_command(command).build()?,
+        None => spec.args.into_iter().map(OsString::from).collect(),
+    };
     match jobs.start_job(
         app.clone(),
-        job_id.clone(),
+        spec.job_id.clone(),
         args.clone(),
-        output_path,
-        exclusive,
+        spec.output_path.into_path_buf(),
+        JobOptions {
+            exclusive: spec.exclusive,
+            low_priority: spec.low_priority,
+            depends_on: spec.depends_on,
+            overwrite: spec.overwrite.unwrap_or_default(),
+            estimated_size,
+            preset_id: preset_id.clone(),
+            duration_sec: spec.duration_sec,
+            segmented: spec.segmented,
+            threads: spec.threads,
+            trash_source: spec.trash_source,
+            preserve_timestamps: spec.preserve_timestamps,
+            metadata: spec.metadata,
+            structured: spec.command.is_some(),
+            ffmpeg_installation: spec.ffmpeg_installation,
+        },
     ) {
         Ok(value) => Ok(value),
         Err(err) => {
             emit_job_failure(
                 &app,
                 JobFailureTelemetry {
-                    job_id,
+                    job_id: spec.job_id,
                     stage: "start".into(),
                     code: err.code.into(),
                     message: err.message.clone(),
-                    args,
+                    args: args
+                        .iter()
+                        .map(|arg| arg.to_string_lossy().into_owned())
+                        .collect(),
+                    preset_id,
                 },
             );
             Err(err)
@@ -44,12 +84,115 @@ pub async fn start_job(
 #[tauri::command]
 pub async fn cancel_job(
     services: State<'_, ServiceRegistry>,
-    job_id: String,
+    mut job_id: String,
 ) -> Result<bool, AppError> {
+    Validator::new().job_id("jobId", &mut job_id).finish()?;
     let jobs = services.inner().jobs.clone();
     jobs.cancel_job(&job_id)
 }
 
+/// Cancels every queued, waiting and running job in one pass.
+#[tauri::command]
+pub async fn cancel_all_jobs(
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<JobActionResult>, AppError> {
+    let jobs = services.inner().jobs.clone();
+    Ok(jobs.cancel_all_jobs())
+}
+
+/// Suspends every running job; they keep their slots until resumed.
+#[tauri::command]
+pub async fn pause_all_jobs(
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<JobActionResult>, AppError> {
+    let jobs = services.inner().jobs.clone();
+    Ok(jobs.pause_all_jobs())
+}
+
+#[tauri::command]
+pub async fn resume_all_jobs(
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<JobActionResult>, AppError> {
+    let jobs = services.inner().jobs.clone();
+    Ok(jobs.resume_all_jobs())
+}
+
+/// Lists jobs interrupted by the last quit or crash. With `resume`, they
+/// are started again from the beginning; otherwise they are only handed
+/// back so the frontend can re-queue them.
+#[tauri::command]
+pub async fn restore_jobs(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    resume: bool,
+) -> Result<Vec<RestoredJob>, AppError> {
+    if resume {
+        services.inner().mode.ensure_writable("resume jobs")?;
+    }
+    let jobs = services.inner().jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || jobs.restore_jobs(app, resume))
+        .await
+        .map_err(|err| AppError::new("jobs_thread_join", err.to_string()))
+}
+
+/// Finished jobs, newest first.
+#[tauri::command]
+pub async fn get_history(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<HistoryEntry>, AppError> {
+    let jobs = services.inner().jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || jobs.history(app))
+        .await
+        .map_err(|err| AppError::new("jobs_thread_join", err.to_string()))
+}
+
+#[tauri::command]
+pub async fn clear_history(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<(), AppError> {
+    services.inner().mode.ensure_writable("clear history")?;
+    let jobs = services.inner().jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || jobs.clear_history(app))
+        .await
+        .map_err(|err| AppError::new("jobs_thread_join", err.to_string()))
+}
+
+/// Path of a job's current ffmpeg log, if one was written.
+#[tauri::command]
+pub async fn get_job_log_path(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    job_id: String,
+) -> Result<Option<String>, AppError> {
+    let jobs = services.inner().jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || jobs.job_log_path(app, &job_id))
+        .await
+        .map_err(|err| AppError::new("jobs_thread_join", err.to_string()))?
+}
+
+/// Copies the logs of one job, or of every job, into `destination`. Files
+/// already there are never overwritten.
+#[tauri::command]
+pub async fn export_job_logs(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut job_id: Option<String>,
+    destination: String,
+) -> Result<Vec<String>, AppError> {
+    services.inner().mode.ensure_writable("export job logs")?;
+    let mut validator = Validator::new();
+    if let Some(job_id) = job_id.as_mut() {
+        validator.job_id("jobId", job_id);
+    }
+    validator.path("destination", &destination).finish()?;
+    let jobs = services.inner().jobs.clone();
+    tauri::async_runtime::spawn_blocking(move || jobs.export_job_logs(app, job_id, destination))
+        .await
+        .map_err(|err| AppError::new("jobs_thread_join", err.to_string()))?
+}
+
 #[tauri::command]
 pub async fn set_max_concurrency(
     services: State<'_, ServiceRegistry>,
@@ -60,6 +203,153 @@ pub async fn set_max_concurrency(
     Ok(())
 }
 
+/// Replaces the policy for retrying jobs that fail transiently. Applies
+/// to failures from now on, including jobs already running.
+#[tauri::command]
+pub async fn set_retry_policy(
+    services: State<'_, ServiceRegistry>,
+    policy: RetryPolicy,
+) -> Result<(), AppError> {
+    let policy = validated("policy", policy)?;
+    let jobs = services.inner().jobs.clone();
+    jobs.set_retry_policy(policy);
+    Ok(())
+}
+
+/// Replaces and saves the hooks run once a job finishes: programs started
+/// with the job's details or webhooks receiving them as JSON.
+#[tauri::command]
+pub async fn set_post_job_hooks(
+    services: State<'_, ServiceRegistry>,
+    hooks: Vec<PostJobHook>,
+) -> Result<(), AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("change post-job hooks")?;
+    let hooks = validated("hooks", hooks)?;
+    let jobs = services.inner().jobs.clone();
+    jobs.set_post_job_hooks(hooks);
+    Ok(())
+}
+
+/// Sets whether conversions run at background priority unless their spec
+/// says otherwise. Applies to jobs started afterwards.
+#[tauri::command]
+pub async fn set_low_priority(
+    services: State<'_, ServiceRegistry>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let jobs = services.inner().jobs.clone();
+    jobs.set_low_priority(enabled);
+    Ok(())
+}
+
+/// Sets the encoder thread limit for jobs whose spec doesn't set one;
+/// `null` lets ffmpeg use every core. Applies to jobs started afterwards.
+#[tauri::command]
+pub async fn set_default_threads(
+    services: State<'_, ServiceRegistry>,
+    threads: Option<u32>,
+) -> Result<(), AppError> {
+    Validator::new().threads("threads", threads).finish()?;
+    let jobs = services.inner().jobs.clone();
+    jobs.set_default_threads(threads);
+    Ok(())
+}
+
+/// Sets whether running jobs keep the system awake. Takes effect
+/// immediately.
+#[tauri::command]
+pub async fn set_prevent_sleep(
+    services: State<'_, ServiceRegistry>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let jobs = services.inner().jobs.clone();
+    jobs.set_prevent_sleep(enabled);
+    Ok(())
+}
+
+/// Sets what the queue does while the machine runs on battery: `ignore`,
+/// `defer` new starts, or also `pause` running jobs.
+#[tauri::command]
+pub async fn set_battery_policy(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    policy: BatteryPolicy,
+) -> Result<(), AppError> {
+    let jobs = services.inner().jobs.clone();
+    jobs.set_battery_policy(app, policy);
+    Ok(())
+}
+
+/// Sets how long a conversion may go without progress before it is
+/// stopped with `job_stalled`; `null` disables the watchdog. Applies to
+/// jobs started afterwards.
+#[tauri::command]
+pub async fn set_stall_timeout(
+    services: State<'_, ServiceRegistry>,
+    timeout_ms: Option<u64>,
+) -> Result<(), AppError> {
+    let jobs = services.inner().jobs.clone();
+    jobs.set_stall_timeout(timeout_ms.map(Duration::from_millis));
+    Ok(())
+}
+
+/// Sets the minimum time between progress events of one job; `0` sends
+/// every event. Applies immediately, including to running jobs.
+#[tauri::command]
+pub async fn set_progress_interval(
+    services: State<'_, ServiceRegistry>,
+    interval_ms: u64,
+) -> Result<(), AppError> {
+    let jobs = services.inner().jobs.clone();
+    jobs.set_progress_interval(Duration::from_millis(interval_ms));
+    Ok(())
+}
+
+/// Queues a decode-only verification of an existing file, optionally
+/// limited to its first `limit_sec` seconds. Nothing is written, so this
+/// stays available in read-only mode.
+#[tauri::command]
+pub async fn start_verification(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut job_id: String,
+    path: String,
+    checksum: bool,
+    limit_sec: Option<f64>,
+) -> Result<(), AppError> {
+    Validator::new()
+        .job_id("jobId", &mut job_id)
+        .existing_path("path", &path)
+        .duration("limitSec", limit_sec)
+        .finish()?;
+    let jobs = services.inner().jobs.clone();
+    jobs.start_verification(app, job_id, path, checksum, limit_sec)
+}
+
+#[tauri::command]
+pub async fn set_verification_concurrency(
+    services: State<'_, ServiceRegistry>,
+    limit: usize,
+) -> Result<(), AppError> {
+    let jobs = services.inner().jobs.clone();
+    jobs.set_verification_concurrency(limit);
+    Ok(())
+}
+
+/// Called once at startup; returns the encoding used for progress and
+/// stderr events from then on.
+#[tauri::command]
+pub async fn negotiate_event_encoding(
+    services: State<'_, ServiceRegistry>,
+    preferred: Vec<EventEncoding>,
+) -> Result<EventEncoding, AppError> {
+    let jobs = services.inner().jobs.clone();
+    Ok(jobs.negotiate_event_encoding(&preferred))
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct JobFailureTelemetry {
     pub(crate) job_id: String,
@@ -67,6 +357,7 @@ pub(crate) struct JobFailureTelemetry {
     pub(crate) code: String,
     pub(crate) message: String,
     pub(crate) args: Vec<String>,
+    pub(crate) preset_id: Option<String>,
 }
 
 #[cfg(test)]
@@ -1,6 +1,11 @@
 use tauri::{AppHandle, State};
 
-use crate::{error::AppError, license::LicenseInfo, services::ServiceRegistry};
+use super::timeout::run_blocking;
+use crate::{
+    error::AppError,
+    license::{Deactivation, LicenseInfo, TrialStatus},
+    services::ServiceRegistry,
+};
 
 #[tauri::command]
 pub async fn verify_license_key(
@@ -17,8 +22,19 @@ pub async fn activate_license(
     services: State<'_, ServiceRegistry>,
     key: String,
 ) -> Result<LicenseInfo, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("activate a license")?;
     let licensing = services.inner().licensing.clone();
-    licensing.activate(&app, &key)
+    // Activation may wait on the license server
+    run_blocking(
+        services.inner(),
+        "activate_license",
+        "license_thread_join",
+        move || licensing.activate(&app, &key),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -35,6 +51,54 @@ pub async fn remove_license(
     app: AppHandle,
     services: State<'_, ServiceRegistry>,
 ) -> Result<(), AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("remove the license")?;
     let licensing = services.inner().licensing.clone();
     licensing.remove(&app)
 }
+
+/// Deactivates the license so its seat can move to another machine.
+/// `notify_server` defaults to releasing the seat with the license server.
+#[tauri::command]
+pub async fn deactivate_license(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    notify_server: Option<bool>,
+) -> Result<Deactivation, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("deactivate the license")?;
+    let licensing = services.inner().licensing.clone();
+    run_blocking(
+        services.inner(),
+        "deactivate_license",
+        "license_thread_join",
+        move || licensing.deactivate(&app, notify_server.unwrap_or(true)),
+    )
+    .await
+}
+
+/// The trial started on first run, with the days it has left.
+#[tauri::command]
+pub async fn trial_status(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<TrialStatus, AppError> {
+    let licensing = services.inner().licensing.clone();
+    licensing.trial_status(&app)
+}
+
+/// Whether the stored license grants a premium feature such as
+/// `watch-folders`.
+#[tauri::command]
+pub async fn has_entitlement(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    feature: String,
+) -> Result<bool, AppError> {
+    let licensing = services.inner().licensing.clone();
+    licensing.has_entitlement(&app, &feature)
+}
@@ -0,0 +1,54 @@
+use tauri::{AppHandle, State};
+
+use super::{
+    timeout::run_blocking,
+    validation::{validated, Validator},
+};
+use crate::{
+    error::AppError,
+    loudness::{
+        replaygain::{GainTagRequest, GainTagResult},
+        LoudnessMeasurement,
+    },
+    services::ServiceRegistry,
+};
+
+#[tauri::command]
+pub async fn analyze_loudness(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    path: String,
+) -> Result<LoudnessMeasurement, AppError> {
+    Validator::new().existing_path("path", &path).finish()?;
+    let loudness = services.inner().loudness.clone();
+    run_blocking(
+        services.inner(),
+        "analyze_loudness",
+        "loudness_thread_join",
+        move || loudness.analyze(&app, &path),
+    )
+    .await
+}
+
+/// Post-step for finished audio conversions: measures each output and
+/// writes ReplayGain 2.0 (or R128 for Opus) tags in place.
+#[tauri::command]
+pub async fn write_gain_tags(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: GainTagRequest,
+) -> Result<Vec<GainTagResult>, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("write loudness tags")?;
+    let request = validated("request", request)?;
+    let loudness = services.inner().loudness.clone();
+    run_blocking(
+        services.inner(),
+        "write_gain_tags",
+        "loudness_thread_join",
+        move || loudness.write_gain_tags(&app, request),
+    )
+    .await
+}
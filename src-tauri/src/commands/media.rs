@@ -1,8 +1,18 @@
 use tauri::{AppHandle, State};
 
+use super::{
+    timeout::run_blocking,
+    validation::{validated, Validator},
+};
 use crate::{
-    error::AppError, ffmpeg_capabilities::CapabilitySnapshot, ffmpeg_probe::ProbeResponse,
+    contact_sheet::{ContactSheet, ContactSheetRequest},
+    error::AppError,
+    ffmpeg_capabilities::{CapabilitySnapshot, EncoderOptions, PlannedPreset, PresetValidation},
+    ffmpeg_probe::ProbeResponse,
+    probe_batch::BatchProbeSummary,
+    probe_report::{ProbeReport, ReportFormat},
     services::ServiceRegistry,
+    waveform::{Waveform, WaveformRequest},
 };
 
 #[tauri::command]
@@ -11,9 +21,62 @@ pub async fn load_capabilities(
     services: State<'_, ServiceRegistry>,
 ) -> Result<CapabilitySnapshot, AppError> {
     let capability_service = services.inner().capabilities.clone();
-    tauri::async_runtime::spawn_blocking(move || capability_service.load(&app))
-        .await
-        .map_err(|err| AppError::new("capability_thread_join", err.to_string()))?
+    run_blocking(
+        services.inner(),
+        "load_capabilities",
+        "capability_thread_join",
+        move || capability_service.load(&app),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn refresh_capabilities(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<CapabilitySnapshot, AppError> {
+    let capability_service = services.inner().capabilities.clone();
+    run_blocking(
+        services.inner(),
+        "refresh_capabilities",
+        "capability_thread_join",
+        move || capability_service.refresh(&app),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_encoder_options(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut name: String,
+) -> Result<EncoderOptions, AppError> {
+    Validator::new().id("name", &mut name).finish()?;
+    let capability_service = services.inner().capabilities.clone();
+    run_blocking(
+        services.inner(),
+        "get_encoder_options",
+        "capability_thread_join",
+        move || capability_service.encoder_options(&app, &name),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn validate_preset(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    preset: PlannedPreset,
+) -> Result<PresetValidation, AppError> {
+    let preset = validated("preset", preset)?;
+    let capability_service = services.inner().capabilities.clone();
+    run_blocking(
+        services.inner(),
+        "validate_preset",
+        "capability_thread_join",
+        move || capability_service.validate_preset(&app, &preset),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -21,11 +84,123 @@ pub async fn probe_media(
     app: AppHandle,
     services: State<'_, ServiceRegistry>,
     path: String,
+    deep: Option<bool>,
+    keyframes: Option<bool>,
 ) -> Result<ProbeResponse, AppError> {
+    Validator::new().media_input("path", &path).finish()?;
+    let probe_service = services.inner().media_probe.clone();
+    run_blocking(
+        services.inner(),
+        "probe_media",
+        "probe_thread_join",
+        move || {
+            let mut response = match deep {
+                Some(true) => probe_service.deep_probe(&app, &path)?,
+                _ => probe_service.probe(&app, &path)?,
+            };
+            if keyframes == Some(true) && response.summary.vcodec.is_some() {
+                response.keyframes = Some(probe_service.keyframes(&app, &path)?);
+            }
+            Ok(response)
+        },
+    )
+    .await
+}
+
+/// Probes many files concurrently, emitting `probe://result` events in input
+/// order. Starting a batch cancels any batch still running.
+#[tauri::command]
+pub async fn probe_media_batch(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut batch_id: String,
+    paths: Vec<String>,
+    concurrency: Option<usize>,
+) -> Result<BatchProbeSummary, AppError> {
+    let mut validator = Validator::new();
+    validator
+        .id("batchId", &mut batch_id)
+        .items("paths", &paths);
+    for (index, path) in paths.iter().enumerate() {
+        validator.path(&format!("paths[{index}]"), path);
+    }
+    validator.finish()?;
+    let probe_service = services.inner().media_probe.clone();
+    run_blocking(
+        services.inner(),
+        "probe_media_batch",
+        "probe_thread_join",
+        move || Ok(probe_service.probe_batch(&app, &batch_id, &paths, concurrency)),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn cancel_probe_batch(
+    services: State<'_, ServiceRegistry>,
+    batch_id: String,
+) -> Result<bool, AppError> {
+    let probe_service = services.inner().media_probe.clone();
+    Ok(probe_service.cancel_probe_batch(&batch_id))
+}
+
+/// Renders a tiled sheet of frames from across the file.
+#[tauri::command]
+pub async fn generate_contact_sheet(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: ContactSheetRequest,
+) -> Result<ContactSheet, AppError> {
+    let request = validated("request", request)?;
+    let probe_service = services.inner().media_probe.clone();
+    run_blocking(
+        services.inner(),
+        "generate_contact_sheet",
+        "probe_thread_join",
+        move || probe_service.contact_sheet(&app, &request),
+    )
+    .await
+}
+
+/// Renders the waveform of an audio file to a PNG in the cache directory.
+#[tauri::command]
+pub async fn generate_waveform(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: WaveformRequest,
+) -> Result<Waveform, AppError> {
+    let request = validated("request", request)?;
+    let probe_service = services.inner().media_probe.clone();
+    run_blocking(
+        services.inner(),
+        "generate_waveform",
+        "probe_thread_join",
+        move || probe_service.waveform(&app, &request),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn export_probe_report(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    paths: Vec<String>,
+    format: ReportFormat,
+) -> Result<ProbeReport, AppError> {
+    let mut validator = Validator::new();
+    validator.items("paths", &paths);
+    for (index, path) in paths.iter().enumerate() {
+        validator.path(&format!("paths[{index}]"), path);
+    }
+    validator.finish()?;
     let probe_service = services.inner().media_probe.clone();
-    tauri::async_runtime::spawn_blocking(move || probe_service.probe(&app, &path))
-        .await
-        .map_err(|err| AppError::new("probe_thread_join", err.to_string()))?
+    run_blocking(
+        services.inner(),
+        "export_probe_report",
+        "probe_thread_join",
+        move || probe_service.export_report(&app, &paths, format),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -34,7 +209,11 @@ pub async fn expand_media_paths(
     paths: Vec<String>,
 ) -> Result<Vec<String>, AppError> {
     let path_service = services.inner().paths.clone();
-    tauri::async_runtime::spawn_blocking(move || path_service.expand_paths(paths))
-        .await
-        .map_err(|err| AppError::new("fs_thread_join", err.to_string()))?
+    run_blocking(
+        services.inner(),
+        "expand_media_paths",
+        "fs_thread_join",
+        move || path_service.expand_paths(paths),
+    )
+    .await
 }
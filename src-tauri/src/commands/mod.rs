@@ -1,10 +1,19 @@
 //! Tauri command handlers. Each function is a thin adapter that validates
 //! input, defers to the appropriate service, and handles threading concerns.
 
+pub mod app;
+pub mod binaries;
+pub mod catalog;
 pub mod dialogs;
 pub mod jobs;
 pub mod licensing;
+pub mod loudness;
 pub mod media;
+pub mod planning;
+pub mod watch_folders;
+
+mod timeout;
+mod validation;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,153 @@
+use tauri::{AppHandle, State};
+
+use super::{
+    timeout::run_blocking,
+    validation::{validated, Validator},
+};
+use crate::{
+    error::AppError,
+    planning::{
+        AlbumPlan, AlbumRequest, ContainerPlan, ContainerRequest, FilterGraphPlan,
+        FilterGraphRequest, PlanDiff, SizeEstimate, SizeEstimateRequest, StreamSelectionPreview,
+        StreamSelectionRules, TrimPlan, TrimRequest,
+    },
+    runner::triage::Remediation,
+    services::ServiceRegistry,
+};
+
+#[tauri::command]
+pub async fn build_video_filters(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: FilterGraphRequest,
+) -> Result<FilterGraphPlan, AppError> {
+    let planning = services.inner().planning.clone();
+    run_blocking(
+        services.inner(),
+        "build_video_filters",
+        "planning_thread_join",
+        move || planning.build_filtergraph(&app, request),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn diff_plans(
+    services: State<'_, ServiceRegistry>,
+    plan_a: Vec<String>,
+    plan_b: Vec<String>,
+) -> Result<PlanDiff, AppError> {
+    let planning = services.inner().planning.clone();
+    Ok(planning.diff_plans(&plan_a, &plan_b))
+}
+
+#[tauri::command]
+pub async fn resolve_output_container(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: ContainerRequest,
+) -> Result<ContainerPlan, AppError> {
+    let planning = services.inner().planning.clone();
+    run_blocking(
+        services.inner(),
+        "resolve_output_container",
+        "planning_thread_join",
+        move || planning.resolve_container(&app, request),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn plan_trim(
+    services: State<'_, ServiceRegistry>,
+    request: TrimRequest,
+) -> Result<TrimPlan, AppError> {
+    let planning = services.inner().planning.clone();
+    planning.plan_trim(&request)
+}
+
+#[tauri::command]
+pub async fn apply_remediation(
+    services: State<'_, ServiceRegistry>,
+    args: Vec<String>,
+    remediation: Remediation,
+) -> Result<Vec<String>, AppError> {
+    let planning = services.inner().planning.clone();
+    Ok(planning.apply_remediation(&args, &remediation))
+}
+
+#[tauri::command]
+pub async fn plan_album(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: AlbumRequest,
+) -> Result<AlbumPlan, AppError> {
+    let request = validated("request", request)?;
+    let planning = services.inner().planning.clone();
+    run_blocking(
+        services.inner(),
+        "plan_album",
+        "planning_thread_join",
+        move || planning.plan_album(&app, request),
+    )
+    .await
+}
+
+/// Writes the album's playlist and MP4 gapless metadata once its tracks
+/// have been encoded; returns warnings for tracks that were skipped.
+#[tauri::command]
+pub async fn finish_album(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: AlbumRequest,
+) -> Result<Vec<String>, AppError> {
+    services.inner().mode.ensure_writable("finish albums")?;
+    let request = validated("request", request)?;
+    let planning = services.inner().planning.clone();
+    run_blocking(
+        services.inner(),
+        "finish_album",
+        "planning_thread_join",
+        move || planning.finish_album(&app, request),
+    )
+    .await
+}
+
+/// Shows which streams the planned mapping would take from `path`, and why,
+/// without starting an encode.
+#[tauri::command]
+pub async fn preview_stream_selection(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    path: String,
+    rules: StreamSelectionRules,
+) -> Result<StreamSelectionPreview, AppError> {
+    Validator::new().existing_path("path", &path).finish()?;
+    let planning = services.inner().planning.clone();
+    run_blocking(
+        services.inner(),
+        "preview_stream_selection",
+        "planning_thread_join",
+        move || planning.preview_stream_selection(&app, &path, &rules),
+    )
+    .await
+}
+
+/// Predicts a job's output size before it starts, optionally by encoding a
+/// short sample with the job's arguments.
+#[tauri::command]
+pub async fn estimate_output_size(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    request: SizeEstimateRequest,
+) -> Result<SizeEstimate, AppError> {
+    let request = validated("request", request)?;
+    let planning = services.inner().planning.clone();
+    run_blocking(
+        services.inner(),
+        "estimate_output_size",
+        "planning_thread_join",
+        move || planning.estimate_output_size(&app, request),
+    )
+    .await
+}
@@ -1,9 +1,17 @@
-use super::jobs;
+use super::{app, jobs};
 use crate::{
+    app_mode::AppMode,
     error::AppError,
+    runner::{
+        battery::BatteryPolicy,
+        coordinator::{JobActionResult, JobOptions},
+        history::HistoryEntry,
+        retry::RetryPolicy,
+    },
     services::{JobServiceApi, ServiceRegistry},
 };
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{test::mock_app, Manager};
 
 #[derive(Default)]
@@ -11,6 +19,13 @@ struct MockJobService {
     start_calls: Mutex<Vec<String>>,
     cancel_calls: Mutex<Vec<String>>,
     concurrency: Mutex<Vec<usize>>,
+    retry_policies: Mutex<Vec<RetryPolicy>>,
+    hooks: Mutex<Vec<Vec<crate::runner::hooks::PostJobHook>>>,
+    stall_timeouts: Mutex<Vec<Option<Duration>>>,
+    progress_intervals: Mutex<Vec<Duration>>,
+    low_priority: Mutex<Vec<bool>>,
+    default_threads: Mutex<Vec<Option<u32>>>,
+    prevent_sleep: Mutex<Vec<bool>>,
 }
 
 impl JobServiceApi for MockJobService {
@@ -18,9 +33,9 @@ impl JobServiceApi for MockJobService {
         &self,
         _app: tauri::AppHandle,
         job_id: String,
-        _args: Vec<String>,
-        _output_path: String,
-        _exclusive: bool,
+        _args: Vec<std::ffi::OsString>,
+        _output_path: std::path::PathBuf,
+        _options: JobOptions,
     ) -> Result<(), AppError> {
         self.start_calls.lock().unwrap().push(job_id);
         Ok(())
@@ -31,9 +46,124 @@ impl JobServiceApi for MockJobService {
         Ok(true)
     }
 
+    fn cancel_all_jobs(&self) -> Vec<JobActionResult> {
+        self.bulk_result("cancel")
+    }
+
+    fn pause_all_jobs(&self) -> Vec<JobActionResult> {
+        self.bulk_result("pause")
+    }
+
+    fn resume_all_jobs(&self) -> Vec<JobActionResult> {
+        self.bulk_result("resume")
+    }
+
     fn set_max_concurrency(&self, limit: usize) {
         self.concurrency.lock().unwrap().push(limit);
     }
+
+    fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.retry_policies.lock().unwrap().push(policy);
+    }
+
+    fn load_post_job_hooks(&self, _app: tauri::AppHandle) {}
+
+    fn set_post_job_hooks(&self, hooks: Vec<crate::runner::hooks::PostJobHook>) {
+        self.hooks.lock().unwrap().push(hooks);
+    }
+
+    fn set_stall_timeout(&self, timeout: Option<Duration>) {
+        self.stall_timeouts.lock().unwrap().push(timeout);
+    }
+
+    fn set_progress_interval(&self, interval: Duration) {
+        self.progress_intervals.lock().unwrap().push(interval);
+    }
+
+    fn set_low_priority(&self, enabled: bool) {
+        self.low_priority.lock().unwrap().push(enabled);
+    }
+
+    fn set_default_threads(&self, threads: Option<u32>) {
+        self.default_threads.lock().unwrap().push(threads);
+    }
+
+    fn set_battery_policy(&self, _app: tauri::AppHandle, _policy: BatteryPolicy) {}
+
+    fn set_prevent_sleep(&self, enabled: bool) {
+        self.prevent_sleep.lock().unwrap().push(enabled);
+    }
+
+    fn restore_jobs(
+        &self,
+        _app: tauri::AppHandle,
+        _resume: bool,
+    ) -> Vec<crate::runner::coordinator::RestoredJob> {
+        Vec::new()
+    }
+
+    fn history(&self, _app: tauri::AppHandle) -> Vec<HistoryEntry> {
+        Vec::new()
+    }
+
+    fn clear_history(&self, _app: tauri::AppHandle) {}
+
+    fn job_log_path(
+        &self,
+        _app: tauri::AppHandle,
+        _job_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        Ok(None)
+    }
+
+    fn export_job_logs(
+        &self,
+        _app: tauri::AppHandle,
+        _job_id: Option<String>,
+        _destination: String,
+    ) -> Result<Vec<String>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn start_verification(
+        &self,
+        _app: tauri::AppHandle,
+        job_id: String,
+        _path: String,
+        _checksum: bool,
+        _limit_sec: Option<f64>,
+    ) -> Result<(), AppError> {
+        self.start_calls.lock().unwrap().push(job_id);
+        Ok(())
+    }
+
+    fn set_verification_concurrency(&self, limit: usize) {
+        self.concurrency.lock().unwrap().push(limit);
+    }
+
+    fn negotiate_event_encoding(
+        &self,
+        preferred: &[crate::runner::events::EventEncoding],
+    ) -> crate::runner::events::EventEncoding {
+        preferred.first().copied().unwrap_or_default()
+    }
+
+    fn active_job_count(&self) -> usize {
+        0
+    }
+
+    fn shutdown(&self) {}
+}
+
+impl MockJobService {
+    fn bulk_result(&self, action: &str) -> Vec<JobActionResult> {
+        self.cancel_calls.lock().unwrap().push(action.to_string());
+        vec![JobActionResult {
+            job_id: "3f2504e0-4f89-41d3-9a0c-0305e82c3301".into(),
+            success: true,
+            error: None,
+        }]
+    }
 }
 
 fn registry_with_job(mock: Arc<dyn JobServiceApi>) -> ServiceRegistry {
@@ -53,17 +183,98 @@ fn cancel_and_limit_commands_delegate() {
     let handle = app.handle();
     tauri::async_runtime::block_on(async {
         let state = handle.state::<ServiceRegistry>();
-        let cancelled = jobs::cancel_job(state.clone(), "abc".into())
-            .await
-            .expect("cancel command");
+        let cancelled = jobs::cancel_job(
+            state.clone(),
+            " 3F2504E0-4F89-41D3-9A0C-0305E82C3301".into(),
+        )
+        .await
+        .expect("cancel command");
         assert!(cancelled);
-        jobs::set_max_concurrency(state, 4)
+        let rejected = jobs::cancel_job(state.clone(), "abc".into())
+            .await
+            .unwrap_err();
+        assert_eq!(rejected.code, "invalid_request");
+        assert_eq!(rejected.message, "jobId: must be a UUID");
+        jobs::set_max_concurrency(state.clone(), 4)
             .await
             .expect("limit command");
+        jobs::set_verification_concurrency(state.clone(), 1)
+            .await
+            .expect("verification limit command");
+        jobs::set_retry_policy(state.clone(), RetryPolicy::default())
+            .await
+            .expect("retry policy command");
+        jobs::set_post_job_hooks(state.clone(), Vec::new())
+            .await
+            .expect("hooks command");
+        jobs::set_stall_timeout(state.clone(), Some(90_000))
+            .await
+            .expect("stall timeout command");
+        jobs::set_progress_interval(state.clone(), 500)
+            .await
+            .expect("progress interval command");
+        jobs::set_low_priority(state.clone(), true)
+            .await
+            .expect("priority command");
+        jobs::set_default_threads(state.clone(), Some(6))
+            .await
+            .expect("threads command");
+        assert!(jobs::set_default_threads(state.clone(), Some(0))
+            .await
+            .is_err());
+        jobs::set_prevent_sleep(state.clone(), false)
+            .await
+            .expect("prevent sleep command");
+        let rejected = jobs::set_retry_policy(
+            state,
+            RetryPolicy {
+                max_retries: 99,
+                ..RetryPolicy::default()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(rejected.code, "invalid_request");
+    });
+
+    assert_eq!(
+        *job_service.cancel_calls.lock().unwrap(),
+        vec!["3f2504e0-4f89-41d3-9a0c-0305e82c3301"]
+    );
+    assert_eq!(*job_service.concurrency.lock().unwrap(), vec![4, 1]);
+    assert_eq!(job_service.retry_policies.lock().unwrap().len(), 1);
+    assert_eq!(job_service.hooks.lock().unwrap().len(), 1);
+    assert_eq!(
+        *job_service.stall_timeouts.lock().unwrap(),
+        vec![Some(Duration::from_secs(90))]
+    );
+    assert_eq!(
+        *job_service.progress_intervals.lock().unwrap(),
+        vec![Duration::from_millis(500)]
+    );
+    assert_eq!(*job_service.low_priority.lock().unwrap(), vec![true]);
+    assert_eq!(*job_service.default_threads.lock().unwrap(), vec![Some(6)]);
+    assert_eq!(*job_service.prevent_sleep.lock().unwrap(), vec![false]);
+}
+
+#[test]
+fn bulk_job_commands_return_per_job_results() {
+    let job_service = Arc::new(MockJobService::default());
+    let app = mock_app();
+    app.manage(registry_with_job(job_service.clone()));
+    let handle = app.handle();
+    tauri::async_runtime::block_on(async {
+        let state = handle.state::<ServiceRegistry>();
+        let paused = jobs::pause_all_jobs(state.clone()).await.unwrap();
+        assert!(paused[0].success);
+        jobs::resume_all_jobs(state.clone()).await.unwrap();
+        jobs::cancel_all_jobs(state).await.unwrap();
     });
 
-    assert_eq!(job_service.cancel_calls.lock().unwrap().len(), 1);
-    assert_eq!(*job_service.concurrency.lock().unwrap(), vec![4]);
+    assert_eq!(
+        *job_service.cancel_calls.lock().unwrap(),
+        vec!["pause", "resume", "cancel"]
+    );
 }
 
 #[test]
@@ -78,6 +289,7 @@ fn start_job_emits_failure_events() {
         code: "job_already_running".into(),
         message: "Job is already running".into(),
         args: vec!["-i".into(), "input.mp4".into()],
+        preset_id: Some("video-to-mp4".into()),
     });
 
     let payloads = jobs::take_job_failures();
@@ -87,3 +299,26 @@ fn start_job_emits_failure_events() {
     assert_eq!(payload.code, "job_already_running");
     assert_eq!(payload.stage, "start");
 }
+
+#[test]
+fn app_mode_command_reports_read_only_registry() {
+    let registry = ServiceRegistry {
+        mode: AppMode { read_only: true },
+        ..ServiceRegistry::default()
+    };
+
+    let app = mock_app();
+    app.manage(registry);
+    let handle = app.handle();
+    let mode = tauri::async_runtime::block_on(async {
+        app::app_mode(handle.state::<ServiceRegistry>())
+            .await
+            .expect("mode command")
+    });
+
+    assert!(mode.read_only);
+    assert_eq!(
+        mode.ensure_writable("start jobs").unwrap_err().code,
+        "read_only_mode"
+    );
+}
@@ -0,0 +1,107 @@
+//! Timed execution of blocking service calls.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::AppError,
+    metrics::{CallOutcome, CommandMetrics},
+    services::ServiceRegistry,
+};
+
+pub(crate) const COMMAND_TIMEOUT: &str = "command_timeout";
+
+/// Runs `task` on the blocking pool under the command's timeout and records
+/// its duration. A timed-out task cannot be interrupted; it keeps its
+/// thread until it returns, but the caller gets an answer right away.
+pub(crate) async fn run_blocking<T, F>(
+    services: &ServiceRegistry,
+    command: &'static str,
+    join_code: &'static str,
+    task: F,
+) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    run_with_timeout(
+        &services.metrics,
+        services.timeouts.resolve(command),
+        command,
+        join_code,
+        task,
+    )
+    .await
+}
+
+async fn run_with_timeout<T, F>(
+    metrics: &CommandMetrics,
+    timeout: Option<Duration>,
+    command: &'static str,
+    join_code: &'static str,
+    task: F,
+) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    let started = Instant::now();
+    let handle = tauri::async_runtime::spawn_blocking(task);
+
+    let joined = match timeout {
+        Some(limit) => match tokio::time::timeout(limit, handle).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                metrics.record(command, started.elapsed(), CallOutcome::TimedOut);
+                return Err(AppError::new(
+                    COMMAND_TIMEOUT,
+                    format!("{command} did not finish within {} s", limit.as_secs_f64()),
+                ));
+            },
+        },
+        None => handle.await,
+    };
+
+    metrics.record(command, started.elapsed(), CallOutcome::Completed);
+    joined.map_err(|err| AppError::new(join_code, err.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_calls_fail_with_command_timeout() {
+        let metrics = CommandMetrics::default();
+        let err = tauri::async_runtime::block_on(run_with_timeout(
+            &metrics,
+            Some(Duration::from_millis(20)),
+            "probe_media",
+            "probe_thread_join",
+            || {
+                std::thread::sleep(Duration::from_millis(300));
+                Ok(())
+            },
+        ))
+        .unwrap_err();
+
+        assert_eq!(err.code, COMMAND_TIMEOUT);
+        assert_eq!(metrics.snapshot()["probe_media"].timeouts, 1);
+    }
+
+    #[test]
+    fn completed_calls_are_recorded() {
+        let metrics = CommandMetrics::default();
+        let value = tauri::async_runtime::block_on(run_with_timeout(
+            &metrics,
+            None,
+            "scan_catalog",
+            "catalog_thread_join",
+            || Ok(7),
+        ))
+        .unwrap();
+
+        assert_eq!(value, 7);
+        let stats = &metrics.snapshot()["scan_catalog"];
+        assert_eq!((stats.calls, stats.timeouts), (1, 0));
+    }
+}
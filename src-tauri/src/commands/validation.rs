@@ -0,0 +1,746 @@
+//! Input validation shared by the command handlers.
+//!
+//! Payloads are normalized and checked here before they reach a service, so
+//! every command rejects malformed input the same way: a single
+//! `invalid_request` error listing each offending field by its path in the
+//! payload (`spec.args[2]`, `request.targets[0].path`).
+
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::{
+    api::{JobSpec, NativePath},
+    contact_sheet::{ContactSheetRequest, MAX_GRID, MAX_TILE_WIDTH},
+    error::AppError,
+    ffmpeg_capabilities::PlannedPreset,
+    loudness::replaygain::GainTagRequest,
+    planning::{AlbumRequest, SizeEstimateRequest},
+    remote_input,
+    runner::{
+        hooks::{HookAction, PostJobHook, PLACEHOLDERS},
+        retry::RetryPolicy,
+    },
+    waveform::{is_hex_color, WaveformRequest, MAX_WAVEFORM_SIDE},
+};
+
+pub(crate) const INVALID_REQUEST: &str = "invalid_request";
+/// Longest accepted path, in `PATH_UNIT`s: macOS `PATH_MAX`.
+#[cfg(target_os = "macos")]
+pub(crate) const MAX_PATH_LEN: usize = 1024;
+/// Longest accepted path, in `PATH_UNIT`s: the extended-length (`\\?\`)
+/// limit, since `MAX_PATH` only binds the legacy APIs.
+#[cfg(windows)]
+pub(crate) const MAX_PATH_LEN: usize = 32_767;
+/// Longest accepted path, in `PATH_UNIT`s: Linux `PATH_MAX`.
+#[cfg(not(any(target_os = "macos", windows)))]
+pub(crate) const MAX_PATH_LEN: usize = 4096;
+/// What the platform measures paths in, and how many raw bytes make one.
+#[cfg(windows)]
+const PATH_UNIT: (&str, usize) = ("UTF-16 units", 2);
+#[cfg(not(windows))]
+const PATH_UNIT: (&str, usize) = ("bytes", 1);
+/// Longest accepted free-form value, such as a metadata value or a URL.
+pub(crate) const MAX_VALUE_LEN: usize = 1024;
+/// Longest accepted identifier or short label.
+pub(crate) const MAX_ID_LEN: usize = 128;
+/// Longest accepted single ffmpeg argument; filtergraphs can be long.
+pub(crate) const MAX_ARG_LEN: usize = 16 * 1024;
+/// Most items accepted in one list payload.
+pub(crate) const MAX_ITEMS: usize = 10_000;
+/// Most automatic retries per job.
+pub(crate) const MAX_RETRIES: u32 = 10;
+/// Longest accepted retry delay.
+pub(crate) const MAX_RETRY_DELAY_MS: u64 = 10 * 60 * 1000;
+/// Most metadata entries one job may carry.
+pub(crate) const MAX_METADATA_ENTRIES: usize = 32;
+/// Most encoder threads a job may ask for.
+pub(crate) const MAX_THREADS: u32 = 256;
+
+/// Collects field errors for one payload.
+#[derive(Debug, Default)]
+pub(crate) struct Validator {
+    problems: Vec<String>,
+}
+
+impl Validator {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn reject(&mut self, field: &str, reason: impl std::fmt::Display) {
+        self.problems.push(format!("{field}: {reason}"));
+    }
+
+    /// Trims and lowercases a job id, which must be a hyphenated UUID.
+    pub(crate) fn job_id(&mut self, field: &str, value: &mut String) -> &mut Self {
+        let normalized = value.trim().to_ascii_lowercase();
+        let is_hyphenated = normalized.len() == 36 && Uuid::parse_str(&normalized).is_ok();
+        if is_hyphenated {
+            *value = normalized;
+        } else {
+            self.reject(field, "must be a UUID");
+        }
+        self
+    }
+
+    /// Trims an identifier and requires it to be non-empty and short.
+    pub(crate) fn id(&mut self, field: &str, value: &mut String) -> &mut Self {
+        let trimmed = value.trim();
+        if trimmed.len() != value.len() {
+            *value = trimmed.to_string();
+        }
+        if value.is_empty() {
+            self.reject(field, "must not be empty");
+        } else {
+            self.bounded(field, value, MAX_ID_LEN);
+        }
+        self
+    }
+
+    pub(crate) fn bounded(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        if value.chars().count() > max {
+            self.reject(field, format_args!("must be at most {max} characters"));
+        }
+        self
+    }
+
+    /// A path the command may create: non-empty, bounded and NUL-free.
+    pub(crate) fn path(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.trim().is_empty() {
+            self.reject(field, "must not be empty");
+        } else if value.contains('\0') {
+            self.reject(field, "must not contain NUL bytes");
+        } else {
+            #[cfg(windows)]
+            let units = value.encode_utf16().count();
+            #[cfg(not(windows))]
+            let units = value.len();
+            self.path_length(field, units);
+        }
+        self
+    }
+
+    fn path_length(&mut self, field: &str, units: usize) {
+        if units > MAX_PATH_LEN {
+            let (unit, _) = PATH_UNIT;
+            self.reject(field, format_args!("must be at most {MAX_PATH_LEN} {unit}"));
+        }
+    }
+
+    /// A path sent as text or raw bytes; raw paths are held to the same
+    /// rules unit for unit.
+    pub(crate) fn native_path(&mut self, field: &str, value: &NativePath) -> &mut Self {
+        match value {
+            NativePath::Text(text) => self.path(field, text),
+            NativePath::Raw { bytes } => {
+                if bytes.is_empty() {
+                    self.reject(field, "must not be empty");
+                } else if bytes.contains(&0) {
+                    self.reject(field, "must not contain NUL bytes");
+                } else {
+                    let (_, unit_bytes) = PATH_UNIT;
+                    self.path_length(field, bytes.len().div_ceil(unit_bytes));
+                }
+                self
+            },
+        }
+    }
+
+    /// A media file that must already exist on disk, or an http(s), RTSP
+    /// or SRT URL, which is left to ffprobe and ffmpeg.
+    pub(crate) fn media_input(&mut self, field: &str, value: &str) -> &mut Self {
+        if remote_input::is_network_url(value) {
+            if value.contains(char::is_control) {
+                self.reject(field, "must not contain control characters");
+            }
+            self.bounded(field, value, MAX_ARG_LEN)
+        } else if remote_input::scheme(value).is_some() {
+            self.reject(field, "must be a file or an http(s), RTSP or SRT URL");
+            self
+        } else {
+            self.existing_path(field, value)
+        }
+    }
+
+    /// A path that must already exist on disk.
+    pub(crate) fn existing_path(&mut self, field: &str, value: &str) -> &mut Self {
+        let before = self.problems.len();
+        self.path(field, value);
+        if self.problems.len() == before && !Path::new(value).exists() {
+            self.reject(field, "does not exist");
+        }
+        self
+    }
+
+    /// An optional thread count, between 1 and `MAX_THREADS` when set.
+    pub(crate) fn threads(&mut self, field: &str, value: Option<u32>) -> &mut Self {
+        if value.is_some_and(|threads| !(1..=MAX_THREADS).contains(&threads)) {
+            self.reject(field, format_args!("must be between 1 and {MAX_THREADS}"));
+        }
+        self
+    }
+
+    /// An optional duration in seconds, positive and finite when set.
+    pub(crate) fn duration(&mut self, field: &str, value: Option<f64>) -> &mut Self {
+        if value.is_some_and(|seconds| !seconds.is_finite() || seconds <= 0.0) {
+            self.reject(field, "must be a positive number of seconds");
+        }
+        self
+    }
+
+    pub(crate) fn items<T>(&mut self, field: &str, items: &[T]) -> &mut Self {
+        if items.len() > MAX_ITEMS {
+            self.reject(field, format_args!("must have at most {MAX_ITEMS} items"));
+        }
+        self
+    }
+
+    pub(crate) fn finish(&mut self) -> Result<(), AppError> {
+        if self.problems.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::new(INVALID_REQUEST, self.problems.join("; ")))
+    }
+}
+
+/// Payload types that validate (and normalize) themselves under `field`.
+pub(crate) trait Validate {
+    fn validate(&mut self, validator: &mut Validator, field: &str);
+}
+
+/// Validates a whole payload, returning it normalized.
+pub(crate) fn validated<T: Validate>(field: &str, mut value: T) -> Result<T, AppError> {
+    let mut validator = Validator::new();
+    value.validate(&mut validator, field);
+    validator.finish()?;
+    Ok(value)
+}
+
+impl Validate for JobSpec {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator
+            .job_id(&format!("{field}.jobId"), &mut self.job_id)
+            .native_path(&format!("{field}.outputPath"), &self.output_path)
+            .items(&format!("{field}.args"), &self.args)
+            .items(&format!("{field}.dependsOn"), &self.depends_on)
+            .threads(&format!("{field}.threads"), self.threads);
+        for (index, parent) in self.depends_on.iter_mut().enumerate() {
+            validator.job_id(&format!("{field}.dependsOn[{index}]"), parent);
+        }
+        match &self.command {
+            None if self.args.is_empty() => {
+                validator.reject(&format!("{field}.args"), "must not be empty");
+            },
+            Some(_) if !self.args.is_empty() => {
+                validator.reject(
+                    &format!("{field}.command"),
+                    "must not be combined with args",
+                );
+            },
+            Some(command) => {
+                if command.inputs.is_empty() {
+                    validator.reject(&format!("{field}.command.inputs"), "must not be empty");
+                }
+                validator.items(&format!("{field}.command.inputs"), &command.inputs);
+                for (index, input) in command.inputs.iter().enumerate() {
+                    validator.native_path(
+                        &format!("{field}.command.inputs[{index}].path"),
+                        &input.path,
+                    );
+                }
+            },
+            None => {},
+        }
+        for (index, arg) in self.args.iter().enumerate() {
+            validator.bounded(&format!("{field}.args[{index}]"), arg, MAX_ARG_LEN);
+        }
+        if let Some(duration) = self.duration_sec {
+            if !duration.is_finite() || duration < 0.0 {
+                validator.reject(
+                    &format!("{field}.durationSec"),
+                    "must be a non-negative number",
+                );
+            }
+        }
+        if let Some(installation) = &mut self.ffmpeg_installation {
+            validator.id(&format!("{field}.ffmpegInstallation"), installation);
+        }
+        if let Some(plan) = &mut self.plan {
+            validator.id(&format!("{field}.plan.preset.id"), &mut plan.preset.id);
+        }
+        if self.metadata.len() > MAX_METADATA_ENTRIES {
+            validator.reject(
+                &format!("{field}.metadata"),
+                format_args!("must have at most {MAX_METADATA_ENTRIES} entries"),
+            );
+        }
+        for (key, value) in &self.metadata {
+            let entry = format!("{field}.metadata.{key}");
+            if key.trim().is_empty() {
+                validator.reject(&entry, "key must not be empty");
+            }
+            validator
+                .bounded(&entry, key, MAX_ID_LEN)
+                .bounded(&entry, value, MAX_VALUE_LEN);
+        }
+    }
+}
+
+impl Validate for GainTagRequest {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator.items(&format!("{field}.targets"), &self.targets);
+        for (index, target) in self.targets.iter().enumerate() {
+            validator.existing_path(&format!("{field}.targets[{index}].path"), &target.path);
+            if let Some(album) = &target.album {
+                validator.bounded(
+                    &format!("{field}.targets[{index}].album"),
+                    album,
+                    MAX_VALUE_LEN,
+                );
+            }
+        }
+    }
+}
+
+impl Validate for AlbumRequest {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator
+            .items(&format!("{field}.paths"), &self.paths)
+            .path(&format!("{field}.outputDirectory"), &self.output_directory)
+            .id(&format!("{field}.container"), &mut self.container);
+        for (index, path) in self.paths.iter().enumerate() {
+            validator.existing_path(&format!("{field}.paths[{index}]"), path);
+        }
+    }
+}
+
+impl Validate for SizeEstimateRequest {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator
+            .existing_path(&format!("{field}.path"), &self.path)
+            .items(&format!("{field}.args"), &self.args)
+            .id(&format!("{field}.plan.preset.id"), &mut self.plan.preset.id);
+        if self.sample && self.args.is_empty() {
+            validator.reject(&format!("{field}.args"), "must not be empty for a sample");
+        }
+        for (index, arg) in self.args.iter().enumerate() {
+            validator.bounded(&format!("{field}.args[{index}]"), arg, MAX_ARG_LEN);
+        }
+    }
+}
+
+impl Validate for PlannedPreset {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator
+            .bounded(&format!("{field}.container"), &self.container, MAX_ID_LEN)
+            .items(&format!("{field}.filters"), &self.filters);
+        for (name, encoder) in [
+            ("videoEncoder", &self.video_encoder),
+            ("audioEncoder", &self.audio_encoder),
+        ] {
+            if let Some(encoder) = encoder {
+                validator.bounded(&format!("{field}.{name}"), encoder, MAX_ID_LEN);
+            }
+        }
+        for (index, filter) in self.filters.iter().enumerate() {
+            validator.bounded(&format!("{field}.filters[{index}]"), filter, MAX_ID_LEN);
+        }
+    }
+}
+
+impl Validate for ContactSheetRequest {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator.existing_path(&format!("{field}.path"), &self.path);
+        for (name, value) in [("columns", self.columns), ("rows", self.rows)] {
+            if !(1..=MAX_GRID).contains(&value) {
+                validator.reject(
+                    &format!("{field}.{name}"),
+                    format_args!("must be between 1 and {MAX_GRID}"),
+                );
+            }
+        }
+        if !(16..=MAX_TILE_WIDTH).contains(&self.tile_width) {
+            validator.reject(
+                &format!("{field}.tileWidth"),
+                format_args!("must be between 16 and {MAX_TILE_WIDTH}"),
+            );
+        }
+    }
+}
+
+impl Validate for WaveformRequest {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator.existing_path(&format!("{field}.path"), &self.path);
+        for (name, value) in [("width", self.width), ("height", self.height)] {
+            if !(16..=MAX_WAVEFORM_SIDE).contains(&value) {
+                validator.reject(
+                    &format!("{field}.{name}"),
+                    format_args!("must be between 16 and {MAX_WAVEFORM_SIDE}"),
+                );
+            }
+        }
+        if !is_hex_color(&self.color) {
+            validator.reject(&format!("{field}.color"), "must be a #rrggbb color");
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        validator.items(field, self);
+        for (index, item) in self.iter_mut().enumerate() {
+            item.validate(validator, &format!("{field}[{index}]"));
+        }
+    }
+}
+
+impl Validate for PostJobHook {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        match &self.action {
+            HookAction::Command { program, args } => {
+                validator
+                    .path(&format!("{field}.program"), program)
+                    .items(&format!("{field}.args"), args);
+                if PLACEHOLDERS
+                    .iter()
+                    .any(|placeholder| program.contains(placeholder))
+                {
+                    validator.reject(
+                        &format!("{field}.program"),
+                        "must not contain placeholders; pass them as arguments",
+                    );
+                }
+                for (index, arg) in args.iter().enumerate() {
+                    validator.bounded(&format!("{field}.args[{index}]"), arg, MAX_ARG_LEN);
+                }
+            },
+            HookAction::Webhook { url } => {
+                validator.bounded(&format!("{field}.url"), url, MAX_VALUE_LEN);
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    validator.reject(&format!("{field}.url"), "must be an http(s) URL");
+                }
+            },
+        }
+    }
+}
+
+impl Validate for RetryPolicy {
+    fn validate(&mut self, validator: &mut Validator, field: &str) {
+        if self.max_retries > MAX_RETRIES {
+            validator.reject(
+                &format!("{field}.maxRetries"),
+                format_args!("must be at most {MAX_RETRIES}"),
+            );
+        }
+        if self.max_delay_ms > MAX_RETRY_DELAY_MS {
+            validator.reject(
+                &format!("{field}.maxDelayMs"),
+                format_args!("must be at most {MAX_RETRY_DELAY_MS}"),
+            );
+        }
+        if self.initial_delay_ms > self.max_delay_ms {
+            validator.reject(
+                &format!("{field}.initialDelayMs"),
+                "must not exceed maxDelayMs",
+            );
+        }
+        validator.items(&format!("{field}.exitCodes"), &self.exit_codes);
+        if self.exit_codes.contains(&0) {
+            validator.reject(&format!("{field}.exitCodes"), "must not contain 0");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{CommandInput, FfmpegCommand};
+    use tempfile::NamedTempFile;
+
+    const JOB_ID: &str = "3F2504E0-4F89-41D3-9A0C-0305E82C3301";
+
+    fn spec() -> JobSpec {
+        JobSpec {
+            job_id: format!("  {JOB_ID} "),
+            args: vec!["-i".into(), "/in.mov".into()],
+            command: None,
+            output_path: "/out/in.mp4".into(),
+            exclusive: false,
+            low_priority: None,
+            depends_on: Vec::new(),
+            overwrite: None,
+            duration_sec: None,
+            segmented: false,
+            threads: None,
+            trash_source: false,
+            preserve_timestamps: false,
+            metadata: Default::default(),
+            ffmpeg_installation: None,
+            plan: None,
+        }
+    }
+
+    #[test]
+    fn job_ids_are_normalized_uuids() {
+        let spec = validated("spec", spec()).unwrap();
+        assert_eq!(spec.job_id, JOB_ID.to_ascii_lowercase());
+
+        let mut simple = JOB_ID.replace('-', "");
+        assert!(Validator::new()
+            .job_id("jobId", &mut simple)
+            .finish()
+            .is_err());
+    }
+
+    #[test]
+    fn errors_list_every_field_path() {
+        let mut bad = spec();
+        bad.job_id = "job-1".into();
+        bad.output_path = NativePath::default();
+        bad.args.push("x".repeat(MAX_ARG_LEN + 1));
+
+        let err = validated("spec", bad).unwrap_err();
+        assert_eq!(err.code, INVALID_REQUEST);
+        assert_eq!(
+            err.message,
+            format!(
+                "spec.jobId: must be a UUID; spec.outputPath: must not be empty; \
+                 spec.args[2]: must be at most {MAX_ARG_LEN} characters"
+            )
+        );
+    }
+
+    #[test]
+    fn thread_counts_are_bounded() {
+        let mut limited = spec();
+        limited.threads = Some(4);
+        assert!(validated("spec", limited.clone()).is_ok());
+
+        limited.threads = Some(0);
+        let err = validated("spec", limited).unwrap_err();
+        assert_eq!(
+            err.message,
+            format!("spec.threads: must be between 1 and {MAX_THREADS}")
+        );
+    }
+
+    #[test]
+    fn metadata_entries_are_bounded() {
+        let mut tagged = spec();
+        tagged.metadata.insert("batch".into(), "b-7".into());
+        assert!(validated("spec", tagged.clone()).is_ok());
+
+        tagged
+            .metadata
+            .insert(" ".into(), "x".repeat(MAX_VALUE_LEN + 1));
+        let err = validated("spec", tagged).unwrap_err();
+        assert!(err.message.contains("key must not be empty"));
+        assert!(err.message.contains(&format!("at most {MAX_VALUE_LEN}")));
+    }
+
+    #[test]
+    fn commands_replace_raw_arguments() {
+        let mut structured = spec();
+        structured.command = Some(FfmpegCommand {
+            inputs: vec![CommandInput {
+                path: "/in & out.mov".into(),
+                options: Vec::new(),
+            }],
+            ..FfmpegCommand::default()
+        });
+        let err = validated("spec", structured.clone()).unwrap_err();
+        assert_eq!(err.message, "spec.command: must not be combined with args");
+
+        structured.args.clear();
+        assert!(validated("spec", structured.clone()).is_ok());
+
+        let mut raw = structured.clone();
+        raw.command.as_mut().unwrap().inputs[0].path = NativePath::Raw {
+            bytes: b"/in\0.mov".to_vec(),
+        };
+        let err = validated("spec", raw).unwrap_err();
+        assert_eq!(
+            err.message,
+            "spec.command.inputs[0].path: must not contain NUL bytes"
+        );
+
+        structured.command = Some(FfmpegCommand::default());
+        let err = validated("spec", structured).unwrap_err();
+        assert_eq!(err.message, "spec.command.inputs: must not be empty");
+    }
+
+    #[test]
+    fn paths_are_bounded_by_the_platform_limit() {
+        let longest = format!("/{}", "a".repeat(MAX_PATH_LEN - 1));
+        assert!(Validator::new().path("path", &longest).finish().is_ok());
+        #[cfg(not(windows))]
+        assert!(Validator::new()
+            .native_path(
+                "path",
+                &NativePath::Raw {
+                    bytes: longest.clone().into_bytes()
+                }
+            )
+            .finish()
+            .is_ok());
+
+        let err = Validator::new()
+            .path("path", &format!("{longest}a"))
+            .finish()
+            .unwrap_err();
+        assert!(err
+            .message
+            .starts_with(&format!("path: must be at most {MAX_PATH_LEN} ")));
+
+        #[cfg(target_os = "linux")]
+        assert!(Validator::new()
+            .path("path", &format!("/{}", "a".repeat(2000)))
+            .finish()
+            .is_ok());
+    }
+
+    #[test]
+    fn existing_paths_are_checked_on_disk() {
+        let file = NamedTempFile::new().unwrap();
+        let present = file.path().to_string_lossy().into_owned();
+
+        assert!(Validator::new()
+            .existing_path("path", &present)
+            .finish()
+            .is_ok());
+        let err = Validator::new()
+            .existing_path("path", "/definitely/not/here.mov")
+            .finish()
+            .unwrap_err();
+        assert_eq!(err.message, "path: does not exist");
+    }
+
+    #[test]
+    fn hooks_need_a_program_or_web_url() {
+        let hooks = vec![
+            PostJobHook {
+                on: Default::default(),
+                action: HookAction::Command {
+                    program: " ".into(),
+                    args: Vec::new(),
+                },
+            },
+            PostJobHook {
+                on: Default::default(),
+                action: HookAction::Webhook {
+                    url: "file:///etc/passwd".into(),
+                },
+            },
+        ];
+        let err = validated("hooks", hooks).unwrap_err();
+        assert_eq!(
+            err.message,
+            "hooks[0].program: must not be empty; hooks[1].url: must be an http(s) URL"
+        );
+
+        let templated = PostJobHook {
+            on: Default::default(),
+            action: HookAction::Command {
+                program: "{output}".into(),
+                args: Vec::new(),
+            },
+        };
+        let err = validated("hook", templated).unwrap_err();
+        assert_eq!(
+            err.message,
+            "hook.program: must not contain placeholders; pass them as arguments"
+        );
+    }
+
+    #[test]
+    fn retry_policies_are_bounded() {
+        assert!(validated("policy", RetryPolicy::default()).is_ok());
+
+        let err = validated(
+            "policy",
+            RetryPolicy {
+                max_retries: MAX_RETRIES + 1,
+                initial_delay_ms: 5_000,
+                max_delay_ms: 1_000,
+                exit_codes: vec![0],
+                retry_spawn_errors: true,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.message,
+            format!(
+                "policy.maxRetries: must be at most {MAX_RETRIES}; \
+                 policy.initialDelayMs: must not exceed maxDelayMs; \
+                 policy.exitCodes: must not contain 0"
+            )
+        );
+    }
+
+    #[test]
+    fn ids_are_trimmed_and_bounded() {
+        let mut id = "  rule-1 ".to_string();
+        Validator::new().id("id", &mut id).finish().unwrap();
+        assert_eq!(id, "rule-1");
+
+        let mut long = "r".repeat(MAX_ID_LEN + 1);
+        assert!(Validator::new().id("id", &mut long).finish().is_err());
+        assert!(Validator::new()
+            .id("id", &mut "  ".to_string())
+            .finish()
+            .is_err());
+    }
+
+    #[test]
+    fn media_inputs_may_be_network_urls() {
+        let file = NamedTempFile::new().unwrap();
+        let check = |value: &str| Validator::new().media_input("path", value).finish();
+
+        assert!(check(&file.path().to_string_lossy()).is_ok());
+        assert!(check("https://example.com/a.mp4").is_ok());
+        assert!(check("srt://10.0.0.2:9000?mode=caller").is_ok());
+        assert!(check("https://example.com/a\n.mp4").is_err());
+        assert!(check("ftp://example.com/a.mp4").is_err());
+        assert!(check("concat:a.ts|b.ts").is_err());
+        assert!(check("/does/not/exist.mov").is_err());
+    }
+
+    #[test]
+    fn durations_must_be_positive() {
+        assert!(Validator::new().duration("limitSec", None).finish().is_ok());
+        assert!(Validator::new()
+            .duration("limitSec", Some(30.0))
+            .finish()
+            .is_ok());
+        for invalid in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(Validator::new()
+                .duration("limitSec", Some(invalid))
+                .finish()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn contact_sheet_grids_are_bounded() {
+        let file = NamedTempFile::new().unwrap();
+        let request = ContactSheetRequest {
+            path: file.path().to_string_lossy().into_owned(),
+            columns: 4,
+            rows: 4,
+            tile_width: 320,
+        };
+        assert!(validated("request", request.clone()).is_ok());
+
+        let err = validated(
+            "request",
+            ContactSheetRequest {
+                columns: 0,
+                tile_width: 4096,
+                ..request
+            },
+        )
+        .unwrap_err();
+        assert!(err.message.contains("request.columns"));
+        assert!(err.message.contains("request.tileWidth"));
+    }
+}
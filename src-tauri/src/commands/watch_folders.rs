@@ -0,0 +1,78 @@
+use tauri::{AppHandle, State};
+
+use super::{timeout::run_blocking, validation::Validator};
+use crate::{
+    error::AppError,
+    services::ServiceRegistry,
+    watch_folders::{WatchCandidate, WatchFolder},
+};
+
+#[tauri::command]
+pub async fn list_watch_folders(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+) -> Result<Vec<WatchFolder>, AppError> {
+    let watch_folders = services.inner().watch_folders.clone();
+    watch_folders.list(&app)
+}
+
+#[tauri::command]
+pub async fn save_watch_folder(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    folder: WatchFolder,
+) -> Result<Vec<WatchFolder>, AppError> {
+    services
+        .inner()
+        .mode
+        .ensure_writable("save watch folders")?;
+    let watch_folders = services.inner().watch_folders.clone();
+    watch_folders.save(&app, folder)
+}
+
+#[tauri::command]
+pub async fn remove_watch_folder(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut id: String,
+) -> Result<Vec<WatchFolder>, AppError> {
+    Validator::new().id("id", &mut id).finish()?;
+    services
+        .inner()
+        .mode
+        .ensure_writable("remove watch folders")?;
+    let watch_folders = services.inner().watch_folders.clone();
+    watch_folders.remove(&app, &id)
+}
+
+#[tauri::command]
+pub async fn poll_watch_folder(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut id: String,
+) -> Result<Vec<WatchCandidate>, AppError> {
+    Validator::new().id("id", &mut id).finish()?;
+    let watch_folders = services.inner().watch_folders.clone();
+    run_blocking(
+        services.inner(),
+        "poll_watch_folder",
+        "watch_folder_thread_join",
+        move || watch_folders.poll(&app, &id),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn resolve_watch_output(
+    app: AppHandle,
+    services: State<'_, ServiceRegistry>,
+    mut id: String,
+    output_path: String,
+) -> Result<Option<String>, AppError> {
+    Validator::new()
+        .id("id", &mut id)
+        .path("outputPath", &output_path)
+        .finish()?;
+    let watch_folders = services.inner().watch_folders.clone();
+    watch_folders.resolve_output(&app, &id, &output_path)
+}
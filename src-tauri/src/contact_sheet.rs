@@ -0,0 +1,187 @@
+//! Tiled contact sheets for checking a source before a long conversion.
+//!
+//! One ffmpeg pass picks `columns × rows` frames spread over the duration
+//! with `select`, scales them and lays them out with `tile`. Only keyframes
+//! are decoded, which keeps long files quick at the cost of exact spacing.
+//! The sheet is returned as a JPEG data URL the webview can show directly.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+
+/// Largest grid side accepted.
+pub const MAX_GRID: u32 = 10;
+/// Widest tile accepted, in pixels.
+pub const MAX_TILE_WIDTH: u32 = 960;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheetRequest {
+    pub path: String,
+    #[serde(default = "default_grid")]
+    pub columns: u32,
+    #[serde(default = "default_grid")]
+    pub rows: u32,
+    /// Tile width in pixels; heights follow the aspect ratio.
+    #[serde(default = "default_tile_width")]
+    pub tile_width: u32,
+}
+
+fn default_grid() -> u32 {
+    4
+}
+
+fn default_tile_width() -> u32 {
+    320
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheet {
+    /// `data:image/jpeg;base64,…`
+    pub data_url: String,
+    pub columns: u32,
+    pub rows: u32,
+    /// Spacing between sampled frames; zero when the duration is unknown
+    /// and the first keyframes were used.
+    pub interval_sec: f64,
+}
+
+/// Spacing between sampled frames, so that they are centred in equal
+/// slices of the duration.
+pub fn sample_interval(duration_sec: f64, frames: u32) -> f64 {
+    if duration_sec.is_finite() && duration_sec > 0.0 && frames > 0 {
+        duration_sec / f64::from(frames)
+    } else {
+        0.0
+    }
+}
+
+/// The `select,scale,tile` chain: keeps a frame once `interval` has passed
+/// since the last one kept.
+pub fn sheet_filter(request: &ContactSheetRequest, interval_sec: f64) -> String {
+    format!(
+        "select='isnan(prev_selected_t)+gte(t-prev_selected_t\\,{interval_sec:.3})',\
+         scale={}:-2,tile={}x{}",
+        request.tile_width, request.columns, request.rows
+    )
+}
+
+/// Arguments for the sheet pass; the JPEG is written to stdout.
+pub fn sheet_args(request: &ContactSheetRequest, duration_sec: f64) -> Vec<String> {
+    let interval = sample_interval(duration_sec, request.columns * request.rows);
+    [
+        "-hide_banner",
+        "-nostdin",
+        "-loglevel",
+        "error",
+        "-skip_frame",
+        "nokey",
+        "-ss",
+        &format!("{:.3}", interval / 2.0),
+        "-i",
+        &request.path,
+        "-map",
+        "0:v:0",
+        "-vf",
+        &sheet_filter(request, interval),
+        "-fps_mode",
+        "vfr",
+        "-frames:v",
+        "1",
+        "-an",
+        "-sn",
+        "-dn",
+        "-c:v",
+        "mjpeg",
+        "-q:v",
+        "3",
+        "-f",
+        "image2pipe",
+        "-",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+/// Renders the sheet for a file of `duration_sec`.
+pub fn generate(
+    app: &AppHandle,
+    request: &ContactSheetRequest,
+    duration_sec: f64,
+) -> Result<ContactSheet, AppError> {
+    let args = sheet_args(request, duration_sec);
+    let mut last_err = None;
+
+    for candidate in crate::binary_resolver::resolve_ffmpeg_paths(app) {
+        match Command::new(&candidate).args(&args).output() {
+            Ok(output) if output.status.success() => {
+                if output.stdout.is_empty() {
+                    return Err(AppError::new(
+                        "contact_sheet_empty",
+                        format!("No frames could be read from {}", request.path),
+                    ));
+                }
+                return Ok(ContactSheet {
+                    data_url: format!("data:image/jpeg;base64,{}", BASE64.encode(&output.stdout)),
+                    columns: request.columns,
+                    rows: request.rows,
+                    interval_sec: sample_interval(duration_sec, request.columns * request.rows),
+                });
+            },
+            Ok(output) => {
+                last_err = Some(format!(
+                    "ffmpeg exited with status {} (stderr: {})",
+                    output
+                        .status
+                        .code()
+                        .map(|code| code.to_string())
+                        .unwrap_or_else(|| "unknown".into()),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            },
+            Err(error) => last_err = Some(error.to_string()),
+        }
+    }
+
+    Err(AppError::new(
+        "contact_sheet_exec",
+        last_err.unwrap_or_else(|| "Unable to execute ffmpeg".into()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ContactSheetRequest {
+        serde_json::from_str(r#"{ "path": "/media/clip.mov", "columns": 3, "rows": 2 }"#).unwrap()
+    }
+
+    #[test]
+    fn frames_are_centred_in_equal_slices() {
+        let request = request();
+        assert_eq!(request.tile_width, 320);
+        assert_eq!(sample_interval(60.0, 6), 10.0);
+        assert_eq!(sample_interval(0.0, 6), 0.0);
+
+        let args = sheet_args(&request, 60.0);
+        let seek = args.iter().position(|arg| arg == "-ss").unwrap();
+        assert_eq!(args[seek + 1], "5.000");
+        assert!(args.contains(&"/media/clip.mov".to_string()));
+    }
+
+    #[test]
+    fn filter_selects_scales_and_tiles() {
+        assert_eq!(
+            sheet_filter(&request(), 10.0),
+            "select='isnan(prev_selected_t)+gte(t-prev_selected_t\\,10.000)',\
+             scale=320:-2,tile=3x2"
+        );
+    }
+}
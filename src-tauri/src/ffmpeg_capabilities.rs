@@ -18,12 +18,22 @@
  * 4. Cache the results for future use
  */
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, ffi::OsString, fs, path::PathBuf, process::Command};
 #[cfg(target_os = "macos")]
 use std::{collections::HashSet, ffi::OsStr};
-use tauri::{AppHandle, Manager};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::UNIX_EPOCH,
+};
+use tauri::{AppHandle, Emitter, Manager};
 
-use crate::error::AppError;
+use crate::{
+    error::AppError,
+    planning::{resolve_container, ContainerRequest},
+};
 
 /**
  * Snapshot of FFmpeg capabilities detected on the system.
@@ -37,7 +47,14 @@ use crate::error::AppError;
  * * `video_encoders` - List of available video encoder names (e.g., "h264", "libx264")
  * * `audio_encoders` - List of available audio encoder names (e.g., "aac", "mp3")
  * * `formats` - List of supported container formats (e.g., "mp4", "mkv", "webm")
+ * * `muxers` - Format names FFmpeg can write, one per alias (e.g., "mp4", "matroska")
  * * `filters` - List of available filter names (e.g., "scale", "crop", "overlay")
+ * * `pixel_formats` - Pixel formats FFmpeg can convert to (e.g., "yuv420p", "p010le")
+ * * `video_decoders` / `audio_decoders` - Available decoder names (e.g., "libdav1d")
+ * * `decodable_codecs` - Codecs at least one decoder handles (e.g., "av1", "prores")
+ * * `hwaccels` - Hardware decoding methods (e.g., "videotoolbox")
+ * * `bitstream_filters` - Bitstream filters for stream copy (e.g., "aac_adtstoasc")
+ * * `ffmpeg` - The parsed `ffmpeg -version` output
  */
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -48,8 +65,128 @@ pub struct CapabilitySnapshot {
     pub audio_encoders: Vec<String>,
     /** Supported container format names */
     pub formats: Vec<String>,
+    /** Names of the formats FFmpeg can write; empty in snapshots cached before it was added */
+    #[serde(default)]
+    pub muxers: Vec<String>,
     /** Available filter names */
     pub filters: Vec<String>,
+    /** Pixel formats FFmpeg can convert frames to */
+    pub pixel_formats: Vec<String>,
+    /** Available video decoder names */
+    pub video_decoders: Vec<String>,
+    /** Available audio decoder names */
+    pub audio_decoders: Vec<String>,
+    /** Codec names (as `ffprobe` reports them) that some decoder handles */
+    pub decodable_codecs: Vec<String>,
+    /** Hardware acceleration methods available for decoding */
+    pub hwaccels: Vec<String>,
+    /** Bitstream filters, which rewrite packets during stream copy */
+    pub bitstream_filters: Vec<String>,
+    /** Version and build details of the binary */
+    pub ffmpeg: Option<FfmpegVersion>,
+}
+
+/**
+ * Version and build details parsed from `ffmpeg -version`.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegVersion {
+    /** Version as printed, e.g. "7.1", "n6.1.1" or "N-113447-g1a2b3c4" */
+    pub version: String,
+    /** Release number as `[major, minor, patch]`; `None` for builds from
+    git, which carry no release number */
+    pub release: Option<[u32; 3]>,
+    /** `./configure` flags the binary was built with */
+    pub configuration: Vec<String>,
+    /** Library versions keyed by library, e.g. "libavcodec" → "61.19.100" */
+    pub libraries: BTreeMap<String, String>,
+}
+
+/** Emitted with a `CapabilityDiff` when a refresh changes the snapshot. */
+pub const CAPABILITIES_CHANGED_EVENT: &str = "capabilities://changed";
+
+/**
+ * What changed between two capability snapshots, e.g. encoders gained or
+ * lost when FFmpeg was upgraded.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityDiff {
+    pub video_encoders: ListDiff,
+    pub audio_encoders: ListDiff,
+    pub formats: ListDiff,
+    pub filters: ListDiff,
+    pub pixel_formats: ListDiff,
+    pub decodable_codecs: ListDiff,
+    pub hwaccels: ListDiff,
+    pub bitstream_filters: ListDiff,
+    /** Version before the refresh, when it changed */
+    pub previous_version: Option<String>,
+    /** Version after the refresh, when it changed */
+    pub version: Option<String>,
+}
+
+/**
+ * Names added to and removed from one capability list.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/** Oldest FFmpeg release supported by default; `-fps_mode` needs 5.1. */
+const DEFAULT_MIN_FFMPEG_VERSION: [u32; 3] = [5, 1, 0];
+/** Overrides the minimum version, e.g. `6.0`. */
+const MIN_VERSION_ENV: &str = "HONEYMELON_MIN_FFMPEG_VERSION";
+
+/**
+ * Settings an encoder accepts, as reported by `ffmpeg -h encoder=NAME`.
+ *
+ * `presets`, `profiles` and `tunes` list the named values of the encoder's
+ * `preset`, `profile` and `tune` options. They are empty when the encoder
+ * has no such option or takes a free-form value, as libx264 does; `options`
+ * tells the two apart.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderOptions {
+    /** Encoder name, e.g. "libx264" */
+    pub name: String,
+    /** Long name from the help header */
+    pub description: Option<String>,
+    /** Named values of the `preset` option */
+    pub presets: Vec<String>,
+    /** Named values of the `profile` option */
+    pub profiles: Vec<String>,
+    /** Named values of the `tune` option */
+    pub tunes: Vec<String>,
+    /** Pixel formats the encoder accepts (video encoders only) */
+    pub pixel_formats: Vec<String>,
+    /** Sample formats the encoder accepts (audio encoders only) */
+    pub sample_formats: Vec<String>,
+    /** Every private option of the encoder, in help order */
+    pub options: Vec<EncoderOption>,
+}
+
+/**
+ * One private option of an encoder.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderOption {
+    /** Option name without the leading dash */
+    pub name: String,
+    /** Value type as printed by FFmpeg ("int", "string", "boolean", ...) */
+    pub value_type: String,
+    /** Help text, without the trailing default */
+    pub description: String,
+    /** Default value, when FFmpeg reports one */
+    pub default: Option<String>,
+    /** Named values the option accepts */
+    pub values: Vec<String>,
 }
 
 /**
@@ -73,36 +210,188 @@ pub struct CapabilitySnapshot {
  *
  * The cache is stored in the application's cache directory as JSON. This approach:
  * - Reduces startup time by avoiding repeated FFmpeg probing
- * - Provides resilience against temporary FFmpeg unavailability
  * - Ensures consistent capability reporting across application sessions
+ *
+ * The cache records the path, size and modification time of the binary it
+ * describes, and is only used while the resolved binary still matches, so
+ * swapping in a different FFmpeg is picked up on the next load.
  */
 pub fn load_capabilities(app: &AppHandle) -> Result<CapabilitySnapshot, AppError> {
+    let binary = current_binary(app);
+
     // Try to load from cache first
     if let Some(cache_path) = cache_path(app) {
         if let Ok(contents) = fs::read_to_string(&cache_path) {
-            if let Ok(mut snapshot) = serde_json::from_str::<CapabilitySnapshot>(&contents) {
-                snapshot.video_encoders = validate_video_encoders(app, snapshot.video_encoders);
-                return Ok(snapshot);
+            if let Ok(cached) = serde_json::from_str::<CapabilityCache>(&contents) {
+                if binary.as_ref() == Some(&cached.binary) {
+                    let mut snapshot = cached.snapshot;
+                    check_minimum_version(&snapshot, minimum_version())?;
+                    snapshot.video_encoders = validate_video_encoders(app, snapshot.video_encoders);
+                    return Ok(snapshot);
+                }
             }
         }
     }
 
-    // Cache miss or invalid, perform fresh detection
-    let snapshot = refresh_capabilities(app)?;
+    // Cache miss, stale or invalid, perform fresh detection
+    refresh_with(app, binary)
+}
 
-    // Cache the results for future use
-    if let Some(cache_path) = cache_path(app) {
+/**
+ * Detects capabilities afresh, ignoring and then replacing the cache.
+ *
+ * Backs the `refresh_capabilities` command, for when the user has changed
+ * their FFmpeg in a way the binary's size and modification time don't show.
+ * The resolver's cached candidates are dropped first for the same reason.
+ * Like detection after a cache miss, it emits `CAPABILITIES_CHANGED_EVENT`
+ * when the result differs from the snapshot it replaces.
+ */
+pub fn refresh_capabilities(app: &AppHandle) -> Result<CapabilitySnapshot, AppError> {
+    crate::binary_resolver::invalidate_resolution_cache();
+    refresh_with(app, current_binary(app))
+}
+
+fn refresh_with(
+    app: &AppHandle,
+    binary: Option<BinaryFingerprint>,
+) -> Result<CapabilitySnapshot, AppError> {
+    let snapshot = detect_capabilities(app)?;
+    check_minimum_version(&snapshot, minimum_version())?;
+
+    // Cache the results for future use; without a binary to key them on
+    // they couldn't be validated later
+    if let (Some(cache_path), Some(binary)) = (cache_path(app), binary) {
+        let previous = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<CapabilityCache>(&contents).ok());
         if let Some(parent) = cache_path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        if let Ok(serialized) = serde_json::to_string(&snapshot) {
+        let cache = CapabilityCache {
+            binary,
+            snapshot: snapshot.clone(),
+        };
+        if let Ok(serialized) = serde_json::to_string(&cache) {
             let _ = fs::write(cache_path, serialized);
         }
+
+        // Only a replaced snapshot is a change; the first detection isn't
+        if let Some(diff) = previous.and_then(|cached| diff_snapshots(&cached.snapshot, &snapshot))
+        {
+            let _ = app.emit(CAPABILITIES_CHANGED_EVENT, &diff);
+        }
     }
 
     Ok(snapshot)
 }
 
+/**
+ * Compares two snapshots, returning `None` when nothing changed.
+ */
+fn diff_snapshots(
+    previous: &CapabilitySnapshot,
+    current: &CapabilitySnapshot,
+) -> Option<CapabilityDiff> {
+    let list = |before: &[String], after: &[String]| {
+        let before: BTreeSet<&String> = before.iter().collect();
+        let after: BTreeSet<&String> = after.iter().collect();
+        ListDiff {
+            added: after
+                .difference(&before)
+                .map(|name| name.to_string())
+                .collect(),
+            removed: before
+                .difference(&after)
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    };
+    let version = |snapshot: &CapabilitySnapshot| {
+        snapshot
+            .ffmpeg
+            .as_ref()
+            .map(|ffmpeg| ffmpeg.version.clone())
+    };
+
+    let mut diff = CapabilityDiff {
+        video_encoders: list(&previous.video_encoders, &current.video_encoders),
+        audio_encoders: list(&previous.audio_encoders, &current.audio_encoders),
+        formats: list(&previous.formats, &current.formats),
+        filters: list(&previous.filters, &current.filters),
+        pixel_formats: list(&previous.pixel_formats, &current.pixel_formats),
+        decodable_codecs: list(&previous.decodable_codecs, &current.decodable_codecs),
+        hwaccels: list(&previous.hwaccels, &current.hwaccels),
+        bitstream_filters: list(&previous.bitstream_filters, &current.bitstream_filters),
+        ..CapabilityDiff::default()
+    };
+    if version(previous) != version(current) {
+        diff.previous_version = version(previous);
+        diff.version = version(current);
+    }
+
+    (diff != CapabilityDiff::default()).then_some(diff)
+}
+
+/**
+ * On-disk capability cache: the snapshot and the binary it was taken from.
+ */
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CapabilityCache {
+    binary: BinaryFingerprint,
+    snapshot: CapabilitySnapshot,
+}
+
+/**
+ * Identifies an FFmpeg binary well enough to notice it being replaced.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinaryFingerprint {
+    path: PathBuf,
+    size: u64,
+    /** Modification time in nanoseconds since the Unix epoch */
+    modified_ns: u64,
+}
+
+/**
+ * Fingerprints the binary capability detection would run: the first
+ * candidate, looked up on `PATH` when it is a bare name.
+ */
+fn current_binary(app: &AppHandle) -> Option<BinaryFingerprint> {
+    let candidate = PathBuf::from(candidate_ffmpeg_paths(app).into_iter().next()?);
+    let path = if candidate.components().count() > 1 {
+        candidate
+    } else {
+        find_on_path(&candidate)?
+    };
+    fingerprint(path)
+}
+
+fn find_on_path(name: &Path) -> Option<PathBuf> {
+    let names = [name.to_path_buf(), name.with_extension("exe")];
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+fn fingerprint(path: PathBuf) -> Option<BinaryFingerprint> {
+    // Follow symlinks, so a package manager relinking `ffmpeg` to a new
+    // version counts as a change.
+    let metadata = fs::metadata(&path).ok()?;
+    let modified_ns = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos() as u64;
+    Some(BinaryFingerprint {
+        path: fs::canonicalize(&path).unwrap_or(path),
+        size: metadata.len(),
+        modified_ns,
+    })
+}
+
 /**
  * Generates the cache file path for capability storage.
  *
@@ -139,30 +428,452 @@ fn cache_path(app: &AppHandle) -> Option<PathBuf> {
  *
  * # FFmpeg Commands Used
  *
- * 1. `ffmpeg -encoders` - Lists all available encoders with their types
- * 2. `ffmpeg -formats` - Lists all supported container formats
- * 3. `ffmpeg -filters` - Lists all available filters
+ * 1. `ffmpeg -version` - Version and build configuration
+ * 2. `ffmpeg -encoders` - Lists all available encoders with their types
+ * 3. `ffmpeg -formats` - Lists all supported container formats
+ * 4. `ffmpeg -filters` - Lists all available filters
+ * 5. `ffmpeg -pix_fmts` - Lists all pixel formats with their conversion support
+ * 6. `ffmpeg -decoders` - Lists all available decoders and the codecs they handle
+ * 7. `ffmpeg -hwaccels` - Lists hardware acceleration methods
+ * 8. `ffmpeg -bsfs` - Lists bitstream filters
  *
  * Each command's output is parsed to extract relevant capability information.
  */
-fn refresh_capabilities(app: &AppHandle) -> Result<CapabilitySnapshot, AppError> {
+fn detect_capabilities(app: &AppHandle) -> Result<CapabilitySnapshot, AppError> {
+    let version_output = run_ffmpeg(app, &["-version"])?;
     let encoders_output = run_ffmpeg(app, &["-hide_banner", "-encoders"])?;
     let formats_output = run_ffmpeg(app, &["-hide_banner", "-formats"])?;
     let filters_output = run_ffmpeg(app, &["-hide_banner", "-filters"])?;
+    let pix_fmts_output = run_ffmpeg(app, &["-hide_banner", "-pix_fmts"])?;
+    let decoders_output = run_ffmpeg(app, &["-hide_banner", "-decoders"])?;
+    let hwaccels_output = run_ffmpeg(app, &["-hide_banner", "-hwaccels"])?;
+    let bsfs_output = run_ffmpeg(app, &["-hide_banner", "-bsfs"])?;
 
     let (video_encoders_raw, audio_encoders) = parse_encoders(&encoders_output);
     let video_encoders = validate_video_encoders(app, video_encoders_raw);
     let formats = parse_formats(&formats_output);
+    let muxers = parse_muxers(&formats_output);
     let filters = parse_filters(&filters_output);
+    let decoders = parse_decoders(&decoders_output);
 
     Ok(CapabilitySnapshot {
         video_encoders,
         audio_encoders,
         formats,
+        muxers,
         filters,
+        pixel_formats: parse_pixel_formats(&pix_fmts_output),
+        video_decoders: decoders.video,
+        audio_decoders: decoders.audio,
+        decodable_codecs: decoders.codecs,
+        hwaccels: parse_name_list(&hwaccels_output),
+        bitstream_filters: parse_name_list(&bsfs_output),
+        ffmpeg: parse_version(&version_output),
     })
 }
 
+/**
+ * The minimum FFmpeg release, from `HONEYMELON_MIN_FFMPEG_VERSION` when it
+ * holds a version number, else `DEFAULT_MIN_FFMPEG_VERSION`.
+ */
+fn minimum_version() -> [u32; 3] {
+    std::env::var(MIN_VERSION_ENV)
+        .ok()
+        .and_then(|value| parse_release(value.trim()))
+        .unwrap_or(DEFAULT_MIN_FFMPEG_VERSION)
+}
+
+/**
+ * Rejects binaries older than `minimum`.
+ *
+ * Builds from git have no release number and are let through, as are
+ * snapshots whose version couldn't be read; they are usually newer than
+ * any release.
+ */
+fn check_minimum_version(snapshot: &CapabilitySnapshot, minimum: [u32; 3]) -> Result<(), AppError> {
+    let Some(ffmpeg) = &snapshot.ffmpeg else {
+        return Ok(());
+    };
+    match ffmpeg.release {
+        Some(release) if release < minimum => Err(AppError::new(
+            "capability_version_unsupported",
+            format!(
+                "FFmpeg {} is older than the minimum supported version {}; install FFmpeg {} or newer.",
+                ffmpeg.version,
+                format_release(minimum),
+                format_release(minimum)
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn format_release([major, minor, patch]: [u32; 3]) -> String {
+    if patch == 0 {
+        format!("{major}.{minor}")
+    } else {
+        format!("{major}.{minor}.{patch}")
+    }
+}
+
+/**
+ * Reads a release number such as "7.1", "n6.1.1" or "6.0-tessus"; missing
+ * minor and patch numbers count as zero.
+ */
+fn parse_release(version: &str) -> Option<[u32; 3]> {
+    let digits = version.strip_prefix('n').unwrap_or(version);
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(digits.len());
+    let mut parts = digits[..end]
+        .split('.')
+        .map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some([major, minor, patch])
+}
+
+/**
+ * Parses `ffmpeg -version` output.
+ *
+ * # Example Output Parsing
+ *
+ * ```text
+ * ffmpeg version 7.1 Copyright (c) 2000-2024 the FFmpeg developers
+ * built with Apple clang version 16.0.0 (clang-1600.0.26.4)
+ * configuration: --prefix=/opt/homebrew --enable-gpl --enable-libx264
+ * libavutil      59. 39.100 / 59. 39.100
+ * ```
+ *
+ * Results in version "7.1", release `[7, 1, 0]`, the three configuration
+ * flags and `libavutil` at "59.39.100". Returns `None` without the
+ * `ffmpeg version` line.
+ */
+fn parse_version(output: &str) -> Option<FfmpegVersion> {
+    let mut lines = output.lines();
+    let version = lines
+        .by_ref()
+        .find_map(|line| line.trim().strip_prefix("ffmpeg version "))?
+        .split_whitespace()
+        .next()?
+        .to_string();
+    let mut parsed = FfmpegVersion {
+        release: parse_release(&version),
+        version,
+        ..Default::default()
+    };
+
+    for line in lines {
+        let line = line.trim();
+        if let Some(flags) = line.strip_prefix("configuration:") {
+            parsed.configuration = flags.split_whitespace().map(str::to_string).collect();
+        } else if let Some(rest) = line.strip_prefix("lib") {
+            // "libavutil      59. 39.100 / 59. 39.100": the build-time version
+            let Some((name, versions)) = rest.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let built = versions.split('/').next().unwrap_or_default();
+            let number: String = built.chars().filter(|c| !c.is_whitespace()).collect();
+            if !number.is_empty() {
+                parsed.libraries.insert(format!("lib{name}"), number);
+            }
+        }
+    }
+
+    Some(parsed)
+}
+
+/**
+ * Lists the presets, profiles, tunes, pixel formats and private options of
+ * one encoder.
+ *
+ * # Errors
+ *
+ * Returns `capability_unknown_encoder` when the name is malformed or FFmpeg
+ * doesn't know the encoder; FFmpeg exits successfully in that case, so the
+ * missing help header is what gives it away.
+ */
+pub fn encoder_options(app: &AppHandle, name: &str) -> Result<EncoderOptions, AppError> {
+    let unknown = || {
+        AppError::new(
+            "capability_unknown_encoder",
+            format!("FFmpeg has no encoder named {name}"),
+        )
+    };
+    let well_formed = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'));
+    if !well_formed {
+        return Err(unknown());
+    }
+    let output = run_ffmpeg(app, &["-hide_banner", "-h", &format!("encoder={name}")])?;
+    parse_encoder_options(name, &output).ok_or_else(unknown)
+}
+
+/**
+ * Parses the output of `ffmpeg -h encoder=NAME`.
+ *
+ * # Example Output Parsing
+ *
+ * ```text
+ * Encoder hevc_videotoolbox [VideoToolbox H.265 Encoder]:
+ *     Supported pixel formats: videotoolbox_vld nv12 yuv420p p010le
+ * hevc_videotoolbox AVOptions:
+ *   -profile           <int>        E..V....... Profile (from -99 to 4) (default -99)
+ *      main            1            E..V....... Main Profile
+ *      main10          2            E..V....... Main10 Profile
+ * ```
+ *
+ * Results in one `profile` option with the values `main` and `main10`.
+ * Returns `None` without the `Encoder NAME` header.
+ */
+fn parse_encoder_options(name: &str, output: &str) -> Option<EncoderOptions> {
+    let mut lines = output.lines();
+    let header = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix("Encoder "))?
+        .strip_prefix(name)?;
+    let mut encoder = EncoderOptions {
+        name: name.to_string(),
+        description: header
+            .trim()
+            .strip_prefix('[')
+            .and_then(|rest| rest.trim_end_matches(':').strip_suffix(']'))
+            .map(str::to_string),
+        ..Default::default()
+    };
+
+    for line in lines {
+        let trimmed = line.trim();
+        if let Some(formats) = trimmed.strip_prefix("Supported pixel formats:") {
+            encoder.pixel_formats = formats.split_whitespace().map(str::to_string).collect();
+        } else if let Some(formats) = trimmed.strip_prefix("Supported sample formats:") {
+            encoder.sample_formats = formats.split_whitespace().map(str::to_string).collect();
+        } else if let Some(option) = trimmed.strip_prefix('-') {
+            let mut fields = option.split_whitespace();
+            let (Some(option_name), Some(value_type)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            // Skip the flags column (E..V.......) to reach the help text.
+            let help = fields.skip(1).collect::<Vec<_>>().join(" ");
+            let (description, default) = match help.rsplit_once(" (default ") {
+                Some((text, default)) if help.ends_with(')') => (
+                    text.to_string(),
+                    Some(default.trim_end_matches(')').trim_matches('"').to_string()),
+                ),
+                _ => (help, None),
+            };
+            encoder.options.push(EncoderOption {
+                name: option_name.to_string(),
+                value_type: value_type
+                    .trim_matches(|c| c == '<' || c == '>')
+                    .to_string(),
+                description,
+                default,
+                values: Vec::new(),
+            });
+        } else if line.starts_with("     ") && !trimmed.is_empty() {
+            // Named values are indented further than the option they belong to.
+            if let (Some(option), Some(value)) = (
+                encoder.options.last_mut(),
+                trimmed.split_whitespace().next(),
+            ) {
+                option.values.push(value.to_string());
+            }
+        }
+    }
+
+    let values = |wanted: &str| {
+        encoder
+            .options
+            .iter()
+            .find(|option| option.name == wanted)
+            .map(|option| option.values.clone())
+            .unwrap_or_default()
+    };
+    encoder.presets = values("preset");
+    encoder.profiles = values("profile");
+    encoder.tunes = values("tune");
+    Some(encoder)
+}
+
+/**
+ * The parts of a planned preset that depend on the FFmpeg build.
+ *
+ * Encoders are FFmpeg encoder names as the plan passes them to `-c:v` and
+ * `-c:a`; `None` or "copy" means the stream isn't encoded.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedPreset {
+    /** Output container, e.g. "mp4" or "mkv" */
+    pub container: String,
+    /** Video encoder, e.g. "libx265" */
+    #[serde(default)]
+    pub video_encoder: Option<String>,
+    /** Audio encoder, e.g. "libopus" */
+    #[serde(default)]
+    pub audio_encoder: Option<String>,
+    /** Names of the filters the plan uses, e.g. "zscale" */
+    #[serde(default)]
+    pub filters: Vec<String>,
+}
+
+/**
+ * One part of a planned preset this FFmpeg build can't provide.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetIssue {
+    /** Field at fault: "container", "videoEncoder", "audioEncoder" or "filters" */
+    pub field: String,
+    /** The unavailable container, encoder or filter */
+    pub value: String,
+    pub message: String,
+    /** An available substitute, when there is one */
+    pub suggestion: Option<String>,
+}
+
+/**
+ * Outcome of checking a planned preset against a capability snapshot.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetValidation {
+    /** True when the preset runs as planned */
+    pub valid: bool,
+    pub issues: Vec<PresetIssue>,
+}
+
+/** Encoders producing the same codec, in order of preference (hardware first). */
+const ENCODER_FAMILIES: &[&[&str]] = &[
+    &["h264_videotoolbox", "libx264", "h264_nvenc", "h264_qsv"],
+    &["hevc_videotoolbox", "libx265", "hevc_nvenc", "hevc_qsv"],
+    &[
+        "libsvtav1",
+        "libaom-av1",
+        "librav1e",
+        "av1_nvenc",
+        "av1_qsv",
+    ],
+    &["libvpx-vp9", "vp9_qsv"],
+    &["prores_videotoolbox", "prores_ks", "prores"],
+    &["aac_at", "aac", "libfdk_aac"],
+    &["libopus", "opus"],
+];
+
+/** Filters and the filters that can stand in for them, in order of preference. */
+const FILTER_SUBSTITUTES: &[(&str, &[&str])] = &[
+    ("zscale", &["scale"]),
+    ("scale_vt", &["scale"]),
+    ("libplacebo", &["zscale", "scale"]),
+    ("yadif_videotoolbox", &["bwdif", "yadif"]),
+    ("bwdif", &["yadif"]),
+];
+
+/**
+ * Checks that the snapshot provides the container, encoders and filters of
+ * a planned preset, suggesting a substitute for each one it lacks.
+ *
+ * Containers fall back the way `resolve_container` does at planning time;
+ * encoders are replaced by the preferred available encoder for the same
+ * codec, so `libx265` on a build without it suggests `hevc_videotoolbox`.
+ */
+pub fn validate_preset(snapshot: &CapabilitySnapshot, preset: &PlannedPreset) -> PresetValidation {
+    let mut issues = Vec::new();
+
+    let request = ContainerRequest {
+        container: preset.container.clone(),
+        fallbacks: Vec::new(),
+        output_path: None,
+    };
+    match resolve_container(&request, &snapshot.muxers) {
+        Ok(plan) if plan.fell_back => issues.push(PresetIssue {
+            field: "container".to_string(),
+            value: preset.container.clone(),
+            message: plan.warnings.join(" "),
+            suggestion: Some(plan.container),
+        }),
+        Ok(_) => {},
+        Err(error) => issues.push(PresetIssue {
+            field: "container".to_string(),
+            value: preset.container.clone(),
+            message: error.message,
+            suggestion: None,
+        }),
+    }
+
+    let encoders = [
+        (
+            "videoEncoder",
+            &preset.video_encoder,
+            &snapshot.video_encoders,
+        ),
+        (
+            "audioEncoder",
+            &preset.audio_encoder,
+            &snapshot.audio_encoders,
+        ),
+    ];
+    for (field, encoder, available) in encoders {
+        let Some(encoder) = encoder.as_deref().filter(|name| *name != "copy") else {
+            continue;
+        };
+        if contains(available, encoder) {
+            continue;
+        }
+        let suggestion = ENCODER_FAMILIES
+            .iter()
+            .find(|family| family.contains(&encoder))
+            .and_then(|family| first_available(family, available));
+        issues.push(unavailable(field, "encoder", encoder, suggestion));
+    }
+
+    for filter in &preset.filters {
+        if contains(&snapshot.filters, filter) {
+            continue;
+        }
+        let suggestion = FILTER_SUBSTITUTES
+            .iter()
+            .find(|(name, _)| name == filter)
+            .and_then(|(_, substitutes)| first_available(substitutes, &snapshot.filters));
+        issues.push(unavailable("filters", "filter", filter, suggestion));
+    }
+
+    PresetValidation {
+        valid: issues.is_empty(),
+        issues,
+    }
+}
+
+fn contains(available: &[String], name: &str) -> bool {
+    available.iter().any(|entry| entry == name)
+}
+
+fn first_available(candidates: &[&str], available: &[String]) -> Option<String> {
+    candidates
+        .iter()
+        .find(|candidate| contains(available, candidate))
+        .map(|candidate| candidate.to_string())
+}
+
+fn unavailable(field: &str, kind: &str, value: &str, suggestion: Option<String>) -> PresetIssue {
+    let message = match &suggestion {
+        Some(substitute) => format!(
+            "The {value} {kind} is unavailable in this FFmpeg build; {substitute} can replace it."
+        ),
+        None => format!("The {value} {kind} is unavailable in this FFmpeg build."),
+    };
+    PresetIssue {
+        field: field.to_string(),
+        value: value.to_string(),
+        message,
+        suggestion,
+    }
+}
+
 /**
  * Executes FFmpeg with the given arguments and returns its output.
  *
@@ -361,6 +1072,57 @@ fn parse_formats(output: &str) -> Vec<String> {
     formats.into_iter().collect()
 }
 
+/**
+ * Parses `ffmpeg -formats` output for the formats FFmpeg can write.
+ *
+ * Only entries with `E` in the mux column count; demux-only entries such as
+ * `mov,mp4,m4a,3gp,3g2,mj2` list aliases that can't be passed to `-f` for
+ * output. Mux entries with aliases are split into one name each. The legend
+ * above the `--` separator is skipped.
+ *
+ * # Example Output Parsing
+ *
+ * ```text
+ *  D. = Demuxing supported
+ *  .E = Muxing supported
+ *  --
+ *  D  mov,mp4,m4a,3gp,3g2,mj2 QuickTime / MOV
+ *   E mp4             MP4 (MPEG-4 Part 14)
+ *  DE matroska,webm   Matroska / WebM
+ * ```
+ *
+ * Results in `["matroska", "mp4", "webm"]`.
+ */
+fn parse_muxers(output: &str) -> Vec<String> {
+    let lines: Vec<&str> = output.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim() == "--")
+        .map_or(0, |separator| separator + 1);
+
+    let mut muxers = BTreeSet::new();
+    for line in &lines[start..] {
+        // The flags sit in fixed columns after one leading space: demux,
+        // mux, then (in newer builds) device.
+        let mut columns = line.chars().skip(1);
+        if columns.nth(1) != Some('E') {
+            continue;
+        }
+        let names = line
+            .get(4..)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap_or_default();
+        muxers.extend(
+            names
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    muxers.into_iter().collect()
+}
+
 /**
  * Parses FFmpeg filter output to extract available filter names.
  *
@@ -404,6 +1166,133 @@ fn parse_filters(output: &str) -> Vec<String> {
     filters.into_iter().collect()
 }
 
+/**
+ * Decoders found in `ffmpeg -decoders` output.
+ */
+#[derive(Debug, Default, PartialEq)]
+struct Decoders {
+    video: Vec<String>,
+    audio: Vec<String>,
+    codecs: Vec<String>,
+}
+
+/**
+ * Parses FFmpeg decoder output to extract decoders and the codecs they
+ * handle.
+ *
+ * The listing has the same layout as `ffmpeg -encoders`. A decoder whose name
+ * differs from its codec says so in a trailing `(codec NAME)`; subtitle
+ * decoders only count towards `codecs`. Legend lines (`V..... = Video`) are
+ * skipped.
+ *
+ * # Example Output Parsing
+ *
+ * ```text
+ *  V....D libdav1d             dav1d AV1 decoder by VideoLAN (codec av1)
+ *  VFS..D prores               ProRes (iCodec Pro)
+ *  A....D aac_at               aac (AudioToolbox) (codec aac)
+ * ```
+ *
+ * Results in video `["libdav1d", "prores"]`, audio `["aac_at"]` and codecs
+ * `["aac", "av1", "prores"]`.
+ */
+fn parse_decoders(output: &str) -> Decoders {
+    let mut video = BTreeSet::new();
+    let mut audio = BTreeSet::new();
+    let mut codecs = BTreeSet::new();
+
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(flags), Some(name)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if flags.len() != 6 || name == "=" {
+            continue;
+        }
+        match flags.chars().next() {
+            Some('V') => video.insert(name.to_string()),
+            Some('A') => audio.insert(name.to_string()),
+            Some('S') => false,
+            _ => continue,
+        };
+        let codec = line
+            .trim_end()
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once("(codec "))
+            .map_or(name, |(_, codec)| codec);
+        codecs.insert(codec.to_string());
+    }
+
+    Decoders {
+        video: video.into_iter().collect(),
+        audio: audio.into_iter().collect(),
+        codecs: codecs.into_iter().collect(),
+    }
+}
+
+/**
+ * Parses plain name listings: one name per line under a heading ending in a
+ * colon, as `ffmpeg -hwaccels` (`Hardware acceleration methods:`) and
+ * `ffmpeg -bsfs` (`Bitstream filters:`) print them.
+ */
+fn parse_name_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.ends_with(':'))
+        .map(str::to_string)
+        .collect()
+}
+
+/**
+ * Parses FFmpeg pixel format output to extract formats usable as a
+ * conversion target.
+ *
+ * Parses the output of `ffmpeg -pix_fmts`, keeping formats whose flags
+ * include 'O' (supported output format for conversion). Hardware surfaces
+ * such as `videotoolbox_vld` can't be converted to and are left out. The
+ * legend above the `-----` separator is skipped.
+ *
+ * # Arguments
+ *
+ * * `output` - Raw output string from `ffmpeg -pix_fmts`
+ *
+ * # Returns
+ *
+ * Returns a sorted vector of pixel format names.
+ *
+ * # Example Output Parsing
+ *
+ * ```text
+ * FLAGS NAME            NB_COMPONENTS BITS_PER_PIXEL BIT_DEPTHS
+ * -----
+ * IO... yuv420p                3             12      8-8-8
+ * ..H.. videotoolbox_vld       0              0      0
+ * IO... p010le                 3             15      10-10-10
+ * ```
+ *
+ * Results in `["p010le", "yuv420p"]`.
+ */
+fn parse_pixel_formats(output: &str) -> Vec<String> {
+    let mut formats = BTreeSet::new();
+
+    let table = output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("-----"))
+        .skip(1);
+    for line in table {
+        let mut fields = line.split_whitespace();
+        let (Some(flags), Some(name)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if flags.len() == 5 && flags.chars().nth(1) == Some('O') {
+            formats.insert(name.to_string());
+        }
+    }
+
+    formats.into_iter().collect()
+}
+
 /**
  * Validates detected video encoders and removes ones that fail self-tests.
  *
@@ -615,6 +1504,26 @@ mod tests {
         assert_eq!(formats.len(), 1);
     }
 
+    #[test]
+    fn parses_only_the_mux_column_as_muxers() {
+        let sample = "File formats:
+ D. = Demuxing supported
+ .E = Muxing supported
+ --
+ D  mov,mp4,m4a,3gp,3g2,mj2 QuickTime / MOV
+  E mp4             MP4 (MPEG-4 Part 14)
+ DE matroska,webm   Matroska / WebM
+  E ipod            iPod H.264 MP4 (MPEG-4 Part 14)
+ D  dash            Dynamic Adaptive Streaming over HTTP
+ D d lavfi          Libavfilter virtual input device
+";
+        assert_eq!(
+            parse_muxers(sample),
+            vec!["ipod", "matroska", "mp4", "webm"]
+        );
+        assert!(parse_muxers("").is_empty());
+    }
+
     #[test]
     fn parses_formats_empty() {
         let sample = "";
@@ -698,6 +1607,280 @@ ab
         assert_eq!(audio[1], "zlib_encoder");
     }
 
+    #[test]
+    fn fingerprints_follow_the_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("ffmpeg");
+        fs::write(&binary, b"v1").unwrap();
+        let first = fingerprint(binary.clone()).unwrap();
+        assert_eq!(fingerprint(binary.clone()), Some(first.clone()));
+
+        fs::write(&binary, b"v2 is larger").unwrap();
+        assert_ne!(fingerprint(binary.clone()), Some(first));
+        assert_eq!(fingerprint(dir.path().join("missing")), None);
+    }
+
+    #[test]
+    fn parses_version_output() {
+        let sample = "\
+ffmpeg version n6.1.1 Copyright (c) 2000-2023 the FFmpeg developers
+built with gcc 13.2.1 (GCC) 20230801
+configuration: --prefix=/usr --enable-gpl --enable-libx264
+libavutil      58. 29.100 / 58. 29.100
+libavcodec     60. 31.102 / 60. 31.102
+";
+        let version = parse_version(sample).unwrap();
+        assert_eq!(version.version, "n6.1.1");
+        assert_eq!(version.release, Some([6, 1, 1]));
+        assert_eq!(
+            version.configuration,
+            vec!["--prefix=/usr", "--enable-gpl", "--enable-libx264"]
+        );
+        assert_eq!(version.libraries["libavcodec"], "60.31.102");
+        assert_eq!(version.libraries.len(), 2);
+        assert_eq!(parse_version("no banner here"), None);
+    }
+
+    #[test]
+    fn parses_release_numbers() {
+        assert_eq!(parse_release("7.1"), Some([7, 1, 0]));
+        assert_eq!(parse_release("6.0-tessus"), Some([6, 0, 0]));
+        assert_eq!(parse_release("5.1.4-0ubuntu1"), Some([5, 1, 4]));
+        assert_eq!(parse_release("N-113447-g1a2b3c4"), None);
+    }
+
+    #[test]
+    fn rejects_binaries_below_the_minimum() {
+        let snapshot = |version: &str| CapabilitySnapshot {
+            video_encoders: Vec::new(),
+            audio_encoders: Vec::new(),
+            formats: Vec::new(),
+            muxers: Vec::new(),
+            filters: Vec::new(),
+            pixel_formats: Vec::new(),
+            video_decoders: Vec::new(),
+            audio_decoders: Vec::new(),
+            decodable_codecs: Vec::new(),
+            hwaccels: Vec::new(),
+            bitstream_filters: Vec::new(),
+            ffmpeg: parse_version(&format!("ffmpeg version {version} Copyright")),
+        };
+
+        let error = check_minimum_version(&snapshot("4.4.2"), [5, 1, 0]).unwrap_err();
+        assert_eq!(error.code, "capability_version_unsupported");
+        assert!(error
+            .message
+            .contains("4.4.2 is older than the minimum supported version 5.1"));
+        assert!(check_minimum_version(&snapshot("5.1"), [5, 1, 0]).is_ok());
+        assert!(check_minimum_version(&snapshot("N-113447-g1a2b3c4"), [5, 1, 0]).is_ok());
+    }
+
+    #[test]
+    fn diffs_snapshots() {
+        let names = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        let snapshot = |encoders: &[&str], version: &str| CapabilitySnapshot {
+            video_encoders: names(encoders),
+            audio_encoders: names(&["aac"]),
+            formats: Vec::new(),
+            muxers: Vec::new(),
+            filters: Vec::new(),
+            pixel_formats: Vec::new(),
+            video_decoders: Vec::new(),
+            audio_decoders: Vec::new(),
+            decodable_codecs: Vec::new(),
+            hwaccels: Vec::new(),
+            bitstream_filters: Vec::new(),
+            ffmpeg: parse_version(&format!("ffmpeg version {version} Copyright")),
+        };
+
+        let before = snapshot(&["libx264", "libx265"], "6.1");
+        assert_eq!(diff_snapshots(&before, &before.clone()), None);
+
+        let after = snapshot(&["libsvtav1", "libx264"], "7.1");
+        let diff = diff_snapshots(&before, &after).unwrap();
+        assert_eq!(diff.video_encoders.added, vec!["libsvtav1"]);
+        assert_eq!(diff.video_encoders.removed, vec!["libx265"]);
+        assert_eq!(diff.audio_encoders, ListDiff::default());
+        assert_eq!(diff.previous_version.as_deref(), Some("6.1"));
+        assert_eq!(diff.version.as_deref(), Some("7.1"));
+    }
+
+    #[test]
+    fn validates_presets_against_the_snapshot() {
+        let names = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        let snapshot = CapabilitySnapshot {
+            video_encoders: names(&["hevc_videotoolbox", "libx264"]),
+            audio_encoders: names(&["aac"]),
+            formats: names(&["mov,mp4,m4a,3gp,3g2,mj2", "matroska,webm"]),
+            muxers: names(&["matroska", "mov", "mp4", "webm"]),
+            filters: names(&["scale", "yadif"]),
+            pixel_formats: Vec::new(),
+            video_decoders: Vec::new(),
+            audio_decoders: Vec::new(),
+            decodable_codecs: Vec::new(),
+            hwaccels: Vec::new(),
+            bitstream_filters: Vec::new(),
+            ffmpeg: None,
+        };
+
+        let supported = PlannedPreset {
+            container: "mp4".to_string(),
+            video_encoder: Some("libx264".to_string()),
+            audio_encoder: Some("copy".to_string()),
+            filters: vec!["scale".to_string()],
+        };
+        assert_eq!(
+            validate_preset(&snapshot, &supported),
+            PresetValidation {
+                valid: true,
+                issues: Vec::new()
+            }
+        );
+
+        let planned = PlannedPreset {
+            container: "avi".to_string(),
+            video_encoder: Some("libx265".to_string()),
+            audio_encoder: Some("libmp3lame".to_string()),
+            filters: vec!["zscale".to_string(), "bwdif".to_string()],
+        };
+        let validation = validate_preset(&snapshot, &planned);
+        assert!(!validation.valid);
+        let summary: Vec<_> = validation
+            .issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.field.as_str(),
+                    issue.value.as_str(),
+                    issue.suggestion.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("container", "avi", None),
+                ("videoEncoder", "libx265", Some("hevc_videotoolbox")),
+                ("audioEncoder", "libmp3lame", None),
+                ("filters", "zscale", Some("scale")),
+                ("filters", "bwdif", Some("yadif")),
+            ]
+        );
+        assert_eq!(
+            validation.issues[1].message,
+            "The libx265 encoder is unavailable in this FFmpeg build; \
+             hevc_videotoolbox can replace it."
+        );
+    }
+
+    #[test]
+    fn parses_encoder_options() {
+        let sample = "\
+Encoder hevc_videotoolbox [VideoToolbox H.265 Encoder]:
+    General capabilities: dr1 delay hardware
+    Threading capabilities: none
+    Supported pixel formats: videotoolbox_vld nv12 yuv420p bgra p010le
+hevc_videotoolbox AVOptions:
+  -profile           <int>        E..V....... Profile (from -99 to 4) (default -99)
+     main            1            E..V....... Main Profile
+     main10          2            E..V....... Main10 Profile
+  -alpha_quality     <double>     E..V....... Compression quality for the alpha channel (from 0 to 1) (default 0)
+  -realtime          <boolean>    E..V....... Hint that encoding should happen in real-time if not faster (default false)
+";
+        let encoder = parse_encoder_options("hevc_videotoolbox", sample).unwrap();
+        assert_eq!(
+            encoder.description.as_deref(),
+            Some("VideoToolbox H.265 Encoder")
+        );
+        assert_eq!(encoder.profiles, vec!["main", "main10"]);
+        assert!(encoder.presets.is_empty());
+        assert_eq!(encoder.pixel_formats.len(), 5);
+        assert_eq!(encoder.options.len(), 3);
+        assert_eq!(encoder.options[0].value_type, "int");
+        assert_eq!(encoder.options[0].default.as_deref(), Some("-99"));
+        assert_eq!(
+            encoder.options[1].description,
+            "Compression quality for the alpha channel (from 0 to 1)"
+        );
+        assert_eq!(encoder.options[2].default.as_deref(), Some("false"));
+    }
+
+    #[test]
+    fn parses_free_form_encoder_options() {
+        let sample = "\
+Encoder libx264 [libx264 H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10]:
+    Supported pixel formats: yuv420p yuv420p10le
+libx264 AVOptions:
+  -preset            <string>     E..V....... Set the encoding preset (cf. x264 --fullhelp) (default \"medium\")
+  -tune              <string>     E..V....... Tune the encoding params (cf. x264 --fullhelp)
+";
+        let encoder = parse_encoder_options("libx264", sample).unwrap();
+        assert!(encoder.presets.is_empty() && encoder.tunes.is_empty());
+        assert_eq!(encoder.options[0].default.as_deref(), Some("medium"));
+        assert_eq!(encoder.options[1].default, None);
+
+        let unknown = "Codec 'libnothing' is not recognized by FFmpeg.\n";
+        assert_eq!(parse_encoder_options("libnothing", unknown), None);
+    }
+
+    #[test]
+    fn parses_decoders() {
+        let sample = "
+Decoders:
+ V..... = Video
+ A..... = Audio
+ S..... = Subtitle
+ ------
+ V....D libdav1d             dav1d AV1 decoder by VideoLAN (codec av1)
+ VFS..D prores               ProRes (iCodec Pro)
+ A....D aac_at               aac (AudioToolbox) (codec aac)
+ A....D flac                 FLAC (Free Lossless Audio Codec)
+ S..... ass                  ASS (Advanced SubStation Alpha) subtitle
+";
+        let decoders = parse_decoders(sample);
+        assert_eq!(decoders.video, vec!["libdav1d", "prores"]);
+        assert_eq!(decoders.audio, vec!["aac_at", "flac"]);
+        assert_eq!(decoders.codecs, vec!["aac", "ass", "av1", "flac", "prores"]);
+    }
+
+    #[test]
+    fn parses_hwaccels() {
+        let sample = "Hardware acceleration methods:\nvideotoolbox\n\n";
+        assert_eq!(parse_name_list(sample), vec!["videotoolbox"]);
+        assert!(parse_name_list("Hardware acceleration methods:\n").is_empty());
+    }
+
+    #[test]
+    fn parses_bitstream_filters() {
+        let sample = "Bitstream filters:\naac_adtstoasc\nh264_mp4toannexb\nhevc_mp4toannexb\n";
+        assert_eq!(
+            parse_name_list(sample),
+            vec!["aac_adtstoasc", "h264_mp4toannexb", "hevc_mp4toannexb"]
+        );
+    }
+
+    #[test]
+    fn parses_pixel_formats() {
+        let sample = "
+Pixel formats:
+I.... = Supported Input  format for conversion
+.O... = Supported Output format for conversion
+..H.. = Hardware accelerated format
+FLAGS NAME            NB_COMPONENTS BITS_PER_PIXEL BIT_DEPTHS
+-----
+IO... yuv420p                3             12      8-8-8
+IO... nv12                   3             12      8-8-8
+..H.. videotoolbox_vld       0              0      0
+I.... bayer_rggb8            3              8      2-4-2
+IO... p010le                 3             15      10-10-10
+";
+        assert_eq!(
+            parse_pixel_formats(sample),
+            vec!["nv12", "p010le", "yuv420p"]
+        );
+        assert!(parse_pixel_formats("").is_empty());
+    }
+
     #[test]
     fn test_parse_formats_deduplication() {
         let sample = "
@@ -77,10 +77,15 @@ Potential areas for expansion:
 */
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{ffi::OsString, process::Command};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::{OsStr, OsString},
+    path::Path,
+    process::Command,
+};
 use tauri::AppHandle;
 
-use crate::error::AppError;
+use crate::{cloud_files, error::AppError, remote_input};
 
 /** Color space metadata extracted from video streams.
 
@@ -93,12 +98,15 @@ color spaces or when preserving HDR content.
 * `primaries` - Color primaries standard (e.g., "bt709", "bt2020")
 * `trc` - Transfer characteristics/curve (e.g., "bt709", "pq", "hlg")
 * `space` - Color space matrix (e.g., "bt709", "bt2020nc", "rgb")
+* `mastering_display`, `content_light` - Static HDR10 metadata
+* `hdr10_plus`, `dolby_vision` - Dynamic HDR metadata formats
 
 # Usage Context
 Used in conversion planning to determine if color space conversion is needed
-and to select appropriate FFmpeg color handling parameters.
+and to select appropriate FFmpeg color handling parameters. The HDR metadata
+tells whether an output needs tone-mapping or can pass the metadata through.
 */
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProbeColor {
     /** Color primaries standard identifier */
@@ -107,6 +115,49 @@ pub struct ProbeColor {
     pub trc: Option<String>,
     /** Color space matrix coefficients */
     pub space: Option<String>,
+    /** Mastering display color volume (SMPTE ST 2086) */
+    pub mastering_display: Option<MasteringDisplay>,
+    /** Content light levels (MaxCLL/MaxFALL) */
+    pub content_light: Option<ContentLight>,
+    /** Whether the first frame carries HDR10+ dynamic metadata (SMPTE ST 2094-40) */
+    pub hdr10_plus: bool,
+    /** Dolby Vision configuration record */
+    pub dolby_vision: Option<DolbyVision>,
+}
+
+/** Mastering display color volume: CIE 1931 chromaticities of the display
+primaries and white point, and its luminance range in cd/m². */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MasteringDisplay {
+    pub red: [f64; 2],
+    pub green: [f64; 2],
+    pub blue: [f64; 2],
+    pub white_point: [f64; 2],
+    pub min_luminance: f64,
+    pub max_luminance: f64,
+}
+
+/** Content light levels in cd/m². */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentLight {
+    /** Maximum content light level (MaxCLL) */
+    pub max_cll: u32,
+    /** Maximum frame-average light level (MaxFALL) */
+    pub max_fall: u32,
+}
+
+/** Dolby Vision configuration of a stream. */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DolbyVision {
+    /** Profile (e.g., 5, 7, 8) */
+    pub profile: u32,
+    /** Level */
+    pub level: Option<u32>,
+    /** Base layer signal compatibility (e.g., 1 for HDR10, 4 for HLG) */
+    pub compatibility_id: Option<u32>,
 }
 
 /** Curated summary of media file metadata for application use.
@@ -147,6 +198,109 @@ pub struct ProbeSummary {
     pub channels: Option<u32>,
     /** Color space metadata (None if not available or not applicable) */
     pub color: Option<ProbeColor>,
+    /** Pixel format of the first video stream (e.g., "yuv420p10le") */
+    pub pix_fmt: Option<String>,
+    /** Bits per color component of the first video stream */
+    pub bit_depth: Option<u32>,
+    /** Codec profile of the first video stream (e.g., "Main 10", "High") */
+    pub profile: Option<String>,
+    /** Codec level as `ffprobe` reports it (e.g., 41 for H.264 4.1, 153 for
+    HEVC 5.1) */
+    pub level: Option<i32>,
+    /** Overall bitrate of the container in bits per second */
+    pub bit_rate: Option<u64>,
+    /** Bitrate of the first video stream in bits per second, reported or
+    estimated (see `ProbeStream::bit_rate`) */
+    pub video_bit_rate: Option<u64>,
+    /** Bitrate of the first audio stream in bits per second */
+    pub audio_bit_rate: Option<u64>,
+    /** Whether the file carries cover art as an attached picture stream */
+    pub has_cover_art: bool,
+    /** Files attached to the container (fonts, covers), as Matroska allows */
+    pub attachments: Vec<ProbeAttachment>,
+    /** Every stream in the file, in file order, for track selection */
+    pub streams: Vec<ProbeStream>,
+    /** Interlacing analysis, only filled by a deep probe */
+    pub interlacing: Option<Interlacing>,
+}
+
+/** Result of running the `idet` filter over a sample of the first video
+stream.
+
+`idet` classifies each frame as top field first, bottom field first,
+progressive or undetermined, and counts frames whose fields repeat the
+previous ones, which is what 3:2 pulldown (telecine) produces.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Interlacing {
+    /** Frames analyzed */
+    pub frames: u32,
+    /** Likelihood (0–1) that the video is interlaced: the share of frames
+    detected as top or bottom field first */
+    pub interlaced: f64,
+    /** Likelihood (0–1) that the video is telecined: how often fields repeat,
+    relative to the two in five frames of 3:2 pulldown */
+    pub telecined: f64,
+    /** Dominant field order of interlaced frames ("tff" or "bff") */
+    pub field_order: Option<String>,
+}
+
+/** One stream of a media file, as listed for track selection.
+
+Unlike the first-stream fields of `ProbeSummary`, these cover every video,
+audio, subtitle and data stream, so multi-language files can offer a choice
+of tracks.
+*/
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeStream {
+    /** Index within the file, as used by `-map 0:<index>` */
+    pub index: u32,
+    /** Stream type ("video", "audio", "subtitle", "data", ...) */
+    #[serde(rename = "type")]
+    pub kind: String,
+    /** Codec name in lowercase */
+    pub codec: Option<String>,
+    /** Language tag (e.g., "eng"); None when untagged or "und" */
+    pub language: Option<String>,
+    /** Track title tag */
+    pub title: Option<String>,
+    /** Number of audio channels (audio streams only) */
+    pub channels: Option<u32>,
+    /** Width in pixels (video streams only) */
+    pub width: Option<u32>,
+    /** Height in pixels (video streams only) */
+    pub height: Option<u32>,
+    /** Whether the stream is flagged as the default of its type */
+    pub default: bool,
+    /** Whether the stream is flagged as forced (forced subtitles) */
+    pub forced: bool,
+    /** Whether the stream is an attached picture (cover art) rather than
+    video */
+    pub cover_art: bool,
+    /** Bitrate in bits per second, as reported or from Matroska's `BPS`
+    statistics tag; otherwise apportioned from the container bitrate */
+    pub bit_rate: Option<u64>,
+    /** Whether `bit_rate` was apportioned rather than reported */
+    pub bit_rate_estimated: bool,
+    /** Every stream tag (language, title, handler_name, ...), keyed in
+    lowercase */
+    pub tags: BTreeMap<String, String>,
+}
+
+/** A file attached to the container, such as a font used by ASS subtitles. */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeAttachment {
+    /** Index within the file, as used by `-map 0:<index>` */
+    pub index: u32,
+    /** Attached file name tag */
+    pub filename: Option<String>,
+    /** MIME type tag (e.g., "font/ttf", "image/jpeg") */
+    pub mime_type: Option<String>,
+    /** Whether the attachment is a font */
+    pub font: bool,
 }
 
 /** Complete probe response containing both raw and summarized data.
@@ -168,6 +322,26 @@ pub struct ProbeResponse {
     pub raw: Value,
     /** Curated summary optimized for application use */
     pub summary: ProbeSummary,
+    /** Chapter markers in file order, for chapter-based trimming and splitting */
+    pub chapters: Vec<ProbeChapter>,
+    /** Container-level tags (title, artist, album, creation_time, ...), keyed
+    in lowercase */
+    pub tags: BTreeMap<String, String>,
+    /** Keyframe timestamps of the first video stream in seconds, ascending;
+    only listed when requested, as every keyframe has to be decoded */
+    pub keyframes: Option<Vec<f64>>,
+}
+
+/** A chapter marker of a media file. */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeChapter {
+    /** Start time in seconds */
+    pub start_sec: f64,
+    /** End time in seconds */
+    pub end_sec: f64,
+    /** Chapter title tag, if any */
+    pub title: Option<String>,
 }
 
 /** Internal representation of `ffprobe` format section.
@@ -180,6 +354,11 @@ minimize parsing overhead and memory usage.
 struct FfprobeFormat {
     /** Total duration as a string (parsed to f64 for calculations) */
     duration: Option<String>,
+    /** Overall bitrate in bits per second, as a string */
+    bit_rate: Option<String>,
+    /** Container-level tags such as title and creation_time */
+    #[serde(default)]
+    tags: HashMap<String, String>,
 }
 
 /** Internal representation of individual `ffprobe` streams.
@@ -223,6 +402,38 @@ struct FfprobeStream {
     /** Color space matrix (video streams only) */
     #[serde(rename = "color_space")]
     color_space: Option<String>,
+    /** Pixel format (video streams only) */
+    pix_fmt: Option<String>,
+    /** Bits per sample as a string; absent or "0" when unknown */
+    bits_per_raw_sample: Option<String>,
+    /** Codec profile name */
+    profile: Option<String>,
+    /** Codec level; -99 when unknown */
+    level: Option<i32>,
+    /** Bitrate in bits per second, as a string; often absent for Matroska */
+    bit_rate: Option<String>,
+    /** Index of the stream within the file */
+    index: Option<u32>,
+    /** Disposition flags such as default and forced */
+    #[serde(default)]
+    disposition: FfprobeDisposition,
+    /** Stream tags such as language and title */
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    /** Stream side data such as HDR mastering metadata */
+    #[serde(default)]
+    side_data_list: Vec<Value>,
+}
+
+/** Disposition flags of an `ffprobe` stream, reported as 0 or 1. */
+#[derive(Debug, Deserialize, Default)]
+struct FfprobeDisposition {
+    #[serde(default)]
+    default: u8,
+    #[serde(default)]
+    forced: u8,
+    #[serde(default)]
+    attached_pic: u8,
 }
 
 /** Internal representation of complete `ffprobe` JSON output.
@@ -239,6 +450,21 @@ struct FfprobeOutput {
     /** Container-level format information */
     #[serde(default)]
     format: FfprobeFormat,
+    /** Chapter markers (present because of `-show_chapters`) */
+    #[serde(default)]
+    chapters: Vec<FfprobeChapter>,
+}
+
+/** Internal representation of an `ffprobe` chapter. */
+#[derive(Debug, Deserialize, Default)]
+struct FfprobeChapter {
+    /** Start time in seconds, as a string */
+    start_time: Option<String>,
+    /** End time in seconds, as a string */
+    end_time: Option<String>,
+    /** Chapter tags such as the title */
+    #[serde(default)]
+    tags: HashMap<String, String>,
 }
 
 /** Probes a media file and returns comprehensive metadata.
@@ -252,10 +478,12 @@ parsing the results, and generating both raw and summarized outputs.
 2. Execute `ffprobe` with optimized arguments for JSON output
 3. Parse JSON response into strongly-typed structures
 4. Generate curated summary for application use
-5. Return both raw and processed results
+5. For PQ and HLG video, read the first frame's HDR side data
+6. Return both raw and processed results
 
 # Error Handling
 Returns `AppError` with context about which step failed:
+- `"input_not_downloaded"`: The file is a cloud placeholder not yet on this device
 - `"probe_ffprobe_exec"`: Unable to execute `ffprobe` with any candidate path
 - `"probe_parse_json"`: Invalid JSON output from `ffprobe`
 - `"probe_parse_struct"`: JSON structure doesn't match expected format
@@ -268,14 +496,20 @@ The function is optimized for quick analysis:
 
 # Arguments
 * `app` - Tauri application handle for path resolution
-* `path` - File system path to the media file to analyze
+* `path` - File system path to the media file to analyze, or an http(s),
+  RTSP or SRT URL
 
 # Returns
 `ProbeResponse` containing both raw JSON and curated summary, or `AppError` on failure
 */
-pub fn probe_media(app: &AppHandle, path: &str) -> Result<ProbeResponse, AppError> {
+pub fn probe_media(app: &AppHandle, path: impl AsRef<OsStr>) -> Result<ProbeResponse, AppError> {
+    // Online-only cloud files would be downloaded by ffprobe, or fail mid-read
+    if !is_network_input(path.as_ref()) {
+        cloud_files::ensure_downloaded(Path::new(path.as_ref()))?;
+    }
+
     // Execute ffprobe and capture JSON output
-    let output = run_ffprobe(app, path)?;
+    let output = run_ffprobe(app, path.as_ref())?;
 
     // Parse raw JSON for preservation and debugging
     let raw: Value = serde_json::from_str(&output)
@@ -286,9 +520,24 @@ pub fn probe_media(app: &AppHandle, path: &str) -> Result<ProbeResponse, AppErro
         .map_err(|err| AppError::new("probe_parse_struct", err.to_string()))?;
 
     // Generate application-optimized summary
-    let summary = summarize(&parsed);
+    let mut summary = summarize(&parsed);
 
-    Ok(ProbeResponse { raw, summary })
+    // HDR10+ and some mastering metadata only travel with frames
+    if let Some(color) = summary.color.as_mut().filter(|color| is_hdr(color)) {
+        if let Ok(side_data) = first_frame_side_data(app, path.as_ref()) {
+            apply_side_data(color, &side_data);
+        }
+    }
+    let chapters = list_chapters(&parsed.chapters);
+    let tags = normalize_tags(&parsed.format.tags);
+
+    Ok(ProbeResponse {
+        raw,
+        summary,
+        chapters,
+        tags,
+        keyframes: None,
+    })
 }
 
 /** Executes `ffprobe` on a media file and returns JSON output.
@@ -305,6 +554,7 @@ The function uses these `ffprobe` arguments for optimal performance:
 - `-print_format json`: Structured output for reliable parsing
 - `-show_format`: Container-level metadata
 - `-show_streams`: Individual stream information
+- `-show_chapters`: Chapter markers (surfaced as `ProbeResponse::chapters`)
 
 # Path Resolution Strategy
 Attempts `ffprobe` execution in this order:
@@ -324,13 +574,10 @@ Returns detailed error information including exit codes and stderr output.
 # Returns
 JSON string output from `ffprobe`, or `AppError` if execution fails
 */
-fn run_ffprobe(app: &AppHandle, path: &str) -> Result<String, AppError> {
-    let mut last_err: Option<String> = None;
-
-    // Try each candidate ffprobe path until one works
-    for candidate in candidate_ffprobe_paths(app) {
-        let mut command = Command::new(&candidate);
-        command.args([
+fn run_ffprobe(app: &AppHandle, path: &OsStr) -> Result<String, AppError> {
+    run_ffprobe_with(
+        app,
+        &[
             "-hide_banner",
             "-loglevel",
             "error",
@@ -338,8 +585,216 @@ fn run_ffprobe(app: &AppHandle, path: &str) -> Result<String, AppError> {
             "json",
             "-show_format",
             "-show_streams",
-            path,
-        ]);
+            "-show_chapters",
+        ],
+        path,
+    )
+}
+
+/** Reads the side data of the first video frame.
+
+HDR10+ metadata is only carried per frame, and some containers keep the
+mastering display metadata there too. Only one frame is decoded, with
+`-read_intervals %+#1`.
+*/
+fn first_frame_side_data(app: &AppHandle, path: &OsStr) -> Result<Vec<Value>, AppError> {
+    let output = run_ffprobe_with(
+        app,
+        &[
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-print_format",
+            "json",
+            "-select_streams",
+            "v:0",
+            "-read_intervals",
+            "%+#1",
+            "-show_entries",
+            "frame=side_data_list",
+        ],
+        path,
+    )?;
+    let raw: Value = serde_json::from_str(&output)
+        .map_err(|err| AppError::new("probe_parse_json", err.to_string()))?;
+    Ok(raw["frames"][0]["side_data_list"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default())
+}
+
+/** Lists the keyframe timestamps of the first video stream.
+
+`-skip_frame nokey` makes the decoder drop everything but keyframes, so only
+those are decoded and reported. Frames without a usable timestamp are
+skipped. Used for keyframe-aligned trimming.
+*/
+pub fn list_keyframes(app: &AppHandle, path: &str) -> Result<Vec<f64>, AppError> {
+    let output = run_ffprobe_with(
+        app,
+        &[
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-print_format",
+            "json",
+            "-select_streams",
+            "v:0",
+            "-skip_frame",
+            "nokey",
+            "-show_frames",
+            "-show_entries",
+            "frame=pts_time,best_effort_timestamp_time",
+        ],
+        OsStr::new(path),
+    )?;
+    let raw: Value = serde_json::from_str(&output)
+        .map_err(|err| AppError::new("probe_parse_json", err.to_string()))?;
+    Ok(parse_keyframes(&raw))
+}
+
+/** Reads frame timestamps from `-show_frames` JSON, sorted and deduplicated. */
+fn parse_keyframes(raw: &Value) -> Vec<f64> {
+    let mut times: Vec<f64> = raw["frames"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|frame| {
+            frame["pts_time"]
+                .as_str()
+                .or_else(|| frame["best_effort_timestamp_time"].as_str())?
+                .parse::<f64>()
+                .ok()
+                .filter(|time| time.is_finite())
+        })
+        .collect();
+    times.sort_by(f64::total_cmp);
+    times.dedup();
+    times
+}
+
+/** Runs a short `idet` pass over a sample of the first video stream.
+
+The sample starts a tenth into files longer than a minute, skipping
+intros and logos that are often progressive, and covers
+`IDET_SAMPLE_FRAMES` frames. Used by deep probes only, as it decodes video.
+*/
+pub fn detect_interlacing(
+    app: &AppHandle,
+    path: &str,
+    duration_sec: f64,
+) -> Result<Interlacing, AppError> {
+    let start = if duration_sec > 60.0 {
+        duration_sec / 10.0
+    } else {
+        0.0
+    };
+    let mut last_err = None;
+    for candidate in crate::binary_resolver::resolve_ffmpeg_paths(app) {
+        let output = Command::new(&candidate)
+            .args([
+                "-hide_banner",
+                "-nostdin",
+                "-ss",
+                &format!("{start:.3}"),
+                "-i",
+            ])
+            .arg(path)
+            .args(["-map", "0:v:0", "-frames:v"])
+            .arg(IDET_SAMPLE_FRAMES.to_string())
+            .args(["-vf", "idet", "-an", "-sn", "-dn", "-f", "null", "-"])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                return parse_idet(&String::from_utf8_lossy(&output.stderr)).ok_or_else(|| {
+                    AppError::new("probe_idet_parse", "ffmpeg reported no idet statistics")
+                });
+            },
+            Ok(output) => {
+                last_err = Some(format!(
+                    "ffmpeg exited with status {}",
+                    output
+                        .status
+                        .code()
+                        .map(|code| code.to_string())
+                        .unwrap_or_else(|| "unknown".into())
+                ));
+            },
+            Err(error) => last_err = Some(error.to_string()),
+        }
+    }
+    Err(AppError::new(
+        "probe_idet_exec",
+        last_err.unwrap_or_else(|| "Unable to execute ffmpeg".into()),
+    ))
+}
+
+/** Frames the `idet` pass analyzes. */
+const IDET_SAMPLE_FRAMES: u32 = 600;
+
+/** Share of frames with a repeated field in 3:2 pulldown. */
+const PULLDOWN_REPEAT_SHARE: f64 = 0.4;
+
+/** Reads the `idet` summary from ffmpeg's log: the multi-frame detection
+counts and the repeated field counts. */
+fn parse_idet(log: &str) -> Option<Interlacing> {
+    let counts = |label: &str| -> Option<HashMap<String, u32>> {
+        let line = log.lines().rev().find(|line| line.contains(label))?;
+        let rest = &line[line.find(label)? + label.len()..];
+        let tokens: Vec<&str> = rest.split_whitespace().collect();
+        Some(
+            tokens
+                .chunks(2)
+                .filter_map(|pair| {
+                    Some((
+                        pair.first()?.trim_end_matches(':').to_lowercase(),
+                        pair.get(1)?.parse().ok()?,
+                    ))
+                })
+                .collect(),
+        )
+    };
+    let detection = counts("Multi frame detection:")?;
+    let repeated = counts("Repeated Fields:").unwrap_or_default();
+    let get = |map: &HashMap<String, u32>, key: &str| map.get(key).copied().unwrap_or_default();
+
+    let (tff, bff) = (get(&detection, "tff"), get(&detection, "bff"));
+    let frames = tff + bff + get(&detection, "progressive") + get(&detection, "undetermined");
+    if frames == 0 {
+        return None;
+    }
+    let repeats = get(&repeated, "top") + get(&repeated, "bottom");
+    Some(Interlacing {
+        frames,
+        interlaced: f64::from(tff + bff) / f64::from(frames),
+        telecined: (f64::from(repeats) / f64::from(frames) / PULLDOWN_REPEAT_SHARE).min(1.0),
+        field_order: if tff + bff == 0 {
+            None
+        } else if bff > tff {
+            Some("bff".into())
+        } else {
+            Some("tff".into())
+        },
+    })
+}
+
+/** Executes `ffprobe` with `args` followed by `path`, trying each candidate
+binary until one succeeds. */
+fn run_ffprobe_with(app: &AppHandle, args: &[&str], path: &OsStr) -> Result<String, AppError> {
+    let mut last_err: Option<String> = None;
+
+    // Try each candidate ffprobe path until one works
+    for candidate in candidate_ffprobe_paths(app) {
+        let mut command = Command::new(&candidate);
+        command.args(args);
+        // An unreachable server would otherwise hang the probe
+        if is_network_input(path) {
+            command
+                .arg("-rw_timeout")
+                .arg(remote_input::NETWORK_TIMEOUT_US.to_string());
+        }
+        command.arg(path);
 
         match command.output() {
             Ok(output) if output.status.success() => {
@@ -372,6 +827,11 @@ fn run_ffprobe(app: &AppHandle, path: &str) -> Result<String, AppError> {
     ))
 }
 
+/** Whether `path` is an http(s), RTSP or SRT URL rather than a file. */
+fn is_network_input(path: &OsStr) -> bool {
+    path.to_str().is_some_and(remote_input::is_network_url)
+}
+
 /** Generates a list of candidate `ffprobe` executable paths.
 
 This function implements a cascading path resolution strategy that ensures
@@ -438,11 +898,12 @@ fn summarize(data: &FfprobeOutput) -> ProbeSummary {
         .and_then(|value| value.parse::<f64>().ok())
         .unwrap_or_default();
 
-    // Find first video and audio streams for metadata extraction
-    let video_stream = data
-        .streams
-        .iter()
-        .find(|stream| matches!(stream.codec_type.as_deref(), Some("video")));
+    // Find first video and audio streams for metadata extraction; cover art
+    // is a video stream too but says nothing about the video
+    let video_stream = data.streams.iter().find(|stream| {
+        matches!(stream.codec_type.as_deref(), Some("video"))
+            && stream.disposition.attached_pic != 1
+    });
 
     let audio_stream = data
         .streams
@@ -452,6 +913,14 @@ fn summarize(data: &FfprobeOutput) -> ProbeSummary {
     // Analyze subtitle presence across all streams
     let subtitle_stats = subtitle_presence(&data.streams);
 
+    let bit_rates = stream_bit_rates(data);
+    let position = |target: &FfprobeStream| {
+        data.streams
+            .iter()
+            .position(|stream| std::ptr::eq(stream, target))
+            .unwrap_or_default()
+    };
+
     // Extract frame rate with fallback from avg_frame_rate to r_frame_rate
     let fps = video_stream
         .and_then(|stream| {
@@ -462,17 +931,21 @@ fn summarize(data: &FfprobeOutput) -> ProbeSummary {
         })
         .and_then(parse_frame_rate);
 
-    // Extract color metadata if any color fields are present
+    // Extract color metadata if any color fields or side data are present
     let color = video_stream.and_then(|stream| {
         if stream.color_primaries.is_some()
             || stream.color_transfer.is_some()
             || stream.color_space.is_some()
+            || !stream.side_data_list.is_empty()
         {
-            Some(ProbeColor {
+            let mut color = ProbeColor {
                 primaries: stream.color_primaries.clone(),
                 trc: stream.color_transfer.clone(),
                 space: stream.color_space.clone(),
-            })
+                ..Default::default()
+            };
+            apply_side_data(&mut color, &stream.side_data_list);
+            Some(color)
         } else {
             None
         }
@@ -494,9 +967,309 @@ fn summarize(data: &FfprobeOutput) -> ProbeSummary {
         has_image_subs: subtitle_stats.1,
         channels: audio_stream.and_then(|stream| stream.channels),
         color,
+        pix_fmt: video_stream.and_then(|stream| stream.pix_fmt.clone()),
+        bit_depth: video_stream.and_then(video_bit_depth),
+        profile: video_stream
+            .and_then(|stream| stream.profile.clone())
+            .filter(|profile| profile != "unknown"),
+        level: video_stream
+            .and_then(|stream| stream.level)
+            .filter(|level| *level > 0),
+        bit_rate: parse_bit_rate(data.format.bit_rate.as_deref()),
+        video_bit_rate: video_stream.and_then(|stream| bit_rates[position(stream)].0),
+        audio_bit_rate: audio_stream.and_then(|stream| bit_rates[position(stream)].0),
+        has_cover_art: data
+            .streams
+            .iter()
+            .any(|stream| stream.disposition.attached_pic == 1),
+        attachments: list_attachments(&data.streams),
+        streams: list_streams(&data.streams, &bit_rates),
+        interlacing: None,
     }
 }
 
+/** Parses an `ffprobe` bitrate; zero and "N/A" count as unknown. */
+fn parse_bit_rate(value: Option<&str>) -> Option<u64> {
+    value
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|bits| *bits > 0)
+}
+
+/** Bitrate a stream reports itself: `bit_rate`, or the `BPS` statistics tag
+mkvmerge writes (sometimes suffixed with a language, as in `BPS-eng`). */
+fn reported_bit_rate(stream: &FfprobeStream) -> Option<u64> {
+    parse_bit_rate(stream.bit_rate.as_deref()).or_else(|| {
+        stream
+            .tags
+            .iter()
+            .find(|(key, _)| {
+                let key = key.to_ascii_uppercase();
+                key == "BPS" || key.starts_with("BPS-")
+            })
+            .and_then(|(_, value)| parse_bit_rate(Some(value)))
+    })
+}
+
+/** Bitrate of every stream, paired with whether it was estimated.
+
+When exactly one audio or video stream reports no bitrate, it gets what
+the reported streams leave of the container bitrate. With several unknown
+streams there is no fair split, so they stay unknown.
+*/
+fn stream_bit_rates(data: &FfprobeOutput) -> Vec<(Option<u64>, bool)> {
+    let mut rates: Vec<(Option<u64>, bool)> = data
+        .streams
+        .iter()
+        .map(|stream| (reported_bit_rate(stream), false))
+        .collect();
+    let unknown: Vec<usize> = data
+        .streams
+        .iter()
+        .enumerate()
+        .filter(|(position, stream)| {
+            rates[*position].0.is_none()
+                && stream.disposition.attached_pic != 1
+                && matches!(stream.codec_type.as_deref(), Some("video" | "audio"))
+        })
+        .map(|(position, _)| position)
+        .collect();
+    if let ([position], Some(total)) = (
+        unknown.as_slice(),
+        parse_bit_rate(data.format.bit_rate.as_deref()),
+    ) {
+        let known: u64 = rates.iter().filter_map(|(rate, _)| *rate).sum();
+        if total > known {
+            rates[*position] = (Some(total - known), true);
+        }
+    }
+    rates
+}
+
+/** Lists attachment streams, telling fonts apart by MIME type, codec or file
+extension. */
+fn list_attachments(streams: &[FfprobeStream]) -> Vec<ProbeAttachment> {
+    streams
+        .iter()
+        .enumerate()
+        .filter(|(_, stream)| stream.codec_type.as_deref() == Some("attachment"))
+        .map(|(position, stream)| {
+            let tag = |name: &str| {
+                stream
+                    .tags
+                    .get(name)
+                    .filter(|value| !value.is_empty())
+                    .cloned()
+            };
+            let filename = tag("filename");
+            let mime_type = tag("mimetype");
+            let font = mime_type.as_deref().is_some_and(is_font_mime_type)
+                || matches!(stream.codec_name.as_deref(), Some("ttf" | "otf"))
+                || filename.as_deref().is_some_and(|name| {
+                    let name = name.to_ascii_lowercase();
+                    [".ttf", ".otf", ".ttc"]
+                        .iter()
+                        .any(|extension| name.ends_with(extension))
+                });
+            ProbeAttachment {
+                index: stream.index.unwrap_or(position as u32),
+                filename,
+                mime_type,
+                font,
+            }
+        })
+        .collect()
+}
+
+/** Whether `mime_type` names a font; Matroska muxers use several spellings. */
+fn is_font_mime_type(mime_type: &str) -> bool {
+    let mime_type = mime_type.to_ascii_lowercase();
+    mime_type.starts_with("font/")
+        || mime_type.starts_with("application/x-font")
+        || matches!(
+            mime_type.as_str(),
+            "application/x-truetype-font"
+                | "application/vnd.ms-opentype"
+                | "application/font-sfnt"
+                | "application/font-woff"
+        )
+}
+
+/** Bit depth of a video stream: `bits_per_raw_sample` when reported, else
+read from the pixel format name. */
+fn video_bit_depth(stream: &FfprobeStream) -> Option<u32> {
+    stream
+        .bits_per_raw_sample
+        .as_deref()
+        .and_then(|bits| bits.parse::<u32>().ok())
+        .filter(|bits| *bits > 0)
+        .or_else(|| stream.pix_fmt.as_deref().map(pix_fmt_bit_depth))
+}
+
+/** Bits per component of an FFmpeg pixel format.
+
+Deeper formats carry their depth in the name: after the planar `p`
+(`yuv420p10le`, `gbrp12le`), after `gray` or `x2rgb`, as the last two digits
+of the semi-planar `p010`/`p016` family, or as bits per pixel of packed RGB
+(`rgb48le`, `rgba64le`). Everything else (`yuv420p`, `nv12`, `rgb24`, ...) is
+8 bits per component.
+*/
+fn pix_fmt_bit_depth(pix_fmt: &str) -> u32 {
+    let name = pix_fmt
+        .strip_suffix("le")
+        .or_else(|| pix_fmt.strip_suffix("be"))
+        .unwrap_or(pix_fmt);
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let Ok(digits) = name[stem.len()..].parse::<u32>() else {
+        return 8;
+    };
+    let depth = match stem {
+        "p" => digits % 100,
+        "gray" | "x2rgb" | "x2bgr" => digits,
+        "rgb" | "bgr" => digits / 3,
+        "rgba" | "bgra" => digits / 4,
+        _ if stem.ends_with('p') => digits,
+        _ => 8,
+    };
+    if (8..=16).contains(&depth) {
+        depth
+    } else {
+        8
+    }
+}
+
+/** Lists every stream for track selection.
+
+Streams without a type are skipped; a missing index falls back to the
+stream's position, which is what `ffprobe` reports anyway. `bit_rates` is
+parallel to `streams`, as `stream_bit_rates` returns it.
+*/
+fn list_streams(streams: &[FfprobeStream], bit_rates: &[(Option<u64>, bool)]) -> Vec<ProbeStream> {
+    streams
+        .iter()
+        .enumerate()
+        .filter_map(|(position, stream)| {
+            let tag = |name: &str| stream.tags.get(name).filter(|value| !value.is_empty());
+            Some(ProbeStream {
+                index: stream.index.unwrap_or(position as u32),
+                kind: stream.codec_type.clone()?,
+                codec: stream.codec_name.as_deref().map(str::to_lowercase),
+                language: tag("language")
+                    .filter(|language| *language != "und")
+                    .cloned(),
+                title: tag("title").cloned(),
+                channels: stream.channels,
+                width: stream.width,
+                height: stream.height,
+                default: stream.disposition.default == 1,
+                forced: stream.disposition.forced == 1,
+                cover_art: stream.disposition.attached_pic == 1,
+                bit_rate: bit_rates[position].0,
+                bit_rate_estimated: bit_rates[position].1,
+                tags: normalize_tags(&stream.tags),
+            })
+        })
+        .collect()
+}
+
+/** Whether `color` describes PQ or HLG video, or Dolby Vision. */
+fn is_hdr(color: &ProbeColor) -> bool {
+    matches!(color.trc.as_deref(), Some("smpte2084" | "arib-std-b67"))
+        || color.dolby_vision.is_some()
+}
+
+/** Fills HDR metadata from stream or frame side data entries. Values already
+set are kept, so stream-level metadata wins over frame-level metadata. */
+fn apply_side_data(color: &mut ProbeColor, side_data: &[Value]) {
+    for entry in side_data {
+        let kind = entry["side_data_type"].as_str().unwrap_or_default();
+        match kind {
+            "Mastering display metadata" if color.mastering_display.is_none() => {
+                color.mastering_display = mastering_display(entry);
+            },
+            "Content light level metadata" if color.content_light.is_none() => {
+                color.content_light = Some(ContentLight {
+                    max_cll: side_data_u32(&entry["max_content"]).unwrap_or_default(),
+                    max_fall: side_data_u32(&entry["max_average"]).unwrap_or_default(),
+                });
+            },
+            "DOVI configuration record" if color.dolby_vision.is_none() => {
+                color.dolby_vision =
+                    side_data_u32(&entry["dv_profile"]).map(|profile| DolbyVision {
+                        profile,
+                        level: side_data_u32(&entry["dv_level"]),
+                        compatibility_id: side_data_u32(&entry["dv_bl_signal_compatibility_id"]),
+                    });
+            },
+            _ if kind.contains("SMPTE2094-40") || kind.contains("HDR10+") => {
+                color.hdr10_plus = true;
+            },
+            _ => {},
+        }
+    }
+}
+
+/** Reads mastering display metadata; `ffprobe` reports each value as a
+rational string such as `"13250/50000"`. */
+fn mastering_display(entry: &Value) -> Option<MasteringDisplay> {
+    let value = |key: &str| side_data_rational(&entry[key]);
+    let point = |prefix: &str| {
+        Some([
+            value(&format!("{prefix}_x"))?,
+            value(&format!("{prefix}_y"))?,
+        ])
+    };
+    Some(MasteringDisplay {
+        red: point("red")?,
+        green: point("green")?,
+        blue: point("blue")?,
+        white_point: point("white_point")?,
+        min_luminance: value("min_luminance")?,
+        max_luminance: value("max_luminance")?,
+    })
+}
+
+/** A side data number, given either as a number or a rational string. */
+fn side_data_rational(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(parse_frame_rate))
+}
+
+fn side_data_u32(value: &Value) -> Option<u32> {
+    side_data_rational(value).map(|number| number.round() as u32)
+}
+
+/** Converts chapter markers, dropping ones without valid times. */
+/** Lowercases tag keys and drops empty values.
+
+Matroska writes tags in uppercase (`TITLE`, `ARTIST`) while MP4 and most
+other containers use lowercase, so keys are folded for a uniform lookup.
+*/
+fn normalize_tags(tags: &HashMap<String, String>) -> BTreeMap<String, String> {
+    tags.iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(key, value)| (key.to_lowercase(), value.clone()))
+        .collect()
+}
+
+fn list_chapters(chapters: &[FfprobeChapter]) -> Vec<ProbeChapter> {
+    let seconds = |value: &Option<String>| value.as_deref()?.parse::<f64>().ok();
+    chapters
+        .iter()
+        .filter_map(|chapter| {
+            Some(ProbeChapter {
+                start_sec: seconds(&chapter.start_time)?,
+                end_sec: seconds(&chapter.end_time)?,
+                title: chapter
+                    .tags
+                    .get("title")
+                    .filter(|title| !title.is_empty())
+                    .cloned(),
+            })
+        })
+        .collect()
+}
+
 /** Analyzes subtitle streams to determine presence of text and image subtitles.
 
 This function scans all streams in a media file to detect subtitle content,
@@ -753,6 +1526,7 @@ mod tests {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("120.5".to_string()),
+                ..Default::default()
             },
             streams: vec![FfprobeStream {
                 codec_type: Some("video".into()),
@@ -765,6 +1539,7 @@ mod tests {
                 color_space: Some("bt709".into()),
                 ..Default::default()
             }],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -786,6 +1561,7 @@ mod tests {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("60.0".to_string()),
+                ..Default::default()
             },
             streams: vec![FfprobeStream {
                 codec_type: Some("audio".into()),
@@ -793,6 +1569,7 @@ mod tests {
                 channels: Some(2),
                 ..Default::default()
             }],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -809,6 +1586,7 @@ mod tests {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("180.25".to_string()),
+                ..Default::default()
             },
             streams: vec![
                 FfprobeStream {
@@ -831,6 +1609,7 @@ mod tests {
                     ..Default::default()
                 },
             ],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -843,6 +1622,255 @@ mod tests {
         assert_eq!(summary.channels, Some(6));
         assert!(summary.has_text_subs);
         assert!(!summary.has_image_subs);
+        assert_eq!(summary.streams.len(), 3);
+    }
+
+    #[test]
+    fn test_summarize_lists_every_stream() {
+        let data: FfprobeOutput = serde_json::from_value(serde_json::json!({
+            "format": { "duration": "60.0" },
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "H264", "width": 1920, "height": 1080 },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac", "channels": 2,
+                  "disposition": { "default": 1, "forced": 0 }, "tags": { "language": "eng" } },
+                { "index": 2, "codec_type": "audio", "codec_name": "ac3", "channels": 6,
+                  "tags": { "language": "und", "title": "Commentary" } },
+                { "index": 3, "codec_type": "subtitle", "codec_name": "subrip",
+                  "disposition": { "default": 0, "forced": 1 }, "tags": { "language": "fra" } },
+            ],
+        }))
+        .unwrap();
+
+        let streams = summarize(&data).streams;
+        assert_eq!(streams.len(), 4);
+        assert_eq!(streams[0].kind, "video");
+        assert_eq!(streams[0].codec.as_deref(), Some("h264"));
+        assert_eq!(
+            (streams[0].width, streams[0].height),
+            (Some(1920), Some(1080))
+        );
+        assert!(streams[1].default && !streams[1].forced);
+        assert_eq!(streams[1].language.as_deref(), Some("eng"));
+        assert_eq!(streams[2].language, None);
+        assert_eq!(streams[2].title.as_deref(), Some("Commentary"));
+        assert_eq!(streams[2].channels, Some(6));
+        assert_eq!(streams[3].index, 3);
+        assert!(streams[3].forced);
+    }
+
+    #[test]
+    fn test_summarize_cover_art_and_attachments() {
+        let data: FfprobeOutput = serde_json::from_value(serde_json::json!({
+            "format": { "duration": "60.0" },
+            "streams": [
+                { "index": 0, "codec_type": "audio", "codec_name": "flac", "channels": 2 },
+                { "index": 1, "codec_type": "video", "codec_name": "mjpeg", "width": 600, "height": 600,
+                  "disposition": { "attached_pic": 1 } },
+                { "index": 2, "codec_type": "attachment", "codec_name": "ttf",
+                  "tags": { "filename": "Roboto.ttf", "mimetype": "application/x-truetype-font" } },
+                { "index": 3, "codec_type": "attachment",
+                  "tags": { "filename": "NotoSans.OTF", "mimetype": "application/octet-stream" } },
+                { "index": 4, "codec_type": "attachment",
+                  "tags": { "filename": "cover.jpg", "mimetype": "image/jpeg" } },
+            ],
+        }))
+        .unwrap();
+
+        let summary = summarize(&data);
+        assert!(summary.has_cover_art);
+        assert_eq!(summary.vcodec, None);
+        assert_eq!(summary.width, None);
+        assert!(summary.streams[1].cover_art);
+        let fonts: Vec<_> = summary
+            .attachments
+            .iter()
+            .map(|attachment| (attachment.index, attachment.font))
+            .collect();
+        assert_eq!(fonts, vec![(2, true), (3, true), (4, false)]);
+        assert_eq!(
+            summary.attachments[2].mime_type.as_deref(),
+            Some("image/jpeg")
+        );
+    }
+
+    #[test]
+    fn test_stream_bit_rates() {
+        let data: FfprobeOutput = serde_json::from_value(serde_json::json!({
+            "format": { "duration": "60.0", "bit_rate": "8500000" },
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac", "bit_rate": "192000" },
+                { "index": 2, "codec_type": "audio", "codec_name": "ac3",
+                  "tags": { "BPS-eng": "448000" } },
+                { "index": 3, "codec_type": "subtitle", "codec_name": "subrip", "bit_rate": "N/A" },
+            ],
+        }))
+        .unwrap();
+
+        let summary = summarize(&data);
+        assert_eq!(summary.bit_rate, Some(8_500_000));
+        assert_eq!(summary.video_bit_rate, Some(7_860_000));
+        assert_eq!(summary.audio_bit_rate, Some(192_000));
+        assert!(summary.streams[0].bit_rate_estimated);
+        assert_eq!(summary.streams[2].bit_rate, Some(448_000));
+        assert!(!summary.streams[2].bit_rate_estimated);
+        assert_eq!(summary.streams[3].bit_rate, None);
+
+        let mut unknown_audio = data;
+        unknown_audio.streams[1].bit_rate = None;
+        let summary = summarize(&unknown_audio);
+        assert_eq!(summary.video_bit_rate, None);
+        assert_eq!(summary.audio_bit_rate, None);
+    }
+
+    #[test]
+    fn test_parse_keyframes() {
+        let raw = serde_json::json!({
+            "frames": [
+                { "pts_time": "4.004000" },
+                { "pts_time": "0.000000" },
+                { "best_effort_timestamp_time": "2.002000" },
+                { "pts_time": "N/A" },
+                { "pts_time": "4.004000" },
+            ],
+        });
+        assert_eq!(parse_keyframes(&raw), vec![0.0, 2.002, 4.004]);
+        assert!(parse_keyframes(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_tags_are_keyed_in_lowercase() {
+        let data: FfprobeOutput = serde_json::from_value(serde_json::json!({
+            "format": { "duration": "60.0", "tags": {
+                "TITLE": "Live Set", "ARTIST": "Band", "creation_time": "2024-05-01T10:00:00Z",
+                "comment": " " } },
+            "streams": [
+                { "index": 0, "codec_type": "audio", "tags": { "language": "eng", "HANDLER_NAME": "Sound" } },
+            ],
+        }))
+        .unwrap();
+
+        let tags = normalize_tags(&data.format.tags);
+        assert_eq!(tags.get("title").map(String::as_str), Some("Live Set"));
+        assert_eq!(tags.get("artist").map(String::as_str), Some("Band"));
+        assert!(tags.contains_key("creation_time"));
+        assert!(!tags.contains_key("comment"));
+
+        let stream = &summarize(&data).streams[0];
+        assert_eq!(
+            stream.tags.get("handler_name").map(String::as_str),
+            Some("Sound")
+        );
+        assert_eq!(stream.tags.get("language").map(String::as_str), Some("eng"));
+    }
+
+    #[test]
+    fn hdr_side_data_is_summarized() {
+        let data: FfprobeOutput = serde_json::from_value(serde_json::json!({
+            "streams": [{
+                "codec_type": "video",
+                "codec_name": "hevc",
+                "color_transfer": "smpte2084",
+                "side_data_list": [
+                    { "side_data_type": "DOVI configuration record", "dv_profile": 8,
+                      "dv_level": 6, "dv_bl_signal_compatibility_id": 1 },
+                    { "side_data_type": "Mastering display metadata",
+                      "red_x": "34000/50000", "red_y": "16000/50000",
+                      "green_x": "13250/50000", "green_y": "34500/50000",
+                      "blue_x": "7500/50000", "blue_y": "3000/50000",
+                      "white_point_x": "15635/50000", "white_point_y": "16450/50000",
+                      "min_luminance": "50/10000", "max_luminance": "10000000/10000" },
+                    { "side_data_type": "Content light level metadata",
+                      "max_content": 1000, "max_average": 400 },
+                ],
+            }],
+        }))
+        .unwrap();
+
+        let mut color = summarize(&data).color.unwrap();
+        assert!(is_hdr(&color));
+        assert_eq!(
+            color.dolby_vision,
+            Some(DolbyVision {
+                profile: 8,
+                level: Some(6),
+                compatibility_id: Some(1),
+            })
+        );
+        let display = color.mastering_display.clone().unwrap();
+        assert_eq!(display.red, [0.68, 0.32]);
+        assert_eq!(display.white_point, [0.3127, 0.329]);
+        assert_eq!(
+            (display.min_luminance, display.max_luminance),
+            (0.005, 1000.0)
+        );
+        assert_eq!(
+            color.content_light,
+            Some(ContentLight {
+                max_cll: 1000,
+                max_fall: 400,
+            })
+        );
+        assert!(!color.hdr10_plus);
+
+        apply_side_data(
+            &mut color,
+            &[serde_json::json!({
+                "side_data_type": "HDR Dynamic Metadata SMPTE2094-40 (HDR10+)",
+            })],
+        );
+        assert!(color.hdr10_plus);
+    }
+
+    #[test]
+    fn idet_statistics_are_parsed() {
+        let log = "\
+[Parsed_idet_0 @ 0x1] Repeated Fields: Neither:   360 Top:   120 Bottom:   120
+[Parsed_idet_0 @ 0x1] Single frame detection: TFF:   300 BFF:     0 Progressive:   250 Undetermined:    50
+[Parsed_idet_0 @ 0x1] Multi frame detection: TFF:   330 BFF:     0 Progressive:   260 Undetermined:    10
+";
+        let interlacing = parse_idet(log).unwrap();
+        assert_eq!(interlacing.frames, 600);
+        assert_eq!(interlacing.interlaced, 0.55);
+        assert_eq!(interlacing.telecined, 1.0);
+        assert_eq!(interlacing.field_order.as_deref(), Some("tff"));
+
+        let progressive = parse_idet(
+            "[Parsed_idet_0 @ 0x1] Multi frame detection: TFF: 0 BFF: 0 Progressive: 600 Undetermined: 0",
+        )
+        .unwrap();
+        assert_eq!(progressive.interlaced, 0.0);
+        assert_eq!(progressive.telecined, 0.0);
+        assert_eq!(progressive.field_order, None);
+        assert_eq!(parse_idet("no statistics"), None);
+    }
+
+    #[test]
+    fn chapters_are_listed_with_their_titles() {
+        let data: FfprobeOutput = serde_json::from_value(serde_json::json!({
+            "chapters": [
+                { "start_time": "0.000000", "end_time": "61.500000", "tags": { "title": "Intro" } },
+                { "start_time": "61.500000", "end_time": "120.000000" },
+                { "start_time": "bad", "end_time": "130.000000" },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            list_chapters(&data.chapters),
+            vec![
+                ProbeChapter {
+                    start_sec: 0.0,
+                    end_sec: 61.5,
+                    title: Some("Intro".into()),
+                },
+                ProbeChapter {
+                    start_sec: 61.5,
+                    end_sec: 120.0,
+                    title: None,
+                },
+            ]
+        );
     }
 
     #[test]
@@ -850,8 +1878,10 @@ mod tests {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("invalid".to_string()),
+                ..Default::default()
             },
             streams: vec![],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -861,8 +1891,9 @@ mod tests {
     #[test]
     fn test_summarize_missing_duration() {
         let data = FfprobeOutput {
-            format: FfprobeFormat { duration: None },
+            format: FfprobeFormat::default(),
             streams: vec![],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -874,6 +1905,7 @@ mod tests {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("10.0".to_string()),
+                ..Default::default()
             },
             streams: vec![FfprobeStream {
                 codec_type: Some("video".into()),
@@ -882,17 +1914,60 @@ mod tests {
                 r_frame_rate: Some("60/1".into()),
                 ..Default::default()
             }],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
         assert_eq!(summary.fps, Some(60.0));
     }
 
+    #[test]
+    fn test_summarize_bit_depth_profile_and_level() {
+        let data = FfprobeOutput {
+            streams: vec![FfprobeStream {
+                codec_type: Some("video".into()),
+                codec_name: Some("hevc".into()),
+                pix_fmt: Some("yuv420p10le".into()),
+                profile: Some("Main 10".into()),
+                level: Some(153),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let summary = summarize(&data);
+        assert_eq!(summary.pix_fmt.as_deref(), Some("yuv420p10le"));
+        assert_eq!(summary.bit_depth, Some(10));
+        assert_eq!(summary.profile.as_deref(), Some("Main 10"));
+        assert_eq!(summary.level, Some(153));
+    }
+
+    #[test]
+    fn test_pix_fmt_bit_depth() {
+        for (pix_fmt, depth) in [
+            ("yuv420p", 8),
+            ("nv12", 8),
+            ("rgb24", 8),
+            ("yuv422p10le", 10),
+            ("gbrp12be", 12),
+            ("p010le", 10),
+            ("p016le", 16),
+            ("gray10le", 10),
+            ("x2rgb10le", 10),
+            ("rgb48le", 16),
+            ("rgba64be", 16),
+            ("rgb565le", 8),
+        ] {
+            assert_eq!(pix_fmt_bit_depth(pix_fmt), depth, "{pix_fmt}");
+        }
+    }
+
     #[test]
     fn test_summarize_partial_color_metadata() {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("10.0".to_string()),
+                ..Default::default()
             },
             streams: vec![FfprobeStream {
                 codec_type: Some("video".into()),
@@ -902,6 +1977,7 @@ mod tests {
                 color_space: None,
                 ..Default::default()
             }],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -916,6 +1992,7 @@ mod tests {
         let data = FfprobeOutput {
             format: FfprobeFormat {
                 duration: Some("10.0".to_string()),
+                ..Default::default()
             },
             streams: vec![FfprobeStream {
                 codec_type: Some("video".into()),
@@ -925,6 +2002,7 @@ mod tests {
                 color_space: None,
                 ..Default::default()
             }],
+            ..Default::default()
         };
 
         let summary = summarize(&data);
@@ -0,0 +1,205 @@
+//! Font discovery for libass subtitle burn-in.
+//!
+//! Static FFmpeg builds ship libass with fontconfig but no configuration, so
+//! on minimal systems (fresh Windows installs in particular) burned-in text
+//! falls back to tofu boxes. This module locates the platform font folders,
+//! prefers a bundled fallback font when one ships with the app, and writes a
+//! private `fonts.conf` that spawned ffmpeg processes pick up through
+//! `FONTCONFIG_FILE`, set on each command rather than on the app's own
+//! environment.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+const FONTCONFIG_ENV: &str = "FONTCONFIG_FILE";
+const FONTCONFIG_FILE_NAME: &str = "fonts.conf";
+const BUNDLED_FONT_DIR: &str = "fonts";
+
+/// The generated configuration, once `configure_fontconfig` has written it.
+static GENERATED_FONTCONFIG: OnceLock<PathBuf> = OnceLock::new();
+
+/// Returns the platform font directories that currently exist on disk.
+pub fn system_font_dirs() -> Vec<PathBuf> {
+    candidate_system_font_dirs()
+        .into_iter()
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Locates the fallback font directory shipped with the app, checking the
+/// development tree before the packaged resources like the binary resolver.
+pub fn bundled_font_dir(app: &AppHandle) -> Option<PathBuf> {
+    let dev_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(BUNDLED_FONT_DIR);
+    if dev_dir.is_dir() {
+        return Some(dev_dir);
+    }
+
+    app.path()
+        .resource_dir()
+        .ok()
+        .map(|dir| dir.join(BUNDLED_FONT_DIR))
+        .filter(|dir| dir.is_dir())
+}
+
+/// Directory handed to the `subtitles` filter via `fontsdir`, preferring the
+/// bundled fallback so rendering is identical across machines.
+pub fn preferred_fonts_dir(app: &AppHandle) -> Option<PathBuf> {
+    bundled_font_dir(app).or_else(|| system_font_dirs().into_iter().next())
+}
+
+/// Writes a fontconfig configuration when neither the user nor the system
+/// provides one; `apply_fontconfig` then points ffmpeg commands at it.
+pub fn configure_fontconfig(app: &AppHandle) {
+    if std::env::var_os(FONTCONFIG_ENV).is_some() || system_fontconfig_exists() {
+        return;
+    }
+
+    let Ok(cache_dir) = app.path().app_cache_dir() else {
+        return;
+    };
+
+    let mut dirs = system_font_dirs();
+    if let Some(bundled) = bundled_font_dir(app) {
+        dirs.insert(0, bundled);
+    }
+
+    match write_fontconfig(&cache_dir, &dirs) {
+        Ok(path) => {
+            let _ = GENERATED_FONTCONFIG.set(path);
+        },
+        Err(err) => eprintln!("[fonts] failed writing fontconfig file: {err}"),
+    }
+}
+
+/// Sets `FONTCONFIG_FILE` on `command` when a configuration was generated.
+pub fn apply_fontconfig(command: &mut Command) {
+    if let Some(path) = GENERATED_FONTCONFIG.get() {
+        command.env(FONTCONFIG_ENV, path);
+    }
+}
+
+fn write_fontconfig(cache_dir: &Path, font_dirs: &[PathBuf]) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(cache_dir)?;
+    let path = cache_dir.join(FONTCONFIG_FILE_NAME);
+    fs::write(
+        &path,
+        render_fontconfig(font_dirs, &cache_dir.join("fontconfig")),
+    )?;
+    Ok(path)
+}
+
+fn render_fontconfig(font_dirs: &[PathBuf], cache_dir: &Path) -> String {
+    let mut config = String::from(
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE fontconfig SYSTEM \"fonts.dtd\">\n<fontconfig>\n",
+    );
+    for dir in font_dirs {
+        config.push_str(&format!(
+            "  <dir>{}</dir>\n",
+            escape_xml(&dir.to_string_lossy())
+        ));
+    }
+    config.push_str(&format!(
+        "  <cachedir>{}</cachedir>\n</fontconfig>\n",
+        escape_xml(&cache_dir.to_string_lossy())
+    ));
+    config
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn system_fontconfig_exists() -> bool {
+    cfg!(unix) && Path::new("/etc/fonts/fonts.conf").is_file()
+}
+
+#[cfg(target_os = "macos")]
+fn candidate_system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/System/Library/Fonts"),
+        PathBuf::from("/Library/Fonts"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join("Library/Fonts"));
+    }
+    dirs
+}
+
+#[cfg(target_os = "windows")]
+fn candidate_system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+    dirs.push(PathBuf::from(windir).join("Fonts"));
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        dirs.push(PathBuf::from(local).join("Microsoft\\Windows\\Fonts"));
+    }
+    dirs
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn candidate_system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/fonts"),
+        PathBuf::from("/usr/local/share/fonts"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        dirs.push(home.join(".local/share/fonts"));
+        dirs.push(home.join(".fonts"));
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn candidate_dirs_cover_the_current_platform() {
+        assert!(!candidate_system_font_dirs().is_empty());
+    }
+
+    #[test]
+    fn renders_dirs_and_cache_into_fontconfig() {
+        let config = render_fontconfig(
+            &[PathBuf::from("/opt/fonts"), PathBuf::from("/srv/a&b")],
+            Path::new("/tmp/cache"),
+        );
+
+        assert!(config.contains("<dir>/opt/fonts</dir>"));
+        assert!(config.contains("<dir>/srv/a&amp;b</dir>"));
+        assert!(config.contains("<cachedir>/tmp/cache</cachedir>"));
+        assert!(config.trim_end().ends_with("</fontconfig>"));
+    }
+
+    #[test]
+    fn generated_fontconfig_is_set_per_command() {
+        let path = GENERATED_FONTCONFIG.get_or_init(|| PathBuf::from("/tmp/cache/fonts.conf"));
+        let mut command = Command::new("ffmpeg");
+        apply_fontconfig(&mut command);
+
+        assert!(command
+            .get_envs()
+            .any(|(name, value)| name == FONTCONFIG_ENV && value == Some(path.as_os_str())));
+        assert!(std::env::var_os(FONTCONFIG_ENV).is_none_or(|value| value != path.as_os_str()));
+    }
+
+    #[test]
+    fn writes_fontconfig_into_cache_dir() {
+        let temp = TempDir::new().unwrap();
+        let path = write_fontconfig(temp.path(), &[PathBuf::from("/fonts")]).unwrap();
+
+        assert_eq!(path, temp.path().join(FONTCONFIG_FILE_NAME));
+        assert!(fs::read_to_string(path)
+            .unwrap()
+            .contains("<dir>/fonts</dir>"));
+    }
+}
@@ -13,10 +13,10 @@
 use std::{
     collections::{HashSet, VecDeque},
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use crate::error::AppError;
+use crate::{cloud_files, error::AppError};
 
 /**
  * Expands a list of file and directory paths into a flat list of all files found.
@@ -71,6 +71,7 @@ use crate::error::AppError;
  * - Filesystem permission errors are ignored (paths skipped)
  * - Invalid UTF-8 paths are filtered out
  * - Empty strings in input are filtered out
+ * - iCloud stubs (`.name.icloud`) are listed as the file they stand in for
  *
  * This defensive approach ensures the function doesn't fail completely due to
  * individual problematic paths, allowing partial success when possible.
@@ -101,8 +102,12 @@ pub fn expand_media_paths(paths: Vec<String>) -> Result<Vec<String>, AppError> {
         // Check the path metadata to determine if it's a file or directory
         match fs::metadata(&current) {
             Ok(meta) if meta.is_file() => {
-                // It's a file, add it to our results
-                files.push(current);
+                // It's a file, add it to our results; an iCloud stub is listed
+                // as the file it stands in for, which probing then reports
+                match cloud_files::stub_target(&current) {
+                    Some(target) => files.push(target),
+                    None => files.push(current),
+                }
             },
             Ok(meta) if meta.is_dir() => {
                 // It's a directory, enqueue all its children for processing
@@ -134,11 +139,101 @@ pub fn expand_media_paths(paths: Vec<String>) -> Result<Vec<String>, AppError> {
     Ok(unique)
 }
 
+/**
+ * Returns the first path derived from `path` that does not exist yet.
+ *
+ * Numbered suffixes are inserted before the extension, Finder-style, so
+ * `clip.mp4` becomes `clip (1).mp4`, then `clip (2).mp4`, and so on. The
+ * original path is returned untouched when nothing occupies it.
+ */
+pub fn next_available_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|index| parent.join(format!("{stem} ({index}){extension}")))
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded suffix search always finds a free path")
+}
+
+/**
+ * Returns the form of `path` to hand to file system calls.
+ *
+ * Windows refuses paths longer than `MAX_PATH` unless they carry the `\\?\`
+ * extended-length prefix, which in turn turns off the normalisation of
+ * `/`, `.` and `..`. Long paths are therefore made absolute first and then
+ * prefixed, with UNC shares (`\\server\share`) written as
+ * `\\?\UNC\server\share`. Short paths and other platforms are unchanged.
+ */
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let extended = std::path::absolute(path)
+            .ok()
+            .and_then(|absolute| absolute.to_str().and_then(extended_length));
+        if let Some(extended) = extended {
+            return PathBuf::from(extended);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Length from which Windows paths get the extended-length prefix; directories
+/// are limited to `MAX_PATH` minus room for an 8.3 file name.
+#[cfg(any(windows, test))]
+const LONG_PATH_THRESHOLD: usize = 248;
+
+/**
+ * Prefixes an absolute Windows path with `\\?\` (or `\\?\UNC\` for
+ * shares) once it reaches `LONG_PATH_THRESHOLD`. Returns `None` when the
+ * path is short, already prefixed or not absolute.
+ */
+#[cfg(any(windows, test))]
+fn extended_length(path: &str) -> Option<String> {
+    if path.len() < LONG_PATH_THRESHOLD || path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{share}"));
+    }
+    let bytes = path.as_bytes();
+    let drive =
+        bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\';
+    drive.then(|| format!(r"\\?\{path}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn long_windows_paths_get_the_extended_length_prefix() {
+        let deep = "folder\\".repeat(40);
+        let drive = format!(r"C:\{deep}clip.mp4");
+        let share = format!(r"\\nas\media\{deep}clip.mp4");
+
+        assert_eq!(extended_length(&drive), Some(format!(r"\\?\{drive}")));
+        assert_eq!(
+            extended_length(&share),
+            Some(format!(r"\\?\UNC\nas\media\{deep}clip.mp4"))
+        );
+        assert_eq!(extended_length(&format!(r"\\?\{drive}")), None);
+        assert_eq!(extended_length(r"C:\Videos\clip.mp4"), None);
+        assert_eq!(extended_length(&format!("{deep}clip.mp4")), None);
+    }
+
     fn create_test_dir() -> std::io::Result<tempfile::TempDir> {
         tempfile::tempdir()
     }
@@ -320,6 +415,25 @@ mod tests {
         assert_eq!(result[0], file.to_str().unwrap());
     }
 
+    #[test]
+    fn test_expand_lists_icloud_stubs_as_their_files() {
+        let temp_dir = create_test_dir().unwrap();
+        fs::File::create(temp_dir.path().join(".clip.mov.icloud")).unwrap();
+
+        let result =
+            expand_media_paths(vec![temp_dir.path().to_str().unwrap().to_string()]).unwrap();
+
+        assert_eq!(
+            result,
+            vec![temp_dir
+                .path()
+                .join("clip.mov")
+                .to_str()
+                .unwrap()
+                .to_string()]
+        );
+    }
+
     #[test]
     fn test_expand_ignores_special_files() {
         let temp_dir = create_test_dir().unwrap();
@@ -333,4 +447,18 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert_eq!(result[0], regular_file.to_str().unwrap());
     }
+
+    #[test]
+    fn test_next_available_path_adds_numbered_suffix() {
+        let temp_dir = create_test_dir().unwrap();
+        let target = temp_dir.path().join("clip.mp4");
+        assert_eq!(next_available_path(&target), target);
+
+        fs::File::create(&target).unwrap();
+        fs::File::create(temp_dir.path().join("clip (1).mp4")).unwrap();
+        assert_eq!(
+            next_available_path(&target),
+            temp_dir.path().join("clip (2).mp4")
+        );
+    }
 }
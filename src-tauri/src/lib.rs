@@ -4,25 +4,44 @@
 //! services, keeping SOLID responsibilities intact and making future refactors
 //! (runner, licensing, etc.) simpler.
 
+mod api;
+mod app_mode;
 mod app_shell;
 mod binary_resolver;
+mod catalog;
+mod cloud_files;
 mod commands;
+mod contact_sheet;
 mod error;
 mod ffmpeg_capabilities;
 mod ffmpeg_probe;
+mod fonts;
 mod fs_utils;
 pub mod job_lifecycle;
 mod license;
+mod loudness;
+mod metrics;
+mod mp4_tags;
+mod persistence;
+mod planning;
+mod probe_batch;
+mod probe_report;
+mod remote_input;
 mod runner;
 mod services;
+mod sync_utils;
+mod watch_folders;
+mod waveform;
 
 pub use fs_utils::expand_media_paths;
 pub use runner::events::{CompletionPayload, ProgressMetrics, ProgressPayload};
+pub use runner::triage::Remediation;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let _ = dotenvy::dotenv();
     crate::app_shell::build_app()
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(crate::app_shell::handle_run_event);
 }
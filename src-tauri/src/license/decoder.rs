@@ -1,12 +1,21 @@
-use super::types::LicenseError;
-use super::types::{BASE32_ALPHABET, PAYLOAD_LENGTH};
+use super::types::{Entitlements, LicenseError};
+use super::types::{BASE32_ALPHABET, FEATURE_FLAGS, MAX_PAYLOAD_LENGTH, PAYLOAD_LENGTH};
 use uuid::Uuid;
 
+/// Record tags of a version-2 payload. Unknown tags are skipped, so later
+/// records don't break older apps.
+const TAG_FEATURE_FLAGS: u8 = 0x01;
+const TAG_MAX_BATCH_SIZE: u8 = 0x02;
+const TAG_EXPIRES_AT: u8 = 0x03;
+
 pub(super) struct ParsedPayload {
     pub license_id: Uuid,
     pub order_id: Uuid,
     pub max_major_version: u8,
     pub issued_at: u64,
+    pub entitlements: Option<Entitlements>,
+    /// Unix time a subscription license lapses; `None` for perpetual ones.
+    pub expires_at: Option<u64>,
 }
 
 pub(super) fn format_key(key: &str) -> String {
@@ -53,15 +62,20 @@ pub(super) fn decode_key(key: &str) -> Result<Vec<u8>, LicenseError> {
     Ok(output)
 }
 
+/// Parses a payload. Version 1 is exactly the fixed fields; version 2
+/// follows them with entitlement and expiry records of a tag byte, a length
+/// byte and that many value bytes.
 pub(super) fn parse_payload(bytes: &[u8]) -> Result<ParsedPayload, LicenseError> {
-    if bytes.len() != PAYLOAD_LENGTH {
-        return Err(LicenseError::InvalidLength);
-    }
-
-    let version = bytes[0];
-    if version != 1 {
-        return Err(LicenseError::UnsupportedVersion(version));
-    }
+    let version = *bytes.first().ok_or(LicenseError::InvalidLength)?;
+    let (entitlements, expires_at) = match version {
+        1 if bytes.len() == PAYLOAD_LENGTH => (None, None),
+        2 if (PAYLOAD_LENGTH..=MAX_PAYLOAD_LENGTH).contains(&bytes.len()) => {
+            let (entitlements, expires_at) = parse_records(&bytes[PAYLOAD_LENGTH..])?;
+            (Some(entitlements), expires_at)
+        },
+        1 | 2 => return Err(LicenseError::InvalidLength),
+        _ => return Err(LicenseError::UnsupportedVersion(version)),
+    };
 
     let license_id = Uuid::from_slice(&bytes[1..17])?;
     let order_id = Uuid::from_slice(&bytes[17..33])?;
@@ -76,9 +90,48 @@ pub(super) fn parse_payload(bytes: &[u8]) -> Result<ParsedPayload, LicenseError>
         order_id,
         max_major_version,
         issued_at,
+        entitlements,
+        expires_at,
     })
 }
 
+/// The entitlements and expiry time a version-2 payload's records carry.
+fn parse_records(mut records: &[u8]) -> Result<(Entitlements, Option<u64>), LicenseError> {
+    let mut entitlements = Entitlements::default();
+    let mut expires_at = None;
+    while let [tag, length, rest @ ..] = records {
+        let length = usize::from(*length);
+        if rest.len() < length {
+            return Err(LicenseError::InvalidLength);
+        }
+        let (value, remaining) = rest.split_at(length);
+        match *tag {
+            TAG_FEATURE_FLAGS => {
+                let flags = u32::from_be_bytes(fixed(value)?);
+                entitlements.features = FEATURE_FLAGS
+                    .iter()
+                    .filter(|(bit, _)| flags & bit != 0)
+                    .map(|(_, name)| name.to_string())
+                    .collect();
+            },
+            TAG_MAX_BATCH_SIZE => {
+                entitlements.max_batch_size = Some(u32::from_be_bytes(fixed(value)?));
+            },
+            TAG_EXPIRES_AT => expires_at = Some(u64::from_be_bytes(fixed(value)?)),
+            _ => {},
+        }
+        records = remaining;
+    }
+    if !records.is_empty() {
+        return Err(LicenseError::InvalidLength);
+    }
+    Ok((entitlements, expires_at))
+}
+
+fn fixed<const N: usize>(value: &[u8]) -> Result<[u8; N], LicenseError> {
+    value.try_into().map_err(|_| LicenseError::InvalidLength)
+}
+
 fn normalized_key(key: &str) -> String {
     key.chars()
         .filter(|ch| ch.is_ascii_alphanumeric())
@@ -155,4 +208,79 @@ mod tests {
         let result = parse_payload(&bytes);
         assert!(matches!(result, Err(LicenseError::InvalidLength)));
     }
+
+    fn v2_payload(records: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; PAYLOAD_LENGTH];
+        bytes[0] = 2;
+        bytes.extend_from_slice(records);
+        bytes
+    }
+
+    #[test]
+    fn parse_payload_reads_v2_entitlements() {
+        let parsed = parse_payload(&v2_payload(&[
+            TAG_FEATURE_FLAGS,
+            4,
+            0,
+            0,
+            0,
+            0b101,
+            0x7F,
+            2,
+            0xAA,
+            0xBB,
+            TAG_MAX_BATCH_SIZE,
+            4,
+            0,
+            0,
+            0,
+            50,
+        ]))
+        .unwrap();
+        let entitlements = parsed.entitlements.unwrap();
+        assert_eq!(entitlements.features, ["hw-encoding", "batch"]);
+        assert_eq!(entitlements.max_batch_size, Some(50));
+        assert_eq!(parsed.expires_at, None);
+
+        let subscription = parse_payload(&v2_payload(&[
+            TAG_EXPIRES_AT,
+            8,
+            0,
+            0,
+            0,
+            0,
+            0x65,
+            0x53,
+            0xF1,
+            0x00,
+        ]))
+        .unwrap();
+        assert_eq!(subscription.expires_at, Some(1_700_000_000));
+
+        let empty = parse_payload(&v2_payload(&[])).unwrap();
+        assert_eq!(empty.entitlements, Some(Entitlements::default()));
+        assert!(parse_payload(&[1; PAYLOAD_LENGTH])
+            .unwrap()
+            .entitlements
+            .is_none());
+    }
+
+    #[test]
+    fn parse_payload_rejects_malformed_v2_records() {
+        for records in [
+            &[TAG_FEATURE_FLAGS, 4, 0, 0][..],
+            &[TAG_MAX_BATCH_SIZE, 2, 0, 1][..],
+            &[TAG_FEATURE_FLAGS][..],
+            &[TAG_EXPIRES_AT, 4, 0, 0, 0, 1][..],
+        ] {
+            assert!(matches!(
+                parse_payload(&v2_payload(records)),
+                Err(LicenseError::InvalidLength)
+            ));
+        }
+        assert!(matches!(
+            parse_payload(&[3; PAYLOAD_LENGTH]),
+            Err(LicenseError::UnsupportedVersion(3))
+        ));
+    }
 }
@@ -0,0 +1,114 @@
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Hashed id of this machine, which binds an activation to it without
+/// revealing the id itself; `None` when the OS doesn't provide one.
+pub fn machine_fingerprint() -> Option<String> {
+    static FINGERPRINT: OnceLock<Option<String>> = OnceLock::new();
+    FINGERPRINT
+        .get_or_init(|| read_machine_id().map(|id| hash_machine_id(&id)))
+        .clone()
+}
+
+fn hash_machine_id(id: &str) -> String {
+    Sha256::digest(format!("honeymelon:{}", id.trim().to_ascii_lowercase()))
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn read_machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    parse_ioreg_uuid(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+fn read_machine_id() -> Option<String> {
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .output()
+        .ok()?;
+    parse_machine_guid(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn read_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+}
+
+/// `IOPlatformUUID` from `ioreg -rd1 -c IOPlatformExpertDevice`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ioreg_uuid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find(|line| line.contains("\"IOPlatformUUID\""))?
+        .split('=')
+        .nth(1)
+        .map(|value| value.trim().trim_matches('"').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// `MachineGuid` from `reg query HKLM\SOFTWARE\Microsoft\Cryptography`.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_machine_guid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::split_whitespace)
+        .find_map(
+            |mut fields| match (fields.next(), fields.next(), fields.next()) {
+                (Some("MachineGuid"), Some("REG_SZ"), Some(guid)) => Some(guid.to_string()),
+                _ => None,
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprints_are_stable_hashes() {
+        let fingerprint = hash_machine_id("3F2504E0-4F89-41D3-9A0C-0305E82C3301");
+        assert_eq!(fingerprint.len(), 64);
+        assert_eq!(
+            fingerprint,
+            hash_machine_id(" 3f2504e0-4f89-41d3-9a0c-0305e82c3301\n")
+        );
+        assert_ne!(fingerprint, hash_machine_id("another-machine"));
+    }
+
+    #[test]
+    fn parses_platform_ids() {
+        let ioreg = "+-o J314sAP  <class IOPlatformExpertDevice>\n\
+                     {\n\
+                       \"IOPlatformSerialNumber\" = \"C02XXXXX\"\n\
+                       \"IOPlatformUUID\" = \"3F2504E0-4F89-41D3-9A0C-0305E82C3301\"\n\
+                     }";
+        assert_eq!(
+            parse_ioreg_uuid(ioreg).as_deref(),
+            Some("3F2504E0-4F89-41D3-9A0C-0305E82C3301")
+        );
+        assert_eq!(parse_ioreg_uuid("{}"), None);
+
+        let reg = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Cryptography\r\n    \
+                   MachineGuid    REG_SZ    6f1c8a4e-29b1-4c9e-9a51-2d6f0e1b7c3a\r\n";
+        assert_eq!(
+            parse_machine_guid(reg).as_deref(),
+            Some("6f1c8a4e-29b1-4c9e-9a51-2d6f0e1b7c3a")
+        );
+        assert_eq!(parse_machine_guid("ERROR: not found"), None);
+    }
+}
@@ -0,0 +1,257 @@
+use super::types::LicenseError;
+
+/// Name the license is filed under in the credential store.
+const SERVICE: &str = "com.honeymelon.desktop.license";
+
+/// Account the license is filed under, for stores that key on one.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const ACCOUNT: &str = "license";
+
+/// A secret store holding the license document as one string.
+pub trait CredentialStore {
+    /// The stored document, or `None` when nothing is stored.
+    fn read(&self) -> Result<Option<String>, LicenseError>;
+    fn write(&self, secret: &str) -> Result<(), LicenseError>;
+    /// Removes the document; succeeds when nothing is stored.
+    fn delete(&self) -> Result<(), LicenseError>;
+}
+
+/// The OS credential store: the Keychain on macOS, Credential Manager on
+/// Windows and the Secret Service (through `secret-tool`) elsewhere.
+pub struct SystemKeychain;
+
+impl CredentialStore for SystemKeychain {
+    fn read(&self) -> Result<Option<String>, LicenseError> {
+        platform::read()
+    }
+
+    fn write(&self, secret: &str) -> Result<(), LicenseError> {
+        platform::write(secret)
+    }
+
+    fn delete(&self) -> Result<(), LicenseError> {
+        platform::delete()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{LicenseError, ACCOUNT, SERVICE};
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use std::io::Write;
+    use std::process::{Command, Output, Stdio};
+
+    /// `security`'s exit code when no such item exists.
+    const ITEM_NOT_FOUND: i32 = 44;
+
+    pub fn read() -> Result<Option<String>, LicenseError> {
+        let output = security(&["find-generic-password", "-s", SERVICE, "-a", ACCOUNT, "-w"])?;
+        match output.status.code() {
+            Some(0) => {
+                let encoded = String::from_utf8_lossy(&output.stdout);
+                let secret = BASE64
+                    .decode(encoded.trim())
+                    .map_err(|err| LicenseError::CredentialStore(err.to_string()))?;
+                String::from_utf8(secret)
+                    .map(Some)
+                    .map_err(|err| LicenseError::CredentialStore(err.to_string()))
+            },
+            Some(ITEM_NOT_FOUND) => Ok(None),
+            _ => Err(failure(&output)),
+        }
+    }
+
+    /// Runs `security` interactively so the secret goes through stdin, not
+    /// the process list. Base64 keeps it clear of the command's quoting.
+    pub fn write(secret: &str) -> Result<(), LicenseError> {
+        let command = format!(
+            "add-generic-password -U -s {SERVICE} -a {ACCOUNT} -w {}\n",
+            BASE64.encode(secret)
+        );
+        let mut child = Command::new("security")
+            .arg("-i")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| LicenseError::CredentialStore(err.to_string()))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(command.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        // `security -i` exits 0 even when a command fails, but reports it
+        if output.status.success() && output.stderr.is_empty() {
+            Ok(())
+        } else {
+            Err(failure(&output))
+        }
+    }
+
+    pub fn delete() -> Result<(), LicenseError> {
+        let output = security(&["delete-generic-password", "-s", SERVICE, "-a", ACCOUNT])?;
+        match output.status.code() {
+            Some(0 | ITEM_NOT_FOUND) => Ok(()),
+            _ => Err(failure(&output)),
+        }
+    }
+
+    fn security(args: &[&str]) -> Result<Output, LicenseError> {
+        Command::new("security")
+            .args(args)
+            .output()
+            .map_err(|err| LicenseError::CredentialStore(err.to_string()))
+    }
+
+    fn failure(output: &Output) -> LicenseError {
+        LicenseError::CredentialStore(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{LicenseError, SERVICE};
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_NOT_FOUND};
+    use windows_sys::Win32::Security::Credentials::{
+        CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+        CRED_TYPE_GENERIC,
+    };
+
+    pub fn read() -> Result<Option<String>, LicenseError> {
+        let target = target_name();
+        let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+        // SAFETY: `target` is NUL-terminated and `credential` receives a
+        // buffer owned by the system, freed below.
+        if unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) } == 0 {
+            return match unsafe { GetLastError() } {
+                ERROR_NOT_FOUND => Ok(None),
+                code => Err(failure(code)),
+            };
+        }
+        // SAFETY: a successful read leaves a valid credential whose blob is
+        // `CredentialBlobSize` bytes long.
+        let secret = unsafe {
+            let credential = &*credential;
+            std::slice::from_raw_parts(
+                credential.CredentialBlob,
+                credential.CredentialBlobSize as usize,
+            )
+            .to_vec()
+        };
+        // SAFETY: `credential` came from `CredReadW` and isn't used again.
+        unsafe { CredFree(credential.cast()) };
+        String::from_utf8(secret)
+            .map(Some)
+            .map_err(|err| LicenseError::CredentialStore(err.to_string()))
+    }
+
+    pub fn write(secret: &str) -> Result<(), LicenseError> {
+        let mut target = target_name();
+        let mut blob = secret.as_bytes().to_vec();
+        let credential = CREDENTIALW {
+            Type: CRED_TYPE_GENERIC,
+            TargetName: target.as_mut_ptr(),
+            CredentialBlobSize: blob.len() as u32,
+            CredentialBlob: blob.as_mut_ptr(),
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            // SAFETY: the remaining fields are integers and pointers for
+            // which zero means unset.
+            ..unsafe { std::mem::zeroed() }
+        };
+        // SAFETY: `credential` points at `target` and `blob`, which outlive
+        // the call.
+        if unsafe { CredWriteW(&credential, 0) } == 0 {
+            return Err(failure(unsafe { GetLastError() }));
+        }
+        Ok(())
+    }
+
+    pub fn delete() -> Result<(), LicenseError> {
+        let target = target_name();
+        // SAFETY: `target` is NUL-terminated.
+        if unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) } == 0 {
+            return match unsafe { GetLastError() } {
+                ERROR_NOT_FOUND => Ok(()),
+                code => Err(failure(code)),
+            };
+        }
+        Ok(())
+    }
+
+    fn target_name() -> Vec<u16> {
+        SERVICE.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn failure(code: u32) -> LicenseError {
+        LicenseError::CredentialStore(std::io::Error::from_raw_os_error(code as i32).to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::{LicenseError, ACCOUNT, SERVICE};
+    use std::io::{ErrorKind, Write};
+    use std::process::{Command, Output, Stdio};
+
+    const ATTRIBUTES: [&str; 4] = ["service", SERVICE, "account", ACCOUNT];
+
+    /// `secret-tool lookup` exits 1 with no output when nothing matches.
+    pub fn read() -> Result<Option<String>, LicenseError> {
+        let Some(output) = secret_tool("lookup")? else {
+            return Ok(None);
+        };
+        if output.status.success() {
+            return Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()));
+        }
+        if output.stderr.is_empty() {
+            return Ok(None);
+        }
+        Err(failure(&output))
+    }
+
+    /// The secret goes through stdin, not the process list.
+    pub fn write(secret: &str) -> Result<(), LicenseError> {
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label=Honeymelon license"])
+            .args(ATTRIBUTES)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| LicenseError::CredentialStore(err.to_string()))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(secret.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(failure(&output))
+        }
+    }
+
+    pub fn delete() -> Result<(), LicenseError> {
+        match secret_tool("clear")? {
+            Some(output) if !output.status.success() => Err(failure(&output)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs `secret-tool` on the license's attributes; `None` when it isn't
+    /// installed, so nothing can have been stored with it.
+    fn secret_tool(command: &str) -> Result<Option<Output>, LicenseError> {
+        match Command::new("secret-tool")
+            .arg(command)
+            .args(ATTRIBUTES)
+            .output()
+        {
+            Ok(output) => Ok(Some(output)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(LicenseError::CredentialStore(err.to_string())),
+        }
+    }
+
+    fn failure(output: &Output) -> LicenseError {
+        LicenseError::CredentialStore(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -2,10 +2,23 @@
 //! and storage layers for improved testability and SOLID alignment.
 
 mod decoder;
+mod fingerprint;
+mod keychain;
+mod server;
 mod storage;
+mod trial;
 mod types;
 mod verifier;
 
-pub use storage::{load, persist, remove};
-pub use types::LicenseInfo;
-pub use verifier::{activate_timestamp, verify};
+pub use fingerprint::machine_fingerprint;
+pub use storage::{
+    deactivate, migrate, persist, remove, revalidate_stored, LicenseCache, Revalidation,
+};
+pub use trial::{trial_status, TrialStatus};
+pub use types::{Deactivation, LicenseInfo};
+pub use verifier::{activate_online, activate_timestamp, verify};
+
+/// Whether a license server is configured for online activation.
+pub fn server_configured() -> bool {
+    server::server_config().is_some()
+}
@@ -0,0 +1,289 @@
+use super::types::{LicenseError, LicenseInfo};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Grace period used when `LICENSE_OFFLINE_GRACE_DAYS` isn't set.
+const DEFAULT_GRACE_DAYS: u64 = 14;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Statuses the server answers with when it refuses a license or an
+/// activation. Anything else that isn't a success, such as 404 from a
+/// misrouted proxy, 429 or a 5xx, means the server wasn't really reached.
+const REFUSAL_STATUSES: [u16; 7] = [400, 401, 402, 403, 409, 410, 422];
+
+/// Appended after the body so the status can be told apart from it.
+const STATUS_SEPARATOR: &str = "\nhoneymelon-http-status:";
+
+/// Where the license server is.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ServerConfig {
+    pub url: String,
+}
+
+/// Token the server issued for this installation, and when the server last
+/// confirmed it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerActivation {
+    pub token: String,
+    pub validated_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivationRequest<'a> {
+    key: &'a str,
+    license_id: &'a str,
+    /// Lets the server count the devices a license is bound to.
+    fingerprint: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidationRequest<'a> {
+    license_id: &'a str,
+    token: &'a str,
+    fingerprint: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct ActivationResponse {
+    token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeactivationResponse {
+    confirmation_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+/// The configured license server, or `None` when licenses are verified
+/// offline only. Read like the public key: runtime environment first, then
+/// the value baked in at build time.
+pub(super) fn server_config() -> Option<ServerConfig> {
+    let url = setting("LICENSE_SERVER_URL", option_env!("LICENSE_SERVER_URL"))?;
+    let url = url.trim().trim_end_matches('/').to_string();
+    if url.is_empty() {
+        return None;
+    }
+    Some(ServerConfig { url })
+}
+
+/// How long an activation stays valid without reaching the server.
+pub(super) fn grace_period_secs() -> u64 {
+    parse_grace_days(
+        setting(
+            "LICENSE_OFFLINE_GRACE_DAYS",
+            option_env!("LICENSE_OFFLINE_GRACE_DAYS"),
+        )
+        .as_deref(),
+    ) * SECONDS_PER_DAY
+}
+
+fn setting(name: &str, baked_in: Option<&str>) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .or_else(|| baked_in.map(str::to_string))
+}
+
+fn parse_grace_days(value: Option<&str>) -> u64 {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_GRACE_DAYS)
+}
+
+/// Registers this installation for `info` and returns the server's token.
+pub(super) fn activate(config: &ServerConfig, info: &LicenseInfo) -> Result<String, LicenseError> {
+    let response: ActivationResponse = post(
+        &format!("{}/activate", config.url),
+        &ActivationRequest {
+            key: &info.key,
+            license_id: &info.license_id,
+            fingerprint: info.machine_fingerprint.as_deref(),
+        },
+    )?;
+    Ok(response.token)
+}
+
+/// Asks the server whether the activation still stands.
+pub(super) fn validate(
+    config: &ServerConfig,
+    info: &LicenseInfo,
+    activation: &ServerActivation,
+) -> Result<(), LicenseError> {
+    post::<serde_json::Value>(
+        &format!("{}/validate", config.url),
+        &ValidationRequest {
+            license_id: &info.license_id,
+            token: &activation.token,
+            fingerprint: info.machine_fingerprint.as_deref(),
+        },
+    )
+    .map(|_| ())
+}
+
+/// Releases the activation's seat and returns the server's confirmation
+/// code, when it sends one.
+pub(super) fn deactivate(
+    config: &ServerConfig,
+    info: &LicenseInfo,
+    activation: &ServerActivation,
+) -> Result<Option<String>, LicenseError> {
+    let response: DeactivationResponse = post(
+        &format!("{}/deactivate", config.url),
+        &ValidationRequest {
+            license_id: &info.license_id,
+            token: &activation.token,
+            fingerprint: info.machine_fingerprint.as_deref(),
+        },
+    )?;
+    Ok(response.confirmation_code)
+}
+
+/// POSTs `body` as JSON with curl. The body goes through stdin so the key
+/// never appears in the process list.
+fn post<T: DeserializeOwned>(url: &str, body: &impl Serialize) -> Result<T, LicenseError> {
+    let body = serde_json::to_vec(body)?;
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--connect-timeout",
+            "10",
+            "--max-time",
+            "30",
+            "--request",
+            "POST",
+            "--header",
+            "Content-Type: application/json",
+            "--header",
+            "Accept: application/json",
+            "--data-binary",
+            "@-",
+            "--write-out",
+        ])
+        .arg(format!("{STATUS_SEPARATOR}%{{http_code}}"))
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| {
+            LicenseError::ServerUnreachable(format!("failed to start curl: {error}"))
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&body)?;
+    }
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let (body, status) = split_status(&stdout);
+    classify(output.status.code(), status, body, &stderr)?;
+    serde_json::from_str(body).map_err(Into::into)
+}
+
+/// Splits curl's output into the body and the status `--write-out` added.
+fn split_status(stdout: &str) -> (&str, Option<u16>) {
+    match stdout.rsplit_once(STATUS_SEPARATOR) {
+        Some((body, status)) => (body, status.trim().parse().ok()),
+        None => (stdout, None),
+    }
+}
+
+/// Tells a refusal by the server apart from not reaching it, which only
+/// counts against the grace period. Only the statuses in
+/// `REFUSAL_STATUSES` count as refusals.
+fn classify(
+    exit_code: Option<i32>,
+    status: Option<u16>,
+    body: &str,
+    stderr: &str,
+) -> Result<(), LicenseError> {
+    if exit_code != Some(0) {
+        return Err(LicenseError::ServerUnreachable(stderr.trim().to_string()));
+    }
+    match status {
+        Some(200..=299) => Ok(()),
+        Some(status) if REFUSAL_STATUSES.contains(&status) => Err(LicenseError::ServerRejected(
+            serde_json::from_str::<ErrorResponse>(body)
+                .map(|response| response.message)
+                .unwrap_or_else(|_| format!("HTTP {status}")),
+        )),
+        Some(status) => Err(LicenseError::ServerUnreachable(format!(
+            "license server answered HTTP {status}"
+        ))),
+        None => Err(LicenseError::ServerUnreachable(
+            "license server sent no HTTP status".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grace_days_default_when_unset_or_invalid() {
+        assert_eq!(parse_grace_days(None), DEFAULT_GRACE_DAYS);
+        assert_eq!(parse_grace_days(Some("soon")), DEFAULT_GRACE_DAYS);
+        assert_eq!(parse_grace_days(Some(" 30 ")), 30);
+        assert_eq!(parse_grace_days(Some("0")), 0);
+    }
+
+    #[test]
+    fn classifies_curl_outcomes() {
+        assert!(classify(Some(0), Some(200), "{}", "").is_ok());
+
+        let rejected = classify(
+            Some(0),
+            Some(403),
+            r#"{"message":"Seat limit reached"}"#,
+            "",
+        );
+        assert_eq!(
+            rejected.unwrap_err().to_string(),
+            "license server refused the license: Seat limit reached"
+        );
+        assert!(matches!(
+            classify(Some(0), Some(410), "<html>", ""),
+            Err(LicenseError::ServerRejected(message)) if message == "HTTP 410"
+        ));
+
+        let unreachable = classify(Some(6), None, "", "curl: (6) Could not resolve host\n");
+        assert_eq!(
+            unreachable.unwrap_err().code(),
+            "license_server_unreachable"
+        );
+        assert!(matches!(
+            classify(None, None, "", ""),
+            Err(LicenseError::ServerUnreachable(_))
+        ));
+    }
+
+    #[test]
+    fn outages_and_misroutes_are_not_refusals() {
+        for status in [404, 429, 500, 502, 503, 301] {
+            assert!(
+                matches!(
+                    classify(Some(0), Some(status), r#"{"message":"down"}"#, ""),
+                    Err(LicenseError::ServerUnreachable(_))
+                ),
+                "HTTP {status}"
+            );
+        }
+    }
+
+    #[test]
+    fn splits_the_status_from_the_body() {
+        let stdout = format!("{{\"token\":\"t\"}}{STATUS_SEPARATOR}201");
+        assert_eq!(split_status(&stdout), ("{\"token\":\"t\"}", Some(201)));
+        assert_eq!(split_status("garbage"), ("garbage", None));
+    }
+}
@@ -1,12 +1,31 @@
-use super::types::{LicenseError, LicenseInfo, LICENSE_FILE_NAME};
-use super::verifier::verify;
+use super::fingerprint::machine_fingerprint;
+use super::keychain::{CredentialStore, SystemKeychain};
+use super::server::grace_period_secs;
+use super::types::{Deactivation, LicenseError, LicenseInfo, LICENSE_FILE_NAME, TRIAL_FILE_NAME};
+use super::verifier::{
+    activate_timestamp, check_activation, check_machine, deactivate_online, revalidate, verify,
+};
+use crate::{persistence, sync_utils::MutexExt};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 
 /// Abstraction for resolving where license data should be stored.
 pub trait LicensePathProvider {
     fn license_store_path(&self) -> Result<PathBuf, LicenseError>;
+
+    /// Credential store to keep the license in. When it works, the license
+    /// file is only read as a fallback until `migrate` moves it over.
+    fn credential_store(&self) -> Option<&dyn CredentialStore> {
+        None
+    }
+
+    /// The trial record lives next to the license file.
+    fn trial_store_path(&self) -> Result<PathBuf, LicenseError> {
+        self.license_store_path()
+            .map(|path| path.with_file_name(TRIAL_FILE_NAME))
+    }
 }
 
 impl LicensePathProvider for AppHandle {
@@ -16,42 +35,195 @@ impl LicensePathProvider for AppHandle {
             .map_err(|_| LicenseError::StoragePath)
             .map(|dir| dir.join(LICENSE_FILE_NAME))
     }
+
+    fn credential_store(&self) -> Option<&dyn CredentialStore> {
+        Some(&SystemKeychain)
+    }
 }
 
+/// Stores the license in the credential store, or in the license file when
+/// there is none or it fails, such as Linux without a Secret Service.
 pub fn persist(
     provider: &impl LicensePathProvider,
     info: &LicenseInfo,
 ) -> Result<(), LicenseError> {
+    if store_credential(provider, info)? {
+        return Ok(());
+    }
+    // Kept as plain JSON rather than a versioned document so older releases
+    // can still read it after a downgrade.
     let path = provider.license_store_path()?;
+    persistence::write_atomic(&path, &serde_json::to_vec_pretty(info)?)?;
+    Ok(())
+}
 
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
+/// Writes `info` to the credential store and deletes the license file.
+/// `false` when there is no store or writing to it failed.
+fn store_credential(
+    provider: &impl LicensePathProvider,
+    info: &LicenseInfo,
+) -> Result<bool, LicenseError> {
+    let Some(store) = provider.credential_store() else {
+        return Ok(false);
+    };
+    if let Err(err) = store.write(&serde_json::to_string(info)?) {
+        eprintln!("[license] keeping the license file; credential store failed: {err}");
+        return Ok(false);
     }
+    remove_file(&provider.license_store_path()?)?;
+    Ok(true)
+}
 
-    let data = serde_json::to_vec_pretty(info)?;
-    fs::write(&path, data)?;
+/// Moves a license found only in the license file into the credential
+/// store. Run at startup, outside read-only mode.
+pub fn migrate(provider: &impl LicensePathProvider) -> Result<(), LicenseError> {
+    if provider.credential_store().is_none() {
+        return Ok(());
+    }
+    if let Some(info) = read_file(&provider.license_store_path()?)? {
+        store_credential(provider, &info)?;
+    }
     Ok(())
 }
 
-pub fn load(provider: &impl LicensePathProvider) -> Result<Option<LicenseInfo>, LicenseError> {
-    let path = provider.license_store_path()?;
+/// The stored license, unverified; reading never writes.
+fn load_stored(provider: &impl LicensePathProvider) -> Result<Option<LicenseInfo>, LicenseError> {
+    if let Some(store) = provider.credential_store() {
+        match store.read() {
+            Ok(Some(secret)) => return Ok(Some(serde_json::from_str(&secret)?)),
+            Ok(None) => {},
+            Err(err) => {
+                eprintln!("[license] reading the license file; credential store failed: {err}")
+            },
+        }
+    }
 
-    if !path.exists() {
-        return Ok(None);
+    read_file(&provider.license_store_path()?)
+}
+
+fn read_file(path: &Path) -> Result<Option<LicenseInfo>, LicenseError> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
     }
+}
 
-    let data = fs::read(&path)?;
-    let stored: LicenseInfo = serde_json::from_slice(&data)?;
+fn checked(verified: LicenseInfo) -> Result<LicenseInfo, LicenseError> {
+    check_machine(&verified, machine_fingerprint().as_deref())?;
+    check_activation(&verified, activate_timestamp(), grace_period_secs())?;
+    Ok(verified)
+}
+
+/// The verified license as last read from storage, so frequent checks such
+/// as entitlements don't go to the credential store each time. Whoever
+/// changes the stored license calls `forget`.
+#[derive(Default)]
+pub struct LicenseCache(Mutex<Option<Option<LicenseInfo>>>);
+
+impl LicenseCache {
+    /// Loads and re-verifies the stored license, reading storage only when
+    /// nothing is cached. A lapsed subscription loads with `expired` set.
+    /// The checks still run every time: a license bound to another machine
+    /// fails with `MachineMismatch`, and an online activation the server
+    /// hasn't confirmed within the grace period with `ActivationExpired`.
+    pub fn load(
+        &self,
+        provider: &impl LicensePathProvider,
+    ) -> Result<Option<LicenseInfo>, LicenseError> {
+        let verified = {
+            let mut cached = self.0.lock_or_recover();
+            match &*cached {
+                Some(verified) => verified.clone(),
+                None => cached.insert(load_verified(provider)?).clone(),
+            }
+        };
+        verified.map(checked).transpose()
+    }
+
+    pub fn forget(&self) {
+        *self.0.lock_or_recover() = None;
+    }
+}
+
+fn load_verified(provider: &impl LicensePathProvider) -> Result<Option<LicenseInfo>, LicenseError> {
+    let Some(stored) = load_stored(provider)? else {
+        return Ok(None);
+    };
 
     let mut verified = verify(&stored.key)?;
     verified.activated_at = stored.activated_at;
+    verified.server_activation = stored.server_activation;
+    verified.machine_fingerprint = stored.machine_fingerprint;
 
     Ok(Some(verified))
 }
 
+/// What a background revalidation found.
+#[derive(Debug)]
+pub enum Revalidation {
+    /// No license is stored.
+    NoLicense,
+    /// The server confirmed the license; the new time is stored.
+    Confirmed(Box<LicenseInfo>),
+    /// The server refused the license, which was removed.
+    Revoked(String),
+    /// The server couldn't be reached; `degraded` once the grace period
+    /// has run out.
+    Offline { degraded: bool },
+}
+
+/// Has the license server confirm the stored license.
+pub fn revalidate_stored(
+    provider: &impl LicensePathProvider,
+    now: u64,
+) -> Result<Revalidation, LicenseError> {
+    let Some(mut info) = load_verified(provider)? else {
+        return Ok(Revalidation::NoLicense);
+    };
+    match revalidate(&mut info, now) {
+        Ok(()) => {
+            persist(provider, &info)?;
+            Ok(Revalidation::Confirmed(Box::new(info)))
+        },
+        Err(LicenseError::ServerRejected(reason)) => {
+            remove(provider)?;
+            Ok(Revalidation::Revoked(reason))
+        },
+        Err(LicenseError::ServerUnreachable(_)) => Ok(Revalidation::Offline {
+            degraded: check_activation(&info, now, grace_period_secs()).is_err(),
+        }),
+        Err(err) => Err(err),
+    }
+}
+
+/// Deactivates the stored license so it can be activated on another
+/// machine: releases the seat with the server when asked, then removes the
+/// license. When the server can't be reached the license stays stored.
+pub fn deactivate(
+    provider: &impl LicensePathProvider,
+    notify_server: bool,
+    now: u64,
+) -> Result<Deactivation, LicenseError> {
+    let info = load_verified(provider)?.ok_or(LicenseError::NotActivated)?;
+    let deactivation = deactivate_online(&info, notify_server, now)?;
+    remove(provider)?;
+    Ok(deactivation)
+}
+
+/// Removes the license from the credential store and the license file. A
+/// failing store, such as Linux without a Secret Service, only holds a
+/// license if writing to it once worked, so the file is removed regardless.
 pub fn remove(provider: &impl LicensePathProvider) -> Result<(), LicenseError> {
-    let path = provider.license_store_path()?;
+    if let Some(store) = provider.credential_store() {
+        if let Err(err) = store.delete() {
+            eprintln!("[license] removing the license file; credential store failed: {err}");
+        }
+    }
+    remove_file(&provider.license_store_path()?)
+}
 
+fn remove_file(path: &Path) -> Result<(), LicenseError> {
     match fs::remove_file(path) {
         Ok(_) => Ok(()),
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
@@ -65,9 +237,14 @@ mod tests {
     use base64::engine::general_purpose::STANDARD as BASE64;
     use base64::Engine;
     use ed25519_dalek::{SecretKey, Signer, SigningKey};
+    use std::sync::Mutex;
     use tempfile::TempDir;
     use uuid::Uuid;
 
+    fn load(provider: &impl LicensePathProvider) -> Result<Option<LicenseInfo>, LicenseError> {
+        LicenseCache::default().load(provider)
+    }
+
     #[derive(Clone)]
     struct TempProvider {
         path: PathBuf,
@@ -79,6 +256,52 @@ mod tests {
         }
     }
 
+    /// Credential store kept in memory; `None` inside makes it fail.
+    struct MemoryStore(Mutex<Option<Option<String>>>);
+
+    impl CredentialStore for MemoryStore {
+        fn read(&self) -> Result<Option<String>, LicenseError> {
+            self.0
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or_else(|| LicenseError::CredentialStore("locked".into()))
+        }
+
+        fn write(&self, secret: &str) -> Result<(), LicenseError> {
+            let mut stored = self.0.lock().unwrap();
+            let slot = stored
+                .as_mut()
+                .ok_or_else(|| LicenseError::CredentialStore("locked".into()))?;
+            *slot = Some(secret.to_string());
+            Ok(())
+        }
+
+        fn delete(&self) -> Result<(), LicenseError> {
+            let mut stored = self.0.lock().unwrap();
+            let slot = stored
+                .as_mut()
+                .ok_or_else(|| LicenseError::CredentialStore("locked".into()))?;
+            *slot = None;
+            Ok(())
+        }
+    }
+
+    struct KeychainProvider {
+        path: PathBuf,
+        store: MemoryStore,
+    }
+
+    impl LicensePathProvider for KeychainProvider {
+        fn license_store_path(&self) -> Result<PathBuf, LicenseError> {
+            Ok(self.path.clone())
+        }
+
+        fn credential_store(&self) -> Option<&dyn CredentialStore> {
+            Some(&self.store)
+        }
+    }
+
     fn encode_base32(bytes: &[u8]) -> String {
         let mut buffer: u32 = 0;
         let mut bits = 0;
@@ -157,8 +380,87 @@ mod tests {
         assert_eq!(loaded.license_id, info.license_id);
         assert_eq!(loaded.activated_at, info.activated_at);
 
+        let raw: serde_json::Value =
+            serde_json::from_slice(&fs::read(&provider.path).unwrap()).unwrap();
+        assert_eq!(raw["key"], info.key.as_str());
+
         remove(&provider).expect("remove succeeds");
         let missing = load(&provider).expect("load without file");
         assert!(missing.is_none());
     }
+
+    #[test]
+    fn migrates_the_license_file_into_the_credential_store() {
+        let (info, file_provider) = make_license_info();
+        persist(&file_provider, &info).expect("persist to file");
+        let provider = KeychainProvider {
+            path: file_provider.path.clone(),
+            store: MemoryStore(Mutex::new(Some(None))),
+        };
+
+        let loaded = load(&provider).expect("load ok").expect("license exists");
+        assert_eq!(loaded.key, info.key);
+        assert!(provider.path.exists(), "loading never writes");
+        assert!(provider.store.read().unwrap().is_none());
+
+        migrate(&provider).expect("migrates");
+        assert!(!provider.path.exists());
+        assert!(provider.store.read().unwrap().is_some());
+        assert_eq!(load(&provider).unwrap().unwrap().key, info.key);
+
+        remove(&provider).expect("remove succeeds");
+        assert!(provider.store.read().unwrap().is_none());
+        assert!(load(&provider).expect("load ok").is_none());
+    }
+
+    #[test]
+    fn falls_back_to_the_file_without_a_working_store() {
+        let (info, file_provider) = make_license_info();
+        let provider = KeychainProvider {
+            path: file_provider.path.clone(),
+            store: MemoryStore(Mutex::new(None)),
+        };
+
+        persist(&provider, &info).expect("persist succeeds");
+        assert!(provider.path.exists());
+        assert_eq!(load(&provider).unwrap().unwrap().key, info.key);
+
+        migrate(&provider).expect("a failing store leaves the file alone");
+        assert!(provider.path.exists());
+
+        remove(&provider).expect("a failing store doesn't block removal");
+        assert!(!provider.path.exists());
+        assert!(load(&provider).unwrap().is_none());
+    }
+
+    #[test]
+    fn the_cache_reads_storage_until_forgotten() {
+        let (info, provider) = make_license_info();
+        let cache = LicenseCache::default();
+        assert!(cache.load(&provider).unwrap().is_none());
+
+        persist(&provider, &info).expect("persist succeeds");
+        assert!(cache.load(&provider).unwrap().is_none());
+
+        cache.forget();
+        assert_eq!(cache.load(&provider).unwrap().unwrap().key, info.key);
+        remove(&provider).expect("remove succeeds");
+        assert_eq!(cache.load(&provider).unwrap().unwrap().key, info.key);
+    }
+
+    #[test]
+    fn deactivate_removes_the_license() {
+        let (info, provider) = make_license_info();
+        assert!(matches!(
+            deactivate(&provider, true, 1_700_000_100),
+            Err(LicenseError::NotActivated)
+        ));
+
+        persist(&provider, &info).expect("persist succeeds");
+        let deactivation = deactivate(&provider, true, 1_700_000_100).expect("deactivates");
+        assert_eq!(deactivation.license_id, info.license_id);
+        assert_eq!(deactivation.deactivated_at, 1_700_000_100);
+        assert!(!deactivation.server_notified);
+        assert!(load(&provider).expect("load without file").is_none());
+    }
 }
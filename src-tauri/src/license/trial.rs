@@ -0,0 +1,164 @@
+use super::storage::LicensePathProvider;
+use super::types::{LicenseError, TRIAL_DAYS};
+use super::verifier::verify_trial;
+use crate::persistence::{self, DocumentSchema};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+const TRIAL_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "trial",
+    migrations: &[],
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The trial started on first run, signed so edits to its dates are caught.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrialRecord {
+    pub started_at: u64,
+    pub expires_at: u64,
+    /// Base64 HMAC-SHA256 of the dates.
+    pub signature: String,
+}
+
+impl TrialRecord {
+    pub(super) fn start(now: u64) -> Self {
+        let expires_at = now + TRIAL_DAYS * SECONDS_PER_DAY;
+        Self {
+            started_at: now,
+            expires_at,
+            signature: sign(now, expires_at),
+        }
+    }
+}
+
+/// Where the trial stands, for gating premium features.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrialStatus {
+    pub started_at: u64,
+    pub expires_at: u64,
+    /// Whole or partial days left; 0 once expired.
+    pub days_remaining: u64,
+    pub expired: bool,
+}
+
+impl TrialStatus {
+    fn new(record: &TrialRecord, now: u64, expired: bool) -> Self {
+        let days_remaining = if expired {
+            0
+        } else {
+            (record.expires_at - now).div_ceil(SECONDS_PER_DAY)
+        };
+        Self {
+            started_at: record.started_at,
+            expires_at: record.expires_at,
+            days_remaining,
+            expired,
+        }
+    }
+}
+
+/// Reports the trial, starting it when no record exists yet.
+///
+/// An expired trial is reported rather than returned as an error; a record
+/// whose signature doesn't match, or that starts after `now`, fails with
+/// `LicenseError::TrialInvalid`.
+pub fn trial_status(
+    provider: &impl LicensePathProvider,
+    now: u64,
+) -> Result<TrialStatus, LicenseError> {
+    let path = provider.trial_store_path()?;
+    let stored: Option<TrialRecord> = persistence::load(&path, &TRIAL_SCHEMA)
+        .map_err(|err| LicenseError::Persistence(err.message))?;
+    let record = match stored {
+        Some(record) => record,
+        None => {
+            let record = TrialRecord::start(now);
+            persistence::persist(&path, &TRIAL_SCHEMA, &record)
+                .map_err(|err| LicenseError::Persistence(err.message))?;
+            record
+        },
+    };
+
+    match verify_trial(&record, now) {
+        Ok(()) => Ok(TrialStatus::new(&record, now, false)),
+        Err(LicenseError::TrialExpired) => Ok(TrialStatus::new(&record, now, true)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Signature over a trial's dates.
+///
+/// The key is compiled in (`TRIAL_SIGNING_SECRET`, required by `build.rs`
+/// for release builds), so this catches edits to the record, not a
+/// determined attacker; deleting the record starts a new trial.
+pub(super) fn sign(started_at: u64, expires_at: u64) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(env!("TRIAL_SIGNING_SECRET").as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("trial:v1:{started_at}:{expires_at}").as_bytes());
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    const NOW: u64 = 1_700_000_000;
+
+    struct TempProvider {
+        dir: TempDir,
+    }
+
+    impl LicensePathProvider for TempProvider {
+        fn license_store_path(&self) -> Result<PathBuf, LicenseError> {
+            Ok(self.dir.path().join("license.json"))
+        }
+    }
+
+    #[test]
+    fn first_run_starts_the_trial() {
+        let provider = TempProvider {
+            dir: TempDir::new().unwrap(),
+        };
+
+        let status = trial_status(&provider, NOW).unwrap();
+        assert_eq!(status.started_at, NOW);
+        assert_eq!(status.days_remaining, TRIAL_DAYS);
+        assert!(!status.expired);
+
+        let later = trial_status(&provider, NOW + SECONDS_PER_DAY + 1).unwrap();
+        assert_eq!(later.started_at, NOW);
+        assert_eq!(later.days_remaining, TRIAL_DAYS - 1);
+
+        let expired = trial_status(&provider, status.expires_at).unwrap();
+        assert!(expired.expired);
+        assert_eq!(expired.days_remaining, 0);
+    }
+
+    #[test]
+    fn edited_records_are_rejected() {
+        let provider = TempProvider {
+            dir: TempDir::new().unwrap(),
+        };
+        let mut record = TrialRecord::start(NOW);
+        record.expires_at += 365 * SECONDS_PER_DAY;
+        persistence::persist(
+            &provider.trial_store_path().unwrap(),
+            &TRIAL_SCHEMA,
+            &record,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            trial_status(&provider, NOW),
+            Err(LicenseError::TrialInvalid)
+        ));
+    }
+}
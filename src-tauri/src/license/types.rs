@@ -1,3 +1,4 @@
+use super::server::ServerActivation;
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -8,12 +9,38 @@ pub const SIGNATURE_LENGTH: usize = 64;
 /** Filename for license storage in application config directory */
 pub const LICENSE_FILE_NAME: &str = "license.json";
 
+/** Filename for the trial record, next to the license file */
+pub const TRIAL_FILE_NAME: &str = "trial.json";
+
+/** Length of the trial started on first run, in days */
+pub const TRIAL_DAYS: u64 = 14;
+
 /** Custom Base32 alphabet excluding ambiguous characters (I, O, 0, 1) */
 pub const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 
 /** Total length of license payload in bytes */
 pub const PAYLOAD_LENGTH: usize = 42;
 
+/** Longest version-2 payload accepted: the fixed fields plus entitlement records */
+pub const MAX_PAYLOAD_LENGTH: usize = 512;
+
+/** Entitlement flag bits of a version-2 payload and the features they grant */
+pub const FEATURE_FLAGS: &[(u32, &str)] = &[
+    (1 << 0, "hw-encoding"),
+    (1 << 1, "watch-folders"),
+    (1 << 2, "batch"),
+];
+
+/** Features and limits a version-2 license grants. */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entitlements {
+    pub features: Vec<String>,
+    /** Most files in one batch; `None` means unlimited */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_batch_size: Option<u32>,
+}
+
 /** Complete license information structure for application use. */
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -26,6 +53,49 @@ pub struct LicenseInfo {
     pub payload: String,
     pub signature: String,
     pub activated_at: Option<u64>,
+    /** Set when the license was activated against the license server */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_activation: Option<ServerActivation>,
+    /** Hashed id of the machine the license was activated on */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_fingerprint: Option<String>,
+    /** Per-feature grants; `None` for version-1 licenses, which grant everything */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entitlements: Option<Entitlements>,
+    /** Unix time a subscription license lapses; `None` for perpetual licenses */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /** Whether `expires_at` had passed when the license was last verified */
+    #[serde(default)]
+    pub expired: bool,
+}
+
+impl LicenseInfo {
+    /**
+     * Whether the license grants `feature`, one of the `FEATURE_FLAGS` names.
+     * An expired subscription grants nothing until it is renewed.
+     */
+    pub fn has_entitlement(&self, feature: &str) -> bool {
+        !self.expired
+            && self.entitlements.as_ref().is_none_or(|entitlements| {
+                entitlements
+                    .features
+                    .iter()
+                    .any(|granted| granted == feature)
+            })
+    }
+}
+
+/** Outcome of deactivating the license, shown to the user when moving a seat. */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deactivation {
+    pub license_id: String,
+    /** Code to quote when activating elsewhere or contacting support */
+    pub confirmation_code: String,
+    pub deactivated_at: u64,
+    /** Whether the license server released the seat */
+    pub server_notified: bool,
 }
 
 /** Comprehensive error types for license validation and management. */
@@ -47,8 +117,26 @@ pub enum LicenseError {
     InvalidPublicKey(String),
     #[error("signature verification failed")]
     InvalidSignature,
+    #[error("trial record is invalid")]
+    TrialInvalid,
+    #[error("trial period has expired")]
+    TrialExpired,
+    #[error("license server could not be reached: {0}")]
+    ServerUnreachable(String),
+    #[error("license server refused the license: {0}")]
+    ServerRejected(String),
+    #[error("license could not be revalidated within the offline grace period")]
+    ActivationExpired,
+    #[error("license was activated on another machine")]
+    MachineMismatch,
+    #[error("no license is activated")]
+    NotActivated,
     #[error("unable to determine license storage path")]
     StoragePath,
+    #[error("license storage failed: {0}")]
+    Persistence(String),
+    #[error("credential store failed: {0}")]
+    CredentialStore(String),
     #[error(transparent)]
     Uuid(#[from] uuid::Error),
     #[error(transparent)]
@@ -71,7 +159,17 @@ impl LicenseError {
                 "license_public_key"
             },
             LicenseError::InvalidSignature => "license_signature",
-            LicenseError::StoragePath | LicenseError::Io(_) => "license_storage",
+            LicenseError::TrialInvalid => "license_trial_invalid",
+            LicenseError::TrialExpired => "license_trial_expired",
+            LicenseError::ServerUnreachable(_) => "license_server_unreachable",
+            LicenseError::ServerRejected(_) => "license_server_rejected",
+            LicenseError::ActivationExpired => "license_activation_expired",
+            LicenseError::MachineMismatch => "license_machine_mismatch",
+            LicenseError::NotActivated => "license_not_activated",
+            LicenseError::StoragePath
+            | LicenseError::Persistence(_)
+            | LicenseError::CredentialStore(_)
+            | LicenseError::Io(_) => "license_storage",
             LicenseError::Serialization(_) => "license_serialization",
             LicenseError::Uuid(_) => "license_uuid",
         }
@@ -96,6 +194,7 @@ mod tests {
             "license_invalid_char"
         );
         assert_eq!(LicenseError::InvalidSignature.code(), "license_signature");
+        assert_eq!(LicenseError::TrialExpired.code(), "license_trial_expired");
     }
 
     #[test]
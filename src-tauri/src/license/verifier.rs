@@ -1,18 +1,29 @@
 use super::decoder::{decode_key, format_key, parse_payload};
-use super::types::{LicenseError, LicenseInfo, PAYLOAD_LENGTH, SIGNATURE_LENGTH};
+use super::server::{self, server_config, ServerActivation};
+use super::trial::{sign, TrialRecord};
+use super::types::{
+    Deactivation, LicenseError, LicenseInfo, BASE32_ALPHABET, MAX_PAYLOAD_LENGTH, PAYLOAD_LENGTH,
+    SIGNATURE_LENGTH,
+};
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Decodes and checks a license key. A lapsed subscription still verifies,
+/// with `expired` set, so the app can prompt for renewal instead of
+/// treating the key as invalid.
 pub fn verify(key: &str) -> Result<LicenseInfo, LicenseError> {
     let blob = decode_key(key)?;
 
-    if blob.len() != PAYLOAD_LENGTH + SIGNATURE_LENGTH {
+    // Version-2 payloads vary in length; the signature is always last
+    let payload_length = blob.len().saturating_sub(SIGNATURE_LENGTH);
+    if !(PAYLOAD_LENGTH..=MAX_PAYLOAD_LENGTH).contains(&payload_length) {
         return Err(LicenseError::InvalidLength);
     }
 
-    let (payload_bytes, signature_bytes) = blob.split_at(PAYLOAD_LENGTH);
+    let (payload_bytes, signature_bytes) = blob.split_at(payload_length);
     let signature_bytes: [u8; SIGNATURE_LENGTH] = signature_bytes
         .try_into()
         .map_err(|_| LicenseError::InvalidSignature)?;
@@ -34,9 +45,151 @@ pub fn verify(key: &str) -> Result<LicenseInfo, LicenseError> {
         payload: BASE64.encode(payload_bytes),
         signature: BASE64.encode(signature_bytes),
         activated_at: None,
+        server_activation: None,
+        machine_fingerprint: None,
+        entitlements: parsed.entitlements,
+        expires_at: parsed.expires_at,
+        expired: is_expired(parsed.expires_at, activate_timestamp()),
     })
 }
 
+fn is_expired(expires_at: Option<u64>, now: u64) -> bool {
+    expires_at.is_some_and(|expires_at| now >= expires_at)
+}
+
+/// Activates `info` with the license server when one is configured and
+/// records the token it issues; without a server this does nothing.
+pub fn activate_online(info: &mut LicenseInfo, now: u64) -> Result<(), LicenseError> {
+    let Some(config) = server_config() else {
+        return Ok(());
+    };
+    let token = server::activate(&config, info)?;
+    info.server_activation = Some(ServerActivation {
+        token,
+        validated_at: now,
+    });
+    Ok(())
+}
+
+/// Has the license server confirm `info`'s activation, or activates it when
+/// it was stored before a server was configured.
+pub fn revalidate(info: &mut LicenseInfo, now: u64) -> Result<(), LicenseError> {
+    let Some(config) = server_config() else {
+        return Ok(());
+    };
+    let Some(activation) = info.server_activation.clone() else {
+        return activate_online(info, now);
+    };
+    server::validate(&config, info, &activation)?;
+    info.server_activation = Some(ServerActivation {
+        validated_at: now,
+        ..activation
+    });
+    Ok(())
+}
+
+/// Releases `info`'s seat with the license server when `notify_server` is
+/// set and the license was activated online, then describes the
+/// deactivation. Without a server code, the confirmation code is derived
+/// from the license, machine and time, so support can match it.
+pub fn deactivate_online(
+    info: &LicenseInfo,
+    notify_server: bool,
+    now: u64,
+) -> Result<Deactivation, LicenseError> {
+    let server_code = match (server_config(), &info.server_activation) {
+        (Some(config), Some(activation)) if notify_server => {
+            Some(server::deactivate(&config, info, activation)?)
+        },
+        _ => None,
+    };
+    let server_notified = server_code.is_some();
+    let confirmation_code = server_code
+        .flatten()
+        .unwrap_or_else(|| confirmation_code(info, now));
+    Ok(Deactivation {
+        license_id: info.license_id.clone(),
+        confirmation_code,
+        deactivated_at: now,
+        server_notified,
+    })
+}
+
+/// Twelve characters of the license alphabet in groups of four.
+fn confirmation_code(info: &LicenseInfo, now: u64) -> String {
+    let digest = Sha256::digest(format!(
+        "deactivate:{}:{}:{now}",
+        info.license_id,
+        info.machine_fingerprint.as_deref().unwrap_or_default()
+    ));
+    let bits = u64::from_be_bytes(digest[..8].try_into().unwrap_or_default());
+    (0..12)
+        .map(|index| BASE32_ALPHABET[((bits >> (59 - index * 5)) & 0x1F) as usize] as char)
+        .collect::<Vec<_>>()
+        .chunks(4)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Fails with `MachineMismatch` when `info` was activated on a machine
+/// other than the one with `fingerprint`. The stored fingerprint isn't
+/// signed, so a version-2 license missing one counts as a mismatch rather
+/// than as unbound. Version-1 licenses activated without a fingerprint,
+/// and machines whose id can't be read, pass.
+pub fn check_machine(info: &LicenseInfo, fingerprint: Option<&str>) -> Result<(), LicenseError> {
+    let Some(current) = fingerprint else {
+        return Ok(());
+    };
+    match info.machine_fingerprint.as_deref() {
+        Some(bound) if bound == current => Ok(()),
+        None if info.entitlements.is_none() => Ok(()),
+        _ => Err(LicenseError::MachineMismatch),
+    }
+}
+
+/// Fails with `ActivationExpired` once the server last confirmed `info`
+/// more than `grace_period_secs` ago. Licenses never activated online
+/// always pass.
+pub fn check_activation(
+    info: &LicenseInfo,
+    now: u64,
+    grace_period_secs: u64,
+) -> Result<(), LicenseError> {
+    match &info.server_activation {
+        Some(activation) if now.saturating_sub(activation.validated_at) > grace_period_secs => {
+            Err(LicenseError::ActivationExpired)
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Checks a trial record's signature and that `now` falls inside it.
+///
+/// A record starting after `now` means the clock was turned back and is
+/// treated like a forged one.
+pub fn verify_trial(record: &TrialRecord, now: u64) -> Result<(), LicenseError> {
+    let expected = sign(record.started_at, record.expires_at);
+    if !constant_time_eq(expected.as_bytes(), record.signature.as_bytes())
+        || now < record.started_at
+    {
+        return Err(LicenseError::TrialInvalid);
+    }
+    if now >= record.expires_at {
+        return Err(LicenseError::TrialExpired);
+    }
+    Ok(())
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 pub fn activate_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -108,19 +261,24 @@ mod tests {
     }
 
     fn signed_license_blob() -> (String, String) {
+        signed_license_blob_with(1, &[])
+    }
+
+    fn signed_license_blob_with(version: u8, records: &[u8]) -> (String, String) {
         const SECRET: [u8; 32] = [9; 32];
         let secret_key = SecretKey::try_from(&SECRET[..]).expect("secret key");
         let signing_key = SigningKey::from_bytes(&secret_key);
         let verifying_key = signing_key.verifying_key();
         let verifying_b64 = BASE64.encode(verifying_key.to_bytes());
 
-        let mut payload = Vec::with_capacity(types::PAYLOAD_LENGTH);
-        payload.push(1);
+        let mut payload = Vec::with_capacity(types::PAYLOAD_LENGTH + records.len());
+        payload.push(version);
         payload.extend_from_slice(Uuid::new_v4().as_bytes());
         payload.extend_from_slice(Uuid::new_v4().as_bytes());
         payload.push(4);
         let issued_at = 1_700_000_100u64;
         payload.extend_from_slice(&issued_at.to_be_bytes());
+        payload.extend_from_slice(records);
 
         let signature = signing_key.sign(&payload);
         let mut blob = payload.clone();
@@ -139,6 +297,39 @@ mod tests {
         assert_eq!(info.payload.len(), 4 * types::PAYLOAD_LENGTH.div_ceil(3));
     }
 
+    #[test]
+    fn verify_reads_v2_entitlements() {
+        let (key, verifying_b64) = signed_license_blob_with(2, &[0x01, 4, 0, 0, 0, 0b010]);
+        std::env::set_var("LICENSE_PUBLIC_KEY", &verifying_b64);
+
+        let info = verify(&key).expect("valid license");
+        assert_eq!(info.max_major_version, 4);
+        assert!(info.has_entitlement("watch-folders"));
+        assert!(!info.has_entitlement("hw-encoding"));
+
+        let (key, _) = signed_license_blob();
+        assert!(verify(&key)
+            .expect("v1 license")
+            .has_entitlement("hw-encoding"));
+    }
+
+    #[test]
+    fn verify_reports_lapsed_subscriptions() {
+        let mut expiry = vec![0x03, 8];
+        expiry.extend_from_slice(&1_700_000_000u64.to_be_bytes());
+        let (key, verifying_b64) = signed_license_blob_with(2, &expiry);
+        std::env::set_var("LICENSE_PUBLIC_KEY", &verifying_b64);
+
+        let info = verify(&key).expect("expired licenses still verify");
+        assert_eq!(info.expires_at, Some(1_700_000_000));
+        assert!(info.expired);
+        assert!(!info.has_entitlement("hw-encoding"));
+
+        assert!(!is_expired(None, u64::MAX));
+        assert!(!is_expired(Some(1_700_000_000), 1_699_999_999));
+        assert!(is_expired(Some(1_700_000_000), 1_700_000_000));
+    }
+
     #[test]
     fn verify_rejects_invalid_signature() {
         let (key, verifying_b64) = signed_license_blob();
@@ -153,6 +344,99 @@ mod tests {
         assert!(matches!(result, Err(LicenseError::InvalidSignature)));
     }
 
+    #[test]
+    fn verify_trial_enforces_expiry() {
+        let record = TrialRecord::start(1_700_000_000);
+        assert!(verify_trial(&record, record.started_at).is_ok());
+        assert!(matches!(
+            verify_trial(&record, record.expires_at),
+            Err(LicenseError::TrialExpired)
+        ));
+        assert!(matches!(
+            verify_trial(&record, record.started_at - 1),
+            Err(LicenseError::TrialInvalid)
+        ));
+
+        let forged = TrialRecord {
+            signature: sign(record.started_at, record.expires_at + 1),
+            ..record
+        };
+        assert!(matches!(
+            verify_trial(&forged, forged.started_at),
+            Err(LicenseError::TrialInvalid)
+        ));
+    }
+
+    #[test]
+    fn check_activation_allows_the_grace_period() {
+        let (key, verifying_b64) = signed_license_blob();
+        std::env::set_var("LICENSE_PUBLIC_KEY", &verifying_b64);
+        let mut info = verify(&key).expect("valid license");
+        let grace = 7 * 24 * 60 * 60;
+        assert!(check_activation(&info, u64::MAX, grace).is_ok());
+
+        info.server_activation = Some(ServerActivation {
+            token: "token".into(),
+            validated_at: 1_700_000_000,
+        });
+        assert!(check_activation(&info, 1_700_000_000 + grace, grace).is_ok());
+        assert!(matches!(
+            check_activation(&info, 1_700_000_001 + grace, grace),
+            Err(LicenseError::ActivationExpired)
+        ));
+    }
+
+    #[test]
+    fn check_machine_rejects_other_machines() {
+        let (key, verifying_b64) = signed_license_blob();
+        std::env::set_var("LICENSE_PUBLIC_KEY", &verifying_b64);
+        let mut info = verify(&key).expect("valid license");
+        assert!(check_machine(&info, Some("abc")).is_ok());
+
+        info.machine_fingerprint = Some("abc".into());
+        assert!(check_machine(&info, Some("abc")).is_ok());
+        assert!(check_machine(&info, None).is_ok());
+        assert!(matches!(
+            check_machine(&info, Some("def")),
+            Err(LicenseError::MachineMismatch)
+        ));
+    }
+
+    #[test]
+    fn check_machine_requires_a_fingerprint_on_v2_licenses() {
+        let (key, verifying_b64) = signed_license_blob_with(2, &[0x01, 4, 0, 0, 0, 0b010]);
+        std::env::set_var("LICENSE_PUBLIC_KEY", &verifying_b64);
+        let mut info = verify(&key).expect("valid license");
+
+        assert!(matches!(
+            check_machine(&info, Some("abc")),
+            Err(LicenseError::MachineMismatch)
+        ));
+        assert!(check_machine(&info, None).is_ok());
+
+        info.machine_fingerprint = Some("abc".into());
+        assert!(check_machine(&info, Some("abc")).is_ok());
+    }
+
+    #[test]
+    fn deactivation_codes_are_stable_per_machine_and_time() {
+        let (key, verifying_b64) = signed_license_blob();
+        std::env::set_var("LICENSE_PUBLIC_KEY", &verifying_b64);
+        let mut info = verify(&key).expect("valid license");
+
+        let code = confirmation_code(&info, 1_700_000_000);
+        assert_eq!(code.len(), 14);
+        assert!(code
+            .split('-')
+            .all(|group| group.len() == 4
+                && group.bytes().all(|b| types::BASE32_ALPHABET.contains(&b))));
+        assert_eq!(code, confirmation_code(&info, 1_700_000_000));
+        assert_ne!(code, confirmation_code(&info, 1_700_000_001));
+
+        info.machine_fingerprint = Some("abc".into());
+        assert_ne!(code, confirmation_code(&info, 1_700_000_000));
+    }
+
     #[test]
     fn activate_timestamp_returns_nonzero() {
         assert!(activate_timestamp() > 0);
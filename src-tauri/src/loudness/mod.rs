@@ -0,0 +1,203 @@
+//! EBU R128 loudness analysis.
+//!
+//! A single ffmpeg `ebur128` pass measures integrated loudness, loudness
+//! range and true peak. The measurements feed the ReplayGain/R128 tagging
+//! post-step in [`replaygain`], which batches tracks per album when album
+//! mode is on, and the `measured_*` options of a second `loudnorm` pass.
+
+pub mod replaygain;
+
+use serde::Serialize;
+use std::process::Command;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+
+/// Result of the analysis pass for one file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessMeasurement {
+    pub path: String,
+    pub integrated_lufs: f64,
+    pub true_peak_dbtp: f64,
+    /// Loudness range (LRA).
+    pub loudness_range_lu: f64,
+    /// Relative gating threshold of the integrated loudness, which
+    /// `loudnorm` takes as `measured_thresh`.
+    pub threshold_lufs: f64,
+    /// Used to weight album loudness; `None` when unknown.
+    pub duration_sec: Option<f64>,
+}
+
+/// Values of the summary block ebur128 prints when the stream ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub integrated_lufs: f64,
+    pub threshold_lufs: f64,
+    pub loudness_range_lu: f64,
+    pub true_peak_dbtp: f64,
+}
+
+/// Arguments for the analysis pass; decoded audio is discarded.
+pub fn analysis_args(path: &str) -> Vec<String> {
+    [
+        "-hide_banner",
+        "-nostats",
+        "-i",
+        path,
+        "-map",
+        "0:a:0",
+        "-af",
+        "ebur128=peak=true",
+        "-f",
+        "null",
+        "-",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+/// Runs the analysis pass and parses the ebur128 summary.
+pub fn analyze(app: &AppHandle, path: &str) -> Result<LoudnessMeasurement, AppError> {
+    let stderr = run_ffmpeg(app, &analysis_args(path))?;
+    let summary = parse_summary(&stderr).ok_or_else(|| {
+        AppError::new(
+            "loudness_parse",
+            format!("No loudness summary reported for {path}"),
+        )
+    })?;
+
+    Ok(LoudnessMeasurement {
+        path: path.to_string(),
+        integrated_lufs: summary.integrated_lufs,
+        true_peak_dbtp: summary.true_peak_dbtp,
+        loudness_range_lu: summary.loudness_range_lu,
+        threshold_lufs: summary.threshold_lufs,
+        duration_sec: None,
+    })
+}
+
+/// Parses the summary block ebur128 prints when the stream ends. The first
+/// threshold belongs to the integrated loudness, the second to the range.
+pub fn parse_summary(stderr: &str) -> Option<Summary> {
+    let summary = &stderr[stderr.rfind("Summary:")?..];
+    let mut integrated = None;
+    let mut threshold = None;
+    let mut range = None;
+    let mut peak = None;
+
+    for line in summary.lines().map(str::trim) {
+        if let Some(value) = line.strip_prefix("I:") {
+            integrated = integrated.or_else(|| leading_number(value));
+        } else if let Some(value) = line.strip_prefix("Threshold:") {
+            threshold = threshold.or_else(|| leading_number(value));
+        } else if let Some(value) = line.strip_prefix("LRA:") {
+            range = range.or_else(|| leading_number(value));
+        } else if let Some(value) = line.strip_prefix("Peak:") {
+            peak = peak.or_else(|| leading_number(value));
+        }
+    }
+
+    Some(Summary {
+        integrated_lufs: integrated?,
+        threshold_lufs: threshold?,
+        loudness_range_lu: range?,
+        true_peak_dbtp: peak?,
+    })
+}
+
+fn leading_number(value: &str) -> Option<f64> {
+    let number = value.split_whitespace().next()?;
+    match number {
+        "-inf" => Some(f64::NEG_INFINITY),
+        _ => number.parse().ok(),
+    }
+}
+
+/// Runs ffmpeg with the first working candidate and returns its stderr.
+pub(crate) fn run_ffmpeg(app: &AppHandle, args: &[String]) -> Result<String, AppError> {
+    let mut last_err: Option<String> = None;
+
+    for candidate in crate::binary_resolver::resolve_ffmpeg_paths(app) {
+        match Command::new(&candidate).args(args).output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stderr).into_owned());
+            },
+            Ok(output) => {
+                last_err = Some(format!(
+                    "ffmpeg exited with status {} (stderr: {})",
+                    output
+                        .status
+                        .code()
+                        .map(|code| code.to_string())
+                        .unwrap_or_else(|| "unknown".into()),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            },
+            Err(error) => last_err = Some(error.to_string()),
+        }
+    }
+
+    Err(AppError::new(
+        "loudness_ffmpeg_exec",
+        last_err.unwrap_or_else(|| "Unable to execute ffmpeg".into()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUMMARY: &str = "\
+[Parsed_ebur128_0 @ 0x600] t: 12.3 TARGET:-23 LUFS M: -15.0 S: -15.2 I: -99.0 LUFS
+[Parsed_ebur128_0 @ 0x600] Summary:
+
+  Integrated loudness:
+    I:         -16.5 LUFS
+    Threshold: -26.8 LUFS
+
+  Loudness range:
+    LRA:         6.3 LU
+    Threshold: -36.9 LUFS
+
+  True peak:
+    Peak:        0.4 dBFS
+";
+
+    #[test]
+    fn parses_integrated_loudness_and_true_peak() {
+        let summary = parse_summary(SUMMARY).unwrap();
+        assert_eq!(
+            (summary.integrated_lufs, summary.true_peak_dbtp),
+            (-16.5, 0.4)
+        );
+    }
+
+    #[test]
+    fn parses_loudness_range_and_gating_threshold() {
+        let summary = parse_summary(SUMMARY).unwrap();
+        assert_eq!(summary.loudness_range_lu, 6.3);
+        assert_eq!(summary.threshold_lufs, -26.8);
+    }
+
+    #[test]
+    fn missing_summary_is_none() {
+        assert_eq!(parse_summary("t: 1.0 I: -20.0 LUFS"), None);
+    }
+
+    #[test]
+    fn silence_reports_negative_infinity_peak() {
+        let silent = SUMMARY.replace("0.4 dBFS", "-inf dBFS");
+        let summary = parse_summary(&silent).unwrap();
+        assert_eq!(summary.integrated_lufs, -16.5);
+        assert_eq!(summary.true_peak_dbtp, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn analysis_maps_first_audio_stream() {
+        let args = analysis_args("/music/a.flac");
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:a:0"]));
+        assert!(args.contains(&"ebur128=peak=true".to_string()));
+    }
+}
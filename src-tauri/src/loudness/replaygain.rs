@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::{run_ffmpeg, LoudnessMeasurement};
+use crate::error::AppError;
+
+/// ReplayGain 2.0 reference loudness.
+const REPLAYGAIN_REFERENCE_LUFS: f64 = -18.0;
+/// Opus R128 gain tags are relative to EBU R128's -23 LUFS.
+const R128_REFERENCE_LUFS: f64 = -23.0;
+
+/// An output file to tag, with the album it belongs to in album mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GainTarget {
+    pub path: String,
+    pub album: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GainTagRequest {
+    pub targets: Vec<GainTarget>,
+    /// Also computes album gain, batching targets by their album.
+    #[serde(default)]
+    pub album_mode: bool,
+}
+
+/// `None` loudness values are digital silence, which measures `-inf` and
+/// would otherwise serialize as `null` behind a plain number.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GainTagResult {
+    pub path: String,
+    pub integrated_lufs: Option<f64>,
+    pub true_peak_dbtp: Option<f64>,
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Combined loudness of an album: the duration-weighted energy mean of the
+/// track loudness values, and the loudest track peak.
+pub fn album_loudness(tracks: &[&LoudnessMeasurement]) -> Option<(f64, f64)> {
+    let audible: Vec<_> = tracks
+        .iter()
+        .filter(|track| track.integrated_lufs.is_finite())
+        .collect();
+    if audible.is_empty() {
+        return None;
+    }
+
+    let weight =
+        |track: &LoudnessMeasurement| track.duration_sec.filter(|d| *d > 0.0).unwrap_or(1.0);
+    let total_weight: f64 = audible.iter().map(|track| weight(track)).sum();
+    let energy: f64 = audible
+        .iter()
+        .map(|track| weight(track) * 10f64.powf(track.integrated_lufs / 10.0))
+        .sum();
+    let peak = tracks
+        .iter()
+        .map(|track| track.true_peak_dbtp)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    Some((10.0 * (energy / total_weight).log10(), peak))
+}
+
+/// Tags for one output. Opus gets integer Q7.8 `R128_*` gains; every other
+/// format gets ReplayGain 2.0 gain/peak pairs.
+pub fn gain_tags(
+    container: &str,
+    track: &LoudnessMeasurement,
+    album: Option<(f64, f64)>,
+) -> BTreeMap<String, String> {
+    let mut tags = BTreeMap::new();
+    if !track.integrated_lufs.is_finite() {
+        return tags;
+    }
+
+    if container == "opus" {
+        tags.insert("R128_TRACK_GAIN".into(), r128_gain(track.integrated_lufs));
+        if let Some((lufs, _)) = album {
+            tags.insert("R128_ALBUM_GAIN".into(), r128_gain(lufs));
+        }
+        return tags;
+    }
+
+    tags.insert(
+        "REPLAYGAIN_TRACK_GAIN".into(),
+        replaygain_gain(track.integrated_lufs),
+    );
+    tags.insert(
+        "REPLAYGAIN_TRACK_PEAK".into(),
+        linear_peak(track.true_peak_dbtp),
+    );
+    if let Some((lufs, peak)) = album {
+        tags.insert("REPLAYGAIN_ALBUM_GAIN".into(), replaygain_gain(lufs));
+        tags.insert("REPLAYGAIN_ALBUM_PEAK".into(), linear_peak(peak));
+    }
+    tags
+}
+
+/// Pairs each target with its measurement and computes its tags, sharing
+/// album values between targets with the same album in album mode.
+pub fn plan_gain_tags(
+    measured: &[(GainTarget, LoudnessMeasurement)],
+    album_mode: bool,
+) -> Vec<GainTagResult> {
+    let mut albums: BTreeMap<&str, Vec<&LoudnessMeasurement>> = BTreeMap::new();
+    if album_mode {
+        for (target, measurement) in measured {
+            albums
+                .entry(target.album.as_deref().unwrap_or_default())
+                .or_default()
+                .push(measurement);
+        }
+    }
+
+    measured
+        .iter()
+        .map(|(target, measurement)| {
+            let album = albums
+                .get(target.album.as_deref().unwrap_or_default())
+                .and_then(|tracks| album_loudness(tracks));
+            GainTagResult {
+                path: target.path.clone(),
+                integrated_lufs: Some(measurement.integrated_lufs).filter(|lufs| lufs.is_finite()),
+                true_peak_dbtp: Some(measurement.true_peak_dbtp).filter(|peak| peak.is_finite()),
+                tags: gain_tags(&container_of(&target.path), measurement, album),
+            }
+        })
+        .collect()
+}
+
+/// Remux arguments that copy every stream and add the gain tags. MP4 files
+/// are tagged in place instead, see [`write_tags`].
+pub fn tag_args(input: &str, output: &str, tags: &BTreeMap<String, String>) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "-hide_banner",
+        "-nostats",
+        "-v",
+        "error",
+        "-y",
+        "-i",
+        input,
+        "-map",
+        "0",
+        "-c",
+        "copy",
+        "-map_metadata",
+        "0",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    for (key, value) in tags {
+        args.push("-metadata".into());
+        args.push(format!("{key}={value}"));
+    }
+
+    args.push(output.to_string());
+    args
+}
+
+/// Rewrites the file with the tags applied, replacing it only once the
+/// remux has succeeded. FFmpeg's MP4 muxer only writes custom keys as
+/// `mdta` items, which players don't read ReplayGain from, so MP4 files get
+/// freeform `----:com.apple.iTunes:replaygain_*` items instead.
+pub fn write_tags(
+    app: &AppHandle,
+    path: &str,
+    tags: &BTreeMap<String, String>,
+) -> Result<(), AppError> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    if is_mp4(path) {
+        let names: Vec<(String, &str)> = tags
+            .iter()
+            .map(|(key, value)| (key.to_lowercase(), value.as_str()))
+            .collect();
+        let items: Vec<(&str, &str)> = names
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        return crate::mp4_tags::write_freeform_tags(Path::new(path), &items);
+    }
+
+    let temp = temp_path(Path::new(path));
+    let temp_arg = temp.to_string_lossy().into_owned();
+    if let Err(err) = run_ffmpeg(app, &tag_args(path, &temp_arg, tags)) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err);
+    }
+
+    std::fs::rename(&temp, path).map_err(|err| {
+        let _ = std::fs::remove_file(&temp);
+        AppError::new(
+            "loudness_tag_write",
+            format!("Failed replacing {path}: {err}"),
+        )
+    })
+}
+
+/// Keeps the extension last so ffmpeg picks the same muxer.
+fn temp_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}.gain-tmp.{}", ext.to_string_lossy()),
+        None => format!("{stem}.gain-tmp"),
+    };
+    path.with_file_name(name)
+}
+
+fn is_mp4(path: &str) -> bool {
+    matches!(container_of(path).as_str(), "m4a" | "m4b" | "mp4" | "mov")
+}
+
+fn container_of(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn replaygain_gain(lufs: f64) -> String {
+    format!("{:.2} dB", REPLAYGAIN_REFERENCE_LUFS - lufs)
+}
+
+fn linear_peak(dbtp: f64) -> String {
+    format!("{:.6}", 10f64.powf(dbtp / 20.0))
+}
+
+fn r128_gain(lufs: f64) -> String {
+    let q78 = ((R128_REFERENCE_LUFS - lufs) * 256.0).round();
+    (q78.clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(path: &str, lufs: f64, peak: f64, duration: f64) -> LoudnessMeasurement {
+        LoudnessMeasurement {
+            path: path.into(),
+            integrated_lufs: lufs,
+            true_peak_dbtp: peak,
+            loudness_range_lu: 6.0,
+            threshold_lufs: lufs - 10.0,
+            duration_sec: Some(duration),
+        }
+    }
+
+    fn target(path: &str, album: &str) -> GainTarget {
+        GainTarget {
+            path: path.into(),
+            album: Some(album.into()),
+        }
+    }
+
+    #[test]
+    fn replaygain_tags_use_minus_18_reference() {
+        let tags = gain_tags("flac", &measurement("a.flac", -12.0, 0.0, 60.0), None);
+
+        assert_eq!(tags["REPLAYGAIN_TRACK_GAIN"], "-6.00 dB");
+        assert_eq!(tags["REPLAYGAIN_TRACK_PEAK"], "1.000000");
+        assert!(!tags.contains_key("REPLAYGAIN_ALBUM_GAIN"));
+    }
+
+    #[test]
+    fn opus_tags_use_q78_r128_gain() {
+        let tags = gain_tags(
+            "opus",
+            &measurement("a.opus", -13.0, -1.0, 60.0),
+            Some((-20.0, -1.0)),
+        );
+
+        assert_eq!(tags["R128_TRACK_GAIN"], "-2560");
+        assert_eq!(tags["R128_ALBUM_GAIN"], "-768");
+        assert!(!tags.contains_key("REPLAYGAIN_TRACK_GAIN"));
+    }
+
+    #[test]
+    fn album_loudness_weights_by_duration() {
+        let loud = measurement("a", -10.0, -0.5, 30.0);
+        let quiet = measurement("b", -20.0, -3.0, 30.0);
+        let (lufs, peak) = album_loudness(&[&loud, &quiet]).unwrap();
+
+        // Equal durations: energy mean of -10 and -20 LUFS is ~-12.6 LUFS.
+        assert!((lufs + 12.6).abs() < 0.05);
+        assert_eq!(peak, -0.5);
+    }
+
+    #[test]
+    fn album_mode_batches_by_album() {
+        let measured = vec![
+            (
+                target("/out/a.mp3", "One"),
+                measurement("a", -10.0, -1.0, 60.0),
+            ),
+            (
+                target("/out/b.mp3", "One"),
+                measurement("b", -14.0, -2.0, 60.0),
+            ),
+            (
+                target("/out/c.mp3", "Two"),
+                measurement("c", -20.0, -6.0, 60.0),
+            ),
+        ];
+
+        let results = plan_gain_tags(&measured, true);
+        assert_eq!(
+            results[0].tags["REPLAYGAIN_ALBUM_GAIN"],
+            results[1].tags["REPLAYGAIN_ALBUM_GAIN"]
+        );
+        assert_eq!(results[2].tags["REPLAYGAIN_ALBUM_GAIN"], "2.00 dB");
+
+        let tracks_only = plan_gain_tags(&measured, false);
+        assert!(!tracks_only[0].tags.contains_key("REPLAYGAIN_ALBUM_GAIN"));
+    }
+
+    #[test]
+    fn tag_args_copy_streams_and_add_tags() {
+        let mut tags = BTreeMap::new();
+        tags.insert("REPLAYGAIN_TRACK_GAIN".to_string(), "-6.00 dB".to_string());
+        let args = tag_args("/out/a.flac", "/out/a.gain-tmp.flac", &tags);
+
+        assert!(args.windows(2).any(|pair| pair == ["-c", "copy"]));
+        assert!(args.contains(&"REPLAYGAIN_TRACK_GAIN=-6.00 dB".to_string()));
+        assert_eq!(args.last().unwrap(), "/out/a.gain-tmp.flac");
+    }
+
+    #[test]
+    fn silent_results_report_no_loudness() {
+        let silent = measurement("a", f64::NEG_INFINITY, f64::NEG_INFINITY, 10.0);
+        let results = plan_gain_tags(&[(target("/out/a.m4a", "One"), silent)], false);
+
+        assert_eq!(results[0].integrated_lufs, None);
+        let json = serde_json::to_value(&results[0]).unwrap();
+        assert!(json["integratedLufs"].is_null());
+        assert!(json["truePeakDbtp"].is_null());
+    }
+
+    #[test]
+    fn temp_path_keeps_extension() {
+        assert_eq!(
+            temp_path(Path::new("/out/01 - Song.flac")),
+            PathBuf::from("/out/01 - Song.gain-tmp.flac")
+        );
+    }
+
+    #[test]
+    fn silent_tracks_get_no_tags() {
+        let silent = measurement("a", f64::NEG_INFINITY, f64::NEG_INFINITY, 10.0);
+        assert!(gain_tags("mp3", &silent, None).is_empty());
+        assert!(album_loudness(&[&silent]).is_none());
+    }
+}
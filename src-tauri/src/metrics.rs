@@ -0,0 +1,230 @@
+//! Command timing metrics and timeout policy.
+//!
+//! Blocking commands that can get stuck (a probe on a dead network mount,
+//! a file dialog on a broken portal) run under a timeout, so they fail with
+//! `command_timeout` instead of leaving the UI waiting forever. Commands
+//! that write files or settings, and any without a default or an override,
+//! run without one. Durations are recorded per command, and calls slower
+//! than the threshold are counted and logged so they can be traced back
+//! later.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use crate::sync_utils::{MutexExt, RwLockExt};
+
+/// Calls taking longer than this are reported as slow.
+pub const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// Commands that write files or settings. A timed-out call keeps running
+/// and may still succeed, so reporting it as failed would be wrong; these
+/// never time out, whatever the overrides say.
+pub const MUTATING_COMMANDS: &[&str] = &[
+    "install_ffmpeg",
+    "set_ffmpeg_path",
+    "register_ffmpeg_installation",
+    "activate_license",
+    "deactivate_license",
+    "write_gain_tags",
+    "scan_catalog",
+    "finish_album",
+];
+
+/// Built-in timeout for `command`; `None` means it may run indefinitely
+/// (long encodes and scans that report their own progress).
+pub fn default_timeout(command: &str) -> Option<Duration> {
+    let secs = match command {
+        "load_capabilities"
+        | "get_binary_info"
+        | "diagnose_binaries"
+        | "probe_media"
+        | "build_video_filters"
+        | "resolve_output_container"
+        | "preview_stream_selection"
+        | "query_catalog" => 30,
+        "expand_media_paths" | "poll_watch_folder" | "plan_rule_batch" => 60,
+        "export_probe_report" | "plan_album" => 10 * 60,
+        // Dialogs wait on the user, so only a broken portal hits this.
+        "pick_media_files" | "choose_output_directory" => 15 * 60,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
+/// Per-command timeouts, with user overrides on top of the defaults.
+#[derive(Debug, Default)]
+pub struct CommandTimeouts {
+    overrides: RwLock<HashMap<String, Option<Duration>>>,
+}
+
+impl CommandTimeouts {
+    pub fn resolve(&self, command: &str) -> Option<Duration> {
+        if MUTATING_COMMANDS.contains(&command) {
+            return None;
+        }
+        self.overrides
+            .read_or_recover()
+            .get(command)
+            .copied()
+            .unwrap_or_else(|| default_timeout(command))
+    }
+
+    /// Overrides the timeout for `command`; `None` disables it.
+    pub fn set(&self, command: &str, timeout: Option<Duration>) {
+        self.overrides
+            .write_or_recover()
+            .insert(command.to_string(), timeout);
+    }
+}
+
+/// How a timed call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    Completed,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStats {
+    pub calls: u64,
+    pub slow_calls: u64,
+    pub timeouts: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug)]
+pub struct CommandMetrics {
+    stats: Mutex<BTreeMap<String, CommandStats>>,
+    slow_threshold: Duration,
+}
+
+impl Default for CommandMetrics {
+    fn default() -> Self {
+        Self::with_slow_threshold(DEFAULT_SLOW_THRESHOLD)
+    }
+}
+
+impl CommandMetrics {
+    pub fn with_slow_threshold(slow_threshold: Duration) -> Self {
+        Self {
+            stats: Mutex::new(BTreeMap::new()),
+            slow_threshold,
+        }
+    }
+
+    /// Records one call and returns whether it counted as slow.
+    pub fn record(&self, command: &str, elapsed: Duration, outcome: CallOutcome) -> bool {
+        let slow = elapsed >= self.slow_threshold;
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+
+        {
+            let mut stats = self.stats.lock_or_recover();
+            let entry = stats.entry(command.to_string()).or_default();
+            entry.calls += 1;
+            entry.total_ms = entry.total_ms.saturating_add(elapsed_ms);
+            entry.max_ms = entry.max_ms.max(elapsed_ms);
+            if slow {
+                entry.slow_calls += 1;
+            }
+            if outcome == CallOutcome::TimedOut {
+                entry.timeouts += 1;
+            }
+        }
+
+        if outcome == CallOutcome::TimedOut {
+            eprintln!("[commands] {command} timed out after {elapsed_ms} ms");
+        } else if slow {
+            eprintln!("[commands] slow command {command} took {elapsed_ms} ms");
+        }
+        slow
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, CommandStats> {
+        self.stats.lock_or_recover().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_durations_slow_calls_and_timeouts() {
+        let metrics = CommandMetrics::with_slow_threshold(Duration::from_millis(100));
+        assert!(!metrics.record(
+            "probe_media",
+            Duration::from_millis(40),
+            CallOutcome::Completed
+        ));
+        assert!(metrics.record(
+            "probe_media",
+            Duration::from_millis(250),
+            CallOutcome::Completed
+        ));
+        metrics.record(
+            "probe_media",
+            Duration::from_secs(30),
+            CallOutcome::TimedOut,
+        );
+
+        let stats = &metrics.snapshot()["probe_media"];
+        assert_eq!(stats.calls, 3);
+        assert_eq!(stats.slow_calls, 2);
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.total_ms, 30_290);
+        assert_eq!(stats.max_ms, 30_000);
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let timeouts = CommandTimeouts::default();
+        assert_eq!(
+            timeouts.resolve("probe_media"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(timeouts.resolve("scan_catalog"), None);
+
+        timeouts.set("probe_media", Some(Duration::from_secs(5)));
+        timeouts.set("pick_media_files", None);
+        assert_eq!(
+            timeouts.resolve("probe_media"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(timeouts.resolve("pick_media_files"), None);
+    }
+
+    #[test]
+    fn mutating_commands_never_time_out() {
+        let timeouts = CommandTimeouts::default();
+        for command in MUTATING_COMMANDS {
+            assert_eq!(default_timeout(command), None, "{command}");
+        }
+
+        timeouts.set("finish_album", Some(Duration::from_secs(5)));
+        assert_eq!(timeouts.resolve("finish_album"), None);
+    }
+
+    #[test]
+    fn poisoned_locks_keep_recording() {
+        let metrics = CommandMetrics::with_slow_threshold(Duration::from_secs(1));
+        let timeouts = CommandTimeouts::default();
+        std::thread::scope(|scope| {
+            let _ = scope
+                .spawn(|| {
+                    let _stats = metrics.stats.lock().unwrap();
+                    let _overrides = timeouts.overrides.write().unwrap();
+                    panic!("poison the locks");
+                })
+                .join();
+        });
+
+        metrics.record("probe_media", Duration::ZERO, CallOutcome::Completed);
+        assert_eq!(metrics.snapshot()["probe_media"].calls, 1);
+        timeouts.set("probe_media", None);
+        assert_eq!(timeouts.resolve("probe_media"), None);
+    }
+}
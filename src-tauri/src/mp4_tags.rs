@@ -0,0 +1,480 @@
+//! Freeform iTunes metadata items in MP4 files.
+//!
+//! Players read gapless and gain information from `----` items named under
+//! `com.apple.iTunes` (`iTunSMPB`, `replaygain_track_gain`, ...). FFmpeg's
+//! MP4 muxer can't write those: custom keys are dropped, or with
+//! `+use_metadata_tags` stored as QuickTime `mdta` keys that iTunes-style
+//! readers ignore. This module edits `moov/udta/meta/ilst` in place of the
+//! muxer, shifting chunk offsets when `moov` sits before the media data.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Mean (namespace) of the freeform items players look for.
+pub const ITUNES_MEAN: &str = "com.apple.iTunes";
+
+const BOX_HEADER_LEN: u64 = 8;
+/// `data` type indicator for UTF-8 text.
+const DATA_TYPE_UTF8: u32 = 1;
+
+/// Sets freeform `----:com.apple.iTunes:<name>` items, replacing existing
+/// items with the same name. The file is rewritten beside the original and
+/// renamed over it once complete.
+pub fn write_freeform_tags(path: &Path, tags: &[(&str, &str)]) -> Result<(), AppError> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    let mut file = File::open(path).map_err(|err| write_error(path, err))?;
+    let file_len = file.metadata().map_err(|err| write_error(path, err))?.len();
+    let boxes = top_level_boxes(&mut file, file_len).map_err(|err| write_error(path, err))?;
+    if boxes.iter().any(|entry| &entry.kind == b"moof") {
+        return Err(unsupported(path, "fragmented files are not supported"));
+    }
+    let moov = boxes
+        .iter()
+        .find(|entry| &entry.kind == b"moov")
+        .ok_or_else(|| unsupported(path, "no moov box"))?;
+
+    let mut original = vec![0; moov.len as usize];
+    file.seek(SeekFrom::Start(moov.offset))
+        .and_then(|_| file.read_exact(&mut original))
+        .map_err(|err| write_error(path, err))?;
+
+    let mut updated =
+        with_freeform_tags(&original, tags).map_err(|reason| unsupported(path, reason))?;
+    let moov_end = moov.offset + moov.len;
+    let delta = updated.len() as i64 - moov.len as i64;
+    if delta != 0 && moov_end < file_len {
+        shift_chunk_offsets(&mut updated, moov_end, delta)
+            .map_err(|reason| unsupported(path, reason))?;
+    }
+
+    let temp = temp_path(path);
+    let result = (|| -> io::Result<()> {
+        let mut out = File::create(&temp)?;
+        file.seek(SeekFrom::Start(0))?;
+        io::copy(&mut (&mut file).take(moov.offset), &mut out)?;
+        out.write_all(&updated)?;
+        file.seek(SeekFrom::Start(moov_end))?;
+        io::copy(&mut file, &mut out)?;
+        out.sync_all()?;
+        drop(file);
+        fs::rename(&temp, path)
+    })();
+    result.map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        write_error(path, err)
+    })
+}
+
+struct TopLevelBox {
+    kind: [u8; 4],
+    offset: u64,
+    len: u64,
+}
+
+fn top_level_boxes(file: &mut File, file_len: u64) -> io::Result<Vec<TopLevelBox>> {
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+    while offset + BOX_HEADER_LEN <= file_len {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut header = [0; 8];
+        file.read_exact(&mut header)?;
+        let size = u64::from(u32::from_be_bytes(header[..4].try_into().unwrap()));
+        let len = match size {
+            0 => file_len - offset,
+            1 => {
+                let mut large = [0; 8];
+                file.read_exact(&mut large)?;
+                u64::from_be_bytes(large)
+            },
+            size => size,
+        };
+        if len < BOX_HEADER_LEN || offset + len > file_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "malformed MP4 box",
+            ));
+        }
+        boxes.push(TopLevelBox {
+            kind: header[4..].try_into().unwrap(),
+            offset,
+            len,
+        });
+        offset += len;
+    }
+    Ok(boxes)
+}
+
+/// A child box's type and its whole bytes, header included.
+type Child<'a> = ([u8; 4], &'a [u8]);
+
+fn children(payload: &[u8]) -> Result<Vec<Child<'_>>, &'static str> {
+    let mut result = Vec::new();
+    let mut rest = payload;
+    while rest.len() >= BOX_HEADER_LEN as usize {
+        let size = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let len = match size {
+            0 => rest.len(),
+            1 => return Err("64-bit boxes inside moov are not supported"),
+            size => size,
+        };
+        if len < BOX_HEADER_LEN as usize || len > rest.len() {
+            return Err("malformed MP4 box");
+        }
+        result.push((rest[4..8].try_into().unwrap(), &rest[..len]));
+        rest = &rest[len..];
+    }
+    Ok(result)
+}
+
+fn find_children<'a>(payload: &'a [u8], kind: &[u8; 4]) -> Vec<&'a [u8]> {
+    children(payload)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(child, _)| child == kind)
+        .map(|(_, bytes)| &bytes[BOX_HEADER_LEN as usize..])
+        .collect()
+}
+
+/// MP4 `meta` is a full box (version and flags first); QuickTime files may
+/// omit them, in which case the `hdlr` child starts right away.
+fn split_meta(payload: &[u8]) -> (&[u8], &[u8]) {
+    if payload.len() >= 8 && &payload[4..8] == b"hdlr" {
+        (&[], payload)
+    } else {
+        payload.split_at(payload.len().min(4))
+    }
+}
+
+fn make_box(kind: &[u8; 4], payload: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let len = u32::try_from(payload.len() + BOX_HEADER_LEN as usize)
+        .map_err(|_| "metadata box too large")?;
+    let mut bytes = Vec::with_capacity(len as usize);
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(payload);
+    Ok(bytes)
+}
+
+/// Rebuilds `moov` with the items set, creating `udta`, `meta` and `ilst`
+/// when they don't exist yet.
+fn with_freeform_tags(moov: &[u8], tags: &[(&str, &str)]) -> Result<Vec<u8>, &'static str> {
+    let payload = rebuild(&moov[BOX_HEADER_LEN as usize..], b"udta", |udta| {
+        rebuild(udta.unwrap_or_default(), b"meta", |meta| {
+            let (prefix, meta_children) = match meta {
+                Some(meta) => split_meta(meta),
+                None => (&[0u8; 4][..], &[][..]),
+            };
+            let mut payload = prefix.to_vec();
+            if meta.is_none() {
+                payload.extend(make_box(b"hdlr", &mdir_handler())?);
+            }
+            payload.extend(rebuild(meta_children, b"ilst", |ilst| {
+                rebuild_ilst(ilst.unwrap_or_default(), tags)
+            })?);
+            Ok(payload)
+        })
+    })?;
+    make_box(b"moov", &payload)
+}
+
+/// Copies `payload`'s children, replacing the first `kind` child with
+/// `update`'s result or appending it when there is none.
+fn rebuild(
+    payload: &[u8],
+    kind: &[u8; 4],
+    update: impl FnOnce(Option<&[u8]>) -> Result<Vec<u8>, &'static str>,
+) -> Result<Vec<u8>, &'static str> {
+    let mut update = Some(update);
+    let mut result = Vec::with_capacity(payload.len());
+    for (child, bytes) in children(payload)? {
+        match update.take_if(|_| &child == kind) {
+            Some(update) => result.extend(make_box(
+                kind,
+                &update(Some(&bytes[BOX_HEADER_LEN as usize..]))?,
+            )?),
+            None => result.extend_from_slice(bytes),
+        }
+    }
+    if let Some(update) = update {
+        result.extend(make_box(kind, &update(None)?)?);
+    }
+    Ok(result)
+}
+
+fn rebuild_ilst(ilst: &[u8], tags: &[(&str, &str)]) -> Result<Vec<u8>, &'static str> {
+    let mut payload = Vec::with_capacity(ilst.len());
+    for (child, bytes) in children(ilst)? {
+        let replaced = &child == b"----"
+            && parse_freeform(&bytes[BOX_HEADER_LEN as usize..]).is_some_and(|(name, _)| {
+                tags.iter().any(|(tag, _)| tag.eq_ignore_ascii_case(&name))
+            });
+        if !replaced {
+            payload.extend_from_slice(bytes);
+        }
+    }
+    for (name, value) in tags {
+        payload.extend(freeform_item(name, value)?);
+    }
+    Ok(payload)
+}
+
+fn freeform_item(name: &str, value: &str) -> Result<Vec<u8>, &'static str> {
+    let text = |text: &str| [&[0u8; 4][..], text.as_bytes()].concat();
+    let mut data = DATA_TYPE_UTF8.to_be_bytes().to_vec();
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(value.as_bytes());
+
+    let mut item = make_box(b"mean", &text(ITUNES_MEAN))?;
+    item.extend(make_box(b"name", &text(name))?);
+    item.extend(make_box(b"data", &data)?);
+    make_box(b"----", &item)
+}
+
+/// `(name, value)` of a `----` item under the iTunes mean.
+fn parse_freeform(item: &[u8]) -> Option<(String, String)> {
+    let field = |kind: &[u8; 4], skip: usize| {
+        find_children(item, kind)
+            .first()
+            .and_then(|payload| payload.get(skip..))
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    };
+    if field(b"mean", 4)? != ITUNES_MEAN {
+        return None;
+    }
+    Some((field(b"name", 4)?, field(b"data", 8)?))
+}
+
+/// Handler payload iTunes writes for item lists.
+fn mdir_handler() -> Vec<u8> {
+    let mut payload = vec![0; 8];
+    payload.extend_from_slice(b"mdirappl");
+    payload.extend_from_slice(&[0; 9]);
+    payload
+}
+
+/// Moves `stco`/`co64` entries that point past the old `moov` by `delta`,
+/// since the media data after it moves with the file.
+fn shift_chunk_offsets(moov: &mut [u8], old_moov_end: u64, delta: i64) -> Result<(), &'static str> {
+    walk_boxes(moov, BOX_HEADER_LEN as usize, &mut |kind, payload| {
+        let (width, entries) = match kind {
+            b"stco" => (4, payload),
+            b"co64" => (8, payload),
+            _ => return Ok(()),
+        };
+        let count = entries
+            .get(4..8)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()) as usize)
+            .ok_or("malformed chunk offset table")?;
+        let table = entries
+            .get_mut(8..8 + count * width)
+            .ok_or("malformed chunk offset table")?;
+        for entry in table.chunks_exact_mut(width) {
+            let offset = match width {
+                4 => u64::from(u32::from_be_bytes(entry.try_into().unwrap())),
+                _ => u64::from_be_bytes(entry.try_into().unwrap()),
+            };
+            if offset < old_moov_end {
+                continue;
+            }
+            let moved = offset
+                .checked_add_signed(delta)
+                .ok_or("chunk offset out of range")?;
+            match width {
+                4 => entry.copy_from_slice(
+                    &u32::try_from(moved)
+                        .map_err(|_| "chunk offset out of range")?
+                        .to_be_bytes(),
+                ),
+                _ => entry.copy_from_slice(&moved.to_be_bytes()),
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Visits the sample tables under `trak/mdia/minf/stbl`.
+fn walk_boxes(
+    bytes: &mut [u8],
+    start: usize,
+    visit: &mut impl FnMut(&[u8; 4], &mut [u8]) -> Result<(), &'static str>,
+) -> Result<(), &'static str> {
+    let mut offset = start;
+    while offset + BOX_HEADER_LEN as usize <= bytes.len() {
+        let size = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let len = if size == 0 {
+            bytes.len() - offset
+        } else {
+            size
+        };
+        if len < BOX_HEADER_LEN as usize || offset + len > bytes.len() {
+            return Err("malformed MP4 box");
+        }
+        let kind: [u8; 4] = bytes[offset + 4..offset + 8].try_into().unwrap();
+        let payload = &mut bytes[offset + BOX_HEADER_LEN as usize..offset + len];
+        match &kind {
+            b"trak" | b"mdia" | b"minf" | b"stbl" => walk_boxes(payload, 0, visit)?,
+            _ => visit(&kind, payload)?,
+        }
+        offset += len;
+    }
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tagging");
+    path.with_file_name(name)
+}
+
+fn write_error(path: &Path, err: io::Error) -> AppError {
+    AppError::new(
+        "mp4_tag_write",
+        format!("Failed writing tags to {}: {err}", path.display()),
+    )
+}
+
+fn unsupported(path: &Path, reason: &str) -> AppError {
+    AppError::new(
+        "mp4_tag_unsupported",
+        format!("Can't tag {}: {reason}.", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_freeform_tags(path: &Path) -> Result<Vec<(String, String)>, AppError> {
+        let mut file = File::open(path).map_err(|err| write_error(path, err))?;
+        let file_len = file.metadata().map_err(|err| write_error(path, err))?.len();
+        let boxes = top_level_boxes(&mut file, file_len).map_err(|err| write_error(path, err))?;
+        let Some(moov) = boxes.iter().find(|entry| &entry.kind == b"moov") else {
+            return Ok(Vec::new());
+        };
+        let mut bytes = vec![0; moov.len as usize];
+        file.seek(SeekFrom::Start(moov.offset))
+            .and_then(|_| file.read_exact(&mut bytes))
+            .map_err(|err| write_error(path, err))?;
+
+        let mut tags = Vec::new();
+        let moov_payload = &bytes[BOX_HEADER_LEN as usize..];
+        for udta in find_children(moov_payload, b"udta") {
+            for meta in find_children(udta, b"meta") {
+                let (_, meta_children) = split_meta(meta);
+                for ilst in find_children(meta_children, b"ilst") {
+                    for item in find_children(ilst, b"----") {
+                        if let Some(tag) = parse_freeform(item) {
+                            tags.push(tag);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(tags)
+    }
+
+    /// `ftyp`, `moov` with one track whose `stco` points into the `mdat`
+    /// that follows, then the `mdat` itself.
+    fn sample_file(moov_first: bool) -> Vec<u8> {
+        let ftyp = make_box(b"ftyp", b"M4A \0\0\0\0M4A mp42").unwrap();
+        let mdat = make_box(b"mdat", b"audio-payload").unwrap();
+        let build_moov = |chunk: u32| {
+            let mut stco = vec![0; 4];
+            stco.extend_from_slice(&1u32.to_be_bytes());
+            stco.extend_from_slice(&chunk.to_be_bytes());
+            let stbl = make_box(b"stbl", &make_box(b"stco", &stco).unwrap()).unwrap();
+            let minf = make_box(b"minf", &stbl).unwrap();
+            let mdia = make_box(b"mdia", &minf).unwrap();
+            make_box(b"moov", &make_box(b"trak", &mdia).unwrap()).unwrap()
+        };
+        let moov_len = build_moov(0).len();
+        if moov_first {
+            let chunk = (ftyp.len() + moov_len) as u32 + BOX_HEADER_LEN as u32;
+            [ftyp, build_moov(chunk), mdat].concat()
+        } else {
+            let chunk = ftyp.len() as u32 + BOX_HEADER_LEN as u32;
+            [ftyp, mdat, build_moov(chunk)].concat()
+        }
+    }
+
+    fn chunk_offset(bytes: &[u8]) -> u64 {
+        let mut bytes = bytes.to_vec();
+        let moov = bytes.windows(4).position(|w| w == b"moov").unwrap() - 4;
+        let mut found = 0;
+        walk_boxes(&mut bytes[moov..], 8, &mut |kind, payload| {
+            if kind == b"stco" {
+                found = u64::from(u32::from_be_bytes(payload[8..12].try_into().unwrap()));
+            }
+            Ok(())
+        })
+        .unwrap();
+        found
+    }
+
+    fn payload_at(bytes: &[u8], offset: u64) -> &[u8] {
+        &bytes[offset as usize..offset as usize + 13]
+    }
+
+    #[test]
+    fn writes_and_replaces_freeform_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.m4a");
+        fs::write(&path, sample_file(false)).unwrap();
+
+        write_freeform_tags(
+            &path,
+            &[
+                ("iTunSMPB", " 00000000 00000400"),
+                ("replaygain_track_gain", "-1.00 dB"),
+            ],
+        )
+        .unwrap();
+        write_freeform_tags(&path, &[("ITUNSMPB", " 00000000 00000840")]).unwrap();
+
+        let tags = read_freeform_tags(&path).unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                ("replaygain_track_gain".to_string(), "-1.00 dB".to_string()),
+                ("ITUNSMPB".to_string(), " 00000000 00000840".to_string()),
+            ]
+        );
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(payload_at(&bytes, chunk_offset(&bytes)), b"audio-payload");
+        assert!(!temp_path(&path).exists());
+    }
+
+    #[test]
+    fn shifts_chunk_offsets_when_moov_comes_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.m4a");
+        let original = sample_file(true);
+        fs::write(&path, &original).unwrap();
+        assert_eq!(
+            payload_at(&original, chunk_offset(&original)),
+            b"audio-payload"
+        );
+
+        write_freeform_tags(&path, &[("iTunSMPB", " 00000000")]).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        assert!(chunk_offset(&bytes) > chunk_offset(&original));
+        assert_eq!(payload_at(&bytes, chunk_offset(&bytes)), b"audio-payload");
+    }
+
+    #[test]
+    fn refuses_files_without_moov() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.m4a");
+        fs::write(&path, make_box(b"mdat", b"x").unwrap()).unwrap();
+
+        let err = write_freeform_tags(&path, &[("iTunSMPB", "x")]).unwrap_err();
+        assert_eq!(err.code, "mp4_tag_unsupported");
+    }
+}
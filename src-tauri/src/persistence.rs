@@ -0,0 +1,288 @@
+//! Versioned JSON documents with forward-only migrations.
+//!
+//! Every persisted document is wrapped as `{ "schemaVersion": N, "data": ... }`.
+//! Files written before versioning existed are treated as version 1. Loading
+//! an older document backs the file up, runs the pending migrations, and
+//! rewrites it. Loading a newer one (written by a later release) parses
+//! leniently and leaves the file alone until it is next saved, at which point
+//! the newer file is backed up first.
+//!
+//! Writes go to a temporary sibling that is synced and renamed over the
+//! document, so a crash leaves either the old or the new file, never a
+//! truncated one.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+const VERSION_KEY: &str = "schemaVersion";
+const DATA_KEY: &str = "data";
+
+/// Upgrades a payload by exactly one version.
+pub type Migration = fn(Value) -> Result<Value, AppError>;
+
+/// Describes a persisted document and the migrations leading to its current
+/// shape. `migrations[i]` upgrades version `i + 1` to `i + 2`.
+pub struct DocumentSchema {
+    pub name: &'static str,
+    pub migrations: &'static [Migration],
+}
+
+impl DocumentSchema {
+    pub fn current_version(&self) -> u32 {
+        self.migrations.len() as u32 + 1
+    }
+
+    fn migrate(&self, mut payload: Value, from: u32) -> Result<Value, AppError> {
+        for migration in self.migrations.iter().skip(from.saturating_sub(1) as usize) {
+            payload = migration(payload)?;
+        }
+        Ok(payload)
+    }
+}
+
+/// Loads a document, migrating it in place when it predates `schema`.
+pub fn load<T: DeserializeOwned>(
+    path: &Path,
+    schema: &DocumentSchema,
+) -> Result<Option<T>, AppError> {
+    let Some((version, payload, enveloped)) = read_document(path)? else {
+        return Ok(None);
+    };
+
+    let current = schema.current_version();
+    let payload = if version < current || !enveloped {
+        backup(path, version)?;
+        let migrated = schema.migrate(payload, version).map_err(|err| {
+            AppError::new(
+                "persist_migration",
+                format!(
+                    "Failed migrating {} from v{version}: {}",
+                    schema.name, err.message
+                ),
+            )
+        })?;
+        write_document(path, current, &migrated)?;
+        migrated
+    } else {
+        payload
+    };
+
+    serde_json::from_value(payload).map(Some).map_err(|err| {
+        AppError::new(
+            "persist_parse",
+            format!("Invalid {} document: {err}", schema.name),
+        )
+    })
+}
+
+/// Writes a document tagged with the schema's current version.
+pub fn persist<T: Serialize>(
+    path: &Path,
+    schema: &DocumentSchema,
+    data: &T,
+) -> Result<(), AppError> {
+    if let Some((version, _, _)) = read_document(path).ok().flatten() {
+        if version > schema.current_version() {
+            backup(path, version)?;
+        }
+    }
+
+    let payload = serde_json::to_value(data)
+        .map_err(|err| AppError::new("persist_write", err.to_string()))?;
+    write_document(path, schema.current_version(), &payload)
+}
+
+/// Returns `(version, payload, enveloped)`; legacy files are version 1.
+fn read_document(path: &Path) -> Result<Option<(u32, Value, bool)>, AppError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let data = fs::read(path).map_err(|err| AppError::new("persist_read", err.to_string()))?;
+    let mut value: Value = serde_json::from_slice(&data)
+        .map_err(|err| AppError::new("persist_parse", err.to_string()))?;
+
+    let version = value
+        .get(VERSION_KEY)
+        .and_then(Value::as_u64)
+        .filter(|_| value.get(DATA_KEY).is_some());
+
+    Ok(Some(match version {
+        Some(version) => (
+            u32::try_from(version).unwrap_or(u32::MAX),
+            value[DATA_KEY].take(),
+            true,
+        ),
+        None => (1, value, false),
+    }))
+}
+
+fn write_document(path: &Path, version: u32, payload: &Value) -> Result<(), AppError> {
+    let document = json!({ VERSION_KEY: version, DATA_KEY: payload });
+    let data = serde_json::to_vec_pretty(&document)
+        .map_err(|err| AppError::new("persist_write", err.to_string()))?;
+    write_atomic(path, &data).map_err(|err| AppError::new("persist_write", err.to_string()))
+}
+
+/// Replaces `path` with `data` through a synced temporary file and a rename.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{file_name}.tmp"));
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|_| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(err);
+    }
+
+    // Persist the rename itself; directories can't be opened on Windows.
+    #[cfg(unix)]
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Copies the file to `<name>.v<version>.bak`, keeping the first backup made
+/// for a given version.
+fn backup(path: &Path, version: u32) -> Result<PathBuf, AppError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let target = path.with_file_name(format!("{file_name}.v{version}.bak"));
+
+    if !target.exists() {
+        fs::copy(path, &target).map_err(|err| AppError::new("persist_backup", err.to_string()))?;
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use tempfile::TempDir;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        name: String,
+        #[serde(default)]
+        retries: u32,
+    }
+
+    const V1: DocumentSchema = DocumentSchema {
+        name: "settings",
+        migrations: &[],
+    };
+
+    fn rename_title(mut payload: Value) -> Result<Value, AppError> {
+        if let Some(title) = payload.get_mut("title").map(Value::take) {
+            payload["name"] = title;
+        }
+        Ok(payload)
+    }
+
+    const V2: DocumentSchema = DocumentSchema {
+        name: "settings",
+        migrations: &[rename_title],
+    };
+
+    fn settings() -> Settings {
+        Settings {
+            name: "demo".into(),
+            retries: 2,
+        }
+    }
+
+    #[test]
+    fn persist_wraps_data_with_version() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("settings.json");
+        persist(&path, &V1, &settings()).unwrap();
+
+        let raw: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(raw["schemaVersion"], 1);
+        assert_eq!(raw["data"]["name"], "demo");
+        assert_eq!(load::<Settings>(&path, &V1).unwrap(), Some(settings()));
+    }
+
+    #[test]
+    fn legacy_documents_are_backed_up_and_enveloped() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, br#"{"name":"legacy"}"#).unwrap();
+
+        let loaded: Settings = load(&path, &V1).unwrap().unwrap();
+        assert_eq!(loaded.name, "legacy");
+        assert!(temp.path().join("settings.json.v1.bak").exists());
+
+        let raw: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(raw["schemaVersion"], 1);
+    }
+
+    #[test]
+    fn older_versions_run_pending_migrations() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(&path, br#"{"schemaVersion":1,"data":{"title":"old"}}"#).unwrap();
+
+        let loaded: Settings = load(&path, &V2).unwrap().unwrap();
+        assert_eq!(loaded.name, "old");
+
+        let backup: Value =
+            serde_json::from_slice(&fs::read(temp.path().join("settings.json.v1.bak")).unwrap())
+                .unwrap();
+        assert_eq!(backup["data"]["title"], "old");
+    }
+
+    #[test]
+    fn newer_versions_parse_leniently_and_are_backed_up_on_save() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("settings.json");
+        fs::write(
+            &path,
+            br#"{"schemaVersion":7,"data":{"name":"future","extra":true}}"#,
+        )
+        .unwrap();
+
+        let loaded: Settings = load(&path, &V1).unwrap().unwrap();
+        assert_eq!(loaded.name, "future");
+        assert!(!temp.path().join("settings.json.v7.bak").exists());
+
+        persist(&path, &V1, &loaded).unwrap();
+        assert!(temp.path().join("settings.json.v7.bak").exists());
+    }
+
+    #[test]
+    fn atomic_writes_replace_the_file_and_leave_no_temporary() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("nested").join("settings.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        let names: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["settings.json"]);
+    }
+}
@@ -0,0 +1,726 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::mp4_tags;
+
+const AAC_FRAME_SIZE: u64 = 1024;
+const ITUNSMPB: &str = "iTunSMPB";
+
+/// Sidecar written next to album outputs to preserve track order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlaylistFormat {
+    Cue,
+    M3u,
+}
+
+/// A source track with the metadata album planning needs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumTrack {
+    pub path: String,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub duration_sec: Option<f64>,
+    pub sample_rate: Option<u32>,
+}
+
+impl AlbumTrack {
+    /// Reads tags and audio parameters from raw ffprobe JSON.
+    pub fn from_probe(path: &str, raw: &Value) -> Self {
+        let format = &raw["format"];
+        let audio = raw["streams"]
+            .as_array()
+            .and_then(|streams| {
+                streams
+                    .iter()
+                    .find(|stream| stream["codec_type"] == "audio")
+            })
+            .unwrap_or(&Value::Null);
+
+        let tag =
+            |key: &str| find_tag(&format["tags"], key).or_else(|| find_tag(&audio["tags"], key));
+
+        Self {
+            path: path.to_string(),
+            track_number: tag("track").as_deref().and_then(parse_position),
+            disc_number: tag("disc").as_deref().and_then(parse_position),
+            title: tag("title"),
+            artist: tag("artist"),
+            album: tag("album"),
+            album_artist: tag("album_artist"),
+            duration_sec: format["duration"]
+                .as_str()
+                .and_then(|value| value.parse().ok()),
+            sample_rate: audio["sample_rate"]
+                .as_str()
+                .and_then(|value| value.parse().ok()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumRequest {
+    pub paths: Vec<String>,
+    pub output_directory: String,
+    /// Output container extension without the dot (`m4a`, `mp3`, `flac`, ...).
+    pub container: String,
+    /// Overrides the album tag read from the sources.
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub playlist: Option<PlaylistFormat>,
+    /// Audio encoder the tracks are encoded with; its priming delay goes
+    /// into the gapless metadata. Defaults to FFmpeg's native `aac`.
+    #[serde(default)]
+    pub audio_encoder: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumTrackPlan {
+    pub source: String,
+    pub output: String,
+    pub track_number: u32,
+    pub disc_number: u32,
+    /// Metadata and gapless arguments appended to the track's output args.
+    pub args: Vec<String>,
+    /// `iTunSMPB` value written into MP4 outputs once they are encoded,
+    /// since FFmpeg can't write iTunes freeform items itself.
+    pub itunsmpb: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistFile {
+    pub path: String,
+    pub content: String,
+}
+
+impl PlaylistFile {
+    /// Writes the playlist, which must be a `.cue` or `.m3u8` file directly
+    /// inside `output_directory`.
+    pub fn write(&self, output_directory: &Path) -> Result<(), AppError> {
+        let path = Path::new(&self.path);
+        let is_playlist = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext, "cue" | "m3u8"));
+        if !is_playlist || path.parent() != Some(output_directory) {
+            return Err(AppError::new(
+                "album_playlist_path",
+                format!(
+                    "Playlists are only written as .cue or .m3u8 files in {}.",
+                    output_directory.display()
+                ),
+            ));
+        }
+        std::fs::write(path, &self.content).map_err(|err| {
+            AppError::new(
+                "album_playlist_write",
+                format!("Failed writing {}: {err}", self.path),
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumPlan {
+    pub tracks: Vec<AlbumTrackPlan>,
+    pub playlist: Option<PlaylistFile>,
+    pub warnings: Vec<String>,
+}
+
+/// Orders the tracks (disc, track number, then file name), numbers them,
+/// and builds per-track args that keep album tags and gapless metadata.
+pub fn plan_album(
+    request: &AlbumRequest,
+    mut tracks: Vec<AlbumTrack>,
+) -> Result<AlbumPlan, AppError> {
+    if tracks.is_empty() {
+        return Err(AppError::new(
+            "album_empty",
+            "Album mode needs at least one track.",
+        ));
+    }
+
+    let container = request
+        .container
+        .trim()
+        .trim_start_matches('.')
+        .to_lowercase();
+    if container.is_empty() {
+        return Err(AppError::new(
+            "album_invalid_container",
+            "Album output container is required.",
+        ));
+    }
+
+    let mut warnings = Vec::new();
+    if tracks.iter().any(|track| track.track_number.is_none()) {
+        warnings.push("Some tracks have no track number; they are ordered by file name.".into());
+    }
+
+    tracks.sort_by(compare_tracks);
+
+    let album = request
+        .album
+        .clone()
+        .or_else(|| tracks.iter().find_map(|track| track.album.clone()));
+    let album_artist = request.album_artist.clone().or_else(|| {
+        tracks
+            .iter()
+            .find_map(|track| track.album_artist.clone().or_else(|| track.artist.clone()))
+    });
+    let disc_total = tracks
+        .iter()
+        .filter_map(|track| track.disc_number)
+        .max()
+        .unwrap_or(1);
+
+    let encoder_delay = encoder_delay(request.audio_encoder.as_deref());
+    let output_dir = PathBuf::from(&request.output_directory);
+    let mut plans = Vec::with_capacity(tracks.len());
+    let mut disc_counts: Vec<(u32, u32)> = Vec::new();
+    for track in &tracks {
+        let disc = track.disc_number.unwrap_or(1);
+        match disc_counts.iter_mut().find(|(entry, _)| *entry == disc) {
+            Some((_, count)) => *count += 1,
+            None => disc_counts.push((disc, 1)),
+        }
+    }
+
+    let mut current_disc = None;
+    let mut position = 0;
+    for track in &tracks {
+        let disc = track.disc_number.unwrap_or(1);
+        if current_disc != Some(disc) {
+            current_disc = Some(disc);
+            position = 0;
+        }
+        position += 1;
+        let number = track.track_number.unwrap_or(position);
+        let disc_tracks = disc_counts
+            .iter()
+            .find(|(entry, _)| *entry == disc)
+            .map(|(_, count)| *count)
+            .unwrap_or(1);
+
+        let mut args = vec!["-map_metadata".to_string(), "0".to_string()];
+        push_metadata(&mut args, "track", format!("{number}/{disc_tracks}"));
+        push_metadata(&mut args, "disc", format!("{disc}/{disc_total}"));
+        if let Some(album) = &album {
+            push_metadata(&mut args, "album", album.clone());
+        }
+        if let Some(album_artist) = &album_artist {
+            push_metadata(&mut args, "album_artist", album_artist.clone());
+        }
+        args.extend(gapless_args(&container));
+        let itunsmpb = if is_mp4(&container) {
+            let value = itunsmpb(track, encoder_delay);
+            if value.is_none() {
+                warnings.push(format!(
+                    "{}: duration or sample rate unknown; iTunSMPB gapless info won't be written.",
+                    file_name(&track.path)
+                ));
+            }
+            value
+        } else {
+            None
+        };
+
+        let prefix = if disc_total > 1 {
+            format!("{disc}-{number:02}")
+        } else {
+            format!("{number:02}")
+        };
+        let name = format!(
+            "{prefix} - {}.{container}",
+            sanitize_file_name(&display_title(track))
+        );
+
+        plans.push(AlbumTrackPlan {
+            source: track.path.clone(),
+            output: output_dir.join(name).to_string_lossy().into_owned(),
+            track_number: number,
+            disc_number: disc,
+            args,
+            itunsmpb,
+        });
+    }
+
+    let playlist_format = match request.playlist {
+        Some(PlaylistFormat::Cue) if cue_file_type(&container).is_none() => {
+            warnings.push(format!(
+                "Cue sheets can't reference .{container} files; an M3U playlist is written instead."
+            ));
+            Some(PlaylistFormat::M3u)
+        },
+        format => format,
+    };
+    let playlist = playlist_format.map(|format| PlaylistFile {
+        path: output_dir
+            .join(format!(
+                "{}.{}",
+                sanitize_file_name(album.as_deref().unwrap_or("album")),
+                match format {
+                    PlaylistFormat::Cue => "cue",
+                    PlaylistFormat::M3u => "m3u8",
+                }
+            ))
+            .to_string_lossy()
+            .into_owned(),
+        content: match format {
+            PlaylistFormat::Cue => render_cue(
+                &tracks,
+                &plans,
+                cue_file_type(&container).unwrap_or("WAVE"),
+                album.as_deref(),
+                album_artist.as_deref(),
+            ),
+            PlaylistFormat::M3u => render_m3u(&tracks, &plans),
+        },
+    });
+
+    Ok(AlbumPlan {
+        tracks: plans,
+        playlist,
+        warnings,
+    })
+}
+
+/// Writes the playlist and the MP4 gapless items once the album's outputs
+/// have been encoded. Tracks whose output is missing are reported and
+/// skipped.
+pub fn finish_album(request: &AlbumRequest, plan: &AlbumPlan) -> Result<Vec<String>, AppError> {
+    let mut warnings = Vec::new();
+    for track in &plan.tracks {
+        let Some(value) = &track.itunsmpb else {
+            continue;
+        };
+        let output = Path::new(&track.output);
+        if !output.is_file() {
+            warnings.push(format!(
+                "{}: output not found; gapless info skipped.",
+                track.output
+            ));
+            continue;
+        }
+        mp4_tags::write_freeform_tags(output, &[(ITUNSMPB, value)])?;
+    }
+
+    if let Some(playlist) = &plan.playlist {
+        playlist.write(Path::new(&request.output_directory))?;
+    }
+    Ok(warnings)
+}
+
+/// Container-specific arguments that keep encoder delay and padding so
+/// players can trim them and play the album without gaps.
+fn gapless_args(container: &str) -> Vec<String> {
+    match container {
+        "mp3" => vec![
+            "-write_xing".into(),
+            "1".into(),
+            "-id3v2_version".into(),
+            "3".into(),
+        ],
+        container if is_mp4(container) => vec!["-use_editlist".into(), "1".into()],
+        // Opus and Vorbis carry pre-skip in their headers; FLAC and WAV are
+        // sample-exact already.
+        _ => Vec::new(),
+    }
+}
+
+fn is_mp4(container: &str) -> bool {
+    matches!(container, "m4a" | "m4b" | "mp4")
+}
+
+/// Priming samples each AAC encoder inserts before the first frame.
+fn encoder_delay(encoder: Option<&str>) -> u64 {
+    match encoder.map(str::trim) {
+        Some("aac_at") => 2112,
+        Some("libfdk_aac") => 2048,
+        _ => 1024,
+    }
+}
+
+/// iTunes gapless descriptor: encoder delay, end padding, and the original
+/// sample count as hex words.
+fn itunsmpb(track: &AlbumTrack, delay: u64) -> Option<String> {
+    let duration = track.duration_sec.filter(|value| *value > 0.0)?;
+    let sample_rate = track.sample_rate.filter(|rate| *rate > 0)?;
+    let samples = (duration * f64::from(sample_rate)).round() as u64;
+    let padding = (AAC_FRAME_SIZE - (samples + delay) % AAC_FRAME_SIZE) % AAC_FRAME_SIZE;
+
+    Some(format!(
+        " 00000000 {delay:08X} {padding:08X} {samples:016X}{}",
+        " 00000000".repeat(8)
+    ))
+}
+
+/// Cue sheet `FILE` type for a container. WAVE is the de facto type for
+/// lossless audio files, FLAC included; lossy formats other than MP3 have
+/// none.
+fn cue_file_type(container: &str) -> Option<&'static str> {
+    match container {
+        "wav" | "flac" => Some("WAVE"),
+        "aif" | "aiff" => Some("AIFF"),
+        "mp3" => Some("MP3"),
+        _ => None,
+    }
+}
+
+fn render_cue(
+    tracks: &[AlbumTrack],
+    plans: &[AlbumTrackPlan],
+    file_type: &str,
+    album: Option<&str>,
+    album_artist: Option<&str>,
+) -> String {
+    let mut cue = String::new();
+    if let Some(artist) = album_artist {
+        cue.push_str(&format!("PERFORMER \"{}\"\n", escape_cue(artist)));
+    }
+    if let Some(album) = album {
+        cue.push_str(&format!("TITLE \"{}\"\n", escape_cue(album)));
+    }
+
+    for (index, (track, plan)) in tracks.iter().zip(plans).enumerate() {
+        cue.push_str(&format!(
+            "FILE \"{}\" {file_type}\n",
+            escape_cue(&file_name(&plan.output))
+        ));
+        cue.push_str(&format!("  TRACK {:02} AUDIO\n", index + 1));
+        cue.push_str(&format!(
+            "    TITLE \"{}\"\n",
+            escape_cue(&display_title(track))
+        ));
+        if let Some(artist) = &track.artist {
+            cue.push_str(&format!("    PERFORMER \"{}\"\n", escape_cue(artist)));
+        }
+        cue.push_str("    INDEX 01 00:00:00\n");
+    }
+    cue
+}
+
+fn render_m3u(tracks: &[AlbumTrack], plans: &[AlbumTrackPlan]) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for (track, plan) in tracks.iter().zip(plans) {
+        let seconds = track
+            .duration_sec
+            .map(|value| value.round() as i64)
+            .unwrap_or(-1);
+        let title = match &track.artist {
+            Some(artist) => format!("{artist} - {}", display_title(track)),
+            None => display_title(track),
+        };
+        m3u.push_str(&format!(
+            "#EXTINF:{seconds},{title}\n{}\n",
+            file_name(&plan.output)
+        ));
+    }
+    m3u
+}
+
+fn compare_tracks(a: &AlbumTrack, b: &AlbumTrack) -> Ordering {
+    a.disc_number
+        .unwrap_or(1)
+        .cmp(&b.disc_number.unwrap_or(1))
+        .then_with(|| match (a.track_number, b.track_number) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        .then_with(|| {
+            file_name(&a.path)
+                .to_lowercase()
+                .cmp(&file_name(&b.path).to_lowercase())
+        })
+}
+
+fn push_metadata(args: &mut Vec<String>, key: &str, value: String) {
+    args.push("-metadata".into());
+    args.push(format!("{key}={value}"));
+}
+
+fn find_tag(tags: &Value, key: &str) -> Option<String> {
+    tags.as_object()?
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .and_then(|(_, value)| value.as_str())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Parses `3` or `3/12` into the leading position.
+fn parse_position(value: &str) -> Option<u32> {
+    value
+        .split('/')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|number| *number > 0)
+}
+
+fn display_title(track: &AlbumTrack) -> String {
+    track.title.clone().unwrap_or_else(|| {
+        Path::new(&track.path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| track.path.clone())
+    })
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn sanitize_file_name(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect();
+    let trimmed = cleaned.trim().trim_end_matches('.');
+    if trimmed.is_empty() {
+        "untitled".into()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn escape_cue(value: &str) -> String {
+    value.replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn track(path: &str, number: Option<u32>, title: &str) -> AlbumTrack {
+        AlbumTrack {
+            path: path.into(),
+            track_number: number,
+            title: Some(title.into()),
+            artist: Some("Band".into()),
+            album: Some("Live".into()),
+            duration_sec: Some(60.0),
+            sample_rate: Some(44_100),
+            ..AlbumTrack::default()
+        }
+    }
+
+    fn request(container: &str, playlist: Option<PlaylistFormat>) -> AlbumRequest {
+        AlbumRequest {
+            paths: Vec::new(),
+            output_directory: "/out".into(),
+            container: container.into(),
+            album: None,
+            album_artist: None,
+            playlist,
+            audio_encoder: None,
+        }
+    }
+
+    #[test]
+    fn reads_tags_and_sample_rate_from_probe_json() {
+        let raw = json!({
+            "format": { "duration": "61.5", "tags": { "TRACK": "3/12", "Title": "Song", "album": "LP" } },
+            "streams": [{ "codec_type": "audio", "sample_rate": "48000", "tags": { "disc": "2" } }]
+        });
+        let parsed = AlbumTrack::from_probe("/a/03.flac", &raw);
+
+        assert_eq!(parsed.track_number, Some(3));
+        assert_eq!(parsed.disc_number, Some(2));
+        assert_eq!(parsed.title.as_deref(), Some("Song"));
+        assert_eq!(parsed.album.as_deref(), Some("LP"));
+        assert_eq!(parsed.sample_rate, Some(48_000));
+        assert_eq!(parsed.duration_sec, Some(61.5));
+    }
+
+    #[test]
+    fn tracks_are_ordered_and_tagged() {
+        let tracks = vec![
+            track("/in/b.flac", Some(2), "Second"),
+            track("/in/a.flac", Some(1), "First"),
+        ];
+        let plan = plan_album(&request("mp3", None), tracks).unwrap();
+
+        assert_eq!(plan.tracks[0].source, "/in/a.flac");
+        assert_eq!(plan.tracks[0].output, "/out/01 - First.mp3");
+        assert!(plan.tracks[1].args.contains(&"track=2/2".to_string()));
+        assert!(plan.tracks[1].args.contains(&"album=Live".to_string()));
+        assert!(plan.tracks[1].args.contains(&"-write_xing".to_string()));
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[test]
+    fn untagged_tracks_fall_back_to_file_order() {
+        let tracks = vec![
+            track("/in/Track 2.wav", None, "B"),
+            track("/in/Track 1.wav", None, "A"),
+        ];
+        let plan = plan_album(&request("flac", None), tracks).unwrap();
+
+        assert_eq!(plan.tracks[0].source, "/in/Track 1.wav");
+        assert_eq!(plan.tracks[1].track_number, 2);
+        assert_eq!(plan.warnings.len(), 1);
+    }
+
+    #[test]
+    fn aac_outputs_carry_itunsmpb() {
+        let plan = plan_album(
+            &request("m4a", None),
+            vec![track("/in/a.flac", Some(1), "A")],
+        )
+        .unwrap();
+        let smpb = plan.tracks[0].itunsmpb.as_deref().expect("iTunSMPB value");
+
+        // 60s at 44.1kHz = 2_646_000 samples; with 1024 priming samples the
+        // last frame needs 16 samples of padding.
+        assert!(smpb.contains(" 00000400 00000010 0000000000285FF0"));
+        assert_eq!(smpb.split_whitespace().count(), 12);
+        assert!(!plan.tracks[0]
+            .args
+            .iter()
+            .any(|arg| arg.contains("iTunSMPB")));
+        assert!(!plan.tracks[0]
+            .args
+            .contains(&"+use_metadata_tags".to_string()));
+    }
+
+    #[test]
+    fn itunsmpb_uses_the_encoders_delay() {
+        let mut request = request("m4a", None);
+        request.audio_encoder = Some("aac_at".into());
+        let plan = plan_album(&request, vec![track("/in/a.flac", Some(1), "A")]).unwrap();
+
+        // 2112 priming samples leave 976 samples of padding.
+        assert!(plan.tracks[0]
+            .itunsmpb
+            .as_deref()
+            .unwrap()
+            .contains(" 00000840 000003D0 0000000000285FF0"));
+    }
+
+    #[test]
+    fn playlist_files_follow_album_order() {
+        let tracks = vec![
+            track("/in/b.flac", Some(2), "Second"),
+            track("/in/a.flac", Some(1), "First"),
+        ];
+        let cue = plan_album(&request("flac", Some(PlaylistFormat::Cue)), tracks.clone())
+            .unwrap()
+            .playlist
+            .unwrap();
+        assert_eq!(cue.path, "/out/Live.cue");
+        let first = cue.content.find("01 - First.flac").unwrap();
+        let second = cue.content.find("02 - Second.flac").unwrap();
+        assert!(first < second);
+
+        let m3u = plan_album(&request("flac", Some(PlaylistFormat::M3u)), tracks)
+            .unwrap()
+            .playlist
+            .unwrap();
+        assert!(m3u
+            .content
+            .starts_with("#EXTM3U\n#EXTINF:60,Band - First\n01 - First.flac\n"));
+    }
+
+    #[test]
+    fn cue_sheets_name_the_file_type() {
+        let tracks = vec![track("/in/a.flac", Some(1), "First")];
+        let mp3 = plan_album(&request("mp3", Some(PlaylistFormat::Cue)), tracks.clone()).unwrap();
+        assert!(mp3
+            .playlist
+            .unwrap()
+            .content
+            .contains("FILE \"01 - First.mp3\" MP3\n"));
+
+        let m4a = plan_album(&request("m4a", Some(PlaylistFormat::Cue)), tracks).unwrap();
+        assert_eq!(m4a.playlist.unwrap().path, "/out/Live.m3u8");
+        assert!(m4a
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("Cue sheets")));
+    }
+
+    #[test]
+    fn finishing_writes_the_playlist_and_gapless_items() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut request = request("m4a", Some(PlaylistFormat::M3u));
+        request.output_directory = temp.path().to_string_lossy().into_owned();
+        let plan = plan_album(&request, vec![track("/in/a.flac", Some(1), "A")]).unwrap();
+        let moov = [
+            &24u32.to_be_bytes()[..],
+            b"moov",
+            &16u32.to_be_bytes(),
+            b"udta",
+            &[0; 8],
+        ]
+        .concat();
+        std::fs::write(&plan.tracks[0].output, moov).unwrap();
+
+        let warnings = finish_album(&request, &plan).unwrap();
+
+        assert!(warnings.is_empty());
+        let playlist = plan.playlist.as_ref().unwrap();
+        assert!(std::fs::read_to_string(&playlist.path)
+            .unwrap()
+            .starts_with("#EXTM3U\n"));
+        let output = std::fs::read(&plan.tracks[0].output).unwrap();
+        assert!(output.windows(8).any(|window| window == b"iTunSMPB"));
+    }
+
+    #[test]
+    fn playlists_stay_inside_the_output_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let outside = PlaylistFile {
+            path: temp
+                .path()
+                .join("nested/Live.m3u8")
+                .to_string_lossy()
+                .into_owned(),
+            content: "#EXTM3U\n".into(),
+        };
+        let wrong_kind = PlaylistFile {
+            path: temp.path().join("Live.sh").to_string_lossy().into_owned(),
+            content: "#EXTM3U\n".into(),
+        };
+
+        for playlist in [outside, wrong_kind] {
+            assert_eq!(
+                playlist.write(temp.path()).unwrap_err().code,
+                "album_playlist_path"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_empty_albums() {
+        assert_eq!(
+            plan_album(&request("flac", None), Vec::new())
+                .unwrap_err()
+                .code,
+            "album_empty"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Options that never take a value, so the token after them is read as the
+/// next option or a positional value.
+const NO_VALUE_FLAGS: &[&str] = &[
+    "-y",
+    "-n",
+    "-nostdin",
+    "-stdin",
+    "-nostats",
+    "-stats",
+    "-hide_banner",
+    "-vn",
+    "-an",
+    "-sn",
+    "-dn",
+    "-shortest",
+    "-re",
+    "-copyts",
+    "-start_at_zero",
+    "-accurate_seek",
+    "-noaccurate_seek",
+    "-autorotate",
+    "-noautorotate",
+    "-benchmark",
+    "-xerror",
+    "-ignore_unknown",
+];
+
+/// A single ffmpeg option as it appears in a planned argument list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanOption {
+    /// Option flag (e.g. `-c:v`), or an empty string for positional values.
+    pub flag: String,
+    pub value: Option<String>,
+}
+
+/// An option present in both plans whose value differs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedOption {
+    pub flag: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Structured difference between two planned argument sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanDiff {
+    pub added: Vec<PlanOption>,
+    pub removed: Vec<PlanOption>,
+    pub changed: Vec<ChangedOption>,
+    pub identical: bool,
+}
+
+/// Compares two ffmpeg argument lists option by option.
+///
+/// Repeated flags such as `-map` or `-i` are matched by occurrence, so the
+/// second `-map` in `before` is compared against the second `-map` in `after`.
+pub fn diff_plans(before: &[String], after: &[String]) -> PlanDiff {
+    let before_options = keyed_options(before);
+    let after_options = keyed_options(after);
+
+    let before_lookup: HashMap<_, _> = before_options
+        .iter()
+        .map(|(key, option)| (key.clone(), option))
+        .collect();
+    let after_lookup: HashMap<_, _> = after_options
+        .iter()
+        .map(|(key, option)| (key.clone(), option))
+        .collect();
+
+    let mut diff = PlanDiff::default();
+
+    for (key, option) in &after_options {
+        match before_lookup.get(key) {
+            None => diff.added.push(option.clone()),
+            Some(previous) if previous.value != option.value => {
+                diff.changed.push(ChangedOption {
+                    flag: option.flag.clone(),
+                    before: previous.value.clone(),
+                    after: option.value.clone(),
+                });
+            },
+            Some(_) => {},
+        }
+    }
+
+    diff.removed = before_options
+        .iter()
+        .filter(|(key, _)| !after_lookup.contains_key(key))
+        .map(|(_, option)| option.clone())
+        .collect();
+
+    diff.identical = diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty();
+    diff
+}
+
+fn keyed_options(args: &[String]) -> Vec<((String, usize), PlanOption)> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    parse_options(args)
+        .into_iter()
+        .map(|option| {
+            let count = occurrences.entry(option.flag.clone()).or_insert(0);
+            let key = (option.flag.clone(), *count);
+            *count += 1;
+            (key, option)
+        })
+        .collect()
+}
+
+fn parse_options(args: &[String]) -> Vec<PlanOption> {
+    let mut options = Vec::new();
+    let mut index = 0;
+
+    while index < args.len() {
+        let current = &args[index];
+        if !is_flag(current) {
+            options.push(PlanOption {
+                flag: String::new(),
+                value: Some(current.clone()),
+            });
+            index += 1;
+            continue;
+        }
+
+        let value = if NO_VALUE_FLAGS.contains(&current.as_str()) {
+            None
+        } else {
+            args.get(index + 1).filter(|next| !is_flag(next)).cloned()
+        };
+        index += if value.is_some() { 2 } else { 1 };
+        options.push(PlanOption {
+            flag: current.clone(),
+            value,
+        });
+    }
+
+    options
+}
+
+/// Flags start with `-`; negative numbers (e.g. `scale=-2` values or `-1`)
+/// are treated as values.
+fn is_flag(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with('-') && arg.parse::<f64>().is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn identical_plans_have_no_differences() {
+        let plan = args(&["-i", "in.mp4", "-c:v", "copy", "-map", "0:v", "-map", "0:a"]);
+        let diff = diff_plans(&plan, &plan);
+        assert!(diff.identical);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_options() {
+        let before = args(&["-i", "in.mp4", "-c:v", "libx264", "-crf", "23", "-y"]);
+        let after = args(&["-i", "in.mp4", "-c:v", "libx265", "-tag:v", "hvc1", "-y"]);
+
+        let diff = diff_plans(&before, &after);
+        assert!(!diff.identical);
+        assert_eq!(
+            diff.changed,
+            vec![ChangedOption {
+                flag: "-c:v".into(),
+                before: Some("libx264".into()),
+                after: Some("libx265".into()),
+            }]
+        );
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].flag, "-tag:v");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].flag, "-crf");
+    }
+
+    #[test]
+    fn boolean_flags_do_not_take_the_next_token() {
+        let before = args(&["-y", "-i", "in.mp4", "-an", "0:v"]);
+        let after = args(&["-y", "-i", "in.mp4", "-vn", "0:v"]);
+
+        let diff = diff_plans(&before, &after);
+        assert!(diff.changed.is_empty());
+        assert_eq!(
+            diff.added[0],
+            PlanOption {
+                flag: "-vn".into(),
+                value: None
+            }
+        );
+        assert_eq!(
+            diff.removed[0],
+            PlanOption {
+                flag: "-an".into(),
+                value: None
+            }
+        );
+    }
+
+    #[test]
+    fn repeated_flags_are_matched_by_occurrence() {
+        let before = args(&["-map", "0:v", "-map", "0:a"]);
+        let after = args(&["-map", "0:v", "-map", "0:a:1", "-map", "0:s"]);
+
+        let diff = diff_plans(&before, &after);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].after.as_deref(), Some("0:a:1"));
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].value.as_deref(), Some("0:s"));
+    }
+
+    #[test]
+    fn negative_numbers_are_values_not_flags() {
+        let options = parse_options(&args(&["-qscale:v", "-1", "-an"]));
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[0].value.as_deref(), Some("-1"));
+        assert_eq!(options[1].value, None);
+    }
+}
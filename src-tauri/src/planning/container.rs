@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// Requested output container plus an ordered list of acceptable substitutes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRequest {
+    pub container: String,
+    /// Preset-specific fallbacks; built-in defaults apply when empty.
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
+    /// Planned output path, re-extended when a fallback is chosen.
+    #[serde(default)]
+    pub output_path: Option<String>,
+}
+
+/// Container the job should actually be written to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerPlan {
+    pub container: String,
+    /// Muxer name to pass to ffmpeg via `-f`.
+    pub muxer: String,
+    pub fell_back: bool,
+    pub warnings: Vec<String>,
+    /// The request's output path with the chosen container's extension.
+    pub output_path: Option<String>,
+}
+
+/// Maps a Honeymelon container to the ffmpeg muxer that writes it.
+pub fn muxer_for_container(container: &str) -> &str {
+    match container {
+        "mkv" => "matroska",
+        "m4a" => "ipod",
+        "png" | "jpg" => "image2",
+        other => other,
+    }
+}
+
+fn default_fallbacks(container: &str) -> &'static [&'static str] {
+    match container {
+        "mp4" => &["mov", "mkv"],
+        "mov" => &["mp4", "mkv"],
+        "webm" => &["mkv"],
+        "m4a" => &["mp4", "mkv"],
+        _ => &[],
+    }
+}
+
+/// Picks the first container in the request's chain whose muxer the detected
+/// FFmpeg build provides. An empty `available_muxers` means capabilities are
+/// unknown, in which case the requested container is trusted as-is.
+pub fn resolve_container(
+    request: &ContainerRequest,
+    available_muxers: &[String],
+) -> Result<ContainerPlan, AppError> {
+    let requested = request.container.trim().to_lowercase();
+    if requested.is_empty() {
+        return Err(AppError::new(
+            "container_missing",
+            "No output container was requested.",
+        ));
+    }
+
+    if available_muxers.is_empty() || has_muxer(available_muxers, &requested) {
+        return Ok(ContainerPlan {
+            muxer: muxer_for_container(&requested).to_string(),
+            output_path: request.output_path.clone(),
+            container: requested,
+            fell_back: false,
+            warnings: Vec::new(),
+        });
+    }
+
+    let chain: Vec<String> = if request.fallbacks.is_empty() {
+        default_fallbacks(&requested)
+            .iter()
+            .map(|value| value.to_string())
+            .collect()
+    } else {
+        request
+            .fallbacks
+            .iter()
+            .map(|value| value.trim().to_lowercase())
+            .collect()
+    };
+
+    let fallback = chain
+        .into_iter()
+        .filter(|candidate| *candidate != requested)
+        .find(|candidate| has_muxer(available_muxers, candidate));
+
+    match fallback {
+        Some(container) => Ok(ContainerPlan {
+            warnings: vec![format!(
+                "The {} muxer is unavailable in this FFmpeg build; writing {container} instead.",
+                muxer_for_container(&requested)
+            )],
+            muxer: muxer_for_container(&container).to_string(),
+            output_path: request
+                .output_path
+                .as_deref()
+                .map(|path| with_extension(path, &container)),
+            container,
+            fell_back: true,
+        }),
+        None => Err(AppError::new(
+            "container_unavailable",
+            format!(
+                "The {} muxer is unavailable and no fallback container is supported.",
+                muxer_for_container(&requested)
+            ),
+        )),
+    }
+}
+
+fn has_muxer(available_muxers: &[String], container: &str) -> bool {
+    let muxer = muxer_for_container(container);
+    available_muxers.iter().any(|name| name == muxer)
+}
+
+fn with_extension(path: &str, container: &str) -> String {
+    Path::new(path)
+        .with_extension(container)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formats(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn request(container: &str, fallbacks: &[&str]) -> ContainerRequest {
+        ContainerRequest {
+            container: container.into(),
+            fallbacks: formats(fallbacks),
+            output_path: None,
+        }
+    }
+
+    #[test]
+    fn keeps_requested_container_when_muxer_exists() {
+        let plan = resolve_container(&request("mkv", &[]), &formats(&["matroska"])).unwrap();
+        assert_eq!(plan.container, "mkv");
+        assert_eq!(plan.muxer, "matroska");
+        assert!(!plan.fell_back);
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[test]
+    fn falls_back_with_warning_when_muxer_is_missing() {
+        let mut request = request("mp4", &[]);
+        request.output_path = Some("/out/clip.mp4".into());
+        let plan = resolve_container(&request, &formats(&["matroska", "webm"])).unwrap();
+        assert_eq!(plan.container, "mkv");
+        assert!(plan.fell_back);
+        assert_eq!(plan.warnings.len(), 1);
+        assert_eq!(plan.output_path.as_deref(), Some("/out/clip.mkv"));
+    }
+
+    #[test]
+    fn preset_fallbacks_override_defaults() {
+        let plan = resolve_container(
+            &request("webm", &["mov", "mkv"]),
+            &formats(&["mov", "matroska"]),
+        )
+        .unwrap();
+        assert_eq!(plan.container, "mov");
+    }
+
+    #[test]
+    fn demuxer_aliases_are_not_muxers() {
+        // `mov,mp4,m4a,3gp,3g2,mj2` is the QuickTime demuxer's name list;
+        // only the mux column says whether `mp4` can be written.
+        let err = resolve_container(
+            &request("mp4", &["mov"]),
+            &formats(&["mov,mp4,m4a,3gp,3g2,mj2"]),
+        )
+        .unwrap_err();
+        assert_eq!(err.code, "container_unavailable");
+        let plan = resolve_container(&request("m4a", &[]), &formats(&["ipod"])).unwrap();
+        assert!(!plan.fell_back);
+    }
+
+    #[test]
+    fn errors_when_no_container_in_chain_is_available() {
+        let err = resolve_container(&request("gif", &[]), &formats(&["matroska"])).unwrap_err();
+        assert_eq!(err.code, "container_unavailable");
+    }
+
+    #[test]
+    fn unknown_capabilities_trust_the_request() {
+        let plan = resolve_container(&request("MP4", &[]), &[]).unwrap();
+        assert_eq!(plan.container, "mp4");
+    }
+}
@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+
+/// Fraction of the output height kept clear below burned-in subtitles when no
+/// explicit margin is requested.
+const DEFAULT_SAFE_MARGIN_RATIO: f64 = 0.05;
+
+/// Crop rectangle expressed in source pixels, mirroring ffmpeg's `crop` filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CropRect {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub x: u32,
+    #[serde(default)]
+    pub y: u32,
+}
+
+/// Subtitle burn-in request rendered through libass.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleBurnIn {
+    /// File containing the subtitle stream (often the source media itself).
+    pub path: String,
+    /// Subtitle stream index within `path`, when it holds several tracks.
+    pub stream_index: Option<u32>,
+    /// Vertical margin in pixels of the uncropped source frame.
+    pub margin_v: Option<u32>,
+    /// Directory libass should search for fonts before the system ones.
+    pub fonts_dir: Option<String>,
+}
+
+/// Inputs for assembling the video filter chain of a job.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterGraphRequest {
+    pub source_width: Option<u32>,
+    pub source_height: Option<u32>,
+    pub crop: Option<CropRect>,
+    pub subtitles: Option<SubtitleBurnIn>,
+    /// Additional filters appended after cropping but before subtitles.
+    #[serde(default)]
+    pub extra_filters: Vec<String>,
+}
+
+/// Ordered filter chain ready to be passed to ffmpeg via `-vf`.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterGraphPlan {
+    pub filters: Vec<String>,
+    pub args: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Builds the `-vf` chain, always cropping before subtitles are rendered so
+/// text never lands in bars that are about to be removed.
+pub fn build_filtergraph(request: &FilterGraphRequest) -> FilterGraphPlan {
+    let mut filters = Vec::new();
+    let mut warnings = Vec::new();
+
+    let source_size = request.source_width.zip(request.source_height);
+    let crop = request
+        .crop
+        .and_then(|rect| validate_crop(rect, source_size, &mut warnings));
+
+    if let Some(rect) = crop {
+        filters.push(format!(
+            "crop={}:{}:{}:{}",
+            rect.width, rect.height, rect.x, rect.y
+        ));
+    }
+
+    filters.extend(
+        request
+            .extra_filters
+            .iter()
+            .map(|filter| filter.trim())
+            .filter(|filter| !filter.is_empty())
+            .map(str::to_string),
+    );
+
+    if let Some(subtitles) = &request.subtitles {
+        if subtitles.path.trim().is_empty() {
+            warnings.push("Subtitle burn-in requested without a subtitle source.".into());
+        } else {
+            filters.push(subtitle_filter(subtitles, crop, source_size));
+        }
+    }
+
+    let args = if filters.is_empty() {
+        Vec::new()
+    } else {
+        vec!["-vf".to_string(), filters.join(",")]
+    };
+
+    FilterGraphPlan {
+        filters,
+        args,
+        warnings,
+    }
+}
+
+fn validate_crop(
+    rect: CropRect,
+    source_size: Option<(u32, u32)>,
+    warnings: &mut Vec<String>,
+) -> Option<CropRect> {
+    if rect.width == 0 || rect.height == 0 {
+        warnings.push("Ignoring crop with an empty width or height.".into());
+        return None;
+    }
+
+    if let Some((width, height)) = source_size {
+        let fits_horizontally = rect.x.saturating_add(rect.width) <= width;
+        let fits_vertically = rect.y.saturating_add(rect.height) <= height;
+        if !fits_horizontally || !fits_vertically {
+            warnings.push(format!(
+                "Ignoring crop {}x{}+{}+{} that exceeds the {width}x{height} source frame.",
+                rect.width, rect.height, rect.x, rect.y
+            ));
+            return None;
+        }
+    }
+
+    Some(rect)
+}
+
+/// Renders the libass `subtitles` filter. When the frame was cropped, the
+/// margin is rescaled to the cropped height and `original_size` pins libass
+/// to the source geometry so styling keeps its authored proportions.
+fn subtitle_filter(
+    subtitles: &SubtitleBurnIn,
+    crop: Option<CropRect>,
+    source_size: Option<(u32, u32)>,
+) -> String {
+    let mut filter = format!(
+        "subtitles=filename={}",
+        escape_filter_value(&subtitles.path)
+    );
+
+    if let Some(index) = subtitles.stream_index {
+        filter.push_str(&format!(":si={index}"));
+    }
+
+    if let Some(dir) = subtitles.fonts_dir.as_deref() {
+        filter.push_str(&format!(":fontsdir={}", escape_filter_value(dir)));
+    }
+
+    let Some(rect) = crop else {
+        if let Some(margin) = subtitles.margin_v {
+            filter.push_str(&format!(":force_style='MarginV={margin}'"));
+        }
+        return filter;
+    };
+
+    if let Some((width, height)) = source_size {
+        filter.push_str(&format!(":original_size={width}x{height}"));
+    }
+
+    filter.push_str(&format!(
+        ":force_style='MarginV={}'",
+        compensated_margin(subtitles.margin_v, rect, source_size)
+    ));
+    filter
+}
+
+fn compensated_margin(
+    requested: Option<u32>,
+    crop: CropRect,
+    source_size: Option<(u32, u32)>,
+) -> u32 {
+    let safe_margin = (f64::from(crop.height) * DEFAULT_SAFE_MARGIN_RATIO).round() as u32;
+    let Some(margin) = requested else {
+        return safe_margin;
+    };
+
+    let scaled = match source_size {
+        Some((_, height)) if height > 0 => {
+            (f64::from(margin) * f64::from(crop.height) / f64::from(height)).round() as u32
+        },
+        _ => margin,
+    };
+
+    scaled.max(safe_margin)
+}
+
+/// Escapes a value for use inside an ffmpeg filter option in a filtergraph.
+/// ffmpeg unescapes twice, first when splitting the graph into filters and
+/// then when splitting a filter's arguments into options, so the value is
+/// escaped for the option level and the result again for the graph level.
+pub(crate) fn escape_filter_value(value: &str) -> String {
+    escape(
+        &escape(value, &['\\', '\'', ':', '=']),
+        &['\\', '\'', '[', ']', ',', ';'],
+    )
+}
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if special.contains(&ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letterboxed_request() -> FilterGraphRequest {
+        FilterGraphRequest {
+            source_width: Some(1920),
+            source_height: Some(1080),
+            crop: Some(CropRect {
+                width: 1920,
+                height: 800,
+                x: 0,
+                y: 140,
+            }),
+            subtitles: Some(SubtitleBurnIn {
+                path: "/media/movie.mkv".into(),
+                stream_index: Some(1),
+                margin_v: Some(81),
+                fonts_dir: None,
+            }),
+            extra_filters: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn subtitles_render_after_crop() {
+        let plan = build_filtergraph(&letterboxed_request());
+
+        assert_eq!(plan.filters.len(), 2);
+        assert!(plan.filters[0].starts_with("crop=1920:800:0:140"));
+        assert!(plan.filters[1].starts_with("subtitles="));
+        assert_eq!(plan.args[0], "-vf");
+        assert_eq!(plan.args[1], plan.filters.join(","));
+    }
+
+    #[test]
+    fn cropped_subtitles_keep_original_size_and_safe_margin() {
+        let plan = build_filtergraph(&letterboxed_request());
+        let subtitles = &plan.filters[1];
+
+        assert!(subtitles.contains(":si=1"));
+        assert!(subtitles.contains(":original_size=1920x1080"));
+        // 81px on the 1080px source keeps its proportion on the 800px crop.
+        assert!(subtitles.contains("MarginV=60"));
+    }
+
+    #[test]
+    fn small_margins_are_raised_to_the_safe_area() {
+        let mut request = letterboxed_request();
+        if let Some(subtitles) = request.subtitles.as_mut() {
+            subtitles.margin_v = Some(10);
+        }
+
+        let plan = build_filtergraph(&request);
+        assert!(plan.filters[1].contains("MarginV=40"));
+    }
+
+    #[test]
+    fn extra_filters_sit_between_crop_and_subtitles() {
+        let mut request = letterboxed_request();
+        request.extra_filters = vec!["scale=1280:-2".into(), " ".into()];
+
+        let plan = build_filtergraph(&request);
+        assert_eq!(plan.filters.len(), 3);
+        assert_eq!(plan.filters[1], "scale=1280:-2");
+    }
+
+    #[test]
+    fn out_of_bounds_crop_is_dropped_with_warning() {
+        let mut request = letterboxed_request();
+        request.crop = Some(CropRect {
+            width: 1920,
+            height: 1000,
+            x: 0,
+            y: 140,
+        });
+
+        let plan = build_filtergraph(&request);
+        assert!(plan
+            .filters
+            .iter()
+            .all(|filter| !filter.starts_with("crop")));
+        assert_eq!(plan.warnings.len(), 1);
+        assert!(!plan.filters[0].contains("original_size"));
+    }
+
+    #[test]
+    fn empty_request_produces_no_args() {
+        let plan = build_filtergraph(&FilterGraphRequest::default());
+        assert!(plan.filters.is_empty());
+        assert!(plan.args.is_empty());
+    }
+
+    #[test]
+    fn fonts_dir_is_passed_to_libass() {
+        let mut request = letterboxed_request();
+        if let Some(subtitles) = request.subtitles.as_mut() {
+            subtitles.fonts_dir = Some("/Library/Fonts".into());
+        }
+
+        let plan = build_filtergraph(&request);
+        assert!(plan.filters[1].contains(":fontsdir=/Library/Fonts"));
+    }
+
+    #[test]
+    fn escapes_special_characters_in_paths() {
+        assert_eq!(
+            escape_filter_value("C:\\subs\\it's,[1].srt"),
+            r"C\\:\\\\subs\\\\it\\\'s\,\[1\].srt"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Backend planning helpers that complement the TypeScript planner.
+//!
+//! The frontend decides copy vs. transcode; these modules cover the pieces
+//! that depend on execution details (filter ordering, escaping) so the
+//! resulting ffmpeg arguments stay correct regardless of how users stack
+//! options.
+
+mod album;
+mod arg_diff;
+mod container;
+mod filtergraph;
+mod size_estimate;
+mod stream_selection;
+mod trim;
+
+pub use album::{finish_album, plan_album, AlbumPlan, AlbumRequest, AlbumTrack};
+pub use arg_diff::{diff_plans, PlanDiff};
+pub use container::{muxer_for_container, resolve_container, ContainerPlan, ContainerRequest};
+pub use filtergraph::{build_filtergraph, FilterGraphPlan, FilterGraphRequest};
+pub use size_estimate::{
+    benefits_from_sample, estimate_size, from_sample, sample_args, SizeEstimate,
+    SizeEstimateRequest, SourceMedia,
+};
+pub use stream_selection::{
+    preview_stream_selection, StreamSelectionPreview, StreamSelectionRules,
+};
+pub use trim::{plan_trim, TrimPlan, TrimRequest};
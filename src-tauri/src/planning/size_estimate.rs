@@ -0,0 +1,423 @@
+//! Output size prediction.
+//!
+//! Streams with a target bitrate are sized from the plan and copied streams
+//! from the source's own bitrate. Video encoded by quality (CRF) has no
+//! bitrate to go by, so it is sized from a bits-per-pixel figure per codec,
+//! halved for every 6 CRF steps above the codec's reference. A short sample
+//! encode can replace the heuristics with a measurement scaled to the full
+//! duration.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    api::{PlanRequest, PresetAudio, PresetVideo},
+    error::AppError,
+};
+
+/// Length of the sample encode, in seconds.
+pub const SAMPLE_SECONDS: f64 = 10.0;
+/// Container overhead added on top of the stream bitrates.
+const MUX_OVERHEAD: f64 = 1.02;
+/// Used for lossy audio without a planned bitrate.
+const DEFAULT_AUDIO_KBPS: f64 = 128.0;
+/// FLAC typically stores about this share of the PCM size.
+const FLAC_RATIO: f64 = 0.6;
+
+/// Bits per pixel at the codec's reference CRF: `(codec, crf, bpp)`.
+const VIDEO_BPP: &[(&str, f64, f64)] = &[
+    ("h264", 23.0, 0.10),
+    ("hevc", 28.0, 0.06),
+    ("av1", 30.0, 0.045),
+    ("vp9", 31.0, 0.06),
+    ("vp8", 10.0, 0.10),
+];
+/// ProRes ignores CRF; 422 runs at roughly this many bits per pixel.
+const PRORES_BPP: f64 = 2.4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeEstimateRequest {
+    /// Source file, probed for duration, picture size and bitrates.
+    pub path: String,
+    pub plan: PlanRequest,
+    /// The job's ffmpeg arguments, needed for `sample`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Encode a short sample when quality-based streams make the estimate
+    /// a guess.
+    #[serde(default)]
+    pub sample: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EstimateMethod {
+    /// Planned or source bitrates only.
+    Bitrate,
+    /// At least one stream sized by a quality heuristic.
+    Heuristic,
+    /// Scaled from a sample encode.
+    Sample,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeEstimate {
+    pub bytes: u64,
+    /// Plausible range around `bytes`, wider for heuristics.
+    pub low_bytes: u64,
+    pub high_bytes: u64,
+    pub method: EstimateMethod,
+    pub duration_sec: f64,
+}
+
+impl SizeEstimate {
+    fn new(bytes: f64, method: EstimateMethod, duration_sec: f64) -> Self {
+        let (low, high) = match method {
+            EstimateMethod::Bitrate => (0.85, 1.05),
+            EstimateMethod::Heuristic => (0.5, 1.6),
+            EstimateMethod::Sample => (0.85, 1.15),
+        };
+        Self {
+            bytes: bytes as u64,
+            low_bytes: (bytes * low) as u64,
+            high_bytes: (bytes * high) as u64,
+            method,
+            duration_sec,
+        }
+    }
+}
+
+/// What the estimate needs to know about the source.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMedia {
+    pub duration_sec: f64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    pub video_kbps: Option<f64>,
+    /// Sum over audio streams.
+    pub audio_kbps: Option<f64>,
+    pub total_kbps: Option<f64>,
+    pub channels: Option<u32>,
+    pub sample_rate: Option<u32>,
+}
+
+impl SourceMedia {
+    /// Reads a raw ffprobe document.
+    pub fn from_probe(raw: &Value) -> Self {
+        let streams = raw["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let video = streams.iter().find(|stream| {
+            stream["codec_type"] == "video" && stream["disposition"]["attached_pic"] != 1
+        });
+        let audio: Vec<&Value> = streams
+            .iter()
+            .filter(|stream| stream["codec_type"] == "audio")
+            .collect();
+        // Matroska streams often only carry mkvmerge's `BPS` statistics tag.
+        let kbps = |value: &Value| {
+            number(&value["bit_rate"])
+                .or_else(|| number(&value["tags"]["BPS"]))
+                .map(|bits| bits / 1000.0)
+        };
+        let audio_kbps: Option<f64> = audio.iter().map(|stream| kbps(stream)).sum();
+
+        Self {
+            duration_sec: number(&raw["format"]["duration"]).unwrap_or_default(),
+            width: video
+                .and_then(|stream| stream["width"].as_u64())
+                .map(|w| w as u32),
+            height: video
+                .and_then(|stream| stream["height"].as_u64())
+                .map(|h| h as u32),
+            fps: video.and_then(|stream| frame_rate(&stream["avg_frame_rate"])),
+            video_kbps: video.and_then(kbps),
+            audio_kbps,
+            total_kbps: kbps(&raw["format"]),
+            channels: audio
+                .first()
+                .and_then(|stream| stream["channels"].as_u64())
+                .map(|channels| channels as u32),
+            sample_rate: audio
+                .first()
+                .and_then(|stream| number(&stream["sample_rate"]))
+                .map(|rate| rate as u32),
+        }
+    }
+
+    /// Video bitrate, falling back to what the audio leaves of the total.
+    fn video_or_remainder_kbps(&self) -> Option<f64> {
+        self.video_kbps.or_else(|| {
+            self.total_kbps
+                .map(|total| (total - self.audio_kbps.unwrap_or_default()).max(0.0))
+        })
+    }
+}
+
+/// Predicts the output size of `plan` applied to `source`.
+pub fn estimate_size(plan: &PlanRequest, source: &SourceMedia) -> Result<SizeEstimate, AppError> {
+    if source.duration_sec.is_nan() || source.duration_sec <= 0.0 {
+        return Err(AppError::new(
+            "estimate_unknown_duration",
+            "The source duration is unknown, so its output size can't be estimated.",
+        ));
+    }
+    let tier = plan.tier.as_deref();
+    let (video_kbps, heuristic) = if plan.preset.media_kind == "audio" {
+        (0.0, false)
+    } else {
+        video_kbps(&plan.preset.video, tier, source)?
+    };
+    let total_kbps = video_kbps + audio_kbps(&plan.preset.audio, tier, source);
+    let bytes = total_kbps * 1000.0 / 8.0 * source.duration_sec * MUX_OVERHEAD;
+    let method = if heuristic {
+        EstimateMethod::Heuristic
+    } else {
+        EstimateMethod::Bitrate
+    };
+    Ok(SizeEstimate::new(bytes, method, source.duration_sec))
+}
+
+/// Whether sizing `plan` involves a quality heuristic a sample would
+/// improve on.
+pub fn benefits_from_sample(plan: &PlanRequest, source: &SourceMedia) -> bool {
+    estimate_size(plan, source).is_ok_and(|estimate| estimate.method == EstimateMethod::Heuristic)
+        && source.duration_sec > SAMPLE_SECONDS * 3.0
+}
+
+/// Arguments encoding `SAMPLE_SECONDS` from the middle of the source:
+/// an input seek before the first `-i` and an output duration at the end.
+pub fn sample_args(args: &[String], source: &SourceMedia) -> Vec<String> {
+    let start = ((source.duration_sec - SAMPLE_SECONDS) / 2.0).max(0.0);
+    let mut result = Vec::with_capacity(args.len() + 4);
+    let mut seeked = false;
+    for arg in args {
+        if !seeked && arg == "-i" {
+            result.extend(["-ss".to_string(), format!("{start:.3}")]);
+            seeked = true;
+        }
+        result.push(arg.clone());
+    }
+    result.extend(["-t".to_string(), format!("{SAMPLE_SECONDS}")]);
+    result
+}
+
+/// Scales the size of a sample encode up to the full source.
+pub fn from_sample(sample_bytes: u64, source: &SourceMedia) -> SizeEstimate {
+    let seconds = SAMPLE_SECONDS.min(source.duration_sec);
+    let bytes = sample_bytes as f64 * source.duration_sec / seconds;
+    SizeEstimate::new(bytes, EstimateMethod::Sample, source.duration_sec)
+}
+
+/// Video bitrate and whether it came from the quality heuristic.
+fn video_kbps(
+    video: &PresetVideo,
+    tier: Option<&str>,
+    source: &SourceMedia,
+) -> Result<(f64, bool), AppError> {
+    match video.codec.as_str() {
+        "none" => return Ok((0.0, false)),
+        "copy" => {
+            return source
+                .video_or_remainder_kbps()
+                .map(|kbps| (kbps, false))
+                .ok_or_else(|| unknown("the source video bitrate is unknown"));
+        },
+        _ => {},
+    }
+    let tier = tier.and_then(|tier| video.tiers.as_ref()?.get(tier));
+    if let Some(kbps) = tier.and_then(|tier| tier.bitrate_k.or(tier.maxrate_k)) {
+        return Ok((f64::from(kbps), false));
+    }
+
+    let (Some(width), Some(height)) = (source.width, source.height) else {
+        return Err(unknown("the source picture size is unknown"));
+    };
+    let fps = source.fps.unwrap_or(30.0);
+    let bpp = if video.codec == "prores" {
+        PRORES_BPP
+    } else {
+        let (_, reference, bpp) = VIDEO_BPP
+            .iter()
+            .find(|(codec, _, _)| *codec == video.codec)
+            .unwrap_or(&VIDEO_BPP[0]);
+        let crf = tier.and_then(|tier| tier.crf).unwrap_or(*reference);
+        bpp * 2f64.powf((reference - crf) / 6.0)
+    };
+    Ok((
+        f64::from(width) * f64::from(height) * fps * bpp / 1000.0,
+        true,
+    ))
+}
+
+fn audio_kbps(audio: &PresetAudio, tier: Option<&str>, source: &SourceMedia) -> f64 {
+    let pcm_kbps = || {
+        f64::from(source.sample_rate.unwrap_or(48_000))
+            * f64::from(source.channels.unwrap_or(2))
+            * 16.0
+            / 1000.0
+    };
+    match audio.codec.as_str() {
+        "none" => 0.0,
+        "copy" => source.audio_kbps.unwrap_or(DEFAULT_AUDIO_KBPS),
+        "flac" => pcm_kbps() * FLAC_RATIO,
+        codec if codec.starts_with("pcm_") => pcm_kbps(),
+        _ => tier
+            .and_then(|tier| audio.tiers.as_ref()?.get(tier)?.bitrate_k)
+            .or(audio.bitrate_k)
+            .map_or(DEFAULT_AUDIO_KBPS, f64::from),
+    }
+}
+
+fn unknown(reason: &str) -> AppError {
+    AppError::new(
+        "estimate_unavailable",
+        format!("Output size can't be estimated: {reason}."),
+    )
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parses ffprobe rates such as `30000/1001`.
+fn frame_rate(value: &Value) -> Option<f64> {
+    let (num, den) = value.as_str()?.split_once('/')?;
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    (den > 0.0 && num > 0.0).then(|| num / den)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Preset, VideoTier};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn plan(video: &str, audio: &str, tier: VideoTier) -> PlanRequest {
+        PlanRequest {
+            preset: Preset {
+                id: "preset".into(),
+                label: "Preset".into(),
+                container: "mp4".into(),
+                media_kind: "video".into(),
+                source_containers: Vec::new(),
+                description: None,
+                video: PresetVideo {
+                    codec: video.into(),
+                    tiers: Some(BTreeMap::from([("balanced".to_string(), tier)])),
+                    copy_color_metadata: None,
+                },
+                audio: PresetAudio {
+                    codec: audio.into(),
+                    bitrate_k: Some(160),
+                    tiers: None,
+                    stereo_only: None,
+                },
+                subs: None,
+                remux_only: None,
+                experimental: None,
+                output_extension: None,
+                tags: None,
+            },
+            tier: Some("balanced".into()),
+        }
+    }
+
+    fn tier(bitrate_k: Option<u32>, crf: Option<f64>) -> VideoTier {
+        VideoTier {
+            bitrate_k,
+            crf,
+            ..VideoTier::default()
+        }
+    }
+
+    fn source() -> SourceMedia {
+        SourceMedia::from_probe(&json!({
+            "format": { "duration": "100.0", "bit_rate": "5128000" },
+            "streams": [
+                { "codec_type": "video", "width": 1920, "height": 1080,
+                  "avg_frame_rate": "30/1", "bit_rate": "5000000" },
+                { "codec_type": "audio", "channels": 2, "sample_rate": "48000",
+                  "bit_rate": "128000" }
+            ]
+        }))
+    }
+
+    #[test]
+    fn probe_documents_are_read() {
+        let source = source();
+        assert_eq!(source.duration_sec, 100.0);
+        assert_eq!((source.width, source.height), (Some(1920), Some(1080)));
+        assert_eq!(source.fps, Some(30.0));
+        assert_eq!(source.video_kbps, Some(5000.0));
+        assert_eq!(source.audio_kbps, Some(128.0));
+        assert_eq!(source.channels, Some(2));
+
+        let matroska = SourceMedia::from_probe(&json!({
+            "format": { "duration": "100.0" },
+            "streams": [{ "codec_type": "video", "tags": { "BPS": "6000000" } }]
+        }));
+        assert_eq!(matroska.video_kbps, Some(6000.0));
+    }
+
+    #[test]
+    fn bitrate_plans_are_sized_from_their_targets() {
+        let estimate =
+            estimate_size(&plan("h264", "aac", tier(Some(4000), None)), &source()).unwrap();
+        assert_eq!(estimate.method, EstimateMethod::Bitrate);
+        // (4000 + 160) kbit/s for 100 s, plus overhead.
+        assert_eq!(
+            estimate.bytes,
+            (4160.0 * 125.0 * 100.0 * MUX_OVERHEAD) as u64
+        );
+        assert!(estimate.low_bytes < estimate.bytes && estimate.bytes < estimate.high_bytes);
+
+        let copied = estimate_size(&plan("copy", "copy", tier(None, None)), &source()).unwrap();
+        assert_eq!(copied.bytes, (5128.0 * 125.0 * 100.0 * MUX_OVERHEAD) as u64);
+    }
+
+    #[test]
+    fn quality_plans_shrink_as_crf_grows() {
+        let at = |crf| {
+            estimate_size(&plan("h264", "none", tier(None, Some(crf))), &source())
+                .unwrap()
+                .bytes as f64
+        };
+        let reference =
+            estimate_size(&plan("h264", "none", tier(None, Some(23.0))), &source()).unwrap();
+        assert_eq!(reference.method, EstimateMethod::Heuristic);
+        assert!((at(29.0) / at(23.0) - 0.5).abs() < 1e-6);
+        assert!(at(18.0) > at(23.0));
+    }
+
+    #[test]
+    fn samples_cover_the_middle_and_scale_up() {
+        let args: Vec<String> = ["-y", "-i", "in.mov", "-c:v", "libx264"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(
+            sample_args(&args, &source()),
+            vec!["-y", "-ss", "45.000", "-i", "in.mov", "-c:v", "libx264", "-t", "10"]
+        );
+        let scaled = from_sample(1_000_000, &source());
+        assert_eq!(scaled.bytes, 10_000_000);
+        assert_eq!(scaled.method, EstimateMethod::Sample);
+    }
+
+    #[test]
+    fn unknown_durations_are_rejected() {
+        let err = estimate_size(
+            &plan("h264", "aac", tier(Some(4000), None)),
+            &SourceMedia::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.code, "estimate_unknown_duration");
+    }
+}
@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Containers that keep cover art when the planner drops video.
+const COVER_ART_CONTAINERS: &[&str] = &["mp3", "m4a", "flac"];
+/// Subtitle codecs unmapped when converting to text subtitles.
+const IMAGE_SUBTITLE_CODECS: &[&str] = &[
+    "hdmv_pgs_subtitle",
+    "pgssub",
+    "dvd_subtitle",
+    "dvb_subtitle",
+    "xsub",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubtitleMode {
+    Drop,
+    #[default]
+    Copy,
+    Convert,
+}
+
+/// The planner's decisions for a job, as produced by the frontend video,
+/// audio and subtitle planners. The preview applies the same mapping the
+/// args builder (`src/lib/builders/ffmpeg-args-builder.ts`) emits for them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamSelectionRules {
+    /// Output container; audio containers keep cover art when video is dropped.
+    pub container: String,
+    pub drop_video: bool,
+    pub drop_audio: bool,
+    pub subtitles: SubtitleMode,
+    /// Unmap image subtitles when converting to text subtitles.
+    pub exclude_image_subtitles: bool,
+    /// Map attached fonts alongside kept subtitles.
+    pub keep_attachments: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+    Other,
+}
+
+/// Whether a stream is mapped, with the rule trace that decided it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamDecision {
+    pub index: u32,
+    pub kind: StreamKind,
+    pub codec: Option<String>,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub selected: bool,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamSelectionPreview {
+    pub streams: Vec<StreamDecision>,
+    /// `-map` arguments in output order.
+    pub map_args: Vec<String>,
+}
+
+struct StreamInfo {
+    decision: StreamDecision,
+    is_cover_art: bool,
+    is_attachment: bool,
+}
+
+/// Applies the planner's mapping to the streams of a raw ffprobe document.
+pub fn preview_stream_selection(
+    raw: &Value,
+    rules: &StreamSelectionRules,
+) -> StreamSelectionPreview {
+    let mut streams: Vec<StreamInfo> = raw["streams"]
+        .as_array()
+        .map(|streams| streams.iter().map(stream_info).collect())
+        .unwrap_or_default();
+    let mut map_args = Vec::new();
+
+    select_video(&mut streams, rules, &mut map_args);
+    select_audio(&mut streams, rules, &mut map_args);
+    select_subtitles(&mut streams, rules, &mut map_args);
+
+    for stream in streams
+        .iter_mut()
+        .filter(|stream| stream.decision.reasons.is_empty())
+    {
+        stream
+            .decision
+            .reasons
+            .push("Data streams are not mapped.".into());
+    }
+
+    StreamSelectionPreview {
+        streams: streams.into_iter().map(|stream| stream.decision).collect(),
+        map_args,
+    }
+}
+
+fn push_map(map_args: &mut Vec<String>, specifier: impl Into<String>) {
+    map_args.push("-map".into());
+    map_args.push(specifier.into());
+}
+
+fn select_video(
+    streams: &mut [StreamInfo],
+    rules: &StreamSelectionRules,
+    map_args: &mut Vec<String>,
+) {
+    if rules.drop_video {
+        let keeps_cover_art = COVER_ART_CONTAINERS.contains(&rules.container.as_str());
+        let mut cover_art_kept = false;
+        for stream in streams
+            .iter_mut()
+            .filter(|stream| stream.decision.kind == StreamKind::Video)
+        {
+            let reason = if stream.is_cover_art && keeps_cover_art && !cover_art_kept {
+                cover_art_kept = true;
+                stream.decision.selected = true;
+                push_map(map_args, format!("0:{}", stream.decision.index));
+                "Cover art kept for an audio container."
+            } else {
+                "Video is dropped by the plan."
+            };
+            stream.decision.reasons.push(reason.into());
+        }
+        return;
+    }
+
+    // `0:V:0?` skips attached pictures.
+    push_map(map_args, "0:V:0?");
+    let mut chosen = false;
+    for stream in streams
+        .iter_mut()
+        .filter(|stream| stream.decision.kind == StreamKind::Video)
+    {
+        let reason = if stream.is_cover_art {
+            "Cover art is not treated as a video track."
+        } else if chosen {
+            "Only the first video stream is mapped."
+        } else {
+            chosen = true;
+            stream.decision.selected = true;
+            "First video stream."
+        };
+        stream.decision.reasons.push(reason.into());
+    }
+}
+
+fn select_audio(
+    streams: &mut [StreamInfo],
+    rules: &StreamSelectionRules,
+    map_args: &mut Vec<String>,
+) {
+    if !rules.drop_audio {
+        push_map(map_args, "0:a:0?");
+    }
+    let mut chosen = false;
+    for stream in streams
+        .iter_mut()
+        .filter(|stream| stream.decision.kind == StreamKind::Audio)
+    {
+        let reason = if rules.drop_audio {
+            "Audio is dropped by the plan."
+        } else if chosen {
+            "Only the first audio stream is mapped."
+        } else {
+            chosen = true;
+            stream.decision.selected = true;
+            "First audio stream."
+        };
+        stream.decision.reasons.push(reason.into());
+    }
+}
+
+fn select_subtitles(
+    streams: &mut [StreamInfo],
+    rules: &StreamSelectionRules,
+    map_args: &mut Vec<String>,
+) {
+    let keep = rules.subtitles != SubtitleMode::Drop;
+    let exclude_images = rules.subtitles == SubtitleMode::Convert && rules.exclude_image_subtitles;
+    let keep_attachments = keep && rules.keep_attachments;
+
+    if keep {
+        push_map(map_args, "0:s?");
+        if exclude_images {
+            for codec in IMAGE_SUBTITLE_CODECS {
+                push_map(map_args, format!("-0:s:m:codec:{codec}?"));
+            }
+        }
+        if keep_attachments {
+            push_map(map_args, "0:t?");
+        }
+    }
+
+    for stream in streams.iter_mut() {
+        let reason = match stream.decision.kind {
+            StreamKind::Subtitle if !keep => "Subtitles are dropped by the plan.",
+            StreamKind::Subtitle
+                if exclude_images
+                    && stream
+                        .decision
+                        .codec
+                        .as_deref()
+                        .is_some_and(|codec| IMAGE_SUBTITLE_CODECS.contains(&codec)) =>
+            {
+                "Image subtitles cannot be converted to text."
+            },
+            StreamKind::Subtitle => {
+                stream.decision.selected = true;
+                "All subtitle streams are mapped."
+            },
+            StreamKind::Other if stream.is_attachment && keep_attachments => {
+                stream.decision.selected = true;
+                "Attachments are kept with the subtitles."
+            },
+            StreamKind::Other if stream.is_attachment => "Attachments are not mapped.",
+            _ => continue,
+        };
+        stream.decision.reasons.push(reason.into());
+    }
+}
+
+fn stream_info(stream: &Value) -> StreamInfo {
+    let codec_type = stream["codec_type"].as_str();
+    let kind = match codec_type {
+        Some("video") => StreamKind::Video,
+        Some("audio") => StreamKind::Audio,
+        Some("subtitle") => StreamKind::Subtitle,
+        _ => StreamKind::Other,
+    };
+
+    StreamInfo {
+        is_cover_art: stream["disposition"]["attached_pic"].as_i64() == Some(1),
+        is_attachment: codec_type == Some("attachment"),
+        decision: StreamDecision {
+            index: stream["index"].as_u64().unwrap_or_default() as u32,
+            kind,
+            codec: stream["codec_name"].as_str().map(str::to_string),
+            language: stream["tags"]["language"]
+                .as_str()
+                .map(|language| language.to_lowercase()),
+            title: stream["tags"]["title"].as_str().map(str::to_string),
+            selected: false,
+            reasons: Vec::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn movie() -> Value {
+        json!({ "streams": [
+            { "index": 0, "codec_type": "video", "codec_name": "h264" },
+            { "index": 1, "codec_type": "audio", "codec_name": "ac3", "tags": { "language": "eng" } },
+            { "index": 2, "codec_type": "audio", "codec_name": "aac", "tags": { "language": "ger" } },
+            { "index": 3, "codec_type": "subtitle", "codec_name": "subrip", "tags": { "language": "ger" } },
+            { "index": 4, "codec_type": "subtitle", "codec_name": "hdmv_pgs_subtitle", "tags": { "language": "eng" } },
+            { "index": 5, "codec_type": "attachment", "codec_name": "ttf" },
+            { "index": 6, "codec_type": "video", "codec_name": "mjpeg", "disposition": { "attached_pic": 1 } }
+        ]})
+    }
+
+    fn decision(preview: &StreamSelectionPreview, index: u32) -> &StreamDecision {
+        preview
+            .streams
+            .iter()
+            .find(|stream| stream.index == index)
+            .unwrap()
+    }
+
+    fn selected(preview: &StreamSelectionPreview) -> Vec<u32> {
+        preview
+            .streams
+            .iter()
+            .filter(|stream| stream.selected)
+            .map(|stream| stream.index)
+            .collect()
+    }
+
+    #[test]
+    fn default_plan_maps_like_the_args_builder() {
+        let preview = preview_stream_selection(&movie(), &StreamSelectionRules::default());
+
+        assert_eq!(
+            preview.map_args,
+            vec!["-map", "0:V:0?", "-map", "0:a:0?", "-map", "0:s?"]
+        );
+        assert_eq!(selected(&preview), vec![0, 1, 3, 4]);
+        assert_eq!(
+            decision(&preview, 2).reasons,
+            vec!["Only the first audio stream is mapped."]
+        );
+        assert_eq!(
+            decision(&preview, 6).reasons,
+            vec!["Cover art is not treated as a video track."]
+        );
+        assert_eq!(
+            decision(&preview, 5).reasons,
+            vec!["Attachments are not mapped."]
+        );
+    }
+
+    #[test]
+    fn converted_subtitles_drop_image_streams_and_keep_fonts() {
+        let rules = StreamSelectionRules {
+            subtitles: SubtitleMode::Convert,
+            exclude_image_subtitles: true,
+            keep_attachments: true,
+            ..StreamSelectionRules::default()
+        };
+        let preview = preview_stream_selection(&movie(), &rules);
+
+        assert!(preview
+            .map_args
+            .contains(&"-0:s:m:codec:hdmv_pgs_subtitle?".to_string()));
+        assert!(preview
+            .map_args
+            .ends_with(&["-map".to_string(), "0:t?".to_string()]));
+        assert!(decision(&preview, 3).selected);
+        assert!(!decision(&preview, 4).selected);
+        assert!(decision(&preview, 5).selected);
+    }
+
+    #[test]
+    fn dropped_video_keeps_cover_art_only_for_audio_containers() {
+        let audio_only = StreamSelectionRules {
+            container: "m4a".into(),
+            drop_video: true,
+            subtitles: SubtitleMode::Drop,
+            ..StreamSelectionRules::default()
+        };
+        let preview = preview_stream_selection(&movie(), &audio_only);
+        assert_eq!(preview.map_args, vec!["-map", "0:6", "-map", "0:a:0?"]);
+        assert_eq!(selected(&preview), vec![1, 6]);
+
+        let no_cover = StreamSelectionRules {
+            container: "mkv".into(),
+            drop_video: true,
+            drop_audio: true,
+            subtitles: SubtitleMode::Drop,
+            ..StreamSelectionRules::default()
+        };
+        let preview = preview_stream_selection(&movie(), &no_cover);
+        assert!(preview.map_args.is_empty());
+        assert!(selected(&preview).is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// Where `-ss` is placed relative to `-i`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SeekMode {
+    /// Input seeking (`-ss` before `-i`): jumps straight to the nearest
+    /// keyframe, fast but frame-accurate only when re-encoding.
+    #[default]
+    Fast,
+    /// Output seeking (`-ss` after `-i`): decodes from the start and discards
+    /// frames until the exact position.
+    Accurate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimRequest {
+    pub start_sec: Option<f64>,
+    pub end_sec: Option<f64>,
+    #[serde(default)]
+    pub seek_mode: SeekMode,
+    /// Container start time reported by ffprobe; negative for some
+    /// transport streams and edited MP4s.
+    pub source_start_sec: Option<f64>,
+    /// Whether the job stream-copies instead of re-encoding.
+    #[serde(default)]
+    pub stream_copy: bool,
+    /// Keyframe timestamps from a keyframe probe, ascending. Lets stream
+    /// copy trims report where the cut will really land.
+    #[serde(default)]
+    pub keyframes: Vec<f64>,
+}
+
+/// Trim arguments split around the `-i` input.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimPlan {
+    pub input_args: Vec<String>,
+    pub output_args: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Where a stream copy trim actually starts: the last keyframe at or
+    /// before the requested start. Only set when keyframes were given.
+    pub keyframe_start_sec: Option<f64>,
+}
+
+/// The last keyframe at or before `time`, which is where a stream copy
+/// cut at `time` starts.
+pub fn keyframe_at_or_before(keyframes: &[f64], time: f64) -> Option<f64> {
+    let index = keyframes.partition_point(|keyframe| *keyframe <= time);
+    index.checked_sub(1).map(|index| keyframes[index])
+}
+
+pub fn plan_trim(request: &TrimRequest) -> Result<TrimPlan, AppError> {
+    let start = request.start_sec.filter(|start| *start > 0.0);
+    let end = request.end_sec;
+
+    for value in [request.start_sec, request.end_sec].into_iter().flatten() {
+        if !value.is_finite() || value < 0.0 {
+            return Err(AppError::new(
+                "trim_invalid_range",
+                "Trim points must be non-negative numbers.",
+            ));
+        }
+    }
+
+    if let Some(end) = end {
+        if end <= start.unwrap_or(0.0) {
+            return Err(AppError::new(
+                "trim_invalid_range",
+                "Trim end must be after the trim start.",
+            ));
+        }
+    }
+
+    let mut plan = TrimPlan::default();
+
+    if let Some(start) = start {
+        let seek = vec!["-ss".to_string(), format_seconds(start)];
+        match request.seek_mode {
+            SeekMode::Fast => plan.input_args.extend(seek),
+            SeekMode::Accurate => plan.output_args.extend(seek),
+        }
+
+        if request.stream_copy {
+            match keyframe_at_or_before(&request.keyframes, start) {
+                Some(keyframe) => {
+                    plan.keyframe_start_sec = Some(keyframe);
+                    if start - keyframe > KEYFRAME_TOLERANCE_SEC {
+                        plan.warnings.push(format!(
+                            "Stream copy starts at the keyframe at {}s, {}s before the requested point; re-encode for an exact cut.",
+                            format_seconds(keyframe),
+                            format_seconds(start - keyframe)
+                        ));
+                    }
+                },
+                None => plan.warnings.push(
+                    "Stream copy cuts on keyframes; the output may start slightly before the requested point."
+                        .into(),
+                ),
+            }
+        }
+    }
+
+    // A duration is used instead of `-to` because input seeking resets
+    // timestamps, which would make `-to` behave like a duration anyway.
+    if let Some(end) = end {
+        plan.output_args
+            .extend(["-t".to_string(), format_seconds(end - start.unwrap_or(0.0))]);
+    }
+
+    let negative_source = request.source_start_sec.is_some_and(|value| value < 0.0);
+    if negative_source || (request.stream_copy && start.is_some()) {
+        plan.output_args
+            .extend(["-avoid_negative_ts".to_string(), "make_zero".to_string()]);
+    }
+
+    Ok(plan)
+}
+
+/// Offsets below a millisecond are timestamp rounding, not a missed cut.
+const KEYFRAME_TOLERANCE_SEC: f64 = 0.001;
+
+fn format_seconds(value: f64) -> String {
+    let formatted = format!("{value:.3}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(start: Option<f64>, end: Option<f64>, seek_mode: SeekMode) -> TrimRequest {
+        TrimRequest {
+            start_sec: start,
+            end_sec: end,
+            seek_mode,
+            ..TrimRequest::default()
+        }
+    }
+
+    #[test]
+    fn fast_seek_places_ss_before_input() {
+        let plan = plan_trim(&request(Some(12.5), Some(20.0), SeekMode::Fast)).unwrap();
+        assert_eq!(plan.input_args, vec!["-ss", "12.5"]);
+        assert_eq!(plan.output_args, vec!["-t", "7.5"]);
+    }
+
+    #[test]
+    fn accurate_seek_places_ss_after_input() {
+        let plan = plan_trim(&request(Some(1.0 / 3.0), None, SeekMode::Accurate)).unwrap();
+        assert!(plan.input_args.is_empty());
+        assert_eq!(plan.output_args, vec!["-ss", "0.333"]);
+    }
+
+    #[test]
+    fn negative_source_timestamps_are_normalised() {
+        let mut trim = request(None, Some(10.0), SeekMode::Fast);
+        trim.source_start_sec = Some(-0.042);
+
+        let plan = plan_trim(&trim).unwrap();
+        assert_eq!(
+            plan.output_args,
+            vec!["-t", "10", "-avoid_negative_ts", "make_zero"]
+        );
+    }
+
+    #[test]
+    fn stream_copy_trims_warn_and_normalise_timestamps() {
+        let mut trim = request(Some(5.0), None, SeekMode::Fast);
+        trim.stream_copy = true;
+
+        let plan = plan_trim(&trim).unwrap();
+        assert_eq!(plan.warnings.len(), 1);
+        assert!(plan.output_args.contains(&"make_zero".to_string()));
+    }
+
+    #[test]
+    fn stream_copy_trims_snap_to_the_previous_keyframe() {
+        let keyframes = vec![0.0, 2.002, 4.004, 6.006];
+        assert_eq!(keyframe_at_or_before(&keyframes, 5.0), Some(4.004));
+        assert_eq!(keyframe_at_or_before(&keyframes, 2.002), Some(2.002));
+        assert_eq!(keyframe_at_or_before(&keyframes[1..], 1.0), None);
+
+        let mut trim = request(Some(5.0), None, SeekMode::Fast);
+        trim.stream_copy = true;
+        trim.keyframes = keyframes;
+        let plan = plan_trim(&trim).unwrap();
+        assert_eq!(plan.keyframe_start_sec, Some(4.004));
+        assert!(plan.warnings[0].contains("4.004s, 0.996s before"));
+
+        trim.start_sec = Some(4.004);
+        let aligned = plan_trim(&trim).unwrap();
+        assert_eq!(aligned.keyframe_start_sec, Some(4.004));
+        assert!(aligned.warnings.is_empty());
+    }
+
+    #[test]
+    fn rejects_inverted_or_negative_ranges() {
+        let inverted = plan_trim(&request(Some(10.0), Some(5.0), SeekMode::Fast)).unwrap_err();
+        assert_eq!(inverted.code, "trim_invalid_range");
+        assert!(plan_trim(&request(Some(-1.0), None, SeekMode::Accurate)).is_err());
+    }
+
+    #[test]
+    fn untrimmed_requests_add_nothing() {
+        let plan = plan_trim(&TrimRequest::default()).unwrap();
+        assert_eq!(plan, TrimPlan::default());
+    }
+}
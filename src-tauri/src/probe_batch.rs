@@ -0,0 +1,270 @@
+//! Concurrent batch probing with cancellation and ordered delivery.
+//!
+//! Workers probe files in parallel, but results are handed to the caller
+//! strictly in input order so the UI can append rows as they arrive
+//! without reshuffling. Starting a new batch supersedes the previous one:
+//! dropping a second folder aborts the scan of the first.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+use crate::error::AppError;
+use crate::sync_utils::MutexExt;
+
+pub const DEFAULT_PROBE_CONCURRENCY: usize = 4;
+pub const MAX_PROBE_CONCURRENCY: usize = 16;
+
+/// One delivered result, emitted in input order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchProbeItem<T> {
+    pub batch_id: String,
+    pub index: usize,
+    pub path: String,
+    pub result: Option<T>,
+    pub error: Option<AppError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchProbeSummary {
+    pub batch_id: String,
+    pub total: usize,
+    /// Results delivered before the batch finished or was cancelled.
+    pub delivered: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+}
+
+/// Tracks running batches so they can be cancelled by id or superseded.
+#[derive(Debug, Default)]
+pub struct BatchRegistry {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl BatchRegistry {
+    /// Registers a batch, cancelling every batch that is still running.
+    pub fn begin(&self, batch_id: &str) -> Arc<AtomicBool> {
+        let mut active = self.active.lock_or_recover();
+        for flag in active.values() {
+            flag.store(true, Ordering::SeqCst);
+        }
+        active.clear();
+
+        let flag = Arc::new(AtomicBool::new(false));
+        active.insert(batch_id.to_string(), Arc::clone(&flag));
+        flag
+    }
+
+    pub fn cancel(&self, batch_id: &str) -> bool {
+        let active = self.active.lock_or_recover();
+        match active.get(batch_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Forgets the batch unless a newer batch already replaced it.
+    pub fn finish(&self, batch_id: &str, flag: &Arc<AtomicBool>) {
+        let mut active = self.active.lock_or_recover();
+        if active
+            .get(batch_id)
+            .is_some_and(|current| Arc::ptr_eq(current, flag))
+        {
+            active.remove(batch_id);
+        }
+    }
+}
+
+pub fn resolve_concurrency(requested: Option<usize>) -> usize {
+    requested
+        .unwrap_or(DEFAULT_PROBE_CONCURRENCY)
+        .clamp(1, MAX_PROBE_CONCURRENCY)
+}
+
+/// Probes `paths` with `concurrency` workers. `deliver` is called on the
+/// calling thread in input order; once `cancel` is set no further probes
+/// start and nothing more is delivered.
+pub fn run_batch<T, P, D>(
+    batch_id: &str,
+    paths: &[String],
+    concurrency: usize,
+    cancel: &AtomicBool,
+    probe: P,
+    mut deliver: D,
+) -> BatchProbeSummary
+where
+    T: Send,
+    P: Fn(&str) -> Result<T, AppError> + Sync,
+    D: FnMut(BatchProbeItem<T>),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel::<(usize, Result<T, AppError>)>();
+    let mut delivered = 0;
+    let mut failed = 0;
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, paths.len().max(1)) {
+            let sender = sender.clone();
+            let (next, probe) = (&next, &probe);
+            scope.spawn(move || loop {
+                if cancel.load(Ordering::SeqCst) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(path) = paths.get(index) else {
+                    break;
+                };
+                if sender.send((index, probe(path))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&delivered) {
+                if cancel.load(Ordering::SeqCst) {
+                    break;
+                }
+                let (result, error) = match result {
+                    Ok(value) => (Some(value), None),
+                    Err(err) => {
+                        failed += 1;
+                        (None, Some(err))
+                    },
+                };
+                deliver(BatchProbeItem {
+                    batch_id: batch_id.to_string(),
+                    index: delivered,
+                    path: paths[delivered].clone(),
+                    result,
+                    error,
+                });
+                delivered += 1;
+            }
+        }
+    });
+
+    BatchProbeSummary {
+        batch_id: batch_id.to_string(),
+        total: paths.len(),
+        delivered,
+        failed,
+        cancelled: cancel.load(Ordering::SeqCst) && delivered < paths.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn paths(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|index| format!("/media/{index}.mov"))
+            .collect()
+    }
+
+    #[test]
+    fn results_are_delivered_in_input_order() {
+        let paths = paths(12);
+        let cancel = AtomicBool::new(false);
+        let mut order = Vec::new();
+
+        let summary = run_batch(
+            "batch",
+            &paths,
+            4,
+            &cancel,
+            |path| {
+                // Earlier files take longer so completions arrive out of order.
+                let index: u64 = path[7..path.len() - 4].parse().unwrap();
+                std::thread::sleep(Duration::from_millis(12 - index));
+                Ok(index)
+            },
+            |item| order.push(item.result.unwrap()),
+        );
+
+        assert_eq!(order, (0..12).collect::<Vec<_>>());
+        assert_eq!(summary.delivered, 12);
+        assert!(!summary.cancelled);
+    }
+
+    #[test]
+    fn failures_are_delivered_with_errors() {
+        let paths = paths(3);
+        let cancel = AtomicBool::new(false);
+        let mut errors = Vec::new();
+
+        let summary = run_batch(
+            "batch",
+            &paths,
+            2,
+            &cancel,
+            |path| {
+                if path.ends_with("1.mov") {
+                    Err(AppError::new("probe_failed", "bad file"))
+                } else {
+                    Ok(())
+                }
+            },
+            |item| errors.push(item.error.map(|err| err.code)),
+        );
+
+        assert_eq!(errors, vec![None, Some("probe_failed"), None]);
+        assert_eq!(summary.failed, 1);
+    }
+
+    #[test]
+    fn cancellation_stops_delivery() {
+        let paths = paths(50);
+        let cancel = AtomicBool::new(false);
+        let mut delivered = 0;
+
+        let summary = run_batch(
+            "batch",
+            &paths,
+            2,
+            &cancel,
+            |_| Ok(()),
+            |_| {
+                delivered += 1;
+                if delivered == 3 {
+                    cancel.store(true, Ordering::SeqCst);
+                }
+            },
+        );
+
+        assert_eq!(summary.delivered, 3);
+        assert!(summary.cancelled);
+    }
+
+    #[test]
+    fn new_batches_supersede_running_ones() {
+        let registry = BatchRegistry::default();
+        let first = registry.begin("first");
+        let second = registry.begin("second");
+
+        assert!(first.load(Ordering::SeqCst));
+        assert!(!second.load(Ordering::SeqCst));
+        assert!(!registry.cancel("first"));
+
+        registry.finish("first", &first);
+        assert!(registry.cancel("second"));
+    }
+
+    #[test]
+    fn concurrency_is_clamped() {
+        assert_eq!(resolve_concurrency(None), DEFAULT_PROBE_CONCURRENCY);
+        assert_eq!(resolve_concurrency(Some(0)), 1);
+        assert_eq!(resolve_concurrency(Some(64)), MAX_PROBE_CONCURRENCY);
+    }
+}
@@ -0,0 +1,472 @@
+//! MediaInfo-style inspection reports built from ffprobe output.
+//!
+//! Each probed file becomes a set of titled sections (container, streams,
+//! chapters) that render to Markdown or HTML; the JSON format keeps the full
+//! raw probe data for tooling.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{error::AppError, ffmpeg_probe::ProbeResponse};
+
+/// Output format for exported probe reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// Rendered report returned to the frontend for saving or display.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeReport {
+    pub format: ReportFormat,
+    pub extension: String,
+    pub content: String,
+}
+
+/// Probe outcome for a single file; failures are reported inline so one bad
+/// file does not abort a batch report.
+pub struct ReportEntry {
+    pub path: String,
+    pub result: Result<ProbeResponse, AppError>,
+}
+
+struct Section {
+    title: String,
+    rows: Vec<(String, String)>,
+}
+
+pub fn render_report(
+    entries: &[ReportEntry],
+    format: ReportFormat,
+) -> Result<ProbeReport, AppError> {
+    let content = match format {
+        ReportFormat::Markdown => render_markdown(entries),
+        ReportFormat::Html => render_html(entries),
+        ReportFormat::Json => render_json(entries)?,
+    };
+
+    Ok(ProbeReport {
+        format,
+        extension: format.extension().to_string(),
+        content,
+    })
+}
+
+fn render_json(entries: &[ReportEntry]) -> Result<String, AppError> {
+    let files: Vec<Value> = entries
+        .iter()
+        .map(|entry| match &entry.result {
+            Ok(response) => json!({
+                "path": entry.path,
+                "summary": response.summary,
+                "raw": response.raw,
+            }),
+            Err(err) => json!({ "path": entry.path, "error": err }),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&json!({ "files": files }))
+        .map_err(|err| AppError::new("report_serialize", err.to_string()))
+}
+
+fn render_markdown(entries: &[ReportEntry]) -> String {
+    let mut out = String::from("# Media report\n");
+
+    for entry in entries {
+        out.push_str(&format!("\n## {}\n", entry.path));
+        let response = match &entry.result {
+            Ok(response) => response,
+            Err(err) => {
+                out.push_str(&format!(
+                    "\n> Probe failed ({}): {}\n",
+                    err.code, err.message
+                ));
+                continue;
+            },
+        };
+
+        for section in sections(&response.raw) {
+            out.push_str(&format!(
+                "\n### {}\n\n| Field | Value |\n| --- | --- |\n",
+                escape_markdown(&section.title)
+            ));
+            for (label, value) in &section.rows {
+                out.push_str(&format!(
+                    "| {} | {} |\n",
+                    escape_markdown(label),
+                    escape_markdown(value)
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+fn render_html(entries: &[ReportEntry]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Media report</title></head>\n<body>\n<h1>Media report</h1>\n",
+    );
+
+    for entry in entries {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&entry.path)));
+        let response = match &entry.result {
+            Ok(response) => response,
+            Err(err) => {
+                out.push_str(&format!(
+                    "<p class=\"error\">Probe failed ({}): {}</p>\n",
+                    escape_html(err.code),
+                    escape_html(&err.message)
+                ));
+                continue;
+            },
+        };
+
+        for section in sections(&response.raw) {
+            out.push_str(&format!(
+                "<h3>{}</h3>\n<table>\n",
+                escape_html(&section.title)
+            ));
+            for (label, value) in &section.rows {
+                out.push_str(&format!(
+                    "<tr><th>{}</th><td>{}</td></tr>\n",
+                    escape_html(label),
+                    escape_html(value)
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn sections(raw: &Value) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let format = &raw["format"];
+
+    let mut container = Vec::new();
+    push_row(
+        &mut container,
+        "Format",
+        text(&format["format_long_name"]).or_else(|| text(&format["format_name"])),
+    );
+    push_row(
+        &mut container,
+        "Duration",
+        number(&format["duration"]).map(format_duration),
+    );
+    push_row(
+        &mut container,
+        "Size",
+        number(&format["size"]).map(format_size),
+    );
+    push_row(
+        &mut container,
+        "Overall bit rate",
+        number(&format["bit_rate"]).map(format_bit_rate),
+    );
+    push_row(&mut container, "Title", text(&format["tags"]["title"]));
+    if !container.is_empty() {
+        sections.push(Section {
+            title: "Container".into(),
+            rows: container,
+        });
+    }
+
+    for stream in raw["streams"].as_array().into_iter().flatten() {
+        sections.push(stream_section(stream));
+    }
+
+    let chapters: Vec<(String, String)> = raw["chapters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, chapter)| {
+            let start = number(&chapter["start_time"])
+                .map(format_duration)
+                .unwrap_or_default();
+            let end = number(&chapter["end_time"])
+                .map(format_duration)
+                .unwrap_or_default();
+            let title =
+                text(&chapter["tags"]["title"]).unwrap_or_else(|| format!("Chapter {}", index + 1));
+            (title, format!("{start} – {end}"))
+        })
+        .collect();
+    if !chapters.is_empty() {
+        sections.push(Section {
+            title: "Chapters".into(),
+            rows: chapters,
+        });
+    }
+
+    sections
+}
+
+fn stream_section(stream: &Value) -> Section {
+    let kind = text(&stream["codec_type"]).unwrap_or_else(|| "unknown".into());
+    let index = stream["index"].as_u64().unwrap_or_default();
+    let mut rows = Vec::new();
+
+    push_row(
+        &mut rows,
+        "Codec",
+        text(&stream["codec_long_name"]).or_else(|| text(&stream["codec_name"])),
+    );
+    push_row(&mut rows, "Profile", text(&stream["profile"]));
+    push_row(
+        &mut rows,
+        "Bit rate",
+        number(&stream["bit_rate"]).map(format_bit_rate),
+    );
+
+    if let (Some(width), Some(height)) = (stream["width"].as_u64(), stream["height"].as_u64()) {
+        rows.push(("Resolution".into(), format!("{width}x{height}")));
+    }
+    push_row(
+        &mut rows,
+        "Frame rate",
+        text(&stream["avg_frame_rate"]).filter(|rate| rate != "0/0"),
+    );
+    push_row(&mut rows, "Pixel format", text(&stream["pix_fmt"]));
+    push_row(
+        &mut rows,
+        "Color primaries",
+        text(&stream["color_primaries"]),
+    );
+    push_row(&mut rows, "Color transfer", text(&stream["color_transfer"]));
+    push_row(&mut rows, "Color space", text(&stream["color_space"]));
+    push_row(
+        &mut rows,
+        "Sample rate",
+        number(&stream["sample_rate"]).map(|rate| format!("{rate} Hz")),
+    );
+    push_row(
+        &mut rows,
+        "Channels",
+        stream["channels"].as_u64().map(|count| count.to_string()),
+    );
+    push_row(&mut rows, "Channel layout", text(&stream["channel_layout"]));
+    push_row(&mut rows, "Language", text(&stream["tags"]["language"]));
+    push_row(&mut rows, "Title", text(&stream["tags"]["title"]));
+
+    Section {
+        title: format!("Stream #{index} ({kind})"),
+        rows,
+    }
+}
+
+fn push_row(rows: &mut Vec<(String, String)>, label: &str, value: Option<String>) {
+    if let Some(value) = value.filter(|value| !value.is_empty()) {
+        rows.push((label.to_string(), value));
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// ffprobe reports most numbers as strings.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn format_duration(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let (hours, rest) = (millis / 3_600_000, millis % 3_600_000);
+    let (minutes, rest) = (rest / 60_000, rest % 60_000);
+    format!(
+        "{hours:02}:{minutes:02}:{:02}.{:03}",
+        rest / 1000,
+        rest % 1000
+    )
+}
+
+fn format_size(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{value:.0} {}", UNITS[unit])
+    } else {
+        format!("{value:.2} {}", UNITS[unit])
+    }
+}
+
+fn format_bit_rate(bits_per_second: f64) -> String {
+    if bits_per_second >= 1_000_000.0 {
+        format!("{:.2} Mb/s", bits_per_second / 1_000_000.0)
+    } else {
+        format!("{:.0} kb/s", bits_per_second / 1000.0)
+    }
+}
+
+/// Keeps tag-derived text on one line and out of the table's cell syntax.
+fn escape_markdown(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffmpeg_probe::ProbeSummary;
+
+    fn sample_entry() -> ReportEntry {
+        let raw = json!({
+            "format": {
+                "format_long_name": "QuickTime / MOV",
+                "duration": "3723.5",
+                "size": "1073741824",
+                "bit_rate": "2306867"
+            },
+            "streams": [
+                {
+                    "index": 0,
+                    "codec_type": "video",
+                    "codec_name": "hevc",
+                    "profile": "Main 10",
+                    "width": 3840,
+                    "height": 2160,
+                    "avg_frame_rate": "24000/1001",
+                    "color_primaries": "bt2020",
+                    "color_transfer": "smpte2084"
+                },
+                {
+                    "index": 1,
+                    "codec_type": "audio",
+                    "codec_name": "aac",
+                    "sample_rate": "48000",
+                    "channels": 2,
+                    "tags": { "language": "eng" }
+                }
+            ],
+            "chapters": [
+                { "start_time": "0.000000", "end_time": "60.000000", "tags": { "title": "Intro" } },
+                { "start_time": "60.000000", "end_time": "90.000000", "tags": { "title": "A | B\nside" } }
+            ]
+        });
+
+        ReportEntry {
+            path: "/media/<clip>.mov".into(),
+            result: Ok(ProbeResponse {
+                raw,
+                summary: ProbeSummary {
+                    duration_sec: 3723.5,
+                    width: Some(3840),
+                    height: Some(2160),
+                    fps: None,
+                    vcodec: Some("hevc".into()),
+                    acodec: Some("aac".into()),
+                    has_text_subs: false,
+                    has_image_subs: false,
+                    channels: Some(2),
+                    color: None,
+                    pix_fmt: None,
+                    bit_depth: None,
+                    profile: None,
+                    level: None,
+                    bit_rate: None,
+                    video_bit_rate: None,
+                    audio_bit_rate: None,
+                    has_cover_art: false,
+                    attachments: Vec::new(),
+                    streams: Vec::new(),
+                    interlacing: None,
+                },
+                chapters: Vec::new(),
+                tags: Default::default(),
+                keyframes: None,
+            }),
+        }
+    }
+
+    fn failed_entry() -> ReportEntry {
+        ReportEntry {
+            path: "/media/broken.mkv".into(),
+            result: Err(AppError::new(
+                "probe_ffprobe_exec",
+                "ffprobe exited with status 1",
+            )),
+        }
+    }
+
+    #[test]
+    fn markdown_lists_container_streams_and_chapters() {
+        let report =
+            render_report(&[sample_entry(), failed_entry()], ReportFormat::Markdown).unwrap();
+        let content = report.content;
+
+        assert_eq!(report.extension, "md");
+        assert!(content.contains("| Duration | 01:02:03.500 |"));
+        assert!(content.contains("| Size | 1.00 GiB |"));
+        assert!(content.contains("| Overall bit rate | 2.31 Mb/s |"));
+        assert!(content.contains("### Stream #0 (video)"));
+        assert!(content.contains("| Color transfer | smpte2084 |"));
+        assert!(content.contains("| Language | eng |"));
+        assert!(content.contains("| Intro | 00:00:00.000 – 00:01:00.000 |"));
+        assert!(content.contains("| A \\| B side | 00:01:00.000 – 00:01:30.000 |"));
+        assert!(content.contains("> Probe failed (probe_ffprobe_exec)"));
+    }
+
+    #[test]
+    fn html_escapes_paths_and_values() {
+        let report = render_report(&[sample_entry()], ReportFormat::Html).unwrap();
+        assert!(report.content.contains("<h2>/media/&lt;clip&gt;.mov</h2>"));
+        assert!(report
+            .content
+            .contains("<tr><th>Resolution</th><td>3840x2160</td></tr>"));
+    }
+
+    #[test]
+    fn json_keeps_raw_data_and_errors() {
+        let report = render_report(&[sample_entry(), failed_entry()], ReportFormat::Json).unwrap();
+        let parsed: Value = serde_json::from_str(&report.content).unwrap();
+
+        assert_eq!(
+            parsed["files"][0]["raw"]["streams"][0]["codec_name"],
+            "hevc"
+        );
+        assert_eq!(parsed["files"][0]["summary"]["vcodec"], "hevc");
+        assert_eq!(parsed["files"][1]["error"]["code"], "probe_ffprobe_exec");
+    }
+}
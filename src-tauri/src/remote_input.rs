@@ -0,0 +1,81 @@
+//! Network inputs that ffprobe and ffmpeg open directly.
+//!
+//! Remote files and streams are accepted as http(s), RTSP or SRT URLs and
+//! passed through untouched; they skip the checks that only make sense on
+//! disk. Any other scheme is refused, which also keeps out the wrapper
+//! protocols that read other local files (`concat:`, `subfile:`).
+
+/// Schemes accepted for network inputs.
+pub const NETWORK_SCHEMES: &[&str] = &["http", "https", "rtsp", "rtsps", "srt"];
+
+/// How long a network read may stall before ffmpeg gives up, in
+/// microseconds as `-rw_timeout` expects.
+pub const NETWORK_TIMEOUT_US: u64 = 15_000_000;
+
+/// The protocol `input` names, such as `https`, `concat` or `crypto+file`,
+/// read the way ffmpeg reads it: letters, digits, `+`, `-` and `.` up to a
+/// `:`, or `subfile` followed by `,` when a `:` comes later. A drive letter
+/// doesn't count.
+pub fn scheme(input: &str) -> Option<&str> {
+    let end = input
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+        .unwrap_or(input.len());
+    let (scheme, rest) = input.split_at(end);
+    let selects_protocol = rest.starts_with(':')
+        || (scheme == "subfile" && rest.starts_with(',') && rest[1..].contains(':'));
+    (selects_protocol && scheme.len() > 1).then_some(scheme)
+}
+
+/// Whether `input` is a URL with one of [`NETWORK_SCHEMES`] and a host.
+pub fn is_network_url(input: &str) -> bool {
+    scheme(input).is_some_and(|scheme| {
+        NETWORK_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str())
+            && input[scheme.len() + 1..]
+                .strip_prefix("//")
+                .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_network_schemes_with_a_host_are_urls() {
+        for url in [
+            "https://example.com/a.mp4",
+            "HTTP://example.com/live.m3u8",
+            "rtsp://camera.local:554/stream",
+            "srt://10.0.0.2:9000?mode=caller",
+        ] {
+            assert!(is_network_url(url), "{url}");
+        }
+        for input in [
+            "/media/clip.mov",
+            r"C:\media\clip.mov",
+            "file:clip.mov",
+            "ftp://example.com/a.mp4",
+            "concat:a.ts|b.ts",
+            "https:///etc/passwd",
+            "https:",
+        ] {
+            assert!(!is_network_url(input), "{input}");
+        }
+        assert_eq!(scheme(r"C:\media\clip.mov"), None);
+        assert_eq!(scheme("concat:a.ts"), Some("concat"));
+    }
+
+    #[test]
+    fn schemes_follow_ffmpeg_url_rules() {
+        assert_eq!(scheme("crypto+file:/x"), Some("crypto+file"));
+        assert_eq!(scheme("my-proto.v2:x"), Some("my-proto.v2"));
+        assert_eq!(
+            scheme("subfile,,start,0,end,0,,:/etc/passwd"),
+            Some("subfile")
+        );
+        assert_eq!(scheme("subfile,clip.mov"), None);
+        assert_eq!(scheme("clip,final:cut.mov"), None);
+        assert_eq!(scheme("/media/a:b.mov"), None);
+        assert_eq!(scheme("-"), None);
+    }
+}
@@ -0,0 +1,187 @@
+//! Power source detection for the battery policy.
+//!
+//! macOS reads `pmset -g batt`, Linux reads `/sys/class/power_supply`, and
+//! Windows asks `GetSystemPowerStatus`. Anything that can't be read counts
+//! as `Unknown`, which the policy treats like mains power.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    Unknown,
+}
+
+/// What the queue does while the machine runs on battery.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatteryPolicy {
+    /// Keep converting as usual.
+    #[default]
+    Ignore,
+    /// Let running jobs finish but start no new ones.
+    Defer,
+    /// Also suspend running jobs until mains power returns.
+    Pause,
+}
+
+pub fn power_source() -> PowerSource {
+    platform::power_source()
+}
+
+/// Reads the source `pmset -g batt` reports on its first line, e.g.
+/// `Now drawing from 'Battery Power'`.
+#[cfg(any(target_os = "macos", test))]
+fn parse_pmset(output: &str) -> PowerSource {
+    let first = output.lines().next().unwrap_or_default();
+    if first.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else if first.contains("'AC Power'") {
+        PowerSource::Ac
+    } else {
+        PowerSource::Unknown
+    }
+}
+
+/// Decides from `(type, online, status)` triples of the power supplies:
+/// an online mains supply wins, then any discharging battery.
+#[cfg(any(all(unix, not(target_os = "macos")), test))]
+fn from_supplies<'a>(
+    supplies: impl IntoIterator<Item = (&'a str, Option<&'a str>, Option<&'a str>)>,
+) -> PowerSource {
+    let mut source = PowerSource::Unknown;
+    for (kind, online, status) in supplies {
+        match kind {
+            "Mains" | "USB" if online == Some("1") => return PowerSource::Ac,
+            "Mains" | "USB" if online == Some("0") && source == PowerSource::Unknown => {
+                source = PowerSource::Battery;
+            },
+            "Battery" if status == Some("Discharging") => source = PowerSource::Battery,
+            _ => {},
+        }
+    }
+    source
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{parse_pmset, PowerSource};
+    use std::process::{Command, Stdio};
+
+    pub fn power_source() -> PowerSource {
+        Command::new("pmset")
+            .args(["-g", "batt"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map_or(PowerSource::Unknown, |output| {
+                parse_pmset(&String::from_utf8_lossy(&output.stdout))
+            })
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::{from_supplies, PowerSource};
+    use std::path::Path;
+
+    const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+    pub fn power_source() -> PowerSource {
+        let Ok(entries) = std::fs::read_dir(POWER_SUPPLY_DIR) else {
+            return PowerSource::Unknown;
+        };
+        let read = |dir: &Path, name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|value| value.trim().to_string())
+        };
+        let supplies: Vec<(String, Option<String>, Option<String>)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .map(|dir| {
+                (
+                    read(&dir, "type").unwrap_or_default(),
+                    read(&dir, "online"),
+                    read(&dir, "status"),
+                )
+            })
+            .collect();
+        from_supplies(
+            supplies.iter().map(|(kind, online, status)| {
+                (kind.as_str(), online.as_deref(), status.as_deref())
+            }),
+        )
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::PowerSource;
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    pub fn power_source() -> PowerSource {
+        // SAFETY: the struct is plain integers, for which zero is valid.
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        // SAFETY: `status` is a valid, writable SYSTEM_POWER_STATUS.
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return PowerSource::Unknown;
+        }
+        match status.ACLineStatus {
+            0 => PowerSource::Battery,
+            1 => PowerSource::Ac,
+            _ => PowerSource::Unknown,
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use super::PowerSource;
+
+    pub fn power_source() -> PowerSource {
+        PowerSource::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pmset_output_is_parsed() {
+        assert_eq!(
+            parse_pmset(
+                "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1)\t82%; discharging"
+            ),
+            PowerSource::Battery
+        );
+        assert_eq!(
+            parse_pmset("Now drawing from 'AC Power'\n"),
+            PowerSource::Ac
+        );
+        assert_eq!(parse_pmset(""), PowerSource::Unknown);
+    }
+
+    #[test]
+    fn mains_supplies_win_over_batteries() {
+        assert_eq!(
+            from_supplies([
+                ("Battery", None, Some("Discharging")),
+                ("Mains", Some("0"), None),
+            ]),
+            PowerSource::Battery
+        );
+        assert_eq!(
+            from_supplies([
+                ("Battery", None, Some("Charging")),
+                ("Mains", Some("1"), None),
+            ]),
+            PowerSource::Ac
+        );
+        assert_eq!(from_supplies([]), PowerSource::Unknown);
+    }
+}
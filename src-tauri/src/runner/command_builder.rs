@@ -0,0 +1,439 @@
+//! Serializes typed ffmpeg invocations into arguments.
+//!
+//! Option names and stream specifiers are checked against ffmpeg's syntax,
+//! options the runner owns or that add inputs are refused, and local input
+//! paths get a `file:` prefix, so a path can neither be mistaken for an
+//! option nor select a protocol. The arguments built here still go through
+//! the same `JobValidator` checks as raw argument lists, which refuse
+//! formats, options and filters that open other files.
+
+use std::ffi::OsString;
+
+use crate::{api::FfmpegCommand, error::AppError, remote_input::is_network_url};
+
+const INVALID_ARGS: &str = "job_invalid_args";
+
+/// Options the builder sets itself or that would add inputs, read files
+/// or take over stdio.
+const FORBIDDEN_OPTIONS: &[&str] = &[
+    "i",
+    "y",
+    "n",
+    "progress",
+    "stdin",
+    "filter_script",
+    "filter_complex_script",
+    "attach",
+    "dump_attachment",
+];
+
+type Opt = (String, Option<String>);
+
+#[derive(Debug)]
+struct Input {
+    path: OsString,
+    options: Vec<Opt>,
+}
+
+/// Builds ffmpeg arguments from inputs, maps, codecs, filters, metadata and
+/// plain options, in the order ffmpeg expects them. Problems are collected
+/// and reported by `build`.
+#[derive(Debug, Default)]
+pub struct FfmpegCommandBuilder {
+    inputs: Vec<Input>,
+    /// Options for the next input.
+    pending: Vec<Opt>,
+    maps: Vec<String>,
+    codecs: Vec<(String, String)>,
+    filters: Vec<(String, String)>,
+    metadata: Vec<(Option<String>, String, String)>,
+    options: Vec<Opt>,
+    format: Option<String>,
+}
+
+impl FfmpegCommandBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an option for the next `input`.
+    pub fn input_option(mut self, name: &str, value: Option<&str>) -> Self {
+        self.pending
+            .push((name.to_string(), value.map(str::to_string)));
+        self
+    }
+
+    pub fn input(mut self, path: impl Into<OsString>) -> Self {
+        self.inputs.push(Input {
+            path: path.into(),
+            options: std::mem::take(&mut self.pending),
+        });
+        self
+    }
+
+    pub fn map(mut self, specifier: &str) -> Self {
+        self.maps.push(specifier.to_string());
+        self
+    }
+
+    /// Encoder for streams matching `stream`; an empty specifier means all.
+    pub fn codec(mut self, stream: &str, codec: &str) -> Self {
+        self.codecs.push((stream.to_string(), codec.to_string()));
+        self
+    }
+
+    pub fn filter(mut self, stream: &str, graph: &str) -> Self {
+        self.filters.push((stream.to_string(), graph.to_string()));
+        self
+    }
+
+    /// Tags the output, or its streams matching `stream`.
+    pub fn metadata(mut self, stream: Option<&str>, key: &str, value: &str) -> Self {
+        self.metadata.push((
+            stream.map(str::to_string),
+            key.to_string(),
+            value.to_string(),
+        ));
+        self
+    }
+
+    pub fn option(mut self, name: &str, value: Option<&str>) -> Self {
+        self.options
+            .push((name.to_string(), value.map(str::to_string)));
+        self
+    }
+
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    pub fn from_command(command: &FfmpegCommand) -> Self {
+        let mut builder = Self::new();
+        for input in &command.inputs {
+            for option in &input.options {
+                builder = builder.input_option(&option.name, option.value.as_deref());
+            }
+            builder = builder.input(input.path.clone().into_os_string());
+        }
+        for map in &command.maps {
+            builder = builder.map(map);
+        }
+        for codec in &command.codecs {
+            builder = builder.codec(&codec.stream, &codec.codec);
+        }
+        for filter in &command.filters {
+            builder = builder.filter(&filter.stream, &filter.graph);
+        }
+        for entry in &command.metadata {
+            builder = builder.metadata(entry.stream.as_deref(), &entry.key, &entry.value);
+        }
+        for option in &command.options {
+            builder = builder.option(&option.name, option.value.as_deref());
+        }
+        if let Some(format) = &command.format {
+            builder = builder.format(format);
+        }
+        builder
+    }
+
+    /// Serializes the command, without the output path the runner appends.
+    pub fn build(self) -> Result<Vec<OsString>, AppError> {
+        let mut problems = Vec::new();
+        let mut args: Vec<OsString> = vec!["-y".into()];
+
+        if self.inputs.is_empty() {
+            problems.push("needs at least one input".to_string());
+        }
+        if !self.pending.is_empty() {
+            problems.push("has input options without an input".to_string());
+        }
+        for input in self.inputs {
+            push_options(&mut args, &input.options, &mut problems);
+            let bytes = input.path.as_encoded_bytes();
+            if bytes.is_empty() || bytes.contains(&0) {
+                problems.push("input paths must be non-empty and NUL-free".to_string());
+                continue;
+            }
+            args.push("-i".into());
+            // Without the prefix, a leading dash reads as an option and a
+            // `name:` prefix as a protocol such as `concat:`.
+            args.push(if input.path.to_str().is_some_and(is_network_url) {
+                input.path
+            } else {
+                let mut prefixed = OsString::from("file:");
+                prefixed.push(&input.path);
+                prefixed
+            });
+        }
+
+        for map in self.maps {
+            if valid_map(&map) {
+                args.extend(["-map".into(), map.into()]);
+            } else {
+                problems.push(format!("invalid map {map:?}"));
+            }
+        }
+        for (stream, codec) in self.codecs {
+            if !valid_specifier(&stream) || !valid_word(&codec) {
+                problems.push(format!("invalid codec {codec:?} for {stream:?}"));
+                continue;
+            }
+            args.extend([with_specifier("-c", &stream).into(), codec.into()]);
+        }
+        for (stream, graph) in self.filters {
+            if !valid_specifier(&stream) || graph.is_empty() || graph.contains('\0') {
+                problems.push(format!("invalid filter for {stream:?}"));
+                continue;
+            }
+            args.extend([with_specifier("-filter", &stream).into(), graph.into()]);
+        }
+        push_options(&mut args, &self.options, &mut problems);
+        for (stream, key, value) in self.metadata {
+            let stream_ok = stream.as_deref().is_none_or(valid_specifier);
+            if !stream_ok || key.is_empty() || key.contains(['=', '\0']) || value.contains('\0') {
+                problems.push(format!("invalid metadata {key:?}"));
+                continue;
+            }
+            let flag = match stream {
+                Some(stream) => format!("-metadata:s:{stream}"),
+                None => "-metadata".into(),
+            };
+            args.extend([flag.into(), format!("{key}={value}").into()]);
+        }
+        if let Some(format) = self.format {
+            if valid_word(&format) {
+                args.extend(["-f".into(), format.into()]);
+            } else {
+                problems.push(format!("invalid format {format:?}"));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(args)
+        } else {
+            Err(AppError::new(
+                INVALID_ARGS,
+                format!("FFmpeg command {}.", problems.join("; ")),
+            ))
+        }
+    }
+}
+
+fn push_options(args: &mut Vec<OsString>, options: &[Opt], problems: &mut Vec<String>) {
+    for (name, value) in options {
+        let base = name.split(':').next().unwrap_or_default();
+        if !valid_option_name(name) || FORBIDDEN_OPTIONS.contains(&base) {
+            problems.push(format!("option {name:?} is not allowed"));
+            continue;
+        }
+        if value.as_deref().is_some_and(|value| value.contains('\0')) {
+            problems.push(format!("option {name:?} has a NUL byte"));
+            continue;
+        }
+        args.push(format!("-{name}").into());
+        args.extend(value.clone().map(OsString::from));
+    }
+}
+
+fn with_specifier(flag: &str, stream: &str) -> String {
+    if stream.is_empty() {
+        flag.to_string()
+    } else {
+        format!("{flag}:{stream}")
+    }
+}
+
+fn valid_option_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':'))
+}
+
+/// Codec and muxer names.
+fn valid_word(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// Stream specifiers such as `v`, `a:1` or `s:m:language:eng`.
+fn valid_specifier(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '#' | '.'))
+}
+
+/// An input stream (`0:v:0?`, `-0:s`) or a filtergraph label (`[out]`).
+fn valid_map(value: &str) -> bool {
+    if let Some(label) = value
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        return valid_word(label);
+    }
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let value = value.strip_suffix('?').unwrap_or(value);
+    value.starts_with(|c: char| c.is_ascii_digit()) && valid_specifier(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn commands_serialize_in_ffmpeg_order() {
+        let args = FfmpegCommandBuilder::new()
+            .input_option("ss", Some("5"))
+            .input("/media/Tom & Jerry.mov")
+            .map("0:v:0?")
+            .map("-0:s")
+            .codec("v", "libx264")
+            .codec("a", "aac")
+            .filter("v", "scale=1280:-2;null")
+            .option("crf", Some("23"))
+            .metadata(Some("a:0"), "language", "eng")
+            .metadata(None, "title", "Cartoon")
+            .format("mp4")
+            .build()
+            .unwrap();
+        assert_eq!(
+            args,
+            strings(&[
+                "-y",
+                "-ss",
+                "5",
+                "-i",
+                "file:/media/Tom & Jerry.mov",
+                "-map",
+                "0:v:0?",
+                "-map",
+                "-0:s",
+                "-c:v",
+                "libx264",
+                "-c:a",
+                "aac",
+                "-filter:v",
+                "scale=1280:-2;null",
+                "-crf",
+                "23",
+                "-metadata:s:a:0",
+                "language=eng",
+                "-metadata",
+                "title=Cartoon",
+                "-f",
+                "mp4",
+            ])
+        );
+    }
+
+    #[test]
+    fn paths_that_look_like_options_stay_inputs() {
+        let args = FfmpegCommandBuilder::new()
+            .input("-rf.mov")
+            .build()
+            .unwrap();
+        assert_eq!(args, strings(&["-y", "-i", "file:-rf.mov"]));
+    }
+
+    #[test]
+    fn only_network_urls_keep_their_protocol() {
+        let args = FfmpegCommandBuilder::new()
+            .input("concat:/a.ts|/b.ts")
+            .input("subfile,,start,0,end,0,,:/etc/passwd")
+            .input("https://example.com/a.mp4")
+            .build()
+            .unwrap();
+        assert_eq!(
+            args,
+            strings(&[
+                "-y",
+                "-i",
+                "file:concat:/a.ts|/b.ts",
+                "-i",
+                "file:subfile,,start,0,end,0,,:/etc/passwd",
+                "-i",
+                "https://example.com/a.mp4",
+            ])
+        );
+    }
+
+    #[test]
+    fn unsafe_pieces_are_rejected() {
+        let err = FfmpegCommandBuilder::new()
+            .input("in.mov")
+            .option("i", Some("/etc/passwd"))
+            .option("filter_complex_script", Some("graph.txt"))
+            .option("-crf", Some("23"))
+            .map("0:v; rm")
+            .codec("v", "libx264 -f")
+            .metadata(None, "a=b", "c")
+            .build()
+            .unwrap_err();
+        assert_eq!(err.code, INVALID_ARGS);
+        for fragment in [
+            "\"i\"",
+            "filter_complex_script",
+            "\"-crf\"",
+            "map",
+            "codec",
+            "metadata",
+        ] {
+            assert!(
+                err.message.contains(fragment),
+                "{fragment}: {}",
+                err.message
+            );
+        }
+
+        assert!(FfmpegCommandBuilder::new().build().is_err());
+        assert!(FfmpegCommandBuilder::new()
+            .input("in.mov")
+            .input_option("ss", Some("1"))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn built_commands_still_need_the_validator() {
+        let validator = crate::runner::validator::JobValidator::new();
+        let built = [
+            FfmpegCommandBuilder::new()
+                .input("in.mov")
+                .filter("", "movie=/etc/passwd")
+                .build(),
+            FfmpegCommandBuilder::new()
+                .input_option("f", Some("lavfi"))
+                .input("anullsrc")
+                .build(),
+            FfmpegCommandBuilder::new()
+                .input("in.mov")
+                .option("passlogfile", Some("/tmp/x"))
+                .build(),
+        ];
+        for args in built {
+            assert!(validator.validate_args(&args.unwrap()).is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths_are_passed_through() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let path = OsString::from_vec(vec![b'i', 0xff, b'.', b'm']);
+        let args = FfmpegCommandBuilder::new()
+            .input(path.clone())
+            .build()
+            .unwrap();
+        let mut prefixed = OsString::from("file:");
+        prefixed.push(&path);
+        assert_eq!(args, vec!["-y".into(), "-i".into(), prefixed]);
+    }
+}
@@ -1,15 +1,32 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::battery::BatteryPolicy;
+use crate::error::AppError;
+use crate::sync_utils::MutexExt;
+
+/// New jobs wait in the queue while the battery policy holds it.
+pub const JOB_QUEUE_SUSPENDED: &str = "job_queue_suspended";
 
 /// Manages concurrency limits for FFmpeg jobs
 pub struct ConcurrencyManager {
     max_concurrency: Arc<AtomicUsize>,
+    battery_policy: Arc<Mutex<BatteryPolicy>>,
+    /// Set while running on battery under a policy other than `Ignore`.
+    suspended: Arc<AtomicBool>,
 }
 
 impl ConcurrencyManager {
     pub fn new() -> Self {
+        Self::with_limit(2)
+    }
+
+    /// Creates a manager starting at `limit` concurrent jobs
+    pub fn with_limit(limit: usize) -> Self {
         Self {
-            max_concurrency: Arc::new(AtomicUsize::new(2)),
+            max_concurrency: Arc::new(AtomicUsize::new(limit.max(1))),
+            battery_policy: Arc::new(Mutex::new(BatteryPolicy::default())),
+            suspended: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -22,6 +39,30 @@ impl ConcurrencyManager {
     pub fn set_limit(&self, limit: usize) {
         self.max_concurrency.store(limit.max(1), Ordering::SeqCst);
     }
+
+    pub fn battery_policy(&self) -> BatteryPolicy {
+        *self.battery_policy.lock_or_recover()
+    }
+
+    pub fn set_battery_policy(&self, policy: BatteryPolicy) {
+        *self.battery_policy.lock_or_recover() = policy;
+    }
+
+    /// Holds or releases new job starts; returns whether that changed.
+    pub fn set_suspended(&self, suspended: bool) -> bool {
+        self.suspended.swap(suspended, Ordering::SeqCst) != suspended
+    }
+
+    /// Refuses new starts while the queue is suspended.
+    pub fn ensure_not_suspended(&self) -> Result<(), AppError> {
+        if self.suspended.load(Ordering::SeqCst) {
+            return Err(AppError::new(
+                JOB_QUEUE_SUSPENDED,
+                "Running on battery power; new jobs wait for mains power.",
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Default for ConcurrencyManager {
@@ -34,6 +75,8 @@ impl Clone for ConcurrencyManager {
     fn clone(&self) -> Self {
         Self {
             max_concurrency: Arc::clone(&self.max_concurrency),
+            battery_policy: Arc::clone(&self.battery_policy),
+            suspended: Arc::clone(&self.suspended),
         }
     }
 }
@@ -55,10 +98,30 @@ mod tests {
         assert_eq!(manager.get_limit(), 5);
     }
 
+    #[test]
+    fn test_with_limit() {
+        assert_eq!(ConcurrencyManager::with_limit(1).get_limit(), 1);
+        assert_eq!(ConcurrencyManager::with_limit(0).get_limit(), 1);
+    }
+
     #[test]
     fn test_minimum_concurrency() {
         let manager = ConcurrencyManager::new();
         manager.set_limit(0);
         assert_eq!(manager.get_limit(), 1); // Minimum is 1
     }
+
+    #[test]
+    fn suspension_refuses_new_starts() {
+        let manager = ConcurrencyManager::new();
+        assert!(manager.ensure_not_suspended().is_ok());
+        assert!(manager.set_suspended(true));
+        assert!(!manager.clone().set_suspended(true));
+        assert_eq!(
+            manager.ensure_not_suspended().unwrap_err().code,
+            JOB_QUEUE_SUSPENDED
+        );
+        assert!(manager.set_suspended(false));
+        assert!(manager.ensure_not_suspended().is_ok());
+    }
 }
@@ -1,111 +1,1297 @@
 use super::{
+    battery::{self, BatteryPolicy, PowerSource},
     concurrency::ConcurrencyManager,
-    events::SharedEmitter,
+    disk_space,
+    events::{
+        CompletionPayload, QueuePositionPayload, QueueSuspendedPayload, RetryingPayload,
+        SharedEmitter, TaggedEmitter, VerificationPayload,
+    },
     external::SpawnController,
-    job_registry::{JobRecord, JobRegistry},
-    output_manager::OutputManager,
+    history::{HistoryEntry, HistoryStore},
+    hooks::{HookOutcome, HookSettings, PostJobHook},
+    job_graph::{Admission, JobGraph},
+    job_log::{JobLog, LogSink},
+    job_queue::{JobQueue, Submission},
+    job_registry::{JobClass, JobRecord, JobRegistry, JobSnapshot},
+    job_store::{JobStore, PersistedJob},
+    output_check::{self, MediaShape},
+    output_manager::{OutputManager, OutputTarget},
+    power::SleepInhibitor,
+    process_spawner::ProcessSpawner,
     progress_monitor::{ProgressMonitor, RunningProcess},
+    retry::{RetryPolicy, RetrySettings},
+    segmented::{SegmentPlan, SegmentedJob},
+    source_watch::SourceSnapshot,
+    triage,
     validator::JobValidator,
+    verification::{self, VerificationMonitor},
+    watchdog::{kill_after_grace, StallTimeout},
 };
-use crate::error::AppError;
-use std::sync::Arc;
+use crate::{
+    api::{JobMetadata, NativePath, OverwritePolicy},
+    error::AppError,
+    ffmpeg_probe,
+    sync_utils::MutexExt,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::AppHandle;
 
+/// How long ffmpeg gets to quit on its own after a cancel before it is
+/// killed.
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+/// How often the power source is checked while a battery policy is set.
+const BATTERY_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Coordinates FFmpeg job lifecycle and collaboration between subsystems.
 pub struct JobCoordinator {
     registry: Arc<JobRegistry>,
+    store: Arc<JobStore>,
+    history: Arc<HistoryStore>,
+    queue: Arc<JobQueue<QueuedJob>>,
+    /// Verifications waiting for a verification slot.
+    verifications: Arc<JobQueue<QueuedVerification>>,
+    /// Jobs waiting on parent jobs, and how finished jobs ended.
+    graph: Arc<JobGraph<QueuedJob>>,
+    /// Jobs waiting out a retry delay.
+    backoff: Arc<Mutex<HashSet<String>>>,
+    retry: RetrySettings,
+    hooks: HookSettings,
+    stall_timeout: StallTimeout,
+    /// Global background-priority preference.
+    low_priority: Arc<AtomicBool>,
+    /// Global encoder thread limit; 0 lets ffmpeg decide.
+    threads: Arc<AtomicU32>,
+    inhibitor: Arc<SleepInhibitor>,
+    /// Whether the power source is being watched.
+    battery_watch: Arc<AtomicBool>,
+    /// Jobs the `Pause` battery policy suspended, resumed on mains power.
+    battery_paused: Arc<Mutex<Vec<String>>>,
     concurrency: ConcurrencyManager,
+    verification_concurrency: ConcurrencyManager,
     spawner: Arc<dyn SpawnController>,
+    /// Set once `shutdown` has run.
+    shut_down: Arc<AtomicBool>,
 }
 
 impl JobCoordinator {
     pub fn with_spawner(spawner: Arc<dyn SpawnController>) -> Self {
+        let store = Arc::new(JobStore::default());
+        let inhibitor = Arc::new(SleepInhibitor::default());
         Self {
-            registry: Arc::new(JobRegistry::new()),
+            registry: Arc::new(
+                JobRegistry::with_store(Arc::clone(&store)).with_inhibitor(Arc::clone(&inhibitor)),
+            ),
+            store,
+            history: Arc::new(HistoryStore::default()),
+            queue: Arc::new(JobQueue::default()),
+            verifications: Arc::new(JobQueue::default()),
+            graph: Arc::new(JobGraph::default()),
+            backoff: Arc::new(Mutex::new(HashSet::new())),
+            retry: RetrySettings::default(),
+            hooks: HookSettings::default(),
+            stall_timeout: StallTimeout::default(),
+            low_priority: Arc::new(AtomicBool::new(false)),
+            threads: Arc::new(AtomicU32::new(0)),
+            inhibitor,
+            battery_watch: Arc::new(AtomicBool::new(false)),
+            battery_paused: Arc::new(Mutex::new(Vec::new())),
             concurrency: ConcurrencyManager::new(),
+            verification_concurrency: ConcurrencyManager::with_limit(1),
             spawner,
+            shut_down: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Starts the job, or queues it when every slot is taken; queued jobs
+    /// start automatically as running ones finish. Jobs with dependencies
+    /// wait until every parent has succeeded.
     pub fn start_job(
         &self,
         app: AppHandle,
         emitter: SharedEmitter,
         job_id: String,
-        args: Vec<String>,
-        output_path: String,
-        exclusive: bool,
+        args: Vec<OsString>,
+        output_path: PathBuf,
+        options: JobOptions,
     ) -> Result<(), AppError> {
-        let validator = JobValidator::new();
-        validator.validate_args(&args)?;
+        JobValidator::new()
+            .validate_args(&args)?
+            .validate_inputs(&args)?
+            .validate_paths(&args, &output_path)?;
+        self.store.attach(&app);
+        self.history.attach(&app);
+        self.hooks.attach(&app);
+        let emitter: SharedEmitter = if options.metadata.is_empty() {
+            emitter
+        } else {
+            Arc::new(TaggedEmitter::new(emitter, options.metadata.clone()))
+        };
+
+        let job = QueuedJob {
+            app,
+            emitter,
+            args,
+            output_path,
+            exclusive: options.exclusive,
+            low_priority: options.low_priority,
+            depends_on: options.depends_on,
+            overwrite: options.overwrite,
+            estimated_size: options.estimated_size,
+            preset_id: options.preset_id,
+            duration_sec: options.duration_sec,
+            segmented: options.segmented,
+            threads: options.threads,
+            trash_source: options.trash_source,
+            preserve_timestamps: options.preserve_timestamps,
+            metadata: options.metadata,
+            structured: options.structured,
+            ffmpeg_installation: options.ffmpeg_installation,
+            fell_back_to_software: false,
+            attempt: 0,
+        };
+        let persisted = job.persisted(&job_id);
+        let depends_on = job.depends_on.clone();
+        let job = match self.graph.admit(&job_id, &depends_on, job)? {
+            Admission::Ready(job) => job,
+            Admission::Waiting => {
+                self.store.upsert(PersistedJob {
+                    queued: true,
+                    ..persisted
+                });
+                return Ok(());
+            },
+            Admission::Skipped { job, parent } => {
+                self.skip(job_id, &job, &parent);
+                return Ok(());
+            },
+        };
+
+        let submission = match self
+            .queue
+            .submit(job_id.clone(), job.clone(), |job_id, job| {
+                self.launch(job_id, job)
+            }) {
+            Ok(submission) => submission,
+            Err(err) if self.retry_failed_launch(&job_id, &job, &err) => return Ok(()),
+            Err(err) => {
+                self.settle(&job_id, false);
+                return Err(err);
+            },
+        };
+
+        if let Submission::Queued(_) = submission {
+            self.store.upsert(PersistedJob {
+                queued: true,
+                ..persisted
+            });
+            self.emit_queue_positions();
+        }
+        Ok(())
+    }
 
-        let ffmpeg_path = self.spawner.resolve_ffmpeg(&app)?;
-        let (final_path, temp_path) = self.spawner.prepare_output(&output_path, exclusive)?;
-        let temp_arg = temp_path
-            .to_str()
-            .ok_or_else(|| {
-                AppError::new("job_output_invalid", "Output path contains invalid UTF-8")
-            })?
-            .to_string();
+    fn launch(&self, job_id: &str, job: &QueuedJob) -> Result<(), AppError> {
+        self.concurrency.ensure_not_suspended()?;
+        let limit = self.concurrency.get_limit();
+        self.registry
+            .check_admission(job_id, job.exclusive, JobClass::Conversion, limit)?;
 
-        let mut child = self.spawner.spawn_job(ffmpeg_path, &args, &temp_arg)?;
+        let ffmpeg_path = self
+            .spawner
+            .resolve_ffmpeg(&job.app, job.ffmpeg_installation.as_deref())?;
+        let mut output =
+            self.spawner
+                .prepare_output(&job.output_path, job_id, job.exclusive, job.overwrite)?;
+        if job.preserve_timestamps {
+            output.preserve_from = job.source_path().map(Path::to_path_buf);
+        }
+        if let Some(estimated) = job.estimated_size {
+            disk_space::ensure_space(&output.temp_path, estimated)?;
+        }
+
+        let low_priority = job
+            .low_priority
+            .unwrap_or_else(|| self.low_priority.load(Ordering::SeqCst));
+        let threads = job
+            .threads
+            .or_else(|| Some(self.threads.load(Ordering::SeqCst)).filter(|&threads| threads > 0));
+        let args = match threads {
+            Some(threads) => ProcessSpawner::limit_threads(&job.args, threads),
+            None => job.args.clone(),
+        };
+        let segmented = if job.segmented {
+            self.segmented(job_id, job, &args, &output, &ffmpeg_path, low_priority)
+        } else {
+            None
+        };
+        let mut child = match &segmented {
+            Some(segmented) => segmented.spawn_split()?,
+            None => self
+                .spawner
+                .spawn_job(ffmpeg_path, &args, &output.temp_path, low_priority)?,
+        };
 
         let stderr = child.stderr.take();
-        let process = Arc::new(RunningProcess::new(child, exclusive));
+        let process = Arc::new(
+            RunningProcess::new(child, job.exclusive)
+                .with_log(job_log(&job.app, job_id))
+                .with_stall_timeout(self.stall_timeout.get())
+                .with_source_watch(job.source_path().and_then(SourceSnapshot::take)),
+        );
         let record = JobRecord::new(
             Arc::clone(&process),
-            final_path.clone(),
-            temp_path.clone(),
-            exclusive,
+            output.final_path.clone(),
+            output.temp_path.clone(),
+            job.exclusive,
+        )
+        .with_persisted(job.persisted(job_id));
+        if let Err(err) = self.registry.register(job_id.to_string(), record, limit) {
+            Self::abandon(&process);
+            OutputManager::cleanup_temp(&output.temp_path);
+            return Err(err);
+        }
+
+        if let Some(child) = process.child.lock_or_recover().as_mut() {
+            child.stderr = stderr;
+        }
+
+        let registry = Arc::clone(&self.registry);
+        let coordinator = self.clone();
+        let (finished_id, finished_job) = (job_id.to_string(), job.clone());
+        let started = Instant::now();
+        let on_exit = move |completion| {
+            coordinator.finish_job(&finished_id, &finished_job, completion, started)
+        };
+        match segmented {
+            Some(segmented) => segmented.start(
+                job.emitter.clone(),
+                registry,
+                job_id.to_string(),
+                process,
+                output,
+                on_exit,
+            ),
+            None => ProgressMonitor::start(
+                job.emitter.clone(),
+                registry,
+                job_id.to_string(),
+                process,
+                output,
+                on_exit,
+            ),
+        }
+
+        Ok(())
+    }
+
+    /// Plans a segmented run of `job`, or `None` when the source doesn't
+    /// qualify and the job runs as one process. Segment commands are built
+    /// as text, so jobs with paths that aren't UTF-8 don't qualify either.
+    fn segmented(
+        &self,
+        job_id: &str,
+        job: &QueuedJob,
+        args: &[OsString],
+        output: &OutputTarget,
+        ffmpeg_path: &OsString,
+        low_priority: bool,
+    ) -> Option<SegmentedJob> {
+        let args = utf8_args(args)?;
+        output.temp_path.to_str()?;
+        let source = output_check::input_path(&job.args)?;
+        let probe = ffmpeg_probe::probe_media(&job.app, source).ok()?;
+        let cores = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let planned = SegmentPlan::new(&args, &MediaShape::from_probe(&probe.raw), cores)
+            .map_err(|reason| reason.to_string())
+            .and_then(|plan| {
+                eprintln!(
+                    "[jobs] job {job_id} encodes in {} segments",
+                    plan.segments()
+                );
+                SegmentedJob::new(
+                    plan,
+                    output,
+                    Arc::clone(&self.spawner),
+                    ffmpeg_path.clone(),
+                    low_priority,
+                )
+                .map_err(|err| err.message)
+            });
+        match planned {
+            Ok(segmented) => Some(segmented),
+            Err(reason) => {
+                eprintln!("[jobs] job {job_id} runs as one process: {reason}");
+                None
+            },
+        }
+    }
+
+    /// Starts queued jobs while slots are free. Jobs that fail to launch
+    /// leave the queue and are reported as failed completions. Queued
+    /// verifications are started the same way in their own class.
+    pub fn pump_queue(&self) {
+        self.pump_verifications();
+        let pumped = self.queue.pump(|job_id, job| self.launch(job_id, job));
+        if pumped.started.is_empty() && pumped.failed.is_empty() {
+            return;
+        }
+
+        for (job_id, job, err) in pumped.failed {
+            self.fail_launch(job_id, &job, err);
+        }
+        self.emit_queue_positions();
+    }
+
+    /// Reports a finished conversion, unless its exit code is one the retry
+    /// policy treats as transient and attempts remain.
+    fn finish_job(
+        &self,
+        job_id: &str,
+        job: &QueuedJob,
+        mut completion: CompletionPayload,
+        started: Instant,
+    ) {
+        if let Some(output) = completion
+            .output_path
+            .clone()
+            .map(NativePath::into_path_buf)
+        {
+            Self::verify_output(job, &output, &mut completion);
+            if job.trash_source && completion.success && completion.verified {
+                Self::trash_source(job, &output, &mut completion);
+            }
+        }
+        completion.fell_back_to_software = job.fell_back_to_software;
+        if self.fall_back_to_software(job_id, job, &completion) {
+            self.pump_queue();
+            return;
+        }
+        let policy = self.retry.get();
+        let transient = !completion.success
+            && !completion.cancelled
+            && policy.retries_exit(completion.exit_code);
+        let retrying = transient
+            && self.schedule_retry(
+                &policy,
+                job_id,
+                job,
+                &completion.code,
+                completion.message.as_deref(),
+            );
+        if !retrying {
+            self.report(job, &completion, Some(started.elapsed()));
+            self.settle(job_id, completion.success);
+        }
+        self.pump_queue();
+    }
+
+    /// Starts a job again with the software equivalent of its hardware
+    /// encoder when that encoder made it fail. Happens at most once per
+    /// job; returns whether it did.
+    fn fall_back_to_software(
+        &self,
+        job_id: &str,
+        job: &QueuedJob,
+        completion: &CompletionPayload,
+    ) -> bool {
+        if completion.success
+            || completion.cancelled
+            || job.fell_back_to_software
+            || !triage::hardware_encoder_failed(&completion.logs)
+        {
+            return false;
+        }
+        let Some(args) = utf8_args(&job.args).and_then(|args| triage::software_encoder_args(&args))
+        else {
+            return false;
+        };
+        eprintln!("[jobs] hardware encoder failed for job {job_id}; retrying in software");
+        self.enqueue(
+            job_id.to_string(),
+            QueuedJob {
+                args: args.into_iter().map(OsString::from).collect(),
+                fell_back_to_software: true,
+                ..job.clone()
+            },
         );
+        true
+    }
+
+    /// Emits a job's final completion, adds it to the history and runs the
+    /// post-job hooks.
+    fn report(&self, job: &QueuedJob, completion: &CompletionPayload, elapsed: Option<Duration>) {
+        job.emitter.emit_completion(completion);
+
+        let file_size = |path: &Path| fs::metadata(path).ok().map(|metadata| metadata.len());
+        let output = completion
+            .output_path
+            .clone()
+            .map(NativePath::into_path_buf);
+        let source_path =
+            output_check::input_path(&job.args).map(|source| source.to_string_lossy().into_owned());
+        let output_path = output
+            .as_deref()
+            .unwrap_or(&job.output_path)
+            .to_string_lossy()
+            .into_owned();
+        self.hooks.run(HookOutcome {
+            job_id: completion.job_id.clone(),
+            status: HookOutcome::status(completion.success, completion.cancelled),
+            code: completion.code.clone(),
+            message: completion.message.clone(),
+            input: source_path.clone(),
+            output: output_path.clone(),
+        });
+        let speed = match (job.duration_sec, elapsed) {
+            (Some(duration), Some(elapsed)) if completion.success && !elapsed.is_zero() => {
+                Some(duration / elapsed.as_secs_f64())
+            },
+            _ => None,
+        };
+        self.history.record(HistoryEntry {
+            job_id: completion.job_id.clone(),
+            source_bytes: job.source_path().and_then(file_size),
+            source_path,
+            output_bytes: output.as_deref().and_then(file_size),
+            output_path,
+            preset_id: job.preset_id.clone(),
+            success: completion.success,
+            cancelled: completion.cancelled,
+            code: completion.code.clone(),
+            message: completion.message.clone(),
+            duration_sec: job.duration_sec,
+            elapsed_ms: elapsed.map(|elapsed| elapsed.as_millis() as u64),
+            speed,
+            finished_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_millis() as u64)
+                .unwrap_or_default(),
+        });
+    }
+
+    /// Probes a finished output against its source, failing the job when
+    /// the output is truncated or unreadable. The file is kept for
+    /// inspection.
+    fn verify_output(job: &QueuedJob, output: &Path, completion: &mut CompletionPayload) {
+        match output_check::verify(&job.app, &job.args, output) {
+            Ok(verified) => completion.verified = verified,
+            Err(err) => {
+                completion.success = false;
+                completion.code = err.code.to_string();
+                completion.logs.push(err.message.clone());
+                completion.message = Some(err.message);
+            },
+        }
+    }
+
+    /// Moves a verified job's source to the trash. Failing to do so leaves
+    /// the job successful and is only logged.
+    fn trash_source(job: &QueuedJob, output: &Path, completion: &mut CompletionPayload) {
+        let Some(source) = job.source_path() else {
+            return;
+        };
+        match OutputManager::trash_source(source, output) {
+            Ok(()) => completion.trashed_source = Some(source.to_string_lossy().into_owned()),
+            Err(err) => completion.logs.push(err.message),
+        }
+    }
+
+    /// Records how a job ended, then queues the children it released and
+    /// reports the ones its failure skipped.
+    fn settle(&self, job_id: &str, success: bool) {
+        let settled = self.graph.finish(job_id, success);
+        for (child_id, child, parent) in settled.skipped {
+            self.skip(child_id, &child, &parent);
+        }
+        for (child_id, child) in settled.ready {
+            self.enqueue(child_id, child);
+        }
+    }
+
+    fn skip(&self, job_id: String, job: &QueuedJob, parent: &str) {
+        self.store.remove(&job_id);
+        let completion = CompletionPayload {
+            job_id,
+            success: false,
+            cancelled: false,
+            exit_code: None,
+            signal: None,
+            code: "job_skipped".into(),
+            message: Some(format!("Skipped because job {parent} did not succeed.")),
+            logs: Vec::new(),
+            log_path: None,
+            suggestions: Vec::new(),
+            output_path: None,
+            verified: false,
+            fell_back_to_software: false,
+            trashed_source: None,
+            metadata: Default::default(),
+        };
+        self.report(job, &completion, None);
+    }
+
+    /// Starts a job released later (by a parent or a retry delay), queueing
+    /// it when no slot is free.
+    fn enqueue(&self, job_id: String, job: QueuedJob) {
+        match self
+            .queue
+            .submit(job_id.clone(), job.clone(), |job_id, job| {
+                self.launch(job_id, job)
+            }) {
+            Ok(Submission::Started) => {},
+            Ok(Submission::Queued(_)) => {
+                self.store.upsert(PersistedJob {
+                    queued: true,
+                    ..job.persisted(&job_id)
+                });
+                self.emit_queue_positions();
+            },
+            Err(err) => self.fail_launch(job_id, &job, err),
+        }
+    }
+
+    /// Reports a job that could not be started, retrying it first when the
+    /// error is transient.
+    fn fail_launch(&self, job_id: String, job: &QueuedJob, err: AppError) {
+        if self.retry_failed_launch(&job_id, job, &err) {
+            return;
+        }
+        self.store.remove(&job_id);
+        let completion = CompletionPayload {
+            job_id: job_id.clone(),
+            success: false,
+            cancelled: false,
+            exit_code: None,
+            signal: None,
+            code: err.code.to_string(),
+            message: Some(err.message),
+            logs: Vec::new(),
+            log_path: None,
+            suggestions: Vec::new(),
+            output_path: None,
+            verified: false,
+            fell_back_to_software: false,
+            trashed_source: None,
+            metadata: Default::default(),
+        };
+        self.report(job, &completion, None);
+        self.settle(&job_id, false);
+    }
+
+    fn retry_failed_launch(&self, job_id: &str, job: &QueuedJob, err: &AppError) -> bool {
+        let policy = self.retry.get();
+        policy.retries_error(err)
+            && self.schedule_retry(&policy, job_id, job, err.code, Some(&err.message))
+    }
+
+    /// Starts the job again after the policy's delay. Returns false when
+    /// no attempts remain.
+    fn schedule_retry(
+        &self,
+        policy: &RetryPolicy,
+        job_id: &str,
+        job: &QueuedJob,
+        code: &str,
+        message: Option<&str>,
+    ) -> bool {
+        let attempt = job.attempt + 1;
+        if !policy.allows(attempt) {
+            return false;
+        }
+        self.backoff.lock_or_recover().insert(job_id.to_string());
+
+        self.store.upsert(PersistedJob {
+            queued: true,
+            ..job.persisted(job_id)
+        });
+        let delay = policy.delay(attempt);
+        job.emitter.emit_retrying(&RetryingPayload {
+            job_id: job_id.to_string(),
+            attempt,
+            max_retries: policy.max_retries,
+            delay_ms: delay.as_millis() as u64,
+            code: code.to_string(),
+            message: message.map(str::to_string),
+        });
+
+        let coordinator = self.clone();
+        let job_id = job_id.to_string();
+        let job = QueuedJob {
+            attempt,
+            ..job.clone()
+        };
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            coordinator.resume_retry(job_id, job);
+        });
+        true
+    }
+
+    fn resume_retry(&self, job_id: String, job: QueuedJob) {
+        let pending = self.backoff.lock_or_recover().remove(&job_id);
+        if !pending {
+            // Cancelled while waiting.
+            return;
+        }
+        self.enqueue(job_id, job);
+    }
+
+    fn emit_queue_positions(&self) {
+        self.queue
+            .for_each_position(|job_id, job, position, queue_length| {
+                job.emitter.emit_queue_position(&QueuePositionPayload {
+                    job_id: job_id.to_string(),
+                    position,
+                    queue_length,
+                });
+            });
+    }
+
+    /// Kills a process that was spawned but could not be registered.
+    fn abandon(process: &RunningProcess) {
+        if let Some(child) = process.child.lock_or_recover().as_mut() {
+            let _ = ProcessSpawner::kill_tree(child);
+            let _ = child.wait();
+        }
+    }
+
+    /// Decodes an existing file, or its first `limit_sec` seconds, without
+    /// producing output, optionally hashing it afterwards. Runs in its own
+    /// concurrency class so verification never takes slots from conversions,
+    /// and waits in its own queue while that class is full.
+    pub fn start_verification(
+        &self,
+        app: AppHandle,
+        emitter: SharedEmitter,
+        job_id: String,
+        path: String,
+        checksum: bool,
+        limit_sec: Option<f64>,
+    ) -> Result<(), AppError> {
+        let source = PathBuf::from(&path);
+        if !source.is_file() {
+            return Err(AppError::new(
+                "verify_missing_file",
+                format!("{path} does not exist or is not a file"),
+            ));
+        }
+
+        let verification = QueuedVerification {
+            app,
+            emitter,
+            path,
+            checksum,
+            limit_sec,
+        };
+        let submission =
+            self.verifications
+                .submit(job_id, verification, |job_id, verification| {
+                    self.launch_verification(job_id, verification)
+                })?;
+        if let Submission::Queued(_) = submission {
+            self.emit_verification_positions();
+        }
+        Ok(())
+    }
+
+    /// Spawns a verification once its class has a free slot; the slot is
+    /// checked first so ffmpeg is never started just to be turned away.
+    fn launch_verification(
+        &self,
+        job_id: &str,
+        verification: &QueuedVerification,
+    ) -> Result<(), AppError> {
+        let limit = self.verification_concurrency.get_limit();
         self.registry
-            .register(job_id.clone(), record, self.concurrency.get_limit())?;
+            .check_admission(job_id, false, JobClass::Verification, limit)?;
 
-        if let Ok(mut child_guard) = process.child.lock() {
-            if let Some(child) = child_guard.as_mut() {
-                child.stderr = stderr;
-            }
+        let ffmpeg_path = self.spawner.resolve_ffmpeg(&verification.app, None)?;
+        let args = verification::verification_args(&verification.path, verification.limit_sec);
+        let mut child = self.spawner.spawn_verification(ffmpeg_path, &args, "-")?;
+
+        let stderr = child.stderr.take();
+        let process = Arc::new(RunningProcess::new(child, false));
+        let record = JobRecord::new(Arc::clone(&process), PathBuf::new(), PathBuf::new(), false)
+            .with_class(JobClass::Verification);
+        if let Err(err) = self.registry.register(job_id.to_string(), record, limit) {
+            Self::abandon(&process);
+            return Err(err);
         }
 
-        let registry = Arc::clone(&self.registry);
-        ProgressMonitor::start(emitter, registry, job_id, process, final_path, temp_path);
+        if let Some(child) = process.child.lock_or_recover().as_mut() {
+            child.stderr = stderr;
+        }
 
+        let registry = Arc::clone(&self.registry);
+        let coordinator = self.clone();
+        VerificationMonitor::start(
+            Arc::clone(&verification.emitter),
+            registry,
+            job_id.to_string(),
+            process,
+            PathBuf::from(&verification.path),
+            verification.checksum,
+            move || coordinator.pump_queue(),
+        );
         Ok(())
     }
 
+    /// Starts queued verifications while verification slots are free.
+    /// Verifications that fail to launch are reported as failed.
+    fn pump_verifications(&self) {
+        let pumped = self
+            .verifications
+            .pump(|job_id, verification| self.launch_verification(job_id, verification));
+        if pumped.started.is_empty() && pumped.failed.is_empty() {
+            return;
+        }
+
+        for (job_id, verification, err) in pumped.failed {
+            verification
+                .emitter
+                .emit_verification(&VerificationPayload {
+                    job_id,
+                    path: verification.path,
+                    success: false,
+                    cancelled: false,
+                    exit_code: None,
+                    decode_errors: 0,
+                    errors: vec![err.message],
+                    checksum: None,
+                });
+        }
+        self.emit_verification_positions();
+    }
+
+    fn emit_verification_positions(&self) {
+        self.verifications
+            .for_each_position(|job_id, verification, position, queue_length| {
+                verification
+                    .emitter
+                    .emit_queue_position(&QueuePositionPayload {
+                        job_id: job_id.to_string(),
+                        position,
+                        queue_length,
+                    });
+            });
+    }
+
     pub fn cancel_job(&self, job_id: &str) -> Result<bool, AppError> {
+        self.queue.wait_for_launch(job_id);
+        self.verifications.wait_for_launch(job_id);
+        if self.verifications.remove(job_id).is_some() {
+            self.emit_verification_positions();
+            return Ok(true);
+        }
+        if self.queue.remove(job_id).is_some() {
+            self.store.remove(job_id);
+            self.settle(job_id, false);
+            self.emit_queue_positions();
+            return Ok(true);
+        }
+        let backing_off = self.backoff.lock_or_recover().remove(job_id);
+        if backing_off || self.graph.remove_waiting(job_id).is_some() {
+            self.store.remove(job_id);
+            self.settle(job_id, false);
+            return Ok(true);
+        }
+
         let Some(snapshot) = self.registry.snapshot(job_id) else {
             return Ok(false);
         };
+        let cancelled = self.cancel_running(job_id, &snapshot)?;
+        if cancelled {
+            self.pump_queue();
+        }
+        Ok(cancelled)
+    }
 
+    /// Asks a running job to quit so ffmpeg can flush its output, killing
+    /// it if it is still running after `CANCEL_GRACE_PERIOD`. A job that
+    /// can't be asked is killed right away.
+    fn cancel_running(&self, job_id: &str, snapshot: &JobSnapshot) -> Result<bool, AppError> {
         snapshot.process.mark_cancelled();
-        let mut child_guard = snapshot.process.child.lock().expect("child mutex poisoned");
-        if let Some(child) = child_guard.as_mut() {
-            child.kill().map_err(|err| {
-                AppError::new(
-                    "job_cancel_failed",
-                    format!("Failed to cancel job {job_id}: {err}"),
-                )
-            })?;
-            OutputManager::cleanup_temp(&snapshot.temp_path);
-            self.registry.remove(job_id);
+        let mut child_guard = snapshot.process.child.lock_or_recover();
+        let Some(child) = child_guard.as_mut() else {
+            return Ok(false);
+        };
+        if snapshot.process.is_paused() {
+            // A stopped process can't read the request.
+            let _ = ProcessSpawner::signal_tree(child, false);
+        }
+        if ProcessSpawner::request_quit(child).is_ok() {
+            // The monitor cleans up and frees the slot once ffmpeg exits.
+            drop(child_guard);
+            kill_after_grace(
+                job_id.to_string(),
+                Arc::clone(&snapshot.process),
+                CANCEL_GRACE_PERIOD,
+            );
             return Ok(true);
         }
 
-        Ok(false)
+        ProcessSpawner::kill_tree(child).map_err(|err| {
+            AppError::new(
+                "job_cancel_failed",
+                format!("Failed to cancel job {job_id}: {err}"),
+            )
+        })?;
+        if !snapshot.temp_path.as_os_str().is_empty() {
+            OutputManager::cleanup_temp(&snapshot.temp_path);
+        }
+        self.registry.remove(job_id);
+        Ok(true)
+    }
+
+    /// Cancels every job: waiting, queued and backing-off jobs are dropped
+    /// first so none of them starts in a freed slot, then every running
+    /// process is killed.
+    pub fn cancel_all_jobs(&self) -> Vec<JobActionResult> {
+        let mut pending: Vec<String> = self
+            .graph
+            .drain_waiting()
+            .into_iter()
+            .chain(self.queue.drain())
+            .map(|(job_id, _)| job_id)
+            .collect();
+        pending.extend(self.backoff.lock_or_recover().drain());
+
+        let mut results = Vec::new();
+        for job_id in pending {
+            self.store.remove(&job_id);
+            self.settle(&job_id, false);
+            results.push(JobActionResult::from_outcome(job_id, Ok(true)));
+        }
+        for (job_id, _) in self.verifications.drain() {
+            results.push(JobActionResult::from_outcome(job_id, Ok(true)));
+        }
+        for (job_id, snapshot) in self.registry.snapshot_all() {
+            let outcome = self.cancel_running(&job_id, &snapshot);
+            results.push(JobActionResult::from_outcome(job_id, outcome));
+        }
+        results
+    }
+
+    /// Running jobs that quitting would interrupt; none once shut down.
+    pub fn active_job_count(&self) -> usize {
+        if self.shut_down.load(Ordering::SeqCst) {
+            return 0;
+        }
+        self.registry.active_count()
+    }
+
+    /// Kills every running process tree as the app exits and removes their
+    /// partial output. The job store is frozen first, so running and queued
+    /// jobs are offered for restore next launch instead of being recorded
+    /// as failed. Later calls do nothing.
+    pub fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.store.freeze();
+        for (job_id, snapshot) in self.registry.snapshot_all() {
+            snapshot.process.mark_cancelled();
+            for helper in snapshot.process.helpers.lock_or_recover().iter_mut() {
+                let _ = ProcessSpawner::kill_tree(helper);
+            }
+            if let Some(child) = snapshot.process.child.lock_or_recover().as_mut() {
+                if let Err(err) = ProcessSpawner::kill_tree(child) {
+                    eprintln!("[jobs] failed killing job {job_id} on exit: {err}");
+                }
+                let _ = child.wait();
+            }
+            if !snapshot.temp_path.as_os_str().is_empty() {
+                OutputManager::cleanup_temp(&snapshot.temp_path);
+            }
+        }
+    }
+
+    /// Suspends every running process. Paused jobs keep their slots.
+    pub fn pause_all_jobs(&self) -> Vec<JobActionResult> {
+        self.signal_all(true)
+    }
+
+    pub fn resume_all_jobs(&self) -> Vec<JobActionResult> {
+        self.signal_all(false)
+    }
+
+    fn signal_all(&self, pause: bool) -> Vec<JobActionResult> {
+        self.registry
+            .snapshot_all()
+            .into_iter()
+            .map(|(job_id, snapshot)| {
+                let outcome = snapshot.process.set_paused(pause).map_err(|err| {
+                    let action = if pause { "pause" } else { "resume" };
+                    AppError::new(
+                        "job_pause_failed",
+                        format!("Failed to {action} job {job_id}: {err}"),
+                    )
+                });
+                JobActionResult::from_outcome(job_id, outcome)
+            })
+            .collect()
+    }
+
+    /// Returns the jobs interrupted by the last quit or crash, starting
+    /// them again from scratch when `resume` is set.
+    pub fn restore_jobs(
+        &self,
+        app: AppHandle,
+        emitter: SharedEmitter,
+        resume: bool,
+    ) -> Vec<RestoredJob> {
+        self.store.attach(&app);
+        self.store
+            .take_restorable()
+            .into_iter()
+            .map(|job| {
+                let error = if resume {
+                    self.start_job(
+                        app.clone(),
+                        emitter.clone(),
+                        job.job_id.clone(),
+                        job.args
+                            .iter()
+                            .cloned()
+                            .map(NativePath::into_os_string)
+                            .collect(),
+                        job.output_path.clone().into_path_buf(),
+                        JobOptions {
+                            exclusive: job.exclusive,
+                            low_priority: job.low_priority,
+                            depends_on: job.depends_on.clone(),
+                            overwrite: job.overwrite,
+                            estimated_size: job.estimated_size,
+                            preset_id: job.preset_id.clone(),
+                            duration_sec: job.duration_sec,
+                            segmented: job.segmented,
+                            threads: job.threads,
+                            trash_source: job.trash_source,
+                            preserve_timestamps: job.preserve_timestamps,
+                            metadata: job.metadata.clone(),
+                            structured: job.structured,
+                            ffmpeg_installation: job.ffmpeg_installation.clone(),
+                        },
+                    )
+                    .err()
+                } else {
+                    None
+                };
+                RestoredJob {
+                    resumed: resume && error.is_none(),
+                    job,
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// Finished jobs, newest first.
+    pub fn history(&self, app: &AppHandle) -> Vec<HistoryEntry> {
+        self.history.attach(app);
+        self.history.entries()
+    }
+
+    pub fn clear_history(&self, app: &AppHandle) {
+        self.history.attach(app);
+        self.history.clear();
     }
 
     pub fn set_max_concurrency(&self, limit: usize) {
         self.concurrency.set_limit(limit);
+        self.pump_queue();
+    }
+
+    pub fn set_verification_concurrency(&self, limit: usize) {
+        self.verification_concurrency.set_limit(limit);
+        self.pump_verifications();
+    }
+
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.retry.set(policy);
+    }
+
+    /// Loads the saved post-job hooks; later changes are saved there.
+    pub fn load_post_job_hooks(&self, app: &AppHandle) {
+        self.hooks.attach(app);
+    }
+
+    /// Replaces and saves the hooks run after each job's final outcome.
+    pub fn set_post_job_hooks(&self, hooks: Vec<PostJobHook>) {
+        self.hooks.set(hooks);
+    }
+
+    /// Default for jobs that don't choose a priority themselves.
+    pub fn set_low_priority(&self, enabled: bool) {
+        self.low_priority.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Default encoder thread limit for jobs that don't set one; `None`
+    /// lets ffmpeg decide.
+    pub fn set_default_threads(&self, threads: Option<u32>) {
+        self.threads.store(threads.unwrap_or(0), Ordering::SeqCst);
+    }
+
+    /// Sets what the queue does on battery power, watching the power
+    /// source for as long as a policy other than `Ignore` is set.
+    pub fn set_battery_policy(&self, policy: BatteryPolicy, emitter: SharedEmitter) {
+        self.concurrency.set_battery_policy(policy);
+        if policy == BatteryPolicy::Ignore {
+            self.apply_power_source(PowerSource::Ac, &emitter);
+            return;
+        }
+        if self.battery_watch.swap(true, Ordering::SeqCst) {
+            self.apply_power_source(battery::power_source(), &emitter);
+            return;
+        }
+        let coordinator = self.clone();
+        std::thread::spawn(move || loop {
+            if coordinator.concurrency.battery_policy() == BatteryPolicy::Ignore {
+                coordinator.battery_watch.store(false, Ordering::SeqCst);
+                return;
+            }
+            coordinator.apply_power_source(battery::power_source(), &emitter);
+            std::thread::sleep(BATTERY_POLL_INTERVAL);
+        });
+    }
+
+    /// Holds or releases the queue for `source` under the current policy.
+    fn apply_power_source(&self, source: PowerSource, emitter: &SharedEmitter) {
+        let policy = self.concurrency.battery_policy();
+        let suspend = source == PowerSource::Battery && policy != BatteryPolicy::Ignore;
+        self.set_battery_paused(suspend && policy == BatteryPolicy::Pause);
+
+        if !self.concurrency.set_suspended(suspend) {
+            return;
+        }
+        emitter.emit_queue_suspended(&QueueSuspendedPayload {
+            suspended: suspend,
+            policy,
+        });
+        if !suspend {
+            self.pump_queue();
+        }
+    }
+
+    /// Suspends running jobs that aren't paused yet, or resumes the ones
+    /// suspended this way. Jobs the user paused are left alone.
+    fn set_battery_paused(&self, pause: bool) {
+        let mut paused = self.battery_paused.lock_or_recover();
+        if pause {
+            for (job_id, snapshot) in self.registry.snapshot_all() {
+                if snapshot.process.is_paused() || paused.contains(&job_id) {
+                    continue;
+                }
+                match snapshot.process.set_paused(true) {
+                    Ok(true) => paused.push(job_id),
+                    Ok(false) => {},
+                    Err(err) => eprintln!("[jobs] failed pausing job {job_id} on battery: {err}"),
+                }
+            }
+            return;
+        }
+        for job_id in paused.drain(..) {
+            let Some(snapshot) = self.registry.snapshot(&job_id) else {
+                continue;
+            };
+            if let Err(err) = snapshot.process.set_paused(false) {
+                eprintln!("[jobs] failed resuming job {job_id}: {err}");
+            }
+        }
+    }
+
+    /// Whether running jobs keep the system from sleeping.
+    pub fn set_prevent_sleep(&self, enabled: bool) {
+        self.inhibitor.set_enabled(enabled);
+    }
+
+    /// Applies to conversions started from now on.
+    pub fn set_stall_timeout(&self, timeout: Option<Duration>) {
+        self.stall_timeout.set(timeout);
     }
 }
 
+/// Kills `process` if it is still running once `grace` has passed.
+/// A verification waiting for a verification slot.
+struct QueuedVerification {
+    app: AppHandle,
+    emitter: SharedEmitter,
+    path: String,
+    checksum: bool,
+    limit_sec: Option<f64>,
+}
+
+/// A conversion waiting for a slot, with what it needs to launch later.
+#[derive(Clone)]
+struct QueuedJob {
+    app: AppHandle,
+    emitter: SharedEmitter,
+    args: Vec<OsString>,
+    output_path: PathBuf,
+    exclusive: bool,
+    low_priority: Option<bool>,
+    depends_on: Vec<String>,
+    overwrite: OverwritePolicy,
+    estimated_size: Option<u64>,
+    preset_id: Option<String>,
+    duration_sec: Option<f64>,
+    segmented: bool,
+    threads: Option<u32>,
+    trash_source: bool,
+    preserve_timestamps: bool,
+    metadata: JobMetadata,
+    structured: bool,
+    ffmpeg_installation: Option<String>,
+    /// Set once the job was restarted with a software encoder.
+    fell_back_to_software: bool,
+    /// Retries made so far.
+    attempt: u32,
+}
+
+impl QueuedJob {
+    /// The local file the job reads; `None` for network sources.
+    fn source_path(&self) -> Option<&Path> {
+        output_check::input_path(&self.args)
+            .filter(|input| !output_check::is_network_source(input))
+            .map(Path::new)
+    }
+
+    fn persisted(&self, job_id: &str) -> PersistedJob {
+        PersistedJob {
+            low_priority: self.low_priority,
+            depends_on: self.depends_on.clone(),
+            overwrite: self.overwrite,
+            estimated_size: self.estimated_size,
+            preset_id: self.preset_id.clone(),
+            duration_sec: self.duration_sec,
+            segmented: self.segmented,
+            threads: self.threads,
+            trash_source: self.trash_source,
+            preserve_timestamps: self.preserve_timestamps,
+            metadata: self.metadata.clone(),
+            structured: self.structured,
+            ffmpeg_installation: self.ffmpeg_installation.clone(),
+            ..PersistedJob::new(job_id, &self.args, &self.output_path, self.exclusive)
+        }
+    }
+}
+
+/// How a conversion runs, beyond its arguments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobOptions {
+    pub exclusive: bool,
+    /// Run ffmpeg at background priority; `None` follows the global
+    /// preference.
+    pub low_priority: Option<bool>,
+    /// Jobs that must succeed before this one starts.
+    pub depends_on: Vec<String>,
+    /// What to do when the output file already exists.
+    pub overwrite: OverwritePolicy,
+    /// Expected output size in bytes, checked against free space before
+    /// ffmpeg starts.
+    pub estimated_size: Option<u64>,
+    /// Preset and source duration, recorded in the history.
+    pub preset_id: Option<String>,
+    pub duration_sec: Option<f64>,
+    /// Encode in parallel segments when the source qualifies.
+    pub segmented: bool,
+    /// Encoder thread limit; `None` follows the global default.
+    pub threads: Option<u32>,
+    /// Move the source to the trash after a verified success.
+    pub trash_source: bool,
+    /// Copy the source's timestamps onto the finalized output.
+    pub preserve_timestamps: bool,
+    /// Caller labels echoed in the job's progress and completion events.
+    pub metadata: JobMetadata,
+    /// `args` were serialized from a typed `FfmpegCommand`.
+    pub structured: bool,
+    /// Registered FFmpeg installation to run instead of the preferred one.
+    pub ffmpeg_installation: Option<String>,
+}
+
+/// Outcome of a bulk action for one job.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobActionResult {
+    pub job_id: String,
+    /// Whether the action took effect; false when the job had already
+    /// finished or the action failed.
+    pub success: bool,
+    pub error: Option<AppError>,
+}
+
+impl JobActionResult {
+    fn from_outcome(job_id: String, outcome: Result<bool, AppError>) -> Self {
+        match outcome {
+            Ok(success) => Self {
+                job_id,
+                success,
+                error: None,
+            },
+            Err(err) => Self {
+                job_id,
+                success: false,
+                error: Some(err),
+            },
+        }
+    }
+}
+
+/// A job found in the store at launch, and whether it was started again.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredJob {
+    #[serde(flatten)]
+    pub job: PersistedJob,
+    pub resumed: bool,
+    pub error: Option<AppError>,
+}
+
+/// Opens the persistent log for a job under the app log directory, falling
+/// back to an in-memory log when the directory is unavailable.
+fn job_log(app: &AppHandle, job_id: &str) -> JobLog {
+    LogSink::for_app(app)
+        .map(|sink| sink.open(job_id))
+        .unwrap_or_else(|_| JobLog::in_memory())
+}
+
+/// `args` as text, for the passes that rewrite arguments; `None` when a
+/// path in them isn't UTF-8.
+fn utf8_args(args: &[OsString]) -> Option<Vec<String>> {
+    args.iter()
+        .map(|arg| arg.to_str().map(str::to_string))
+        .collect()
+}
+
 impl Clone for JobCoordinator {
     fn clone(&self) -> Self {
         Self {
             registry: Arc::clone(&self.registry),
+            store: Arc::clone(&self.store),
+            history: Arc::clone(&self.history),
+            queue: Arc::clone(&self.queue),
+            verifications: Arc::clone(&self.verifications),
+            graph: Arc::clone(&self.graph),
+            backoff: Arc::clone(&self.backoff),
+            retry: self.retry.clone(),
+            hooks: self.hooks.clone(),
+            stall_timeout: self.stall_timeout.clone(),
+            low_priority: Arc::clone(&self.low_priority),
+            threads: Arc::clone(&self.threads),
+            inhibitor: Arc::clone(&self.inhibitor),
+            battery_watch: Arc::clone(&self.battery_watch),
+            battery_paused: Arc::clone(&self.battery_paused),
             concurrency: self.concurrency.clone(),
+            verification_concurrency: self.verification_concurrency.clone(),
             spawner: Arc::clone(&self.spawner),
+            shut_down: Arc::clone(&self.shut_down),
         }
     }
 }
@@ -151,6 +1337,133 @@ mod tests {
         assert!(!temp_path.exists(), "temp file should be cleaned");
     }
 
+    #[test]
+    fn shutdown_kills_running_processes() {
+        let coordinator = JobCoordinator::with_spawner(Arc::new(DefaultSpawnController));
+        let process = sleeping_process();
+        coordinator
+            .registry
+            .register(
+                "job".into(),
+                JobRecord::new(Arc::clone(&process), PathBuf::new(), PathBuf::new(), false),
+                10,
+            )
+            .unwrap();
+        assert_eq!(coordinator.active_job_count(), 1);
+
+        coordinator.shutdown();
+        assert_eq!(coordinator.active_job_count(), 0);
+        assert!(process.is_cancelled());
+        let mut child_guard = process.child.lock().unwrap();
+        let status = child_guard.as_mut().unwrap().wait().unwrap();
+        assert!(!status.success());
+    }
+
+    /// A process with piped stdin running `script`, like a conversion.
+    fn interactive_process(script: &str) -> Arc<RunningProcess> {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn shell");
+        Arc::new(RunningProcess::new(child, false))
+    }
+
+    #[test]
+    fn cancel_asks_the_process_to_quit_first() {
+        let coordinator = JobCoordinator::with_spawner(Arc::new(DefaultSpawnController));
+        let process = interactive_process("head -c 1 >/dev/null; exit 0");
+        coordinator
+            .registry
+            .register(
+                "job".into(),
+                JobRecord::new(Arc::clone(&process), PathBuf::new(), PathBuf::new(), false),
+                10,
+            )
+            .unwrap();
+
+        assert!(coordinator.cancel_job("job").expect("cancel call"));
+        // The slot stays taken until the monitor sees the process exit.
+        assert!(coordinator.registry.snapshot("job").is_some());
+        let status = process.child.lock().unwrap().as_mut().unwrap().wait();
+        assert!(status.unwrap().success(), "process should quit on its own");
+    }
+
+    #[test]
+    fn processes_ignoring_quit_are_killed_after_the_grace_period() {
+        let process = interactive_process("sleep 5");
+        ProcessSpawner::request_quit(process.child.lock().unwrap().as_mut().unwrap()).unwrap();
+
+        kill_after_grace(
+            "job".into(),
+            Arc::clone(&process),
+            Duration::from_millis(100),
+        );
+        std::thread::sleep(Duration::from_millis(500));
+
+        let status = process.child.lock().unwrap().as_mut().unwrap().wait();
+        assert!(!status.unwrap().success());
+    }
+
+    #[test]
+    fn bulk_actions_cover_every_running_job() {
+        let coordinator = JobCoordinator::with_spawner(Arc::new(DefaultSpawnController));
+        for job_id in ["a", "b"] {
+            coordinator
+                .registry
+                .register(
+                    job_id.into(),
+                    JobRecord::new(sleeping_process(), PathBuf::new(), PathBuf::new(), false),
+                    10,
+                )
+                .unwrap();
+        }
+
+        let paused = coordinator.pause_all_jobs();
+        assert_eq!(paused.len(), 2);
+        assert!(paused.iter().all(|result| result.success));
+        let process = coordinator.registry.snapshot("a").unwrap().process;
+        assert!(process.is_paused());
+
+        let cancelled = coordinator.cancel_all_jobs();
+        let ids: Vec<&str> = cancelled
+            .iter()
+            .map(|result| result.job_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert!(cancelled.iter().all(|result| result.success));
+        assert!(coordinator.registry.snapshot_all().is_empty());
+    }
+
+    #[test]
+    fn battery_pause_spares_jobs_the_user_paused() {
+        let coordinator = JobCoordinator::with_spawner(Arc::new(DefaultSpawnController));
+        for job_id in ["auto", "manual"] {
+            coordinator
+                .registry
+                .register(
+                    job_id.into(),
+                    JobRecord::new(sleeping_process(), PathBuf::new(), PathBuf::new(), false),
+                    10,
+                )
+                .unwrap();
+        }
+        let process = |job_id| coordinator.registry.snapshot(job_id).unwrap().process;
+        process("manual").set_paused(true).unwrap();
+
+        coordinator.set_battery_paused(true);
+        assert!(process("auto").is_paused());
+        assert_eq!(*coordinator.battery_paused.lock().unwrap(), vec!["auto"]);
+
+        coordinator.set_battery_paused(false);
+        assert!(!process("auto").is_paused());
+        assert!(process("manual").is_paused());
+        coordinator.cancel_all_jobs();
+    }
+
     #[test]
     fn cancel_job_returns_false_for_unknown_id() {
         let coordinator = JobCoordinator::with_spawner(Arc::new(DefaultSpawnController));
@@ -0,0 +1,102 @@
+//! Free-space preflight for conversions.
+//!
+//! A conversion that runs out of disk dies mid-encode after doing most of
+//! its work. When the expected output size is known, the coordinator checks
+//! the output volume before spawning ffmpeg and fails fast with
+//! `job_disk_space` instead.
+
+use std::path::Path;
+
+use crate::error::AppError;
+
+pub const JOB_DISK_SPACE: &str = "job_disk_space";
+
+/// Extra room required on top of the estimate, since bitrates are targets
+/// rather than limits and containers add overhead.
+const HEADROOM_PERCENT: u64 = 10;
+const MIN_HEADROOM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Fails when the volume holding `output` has less room than `estimated`
+/// bytes plus headroom. Volumes whose free space can't be read pass.
+pub fn ensure_space(output: &Path, estimated: u64) -> Result<(), AppError> {
+    let directory = output
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let Some(available) = available_bytes(directory) else {
+        return Ok(());
+    };
+
+    let required = required_bytes(estimated);
+    if available >= required {
+        return Ok(());
+    }
+    Err(AppError::new(
+        JOB_DISK_SPACE,
+        format!(
+            "Not enough free space in {}: about {} MB needed, {} MB available.",
+            directory.display(),
+            required.div_ceil(1_000_000),
+            available / 1_000_000
+        ),
+    ))
+}
+
+fn required_bytes(estimated: u64) -> u64 {
+    let headroom = (estimated / 100)
+        .saturating_mul(HEADROOM_PERCENT)
+        .max(MIN_HEADROOM_BYTES);
+    estimated.saturating_add(headroom)
+}
+
+/// Bytes available to unprivileged users on the volume holding `directory`.
+#[cfg(unix)]
+fn available_bytes(directory: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(directory.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` points to
+    // writable memory large enough for a `statvfs`.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: `statvfs` returned 0, so it initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+    Some(widen(stat.f_bavail).saturating_mul(widen(stat.f_frsize)))
+}
+
+/// `statvfs` field widths differ between platforms.
+#[cfg(unix)]
+fn widen(value: impl Into<u64>) -> u64 {
+    value.into()
+}
+
+#[cfg(not(unix))]
+fn available_bytes(_directory: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headroom_scales_with_the_estimate() {
+        assert_eq!(required_bytes(0), MIN_HEADROOM_BYTES);
+        assert_eq!(required_bytes(10_000_000_000), 11_000_000_000);
+        assert_eq!(required_bytes(u64::MAX), u64::MAX);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn outputs_larger_than_the_volume_fail_fast() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out.mp4");
+
+        assert!(ensure_space(&output, 1_000).is_ok());
+        let err = ensure_space(&output, u64::MAX / 2).unwrap_err();
+        assert_eq!(err.code, JOB_DISK_SPACE);
+    }
+}
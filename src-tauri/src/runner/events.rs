@@ -1,33 +1,125 @@
-use serde::Serialize;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
+use super::{battery::BatteryPolicy, severity, triage::Remediation};
+use crate::api::{JobMetadata, NativePath};
+use crate::sync_utils::MutexExt;
+
 /// Event IDs emitted to the frontend.
 pub const PROGRESS_EVENT: &str = "ffmpeg://progress";
 pub const COMPLETION_EVENT: &str = "ffmpeg://completion";
 pub const STDERR_EVENT: &str = "ffmpeg://stderr";
+pub const VERIFICATION_EVENT: &str = "verify://completion";
+pub const QUEUE_EVENT: &str = "ffmpeg://queue";
+pub const RETRYING_EVENT: &str = "ffmpeg://retrying";
+pub const QUEUE_SUSPENDED_EVENT: &str = "ffmpeg://queue-suspended";
+/// Compact variants of the high-frequency events.
+pub const COMPACT_PROGRESS_EVENT: &str = "ffmpeg://progress-compact";
+pub const COMPACT_STDERR_EVENT: &str = "ffmpeg://stderr-compact";
+
+/// Wire format for progress and stderr events.
+///
+/// `Compact` sends positional arrays instead of keyed objects:
+/// progress is `[jobId, processedSeconds, fps, speed]` and is only sent when
+/// ffmpeg reported metrics (the raw line already travels as stderr), and
+/// stderr is `[jobId, line, severity]`. Completion events are rare and stay
+/// JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventEncoding {
+    #[default]
+    Json,
+    Compact,
+}
+
+/// Encoding chosen by the frontend at startup, shared by every emitter.
+#[derive(Debug, Clone, Default)]
+pub struct EncodingSetting(Arc<AtomicBool>);
+
+impl EncodingSetting {
+    /// Picks the first supported encoding from the frontend's preference
+    /// list, falling back to JSON.
+    pub fn negotiate(&self, preferred: &[EventEncoding]) -> EventEncoding {
+        let encoding = preferred.first().copied().unwrap_or_default();
+        self.0
+            .store(encoding == EventEncoding::Compact, Ordering::SeqCst);
+        encoding
+    }
+
+    pub fn get(&self) -> EventEncoding {
+        if self.0.load(Ordering::SeqCst) {
+            EventEncoding::Compact
+        } else {
+            EventEncoding::Json
+        }
+    }
+}
+
+/// Default minimum time between progress events of one job.
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Minimum progress event interval shared by every emitter; zero sends
+/// every event.
+#[derive(Debug, Clone)]
+pub struct ProgressInterval(Arc<AtomicU64>);
+
+impl Default for ProgressInterval {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU64::new(
+            DEFAULT_PROGRESS_INTERVAL.as_millis() as u64,
+        )))
+    }
+}
+
+impl ProgressInterval {
+    pub fn get(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+
+    pub fn set(&self, interval: Duration) {
+        self.0.store(interval.as_millis() as u64, Ordering::SeqCst);
+    }
+}
 
 /// Abstraction over event emission to decouple process monitoring from Tauri.
 pub trait ProgressEmitter: Send + Sync {
     fn emit_progress(&self, payload: &ProgressPayload);
     fn emit_completion(&self, payload: &CompletionPayload);
     fn emit_stderr(&self, job_id: &str, line: &str);
+    fn emit_verification(&self, payload: &VerificationPayload);
+    fn emit_queue_position(&self, payload: &QueuePositionPayload);
+    fn emit_retrying(&self, payload: &RetryingPayload);
+    fn emit_queue_suspended(&self, payload: &QueueSuspendedPayload);
 }
 
 /// Concrete emitter that forwards events to the Tauri frontend.
 pub struct TauriEmitter {
     app: AppHandle,
+    encoding: EventEncoding,
 }
 
 impl TauriEmitter {
-    pub fn new(app: AppHandle) -> Self {
-        Self { app }
+    pub fn new(app: AppHandle, encoding: EventEncoding) -> Self {
+        Self { app, encoding }
     }
 }
 
 impl ProgressEmitter for TauriEmitter {
     fn emit_progress(&self, payload: &ProgressPayload) {
-        let _ = self.app.emit(PROGRESS_EVENT, payload);
+        match self.encoding {
+            EventEncoding::Json => {
+                let _ = self.app.emit(PROGRESS_EVENT, payload);
+            },
+            EventEncoding::Compact => {
+                if let Some(compact) = payload.compact() {
+                    let _ = self.app.emit(COMPACT_PROGRESS_EVENT, compact);
+                }
+            },
+        }
     }
 
     fn emit_completion(&self, payload: &CompletionPayload) {
@@ -35,32 +127,221 @@ impl ProgressEmitter for TauriEmitter {
     }
 
     fn emit_stderr(&self, job_id: &str, line: &str) {
+        let severity = severity::classify(line);
+        if self.encoding == EventEncoding::Compact {
+            let _ = self
+                .app
+                .emit(COMPACT_STDERR_EVENT, (job_id, line, severity));
+            return;
+        }
+
         let _ = self.app.emit(
             STDERR_EVENT,
             serde_json::json!({
                 "jobId": job_id,
                 "line": line,
+                "severity": severity,
             }),
         );
     }
+
+    fn emit_verification(&self, payload: &VerificationPayload) {
+        let _ = self.app.emit(VERIFICATION_EVENT, payload);
+    }
+
+    fn emit_queue_position(&self, payload: &QueuePositionPayload) {
+        let _ = self.app.emit(QUEUE_EVENT, payload);
+    }
+
+    fn emit_retrying(&self, payload: &RetryingPayload) {
+        let _ = self.app.emit(RETRYING_EVENT, payload);
+    }
+
+    fn emit_queue_suspended(&self, payload: &QueueSuspendedPayload) {
+        let _ = self.app.emit(QUEUE_SUSPENDED_EVENT, payload);
+    }
+}
+
+/// Progress throttle state of one job.
+struct Throttle {
+    last_sent: Instant,
+    /// Newest event held back since `last_sent`.
+    pending: Option<ProgressPayload>,
+}
+
+/// Coalesces progress events so each job sends at most one per interval.
+/// The newest held-back event is sent with the next one due, and flushed
+/// before the job's completion or retry so the final progress is never
+/// lost.
+pub struct ThrottledEmitter {
+    inner: SharedEmitter,
+    interval: ProgressInterval,
+    jobs: Mutex<HashMap<String, Throttle>>,
+}
+
+impl ThrottledEmitter {
+    pub fn new(inner: SharedEmitter, interval: ProgressInterval) -> Self {
+        Self {
+            inner,
+            interval,
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sends the event held back for `job_id`, if any, and forgets the job.
+    fn flush(&self, job_id: &str) {
+        let pending = self
+            .jobs
+            .lock_or_recover()
+            .remove(job_id)
+            .and_then(|throttle| throttle.pending);
+        if let Some(payload) = pending {
+            self.inner.emit_progress(&payload);
+        }
+    }
+}
+
+impl ProgressEmitter for ThrottledEmitter {
+    fn emit_progress(&self, payload: &ProgressPayload) {
+        let interval = self.interval.get();
+        if interval.is_zero() {
+            self.inner.emit_progress(payload);
+            return;
+        }
+        let mut jobs = self.jobs.lock_or_recover();
+        let now = Instant::now();
+        match jobs.get_mut(&payload.job_id) {
+            Some(throttle) if now.duration_since(throttle.last_sent) < interval => {
+                throttle.pending = Some(payload.clone());
+            },
+            _ => {
+                jobs.insert(
+                    payload.job_id.clone(),
+                    Throttle {
+                        last_sent: now,
+                        pending: None,
+                    },
+                );
+                drop(jobs);
+                self.inner.emit_progress(payload);
+            },
+        }
+    }
+
+    fn emit_completion(&self, payload: &CompletionPayload) {
+        self.flush(&payload.job_id);
+        self.inner.emit_completion(payload);
+    }
+
+    fn emit_stderr(&self, job_id: &str, line: &str) {
+        self.inner.emit_stderr(job_id, line);
+    }
+
+    fn emit_verification(&self, payload: &VerificationPayload) {
+        self.inner.emit_verification(payload);
+    }
+
+    fn emit_queue_position(&self, payload: &QueuePositionPayload) {
+        self.inner.emit_queue_position(payload);
+    }
+
+    fn emit_retrying(&self, payload: &RetryingPayload) {
+        self.flush(&payload.job_id);
+        self.inner.emit_retrying(payload);
+    }
+
+    fn emit_queue_suspended(&self, payload: &QueueSuspendedPayload) {
+        self.inner.emit_queue_suspended(payload);
+    }
+}
+
+/// Stamps a job's metadata onto its progress and completion events.
+pub struct TaggedEmitter {
+    inner: SharedEmitter,
+    metadata: JobMetadata,
+}
+
+impl TaggedEmitter {
+    pub fn new(inner: SharedEmitter, metadata: JobMetadata) -> Self {
+        Self { inner, metadata }
+    }
+}
+
+impl ProgressEmitter for TaggedEmitter {
+    fn emit_progress(&self, payload: &ProgressPayload) {
+        self.inner.emit_progress(&ProgressPayload {
+            metadata: self.metadata.clone(),
+            ..payload.clone()
+        });
+    }
+
+    fn emit_completion(&self, payload: &CompletionPayload) {
+        self.inner.emit_completion(&CompletionPayload {
+            metadata: self.metadata.clone(),
+            ..payload.clone()
+        });
+    }
+
+    fn emit_stderr(&self, job_id: &str, line: &str) {
+        self.inner.emit_stderr(job_id, line);
+    }
+
+    fn emit_verification(&self, payload: &VerificationPayload) {
+        self.inner.emit_verification(payload);
+    }
+
+    fn emit_queue_position(&self, payload: &QueuePositionPayload) {
+        self.inner.emit_queue_position(payload);
+    }
+
+    fn emit_retrying(&self, payload: &RetryingPayload) {
+        self.inner.emit_retrying(payload);
+    }
+
+    fn emit_queue_suspended(&self, payload: &QueueSuspendedPayload) {
+        self.inner.emit_queue_suspended(payload);
+    }
 }
 
 /// Parsed progress metrics extracted from FFmpeg output.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressMetrics {
     pub processed_seconds: Option<f64>,
     pub fps: Option<f64>,
     pub speed: Option<f64>,
+    /// Bytes written to the output so far.
+    pub total_size: Option<u64>,
+    /// Current output bitrate in kbit/s.
+    pub bitrate_kbps: Option<f64>,
 }
 
 /// Payload for progress update events.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressPayload {
     pub job_id: String,
     pub progress: Option<ProgressMetrics>,
     pub raw: String,
+    /// The job's caller labels; not part of the compact form.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: JobMetadata,
+}
+
+/// Positional progress tuple sent in compact mode.
+pub type CompactProgress<'a> = (&'a str, Option<f64>, Option<f64>, Option<f64>);
+
+impl ProgressPayload {
+    /// Compact form, or `None` when the line carried no metrics.
+    pub fn compact(&self) -> Option<CompactProgress<'_>> {
+        let progress = self.progress.as_ref()?;
+        Some((
+            &self.job_id,
+            progress.processed_seconds,
+            progress.fps,
+            progress.speed,
+        ))
+    }
 }
 
 /// Payload for job completion events.
@@ -75,7 +356,217 @@ pub struct CompletionPayload {
     pub code: String,
     pub message: Option<String>,
     pub logs: Vec<String>,
+    /// Full log file for the job, when one could be created.
+    pub log_path: Option<String>,
+    /// Retry suggestions derived from the logs of a failed job.
+    pub suggestions: Vec<Remediation>,
+    /// Where a successful job's output ended up, which differs from the
+    /// requested path when it was renamed to avoid an existing file.
+    pub output_path: Option<NativePath>,
+    /// Whether the finished output was probed and matched its source.
+    pub verified: bool,
+    /// Whether the job was retried with a software encoder after its
+    /// hardware encoder failed.
+    pub fell_back_to_software: bool,
+    /// Source moved to the trash after the job succeeded.
+    pub trashed_source: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: JobMetadata,
+}
+
+/// Payload emitted when a verification-only job finishes.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationPayload {
+    pub job_id: String,
+    pub path: String,
+    pub success: bool,
+    pub cancelled: bool,
+    pub exit_code: Option<i32>,
+    /// Number of decode errors ffmpeg reported.
+    pub decode_errors: usize,
+    /// Most recent error lines (capped like job logs).
+    pub errors: Vec<String>,
+    /// Hex SHA-256 of the file when a checksum was requested.
+    pub checksum: Option<String>,
+}
+
+/// Position of a job waiting for a free slot. `position` is 1-based; a
+/// job that leaves the queue to start is reported once with position 0.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuePositionPayload {
+    pub job_id: String,
+    pub position: usize,
+    pub queue_length: usize,
+}
+
+/// Payload emitted when a failed job will be started again.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryingPayload {
+    pub job_id: String,
+    /// 1-based retry number.
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay_ms: u64,
+    /// Failure that triggered the retry.
+    pub code: String,
+    pub message: Option<String>,
+}
+
+/// Payload emitted when the battery policy holds or releases the queue.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSuspendedPayload {
+    pub suspended: bool,
+    pub policy: BatteryPolicy,
 }
 
 /// Shared alias for trait objects.
 pub type SharedEmitter = Arc<dyn ProgressEmitter>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> ProgressPayload {
+        ProgressPayload {
+            job_id: "6f1d2c1e-2c4b-4a55-9d0e-0c7f4d3e2a10".into(),
+            progress: Some(ProgressMetrics {
+                processed_seconds: Some(1234.56),
+                fps: Some(59.94),
+                speed: Some(2.5),
+                ..ProgressMetrics::default()
+            }),
+            raw: "frame=74000 fps=59.94 q=28.0 size=  102400kB time=00:20:34.56 bitrate=679.4kbits/s speed=2.5x".into(),
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn compact_progress_is_positional_and_smaller() {
+        let payload = payload();
+        let json = serde_json::to_string(&payload).unwrap();
+        let compact = serde_json::to_string(&payload.compact().unwrap()).unwrap();
+
+        assert_eq!(
+            compact,
+            "[\"6f1d2c1e-2c4b-4a55-9d0e-0c7f4d3e2a10\",1234.56,59.94,2.5]"
+        );
+        assert!(compact.len() * 3 < json.len());
+    }
+
+    #[test]
+    fn lines_without_metrics_have_no_compact_progress() {
+        let mut payload = payload();
+        payload.progress = None;
+        assert!(payload.compact().is_none());
+    }
+
+    #[derive(Default)]
+    struct RecordingEmitter {
+        events: Mutex<Vec<String>>,
+        metadata: Mutex<Vec<JobMetadata>>,
+    }
+
+    impl ProgressEmitter for RecordingEmitter {
+        fn emit_progress(&self, payload: &ProgressPayload) {
+            self.events.lock().unwrap().push(payload.raw.clone());
+            self.metadata.lock().unwrap().push(payload.metadata.clone());
+        }
+        fn emit_completion(&self, payload: &CompletionPayload) {
+            self.events.lock().unwrap().push("completion".into());
+            self.metadata.lock().unwrap().push(payload.metadata.clone());
+        }
+        fn emit_stderr(&self, _job_id: &str, _line: &str) {}
+        fn emit_verification(&self, _payload: &VerificationPayload) {}
+        fn emit_queue_position(&self, _payload: &QueuePositionPayload) {}
+        fn emit_retrying(&self, _payload: &RetryingPayload) {}
+        fn emit_queue_suspended(&self, _payload: &QueueSuspendedPayload) {}
+    }
+
+    fn progress(job_id: &str, raw: &str) -> ProgressPayload {
+        ProgressPayload {
+            job_id: job_id.into(),
+            progress: None,
+            raw: raw.into(),
+            metadata: Default::default(),
+        }
+    }
+
+    fn completion(job_id: &str) -> CompletionPayload {
+        CompletionPayload {
+            job_id: job_id.into(),
+            success: true,
+            cancelled: false,
+            exit_code: Some(0),
+            signal: None,
+            code: "job_complete".into(),
+            message: None,
+            logs: Vec::new(),
+            log_path: None,
+            suggestions: Vec::new(),
+            output_path: None,
+            verified: false,
+            fell_back_to_software: false,
+            trashed_source: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn progress_is_coalesced_and_flushed_on_completion() {
+        let recorder = Arc::new(RecordingEmitter::default());
+        let interval = ProgressInterval::default();
+        interval.set(Duration::from_secs(60));
+        let emitter = ThrottledEmitter::new(recorder.clone(), interval.clone());
+
+        for index in 0..5 {
+            emitter.emit_progress(&progress("a", &format!("a{index}")));
+        }
+        emitter.emit_progress(&progress("b", "b0"));
+        emitter.emit_completion(&completion("a"));
+        emitter.emit_completion(&completion("b"));
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec!["a0", "b0", "a4", "completion", "completion"]
+        );
+
+        interval.set(Duration::ZERO);
+        recorder.events.lock().unwrap().clear();
+        emitter.emit_progress(&progress("a", "a0"));
+        emitter.emit_progress(&progress("a", "a1"));
+        assert_eq!(*recorder.events.lock().unwrap(), vec!["a0", "a1"]);
+    }
+
+    #[test]
+    fn tagged_events_carry_the_job_metadata() {
+        let recorder = Arc::new(RecordingEmitter::default());
+        let metadata = JobMetadata::from([("batch".to_string(), "b-7".to_string())]);
+        let emitter = TaggedEmitter::new(recorder.clone(), metadata.clone());
+
+        emitter.emit_progress(&progress("a", "a0"));
+        emitter.emit_completion(&completion("a"));
+
+        assert_eq!(
+            *recorder.metadata.lock().unwrap(),
+            vec![metadata.clone(), metadata]
+        );
+        let json = serde_json::to_value(progress("a", "a0")).unwrap();
+        assert!(json.get("metadata").is_none());
+    }
+
+    #[test]
+    fn negotiation_takes_first_preference() {
+        let setting = EncodingSetting::default();
+        assert_eq!(setting.get(), EventEncoding::Json);
+        assert_eq!(
+            setting.negotiate(&[EventEncoding::Compact, EventEncoding::Json]),
+            EventEncoding::Compact
+        );
+        assert_eq!(setting.get(), EventEncoding::Compact);
+        assert_eq!(setting.negotiate(&[]), EventEncoding::Json);
+    }
+}
@@ -1,24 +1,40 @@
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Child;
 
 use tauri::AppHandle;
 
-use crate::error::AppError;
+use super::output_manager::OutputTarget;
+use crate::{api::OverwritePolicy, error::AppError};
 
 /// Abstraction for spawning ffmpeg processes to ease testing.
 pub trait SpawnController: Send + Sync {
-    fn resolve_ffmpeg(&self, app: &AppHandle) -> Result<OsString, AppError>;
+    /// The FFmpeg to run: the registered `installation` when one is named,
+    /// otherwise the resolver's first choice.
+    fn resolve_ffmpeg(
+        &self,
+        app: &AppHandle,
+        installation: Option<&str>,
+    ) -> Result<OsString, AppError>;
     fn prepare_output(
         &self,
-        output_path: &str,
+        output_path: &Path,
+        job_id: &str,
         exclusive: bool,
-    ) -> Result<(PathBuf, PathBuf), AppError>;
+        overwrite: OverwritePolicy,
+    ) -> Result<OutputTarget, AppError>;
     fn spawn_job(
+        &self,
+        ffmpeg_path: OsString,
+        args: &[OsString],
+        temp_output: &Path,
+        low_priority: bool,
+    ) -> Result<Child, AppError>;
+    fn spawn_verification(
         &self,
         ffmpeg_path: OsString,
         args: &[String],
-        temp_output: &str,
+        output: &str,
     ) -> Result<Child, AppError>;
 }
 
@@ -27,24 +43,43 @@ pub trait SpawnController: Send + Sync {
 pub struct DefaultSpawnController;
 
 impl SpawnController for DefaultSpawnController {
-    fn resolve_ffmpeg(&self, app: &AppHandle) -> Result<OsString, AppError> {
-        super::process_spawner::ProcessSpawner::resolve_ffmpeg(app)
+    fn resolve_ffmpeg(
+        &self,
+        app: &AppHandle,
+        installation: Option<&str>,
+    ) -> Result<OsString, AppError> {
+        match installation {
+            Some(id) => crate::binary_resolver::installation_ffmpeg(app, id),
+            None => super::process_spawner::ProcessSpawner::resolve_ffmpeg(app),
+        }
     }
 
     fn prepare_output(
         &self,
-        output_path: &str,
+        output_path: &Path,
+        job_id: &str,
         exclusive: bool,
-    ) -> Result<(PathBuf, PathBuf), AppError> {
-        super::output_manager::OutputManager::prepare(output_path, exclusive)
+        overwrite: OverwritePolicy,
+    ) -> Result<OutputTarget, AppError> {
+        super::output_manager::OutputManager::prepare(output_path, job_id, exclusive, overwrite)
     }
 
     fn spawn_job(
+        &self,
+        ffmpeg_path: OsString,
+        args: &[OsString],
+        temp_output: &Path,
+        low_priority: bool,
+    ) -> Result<Child, AppError> {
+        super::process_spawner::ProcessSpawner::spawn(ffmpeg_path, args, temp_output, low_priority)
+    }
+
+    fn spawn_verification(
         &self,
         ffmpeg_path: OsString,
         args: &[String],
-        temp_output: &str,
+        output: &str,
     ) -> Result<Child, AppError> {
-        super::process_spawner::ProcessSpawner::spawn(ffmpeg_path, args, temp_output)
+        super::process_spawner::ProcessSpawner::spawn_decode(ffmpeg_path, args, output)
     }
 }
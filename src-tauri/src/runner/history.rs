@@ -0,0 +1,193 @@
+//! History of finished conversions.
+//!
+//! Every job that reaches a final outcome (completed, failed, cancelled or
+//! skipped) is appended to `history.json` in the app data directory so the
+//! frontend can show what ran, with sizes and speed. Only the newest
+//! `MAX_HISTORY_ENTRIES` are kept.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::AppError,
+    persistence::{self, DocumentSchema},
+    sync_utils::MutexExt,
+};
+
+const HISTORY_FILE_NAME: &str = "history.json";
+const HISTORY_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "conversion history",
+    migrations: &[],
+};
+pub const MAX_HISTORY_ENTRIES: usize = 1_000;
+
+/// One finished job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub job_id: String,
+    pub source_path: Option<String>,
+    pub output_path: String,
+    pub preset_id: Option<String>,
+    pub success: bool,
+    pub cancelled: bool,
+    /// Completion code, e.g. `job_complete` or `job_failed`.
+    pub code: String,
+    pub message: Option<String>,
+    /// Media duration of the source.
+    pub duration_sec: Option<f64>,
+    /// Wall-clock time ffmpeg ran for.
+    pub elapsed_ms: Option<u64>,
+    pub source_bytes: Option<u64>,
+    pub output_bytes: Option<u64>,
+    /// Media seconds converted per wall-clock second.
+    pub speed: Option<f64>,
+    /// Milliseconds since the Unix epoch when the job finished.
+    pub finished_at_ms: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredHistory {
+    /// Oldest first.
+    entries: VecDeque<HistoryEntry>,
+}
+
+#[derive(Default)]
+pub struct HistoryStore {
+    path: OnceLock<PathBuf>,
+    history: Mutex<StoredHistory>,
+}
+
+impl HistoryStore {
+    /// Binds the store to the app data directory on first use.
+    pub fn attach(&self, app: &AppHandle) {
+        if self.path.get().is_some() {
+            return;
+        }
+        match app.path().app_data_dir() {
+            Ok(dir) => self.attach_path(dir.join(HISTORY_FILE_NAME)),
+            Err(err) => eprintln!("[history] history store unavailable: {err}"),
+        }
+    }
+
+    /// Binds the store to `path`, keeping entries recorded before that.
+    pub fn attach_path(&self, path: PathBuf) {
+        if self.path.set(path.clone()).is_err() {
+            return;
+        }
+        let stored: StoredHistory = match persistence::load(&path, &HISTORY_SCHEMA) {
+            Ok(stored) => stored.unwrap_or_default(),
+            Err(err) => {
+                eprintln!(
+                    "[history] failed loading {}: {}",
+                    path.display(),
+                    err.message
+                );
+                return;
+            },
+        };
+        self.update(|history| {
+            let recorded = std::mem::replace(&mut history.entries, stored.entries);
+            history.entries.extend(recorded);
+        });
+    }
+
+    pub fn record(&self, entry: HistoryEntry) {
+        self.update(|history| history.entries.push_back(entry));
+    }
+
+    /// Entries newest first.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let history = self.history.lock_or_recover();
+        history.entries.iter().rev().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.update(|history| history.entries.clear());
+    }
+
+    fn update(&self, change: impl FnOnce(&mut StoredHistory)) {
+        let mut history = self.history.lock_or_recover();
+        change(&mut history);
+        while history.entries.len() > MAX_HISTORY_ENTRIES {
+            history.entries.pop_front();
+        }
+
+        if let Some(path) = self.path.get() {
+            if let Err(err) = write(path, &history) {
+                eprintln!(
+                    "[history] failed saving {}: {}",
+                    path.display(),
+                    err.message
+                );
+            }
+        }
+    }
+}
+
+fn write(path: &Path, history: &StoredHistory) -> Result<(), AppError> {
+    persistence::persist(path, &HISTORY_SCHEMA, history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(job_id: &str) -> HistoryEntry {
+        HistoryEntry {
+            job_id: job_id.into(),
+            source_path: Some("/in.mov".into()),
+            output_path: "/out.mp4".into(),
+            preset_id: Some("video-to-mp4".into()),
+            success: true,
+            cancelled: false,
+            code: "job_complete".into(),
+            message: None,
+            duration_sec: Some(60.0),
+            elapsed_ms: Some(20_000),
+            source_bytes: Some(1_000),
+            output_bytes: Some(500),
+            speed: Some(3.0),
+            finished_at_ms: 1,
+        }
+    }
+
+    #[test]
+    fn entries_survive_restarts_newest_first() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(HISTORY_FILE_NAME);
+
+        let first = HistoryStore::default();
+        first.attach_path(path.clone());
+        first.record(entry("a"));
+        first.record(entry("b"));
+
+        let second = HistoryStore::default();
+        second.record(entry("c"));
+        second.attach_path(path.clone());
+        let ids: Vec<String> = second.entries().into_iter().map(|e| e.job_id).collect();
+        assert_eq!(ids, vec!["c", "b", "a"]);
+
+        second.clear();
+        let third = HistoryStore::default();
+        third.attach_path(path);
+        assert!(third.entries().is_empty());
+    }
+
+    #[test]
+    fn only_the_newest_entries_are_kept() {
+        let store = HistoryStore::default();
+        for index in 0..=MAX_HISTORY_ENTRIES {
+            store.record(entry(&index.to_string()));
+        }
+        let entries = store.entries();
+        assert_eq!(entries.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(entries[0].job_id, MAX_HISTORY_ENTRIES.to_string());
+        assert_eq!(entries.last().unwrap().job_id, "1");
+    }
+}
@@ -0,0 +1,343 @@
+//! Post-job hooks.
+//!
+//! Once a job reaches its final outcome, every configured hook whose trigger
+//! matches runs in the background: a program started directly (no shell)
+//! with templated arguments, or a JSON POST to a webhook sent through
+//! `curl`. `{output}`, `{input}`, `{jobId}`, `{status}` and `{code}` are
+//! replaced in arguments, and percent-encoded in URLs; the program itself
+//! is never templated. Hooks are saved to `post-job-hooks.json` in the app
+//! config directory. Hook failures are logged and never change the job's
+//! outcome.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock, RwLock};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    persistence::{self, DocumentSchema},
+    sync_utils::RwLockExt,
+};
+
+/// Longest a webhook request may take, in seconds.
+const WEBHOOK_TIMEOUT_SECS: &str = "10";
+
+const HOOKS_FILE_NAME: &str = "post-job-hooks.json";
+const HOOKS_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "post-job hooks",
+    migrations: &[],
+};
+
+/// Template variables hooks may use.
+pub const PLACEHOLDERS: [&str; 5] = ["{output}", "{input}", "{jobId}", "{status}", "{code}"];
+
+/// Which outcomes run a hook.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookTrigger {
+    Success,
+    Failure,
+    /// Every outcome, cancellation included.
+    #[default]
+    Always,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum HookAction {
+    /// Runs `program` with `args`; neither goes through a shell.
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// POSTs the outcome as JSON to `url`.
+    Webhook { url: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostJobHook {
+    #[serde(default)]
+    pub on: HookTrigger,
+    #[serde(flatten)]
+    pub action: HookAction,
+}
+
+/// A finished job as hooks see it; also the webhook body.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookOutcome {
+    pub job_id: String,
+    /// `completed`, `failed` or `cancelled`.
+    pub status: &'static str,
+    pub code: String,
+    pub message: Option<String>,
+    pub input: Option<String>,
+    pub output: String,
+}
+
+impl HookOutcome {
+    pub fn status(success: bool, cancelled: bool) -> &'static str {
+        match (success, cancelled) {
+            (true, _) => "completed",
+            (false, true) => "cancelled",
+            (false, false) => "failed",
+        }
+    }
+
+    fn matches(&self, trigger: HookTrigger) -> bool {
+        match trigger {
+            HookTrigger::Success => self.status == "completed",
+            HookTrigger::Failure => self.status == "failed",
+            HookTrigger::Always => true,
+        }
+    }
+
+    /// Replaces the template variables in `template`.
+    fn expand(&self, template: &str) -> String {
+        self.expand_with(template, |value| value.to_string())
+    }
+
+    /// Replaces the template variables in a URL, percent-encoding the
+    /// values so paths can't add query parameters or change the host.
+    fn expand_url(&self, template: &str) -> String {
+        self.expand_with(template, percent_encode)
+    }
+
+    fn expand_with(&self, template: &str, encode: impl Fn(&str) -> String) -> String {
+        template
+            .replace("{output}", &encode(&self.output))
+            .replace(
+                "{input}",
+                &encode(self.input.as_deref().unwrap_or_default()),
+            )
+            .replace("{jobId}", &encode(&self.job_id))
+            .replace("{status}", &encode(self.status))
+            .replace("{code}", &encode(&self.code))
+    }
+}
+
+/// Encodes everything but RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            },
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Hooks shared by every clone of the coordinator.
+#[derive(Debug, Clone, Default)]
+pub struct HookSettings {
+    path: Arc<OnceLock<PathBuf>>,
+    hooks: Arc<RwLock<Vec<PostJobHook>>>,
+}
+
+impl HookSettings {
+    /// Binds the settings to the app config directory on first use and
+    /// loads the saved hooks.
+    pub fn attach(&self, app: &AppHandle) {
+        if self.path.get().is_some() {
+            return;
+        }
+        match app.path().app_config_dir() {
+            Ok(dir) => self.attach_path(dir.join(HOOKS_FILE_NAME)),
+            Err(err) => eprintln!("[hooks] hook settings unavailable: {err}"),
+        }
+    }
+
+    /// Binds the settings to `path` and loads the hooks saved there.
+    pub fn attach_path(&self, path: PathBuf) {
+        if self.path.set(path.clone()).is_err() {
+            return;
+        }
+        match persistence::load(&path, &HOOKS_SCHEMA) {
+            Ok(stored) => *self.hooks.write_or_recover() = stored.unwrap_or_default(),
+            Err(err) => eprintln!("[hooks] failed loading {}: {}", path.display(), err.message),
+        }
+    }
+
+    /// Replaces the hooks, saving them when attached.
+    pub fn set(&self, hooks: Vec<PostJobHook>) {
+        let mut current = self.hooks.write_or_recover();
+        *current = hooks;
+        self.save(&current);
+    }
+
+    fn save(&self, hooks: &[PostJobHook]) {
+        let Some(path) = self.path.get() else {
+            return;
+        };
+        if let Err(err) = persistence::persist(path, &HOOKS_SCHEMA, &hooks) {
+            eprintln!("[hooks] failed saving {}: {}", path.display(), err.message);
+        }
+    }
+
+    /// Starts the hooks matching `outcome` on a background thread.
+    pub fn run(&self, outcome: HookOutcome) {
+        let hooks: Vec<PostJobHook> = self
+            .hooks
+            .read_or_recover()
+            .iter()
+            .filter(|hook| outcome.matches(hook.on))
+            .cloned()
+            .collect();
+        if hooks.is_empty() {
+            return;
+        }
+        std::thread::spawn(move || {
+            for hook in hooks {
+                if let Err(err) = run_hook(&hook.action, &outcome) {
+                    eprintln!("[hooks] hook for job {} failed: {err}", outcome.job_id);
+                }
+            }
+        });
+    }
+}
+
+fn run_hook(action: &HookAction, outcome: &HookOutcome) -> std::io::Result<()> {
+    let (mut command, body) = match action {
+        HookAction::Command { program, args } => {
+            let mut command = Command::new(program);
+            command.args(args.iter().map(|arg| outcome.expand(arg)));
+            command.stdin(Stdio::null());
+            (command, None)
+        },
+        HookAction::Webhook { url } => {
+            let mut command = Command::new("curl");
+            command
+                .args(["--silent", "--show-error", "--fail", "--max-time"])
+                .arg(WEBHOOK_TIMEOUT_SECS)
+                .args([
+                    "-H",
+                    "Content-Type: application/json",
+                    "--data-binary",
+                    "@-",
+                ])
+                .arg(outcome.expand_url(url))
+                .stdin(Stdio::piped());
+            (command, Some(serde_json::to_vec(outcome)?))
+        },
+    };
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let (Some(body), Some(mut stdin)) = (body, child.stdin.take()) {
+        stdin.write_all(&body)?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(std::io::Error::other(format!(
+        "{}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(success: bool, cancelled: bool) -> HookOutcome {
+        HookOutcome {
+            job_id: "job-1".into(),
+            status: HookOutcome::status(success, cancelled),
+            code: if success { "ok" } else { "job_failed" }.into(),
+            message: None,
+            input: Some("/in/a b.mov".into()),
+            output: "/out/a b.mp4".into(),
+        }
+    }
+
+    #[test]
+    fn triggers_match_outcomes() {
+        let done = outcome(true, false);
+        let failed = outcome(false, false);
+        let cancelled = outcome(false, true);
+        assert!(done.matches(HookTrigger::Success) && !done.matches(HookTrigger::Failure));
+        assert!(failed.matches(HookTrigger::Failure) && !failed.matches(HookTrigger::Success));
+        assert!(!cancelled.matches(HookTrigger::Failure));
+        assert!(cancelled.matches(HookTrigger::Always));
+    }
+
+    #[test]
+    fn templates_expand_per_argument() {
+        assert_eq!(
+            outcome(true, false).expand("{jobId}:{status}:{output}<-{input}"),
+            "job-1:completed:/out/a b.mp4<-/in/a b.mov"
+        );
+    }
+
+    #[test]
+    fn url_templates_are_percent_encoded() {
+        let mut outcome = outcome(true, false);
+        outcome.output = "/out/a&b=c?#.mp4".into();
+        assert_eq!(
+            outcome.expand_url("https://example.com/done?file={output}&job={jobId}"),
+            "https://example.com/done?file=%2Fout%2Fa%26b%3Dc%3F%23.mp4&job=job-1"
+        );
+    }
+
+    #[test]
+    fn hooks_are_saved_and_restored() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join(HOOKS_FILE_NAME);
+        let hook = PostJobHook {
+            on: HookTrigger::Failure,
+            action: HookAction::Webhook {
+                url: "https://example.com/failed".into(),
+            },
+        };
+
+        let settings = HookSettings::default();
+        settings.attach_path(path.clone());
+        settings.set(vec![hook.clone()]);
+
+        let restored = HookSettings::default();
+        restored.attach_path(path);
+        assert_eq!(*restored.hooks.read_or_recover(), vec![hook]);
+    }
+
+    #[test]
+    fn hooks_deserialize_by_kind() {
+        let hooks: Vec<PostJobHook> = serde_json::from_str(
+            r#"[{"kind":"command","program":"notify","args":["{output}"],"on":"success"},
+                {"kind":"webhook","url":"https://example.com/done"}]"#,
+        )
+        .unwrap();
+        assert_eq!(hooks[0].on, HookTrigger::Success);
+        assert_eq!(hooks[1].on, HookTrigger::Always);
+        assert_eq!(
+            hooks[1].action,
+            HookAction::Webhook {
+                url: "https://example.com/done".into()
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_hooks_fail_on_nonzero_exit() {
+        let run = |program: &str| {
+            run_hook(
+                &HookAction::Command {
+                    program: program.into(),
+                    args: vec!["{jobId}".into()],
+                },
+                &outcome(true, false),
+            )
+        };
+        assert!(run("true").is_ok());
+        assert!(run("false").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Dependencies between conversions.
+//!
+//! A job may name parent jobs that must succeed before it starts, so
+//! multi-step pipelines (extract audio, normalize, mux) can be submitted at
+//! once. Parents must already be known when the child is submitted, which
+//! rules out cycles. When a parent fails or is cancelled, every job waiting
+//! on it, directly or through other jobs, is skipped.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use crate::error::AppError;
+use crate::sync_utils::MutexExt;
+
+/// Outcomes remembered for jobs that have finished, so late children can
+/// still find their parents.
+const FINISHED_CAPACITY: usize = 4096;
+
+pub enum Admission<T> {
+    /// Every parent has succeeded; the job can start.
+    Ready(T),
+    /// Waiting for parents that are still open.
+    Waiting,
+    /// A parent already failed, so the job will never run.
+    Skipped { job: T, parent: String },
+}
+
+/// Jobs released or skipped by a parent finishing.
+pub struct Settled<T> {
+    pub ready: Vec<(String, T)>,
+    /// Skipped jobs with the parent whose failure caused it.
+    pub skipped: Vec<(String, T, String)>,
+}
+
+struct Waiting<T> {
+    job_id: String,
+    parents: Vec<String>,
+    job: T,
+}
+
+struct GraphState<T> {
+    /// Jobs submitted and not finished yet.
+    open: HashSet<String>,
+    finished: HashMap<String, bool>,
+    finished_order: VecDeque<String>,
+    /// Jobs blocked on parents, in submission order.
+    waiting: Vec<Waiting<T>>,
+}
+
+impl<T> GraphState<T> {
+    fn record(&mut self, job_id: &str, success: bool) {
+        self.open.remove(job_id);
+        if self.finished.insert(job_id.to_string(), success).is_none() {
+            self.finished_order.push_back(job_id.to_string());
+        }
+        while self.finished_order.len() > FINISHED_CAPACITY {
+            if let Some(oldest) = self.finished_order.pop_front() {
+                self.finished.remove(&oldest);
+            }
+        }
+    }
+}
+
+pub struct JobGraph<T> {
+    state: Mutex<GraphState<T>>,
+}
+
+impl<T> Default for JobGraph<T> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(GraphState {
+                open: HashSet::new(),
+                finished: HashMap::new(),
+                finished_order: VecDeque::new(),
+                waiting: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl<T> JobGraph<T> {
+    /// Registers a submitted job and decides whether it can start now.
+    pub fn admit(
+        &self,
+        job_id: &str,
+        depends_on: &[String],
+        job: T,
+    ) -> Result<Admission<T>, AppError> {
+        let mut state = self.state.lock_or_recover();
+        if state.open.contains(job_id) {
+            return Err(AppError::new(
+                "job_already_running",
+                format!("Job {job_id} is already running."),
+            ));
+        }
+
+        let mut parents = Vec::new();
+        let mut failed_parent = None;
+        for parent in depends_on {
+            if parent == job_id {
+                return Err(AppError::new(
+                    "job_dependency_invalid",
+                    format!("Job {job_id} cannot depend on itself."),
+                ));
+            }
+            match state.finished.get(parent) {
+                Some(true) => {},
+                Some(false) => failed_parent = failed_parent.or(Some(parent.clone())),
+                None if state.open.contains(parent) => parents.push(parent.clone()),
+                None => {
+                    return Err(AppError::new(
+                        "job_dependency_unknown",
+                        format!("Job {job_id} depends on unknown job {parent}."),
+                    ))
+                },
+            }
+        }
+
+        if let Some(parent) = failed_parent {
+            state.record(job_id, false);
+            return Ok(Admission::Skipped { job, parent });
+        }
+        state.open.insert(job_id.to_string());
+        if parents.is_empty() {
+            return Ok(Admission::Ready(job));
+        }
+        state.waiting.push(Waiting {
+            job_id: job_id.to_string(),
+            parents,
+            job,
+        });
+        Ok(Admission::Waiting)
+    }
+
+    /// Records how a job ended, releasing children whose parents have all
+    /// succeeded and skipping everything downstream of a failure.
+    pub fn finish(&self, job_id: &str, success: bool) -> Settled<T> {
+        let mut settled = Settled {
+            ready: Vec::new(),
+            skipped: Vec::new(),
+        };
+        let mut state = self.state.lock_or_recover();
+
+        let mut finished = VecDeque::from([(job_id.to_string(), success)]);
+        while let Some((parent, success)) = finished.pop_front() {
+            state.record(&parent, success);
+
+            let mut index = 0;
+            while index < state.waiting.len() {
+                let waiting = &mut state.waiting[index];
+                if !waiting.parents.contains(&parent) {
+                    index += 1;
+                    continue;
+                }
+                waiting.parents.retain(|id| *id != parent);
+                if success && !waiting.parents.is_empty() {
+                    index += 1;
+                    continue;
+                }
+
+                let waiting = state.waiting.remove(index);
+                if success {
+                    settled.ready.push((waiting.job_id, waiting.job));
+                } else {
+                    finished.push_back((waiting.job_id.clone(), false));
+                    settled
+                        .skipped
+                        .push((waiting.job_id, waiting.job, parent.clone()));
+                }
+            }
+        }
+        settled
+    }
+
+    /// Takes every job still waiting on its parents.
+    pub fn drain_waiting(&self) -> Vec<(String, T)> {
+        self.state
+            .lock_or_recover()
+            .waiting
+            .drain(..)
+            .map(|waiting| (waiting.job_id, waiting.job))
+            .collect()
+    }
+
+    /// Takes a job still waiting on its parents, e.g. to cancel it.
+    pub fn remove_waiting(&self, job_id: &str) -> Option<T> {
+        let mut state = self.state.lock_or_recover();
+        let index = state
+            .waiting
+            .iter()
+            .position(|waiting| waiting.job_id == job_id)?;
+        Some(state.waiting.remove(index).job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn children_start_once_every_parent_succeeds() {
+        let graph = JobGraph::default();
+        assert!(matches!(
+            graph.admit("extract", &[], ()).unwrap(),
+            Admission::Ready(())
+        ));
+        assert!(matches!(
+            graph.admit("normalize", &ids(&["extract"]), ()).unwrap(),
+            Admission::Waiting
+        ));
+        assert!(matches!(
+            graph
+                .admit("mux", &ids(&["extract", "normalize"]), ())
+                .unwrap(),
+            Admission::Waiting
+        ));
+
+        let settled = graph.finish("extract", true);
+        assert_eq!(settled.ready.len(), 1);
+        assert_eq!(settled.ready[0].0, "normalize");
+
+        let settled = graph.finish("normalize", true);
+        assert_eq!(settled.ready[0].0, "mux");
+
+        // Parents that already succeeded don't hold back late children.
+        assert!(matches!(
+            graph.admit("thumbnail", &ids(&["extract"]), ()).unwrap(),
+            Admission::Ready(())
+        ));
+    }
+
+    #[test]
+    fn failures_cascade_to_all_descendants() {
+        let graph = JobGraph::default();
+        graph.admit("extract", &[], ()).unwrap();
+        graph.admit("normalize", &ids(&["extract"]), ()).unwrap();
+        graph.admit("mux", &ids(&["normalize"]), ()).unwrap();
+
+        let settled = graph.finish("extract", false);
+        let skipped: Vec<(&str, &str)> = settled
+            .skipped
+            .iter()
+            .map(|(id, _, parent)| (id.as_str(), parent.as_str()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![("normalize", "extract"), ("mux", "normalize")]
+        );
+
+        assert!(matches!(
+            graph.admit("late", &ids(&["mux"]), ()).unwrap(),
+            Admission::Skipped { parent, .. } if parent == "mux"
+        ));
+    }
+
+    #[test]
+    fn unknown_or_self_dependencies_are_rejected() {
+        let graph = JobGraph::default();
+        let err = graph.admit("a", &ids(&["missing"]), ()).err().unwrap();
+        assert_eq!(err.code, "job_dependency_unknown");
+        let err = graph.admit("a", &ids(&["a"]), ()).err().unwrap();
+        assert_eq!(err.code, "job_dependency_invalid");
+    }
+}
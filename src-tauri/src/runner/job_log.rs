@@ -0,0 +1,725 @@
+//! Per-job ffmpeg log storage.
+//!
+//! Stderr lines are appended once to a per-job log file that is memory
+//! mapped on Unix. The file is the persistent log, and the bounded view
+//! handed to completion payloads is a window of line offsets into the same
+//! mapping, so lines are not copied into a separate ring buffer while the
+//! job runs. Jobs without a log directory keep an in-memory buffer that
+//! is compacted as old lines fall out of the view.
+//!
+//! `LogSink` owns the log directory. Opening a job's log again, e.g. for a
+//! retry, rotates the previous attempts to `<job>.log.1`, `<job>.log.2`,
+//! and so does a log outgrowing `MAX_LOG_BYTES` mid-job. The directory is
+//! pruned to the newest `MAX_LOG_FILES` files.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::sync_utils::MutexExt;
+
+/// Lines kept in the bounded view.
+pub const MAX_VIEW_LINES: usize = 500;
+/// Initial size of the mapped log file; it doubles as needed.
+const INITIAL_CAPACITY: usize = 64 * 1024;
+/// Size at which a running job's log is rotated.
+pub const MAX_LOG_BYTES: usize = 8 * 1024 * 1024;
+/// Earlier attempts, or earlier parts of a long attempt, kept per job.
+pub const MAX_ROTATED_LOGS: usize = 3;
+/// Log files kept in the directory, rotated ones included.
+pub const MAX_LOG_FILES: usize = 500;
+
+pub const JOB_LOG_NOT_FOUND: &str = "job_log_not_found";
+pub const JOB_LOG_EXPORT_FAILED: &str = "job_log_export_failed";
+
+/// The directory per-job logs are written to.
+pub struct LogSink {
+    dir: PathBuf,
+}
+
+impl LogSink {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The `jobs` directory under the app log directory.
+    pub fn for_app(app: &AppHandle) -> Result<Self, AppError> {
+        let dir = app.path().app_log_dir().map_err(|err| {
+            AppError::new(
+                JOB_LOG_NOT_FOUND,
+                format!("Log directory unavailable: {err}"),
+            )
+        })?;
+        Ok(Self::new(dir.join("jobs")))
+    }
+
+    /// Path of the current log for `job_id`, whether or not it exists.
+    pub fn path(&self, job_id: &str) -> PathBuf {
+        self.dir.join(log_file_name(job_id))
+    }
+
+    /// Opens a fresh log for `job_id`, rotating earlier attempts. Falls back
+    /// to an in-memory log when the file can't be created.
+    pub fn open(&self, job_id: &str) -> JobLog {
+        let path = self.path(job_id);
+        if let Err(err) = rotate(&path) {
+            eprintln!("[job-log] failed rotating {}: {err}", path.display());
+        }
+        if let Err(err) = self.prune() {
+            eprintln!("[job-log] failed pruning {}: {err}", self.dir.display());
+        }
+        JobLog::create(&path).unwrap_or_else(|err| {
+            eprintln!("[job-log] failed creating log {}: {err}", path.display());
+            JobLog::in_memory()
+        })
+    }
+
+    /// Copies the logs of `job_id`, or every log when `None`, into the
+    /// `destination` directory and returns the copies.
+    pub fn export(
+        &self,
+        job_id: Option<&str>,
+        destination: &Path,
+    ) -> Result<Vec<PathBuf>, AppError> {
+        let export_error = |err: io::Error| {
+            AppError::new(
+                JOB_LOG_EXPORT_FAILED,
+                format!("Failed exporting logs to {}: {err}", destination.display()),
+            )
+        };
+        let sources: Vec<PathBuf> = match job_id {
+            Some(job_id) => {
+                let current = self.path(job_id);
+                std::iter::once(current.clone())
+                    .chain(
+                        (1..=MAX_ROTATED_LOGS).map(|generation| rotated_path(&current, generation)),
+                    )
+                    .filter(|path| path.is_file())
+                    .collect()
+            },
+            None => self
+                .files()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect(),
+        };
+        if sources.is_empty() {
+            return Err(AppError::new(
+                JOB_LOG_NOT_FOUND,
+                match job_id {
+                    Some(job_id) => format!("No logs found for job {job_id}."),
+                    None => "No job logs found.".to_string(),
+                },
+            ));
+        }
+
+        let copies: Vec<(PathBuf, PathBuf)> = sources
+            .into_iter()
+            .filter_map(|source| Some((destination.join(source.file_name()?), source)))
+            .collect();
+        if let Some((existing, _)) = copies.iter().find(|(target, _)| target.exists()) {
+            return Err(AppError::new(
+                JOB_LOG_EXPORT_FAILED,
+                format!(
+                    "{} already exists; choose another folder.",
+                    existing.display()
+                ),
+            ));
+        }
+
+        std::fs::create_dir_all(destination).map_err(export_error)?;
+        copies
+            .into_iter()
+            .map(|(target, source)| {
+                // `create_new` also refuses a file created since the check.
+                let mut copy = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&target)
+                    .map_err(export_error)?;
+                io::copy(&mut File::open(&source).map_err(export_error)?, &mut copy)
+                    .map_err(export_error)?;
+                Ok(target)
+            })
+            .collect()
+    }
+
+    /// Log files with their modification time.
+    fn files(&self) -> io::Result<Vec<(PathBuf, SystemTime)>> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() && is_log_file(&entry.file_name().to_string_lossy()) {
+                files.push((entry.path(), metadata.modified()?));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Removes the oldest files so that one more log still fits.
+    fn prune(&self) -> io::Result<()> {
+        let mut files = match self.files() {
+            Ok(files) => files,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        if files.len() < MAX_LOG_FILES {
+            return Ok(());
+        }
+        files.sort_by_key(|(_, modified)| *modified);
+        let excess = files.len() + 1 - MAX_LOG_FILES;
+        for (path, _) in files.into_iter().take(excess) {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shifts `path` to `path.1`, `path.1` to `path.2` and so on, dropping the
+/// oldest generation.
+fn rotate(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for generation in (1..MAX_ROTATED_LOGS).rev() {
+        let from = rotated_path(path, generation);
+        if from.exists() {
+            std::fs::rename(&from, rotated_path(path, generation + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+fn rotated_path(path: &Path, generation: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{generation}"));
+    PathBuf::from(name)
+}
+
+/// `<job>.log` or a rotated `<job>.log.<n>`.
+fn is_log_file(name: &str) -> bool {
+    match name.rsplit_once(".log") {
+        Some((stem, "")) => !stem.is_empty(),
+        Some((stem, suffix)) => {
+            !stem.is_empty()
+                && suffix
+                    .strip_prefix('.')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        },
+        None => false,
+    }
+}
+
+pub struct JobLog {
+    state: Mutex<LogState>,
+    path: Option<PathBuf>,
+}
+
+struct LogState {
+    storage: Storage,
+    view: VecDeque<ViewLine>,
+}
+
+/// A line of the bounded view.
+enum ViewLine {
+    /// `(offset, length)` in the current storage.
+    Stored(usize, usize),
+    /// Copied out when the storage it was in was rotated away.
+    Carried(String),
+}
+
+enum Storage {
+    #[cfg(unix)]
+    Mapped(mapped::MappedFile),
+    /// Used without a log file, and as the write-through fallback on
+    /// platforms without the mapped implementation.
+    Buffered {
+        bytes: Vec<u8>,
+        /// Offset of `bytes[0]` in the logical log.
+        base: usize,
+        file: Option<File>,
+    },
+}
+
+impl JobLog {
+    /// A log that only keeps the bounded view in memory.
+    pub fn in_memory() -> Self {
+        Self {
+            state: Mutex::new(LogState {
+                storage: Storage::Buffered {
+                    bytes: Vec::new(),
+                    base: 0,
+                    file: None,
+                },
+                view: VecDeque::new(),
+            }),
+            path: None,
+        }
+    }
+
+    /// Creates (or truncates) the log file at `path`.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            state: Mutex::new(LogState {
+                storage: Storage::create(path)?,
+                view: VecDeque::new(),
+            }),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn push(&self, line: &str) {
+        let mut state = self.state.lock_or_recover();
+
+        if let Some(path) = &self.path {
+            if state.storage.len() + line.len() + 1 > MAX_LOG_BYTES {
+                state.roll_over(path);
+            }
+        }
+
+        let offset = match state.storage.append(line.as_bytes()) {
+            Ok(offset) => offset,
+            Err(err) => {
+                eprintln!("[job-log] failed appending log line: {err}");
+                return;
+            },
+        };
+
+        if state.view.len() >= MAX_VIEW_LINES {
+            state.view.pop_front();
+        }
+        state.view.push_back(ViewLine::Stored(offset, line.len()));
+
+        if let Some(&ViewLine::Stored(oldest, _)) = state.view.front() {
+            state.storage.release_before(oldest);
+        }
+    }
+
+    /// Returns the bounded view and clears it. The log file is untouched.
+    pub fn drain(&self) -> Vec<String> {
+        let mut state = self.state.lock_or_recover();
+        let view = std::mem::take(&mut state.view);
+        view.into_iter()
+            .map(|line| match line {
+                ViewLine::Stored(offset, len) => {
+                    String::from_utf8_lossy(state.storage.read(offset, len)).into_owned()
+                },
+                ViewLine::Carried(line) => line,
+            })
+            .collect()
+    }
+}
+
+impl LogState {
+    /// Rotates the full log away and continues in a fresh file at `path`,
+    /// carrying the view over. Falls back to an in-memory log when the new
+    /// file can't be created, so the log stops growing either way.
+    fn roll_over(&mut self, path: &Path) {
+        for line in self.view.iter_mut() {
+            if let ViewLine::Stored(offset, len) = *line {
+                *line = ViewLine::Carried(
+                    String::from_utf8_lossy(self.storage.read(offset, len)).into_owned(),
+                );
+            }
+        }
+        // Closed first: an open file can't be renamed everywhere.
+        self.storage = Storage::Buffered {
+            bytes: Vec::new(),
+            base: 0,
+            file: None,
+        };
+        let reopened = rotate(path).and_then(|()| Storage::create(path));
+        match reopened {
+            Ok(storage) => self.storage = storage,
+            Err(err) => eprintln!("[job-log] failed rotating {}: {err}", path.display()),
+        }
+    }
+}
+
+impl Storage {
+    fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        #[cfg(unix)]
+        let storage = Storage::Mapped(mapped::MappedFile::new(file, INITIAL_CAPACITY)?);
+        #[cfg(not(unix))]
+        let storage = Storage::Buffered {
+            bytes: Vec::new(),
+            base: 0,
+            file: Some(file),
+        };
+        Ok(storage)
+    }
+
+    /// Bytes written so far.
+    fn len(&self) -> usize {
+        match self {
+            #[cfg(unix)]
+            Storage::Mapped(file) => file.len(),
+            Storage::Buffered { bytes, base, .. } => base + bytes.len(),
+        }
+    }
+
+    /// Appends `line` plus a newline and returns the line's offset.
+    fn append(&mut self, line: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Storage::Mapped(file) => file.append(line),
+            Storage::Buffered { bytes, base, file } => {
+                if let Some(file) = file {
+                    use std::io::Write;
+                    file.write_all(line)?;
+                    file.write_all(b"\n")?;
+                }
+                let offset = *base + bytes.len();
+                bytes.extend_from_slice(line);
+                bytes.push(b'\n');
+                Ok(offset)
+            },
+        }
+    }
+
+    fn read(&self, offset: usize, len: usize) -> &[u8] {
+        match self {
+            #[cfg(unix)]
+            Storage::Mapped(file) => file.read(offset, len),
+            Storage::Buffered { bytes, base, .. } => {
+                let start = offset.saturating_sub(*base);
+                bytes.get(start..start + len).unwrap_or_default()
+            },
+        }
+    }
+
+    /// Lets the in-memory buffer drop bytes no longer in the view. Mapped
+    /// files keep everything since they are the persistent log.
+    fn release_before(&mut self, offset: usize) {
+        if let Storage::Buffered { bytes, base, .. } = self {
+            let releasable = offset.saturating_sub(*base);
+            if releasable > bytes.len() / 2 {
+                bytes.drain(..releasable);
+                *base = offset;
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod mapped {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    /// A file mapped read/write that grows by remapping. The file's blocks
+    /// are allocated before they are mapped, so a full disk fails `append`
+    /// instead of raising SIGBUS on a write into a sparse page.
+    pub struct MappedFile {
+        file: File,
+        ptr: *mut u8,
+        capacity: usize,
+        len: usize,
+    }
+
+    // SAFETY: the mapping is owned exclusively by this value and only
+    // accessed through `&mut self` / `&self` behind the `JobLog` mutex.
+    unsafe impl Send for MappedFile {}
+
+    impl MappedFile {
+        pub fn new(file: File, capacity: usize) -> io::Result<Self> {
+            reserve(&file, 0, capacity)?;
+            let ptr = map(&file, capacity)?;
+            Ok(Self {
+                file,
+                ptr,
+                capacity,
+                len: 0,
+            })
+        }
+
+        pub fn append(&mut self, line: &[u8]) -> io::Result<usize> {
+            let offset = self.len;
+            let needed = offset + line.len() + 1;
+            if needed > self.capacity {
+                self.grow(needed)?;
+            }
+
+            // SAFETY: `needed <= capacity`, so both copies stay inside the
+            // mapping, and `line` cannot overlap memory we own.
+            unsafe {
+                std::ptr::copy_nonoverlapping(line.as_ptr(), self.ptr.add(offset), line.len());
+                *self.ptr.add(offset + line.len()) = b'\n';
+            }
+            self.len = needed;
+            Ok(offset)
+        }
+
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn read(&self, offset: usize, len: usize) -> &[u8] {
+            if offset + len > self.len {
+                return &[];
+            }
+            // SAFETY: the range was written by `append` and lies within the
+            // current mapping.
+            unsafe { std::slice::from_raw_parts(self.ptr.add(offset), len) }
+        }
+
+        /// Maps the larger region before dropping the old one, so a failure
+        /// leaves the current mapping in place.
+        fn grow(&mut self, needed: usize) -> io::Result<()> {
+            let capacity = (self.capacity * 2).max(needed);
+            reserve(&self.file, self.capacity, capacity)?;
+            let ptr = map(&self.file, capacity)?;
+            // SAFETY: `ptr`/`capacity` describe the live mapping created by
+            // `map`; the new mapping replaces it before any further access.
+            unsafe {
+                libc::munmap(self.ptr.cast(), self.capacity);
+            }
+            self.ptr = ptr;
+            self.capacity = capacity;
+            Ok(())
+        }
+    }
+
+    impl Drop for MappedFile {
+        fn drop(&mut self) {
+            // SAFETY: unmapping the mapping this value owns.
+            unsafe {
+                libc::munmap(self.ptr.cast(), self.capacity);
+            }
+            // Trim the unused preallocated tail so the log reads cleanly.
+            let _ = self.file.set_len(self.len as u64);
+        }
+    }
+
+    /// Allocates the file's blocks from `from` to `to` and extends it to
+    /// `to` bytes.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fn reserve(file: &File, from: usize, to: usize) -> io::Result<()> {
+        // SAFETY: plain syscall on a descriptor we own; errors come back as
+        // the return value.
+        let result = unsafe {
+            libc::posix_fallocate(
+                file.as_raw_fd(),
+                from as libc::off_t,
+                (to - from) as libc::off_t,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::from_raw_os_error(result));
+        }
+        Ok(())
+    }
+
+    #[cfg(target_vendor = "apple")]
+    fn reserve(file: &File, from: usize, to: usize) -> io::Result<()> {
+        let mut store = libc::fstore_t {
+            fst_flags: libc::F_ALLOCATEALL,
+            fst_posmode: libc::F_PEOFPOSMODE,
+            fst_offset: 0,
+            fst_length: (to - from) as libc::off_t,
+            fst_bytesalloc: 0,
+        };
+        // SAFETY: `store` outlives the call, which only reads and updates it.
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &mut store) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        file.set_len(to as u64)
+    }
+
+    /// Other systems can't preallocate, so only the size is set.
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_vendor = "apple"
+    )))]
+    fn reserve(file: &File, _from: usize, to: usize) -> io::Result<()> {
+        file.set_len(to as u64)
+    }
+
+    fn map(file: &File, capacity: usize) -> io::Result<*mut u8> {
+        // SAFETY: mapping a file descriptor we own with a length matching
+        // the file size; failure is reported through MAP_FAILED.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                capacity,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ptr.cast())
+    }
+}
+
+/// File name for a job's log, keeping ids filesystem-safe.
+pub fn log_file_name(job_id: &str) -> String {
+    let safe: String = job_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{safe}.log")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn in_memory_view_is_bounded() {
+        let log = JobLog::in_memory();
+        for index in 0..(MAX_VIEW_LINES + 20) {
+            log.push(&format!("line {index}"));
+        }
+
+        let lines = log.drain();
+        assert_eq!(lines.len(), MAX_VIEW_LINES);
+        assert_eq!(lines[0], "line 20");
+        assert_eq!(
+            lines.last().unwrap(),
+            &format!("line {}", MAX_VIEW_LINES + 19)
+        );
+        assert!(log.drain().is_empty());
+    }
+
+    #[test]
+    fn file_log_persists_every_line_and_grows() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("jobs").join("job.log");
+        let log = JobLog::create(&path).unwrap();
+
+        // Enough 256-byte lines to outgrow the initial mapping twice over
+        // and to overflow the bounded view.
+        let long_line = "x".repeat(255);
+        let count = INITIAL_CAPACITY / 256 * 3;
+        for _ in 0..count {
+            log.push(&long_line);
+        }
+        log.push("done");
+
+        let view = log.drain();
+        assert_eq!(view.len(), MAX_VIEW_LINES);
+        assert_eq!(view.last().unwrap(), "done");
+        assert_eq!(log.path(), Some(path.as_path()));
+
+        drop(log);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), count + 1);
+        assert!(contents.ends_with("done\n"));
+    }
+
+    #[test]
+    fn outgrown_logs_rotate_and_keep_the_view() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("job.log");
+        let log = JobLog::create(&path).unwrap();
+
+        let chunk = "x".repeat(64 * 1024 - 1);
+        let chunks = MAX_LOG_BYTES / (64 * 1024) + 2;
+        for _ in 0..chunks {
+            log.push(&chunk);
+        }
+        log.push("done");
+
+        assert_eq!(log.drain().len(), chunks + 1);
+        drop(log);
+        let rotated = std::fs::metadata(rotated_path(&path, 1)).unwrap().len();
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(rotated as usize, MAX_LOG_BYTES);
+        assert_eq!(current.lines().count(), 3);
+        assert!(current.ends_with("done\n"));
+    }
+
+    #[test]
+    fn exports_never_overwrite() {
+        let temp = TempDir::new().unwrap();
+        let sink = LogSink::new(temp.path().join("jobs"));
+        sink.open("job").push("line");
+
+        let exported = temp.path().join("export");
+        sink.export(Some("job"), &exported).unwrap();
+        let err = sink.export(Some("job"), &exported).unwrap_err();
+        assert_eq!(err.code, JOB_LOG_EXPORT_FAILED);
+        assert!(err.message.contains("already exists"));
+    }
+
+    #[test]
+    fn reopening_a_log_rotates_earlier_attempts() {
+        let temp = TempDir::new().unwrap();
+        let sink = LogSink::new(temp.path().join("jobs"));
+        for attempt in 0..=MAX_ROTATED_LOGS + 1 {
+            sink.open("job").push(&format!("attempt {attempt}"));
+        }
+
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap();
+        let current = sink.path("job");
+        let newest = MAX_ROTATED_LOGS + 1;
+        assert_eq!(read(current.clone()), format!("attempt {newest}\n"));
+        for generation in 1..=MAX_ROTATED_LOGS {
+            assert_eq!(
+                read(rotated_path(&current, generation)),
+                format!("attempt {}\n", newest - generation)
+            );
+        }
+        assert!(!rotated_path(&current, MAX_ROTATED_LOGS + 1).exists());
+
+        let exported = temp.path().join("export");
+        let copies = sink.export(Some("job"), &exported).unwrap();
+        assert_eq!(copies.len(), MAX_ROTATED_LOGS + 1);
+        assert_eq!(
+            read(exported.join("job.log")),
+            format!("attempt {newest}\n")
+        );
+
+        let err = sink.export(Some("other"), &exported).unwrap_err();
+        assert_eq!(err.code, JOB_LOG_NOT_FOUND);
+    }
+
+    #[test]
+    fn log_files_are_recognized() {
+        assert!(is_log_file("job-1.log"));
+        assert!(is_log_file("job-1.log.2"));
+        assert!(!is_log_file(".log"));
+        assert!(!is_log_file("job-1.log.x"));
+        assert!(!is_log_file("notes.txt"));
+    }
+
+    #[test]
+    fn log_file_names_are_sanitized() {
+        assert_eq!(log_file_name("job-1"), "job-1.log");
+        assert_eq!(log_file_name("../a b"), "___a_b.log");
+    }
+}
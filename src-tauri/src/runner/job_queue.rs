@@ -0,0 +1,312 @@
+//! FIFO queue for jobs that cannot start yet.
+//!
+//! Jobs refused by the registry because every slot is taken (or an
+//! exclusive job is running) wait here instead of being rejected, and are
+//! launched in submission order as slots free up. A job never overtakes
+//! one queued before it, so an exclusive job at the head holds back the
+//! jobs behind it until it has run.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::concurrency::JOB_QUEUE_SUSPENDED;
+use crate::error::AppError;
+use crate::sync_utils::MutexExt;
+
+/// Errors that mean "try again once a slot frees up or the queue resumes".
+pub fn is_capacity_error(err: &AppError) -> bool {
+    matches!(
+        err.code,
+        "job_concurrency_limit" | "job_exclusive_blocked" | JOB_QUEUE_SUSPENDED
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+    Started,
+    /// Queued at this 1-based position.
+    Queued(usize),
+}
+
+/// Outcome of draining the queue.
+pub struct Pumped<T> {
+    pub started: Vec<String>,
+    /// Jobs that left the queue because they failed to launch.
+    pub failed: Vec<(String, T, AppError)>,
+}
+
+struct Pending<T> {
+    jobs: VecDeque<(String, T)>,
+    /// Job taken off the queue and being launched outside the lock.
+    launching: Option<String>,
+}
+
+/// Launching spawns processes and may probe the input, so it runs outside
+/// the `pending` lock; `launch` keeps launches one at a time so a job
+/// never overtakes one taken off the queue before it.
+pub struct JobQueue<T> {
+    pending: Mutex<Pending<T>>,
+    launch: Mutex<()>,
+}
+
+impl<T> Default for JobQueue<T> {
+    fn default() -> Self {
+        Self {
+            pending: Mutex::new(Pending {
+                jobs: VecDeque::new(),
+                launching: None,
+            }),
+            launch: Mutex::new(()),
+        }
+    }
+}
+
+impl<T> JobQueue<T> {
+    /// Launches the job right away when nothing is waiting or launching and
+    /// a slot is free, otherwise queues it behind the jobs already waiting.
+    pub fn submit(
+        &self,
+        job_id: String,
+        job: T,
+        mut launch: impl FnMut(&str, &T) -> Result<(), AppError>,
+    ) -> Result<Submission, AppError> {
+        let mut pending = self.pending.lock_or_recover();
+        if pending.launching.as_deref() == Some(job_id.as_str())
+            || pending.jobs.iter().any(|(id, _)| *id == job_id)
+        {
+            return Err(AppError::new(
+                "job_already_running",
+                format!("Job {job_id} is already queued."),
+            ));
+        }
+
+        let launch_guard = if pending.jobs.is_empty() {
+            self.launch.try_lock_or_recover()
+        } else {
+            None
+        };
+        let Some(launch_guard) = launch_guard else {
+            pending.jobs.push_back((job_id, job));
+            return Ok(Submission::Queued(pending.jobs.len()));
+        };
+        pending.launching = Some(job_id.clone());
+        drop(pending);
+
+        let result = launch(&job_id, &job);
+
+        let mut pending = self.pending.lock_or_recover();
+        pending.launching = None;
+        let submission = match result {
+            Ok(()) => Ok(Submission::Started),
+            Err(err) if !is_capacity_error(&err) => Err(err),
+            Err(_) => {
+                // Anything submitted meanwhile queued behind this job.
+                pending.jobs.push_front((job_id, job));
+                Ok(Submission::Queued(1))
+            },
+        };
+        // Released under `pending` so a submit never sees an empty queue
+        // while a launcher is about to stop looking at it.
+        drop(launch_guard);
+        submission
+    }
+
+    /// Launches queued jobs from the head until one has to keep waiting.
+    pub fn pump(&self, mut launch: impl FnMut(&str, &T) -> Result<(), AppError>) -> Pumped<T> {
+        let mut pumped = Pumped {
+            started: Vec::new(),
+            failed: Vec::new(),
+        };
+        let launch_guard = self.launch.lock_or_recover();
+
+        loop {
+            let mut pending = self.pending.lock_or_recover();
+            let Some((job_id, job)) = pending.jobs.pop_front() else {
+                drop(launch_guard);
+                break;
+            };
+            pending.launching = Some(job_id.clone());
+            drop(pending);
+
+            let result = launch(&job_id, &job);
+
+            let mut pending = self.pending.lock_or_recover();
+            pending.launching = None;
+            match result {
+                Ok(()) => pumped.started.push(job_id),
+                Err(err) if is_capacity_error(&err) => {
+                    pending.jobs.push_front((job_id, job));
+                    drop(launch_guard);
+                    break;
+                },
+                Err(err) => pumped.failed.push((job_id, job, err)),
+            }
+        }
+        pumped
+    }
+
+    /// Blocks while `job_id` is being launched, so a caller then finds it
+    /// either back in the queue or running.
+    pub fn wait_for_launch(&self, job_id: &str) {
+        let launching = self.pending.lock_or_recover().launching.as_deref() == Some(job_id);
+        if launching {
+            drop(self.launch.lock_or_recover());
+        }
+    }
+
+    /// Empties the queue, returning the jobs in order.
+    pub fn drain(&self) -> Vec<(String, T)> {
+        self.pending.lock_or_recover().jobs.drain(..).collect()
+    }
+
+    pub fn remove(&self, job_id: &str) -> Option<T> {
+        let mut pending = self.pending.lock_or_recover();
+        let index = pending.jobs.iter().position(|(id, _)| id == job_id)?;
+        pending.jobs.remove(index).map(|(_, job)| job)
+    }
+
+    /// Calls `visit` with each queued job and its 1-based position.
+    pub fn for_each_position(&self, mut visit: impl FnMut(&str, &T, usize, usize)) {
+        let pending = self.pending.lock_or_recover();
+        let length = pending.jobs.len();
+        for (index, (job_id, job)) in pending.jobs.iter().enumerate() {
+            visit(job_id, job, index + 1, length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Launcher with `slots` free slots that records what it started.
+    struct Slots {
+        free: RefCell<usize>,
+        started: RefCell<Vec<String>>,
+    }
+
+    impl Slots {
+        fn new(free: usize) -> Self {
+            Self {
+                free: RefCell::new(free),
+                started: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn launch(&self, job_id: &str, fails: &bool) -> Result<(), AppError> {
+            let mut free = self.free.borrow_mut();
+            if *free == 0 {
+                return Err(AppError::new("job_concurrency_limit", "full"));
+            }
+            if *fails {
+                return Err(AppError::new("job_spawn_failed", "boom"));
+            }
+            *free -= 1;
+            self.started.borrow_mut().push(job_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn jobs_beyond_the_limit_wait_in_order() {
+        let queue = JobQueue::default();
+        let slots = Slots::new(1);
+        let submit = |id: &str| queue.submit(id.into(), false, |id, job| slots.launch(id, job));
+
+        assert_eq!(submit("a").unwrap(), Submission::Started);
+        assert_eq!(submit("b").unwrap(), Submission::Queued(1));
+        assert_eq!(submit("c").unwrap(), Submission::Queued(2));
+        assert_eq!(submit("b").unwrap_err().code, "job_already_running");
+
+        *slots.free.borrow_mut() = 1;
+        let pumped = queue.pump(|id, job| slots.launch(id, job));
+        assert_eq!(pumped.started, vec!["b"]);
+
+        let mut positions = Vec::new();
+        queue.for_each_position(|id, _, position, length| {
+            positions.push((id.to_string(), position, length))
+        });
+        assert_eq!(positions, vec![("c".to_string(), 1, 1)]);
+    }
+
+    #[test]
+    fn new_jobs_do_not_overtake_queued_ones() {
+        let queue = JobQueue::default();
+        let slots = Slots::new(0);
+        queue
+            .submit("a".into(), false, |id, job| slots.launch(id, job))
+            .unwrap();
+
+        // A slot frees up, but "a" is still waiting, so "b" queues behind it.
+        *slots.free.borrow_mut() = 1;
+        let submitted = queue
+            .submit("b".into(), false, |id, job| slots.launch(id, job))
+            .unwrap();
+        assert_eq!(submitted, Submission::Queued(2));
+
+        queue.pump(|id, job| slots.launch(id, job));
+        assert_eq!(*slots.started.borrow(), vec!["a"]);
+    }
+
+    #[test]
+    fn failed_launches_leave_the_queue() {
+        let queue = JobQueue::default();
+        let slots = Slots::new(0);
+        for (id, fails) in [("a", true), ("b", false)] {
+            queue
+                .submit(id.into(), fails, |id, job| slots.launch(id, job))
+                .unwrap();
+        }
+
+        *slots.free.borrow_mut() = 1;
+        let pumped = queue.pump(|id, job| slots.launch(id, job));
+        assert_eq!(pumped.failed.len(), 1);
+        assert_eq!(pumped.failed[0].0, "a");
+        assert_eq!(pumped.started, vec!["b"]);
+    }
+
+    #[test]
+    fn immediate_failures_are_not_queued() {
+        let queue = JobQueue::default();
+        let slots = Slots::new(1);
+        let err = queue
+            .submit("a".into(), true, |id, job| slots.launch(id, job))
+            .unwrap_err();
+        assert_eq!(err.code, "job_spawn_failed");
+        assert!(queue.remove("a").is_none());
+    }
+
+    #[test]
+    fn launches_run_outside_the_queue_lock() {
+        let queue = JobQueue::default();
+        let slots = Slots::new(0);
+        for id in ["a", "b"] {
+            queue
+                .submit(id.into(), false, |id, job| slots.launch(id, job))
+                .unwrap();
+        }
+
+        *slots.free.borrow_mut() = 1;
+        let mut seen = Vec::new();
+        let pumped = queue.pump(|id, job| {
+            // Each of these would deadlock if the queue were still locked.
+            queue.for_each_position(|waiting, _, position, _| {
+                seen.push((id.to_string(), waiting.to_string(), position))
+            });
+            if id == "a" {
+                let again = queue.submit("a".into(), false, |_, _| unreachable!());
+                assert_eq!(again.unwrap_err().code, "job_already_running");
+                let behind = queue.submit("c".into(), false, |_, _| unreachable!());
+                assert_eq!(behind.unwrap(), Submission::Queued(2));
+            }
+            slots.launch(id, job)
+        });
+
+        assert_eq!(pumped.started, vec!["a"]);
+        assert_eq!(seen[0], ("a".to_string(), "b".to_string(), 1));
+        let mut positions = Vec::new();
+        queue.for_each_position(|id, _, position, _| positions.push((id.to_string(), position)));
+        assert_eq!(positions, vec![("b".to_string(), 1), ("c".to_string(), 2)]);
+    }
+}
@@ -1,76 +1,166 @@
+use super::job_store::{JobStore, PersistedJob};
+use super::power::SleepInhibitor;
 use super::progress_monitor::RunningProcess;
 use crate::error::AppError;
+use crate::sync_utils::MutexExt;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 pub struct JobRegistry {
     records: Mutex<HashMap<String, JobRecord>>,
+    store: Arc<JobStore>,
+    /// Kept active while any job is registered.
+    inhibitor: Arc<SleepInhibitor>,
 }
 
 impl JobRegistry {
+    #[cfg(test)]
     pub fn new() -> Self {
+        Self::with_store(Arc::new(JobStore::default()))
+    }
+
+    /// Registry that mirrors persisted records into `store`.
+    pub fn with_store(store: Arc<JobStore>) -> Self {
         Self {
             records: Mutex::new(HashMap::new()),
+            store,
+            inhibitor: Arc::new(SleepInhibitor::default()),
         }
     }
 
+    pub fn with_inhibitor(mut self, inhibitor: Arc<SleepInhibitor>) -> Self {
+        self.inhibitor = inhibitor;
+        self
+    }
+
     pub fn register(
         &self,
         job_id: String,
         record: JobRecord,
         max_concurrency: usize,
     ) -> Result<(), AppError> {
-        let mut guard = self.records.lock().expect("job registry poisoned");
-        if guard.contains_key(&job_id) {
+        let mut guard = self.records.lock_or_recover();
+        Self::admit(
+            &guard,
+            &job_id,
+            record.exclusive,
+            record.class,
+            max_concurrency,
+        )?;
+
+        if let Some(persisted) = &record.persisted {
+            self.store.upsert(persisted.clone());
+        }
+        guard.insert(job_id, record);
+        self.inhibitor.set_active(true);
+        Ok(())
+    }
+
+    /// Checks whether a job could be registered right now, so callers can
+    /// avoid spawning processes that would be turned away.
+    pub fn check_admission(
+        &self,
+        job_id: &str,
+        exclusive: bool,
+        class: JobClass,
+        max_concurrency: usize,
+    ) -> Result<(), AppError> {
+        let guard = self.records.lock_or_recover();
+        Self::admit(&guard, job_id, exclusive, class, max_concurrency)
+    }
+
+    fn admit(
+        records: &HashMap<String, JobRecord>,
+        job_id: &str,
+        exclusive: bool,
+        class: JobClass,
+        max_concurrency: usize,
+    ) -> Result<(), AppError> {
+        if records.contains_key(job_id) {
             return Err(AppError::new(
                 "job_already_running",
                 format!("Job {job_id} is already running."),
             ));
         }
 
-        if record.exclusive && !guard.is_empty() {
+        if exclusive && !records.is_empty() {
             return Err(AppError::new(
                 "job_exclusive_blocked",
                 "Exclusive job requested while other jobs are active.",
             ));
         }
 
-        if guard.values().any(|entry| entry.exclusive) {
+        if records.values().any(|entry| entry.exclusive) {
             return Err(AppError::new(
                 "job_exclusive_blocked",
                 "Another exclusive job is currently running.",
             ));
         }
 
-        if guard.len() >= max_concurrency.max(1) {
+        let active_in_class = records
+            .values()
+            .filter(|entry| entry.class == class)
+            .count();
+        if active_in_class >= max_concurrency.max(1) {
             return Err(AppError::new(
                 "job_concurrency_limit",
                 format!("Concurrency limit reached ({max_concurrency}); defer job start."),
             ));
         }
-
-        guard.insert(job_id, record);
         Ok(())
     }
 
     pub fn snapshot(&self, job_id: &str) -> Option<JobSnapshot> {
-        let guard = self.records.lock().ok()?;
+        let guard = self.records.lock_or_recover();
         guard.get(job_id).map(|record| record.snapshot())
     }
 
+    /// Snapshots every active job under a single lock, ordered by id.
+    pub fn snapshot_all(&self) -> Vec<(String, JobSnapshot)> {
+        let guard = self.records.lock_or_recover();
+        let mut snapshots: Vec<(String, JobSnapshot)> = guard
+            .iter()
+            .map(|(job_id, record)| (job_id.clone(), record.snapshot()))
+            .collect();
+        snapshots.sort_by(|a, b| a.0.cmp(&b.0));
+        snapshots
+    }
+
+    /// Number of registered jobs, verifications included.
+    pub fn active_count(&self) -> usize {
+        self.records.lock_or_recover().len()
+    }
+
     pub fn remove(&self, job_id: &str) -> Option<JobRecord> {
-        let mut guard = self.records.lock().ok()?;
-        guard.remove(job_id)
+        let mut guard = self.records.lock_or_recover();
+        let record = guard.remove(job_id)?;
+        if record.persisted.is_some() {
+            self.store.remove(job_id);
+        }
+        if guard.is_empty() {
+            self.inhibitor.set_active(false);
+        }
+        Some(record)
     }
 }
 
+/// Concurrency class; each class is limited independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobClass {
+    Conversion,
+    Verification,
+}
+
 pub struct JobRecord {
     pub process: Arc<RunningProcess>,
     #[allow(dead_code)]
     pub final_path: PathBuf,
     pub temp_path: PathBuf,
     pub exclusive: bool,
+    pub class: JobClass,
+    /// Set for jobs that should be restorable after a restart.
+    pub persisted: Option<PersistedJob>,
 }
 
 impl JobRecord {
@@ -85,9 +175,21 @@ impl JobRecord {
             final_path,
             temp_path,
             exclusive,
+            class: JobClass::Conversion,
+            persisted: None,
         }
     }
 
+    pub fn with_class(mut self, class: JobClass) -> Self {
+        self.class = class;
+        self
+    }
+
+    pub fn with_persisted(mut self, job: PersistedJob) -> Self {
+        self.persisted = Some(job);
+        self
+    }
+
     fn snapshot(&self) -> JobSnapshot {
         JobSnapshot {
             process: Arc::clone(&self.process),
@@ -179,4 +281,120 @@ mod tests {
             .expect_err("should hit limit");
         assert_eq!(err.code, "job_concurrency_limit");
     }
+
+    #[test]
+    fn concurrency_limits_apply_per_class() {
+        let registry = JobRegistry::new();
+        registry
+            .register(
+                "convert".into(),
+                JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false),
+                1,
+            )
+            .expect("insert conversion");
+        registry
+            .register(
+                "verify-a".into(),
+                JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false)
+                    .with_class(JobClass::Verification),
+                1,
+            )
+            .expect("verification has its own slot");
+
+        let err = registry
+            .register(
+                "verify-b".into(),
+                JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false)
+                    .with_class(JobClass::Verification),
+                1,
+            )
+            .expect_err("verification slot is taken");
+        assert_eq!(err.code, "job_concurrency_limit");
+    }
+
+    #[test]
+    fn a_panicking_job_thread_does_not_wedge_the_registry() {
+        let registry = Arc::new(JobRegistry::new());
+        registry
+            .register(
+                "crashed".into(),
+                JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false),
+                4,
+            )
+            .unwrap();
+        let poisoner = Arc::clone(&registry);
+        let _ = std::thread::spawn(move || {
+            let _records = poisoner.records.lock().unwrap();
+            panic!("monitor thread crashed");
+        })
+        .join();
+        assert!(registry.records.is_poisoned());
+
+        assert!(registry.snapshot("crashed").is_some());
+        assert!(registry.remove("crashed").is_some());
+        registry
+            .register(
+                "next".into(),
+                JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false),
+                1,
+            )
+            .expect("registry accepts jobs after a panic");
+        assert_eq!(registry.active_count(), 1);
+    }
+
+    #[test]
+    fn inhibitor_is_active_until_the_registry_drains() {
+        let inhibitor = Arc::new(SleepInhibitor::default());
+        inhibitor.set_enabled(false);
+        let registry = JobRegistry::new().with_inhibitor(Arc::clone(&inhibitor));
+        for job_id in ["a", "b"] {
+            registry
+                .register(
+                    job_id.into(),
+                    JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false),
+                    4,
+                )
+                .unwrap();
+        }
+        assert!(inhibitor.is_active());
+
+        registry.remove("a");
+        assert!(inhibitor.is_active());
+        registry.remove("b");
+        assert!(!inhibitor.is_active());
+    }
+
+    #[test]
+    fn persisted_records_are_mirrored_to_the_store() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("jobs.json");
+        let store = Arc::new(JobStore::default());
+        store.attach_path(path.clone());
+        let registry = JobRegistry::with_store(Arc::clone(&store));
+
+        let persisted = PersistedJob::new(
+            "kept",
+            &["-i".into()],
+            std::path::Path::new("/out/a.mp4"),
+            false,
+        );
+        for id in ["kept", "finished"] {
+            registry
+                .register(
+                    id.into(),
+                    JobRecord::new(stub_process(), PathBuf::new(), PathBuf::new(), false)
+                        .with_persisted(PersistedJob {
+                            job_id: id.into(),
+                            ..persisted.clone()
+                        }),
+                    4,
+                )
+                .unwrap();
+        }
+        registry.remove("finished");
+
+        let next_session = JobStore::default();
+        next_session.attach_path(path);
+        assert_eq!(next_session.take_restorable(), vec![persisted]);
+    }
 }
@@ -0,0 +1,292 @@
+//! Persists active jobs so they survive a quit or crash.
+//!
+//! Every queued or running conversion is written to `jobs.json` in the app data
+//! directory and removed again once it completes, fails or is cancelled.
+//! Whatever is still in the file at the next launch was interrupted; it is
+//! kept aside as restorable until `restore_jobs` takes it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    api::{JobMetadata, NativePath, OverwritePolicy},
+    error::AppError,
+    persistence::{self, DocumentSchema},
+    sync_utils::MutexExt,
+};
+
+const JOB_STORE_FILE_NAME: &str = "jobs.json";
+const JOB_STORE_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "job queue",
+    migrations: &[],
+};
+
+/// Everything needed to start a job again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedJob {
+    pub job_id: String,
+    pub args: Vec<NativePath>,
+    pub output_path: NativePath,
+    pub exclusive: bool,
+    /// Still waiting in the queue rather than running.
+    #[serde(default)]
+    pub queued: bool,
+    /// Per-job priority choice; `None` follows the global preference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_priority: Option<bool>,
+    /// Parent jobs that must succeed first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub overwrite: OverwritePolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_sec: Option<f64>,
+    #[serde(default)]
+    pub segmented: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    #[serde(default)]
+    pub trash_source: bool,
+    #[serde(default)]
+    pub preserve_timestamps: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: JobMetadata,
+    /// Built from a typed command rather than raw arguments.
+    #[serde(default)]
+    pub structured: bool,
+    /// Registered FFmpeg installation the job runs with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ffmpeg_installation: Option<String>,
+    /// Milliseconds since the Unix epoch when the job was registered.
+    pub saved_at_ms: u64,
+}
+
+impl PersistedJob {
+    pub fn new(job_id: &str, args: &[OsString], output_path: &Path, exclusive: bool) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            args: args.iter().map(|arg| arg.as_os_str().into()).collect(),
+            output_path: output_path.into(),
+            exclusive,
+            queued: false,
+            low_priority: None,
+            depends_on: Vec::new(),
+            overwrite: OverwritePolicy::default(),
+            estimated_size: None,
+            preset_id: None,
+            duration_sec: None,
+            segmented: false,
+            threads: None,
+            trash_source: false,
+            preserve_timestamps: false,
+            metadata: JobMetadata::new(),
+            structured: false,
+            ffmpeg_installation: None,
+            saved_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredJobs {
+    jobs: Vec<PersistedJob>,
+}
+
+#[derive(Default)]
+struct StoreState {
+    active: BTreeMap<String, PersistedJob>,
+    /// Jobs left over from the previous session.
+    restorable: BTreeMap<String, PersistedJob>,
+}
+
+#[derive(Default)]
+pub struct JobStore {
+    path: OnceLock<PathBuf>,
+    state: Mutex<StoreState>,
+    /// Set on app exit so jobs killed during shutdown stay restorable.
+    frozen: AtomicBool,
+}
+
+impl JobStore {
+    /// Binds the store to the app data directory on first use.
+    pub fn attach(&self, app: &AppHandle) {
+        if self.path.get().is_some() {
+            return;
+        }
+        match app.path().app_data_dir() {
+            Ok(dir) => self.attach_path(dir.join(JOB_STORE_FILE_NAME)),
+            Err(err) => eprintln!("[jobs] job store unavailable: {err}"),
+        }
+    }
+
+    /// Binds the store to `path`, loading jobs interrupted last session.
+    pub fn attach_path(&self, path: PathBuf) {
+        if self.path.set(path.clone()).is_err() {
+            return;
+        }
+        let stored: StoredJobs = match persistence::load(&path, &JOB_STORE_SCHEMA) {
+            Ok(stored) => stored.unwrap_or_default(),
+            Err(err) => {
+                eprintln!("[jobs] failed loading {}: {}", path.display(), err.message);
+                return;
+            },
+        };
+
+        let mut state = self.state.lock_or_recover();
+        for job in stored.jobs {
+            if !state.active.contains_key(&job.job_id) {
+                state.restorable.insert(job.job_id.clone(), job);
+            }
+        }
+    }
+
+    pub fn upsert(&self, job: PersistedJob) {
+        self.update(|state| {
+            state.restorable.remove(&job.job_id);
+            state.active.insert(job.job_id.clone(), job);
+        });
+    }
+
+    pub fn remove(&self, job_id: &str) {
+        self.update(|state| {
+            state.active.remove(job_id);
+        });
+    }
+
+    /// Hands over the interrupted jobs, dropping them from the store.
+    pub fn take_restorable(&self) -> Vec<PersistedJob> {
+        let mut taken = Vec::new();
+        self.update(|state| {
+            taken = std::mem::take(&mut state.restorable)
+                .into_values()
+                .collect();
+        });
+        taken.sort_by_key(|job| job.saved_at_ms);
+        taken
+    }
+
+    /// Stops recording changes; what is on disk now is what the next launch
+    /// restores.
+    pub fn freeze(&self) {
+        self.frozen.store(true, Ordering::SeqCst);
+    }
+
+    fn update(&self, change: impl FnOnce(&mut StoreState)) {
+        if self.frozen.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut state = self.state.lock_or_recover();
+        change(&mut state);
+
+        if let Some(path) = self.path.get() {
+            if let Err(err) = write(path, &state) {
+                eprintln!("[jobs] failed saving {}: {}", path.display(), err.message);
+            }
+        }
+    }
+}
+
+fn write(path: &Path, state: &StoreState) -> Result<(), AppError> {
+    let stored = StoredJobs {
+        jobs: state
+            .restorable
+            .values()
+            .chain(state.active.values())
+            .cloned()
+            .collect(),
+    };
+    persistence::persist(path, &JOB_STORE_SCHEMA, &stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn job(id: &str, saved_at_ms: u64) -> PersistedJob {
+        PersistedJob {
+            saved_at_ms,
+            ..PersistedJob::new(
+                id,
+                &["-i".into(), "in.mov".into()],
+                Path::new("/out/a.mp4"),
+                false,
+            )
+        }
+    }
+
+    #[test]
+    fn interrupted_jobs_are_restorable_next_session() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(JOB_STORE_FILE_NAME);
+
+        let first = JobStore::default();
+        first.attach_path(path.clone());
+        first.upsert(job("b", 2));
+        first.upsert(job("a", 1));
+        first.upsert(job("done", 3));
+        first.remove("done");
+        drop(first);
+
+        let second = JobStore::default();
+        second.attach_path(path.clone());
+        second.upsert(job("new", 4));
+
+        let restored = second.take_restorable();
+        assert_eq!(
+            restored
+                .iter()
+                .map(|job| job.job_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert!(second.take_restorable().is_empty());
+
+        // Only the job still running in this session remains on disk.
+        let third = JobStore::default();
+        third.attach_path(path);
+        let remaining = third.take_restorable();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].job_id, "new");
+    }
+
+    #[test]
+    fn frozen_store_keeps_jobs_killed_on_exit() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(JOB_STORE_FILE_NAME);
+
+        let first = JobStore::default();
+        first.attach_path(path.clone());
+        first.upsert(job("a", 1));
+        first.freeze();
+        first.remove("a");
+        drop(first);
+
+        let second = JobStore::default();
+        second.attach_path(path);
+        assert_eq!(second.take_restorable().len(), 1);
+    }
+
+    #[test]
+    fn unattached_store_keeps_jobs_in_memory() {
+        let store = JobStore::default();
+        store.upsert(job("a", 1));
+        store.remove("a");
+        assert!(store.take_restorable().is_empty());
+    }
+}
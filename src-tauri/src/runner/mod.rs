@@ -1,11 +1,29 @@
+pub mod battery;
+pub mod command_builder;
 pub mod concurrency;
 pub mod coordinator;
+pub mod disk_space;
 pub mod events;
 pub mod external;
+pub mod history;
+pub mod hooks;
+pub mod job_graph;
+pub mod job_log;
+pub mod job_queue;
 pub mod job_registry;
+pub mod job_store;
+pub mod output_check;
 pub mod output_manager;
+pub mod power;
 pub mod process_spawner;
 pub mod progress_monitor;
+pub mod retry;
+pub mod segmented;
+pub mod severity;
+pub mod source_watch;
+pub mod triage;
 pub mod validator;
+pub mod verification;
+pub mod watchdog;
 
 pub use progress_monitor::RunningProcess;
@@ -0,0 +1,264 @@
+//! Post-conversion sanity check of finished outputs.
+//!
+//! ffmpeg can exit cleanly and still leave a truncated or unreadable file
+//! (a full disk, a dying network share). Once a job's output is finalized
+//! it is probed alongside its source; a missing stream kind or a clearly
+//! shorter duration fails the job as `job_output_invalid`.
+
+use std::{
+    ffi::{OsStr, OsString},
+    path::Path,
+};
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::{error::AppError, ffmpeg_probe, remote_input};
+
+pub const JOB_OUTPUT_INVALID: &str = "job_output_invalid";
+
+/// Share of the source duration an output must reach.
+const MIN_DURATION_RATIO: f64 = 0.98;
+/// Slack for container and encoder rounding, in seconds.
+const DURATION_SLACK_SEC: f64 = 0.5;
+
+/// Arguments that legitimately change the duration, which rule out the
+/// duration comparison.
+const DURATION_ARGS: &[&str] = &[
+    "-t",
+    "-to",
+    "-ss",
+    "-sseof",
+    "-frames",
+    "-frames:v",
+    "-shortest",
+];
+/// Filtergraphs can produce any layout, which rules out the stream check.
+const FILTERGRAPH_ARGS: &[&str] = &["-filter_complex", "-lavfi"];
+
+/// What a probe says about a file, as far as the check cares.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaShape {
+    pub duration_sec: f64,
+    pub video_streams: usize,
+    pub audio_streams: usize,
+    pub streams: usize,
+}
+
+impl MediaShape {
+    /// Reads the shape from raw `ffprobe` JSON. Cover art is not counted
+    /// as video.
+    pub fn from_probe(raw: &Value) -> Self {
+        let streams = raw["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let count = |kind: &str| {
+            streams
+                .iter()
+                .filter(|stream| stream["codec_type"] == kind)
+                .filter(|stream| stream["disposition"]["attached_pic"] != 1)
+                .count()
+        };
+        Self {
+            duration_sec: raw["format"]["duration"]
+                .as_str()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            video_streams: count("video"),
+            audio_streams: count("audio"),
+            streams: streams.len(),
+        }
+    }
+}
+
+/// Probes `source` and `output`. Returns `Ok(false)` when the check could
+/// not run because the source itself can't be probed (no ffprobe, an input
+/// ffprobe doesn't understand, or a network source that may be live or
+/// have changed), and an error when the output is unreadable or doesn't
+/// match the source.
+pub fn verify(app: &AppHandle, args: &[OsString], output: &Path) -> Result<bool, AppError> {
+    let Some(source) = input_path(args).filter(|source| !is_network_source(source)) else {
+        return Ok(false);
+    };
+    let Ok(source) = ffmpeg_probe::probe_media(app, source) else {
+        return Ok(false);
+    };
+    let output_probe = ffmpeg_probe::probe_media(app, output).map_err(|err| {
+        AppError::new(
+            JOB_OUTPUT_INVALID,
+            format!("Output {} is unreadable: {}", output.display(), err.message),
+        )
+    })?;
+
+    // Only option names and stream maps are compared, so a lossy copy of a
+    // non-UTF-8 path argument does no harm.
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    compare(
+        &MediaShape::from_probe(&source.raw),
+        &MediaShape::from_probe(&output_probe.raw),
+        &args,
+    )
+    .map_err(|problem| {
+        AppError::new(
+            JOB_OUTPUT_INVALID,
+            format!("Output {} {problem}.", output.display()),
+        )
+    })?;
+    Ok(true)
+}
+
+/// First input file named by `args`, without the `file:` prefix typed
+/// commands give local paths.
+pub fn input_path(args: &[OsString]) -> Option<&OsStr> {
+    args.windows(2)
+        .find(|pair| pair[0] == "-i")
+        .map(|pair| without_file_protocol(&pair[1]).as_os_str())
+}
+
+/// Whether `input` is an http(s), RTSP or SRT URL.
+pub fn is_network_source(input: &OsStr) -> bool {
+    input.to_str().is_some_and(remote_input::is_network_url)
+}
+
+/// `input` as a path, without the `file:` prefix typed commands add to
+/// local paths.
+pub fn without_file_protocol(input: &OsStr) -> &Path {
+    let bytes = input.as_encoded_bytes();
+    match bytes.strip_prefix(b"file:") {
+        // SAFETY: the rest follows an ASCII prefix of an OS string, which is
+        // a valid split point of its encoding.
+        Some(rest) => Path::new(unsafe { OsStr::from_encoded_bytes_unchecked(rest) }),
+        None => Path::new(input),
+    }
+}
+
+/// Whether `args` keep streams of `kind` (`v` or `a`): not disabled with
+/// `-vn`/`-an`, and selected by a `-map` when any are given. Optional maps
+/// (`0:a:0?`) count, so a source without that kind passes anyway.
+fn keeps(args: &[String], kind: char) -> bool {
+    if args.iter().any(|arg| *arg == format!("-{kind}n")) {
+        return false;
+    }
+    let mut maps = args
+        .windows(2)
+        .filter(|pair| pair[0] == "-map")
+        .map(|pair| pair[1].as_str())
+        .filter(|map| !map.starts_with('-'))
+        .peekable();
+    if maps.peek().is_none() {
+        return true;
+    }
+    maps.any(|map| {
+        let mut parts = map.trim_end_matches('?').split(':').skip(1);
+        match parts.next() {
+            None => true,
+            Some(selector) => selector.starts_with(kind) || selector.starts_with('#'),
+        }
+    })
+}
+
+fn compare(source: &MediaShape, output: &MediaShape, args: &[String]) -> Result<(), String> {
+    if output.streams == 0 {
+        return Err("has no streams".into());
+    }
+    let has_any = |flags: &[&str]| args.iter().any(|arg| flags.contains(&arg.as_str()));
+
+    if !has_any(FILTERGRAPH_ARGS) {
+        if source.video_streams > 0 && output.video_streams == 0 && keeps(args, 'v') {
+            return Err("is missing its video stream".into());
+        }
+        if source.audio_streams > 0 && output.audio_streams == 0 && keeps(args, 'a') {
+            return Err("is missing its audio stream".into());
+        }
+    }
+
+    let still_image = source.video_streams > 0 && source.duration_sec == 0.0;
+    if !has_any(DURATION_ARGS) && !still_image {
+        let expected = source.duration_sec * MIN_DURATION_RATIO - DURATION_SLACK_SEC;
+        if output.duration_sec < expected {
+            return Err(format!(
+                "is truncated: {:.1} s of {:.1} s",
+                output.duration_sec, source.duration_sec
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn shape(duration_sec: f64, video_streams: usize, audio_streams: usize) -> MediaShape {
+        MediaShape {
+            duration_sec,
+            video_streams,
+            audio_streams,
+            streams: video_streams + audio_streams,
+        }
+    }
+
+    #[test]
+    fn probe_json_is_summarized() {
+        let raw = json!({
+            "format": { "duration": "61.250000" },
+            "streams": [
+                { "codec_type": "video" },
+                { "codec_type": "audio" },
+                { "codec_type": "video", "disposition": { "attached_pic": 1 } },
+            ],
+        });
+        assert_eq!(
+            MediaShape::from_probe(&raw),
+            MediaShape {
+                duration_sec: 61.25,
+                video_streams: 1,
+                audio_streams: 1,
+                streams: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_or_incomplete_outputs_are_rejected() {
+        let source = shape(120.0, 1, 1);
+        let plain = args(&["-i", "in.mov", "-c:v", "libx264"]);
+
+        assert!(compare(&source, &shape(119.8, 1, 1), &plain).is_ok());
+        assert!(compare(&source, &shape(60.0, 1, 1), &plain)
+            .unwrap_err()
+            .contains("truncated"));
+        assert!(compare(&source, &shape(120.0, 1, 0), &plain)
+            .unwrap_err()
+            .contains("audio"));
+        assert!(compare(&source, &shape(0.0, 0, 0), &plain).is_err());
+    }
+
+    #[test]
+    fn trims_and_dropped_streams_are_expected() {
+        let source = shape(120.0, 1, 1);
+        let trimmed = args(&["-ss", "10", "-i", "in.mov", "-t", "30", "-an"]);
+        assert!(compare(&source, &shape(30.0, 1, 0), &trimmed).is_ok());
+
+        let mapped = args(&["-i", "in.mov", "-map", "0:a"]);
+        assert!(compare(&source, &shape(120.0, 0, 1), &mapped).is_ok());
+        let os_args: Vec<OsString> = mapped.iter().map(OsString::from).collect();
+        assert_eq!(input_path(&os_args), Some(OsStr::new("in.mov")));
+        assert_eq!(
+            without_file_protocol(OsStr::new("file:-in.mov")),
+            Path::new("-in.mov")
+        );
+
+        // Mapped kinds are still expected in the output.
+        let both = args(&[
+            "-i", "in.mov", "-map", "0:v:0?", "-map", "0:a:0?", "-map", "-0:s",
+        ]);
+        assert!(compare(&source, &shape(120.0, 1, 0), &both).is_err());
+    }
+}
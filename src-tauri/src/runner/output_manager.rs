@@ -1,20 +1,47 @@
-use crate::error::AppError;
+use crate::{api::OverwritePolicy, error::AppError, fs_utils::long_path};
+use std::collections::HashSet;
+use std::ffi::OsStr;
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Highest `name (n).ext` suffix tried before giving up on renaming.
+const MAX_RENAME_SUFFIX: u32 = 9_999;
+/// Characters of the job id in temporary file names.
+const TEMP_TAG_LEN: usize = 8;
+
+/// Where a job writes while running and where its output ends up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputTarget {
+    pub final_path: PathBuf,
+    pub temp_path: PathBuf,
+    pub overwrite: OverwritePolicy,
+    /// Source whose timestamps (and, on macOS, extended attributes such as
+    /// Finder tags) the finalized output takes on.
+    pub preserve_from: Option<PathBuf>,
+}
 
 /// Manages output file paths and temporary file creation
 pub struct OutputManager;
 
 impl OutputManager {
-    /// Prepares output path, creating directories and validating permissions
-    pub fn prepare(output_path: &str, _exclusive: bool) -> Result<(PathBuf, PathBuf), AppError> {
-        let output = PathBuf::from(output_path);
+    /// Prepares output path, creating directories and validating permissions.
+    /// An existing output is resolved by `overwrite` before the job starts.
+    /// The temporary file is named after `job_id`, so jobs writing the same
+    /// output name never share one.
+    pub fn prepare(
+        output_path: &Path,
+        job_id: &str,
+        _exclusive: bool,
+        overwrite: OverwritePolicy,
+    ) -> Result<OutputTarget, AppError> {
+        let output = Self::resolve_existing(output_path.to_path_buf(), overwrite)?;
 
         // Create parent directories if needed
         if let Some(parent) = output.parent() {
             if !parent.as_os_str().is_empty() {
-                fs::create_dir_all(parent).map_err(|err| {
+                fs::create_dir_all(long_path(parent)).map_err(|err| {
                     AppError::new(
                         "job_output_directory",
                         format!(
@@ -26,26 +53,110 @@ impl OutputManager {
             }
         }
 
-        // Generate temporary file path
-        let temp_file_name = output
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|name| format!("{name}.tmp"))
-            .unwrap_or_else(|| "output.tmp".to_string());
-        let temp_path = output.with_file_name(temp_file_name);
+        let temp_path = Self::temp_path(&output, job_id);
 
         // Validate write permissions
         Self::validate_permissions(&temp_path, &output)?;
 
-        Ok((output, temp_path))
+        Ok(OutputTarget {
+            final_path: output,
+            temp_path,
+            overwrite,
+            preserve_from: None,
+        })
+    }
+
+    /// Applies `overwrite` when `output` already exists: skipping fails with
+    /// `job_output_exists` and renaming picks the first free `name (n).ext`.
+    /// Overwriting keeps the path; the old file is replaced on finalize.
+    fn resolve_existing(output: PathBuf, overwrite: OverwritePolicy) -> Result<PathBuf, AppError> {
+        if !long_path(&output).exists() {
+            return Ok(output);
+        }
+        match overwrite {
+            OverwritePolicy::Overwrite => Ok(output),
+            OverwritePolicy::Skip => Err(AppError::new(
+                "job_output_exists",
+                format!("{} already exists; skipping the job.", output.display()),
+            )),
+            OverwritePolicy::Rename => Self::available_path(&output).ok_or_else(|| {
+                AppError::new(
+                    "job_output_exists",
+                    format!("No free name left next to {}.", output.display()),
+                )
+            }),
+        }
+    }
+
+    /// `name.ext.<tag>.tmp` beside `output`, where the tag is the start of
+    /// the job id.
+    fn temp_path(output: &Path, job_id: &str) -> PathBuf {
+        let mut name = output
+            .file_name()
+            .map(OsStr::to_os_string)
+            .unwrap_or_else(|| "output".into());
+        let tag: String = job_id
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .take(TEMP_TAG_LEN)
+            .collect();
+        let tag = if tag.is_empty() {
+            "job".to_string()
+        } else {
+            tag
+        };
+        name.push(format!(".{tag}.tmp"));
+        output.with_file_name(name)
+    }
+
+    /// Output names in `dir` that jobs are writing, read from their
+    /// temporary files. Names that aren't UTF-8 are kept lossily.
+    fn names_in_progress(dir: &Path) -> HashSet<String> {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let Ok(entries) = fs::read_dir(long_path(dir)) else {
+            return HashSet::new();
+        };
+        entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let (output, _tag) = name.strip_suffix(".tmp")?.rsplit_once('.')?;
+                Some(output.to_string())
+            })
+            .collect()
+    }
+
+    /// First `name (n).ext` beside `output` that neither exists nor is
+    /// being written by another job.
+    fn available_path(output: &Path) -> Option<PathBuf> {
+        let stem = output.file_stem()?;
+        let in_progress = Self::names_in_progress(output.parent()?);
+        (1..=MAX_RENAME_SUFFIX)
+            .map(|suffix| {
+                let mut name = stem.to_os_string();
+                name.push(format!(" ({suffix})"));
+                if let Some(extension) = output.extension() {
+                    name.push(".");
+                    name.push(extension);
+                }
+                name
+            })
+            .filter(|name| !in_progress.contains(name.to_string_lossy().as_ref()))
+            .map(|name| output.with_file_name(name))
+            .find(|candidate| !long_path(candidate).exists())
     }
 
     /// Validates write permissions by attempting to create the temp file
     fn validate_permissions(temp_path: &Path, output: &Path) -> Result<(), AppError> {
-        match fs::File::create(temp_path) {
+        let temp_path = long_path(temp_path);
+        match fs::File::create(&temp_path) {
             Ok(file) => {
                 drop(file);
-                let _ = fs::remove_file(temp_path);
+                let _ = fs::remove_file(&temp_path);
                 Ok(())
             }
             Err(err) if err.kind() == ErrorKind::PermissionDenied => Err(AppError::new(
@@ -62,27 +173,218 @@ impl OutputManager {
         }
     }
 
-    /// Finalizes output by moving temp file to final location
-    pub fn finalize(temp_path: &Path, final_path: &Path) -> Result<(), AppError> {
+    /// Finalizes output by moving temp file to final location, copying it
+    /// when the two live on different filesystems. A file that appeared at
+    /// the final path while the job ran is handled by the overwrite policy
+    /// again. Returns where the output ended up.
+    pub fn finalize(target: &OutputTarget) -> Result<PathBuf, AppError> {
+        let temp_path = &long_path(&target.temp_path);
+        let final_path = match Self::resolve_existing(target.final_path.clone(), target.overwrite) {
+            Ok(path) => path,
+            Err(err) => {
+                let _ = fs::remove_file(temp_path);
+                return Err(err);
+            },
+        };
+        let destination = long_path(&final_path);
         // Remove any existing output file
-        if final_path.exists() {
-            let _ = fs::remove_file(final_path);
+        if destination.exists() {
+            let _ = fs::remove_file(&destination);
         }
 
         // Atomically move temp file to final location
-        fs::rename(temp_path, final_path).map_err(|err| {
+        let result = match fs::rename(temp_path, &destination) {
+            Err(err) if err.kind() == ErrorKind::CrossesDevices => {
+                Self::copy_across_devices(temp_path, &destination)
+            },
+            result => result,
+        };
+        if let Err(err) = result {
             // Clean up temp file on rename failure
             let _ = fs::remove_file(temp_path);
-            AppError::new(
+            return Err(AppError::new(
                 "job_finalize_failed",
                 format!("Failed to finalize output file: {err}"),
+            ));
+        }
+        if let Some(source) = &target.preserve_from {
+            if let Err(err) = Self::copy_file_info(&long_path(source), &destination) {
+                eprintln!(
+                    "[output] could not copy file info from {} to {}: {err}",
+                    source.display(),
+                    final_path.display()
+                );
+            }
+        }
+        Ok(final_path)
+    }
+
+    /// Gives `destination` the access, modification and (where the
+    /// platform has one) creation time of `source`, plus its extended
+    /// attributes on macOS.
+    fn copy_file_info(source: &Path, destination: &Path) -> std::io::Result<()> {
+        #[cfg(target_os = "macos")]
+        xattrs::copy(source, destination)?;
+
+        let metadata = fs::metadata(source)?;
+        let times = fs::FileTimes::new()
+            .set_accessed(metadata.accessed()?)
+            .set_modified(metadata.modified()?);
+        let times = match metadata.created() {
+            Ok(created) => with_created(times, created),
+            Err(_) => times,
+        };
+        fs::File::options()
+            .write(true)
+            .open(destination)?
+            .set_times(times)
+    }
+
+    /// Moves a file to another filesystem: copies it beside the
+    /// destination, flushes it to disk, renames it into place and only
+    /// then removes the source, so a failure never leaves a partial output.
+    fn copy_across_devices(source: &Path, destination: &Path) -> std::io::Result<()> {
+        let staging_name = destination
+            .file_name()
+            .map(|name| format!("{}.partial", name.to_string_lossy()))
+            .unwrap_or_else(|| "output.partial".to_string());
+        let staging = destination.with_file_name(staging_name);
+
+        let copied = fs::copy(source, &staging)
+            .and_then(|_| fs::File::open(&staging)?.sync_all())
+            .and_then(|()| fs::rename(&staging, destination));
+        if let Err(err) = copied {
+            let _ = fs::remove_file(&staging);
+            return Err(err);
+        }
+        fs::remove_file(source)
+    }
+
+    /// Moves a conversion's source to the OS trash, refusing when it is
+    /// the output itself.
+    pub fn trash_source(source: &Path, output: &Path) -> Result<(), AppError> {
+        let same_file = match (fs::canonicalize(source), fs::canonicalize(output)) {
+            (Ok(source), Ok(output)) => source == output,
+            _ => source == output,
+        };
+        if same_file {
+            return Err(AppError::new(
+                "job_trash_failed",
+                format!("Kept {}: it is also the output.", source.display()),
+            ));
+        }
+        trash::delete(source).map_err(|err| {
+            AppError::new(
+                "job_trash_failed",
+                format!("Failed to move {} to the trash: {err}", source.display()),
             )
         })
     }
 
     /// Cleans up a temporary file
     pub fn cleanup_temp(temp_path: &Path) {
-        let _ = fs::remove_file(temp_path);
+        let _ = fs::remove_file(long_path(temp_path));
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn with_created(times: fs::FileTimes, created: SystemTime) -> fs::FileTimes {
+    use std::os::macos::fs::FileTimesExt;
+    times.set_created(created)
+}
+
+#[cfg(windows)]
+fn with_created(times: fs::FileTimes, created: SystemTime) -> fs::FileTimes {
+    use std::os::windows::fs::FileTimesExt;
+    times.set_created(created)
+}
+
+/// Other platforms can't set a creation time.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn with_created(times: fs::FileTimes, _created: SystemTime) -> fs::FileTimes {
+    times
+}
+
+/// Extended attributes, which carry Finder tags, comments and colour labels.
+#[cfg(target_os = "macos")]
+mod xattrs {
+    use std::ffi::{CStr, CString};
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    /// Marks downloads for Gatekeeper; a converted file shouldn't inherit it.
+    const SKIPPED: &[&[u8]] = &[b"com.apple.quarantine"];
+
+    fn c_path(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+    }
+
+    pub fn copy(source: &Path, destination: &Path) -> io::Result<()> {
+        let (source, destination) = (c_path(source)?, c_path(destination)?);
+        let names = read(|buffer, size| {
+            // SAFETY: `buffer` is null or writable for `size` bytes.
+            unsafe { libc::listxattr(source.as_ptr(), buffer, size, libc::XATTR_NOFOLLOW) }
+        })?;
+        for name in names
+            .split(|byte| *byte == 0)
+            .filter(|name| !name.is_empty())
+        {
+            if SKIPPED.contains(&name) {
+                continue;
+            }
+            let name = CString::new(name)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let value = read(|buffer, size| {
+                // SAFETY: as above; `name` is NUL-terminated.
+                unsafe {
+                    libc::getxattr(
+                        source.as_ptr(),
+                        name.as_ptr(),
+                        buffer.cast(),
+                        size,
+                        0,
+                        libc::XATTR_NOFOLLOW,
+                    )
+                }
+            })?;
+            // SAFETY: `value` is readable for its length.
+            let status = unsafe {
+                libc::setxattr(
+                    destination.as_ptr(),
+                    name.as_ptr(),
+                    value.as_ptr().cast(),
+                    value.len(),
+                    0,
+                    libc::XATTR_NOFOLLOW,
+                )
+            };
+            if status != 0 {
+                return Err(annotate(&name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `get` once to size the buffer and again to fill it.
+    fn read(get: impl Fn(*mut libc::c_char, usize) -> isize) -> io::Result<Vec<u8>> {
+        let size = get(std::ptr::null_mut(), 0);
+        if size < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut buffer = vec![0u8; size as usize];
+        let read = get(buffer.as_mut_ptr().cast(), buffer.len());
+        if read < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        buffer.truncate(read as usize);
+        Ok(buffer)
+    }
+
+    fn annotate(name: &CStr) -> io::Error {
+        let err = io::Error::last_os_error();
+        io::Error::new(err.kind(), format!("{}: {err}", name.to_string_lossy()))
     }
 }
 
@@ -96,7 +398,7 @@ mod tests {
         let temp_dir = std::env::temp_dir();
         let test_path = temp_dir.join("test_honeymelon_output/file.mp4");
 
-        let result = OutputManager::prepare(test_path.to_str().unwrap(), false);
+        let result = OutputManager::prepare(&test_path, "job", false, OverwritePolicy::Overwrite);
         assert!(result.is_ok());
 
         // Cleanup
@@ -113,12 +415,151 @@ mod tests {
         File::create(&temp_file).unwrap();
 
         // Finalize
-        let result = OutputManager::finalize(&temp_file, &final_file);
-        assert!(result.is_ok());
+        let result = OutputManager::finalize(&OutputTarget {
+            final_path: final_file.clone(),
+            temp_path: temp_file.clone(),
+            overwrite: OverwritePolicy::Overwrite,
+            preserve_from: None,
+        });
+        assert_eq!(result.unwrap(), final_file);
         assert!(!temp_file.exists());
         assert!(final_file.exists());
 
         // Cleanup
         let _ = fs::remove_file(&final_file);
     }
+
+    #[test]
+    fn existing_outputs_follow_the_overwrite_policy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("clip.mp4");
+        fs::write(&output, b"original").unwrap();
+        fs::write(dir.path().join("clip (1).mp4.0a1b2c3d.tmp"), b"").unwrap();
+
+        let err = OutputManager::prepare(&output, "job", false, OverwritePolicy::Skip).unwrap_err();
+        assert_eq!(err.code, "job_output_exists");
+
+        // "clip (1)" is being written by another job, so "clip (2)" is next.
+        let target =
+            OutputManager::prepare(&output, "3f2504e0-4f89", false, OverwritePolicy::Rename)
+                .unwrap();
+        assert_eq!(target.final_path, dir.path().join("clip (2).mp4"));
+        assert_eq!(
+            target.temp_path,
+            dir.path().join("clip (2).mp4.3f2504e0.tmp")
+        );
+
+        fs::write(&target.temp_path, b"encoded").unwrap();
+        let finalized = OutputManager::finalize(&target).unwrap();
+        assert_eq!(finalized, target.final_path);
+        assert_eq!(fs::read(&output).unwrap(), b"original");
+    }
+
+    #[test]
+    fn jobs_writing_the_same_output_get_their_own_temp_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("video.mp4");
+
+        let first =
+            OutputManager::prepare(&output, "aaaa-1111", false, OverwritePolicy::Rename).unwrap();
+        let second =
+            OutputManager::prepare(&output, "bbbb-2222", false, OverwritePolicy::Rename).unwrap();
+        assert_eq!(first.final_path, second.final_path);
+        assert_ne!(first.temp_path, second.temp_path);
+        assert_eq!(first.temp_path, dir.path().join("video.mp4.aaaa1111.tmp"));
+    }
+
+    // APFS refuses names that aren't UTF-8, so this runs on Linux only.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn non_utf8_output_names_survive_renames() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join(OsStr::from_bytes(b"caf\xe9.mp4"));
+        fs::write(&output, b"original").unwrap();
+
+        let target =
+            OutputManager::prepare(&output, "job", false, OverwritePolicy::Rename).unwrap();
+        assert_eq!(
+            target.final_path,
+            dir.path().join(OsStr::from_bytes(b"caf\xe9 (1).mp4"))
+        );
+        assert_eq!(
+            target.temp_path,
+            dir.path()
+                .join(OsStr::from_bytes(b"caf\xe9 (1).mp4.job.tmp"))
+        );
+    }
+
+    #[test]
+    fn skip_discards_output_when_the_file_appears_mid_job() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("clip.mp4");
+        let target = OutputManager::prepare(&output, "job", false, OverwritePolicy::Skip).unwrap();
+        fs::write(&target.temp_path, b"encoded").unwrap();
+        fs::write(&output, b"original").unwrap();
+
+        let err = OutputManager::finalize(&target).unwrap_err();
+        assert_eq!(err.code, "job_output_exists");
+        assert!(!target.temp_path.exists());
+        assert_eq!(fs::read(&output).unwrap(), b"original");
+    }
+
+    #[test]
+    fn finalized_outputs_can_keep_source_times() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("source.mov");
+        fs::write(&source, b"source").unwrap();
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_times(fs::FileTimes::new().set_modified(modified))
+            .unwrap();
+
+        let mut target = OutputManager::prepare(
+            &dir.path().join("out.mp4"),
+            "job",
+            false,
+            OverwritePolicy::Overwrite,
+        )
+        .unwrap();
+        target.preserve_from = Some(source);
+        fs::write(&target.temp_path, b"encoded").unwrap();
+        let finalized = OutputManager::finalize(&target).unwrap();
+
+        assert_eq!(
+            fs::metadata(finalized).unwrap().modified().unwrap(),
+            modified
+        );
+    }
+
+    #[test]
+    fn copy_across_devices_leaves_only_the_destination() {
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let destination_dir = tempfile::TempDir::new().unwrap();
+        let source = source_dir.path().join("out.mp4.tmp");
+        let destination = destination_dir.path().join("out.mp4");
+        fs::write(&source, b"encoded").unwrap();
+
+        OutputManager::copy_across_devices(&source, &destination).unwrap();
+
+        assert!(!source.exists());
+        assert_eq!(fs::read(&destination).unwrap(), b"encoded");
+        assert!(!destination_dir.path().join("out.mp4.partial").exists());
+    }
+
+    #[test]
+    fn outputs_are_never_trashed_as_their_own_source() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("clip.mp4");
+        File::create(&file).unwrap();
+        let alias = dir.path().join(".").join("clip.mp4");
+
+        let err = OutputManager::trash_source(&file, &alias).unwrap_err();
+        assert_eq!(err.code, "job_trash_failed");
+        assert!(file.exists());
+    }
 }
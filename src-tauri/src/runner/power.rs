@@ -0,0 +1,329 @@
+//! Keeps the system awake while conversions run.
+//!
+//! The job registry marks the inhibitor active when its first job registers
+//! and inactive once it drains. While active and enabled, a platform
+//! assertion stops idle sleep: an IOKit power assertion on macOS,
+//! `SetThreadExecutionState` on Windows and a `systemd-inhibit` lock on
+//! Linux. Failing to take the assertion is logged and otherwise ignored.
+
+use std::sync::Mutex;
+
+use crate::sync_utils::MutexExt;
+
+/// Shown by the OS as the reason sleep is blocked.
+const REASON: &str = "Honeymelon is converting media";
+
+pub struct SleepInhibitor {
+    state: Mutex<InhibitState>,
+}
+
+struct InhibitState {
+    enabled: bool,
+    active: bool,
+    assertion: Option<platform::Assertion>,
+}
+
+impl Default for SleepInhibitor {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(InhibitState {
+                enabled: true,
+                active: false,
+                assertion: None,
+            }),
+        }
+    }
+}
+
+impl SleepInhibitor {
+    /// User preference; disabling releases a held assertion right away.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.update(|state| state.enabled = enabled);
+    }
+
+    /// Whether any job is running.
+    pub fn set_active(&self, active: bool) {
+        self.update(|state| state.active = active);
+    }
+
+    #[cfg(test)]
+    pub fn is_active(&self) -> bool {
+        self.state.lock_or_recover().active
+    }
+
+    fn update(&self, change: impl FnOnce(&mut InhibitState)) {
+        let mut state = self.state.lock_or_recover();
+        change(&mut state);
+
+        let wanted = state.enabled && state.active;
+        if !wanted {
+            state.assertion = None;
+        } else if state.assertion.is_none() {
+            match platform::Assertion::acquire(REASON) {
+                Ok(assertion) => state.assertion = Some(assertion),
+                Err(err) => eprintln!("[power] failed preventing sleep: {err}"),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::{c_char, c_void, CString};
+    use std::io;
+
+    type CFStringRef = *const c_void;
+
+    const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+    const K_IOPM_ASSERTION_LEVEL_ON: u32 = 255;
+    const K_IO_RETURN_SUCCESS: i32 = 0;
+    const ASSERTION_TYPE: &str = "PreventUserIdleSystemSleep";
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(
+            alloc: *const c_void,
+            value: *const c_char,
+            encoding: u32,
+        ) -> CFStringRef;
+        fn CFRelease(value: *const c_void);
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(
+            assertion_type: CFStringRef,
+            level: u32,
+            name: CFStringRef,
+            assertion_id: *mut u32,
+        ) -> i32;
+        fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+    }
+
+    pub struct Assertion(u32);
+
+    impl Assertion {
+        pub fn acquire(reason: &str) -> io::Result<Self> {
+            let assertion_type = cf_string(ASSERTION_TYPE)?;
+            let name = match cf_string(reason) {
+                Ok(name) => name,
+                Err(err) => {
+                    // SAFETY: `assertion_type` was created above and is
+                    // released exactly once.
+                    unsafe { CFRelease(assertion_type) };
+                    return Err(err);
+                },
+            };
+            let mut id = 0;
+            // SAFETY: both strings are valid CFStrings and `id` is a valid
+            // out pointer; the strings are released once IOKit has copied
+            // them.
+            let result = unsafe {
+                let result = IOPMAssertionCreateWithName(
+                    assertion_type,
+                    K_IOPM_ASSERTION_LEVEL_ON,
+                    name,
+                    &mut id,
+                );
+                CFRelease(assertion_type);
+                CFRelease(name);
+                result
+            };
+            if result != K_IO_RETURN_SUCCESS {
+                return Err(io::Error::other(format!(
+                    "IOPMAssertionCreateWithName returned {result:#x}"
+                )));
+            }
+            Ok(Self(id))
+        }
+    }
+
+    impl Drop for Assertion {
+        fn drop(&mut self) {
+            // SAFETY: releasing the assertion this value owns.
+            unsafe {
+                IOPMAssertionRelease(self.0);
+            }
+        }
+    }
+
+    fn cf_string(value: &str) -> io::Result<CFStringRef> {
+        let value = CString::new(value).map_err(io::Error::other)?;
+        // SAFETY: `value` is NUL-terminated UTF-8 and outlives the call.
+        let string = unsafe {
+            CFStringCreateWithCString(std::ptr::null(), value.as_ptr(), K_CF_STRING_ENCODING_UTF8)
+        };
+        if string.is_null() {
+            return Err(io::Error::other("CFStringCreateWithCString failed"));
+        }
+        Ok(string)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::io;
+    use std::sync::mpsc;
+    use windows_sys::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+    };
+
+    /// The execution state belongs to the thread that set it, so a
+    /// dedicated thread holds it until the assertion is dropped.
+    pub struct Assertion {
+        _release: mpsc::Sender<()>,
+    }
+
+    impl Assertion {
+        pub fn acquire(_reason: &str) -> io::Result<Self> {
+            let (release, released) = mpsc::channel::<()>();
+            let (ready, acquired) = mpsc::channel();
+            std::thread::spawn(move || {
+                // SAFETY: plain Win32 call without pointers.
+                let previous =
+                    unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+                let _ = ready.send(previous != 0);
+                if previous == 0 {
+                    return;
+                }
+                // Returns once the sender is dropped.
+                let _ = released.recv();
+                // SAFETY: plain Win32 call without pointers.
+                unsafe {
+                    SetThreadExecutionState(ES_CONTINUOUS);
+                }
+            });
+            match acquired.recv() {
+                Ok(true) => Ok(Self { _release: release }),
+                _ => Err(io::Error::other("SetThreadExecutionState failed")),
+            }
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::io;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command, Stdio};
+
+    /// A `systemd-inhibit` process holding the lock for as long as it runs.
+    pub struct Assertion(Child);
+
+    impl Assertion {
+        pub fn acquire(reason: &str) -> io::Result<Self> {
+            let mut command = Command::new("systemd-inhibit");
+            command
+                .args(["--what=idle:sleep", "--who=Honeymelon", "--mode=block"])
+                .arg(format!("--why={reason}"))
+                .args(["sleep", "infinity"]);
+            Self::hold(command)
+        }
+
+        /// Starts the holder in its own process group, so dropping the
+        /// assertion also ends the `sleep` it runs.
+        pub(super) fn hold(mut command: Command) -> io::Result<Self> {
+            command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .process_group(0)
+                .spawn()
+                .map(Self)
+        }
+    }
+
+    impl Drop for Assertion {
+        fn drop(&mut self) {
+            let signalled = libc::pid_t::try_from(self.0.id())
+                // SAFETY: signalling a process group id has no memory-safety
+                // requirements; failures are reported through the return value.
+                .is_ok_and(|pid| unsafe { libc::kill(-pid, libc::SIGTERM) } == 0);
+            if !signalled {
+                let _ = self.0.kill();
+            }
+            let _ = self.0.wait();
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+
+    pub struct Assertion;
+
+    impl Assertion {
+        pub fn acquire(_reason: &str) -> io::Result<Self> {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_inhibitor_tracks_activity_without_asserting() {
+        let inhibitor = SleepInhibitor::default();
+        inhibitor.set_enabled(false);
+        inhibitor.set_active(true);
+        assert!(inhibitor.is_active());
+        assert!(inhibitor.state.lock().unwrap().assertion.is_none());
+
+        inhibitor.set_active(false);
+        assert!(!inhibitor.is_active());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropping_the_assertion_ends_the_whole_holder() {
+        use std::process::Command;
+        use std::time::{Duration, Instant};
+
+        // Orphans are reparented to us instead of an init that may never
+        // reap them, so the ended grandchild can't linger as a zombie.
+        // SAFETY: prctl with PR_SET_CHILD_SUBREAPER only sets a process flag.
+        unsafe {
+            libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1);
+        }
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let pid_file = temp.path().join("sleep.pid");
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            &format!("sleep 30 & echo $! > '{}'; wait", pid_file.display()),
+        ]);
+        let assertion = platform::Assertion::hold(command).expect("spawn holder");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let grandchild: libc::pid_t = loop {
+            let written = std::fs::read_to_string(&pid_file).unwrap_or_default();
+            if let Ok(pid) = written.trim().parse() {
+                break pid;
+            }
+            assert!(Instant::now() < deadline, "holder never started sleep");
+            std::thread::sleep(Duration::from_millis(20));
+        };
+
+        drop(assertion);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let gone = loop {
+            // SAFETY: reaping a process we may have adopted and probing a pid
+            // with signal 0 have no memory-safety requirements.
+            let gone = unsafe {
+                libc::waitpid(grandchild, std::ptr::null_mut(), libc::WNOHANG);
+                libc::kill(grandchild, 0) == -1
+                    && std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH)
+            };
+            if gone || Instant::now() > deadline {
+                break gone;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert!(gone, "sleep {grandchild} outlived the assertion");
+    }
+}
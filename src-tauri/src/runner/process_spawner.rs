@@ -1,9 +1,26 @@
 use crate::{binary_resolver, error::AppError};
 use std::ffi::{OsStr, OsString};
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use tauri::AppHandle;
 
+/// Global options making ffmpeg write `key=value` progress blocks to stdout
+/// instead of the interactive status line, so stderr carries only logs.
+const PROGRESS_ARGS: [&str; 3] = ["-progress", "pipe:1", "-nostats"];
+
+/// Variables that make the loader inject or swap libraries in ffmpeg.
+const LOADER_ENV: &[&str] = &["LD_PRELOAD", "LD_AUDIT"];
+/// Prefix of the macOS loader's variables (`DYLD_INSERT_LIBRARIES`, ...).
+const DYLD_PREFIX: &str = "DYLD_";
+/// Variables of a user's ffmpeg setup that change what it logs or writes.
+const FFMPEG_ENV: &[&str] = &["FFREPORT", "AV_LOG_FORCE_COLOR", "AV_LOG_FORCE_256COLOR"];
+/// Passed on to ffmpeg as `FFREPORT`, to have it write a report file.
+const FFREPORT_ENV: &str = "HONEYMELON_FFREPORT";
+/// Search path of spawned processes.
+#[cfg(unix)]
+const SYSTEM_PATH: &str = "/usr/bin:/bin:/usr/sbin:/sbin";
+
 /// Manages FFmpeg binary resolution and process spawning
 pub struct ProcessSpawner;
 
@@ -19,25 +36,398 @@ impl ProcessSpawner {
         })
     }
 
-    /// Spawns an FFmpeg process with the given arguments and output path
+    /// Spawns an FFmpeg conversion with the given arguments and output
+    /// path. Structured progress is piped to stdout and logs to stderr;
+    /// stdin stays open so the job can be asked to quit (`request_quit`).
+    /// With `low_priority` the process yields the CPU to interactive apps.
     pub fn spawn(
+        ffmpeg_path: OsString,
+        args: &[OsString],
+        output_path: &Path,
+        low_priority: bool,
+    ) -> Result<Child, AppError> {
+        let mut command = Self::command(ffmpeg_path);
+        command.args(with_progress_args(args));
+        command.arg(output_path);
+        command.stdin(Stdio::piped());
+        command.stdout(Stdio::piped());
+        if low_priority {
+            lower_priority(&mut command);
+        }
+        Self::launch(command)
+    }
+
+    /// Spawns a decode-only FFmpeg process whose stderr carries errors only.
+    pub fn spawn_decode(
         ffmpeg_path: OsString,
         args: &[String],
         output_path: &str,
     ) -> Result<Child, AppError> {
-        let mut command = Command::new(ffmpeg_path);
+        let mut command = Self::command(ffmpeg_path);
         command.args(args);
         command.arg(output_path);
         command.stdin(Stdio::null());
         command.stdout(Stdio::null());
-        command.stderr(Stdio::piped());
+        Self::launch(command)
+    }
 
+    /// An ffmpeg command with a clean environment, so preloaded libraries
+    /// and shell setups don't change how conversions run. A bare program
+    /// name is looked up on the app's `PATH` first, as the process only
+    /// gets the system directories. The app's generated fontconfig file is
+    /// passed on for subtitle burn-in.
+    fn command(ffmpeg_path: OsString) -> Command {
+        let mut command = Command::new(locate(ffmpeg_path));
+        sanitize_environment(&mut command, std::env::vars_os());
+        crate::fonts::apply_fontconfig(&mut command);
         command
+    }
+
+    fn launch(mut command: Command) -> Result<Child, AppError> {
+        command.stderr(Stdio::piped());
+        isolate_process_group(&mut command);
+
+        let child = command
             .spawn()
-            .map_err(|err| AppError::new("job_spawn_failed", err.to_string()))
+            .map_err(|err| AppError::new("job_spawn_failed", err.to_string()))?;
+        #[cfg(windows)]
+        if let Err(err) = job_object::contain(&child) {
+            eprintln!("[jobs] failed assigning ffmpeg to a job object: {err}");
+        }
+        Ok(child)
+    }
+
+    /// Caps the encoder at `threads`. `-threads` goes last so it overrides
+    /// the caller's, and x265 and SVT-AV1, which size their own thread
+    /// pools, also get the limit through their private parameters.
+    pub fn limit_threads(args: &[OsString], threads: u32) -> Vec<OsString> {
+        let codecs: Vec<&str> = args
+            .windows(2)
+            .filter(|pair| pair[0].to_str().is_some_and(is_codec_flag))
+            .filter_map(|pair| pair[1].to_str())
+            .collect();
+        let mut pending: Vec<(&str, String)> = POOLED_ENCODERS
+            .iter()
+            .filter(|(encoder, _, _)| codecs.contains(encoder))
+            .map(|(_, flag, key)| (*flag, format!("{key}={threads}")))
+            .collect();
+
+        let mut result = Vec::with_capacity(args.len() + 2 + pending.len() * 2);
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            result.push(arg.clone());
+            let Some(index) = pending.iter().position(|(flag, _)| arg == flag) else {
+                continue;
+            };
+            let (_, limit) = pending.remove(index);
+            result.push(match iter.next() {
+                Some(params) if !params.is_empty() => {
+                    let mut params = params.clone();
+                    params.push(format!(":{limit}"));
+                    params
+                },
+                _ => limit.into(),
+            });
+        }
+        for (flag, limit) in pending {
+            result.extend([flag.into(), limit.into()]);
+        }
+        result.extend(["-threads".into(), threads.to_string().into()]);
+        result
+    }
+
+    /// Asks ffmpeg to stop by sending `q` on stdin, which lets it finish
+    /// writing the container instead of dying mid-write. Fails when stdin
+    /// is not piped or ffmpeg has already closed it.
+    pub fn request_quit(child: &mut Child) -> std::io::Result<()> {
+        let mut stdin = child.stdin.take().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotConnected, "stdin is not piped")
+        })?;
+        stdin.write_all(b"q")?;
+        stdin.flush()
+    }
+
+    /// Kills the process along with anything it launched. On Unix the whole
+    /// process group is signalled and on Windows the job object is
+    /// terminated, so filter helpers do not outlive a cancel.
+    pub fn kill_tree(child: &mut Child) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+                // SAFETY: signalling a process group id has no memory-safety
+                // requirements; failures are reported through the return value.
+                if unsafe { libc::kill(-pid, libc::SIGKILL) } == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        #[cfg(windows)]
+        if job_object::terminate(child.id()) {
+            return Ok(());
+        }
+
+        child.kill()
+    }
+
+    /// Stops (`pause`) or continues the process group, falling back to the
+    /// process alone like `kill_tree`.
+    pub fn signal_tree(child: &Child, pause: bool) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            let signal = if pause { libc::SIGSTOP } else { libc::SIGCONT };
+            let pid = libc::pid_t::try_from(child.id())
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+            // SAFETY: signalling a process or group id has no memory-safety
+            // requirements; failures are reported through errno.
+            if unsafe { libc::kill(-pid, signal) } == 0 || unsafe { libc::kill(pid, signal) } == 0 {
+                return Ok(());
+            }
+            Err(std::io::Error::last_os_error())
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (child, pause);
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "pausing jobs is not supported on this platform",
+            ))
+        }
+    }
+}
+
+/// Starts ffmpeg in its own process group so terminal signals aimed at the
+/// app (Ctrl+C during `tauri dev`) don't reach running conversions.
+#[cfg(unix)]
+fn isolate_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(not(unix))]
+fn isolate_process_group(_command: &mut Command) {}
+
+/// Windows has no process groups, so each spawned ffmpeg gets a job object
+/// that its descendants inherit. Jobs are created with kill-on-close: if
+/// the app dies, Windows closes the handles and takes the trees down too.
+#[cfg(windows)]
+mod job_object {
+    use std::collections::HashMap;
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::sync::{Mutex, OnceLock};
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicAccountingInformation,
+        JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
+        TerminateJobObject, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    use crate::sync_utils::MutexExt;
+
+    struct Job(HANDLE);
+
+    // SAFETY: job handles may be used from any thread.
+    unsafe impl Send for Job {}
+
+    impl Job {
+        fn is_empty(&self) -> bool {
+            // SAFETY: the struct is plain integers, for which zero is valid.
+            let mut info: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = unsafe { std::mem::zeroed() };
+            // SAFETY: `info` is a writable buffer of the size passed.
+            let ok = unsafe {
+                QueryInformationJobObject(
+                    self.0,
+                    JobObjectBasicAccountingInformation,
+                    (&mut info as *mut JOBOBJECT_BASIC_ACCOUNTING_INFORMATION).cast(),
+                    size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as u32,
+                    std::ptr::null_mut(),
+                )
+            };
+            ok != 0 && info.ActiveProcesses == 0
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: closing the handle this value owns.
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+
+    /// Jobs by the pid of the process they were created for.
+    fn jobs() -> &'static Mutex<HashMap<u32, Job>> {
+        static JOBS: OnceLock<Mutex<HashMap<u32, Job>>> = OnceLock::new();
+        JOBS.get_or_init(Default::default)
+    }
+
+    pub fn contain(child: &Child) -> io::Result<()> {
+        // SAFETY: no security attributes and an anonymous job.
+        let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let job = Job(handle);
+
+        // SAFETY: the struct is plain integers, for which zero is valid.
+        let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        // SAFETY: `limits` is a valid JOBOBJECT_EXTENDED_LIMIT_INFORMATION
+        // and `child` owns a live process handle.
+        let ok = unsafe {
+            SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                (&limits as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION).cast(),
+                size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) != 0
+                && AssignProcessToJobObject(job.0, child.as_raw_handle()) != 0
+        };
+        if !ok {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut jobs = jobs().lock_or_recover();
+        // Closing an empty job kills nothing, so finished ones go here.
+        jobs.retain(|_, job| !job.is_empty());
+        jobs.insert(child.id(), job);
+        Ok(())
+    }
+
+    /// Kills every process in the job created for `pid`; false when there
+    /// is none or terminating it failed.
+    pub fn terminate(pid: u32) -> bool {
+        let Some(job) = jobs().lock_or_recover().remove(&pid) else {
+            return false;
+        };
+        // SAFETY: `job` owns a live job handle.
+        unsafe { TerminateJobObject(job.0, 1) != 0 }
     }
 }
 
+/// `nice` increment applied to background conversions.
+#[cfg(unix)]
+const BACKGROUND_NICENESS: libc::c_int = 10;
+
+#[cfg(unix)]
+fn lower_priority(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: the hook only calls `setpriority`, which is async-signal-safe.
+    unsafe {
+        command.pre_exec(|| {
+            libc::setpriority(libc::PRIO_PROCESS, 0, BACKGROUND_NICENESS);
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn lower_priority(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    use windows_sys::Win32::System::Threading::BELOW_NORMAL_PRIORITY_CLASS;
+    command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn lower_priority(_command: &mut Command) {}
+
+/// Encoders with their own thread pools: name, private parameter option and
+/// the parameter limiting the pool.
+const POOLED_ENCODERS: &[(&str, &str, &str)] = &[
+    ("libx265", "-x265-params", "pools"),
+    ("libsvtav1", "-svtav1-params", "lp"),
+];
+
+/// `-c`, `-codec` and `-vcodec`, with or without a stream specifier.
+fn is_codec_flag(arg: &str) -> bool {
+    let flag = arg.split(':').next().unwrap_or_default();
+    matches!(flag, "-c" | "-codec" | "-vcodec")
+}
+
+/// Puts the runner's progress options in front of `args`, dropping any
+/// progress or stats flags the caller passed, and `-nostdin`, which would
+/// make ffmpeg ignore quit requests.
+fn with_progress_args(args: &[OsString]) -> Vec<OsString> {
+    let mut result: Vec<OsString> = PROGRESS_ARGS.iter().map(OsString::from).collect();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.to_str().unwrap_or_default() {
+            "-progress" => {
+                iter.next();
+            },
+            "-nostats" | "-stats" | "-nostdin" => {},
+            _ => result.push(arg.clone()),
+        }
+    }
+    result
+}
+
+/// Strips loader and ffmpeg variables found in `inherited` from `command`,
+/// pins `PATH` and turns off colored logs, which would garble the parsed
+/// stderr. `FFREPORT` is only set from `HONEYMELON_FFREPORT`.
+fn sanitize_environment(
+    command: &mut Command,
+    inherited: impl IntoIterator<Item = (OsString, OsString)>,
+) {
+    for name in LOADER_ENV.iter().chain(FFMPEG_ENV) {
+        command.env_remove(name);
+    }
+    for (name, value) in inherited {
+        if name.to_string_lossy().starts_with(DYLD_PREFIX) {
+            command.env_remove(name);
+        } else if name == FFREPORT_ENV {
+            command.env("FFREPORT", value);
+        }
+    }
+    if let Some(path) = system_path() {
+        command.env("PATH", path);
+    }
+    command.env("AV_LOG_FORCE_NOCOLOR", "1");
+}
+
+#[cfg(unix)]
+fn system_path() -> Option<OsString> {
+    Some(SYSTEM_PATH.into())
+}
+
+#[cfg(windows)]
+fn system_path() -> Option<OsString> {
+    let root = PathBuf::from(std::env::var_os("SystemRoot")?);
+    std::env::join_paths([
+        root.join("System32"),
+        root.clone(),
+        root.join("System32").join("Wbem"),
+    ])
+    .ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_path() -> Option<OsString> {
+    None
+}
+
+/// `program` as found on the app's `PATH` when it is a bare name, or
+/// unchanged.
+fn locate(program: OsString) -> OsString {
+    if has_path_separator(&program) {
+        return program;
+    }
+    let mut file_name = program.clone();
+    if cfg!(windows) && Path::new(&program).extension().is_none() {
+        file_name.push(".exe");
+    }
+    std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .map(|dir: PathBuf| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+        .map_or(program, PathBuf::into_os_string)
+}
+
 fn select_ffmpeg_candidate(candidates: &[OsString]) -> Option<OsString> {
     for candidate in candidates {
         let candidate_path = Path::new(candidate);
@@ -87,4 +477,172 @@ mod tests {
 
         assert_eq!(selected, OsString::from("ffmpeg"));
     }
+
+    #[test]
+    fn spawned_processes_get_a_clean_environment() {
+        let mut command = Command::new("ffmpeg");
+        sanitize_environment(
+            &mut command,
+            [
+                ("DYLD_INSERT_LIBRARIES", "/tmp/hook.dylib"),
+                ("HONEYMELON_FFREPORT", "file=/tmp/report.log"),
+                ("HOME", "/home/user"),
+            ]
+            .map(|(name, value)| (OsString::from(name), OsString::from(value))),
+        );
+        let envs: Vec<(&OsStr, Option<&OsStr>)> = command.get_envs().collect();
+        let value = |name: &str| {
+            envs.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+
+        assert_eq!(value("LD_PRELOAD"), Some(None));
+        assert_eq!(value("DYLD_INSERT_LIBRARIES"), Some(None));
+        assert_eq!(
+            value("FFREPORT"),
+            Some(Some(OsStr::new("file=/tmp/report.log")))
+        );
+        assert_eq!(value("AV_LOG_FORCE_NOCOLOR"), Some(Some(OsStr::new("1"))));
+        assert_eq!(value("HOME"), None);
+        #[cfg(unix)]
+        assert_eq!(value("PATH"), Some(Some(OsStr::new(SYSTEM_PATH))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bare_program_names_are_located_on_the_path() {
+        let located = PathBuf::from(locate(OsString::from("sh")));
+        assert!(located.is_absolute() && located.is_file());
+        assert_eq!(
+            locate(OsString::from("./ffmpeg")),
+            OsString::from("./ffmpeg")
+        );
+    }
+
+    #[test]
+    fn progress_args_replace_caller_progress_flags() {
+        let args: Vec<OsString> = [
+            "-progress",
+            "pipe:2",
+            "-nostats",
+            "-nostdin",
+            "-i",
+            "in.mov",
+            "-stats",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(
+            with_progress_args(&args),
+            vec!["-progress", "pipe:1", "-nostats", "-i", "in.mov"]
+        );
+    }
+
+    #[test]
+    fn thread_limits_reach_pooled_encoders() {
+        let args =
+            |values: &[&str]| -> Vec<OsString> { values.iter().map(OsString::from).collect() };
+        assert_eq!(
+            ProcessSpawner::limit_threads(&args(&["-i", "in.mov", "-c:v", "libx264"]), 3),
+            args(&["-i", "in.mov", "-c:v", "libx264", "-threads", "3"])
+        );
+        assert_eq!(
+            ProcessSpawner::limit_threads(
+                &args(&["-i", "in.mov", "-c:v", "libx265", "-x265-params", "crf=20"]),
+                4
+            ),
+            args(&[
+                "-i",
+                "in.mov",
+                "-c:v",
+                "libx265",
+                "-x265-params",
+                "crf=20:pools=4",
+                "-threads",
+                "4"
+            ])
+        );
+        assert_eq!(
+            ProcessSpawner::limit_threads(&args(&["-i", "in.mkv", "-vcodec", "libsvtav1"]), 2),
+            args(&[
+                "-i",
+                "in.mkv",
+                "-vcodec",
+                "libsvtav1",
+                "-svtav1-params",
+                "lp=2",
+                "-threads",
+                "2"
+            ])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn low_priority_processes_are_niced() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "ps -o nice= -p $$"]);
+        command.stdout(Stdio::piped());
+        lower_priority(&mut command);
+        let output = command.output().expect("run shell");
+
+        let nice: i32 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .expect("nice value");
+        assert!(nice >= BACKGROUND_NICENESS);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn isolated_process_group_is_killed_as_a_whole() {
+        use std::io::{BufRead, BufReader};
+        use std::time::{Duration, Instant};
+
+        // Orphans are reparented to us instead of an init that may never
+        // reap them, so the killed grandchild can't linger as a zombie.
+        #[cfg(target_os = "linux")]
+        // SAFETY: prctl with PR_SET_CHILD_SUBREAPER only sets a process flag.
+        unsafe {
+            libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1);
+        }
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "sleep 30 & echo $!; wait"]);
+        command.stdout(Stdio::piped());
+        isolate_process_group(&mut command);
+        let mut child = command.spawn().expect("spawn shell");
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .expect("read grandchild pid");
+        let grandchild: libc::pid_t = line.trim().parse().expect("grandchild pid");
+
+        let pid = libc::pid_t::try_from(child.id()).unwrap();
+        // SAFETY: getpgid only reads process metadata.
+        assert_eq!(unsafe { libc::getpgid(pid) }, pid);
+
+        ProcessSpawner::kill_tree(&mut child).expect("kill group");
+        let status = child.wait().expect("wait for shell");
+        assert!(!status.success());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let gone = loop {
+            // SAFETY: reaping a process we may have adopted and probing a pid
+            // with signal 0 have no memory-safety requirements.
+            let gone = unsafe {
+                libc::waitpid(grandchild, std::ptr::null_mut(), libc::WNOHANG);
+                libc::kill(grandchild, 0) == -1
+                    && std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH)
+            };
+            if gone || Instant::now() > deadline {
+                break gone;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert!(gone, "grandchild {grandchild} outlived the kill");
+    }
 }
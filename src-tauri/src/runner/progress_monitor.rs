@@ -1,38 +1,105 @@
+use crate::api::NativePath;
 use crate::error::AppError;
-use std::collections::VecDeque;
-use std::io::{BufRead, BufReader};
-use std::path::{Path, PathBuf};
+use crate::sync_utils::MutexExt;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
 use std::process::{Child, ExitStatus};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use super::{
     events::{CompletionPayload, ProgressMetrics, ProgressPayload, SharedEmitter},
+    job_log::JobLog,
     job_registry::JobRegistry,
-    output_manager::OutputManager,
+    output_manager::{OutputManager, OutputTarget},
+    process_spawner::ProcessSpawner,
+    source_watch::{self, SourceSnapshot, JOB_SOURCE_CHANGED},
+    triage,
+    watchdog::{self, JOB_STALLED},
 };
 
 /// Wrapper around an active FFmpeg child process with management metadata
 pub struct RunningProcess {
     /// The actual FFmpeg child process handle
     pub child: Mutex<Option<Child>>,
+    /// Processes running beside `child` for the same job, such as
+    /// parallel segment encoders; paused and resumed along with it
+    pub helpers: Mutex<Vec<Child>>,
     /// Atomic flag indicating if the process has been cancelled
     pub cancelled: AtomicBool,
+    /// Set when the watchdog killed the process for making no progress
+    stalled: AtomicBool,
+    /// Whether the process is currently suspended
+    paused: AtomicBool,
+    /// Set once ffmpeg reported `progress=end` and is writing the trailer
+    finishing: AtomicBool,
+    /// When ffmpeg last reported progress, or when it was started
+    last_progress: Mutex<Instant>,
+    /// How long the process may go without progress before it is killed
+    stall_timeout: Option<Duration>,
     /// Whether this job requires exclusive execution while running
     exclusive: AtomicBool,
-    /// Circular buffer of recent log lines
-    pub logs: Mutex<VecDeque<String>>,
+    /// Persistent per-job log and the bounded view of recent lines
+    log: JobLog,
+    /// The input file as it was at launch, watched for changes
+    source: Option<SourceSnapshot>,
+    /// Why the job was stopped after its source changed
+    source_changed: Mutex<Option<String>>,
 }
 impl RunningProcess {
     pub fn new(child: Child, exclusive: bool) -> Self {
         Self {
             child: Mutex::new(Some(child)),
+            helpers: Mutex::new(Vec::new()),
             cancelled: AtomicBool::new(false),
+            stalled: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            finishing: AtomicBool::new(false),
+            last_progress: Mutex::new(Instant::now()),
+            stall_timeout: None,
             exclusive: AtomicBool::new(exclusive),
-            logs: Mutex::new(VecDeque::with_capacity(256)),
+            log: JobLog::in_memory(),
+            source: None,
+            source_changed: Mutex::new(None),
         }
     }
 
+    /// Replaces the in-memory log with a file-backed one
+    pub fn with_log(mut self, log: JobLog) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Enables the stall watchdog for this process
+    pub fn with_stall_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.stall_timeout = timeout;
+        self
+    }
+
+    /// Watches `source` for deletion or changes while the process runs
+    pub fn with_source_watch(mut self, source: Option<SourceSnapshot>) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn watched_source(&self) -> Option<&SourceSnapshot> {
+        self.source.as_ref()
+    }
+
+    pub fn mark_source_changed(&self, reason: String) {
+        *self.source_changed.lock_or_recover() = Some(reason);
+    }
+
+    /// Why the source watcher stopped the process, if it did.
+    pub fn source_change(&self) -> Option<String> {
+        self.source_changed.lock_or_recover().clone()
+    }
+
+    pub fn log_path(&self) -> Option<&Path> {
+        self.log.path()
+    }
+
     /// Updates the exclusivity flag, typically when cleaning up
     pub fn set_exclusive(&self, exclusive: bool) {
         self.exclusive.store(exclusive, Ordering::SeqCst);
@@ -51,20 +118,104 @@ impl RunningProcess {
         self.cancelled.load(Ordering::SeqCst)
     }
 
-    pub fn push_log(&self, line: &str) {
-        if let Ok(mut logs) = self.logs.lock() {
-            if logs.len() >= 500 {
-                logs.pop_front();
-            }
-            logs.push_back(line.to_string());
+    /// Suspends or resumes the process and everything it launched.
+    /// Returns false when it has already exited.
+    pub fn set_paused(&self, pause: bool) -> std::io::Result<bool> {
+        let child_guard = self.child.lock_or_recover();
+        let Some(child) = child_guard.as_ref() else {
+            return Ok(false);
+        };
+        ProcessSpawner::signal_tree(child, pause)?;
+        for helper in self.helpers.lock_or_recover().iter() {
+            // Helpers may already have exited.
+            let _ = ProcessSpawner::signal_tree(helper, pause);
         }
+        self.paused.store(pause, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn mark_stalled(&self) {
+        self.stalled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.stalled.load(Ordering::SeqCst)
+    }
+
+    pub fn mark_finishing(&self) {
+        self.finishing.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_finishing(&self) -> bool {
+        self.finishing.load(Ordering::SeqCst)
+    }
+
+    /// Completion message for a job the watchdog stopped.
+    pub fn stall_message(&self) -> String {
+        format!(
+            "ffmpeg made no progress for {} s and was stopped.",
+            self.stall_timeout.unwrap_or_default().as_secs()
+        )
+    }
+
+    pub fn touch_progress(&self) {
+        *self.last_progress.lock_or_recover() = Instant::now();
+    }
+
+    /// Time since the last progress report.
+    pub fn idle_for(&self) -> Duration {
+        self.last_progress.lock_or_recover().elapsed()
+    }
+
+    pub fn push_log(&self, line: &str) {
+        self.log.push(line);
     }
 
     pub fn drain_logs(&self) -> Vec<String> {
-        match self.logs.lock() {
-            Ok(mut guard) => guard.drain(..).collect(),
-            Err(_) => Vec::new(),
+        self.log.drain()
+    }
+}
+
+/// Collects one block of `-progress` output. ffmpeg writes `key=value`
+/// lines and closes each block with `progress=continue` or `progress=end`.
+#[derive(Default)]
+pub(super) struct ProgressBlock {
+    metrics: ProgressMetrics,
+}
+
+impl ProgressBlock {
+    /// Feeds one line, returning the block's metrics once it is complete.
+    pub(super) fn push(&mut self, line: &str) -> Option<ProgressMetrics> {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim();
+        let metrics = &mut self.metrics;
+        match key {
+            "out_time_us" => {
+                metrics.processed_seconds = value
+                    .parse::<i64>()
+                    .ok()
+                    .map(|micros| micros.max(0) as f64 / 1_000_000.0);
+            },
+            "out_time" if metrics.processed_seconds.is_none() => {
+                metrics.processed_seconds = ProgressMonitor::parse_timecode(value);
+            },
+            "fps" => metrics.fps = value.parse().ok(),
+            "speed" => metrics.speed = value.trim_end_matches('x').trim().parse().ok(),
+            "total_size" => metrics.total_size = value.parse().ok(),
+            "bitrate" => {
+                metrics.bitrate_kbps = value.trim_end_matches("kbits/s").trim().parse().ok();
+            },
+            "progress" => {
+                let metrics = std::mem::take(&mut self.metrics);
+                return (metrics != ProgressMetrics::default()).then_some(metrics);
+            },
+            _ => {},
         }
+        None
     }
 }
 
@@ -72,79 +223,117 @@ impl RunningProcess {
 pub struct ProgressMonitor;
 
 impl ProgressMonitor {
-    /// Starts monitoring an FFmpeg process
+    /// Starts monitoring an FFmpeg process, killing it when its stall
+    /// timeout passes without progress. Once it exits and its slot is
+    /// freed, the outcome is handed to `on_exit`, which reports it.
     pub fn start(
         emitter: SharedEmitter,
         registry: Arc<JobRegistry>,
         job_id: String,
         process: Arc<RunningProcess>,
-        final_path: PathBuf,
-        temp_path: PathBuf,
+        output: OutputTarget,
+        on_exit: impl FnOnce(CompletionPayload) + Send + 'static,
     ) {
+        Self::start_watchers(&job_id, &process);
         tauri::async_runtime::spawn_blocking(move || {
             Self::monitor_process(emitter.clone(), &job_id, &process);
-            Self::handle_completion(emitter, &job_id, &process, &final_path, &temp_path);
+            let completion = Self::handle_completion(&emitter, &job_id, &process, &output);
             registry.remove(&job_id);
+            on_exit(completion);
         });
     }
 
-    /// Monitors FFmpeg stderr for progress
+    /// Starts the stall watchdog and the source watcher the process asked
+    /// for.
+    pub(super) fn start_watchers(job_id: &str, process: &Arc<RunningProcess>) {
+        if let Some(timeout) = process.stall_timeout {
+            watchdog::spawn(job_id.to_string(), Arc::clone(process), timeout);
+        }
+        if let Some(source) = process.watched_source() {
+            source_watch::spawn(job_id.to_string(), Arc::clone(process), source.clone());
+        }
+    }
+
+    /// Reads structured progress from stdout while forwarding stderr to
+    /// the log, returning once both pipes have closed.
     fn monitor_process(emitter: SharedEmitter, job_id: &str, process: &Arc<RunningProcess>) {
-        let mut child_guard = process.child.lock().expect("child mutex poisoned");
+        let mut child_guard = process.child.lock_or_recover();
         let Some(child) = child_guard.as_mut() else {
             return;
         };
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        drop(child_guard);
 
-        let stderr = match child.stderr.take() {
-            Some(s) => s,
-            None => return,
-        };
+        let log_reader = stderr.map(|stderr| {
+            let emitter = emitter.clone();
+            let job_id = job_id.to_string();
+            let process = Arc::clone(process);
+            std::thread::spawn(move || Self::forward_logs(&emitter, &job_id, &process, stderr))
+        });
 
-        drop(child_guard);
+        if let Some(stdout) = stdout {
+            let mut block = ProgressBlock::default();
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if line.trim() == "progress=end" {
+                    process.mark_finishing();
+                }
+                let Some(progress) = block.push(&line) else {
+                    continue;
+                };
+                process.touch_progress();
+                emitter.emit_progress(&ProgressPayload {
+                    job_id: job_id.to_string(),
+                    progress: Some(progress),
+                    raw: String::new(),
+                    metadata: Default::default(),
+                });
+            }
+        }
 
-        let reader = BufReader::new(stderr);
-        for line_result in reader.lines() {
-            let line = match line_result {
-                Ok(value) => value,
-                Err(_) => break,
-            };
+        if let Some(handle) = log_reader {
+            let _ = handle.join();
+        }
+    }
 
+    fn forward_logs(
+        emitter: &SharedEmitter,
+        job_id: &str,
+        process: &RunningProcess,
+        stderr: impl Read,
+    ) {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
             eprintln!("[ffmpeg][{}] {}", job_id, line);
-
             emitter.emit_stderr(job_id, &line);
-
             process.push_log(&line);
-
-            // Parse and emit progress
-            let progress = Self::parse_progress_line(&line);
-            if progress.is_some() {
-                eprintln!("[ffmpeg-progress][{}] {:?}", job_id, progress);
-            }
-            let payload = ProgressPayload {
-                job_id: job_id.to_string(),
-                progress,
-                raw: line,
-            };
-            emitter.emit_progress(&payload);
         }
     }
 
     /// Handles process completion and file finalization
-    fn handle_completion(
-        emitter: SharedEmitter,
+    pub(super) fn handle_completion(
+        emitter: &SharedEmitter,
         job_id: &str,
         process: &Arc<RunningProcess>,
-        final_path: &Path,
-        temp_path: &Path,
-    ) {
+        output: &OutputTarget,
+    ) -> CompletionPayload {
         let exit_status = Self::wait_for_exit(job_id, process);
         let cancelled = process.is_cancelled();
         let mut code_override: Option<&'static str> = None;
         let mut message_override: Option<String> = None;
+        let source_change = process.source_change().filter(|_| !cancelled);
+        if let Some(reason) = &source_change {
+            code_override = Some(JOB_SOURCE_CHANGED);
+            message_override = Some(reason.clone());
+        } else if process.is_stalled() && !cancelled {
+            code_override = Some(JOB_STALLED);
+            message_override = Some(process.stall_message());
+        }
 
+        // A stalled job quits like a cancelled one: whatever it wrote is cut
+        // short, so it is discarded rather than taking the output's place.
         let (mut success, exit_code, signal) = match exit_status {
             Ok(status) => (
-                status.success() && !cancelled,
+                status.success() && !cancelled && source_change.is_none() && !process.is_stalled(),
                 status.code(),
                 Self::extract_signal(&status),
             ),
@@ -156,6 +345,7 @@ impl ProgressMonitor {
                     job_id: job_id.to_string(),
                     progress: None,
                     raw: detail.clone(),
+                    metadata: Default::default(),
                 });
                 message_override = Some(detail);
                 (false, None, None)
@@ -174,15 +364,19 @@ impl ProgressMonitor {
         };
 
         // Finalize output file
+        let mut output_path = None;
         if success && !cancelled {
-            if let Err(err) = OutputManager::finalize(temp_path, final_path) {
-                success = false;
-                code = err.code;
-                message = Some(err.message.clone());
-                process.push_log(&err.message);
+            match OutputManager::finalize(output) {
+                Ok(path) => output_path = Some(NativePath::from(path.as_path())),
+                Err(err) => {
+                    success = false;
+                    code = err.code;
+                    message = Some(err.message.clone());
+                    process.push_log(&err.message);
+                },
             }
         } else {
-            OutputManager::cleanup_temp(temp_path);
+            OutputManager::cleanup_temp(&output.temp_path);
         }
 
         // Generate error message if needed
@@ -199,8 +393,14 @@ impl ProgressMonitor {
         }
 
         let logs = process.drain_logs();
+        let suggestions = if success || cancelled {
+            Vec::new()
+        } else {
+            triage::suggest_remediations(&logs)
+        };
 
-        let completion = CompletionPayload {
+        process.set_exclusive(false);
+        CompletionPayload {
             job_id: job_id.to_string(),
             success,
             cancelled,
@@ -209,14 +409,23 @@ impl ProgressMonitor {
             code: code.to_string(),
             message,
             logs,
-        };
-
-        process.set_exclusive(false);
-        emitter.emit_completion(&completion);
+            log_path: process
+                .log_path()
+                .map(|path| path.to_string_lossy().into_owned()),
+            suggestions,
+            output_path,
+            verified: false,
+            fell_back_to_software: false,
+            trashed_source: None,
+            metadata: Default::default(),
+        }
     }
 
-    fn wait_for_exit(job_id: &str, process: &Arc<RunningProcess>) -> Result<ExitStatus, AppError> {
-        let mut child_guard = process.child.lock().expect("child mutex poisoned");
+    pub(super) fn wait_for_exit(
+        job_id: &str,
+        process: &Arc<RunningProcess>,
+    ) -> Result<ExitStatus, AppError> {
+        let mut child_guard = process.child.lock_or_recover();
         let Some(mut child) = child_guard.take() else {
             return Err(AppError::new(
                 "job_missing_child",
@@ -229,49 +438,6 @@ impl ProgressMonitor {
             .map_err(|err| AppError::new("job_wait_failed", err.to_string()))
     }
 
-    fn parse_progress_line(line: &str) -> Option<ProgressMetrics> {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            return None;
-        }
-
-        let mut processed_seconds: Option<f64> = None;
-        let mut fps: Option<f64> = None;
-        let mut speed: Option<f64> = None;
-
-        if let Some(value) = trimmed.strip_prefix("out_time=") {
-            processed_seconds = Self::parse_timecode(value);
-        } else if let Some(value) = trimmed.strip_prefix("fps=") {
-            fps = value.parse::<f64>().ok();
-        } else if let Some(value) = trimmed.strip_prefix("speed=") {
-            let cleaned = value.trim_end_matches('x').trim();
-            speed = cleaned.parse::<f64>().ok();
-        } else {
-            for token in trimmed.split_whitespace() {
-                if let Some(value) = token.strip_prefix("time=") {
-                    processed_seconds = Self::parse_timecode(value);
-                } else if let Some(value) = token.strip_prefix("out_time=") {
-                    processed_seconds = Self::parse_timecode(value);
-                } else if let Some(value) = token.strip_prefix("fps=") {
-                    fps = value.parse::<f64>().ok();
-                } else if let Some(value) = token.strip_prefix("speed=") {
-                    let cleaned = value.trim_end_matches('x').trim();
-                    speed = cleaned.parse::<f64>().ok();
-                }
-            }
-        }
-
-        if processed_seconds.is_none() && fps.is_none() && speed.is_none() {
-            return None;
-        }
-
-        Some(ProgressMetrics {
-            processed_seconds,
-            fps,
-            speed,
-        })
-    }
-
     fn parse_timecode(value: &str) -> Option<f64> {
         if value.is_empty() {
             return None;
@@ -315,6 +481,56 @@ impl ProgressMonitor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::OverwritePolicy;
+    use crate::runner::events::{
+        ProgressEmitter, QueuePositionPayload, QueueSuspendedPayload, RetryingPayload,
+        VerificationPayload,
+    };
+    use std::fs;
+    use std::process::{Command, Stdio};
+
+    struct NullEmitter;
+
+    impl ProgressEmitter for NullEmitter {
+        fn emit_progress(&self, _payload: &ProgressPayload) {}
+        fn emit_completion(&self, _payload: &CompletionPayload) {}
+        fn emit_stderr(&self, _job_id: &str, _line: &str) {}
+        fn emit_verification(&self, _payload: &VerificationPayload) {}
+        fn emit_queue_position(&self, _payload: &QueuePositionPayload) {}
+        fn emit_retrying(&self, _payload: &RetryingPayload) {}
+        fn emit_queue_suspended(&self, _payload: &QueueSuspendedPayload) {}
+    }
+
+    #[test]
+    fn stalled_jobs_discard_their_output_even_when_ffmpeg_exits_cleanly() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = OutputTarget {
+            final_path: dir.path().join("out.mp4"),
+            temp_path: dir.path().join("out.mp4.tmp"),
+            overwrite: OverwritePolicy::Skip,
+            preserve_from: None,
+        };
+        fs::write(&output.temp_path, b"truncated").unwrap();
+
+        let child = Command::new("true")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn true");
+        let process = Arc::new(RunningProcess::new(child, false));
+        process.mark_stalled();
+
+        let emitter: SharedEmitter = Arc::new(NullEmitter);
+        let completion = ProgressMonitor::handle_completion(&emitter, "job", &process, &output);
+
+        assert!(!completion.success);
+        assert_eq!(completion.code, JOB_STALLED);
+        assert_eq!(completion.message, Some(process.stall_message()));
+        assert!(completion.output_path.is_none());
+        assert!(!output.temp_path.exists());
+        assert!(!output.final_path.exists());
+    }
 
     #[test]
     fn parse_timecode_supports_hms_and_seconds_only() {
@@ -324,12 +540,32 @@ mod tests {
     }
 
     #[test]
-    fn parse_progress_line_detects_metrics_from_tokens() {
-        let line = "frame=10 fps=29.97 q=-1.0 time=00:00:05.00 speed=1.5x";
-        let metrics = ProgressMonitor::parse_progress_line(line).expect("metrics");
+    fn progress_blocks_yield_metrics_when_complete() {
+        let mut block = ProgressBlock::default();
+        let lines = [
+            "frame=120",
+            "fps=29.97",
+            "bitrate= 679.4kbits/s",
+            "total_size=524288",
+            "out_time_us=5000000",
+            "out_time=00:00:05.000000",
+            "speed=1.5x",
+        ];
+        for line in lines {
+            assert!(block.push(line).is_none());
+        }
+
+        let metrics = block.push("progress=continue").expect("metrics");
         assert_eq!(metrics.processed_seconds, Some(5.0));
         assert_eq!(metrics.fps, Some(29.97));
         assert_eq!(metrics.speed, Some(1.5));
+        assert_eq!(metrics.total_size, Some(524288));
+        assert_eq!(metrics.bitrate_kbps, Some(679.4));
+
+        // Fields reported as N/A leave the next block empty.
+        block.push("bitrate=N/A");
+        block.push("speed=N/A");
+        assert!(block.push("progress=end").is_none());
     }
 
     #[test]
@@ -0,0 +1,119 @@
+//! Automatic retries for transient job failures.
+//!
+//! A conversion that fails to spawn, or exits with one of the configured
+//! exit codes, is started again after an exponentially growing delay
+//! instead of being reported as failed. Only the final failure reaches the
+//! frontend as a completion; each retry is announced with a retrying event.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::sync_utils::RwLockExt;
+
+/// Errors from starting ffmpeg that are worth another attempt.
+const RETRYABLE_ERRORS: &[&str] = &["job_spawn_failed"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each one after it.
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// ffmpeg exit codes treated as transient.
+    #[serde(default)]
+    pub exit_codes: Vec<i32>,
+    /// Whether failures to spawn ffmpeg are retried.
+    #[serde(default)]
+    pub retry_spawn_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_delay_ms: 1_000,
+            max_delay_ms: 30_000,
+            exit_codes: Vec::new(),
+            retry_spawn_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (1-based).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        let delay = self
+            .initial_delay_ms
+            .saturating_mul(factor)
+            .min(self.max_delay_ms);
+        Duration::from_millis(delay)
+    }
+
+    pub fn allows(&self, attempt: u32) -> bool {
+        attempt <= self.max_retries
+    }
+
+    pub fn retries_exit(&self, exit_code: Option<i32>) -> bool {
+        exit_code.is_some_and(|code| self.exit_codes.contains(&code))
+    }
+
+    pub fn retries_error(&self, err: &AppError) -> bool {
+        self.retry_spawn_errors && RETRYABLE_ERRORS.contains(&err.code)
+    }
+}
+
+/// Retry policy shared by every clone of the coordinator.
+#[derive(Debug, Clone, Default)]
+pub struct RetrySettings(Arc<RwLock<RetryPolicy>>);
+
+impl RetrySettings {
+    pub fn get(&self) -> RetryPolicy {
+        self.0.read_or_recover().clone()
+    }
+
+    pub fn set(&self, policy: RetryPolicy) {
+        *self.0.write_or_recover() = policy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_double_up_to_the_cap() {
+        let policy = RetryPolicy {
+            initial_delay_ms: 500,
+            max_delay_ms: 3_000,
+            ..RetryPolicy::default()
+        };
+        let delays: Vec<u64> = (1..=5)
+            .map(|attempt| policy.delay(attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![500, 1_000, 2_000, 3_000, 3_000]);
+        assert_eq!(policy.delay(200), Duration::from_millis(3_000));
+    }
+
+    #[test]
+    fn only_configured_failures_are_retried() {
+        let policy = RetryPolicy {
+            exit_codes: vec![137],
+            ..RetryPolicy::default()
+        };
+        assert!(policy.retries_exit(Some(137)));
+        assert!(!policy.retries_exit(Some(1)));
+        assert!(!policy.retries_exit(None));
+        assert!(policy.retries_error(&AppError::new("job_spawn_failed", "EAGAIN")));
+        assert!(!policy.retries_error(&AppError::new("job_ffmpeg_not_found", "missing")));
+
+        assert!(policy.allows(2));
+        assert!(!policy.allows(3));
+    }
+}
@@ -0,0 +1,953 @@
+//! Segment-parallel encoding of long single files.
+//!
+//! One ffmpeg process rarely keeps every core of a large machine busy. A
+//! segmented job first copies the video stream into keyframe-aligned
+//! pieces, then encodes the pieces concurrently next to one audio pass
+//! over the whole source, and finally joins everything with stream copy.
+//! Every process belongs to the job's `RunningProcess`, so cancel, pause
+//! and the stall watchdog work as for a plain conversion, and progress is
+//! reported as the sum over all segments.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::{
+    events::{CompletionPayload, ProgressMetrics, ProgressPayload, SharedEmitter},
+    external::SpawnController,
+    job_registry::JobRegistry,
+    output_check::MediaShape,
+    output_manager::{OutputManager, OutputTarget},
+    process_spawner::ProcessSpawner,
+    progress_monitor::{ProgressBlock, ProgressMonitor, RunningProcess},
+    source_watch::JOB_SOURCE_CHANGED,
+    triage,
+    watchdog::JOB_STALLED,
+};
+use crate::error::AppError;
+use crate::sync_utils::MutexExt;
+
+/// Shortest piece worth a process of its own.
+pub const MIN_SEGMENT_SEC: f64 = 60.0;
+/// Upper bound on concurrent encoders per job.
+pub const MAX_SEGMENTS: usize = 8;
+
+/// Intermediate files are Matroska, which holds any codec ffmpeg encodes.
+const SEGMENT_FORMAT: &str = "matroska";
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Options that take no value.
+const FLAGS: &[&str] = &[
+    "-y",
+    "-n",
+    "-nostdin",
+    "-nostats",
+    "-stats",
+    "-hide_banner",
+    "-vn",
+    "-an",
+    "-sn",
+    "-dn",
+    "-shortest",
+];
+/// Options the pieces can't reproduce: trims and filtergraphs.
+const UNSUPPORTED: &[&str] = &[
+    "-filter_complex",
+    "-lavfi",
+    "-ss",
+    "-sseof",
+    "-t",
+    "-to",
+    "-frames",
+    "-frames:v",
+    "-shortest",
+    "-c",
+    "-codec",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Video,
+    Audio,
+    Subtitle,
+    Container,
+    /// Replaced by the segment pipeline's own settings.
+    Dropped,
+}
+
+fn kind(name: &str) -> Kind {
+    match name {
+        "-f" | "-movflags" | "-map_metadata" | "-map_chapters" | "-brand" => Kind::Container,
+        name if name.starts_with("-metadata") || name.starts_with("-disposition") => {
+            Kind::Container
+        },
+        "-y" | "-n" | "-nostdin" | "-nostats" | "-stats" | "-progress" | "-hide_banner"
+        | "-threads" | "-vn" | "-an" | "-sn" | "-dn" => Kind::Dropped,
+        "-acodec" | "-ab" | "-ac" | "-ar" | "-af" | "-aq" | "-sample_fmt" | "-channel_layout" => {
+            Kind::Audio
+        },
+        "-scodec" => Kind::Subtitle,
+        name => match name
+            .split_once(':')
+            .and_then(|(_, spec)| spec.chars().next())
+        {
+            Some('a') => Kind::Audio,
+            Some('s') => Kind::Subtitle,
+            _ => Kind::Video,
+        },
+    }
+}
+
+/// How a job's arguments map onto the split, encode and join passes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentPlan {
+    /// Options before `-i`.
+    global: Vec<String>,
+    input: String,
+    duration_sec: f64,
+    segments: usize,
+    /// Encoder threads per segment.
+    threads: usize,
+    video: Vec<String>,
+    /// Maps and options of the audio pass; `None` without audio.
+    audio: Option<Vec<String>>,
+    /// Maps and options of subtitles, carried over from the source when
+    /// the pieces are joined.
+    subtitles: Vec<String>,
+    container: Vec<String>,
+}
+
+impl SegmentPlan {
+    /// Plans `args` for a source of `shape` on a machine with `cores`
+    /// cores, or explains why the job should run as one process.
+    pub fn new(args: &[String], shape: &MediaShape, cores: usize) -> Result<Self, String> {
+        let input_at = match args.iter().filter(|arg| *arg == "-i").count() {
+            1 => args.iter().position(|arg| arg == "-i").unwrap_or_default(),
+            _ => return Err("it needs exactly one input".into()),
+        };
+        let input = args
+            .get(input_at + 1)
+            .ok_or("the input path is missing")?
+            .clone();
+        let global = args[..input_at].to_vec();
+        let global_options = options(&global)?;
+        let options = options(&args[input_at + 2..])?;
+        if let Some((name, _)) = options
+            .iter()
+            .chain(&global_options)
+            .find(|(name, _)| UNSUPPORTED.contains(&name.as_str()))
+        {
+            return Err(format!("{name} can't be applied per segment"));
+        }
+
+        let codec = options
+            .iter()
+            .find(|(name, _)| matches!(name.as_str(), "-c:v" | "-codec:v" | "-vcodec"))
+            .and_then(|(_, value)| value.as_deref());
+        if matches!(codec, None | Some("copy")) || options.iter().any(|(name, _)| name == "-vn") {
+            return Err("the video stream isn't re-encoded".into());
+        }
+        if shape.video_streams == 0 {
+            return Err("the source has no video".into());
+        }
+
+        let segments = cores
+            .min(MAX_SEGMENTS)
+            .min((shape.duration_sec / MIN_SEGMENT_SEC) as usize);
+        if segments < 2 {
+            return Err("the source is too short to split".into());
+        }
+
+        let mut plan = Self {
+            global,
+            input,
+            duration_sec: shape.duration_sec,
+            segments,
+            threads: (cores / segments).max(1),
+            video: Vec::new(),
+            audio: None,
+            subtitles: Vec::new(),
+            container: Vec::new(),
+        };
+        let mut audio = Vec::new();
+        let mut audio_maps = 0;
+        let mut video_maps = 0;
+        let mut maps = 0;
+        let drops = |flag: &str| options.iter().any(|(name, _)| name == flag);
+        for (name, value) in &options {
+            let mut pair = vec![name.clone()];
+            pair.extend(value.clone());
+            if name == "-map" {
+                let map = value.as_deref().unwrap_or_default();
+                let positive = !map.starts_with('-');
+                maps += usize::from(positive);
+                let mut parts = map.trim_start_matches('-').split(':');
+                if parts.next() != Some("0") {
+                    return Err(format!("map {map} doesn't select from the input"));
+                }
+                match parts.next().and_then(|selector| selector.chars().next()) {
+                    Some('v') => video_maps += usize::from(positive),
+                    Some('a') => {
+                        audio_maps += usize::from(positive);
+                        audio.extend(pair);
+                    },
+                    Some('s') => plan.subtitles.extend(pair),
+                    _ => return Err(format!("map {map} isn't limited to one stream kind")),
+                }
+                continue;
+            }
+            match kind(name) {
+                Kind::Video => plan.video.extend(pair),
+                Kind::Audio => audio.extend(pair),
+                Kind::Subtitle => plan.subtitles.extend(pair),
+                Kind::Container => plan.container.extend(pair),
+                Kind::Dropped => {},
+            }
+        }
+        if video_maps > 1 || (maps > 0 && video_maps == 0) {
+            return Err("it doesn't map exactly one video stream".into());
+        }
+        if drops("-sn") {
+            plan.subtitles.clear();
+        }
+        let keeps_audio = if maps == 0 { true } else { audio_maps > 0 };
+        if keeps_audio && !drops("-an") && shape.audio_streams > 0 {
+            if maps == 0 {
+                let mut mapped = vec!["-map".to_string(), "0:a:0?".to_string()];
+                mapped.append(&mut audio);
+                audio = mapped;
+            }
+            plan.audio = Some(audio);
+        }
+        Ok(plan)
+    }
+
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+
+    /// Cut points splitting the source into equal pieces; the segment
+    /// muxer moves each to the next keyframe.
+    fn segment_times(&self) -> String {
+        (1..self.segments)
+            .map(|index| {
+                format!(
+                    "{:.3}",
+                    self.duration_sec * index as f64 / self.segments as f64
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Copies the video into `seg_NNN` pieces; the output is the pattern
+    /// returned by `split_pattern`.
+    fn split_args(&self) -> Vec<String> {
+        let mut args = self.global.clone();
+        args.extend(
+            [
+                "-i",
+                &self.input,
+                "-map",
+                "0:v:0",
+                "-c",
+                "copy",
+                "-f",
+                "segment",
+                "-segment_format",
+                SEGMENT_FORMAT,
+                "-segment_times",
+                &self.segment_times(),
+                "-reset_timestamps",
+                "1",
+            ]
+            .map(String::from),
+        );
+        args
+    }
+
+    fn segment_args(&self, segment: &Path) -> Vec<String> {
+        let mut args = self.global.clone();
+        args.extend(["-i".into(), segment.to_string_lossy().into_owned()]);
+        args.extend(["-map", "0:v:0"].map(String::from));
+        args.extend(self.video.iter().cloned());
+        args.extend(
+            [
+                "-threads",
+                &self.threads.to_string(),
+                "-an",
+                "-sn",
+                "-dn",
+                "-f",
+                SEGMENT_FORMAT,
+            ]
+            .map(String::from),
+        );
+        args
+    }
+
+    fn audio_args(&self) -> Option<Vec<String>> {
+        let audio = self.audio.as_ref()?;
+        let mut args = self.global.clone();
+        args.extend(["-i".into(), self.input.clone()]);
+        args.extend(audio.iter().cloned());
+        args.extend(["-vn", "-sn", "-dn", "-f", SEGMENT_FORMAT].map(String::from));
+        Some(args)
+    }
+
+    /// Joins the encoded pieces listed in `list` with the audio pass and
+    /// the source's subtitles, metadata and chapters.
+    fn join_args(&self, list: &Path, audio: &Path) -> Vec<String> {
+        let mut args: Vec<String> = ["-y", "-f", "concat", "-safe", "0", "-i"]
+            .map(String::from)
+            .into();
+        args.push(list.to_string_lossy().into_owned());
+        if self.audio.is_some() {
+            args.extend(["-i".into(), audio.to_string_lossy().into_owned()]);
+        }
+        let source = if self.audio.is_some() { "2" } else { "1" };
+        args.extend(["-i".into(), self.input.clone()]);
+        args.extend(["-map", "0:v:0"].map(String::from));
+        if self.audio.is_some() {
+            args.extend(["-map", "1:a"].map(String::from));
+        }
+        let mut subtitles = self.subtitles.iter();
+        while let Some(arg) = subtitles.next() {
+            args.push(arg.clone());
+            if arg == "-map" {
+                // Subtitles come from the source, now a later input.
+                let map = subtitles.next().cloned().unwrap_or_default();
+                args.push(match map.strip_prefix('-') {
+                    Some(map) => format!("-{source}{}", &map[1..]),
+                    None => format!("{source}{}", &map[1..]),
+                });
+            }
+        }
+        args.extend(
+            [
+                "-map_metadata",
+                source,
+                "-map_chapters",
+                source,
+                "-c:v",
+                "copy",
+                "-c:a",
+                "copy",
+            ]
+            .map(String::from),
+        );
+        args.extend(self.container.iter().cloned());
+        args
+    }
+}
+
+/// Splits `args` into options and their values.
+fn options(args: &[String]) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut options = Vec::new();
+    let mut iter = args.iter();
+    while let Some(name) = iter.next() {
+        if !name.starts_with('-') {
+            return Err(format!("argument {name} isn't an option"));
+        }
+        let value = if FLAGS.contains(&name.as_str()) {
+            None
+        } else {
+            iter.next().cloned()
+        };
+        options.push((name.clone(), value));
+    }
+    Ok(options)
+}
+
+/// Escapes `path` for an `ffconcat` list.
+fn concat_entry(path: &Path) -> String {
+    format!("file '{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
+/// Sums the latest progress of every segment into one report.
+fn combine(parts: &[ProgressMetrics], duration_sec: f64) -> ProgressMetrics {
+    let sum = |value: fn(&ProgressMetrics) -> Option<f64>| {
+        parts
+            .iter()
+            .filter_map(value)
+            .fold(None, |total: Option<f64>, value| {
+                Some(total.unwrap_or_default() + value)
+            })
+    };
+    ProgressMetrics {
+        processed_seconds: sum(|part| part.processed_seconds)
+            .map(|seconds| seconds.min(duration_sec)),
+        fps: sum(|part| part.fps),
+        speed: sum(|part| part.speed),
+        total_size: parts
+            .iter()
+            .filter_map(|part| part.total_size)
+            .reduce(|total, size| total + size),
+        bitrate_kbps: None,
+    }
+}
+
+/// Latest progress of every segment encoder.
+struct SegmentProgress {
+    parts: Mutex<Vec<ProgressMetrics>>,
+    duration_sec: f64,
+}
+
+impl SegmentProgress {
+    fn update(&self, index: usize, metrics: ProgressMetrics) -> ProgressMetrics {
+        let mut parts = self.parts.lock_or_recover();
+        parts[index] = metrics;
+        combine(&parts, self.duration_sec)
+    }
+}
+
+/// A segmented conversion in progress. Its working directory, next to the
+/// temporary output, is removed when it is dropped.
+pub struct SegmentedJob {
+    plan: SegmentPlan,
+    work_dir: PathBuf,
+    spawner: Arc<dyn SpawnController>,
+    ffmpeg: OsString,
+    low_priority: bool,
+}
+
+impl SegmentedJob {
+    pub fn new(
+        plan: SegmentPlan,
+        output: &OutputTarget,
+        spawner: Arc<dyn SpawnController>,
+        ffmpeg: OsString,
+        low_priority: bool,
+    ) -> Result<Self, AppError> {
+        let mut work_dir = output.temp_path.clone().into_os_string();
+        work_dir.push(".segments");
+        let work_dir = PathBuf::from(work_dir);
+        let _ = fs::remove_dir_all(&work_dir);
+        fs::create_dir_all(&work_dir).map_err(|err| {
+            AppError::new(
+                "job_output_invalid",
+                format!("Failed creating {}: {err}", work_dir.display()),
+            )
+        })?;
+        Ok(Self {
+            plan,
+            work_dir,
+            spawner,
+            ffmpeg,
+            low_priority,
+        })
+    }
+
+    /// Starts the split pass, the job's first process.
+    pub fn spawn_split(&self) -> Result<Child, AppError> {
+        let pattern = self.work_dir.join("seg_%03d.mkv");
+        self.spawn(&self.plan.split_args(), &pattern)
+    }
+
+    /// Runs the remaining passes once the split process is registered,
+    /// then hands the outcome to `on_exit` like `ProgressMonitor::start`.
+    pub fn start(
+        self,
+        emitter: SharedEmitter,
+        registry: Arc<JobRegistry>,
+        job_id: String,
+        process: Arc<RunningProcess>,
+        output: OutputTarget,
+        on_exit: impl FnOnce(CompletionPayload) + Send + 'static,
+    ) {
+        ProgressMonitor::start_watchers(&job_id, &process);
+        tauri::async_runtime::spawn_blocking(move || {
+            let completion = match self.run(&emitter, &job_id, &process, &output) {
+                Ok(()) => ProgressMonitor::handle_completion(&emitter, &job_id, &process, &output),
+                Err(err) => Self::failed(&job_id, &process, &output, err),
+            };
+            drop(self);
+            registry.remove(&job_id);
+            on_exit(completion);
+        });
+    }
+
+    /// Waits for the split, encodes the pieces and starts the join. On
+    /// success the finished join process is the job's child.
+    fn run(
+        &self,
+        emitter: &SharedEmitter,
+        job_id: &str,
+        process: &Arc<RunningProcess>,
+        output: &OutputTarget,
+    ) -> Result<(), AppError> {
+        let readers = process
+            .child
+            .lock_or_recover()
+            .as_mut()
+            .map(|child| watch(emitter, job_id, process, child, "split", None))
+            .unwrap_or_default();
+        Self::finish_pass(process, readers, |_| "Splitting the source failed.".into())?;
+
+        let mut segments: Vec<PathBuf> = fs::read_dir(&self.work_dir)
+            .map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()))
+            .into_iter()
+            .flatten()
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("seg_"))
+            })
+            .collect();
+        segments.sort();
+        if segments.is_empty() {
+            return Err(AppError::new(
+                "job_failed",
+                "Splitting the source produced no segments.",
+            ));
+        }
+
+        let progress = Arc::new(SegmentProgress {
+            parts: Mutex::new(vec![ProgressMetrics::default(); segments.len()]),
+            duration_sec: self.plan.duration_sec,
+        });
+        let mut readers = Vec::new();
+        let mut encoded = Vec::new();
+        for (index, segment) in segments.iter().enumerate() {
+            let target = self.work_dir.join(format!("enc_{index:03}.mkv"));
+            let mut child = self.spawn(&self.plan.segment_args(segment), &target)?;
+            let label = format!("segment {}", index + 1);
+            let progress = Some((Arc::clone(&progress), index));
+            readers.extend(watch(
+                emitter, job_id, process, &mut child, &label, progress,
+            ));
+            adopt(process, child, index == 0);
+            encoded.push(target);
+        }
+        let audio = self.work_dir.join("audio.mka");
+        if let Some(args) = self.plan.audio_args() {
+            let mut child = self.spawn(&args, &audio)?;
+            readers.extend(watch(emitter, job_id, process, &mut child, "audio", None));
+            adopt(process, child, false);
+        }
+        let segment_count = segments.len();
+        Self::finish_pass(process, readers, |index| {
+            if index < segment_count {
+                format!("Encoding segment {} failed.", index + 1)
+            } else {
+                "Encoding the audio failed.".into()
+            }
+        })?;
+
+        let list = self.work_dir.join("segments.ffconcat");
+        let entries: Vec<String> = encoded.iter().map(|path| concat_entry(path)).collect();
+        fs::write(
+            &list,
+            format!("ffconcat version 1.0\n{}\n", entries.join("\n")),
+        )
+        .map_err(|err| {
+            AppError::new(
+                "job_failed",
+                format!("Failed writing {}: {err}", list.display()),
+            )
+        })?;
+        let mut child = self.spawn(&self.plan.join_args(&list, &audio), &output.temp_path)?;
+        let readers = watch(emitter, job_id, process, &mut child, "join", None);
+        adopt(process, child, true);
+        Self::wait(process);
+        for reader in readers {
+            let _ = reader.join();
+        }
+        Ok(())
+    }
+
+    fn spawn(&self, args: &[String], output: &Path) -> Result<Child, AppError> {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        self.spawner
+            .spawn_job(self.ffmpeg.clone(), &args, output, self.low_priority)
+    }
+
+    /// Waits for every process of a pass and its output readers. Fails
+    /// when the job was stopped or a process failed, naming the first
+    /// failed process (the lead is index 0) with `describe`.
+    fn finish_pass(
+        process: &RunningProcess,
+        readers: Vec<JoinHandle<()>>,
+        describe: impl Fn(usize) -> String,
+    ) -> Result<(), AppError> {
+        let outcomes = Self::wait(process);
+        for reader in readers {
+            let _ = reader.join();
+        }
+        if process.is_cancelled() || process.is_stalled() || process.source_change().is_some() {
+            return Err(AppError::new("job_cancelled", "The job was stopped."));
+        }
+        if let Some(index) = outcomes.iter().position(|success| !success) {
+            return Err(AppError::new("job_failed", describe(index)));
+        }
+        process.helpers.lock_or_recover().clear();
+        Ok(())
+    }
+
+    /// Polls the lead and helper processes until all have exited, killing
+    /// the rest once the job is stopped or one of them fails. Returns
+    /// whether each succeeded, lead first.
+    fn wait(process: &RunningProcess) -> Vec<bool> {
+        let mut stopping = false;
+        loop {
+            let mut outcomes = Vec::new();
+            let (mut lead, mut helpers) = (
+                process.child.lock_or_recover(),
+                process.helpers.lock_or_recover(),
+            );
+            for child in lead.iter_mut().chain(helpers.iter_mut()) {
+                let outcome = match child.try_wait() {
+                    Ok(status) => status.map(|status| status.success()),
+                    Err(_) => Some(false),
+                };
+                if stopping && outcome.is_none() {
+                    let _ = ProcessSpawner::kill_tree(child);
+                }
+                outcomes.push(outcome);
+            }
+            drop((lead, helpers));
+            if outcomes.iter().all(Option::is_some) {
+                return outcomes.into_iter().flatten().collect();
+            }
+            stopping |= process.is_cancelled()
+                || process.is_stalled()
+                || process.source_change().is_some()
+                || outcomes.contains(&Some(false));
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Completion for a job that ended before the join finished. Leftover
+    /// processes are stopped and the partial output removed.
+    fn failed(
+        job_id: &str,
+        process: &RunningProcess,
+        output: &OutputTarget,
+        err: AppError,
+    ) -> CompletionPayload {
+        let (mut lead, mut helpers) = (
+            process.child.lock_or_recover(),
+            process.helpers.lock_or_recover(),
+        );
+        for mut child in lead.take().into_iter().chain(helpers.drain(..)) {
+            if let Ok(None) = child.try_wait() {
+                let _ = ProcessSpawner::kill_tree(&mut child);
+            }
+            let _ = child.wait();
+        }
+        drop((lead, helpers));
+        OutputManager::cleanup_temp(&output.temp_path);
+
+        let cancelled = process.is_cancelled();
+        let (code, message) = if cancelled {
+            ("job_cancelled", None)
+        } else if let Some(reason) = process.source_change() {
+            (JOB_SOURCE_CHANGED, Some(reason))
+        } else if process.is_stalled() {
+            (JOB_STALLED, Some(process.stall_message()))
+        } else {
+            (err.code, Some(err.message))
+        };
+        if let Some(message) = &message {
+            process.push_log(message);
+        }
+        let logs = process.drain_logs();
+        let suggestions = if cancelled {
+            Vec::new()
+        } else {
+            triage::suggest_remediations(&logs)
+        };
+        process.set_exclusive(false);
+        CompletionPayload {
+            job_id: job_id.to_string(),
+            success: false,
+            cancelled,
+            exit_code: None,
+            signal: None,
+            code: code.to_string(),
+            message,
+            logs,
+            log_path: process
+                .log_path()
+                .map(|path| path.to_string_lossy().into_owned()),
+            suggestions,
+            output_path: None,
+            verified: false,
+            fell_back_to_software: false,
+            trashed_source: None,
+            metadata: Default::default(),
+        }
+    }
+}
+
+impl Drop for SegmentedJob {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.work_dir) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("[jobs] failed removing {}: {err}", self.work_dir.display());
+            }
+        }
+    }
+}
+
+/// Makes `child` part of the job: as its lead process, which cancel and
+/// the watchdog act on, or as a helper. A paused job pauses it right away.
+fn adopt(process: &RunningProcess, child: Child, lead: bool) {
+    let (mut current, mut helpers) = (
+        process.child.lock_or_recover(),
+        process.helpers.lock_or_recover(),
+    );
+    if process.is_paused() {
+        let _ = ProcessSpawner::signal_tree(&child, true);
+    }
+    if lead {
+        *current = Some(child);
+    } else {
+        helpers.push(child);
+    }
+}
+
+/// Forwards the stderr of one pass process to the job log, prefixed with
+/// `label`, and reads its progress. Segment encoders report into their
+/// slot of `progress`; other passes only keep the watchdog fed.
+fn watch(
+    emitter: &SharedEmitter,
+    job_id: &str,
+    process: &Arc<RunningProcess>,
+    child: &mut Child,
+    label: &str,
+    progress: Option<(Arc<SegmentProgress>, usize)>,
+) -> Vec<JoinHandle<()>> {
+    let mut readers = Vec::new();
+    if let Some(stderr) = child.stderr.take() {
+        let (emitter, job_id, process) = (emitter.clone(), job_id.to_string(), Arc::clone(process));
+        let label = label.to_string();
+        readers.push(std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                let line = format!("[{label}] {line}");
+                eprintln!("[ffmpeg][{job_id}] {line}");
+                emitter.emit_stderr(&job_id, &line);
+                process.push_log(&line);
+            }
+        }));
+    }
+    if let Some(stdout) = child.stdout.take() {
+        let (emitter, job_id, process) = (emitter.clone(), job_id.to_string(), Arc::clone(process));
+        readers.push(std::thread::spawn(move || {
+            let mut block = ProgressBlock::default();
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let Some(metrics) = block.push(&line) else {
+                    continue;
+                };
+                process.touch_progress();
+                if let Some((progress, index)) = &progress {
+                    emitter.emit_progress(&ProgressPayload {
+                        job_id: job_id.clone(),
+                        progress: Some(progress.update(*index, metrics)),
+                        raw: String::new(),
+                        metadata: Default::default(),
+                    });
+                }
+            }
+        }));
+    }
+    readers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn shape(duration_sec: f64, audio_streams: usize) -> MediaShape {
+        MediaShape {
+            duration_sec,
+            video_streams: 1,
+            audio_streams,
+            streams: 1 + audio_streams,
+        }
+    }
+
+    fn job_args() -> Vec<String> {
+        args(&[
+            "-y",
+            "-i",
+            "/in.mov",
+            "-map",
+            "0:v:0?",
+            "-c:v",
+            "libx264",
+            "-crf",
+            "23",
+            "-map",
+            "0:a:0?",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            "-map",
+            "0:s?",
+            "-c:s",
+            "mov_text",
+            "-movflags",
+            "+faststart",
+            "-f",
+            "mp4",
+            "-nostdin",
+        ])
+    }
+
+    #[test]
+    fn job_arguments_are_split_by_pass() {
+        let plan = SegmentPlan::new(&job_args(), &shape(600.0, 1), 8).unwrap();
+        assert_eq!(plan.segments(), 8);
+        assert_eq!(plan.segment_times().split(',').count(), 7);
+        assert!(plan.segment_times().starts_with("75.000,150.000"));
+
+        let split = plan.split_args();
+        assert_eq!(split[..3], args(&["-y", "-i", "/in.mov"]));
+        assert!(split.windows(2).any(|pair| pair == ["-f", "segment"]));
+
+        assert_eq!(
+            plan.segment_args(Path::new("/w/seg_000.mkv")),
+            args(&[
+                "-y",
+                "-i",
+                "/w/seg_000.mkv",
+                "-map",
+                "0:v:0",
+                "-c:v",
+                "libx264",
+                "-crf",
+                "23",
+                "-threads",
+                "1",
+                "-an",
+                "-sn",
+                "-dn",
+                "-f",
+                "matroska",
+            ])
+        );
+        assert_eq!(
+            plan.audio_args().unwrap(),
+            args(&[
+                "-y", "-i", "/in.mov", "-map", "0:a:0?", "-c:a", "aac", "-b:a", "128k", "-vn",
+                "-sn", "-dn", "-f", "matroska",
+            ])
+        );
+        assert_eq!(
+            plan.join_args(Path::new("/w/list"), Path::new("/w/audio.mka")),
+            args(&[
+                "-y",
+                "-f",
+                "concat",
+                "-safe",
+                "0",
+                "-i",
+                "/w/list",
+                "-i",
+                "/w/audio.mka",
+                "-i",
+                "/in.mov",
+                "-map",
+                "0:v:0",
+                "-map",
+                "1:a",
+                "-map",
+                "2:s?",
+                "-c:s",
+                "mov_text",
+                "-map_metadata",
+                "2",
+                "-map_chapters",
+                "2",
+                "-c:v",
+                "copy",
+                "-c:a",
+                "copy",
+                "-movflags",
+                "+faststart",
+                "-f",
+                "mp4",
+            ])
+        );
+    }
+
+    #[test]
+    fn audio_pass_follows_the_source_and_arguments() {
+        let silent = SegmentPlan::new(&job_args(), &shape(600.0, 0), 4).unwrap();
+        assert!(silent.audio_args().is_none());
+        let join = silent.join_args(Path::new("/w/list"), Path::new("/w/audio.mka"));
+        assert!(join.windows(2).any(|pair| pair == ["-map", "1:s?"]));
+
+        let unmapped = args(&["-i", "/in.mov", "-c:v", "libx265", "-an"]);
+        let plan = SegmentPlan::new(&unmapped, &shape(600.0, 1), 4).unwrap();
+        assert!(plan.audio_args().is_none());
+
+        let unmapped = args(&["-i", "/in.mov", "-c:v", "libx265"]);
+        let plan = SegmentPlan::new(&unmapped, &shape(600.0, 1), 4).unwrap();
+        assert!(plan
+            .audio_args()
+            .unwrap()
+            .windows(2)
+            .any(|pair| pair == ["-map", "0:a:0?"]));
+    }
+
+    #[test]
+    fn unsuitable_jobs_run_as_one_process() {
+        let long = shape(3_600.0, 1);
+        for unsuitable in [
+            args(&["-i", "/in.mov", "-c:v", "copy"]),
+            args(&["-i", "/in.mov", "-vn", "-c:a", "aac"]),
+            args(&["-ss", "10", "-i", "/in.mov", "-c:v", "libx264"]),
+            args(&[
+                "-i",
+                "/in.mov",
+                "-filter_complex",
+                "overlay",
+                "-c:v",
+                "libx264",
+            ]),
+            args(&["-i", "/a.mov", "-i", "/b.mov", "-c:v", "libx264"]),
+            args(&["-i", "/in.mov", "-map", "0", "-c:v", "libx264"]),
+        ] {
+            assert!(
+                SegmentPlan::new(&unsuitable, &long, 8).is_err(),
+                "{unsuitable:?}"
+            );
+        }
+        assert!(SegmentPlan::new(&job_args(), &shape(90.0, 1), 8).is_err());
+        assert!(SegmentPlan::new(&job_args(), &long, 1).is_err());
+    }
+
+    #[test]
+    fn segment_progress_is_summed() {
+        let part = |seconds: f64, speed: f64| ProgressMetrics {
+            processed_seconds: Some(seconds),
+            speed: Some(speed),
+            total_size: Some(100),
+            ..ProgressMetrics::default()
+        };
+        let combined = combine(&[part(30.0, 2.0), part(45.0, 1.5)], 60.0);
+        assert_eq!(combined.processed_seconds, Some(60.0));
+        assert_eq!(combined.speed, Some(3.5));
+        assert_eq!(combined.total_size, Some(200));
+        assert_eq!(combined.fps, None);
+    }
+
+    #[test]
+    fn concat_entries_escape_quotes() {
+        assert_eq!(
+            concat_entry(Path::new("/w/it's.mkv")),
+            r"file '/w/it'\''s.mkv'"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Severity of ffmpeg stderr lines.
+//!
+//! Each forwarded stderr line is tagged `info`, `warning` or `error` by
+//! matching known ffmpeg messages, so the frontend can highlight problems
+//! without pattern-matching raw text itself.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogSeverity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+/// Lowercase fragments of lines reporting a failure.
+const ERROR_PATTERNS: &[&str] = &[
+    "[error]",
+    "[fatal]",
+    "[panic]",
+    "error while decoding",
+    "error while opening",
+    "error opening",
+    "error initializing",
+    "error during demuxing",
+    "error writing",
+    "invalid data found when processing input",
+    "no such file or directory",
+    "permission denied",
+    "no space left on device",
+    "conversion failed",
+    "could not open",
+    "could not write header",
+    "unknown encoder",
+    "unknown decoder",
+    "unrecognized option",
+    "invalid argument",
+    "failed to",
+    "corrupt",
+];
+
+/// Lowercase fragments of lines reporting something suspicious that does
+/// not stop the conversion.
+const WARNING_PATTERNS: &[&str] = &[
+    "[warning]",
+    "warning",
+    "deprecated",
+    "past duration",
+    "non-monotonous dts",
+    "non monotonically increasing dts",
+    "queue input is backward in time",
+    "estimating duration from bitrate",
+    "too many packets buffered",
+    "guessed channel layout",
+    "application provided invalid",
+    "discarding",
+    "skipping",
+    "missing picture",
+    "concealing",
+    "timestamps are unset",
+];
+
+/// Classifies one stderr line.
+pub fn classify(line: &str) -> LogSeverity {
+    let line = line.to_lowercase();
+    let matches = |patterns: &[&str]| patterns.iter().any(|pattern| line.contains(pattern));
+    if matches(ERROR_PATTERNS) {
+        LogSeverity::Error
+    } else if matches(WARNING_PATTERNS) {
+        LogSeverity::Warning
+    } else {
+        LogSeverity::Info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_ffmpeg_messages_are_classified() {
+        assert_eq!(
+            classify("[h264 @ 0x7f8] error while decoding MB 12 34, bytestream -5"),
+            LogSeverity::Error
+        );
+        assert_eq!(
+            classify("in.mov: Invalid data found when processing input"),
+            LogSeverity::Error
+        );
+        assert_eq!(
+            classify("[swscaler @ 0x1] deprecated pixel format used, make sure you did set range correctly"),
+            LogSeverity::Warning
+        );
+        assert_eq!(
+            classify("[mp4 @ 0x2] Non-monotonous DTS in output stream 0:1"),
+            LogSeverity::Warning
+        );
+        assert_eq!(
+            classify("  Stream #0:0: Video: h264 (High), yuv420p, 1920x1080, 30 fps"),
+            LogSeverity::Info
+        );
+    }
+
+    #[test]
+    fn severity_serializes_lowercase() {
+        assert_eq!(
+            serde_json::to_string(&LogSeverity::Warning).unwrap(),
+            "\"warning\""
+        );
+    }
+}
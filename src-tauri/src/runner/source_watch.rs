@@ -0,0 +1,133 @@
+//! Source change detection for running conversions.
+//!
+//! A source deleted or rewritten mid-encode makes ffmpeg fail with an I/O
+//! error that doesn't say why, or finish with a truncated output. A watcher
+//! runs beside each conversion of a local file, compares the file's
+//! modification time and size with those taken at launch, and stops ffmpeg
+//! once they differ; the job then fails with `job_source_changed`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use super::{process_spawner::ProcessSpawner, progress_monitor::RunningProcess};
+use crate::sync_utils::MutexExt;
+
+pub const JOB_SOURCE_CHANGED: &str = "job_source_changed";
+
+const POLL: Duration = Duration::from_secs(2);
+
+/// The source file as it was when the job launched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSnapshot {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl SourceSnapshot {
+    /// `None` for inputs that aren't regular files, such as URLs or pipes.
+    pub fn take(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.is_file())?;
+        Some(Self {
+            path: path.to_path_buf(),
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+
+    /// Why the file no longer matches the snapshot, if it doesn't.
+    pub fn changed(&self) -> Option<String> {
+        let path = self.path.display();
+        match Self::take(&self.path) {
+            None => Some(format!(
+                "Source {path} was deleted or moved during conversion."
+            )),
+            Some(current) if current != *self => {
+                Some(format!("Source {path} was modified during conversion."))
+            },
+            Some(_) => None,
+        }
+    }
+}
+
+/// Watches the source of `process` on its own thread until it exits.
+pub fn spawn(job_id: String, process: Arc<RunningProcess>, source: SourceSnapshot) {
+    std::thread::spawn(move || watch(&job_id, &process, &source, POLL));
+}
+
+fn watch(job_id: &str, process: &RunningProcess, source: &SourceSnapshot, poll: Duration) {
+    loop {
+        std::thread::sleep(poll);
+        if process.is_cancelled() || process.is_stalled() {
+            return;
+        }
+
+        // The monitor holds the lock only while waiting for exit, and
+        // takes the child once it has exited.
+        let Some(mut child_guard) = process.child.try_lock_or_recover() else {
+            continue;
+        };
+        let Some(child) = child_guard.as_mut() else {
+            return;
+        };
+        let Some(reason) = source.changed() else {
+            continue;
+        };
+        process.push_log(&reason);
+        process.mark_source_changed(reason);
+        if let Err(err) = ProcessSpawner::kill_tree(child) {
+            eprintln!("[jobs] failed stopping job {job_id} after its source changed: {err}");
+        }
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn snapshots_notice_edits_and_deletion() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("in.mov");
+        fs::write(&source, b"frames").unwrap();
+        let snapshot = SourceSnapshot::take(&source).unwrap();
+        assert_eq!(snapshot.changed(), None);
+
+        fs::write(&source, b"more frames").unwrap();
+        assert!(snapshot.changed().unwrap().contains("modified"));
+
+        fs::remove_file(&source).unwrap();
+        assert!(snapshot.changed().unwrap().contains("deleted"));
+        assert!(SourceSnapshot::take(dir.path()).is_none());
+    }
+
+    #[test]
+    fn jobs_stop_when_their_source_disappears() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("in.mov");
+        fs::write(&source, b"frames").unwrap();
+        let snapshot = SourceSnapshot::take(&source).unwrap();
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg("sleep 5")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn sleep");
+        let process = RunningProcess::new(child, false);
+
+        fs::remove_file(&source).unwrap();
+        watch("job", &process, &snapshot, Duration::from_millis(50));
+
+        assert!(process.source_change().is_some());
+        let status = process.child.lock().unwrap().as_mut().unwrap().wait();
+        assert!(!status.unwrap().success());
+    }
+}
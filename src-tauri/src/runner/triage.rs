@@ -0,0 +1,387 @@
+//! Failure triage: turns ffmpeg stderr into retryable remediations.
+
+use serde::{Deserialize, Serialize};
+
+use super::severity::{self, LogSeverity};
+
+/// Thread count suggested when ffmpeg runs out of resources.
+const REDUCED_THREADS: u32 = 2;
+/// Container that accepts nearly every codec combination.
+const PERMISSIVE_CONTAINER: &str = "mkv";
+/// Hardware encoders and the software encoder standing in for each.
+const SOFTWARE_ENCODERS: &[(&str, &str)] = &[
+    ("h264_videotoolbox", "libx264"),
+    ("hevc_videotoolbox", "libx265"),
+    ("prores_videotoolbox", "prores_ks"),
+    ("h264_nvenc", "libx264"),
+    ("hevc_nvenc", "libx265"),
+    ("av1_nvenc", "libsvtav1"),
+    ("h264_qsv", "libx264"),
+    ("hevc_qsv", "libx265"),
+    ("av1_qsv", "libsvtav1"),
+    ("vp9_qsv", "libvpx-vp9"),
+];
+/// Options only hardware encoders or hardware frames understand.
+const HARDWARE_OPTIONS: &[&str] = &[
+    "-hwaccel",
+    "-hwaccel_output_format",
+    "-hwaccel_device",
+    "-allow_sw",
+    "-require_sw",
+    "-realtime",
+    "-rc",
+    "-cq",
+    "-global_quality",
+    "-look_ahead",
+    "-preset",
+];
+/// Lowercase fragments of messages hardware decoding fails with.
+const HWACCEL_ERRORS: &[&str] = &[
+    "failed setup for format",
+    "hwaccel initialisation returned error",
+    "device creation failed",
+    "device setup failed for decoder",
+    "no device available for decoder",
+    "hardware accelerated decoding failed",
+];
+/// How the version banner and stream dumps start; they mention build flags
+/// such as `--enable-videotoolbox` and codec names, not failures.
+const BANNER_PREFIXES: &[&str] = &[
+    "ffmpeg version",
+    "built with",
+    "configuration:",
+    "libavutil",
+    "libavcodec",
+    "libavformat",
+    "libavdevice",
+    "libavfilter",
+    "libswscale",
+    "libswresample",
+    "libpostproc",
+    "input #",
+    "output #",
+    "stream #",
+    "stream mapping:",
+    "metadata:",
+    "duration:",
+    "press [q]",
+];
+/// Muxer options that only the MP4 family understands.
+const MP4_MUXER_OPTIONS: &[&str] = &["-movflags", "-brand", "-use_editlist"];
+/// Lowercase fragments of messages hardware encoders fail with.
+const HARDWARE_ENCODER_ERRORS: &[&str] = &[
+    "cannot create compression session",
+    "vtcompressionsession",
+    "encoder malfunction",
+    "openencodesessionex failed",
+    "no capable devices found",
+    "cannot load libnvidia-encode",
+    "error initializing an internal mfx session",
+    "mfx_err",
+];
+
+/// Machine-actionable fix the frontend can offer as "Retry with fix".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum Remediation {
+    /// Drop hardware decoding and let ffmpeg decode in software.
+    SoftwareDecode,
+    /// Cap ffmpeg's worker threads.
+    #[serde(rename_all = "camelCase")]
+    LowerThreads { threads: u32 },
+    /// Write the output into a more permissive container.
+    #[serde(rename_all = "camelCase")]
+    ChangeContainer { container: String },
+    /// Free space on the output volume before retrying unchanged.
+    FreeDisk,
+}
+
+/// Derives remediation suggestions from a failed job's log lines.
+pub fn suggest_remediations(logs: &[String]) -> Vec<Remediation> {
+    let mut suggestions = Vec::new();
+    let mut push = |remediation: Remediation| {
+        if !suggestions.contains(&remediation) {
+            suggestions.push(remediation);
+        }
+    };
+
+    for line in logs {
+        let line = line.to_lowercase();
+        if is_banner_line(&line) {
+            continue;
+        }
+
+        if line.contains("no space left on device") || line.contains("disk full") {
+            push(Remediation::FreeDisk);
+        }
+
+        if HWACCEL_ERRORS.iter().any(|pattern| line.contains(pattern)) {
+            push(Remediation::SoftwareDecode);
+        }
+
+        if line.contains("cannot allocate memory")
+            || line.contains("resource temporarily unavailable")
+            || line.contains("out of memory")
+        {
+            push(Remediation::LowerThreads {
+                threads: REDUCED_THREADS,
+            });
+        }
+
+        if line.contains("could not find tag for codec")
+            || line.contains("not currently supported in container")
+            || line.contains("codec not currently supported")
+        {
+            push(Remediation::ChangeContainer {
+                container: PERMISSIVE_CONTAINER.into(),
+            });
+        }
+    }
+
+    suggestions
+}
+
+fn is_banner_line(lowercase: &str) -> bool {
+    let trimmed = lowercase.trim_start();
+    BANNER_PREFIXES
+        .iter()
+        .any(|prefix| trimmed.starts_with(prefix))
+}
+
+/// Whether a failed job's logs show its hardware encoder giving up: a
+/// known failure message, or an error line from the encoder itself.
+pub fn hardware_encoder_failed(logs: &[String]) -> bool {
+    logs.iter().any(|line| {
+        let lower = line.to_lowercase();
+        HARDWARE_ENCODER_ERRORS
+            .iter()
+            .any(|pattern| lower.contains(pattern))
+            || (["_videotoolbox @", "_nvenc @", "_qsv @"]
+                .iter()
+                .any(|encoder| lower.contains(encoder))
+                && severity::classify(line) == LogSeverity::Error)
+    })
+}
+
+/// Rewrites `args` to encode video with the software equivalent of their
+/// hardware encoder, dropping hardware-only options. `None` when no
+/// hardware encoder is used.
+pub fn software_encoder_args(args: &[String]) -> Option<Vec<String>> {
+    let index = args.windows(2).position(|pair| {
+        matches!(pair[0].as_str(), "-c:v" | "-codec:v" | "-vcodec")
+            && SOFTWARE_ENCODERS
+                .iter()
+                .any(|(hardware, _)| pair[1] == *hardware)
+    })?;
+    let software = SOFTWARE_ENCODERS
+        .iter()
+        .find(|(hardware, _)| args[index + 1] == *hardware)
+        .map(|(_, software)| software.to_string())?;
+    let mut patched = args.to_vec();
+    patched[index + 1] = software;
+    Some(strip_options(&patched, HARDWARE_OPTIONS))
+}
+
+/// Rewrites planned ffmpeg arguments to apply a remediation. Container
+/// changes set `-f`, adding it when ffmpeg would have picked the muxer from
+/// the output's extension, and drop MP4-only muxer options; the caller
+/// renames the output.
+pub fn apply_remediation(args: &[String], remediation: &Remediation) -> Vec<String> {
+    match remediation {
+        Remediation::SoftwareDecode => strip_options(
+            args,
+            &["-hwaccel", "-hwaccel_output_format", "-hwaccel_device"],
+        ),
+        Remediation::LowerThreads { threads } => {
+            let mut patched = strip_options(args, &["-threads"]);
+            patched.extend(["-threads".to_string(), threads.to_string()]);
+            patched
+        },
+        Remediation::ChangeContainer { container } => {
+            let muxer = crate::planning::muxer_for_container(container).to_string();
+            let mut patched = if matches!(container.as_str(), "mp4" | "mov" | "m4a") {
+                args.to_vec()
+            } else {
+                strip_options(args, MP4_MUXER_OPTIONS)
+            };
+            // The last `-f` is the output's; earlier ones force input formats.
+            let output_format = patched
+                .iter()
+                .rposition(|arg| arg == "-f")
+                .filter(|index| !patched[*index..].iter().any(|arg| arg == "-i"));
+            match output_format.and_then(|index| patched.get_mut(index + 1)) {
+                Some(value) => *value = muxer,
+                None => patched.extend(["-f".to_string(), muxer]),
+            }
+            patched
+        },
+        Remediation::FreeDisk => args.to_vec(),
+    }
+}
+
+fn strip_options(args: &[String], flags: &[&str]) -> Vec<String> {
+    let mut patched = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if flags.contains(&arg.as_str()) {
+            iter.next();
+            continue;
+        }
+        patched.push(arg.clone());
+    }
+    patched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn classifies_common_failures() {
+        let logs = lines(&[
+            "[h264 @ 0x1] Failed setup for format videotoolbox_vld: hwaccel initialisation returned error.",
+            "av_interleaved_write_frame(): No space left on device",
+            "[mp4 @ 0x2] Could not find tag for codec pcm_s24le in stream #1",
+            "[mp4 @ 0x2] Could not find tag for codec pcm_s24le in stream #2",
+        ]);
+
+        assert_eq!(
+            suggest_remediations(&logs),
+            vec![
+                Remediation::SoftwareDecode,
+                Remediation::FreeDisk,
+                Remediation::ChangeContainer {
+                    container: "mkv".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_failures_have_no_suggestions() {
+        assert!(suggest_remediations(&lines(&["Conversion failed!"])).is_empty());
+    }
+
+    #[test]
+    fn banner_and_stream_dumps_are_not_failures() {
+        let logs = lines(&[
+            "ffmpeg version 7.1.1 Copyright (c) 2000-2025 the FFmpeg developers",
+            "  configuration: --enable-videotoolbox --enable-hwaccels --enable-audiotoolbox",
+            "  libavcodec     61. 19.101 / 61. 19.101",
+            "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'in.mov':",
+            "  Stream #0:0[0x1](und): Video: hevc (Main) (hvc1 / 0x31637668), yuv420p",
+            "Stream mapping:",
+            "  Stream #0:0 -> #0:0 (hevc (native) -> h264 (h264_videotoolbox))",
+            "[h264_videotoolbox @ 0x1] Using hardware acceleration",
+            "Conversion failed!",
+        ]);
+        assert!(suggest_remediations(&logs).is_empty());
+    }
+
+    #[test]
+    fn software_decode_removes_hwaccel_options() {
+        let args = lines(&[
+            "-hwaccel",
+            "videotoolbox",
+            "-i",
+            "in.mov",
+            "-c:v",
+            "libx264",
+        ]);
+        assert_eq!(
+            apply_remediation(&args, &Remediation::SoftwareDecode),
+            lines(&["-i", "in.mov", "-c:v", "libx264"])
+        );
+    }
+
+    #[test]
+    fn lower_threads_replaces_existing_value() {
+        let args = lines(&["-i", "in.mov", "-threads", "16", "-c:v", "libx265"]);
+        assert_eq!(
+            apply_remediation(&args, &Remediation::LowerThreads { threads: 2 }),
+            lines(&["-i", "in.mov", "-c:v", "libx265", "-threads", "2"])
+        );
+    }
+
+    #[test]
+    fn change_container_updates_explicit_muxer() {
+        let args = lines(&["-i", "in.mov", "-f", "mp4"]);
+        let patched = apply_remediation(
+            &args,
+            &Remediation::ChangeContainer {
+                container: "mkv".into(),
+            },
+        );
+        assert_eq!(patched, lines(&["-i", "in.mov", "-f", "matroska"]));
+    }
+
+    #[test]
+    fn change_container_adds_a_muxer_when_the_extension_chose_it() {
+        let args = lines(&[
+            "-f",
+            "concat",
+            "-i",
+            "list.txt",
+            "-c",
+            "copy",
+            "-movflags",
+            "+faststart",
+        ]);
+        let patched = apply_remediation(
+            &args,
+            &Remediation::ChangeContainer {
+                container: "mkv".into(),
+            },
+        );
+        assert_eq!(
+            patched,
+            lines(&["-f", "concat", "-i", "list.txt", "-c", "copy", "-f", "matroska"])
+        );
+    }
+
+    #[test]
+    fn hardware_encoder_failures_are_recognized() {
+        assert!(hardware_encoder_failed(&lines(&[
+            "[h264_videotoolbox @ 0x7f] Error: cannot create compression session: -12908",
+        ])));
+        assert!(hardware_encoder_failed(&lines(&[
+            "[hevc_nvenc @ 0x1] OpenEncodeSessionEx failed: out of memory (10)",
+        ])));
+        assert!(!hardware_encoder_failed(&lines(&[
+            "[h264_videotoolbox @ 0x7f] Using profile high",
+            "Conversion failed!",
+        ])));
+    }
+
+    #[test]
+    fn software_encoder_replaces_hardware_encoder() {
+        let args = lines(&[
+            "-hwaccel",
+            "videotoolbox",
+            "-i",
+            "in.mov",
+            "-c:v",
+            "hevc_videotoolbox",
+            "-allow_sw",
+            "1",
+            "-b:v",
+            "6000k",
+        ]);
+        assert_eq!(
+            software_encoder_args(&args).unwrap(),
+            lines(&["-i", "in.mov", "-c:v", "libx265", "-b:v", "6000k"])
+        );
+        assert!(software_encoder_args(&lines(&["-i", "in.mov", "-c:v", "libx264"])).is_none());
+    }
+
+    #[test]
+    fn remediations_serialize_with_action_tag() {
+        let json = serde_json::to_value(Remediation::LowerThreads { threads: 2 }).unwrap();
+        assert_eq!(json["action"], "lowerThreads");
+        assert_eq!(json["threads"], 2);
+    }
+}
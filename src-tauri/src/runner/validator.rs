@@ -1,4 +1,89 @@
+use super::output_check::without_file_protocol;
+use crate::cloud_files::{self, INPUT_NOT_DOWNLOADED};
 use crate::error::AppError;
+use crate::fs_utils::long_path;
+use crate::remote_input::{is_network_url, scheme};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Options that read scripts, presets or attachments from, or write logs,
+/// stats and attachments to, paths of their own.
+const FORBIDDEN_OPTIONS: &[&str] = &[
+    "filter_script",
+    "filter_complex_script",
+    "attach",
+    "dump_attachment",
+    "vstats",
+    "vstats_file",
+    "passlogfile",
+    "sdp_file",
+    "report",
+    "stats_enc_pre",
+    "stats_enc_post",
+    "stats_mux_pre",
+    "fpre",
+    "segment_list",
+    "hls_segment_filename",
+    "hls_key_info_file",
+    "hls_fmp4_init_filename",
+    "strftime",
+];
+
+/// Formats that read or write files named inside their input or output,
+/// or write segments and playlists beside it.
+const FORBIDDEN_FORMATS: &[&str] = &[
+    "concat",
+    "lavfi",
+    "tee",
+    "segment",
+    "ssegment",
+    "stream_segment",
+    "hls",
+    "dash",
+];
+
+/// Filters that open files, models or libraries whatever their options.
+const FILE_FILTERS: &[&str] = &[
+    "movie",
+    "amovie",
+    "sendcmd",
+    "asendcmd",
+    "lut1d",
+    "lut3d",
+    "vidstabdetect",
+    "vidstabtransform",
+    "signature",
+    "libvmaf",
+    "ocr",
+    "arnndn",
+    "sr",
+    "dnn_processing",
+    "dnn_detect",
+    "dnn_classify",
+    "ladspa",
+    "lv2",
+    "frei0r",
+    "frei0r_src",
+];
+
+/// Filters with an option that reads or writes a file, the option's names
+/// and the position it takes when given without a name.
+const FILE_OPTIONS: &[(&str, &[&str], usize)] = &[
+    ("drawtext", &["textfile"], 2),
+    ("metadata", &["file"], 5),
+    ("ametadata", &["file"], 5),
+    ("psnr", &["stats_file", "f"], 0),
+    ("ssim", &["stats_file", "f"], 0),
+    ("curves", &["psfile"], 6),
+];
+
+/// Filters that render a subtitle file named by their first option. They
+/// may open the job's inputs and subtitle files, but nothing else.
+const SUBTITLE_FILTERS: &[&str] = &["subtitles", "ass"];
+
+const SUBTITLE_EXTENSIONS: &[&str] = &["srt", "ass", "ssa", "vtt"];
 
 /// Validates FFmpeg arguments for security and correctness
 pub struct JobValidator;
@@ -8,30 +93,151 @@ impl JobValidator {
         Self
     }
 
-    /// Validates that FFmpeg arguments are safe and non-empty
-    pub fn validate_args(&self, args: &[String]) -> Result<&Self, AppError> {
+    /// Validates that FFmpeg arguments are non-empty and don't make ffmpeg
+    /// open files other than the job's inputs and output. ffmpeg is spawned
+    /// without a shell, so `&`, `;`, `|` and backticks in filtergraphs and
+    /// file names are harmless; what's refused are wrapper protocols on
+    /// inputs, virtual and segmenting formats, image sequence outputs,
+    /// options that read scripts or write logs and side files, and filters
+    /// that open files other than the inputs and subtitles. Inputs with a
+    /// protocol must be `file:` paths or network URLs.
+    /// Paths that aren't UTF-8 are checked through a lossy copy.
+    pub fn validate_args<A: AsRef<OsStr>>(&self, args: &[A]) -> Result<&Self, AppError> {
         if args.is_empty() {
             return Err(AppError::new(
                 "job_invalid_args",
                 "FFmpeg arguments must not be empty.",
             ));
         }
+        let args: Vec<_> = args
+            .iter()
+            .map(|arg| arg.as_ref().to_string_lossy())
+            .collect();
+        let inputs: Vec<&Path> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "-i")
+            .map(|pair| without_file_protocol(OsStr::new(&*pair[1])))
+            .collect();
+
+        let unsafe_arg = |arg: &str, reason: &str| {
+            Err(AppError::new(
+                "job_invalid_args",
+                format!("Unsafe argument {arg:?}: {reason}."),
+            ))
+        };
+        for (index, arg) in args.iter().enumerate() {
+            if arg.contains('\0') {
+                return unsafe_arg(arg, "contains a NUL byte");
+            }
+            let value = args.get(index + 1).map(|value| &**value).unwrap_or("");
+            let Some(option) = arg.strip_prefix('-') else {
+                continue;
+            };
+            if option.starts_with('/') {
+                return unsafe_arg(arg, "option values may not be read from files");
+            }
+            let base = option.split(':').next().unwrap_or(option);
+            if FORBIDDEN_OPTIONS.contains(&base) {
+                return unsafe_arg(arg, "the option reads or writes files by itself");
+            }
+            match base {
+                "i" => {
+                    let allowed = match scheme(value) {
+                        None => true,
+                        Some(scheme) => scheme == "file" || is_network_url(value),
+                    };
+                    if !allowed {
+                        return unsafe_arg(
+                            value,
+                            "inputs may only be files or http(s), RTSP and SRT URLs",
+                        );
+                    }
+                },
+                "f" if FORBIDDEN_FORMATS.contains(&value) => {
+                    return unsafe_arg(value, "this format opens other files");
+                },
+                "f" if value == "image2" => {
+                    if let Some(pattern) = args[index + 2..]
+                        .iter()
+                        .find(|arg| is_sequence_pattern(arg))
+                    {
+                        return unsafe_arg(pattern, "image sequences write a file per frame");
+                    }
+                },
+                "progress" if !value.starts_with("pipe:") => {
+                    return unsafe_arg(value, "progress may only be written to a pipe");
+                },
+                "vf" | "af" | "filter" | "filter_complex" | "lavfi"
+                    if opens_files(value, &inputs) =>
+                {
+                    return unsafe_arg(value, "filters may not open files");
+                },
+                _ => {},
+            }
+        }
+
+        Ok(self)
+    }
 
-        // Security validation: prevent command injection attacks
-        for arg in args {
-            if arg.contains(';')
-                || arg.contains('|')
-                || arg.contains('&')
-                || arg.starts_with("$(")
-                || arg.contains("`")
-            {
+    /// Rejects jobs whose output is one of their inputs, which ffmpeg
+    /// would destroy while reading it. Paths are compared as files, so
+    /// links, relative paths and case differences are caught too.
+    pub fn validate_paths<A: AsRef<OsStr>>(
+        &self,
+        args: &[A],
+        output_path: &Path,
+    ) -> Result<&Self, AppError> {
+        for input in input_paths(args) {
+            if same_file(input, output_path) {
                 return Err(AppError::new(
-                    "job_invalid_args",
-                    format!("Unsafe argument detected: {}", arg),
+                    "job_output_is_input",
+                    format!(
+                        "The output {} would overwrite the input {}.",
+                        output_path.display(),
+                        input.display()
+                    ),
                 ));
             }
         }
+        Ok(self)
+    }
 
+    /// Checks that every local input exists, is a readable file and isn't
+    /// empty or only stored online, so a bad source fails with a clear code
+    /// instead of an ffmpeg exit status. URLs, protocols, stdin and image sequence patterns are
+    /// left to ffmpeg.
+    pub fn validate_inputs<A: AsRef<OsStr>>(&self, args: &[A]) -> Result<&Self, AppError> {
+        for input in input_paths(args).filter(|input| is_local_file(&input.to_string_lossy())) {
+            let fail = |code: &'static str, reason: &str| {
+                Err(AppError::new(
+                    code,
+                    format!("Input {} {reason}.", input.display()),
+                ))
+            };
+            if cloud_files::is_online_only(input) {
+                return fail(
+                    INPUT_NOT_DOWNLOADED,
+                    "is only stored online; download it to this device first",
+                );
+            }
+            let path = long_path(input);
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    return fail("job_input_missing", "does not exist");
+                },
+                Err(err) => return fail("job_input_unreadable", &format!("can't be read: {err}")),
+            };
+            if !metadata.is_file() {
+                return fail("job_input_not_file", "is not a file");
+            }
+            if let Err(err) = fs::File::open(&path) {
+                return fail("job_input_unreadable", &format!("can't be read: {err}"));
+            }
+            if metadata.len() == 0 {
+                return fail("job_input_empty", "is empty");
+            }
+        }
         Ok(self)
     }
 
@@ -83,6 +289,180 @@ impl JobValidator {
     }
 }
 
+/// Paths given to `-i`, without the `file:` prefix typed commands add to
+/// local paths.
+fn input_paths<A: AsRef<OsStr>>(args: &[A]) -> impl Iterator<Item = &Path> {
+    args.windows(2)
+        .filter(|pair| pair[0].as_ref() == "-i")
+        .map(|pair| without_file_protocol(pair[1].as_ref()))
+}
+
+/// Whether ffmpeg reads `input` as a plain file: not stdin, a URL or
+/// protocol such as `concat:` (a drive letter doesn't count), or a
+/// `%03d`/glob sequence pattern.
+fn is_local_file(input: &str) -> bool {
+    !(input == "-" || scheme(input).is_some() || input.contains(['%', '*', '?']))
+}
+
+/// Whether `name` has an image2 sequence number such as `%d` or `%04d`.
+fn is_sequence_pattern(name: &str) -> bool {
+    name.split('%').skip(1).any(|rest| {
+        rest.trim_start_matches(|c: char| c.is_ascii_digit())
+            .starts_with('d')
+    })
+}
+
+/// Whether a filtergraph has a filter that opens a file other than one of
+/// `inputs` or a subtitle file.
+fn opens_files(graph: &str, inputs: &[&Path]) -> bool {
+    filters(graph).into_iter().any(|(name, args)| {
+        if FILE_FILTERS.contains(&name.as_str()) {
+            return true;
+        }
+        let options = filter_options(&args);
+        if let Some((_, names, position)) = FILE_OPTIONS.iter().find(|(filter, ..)| *filter == name)
+        {
+            return option(&options, names, *position).is_some();
+        }
+        if SUBTITLE_FILTERS.contains(&name.as_str()) {
+            return option(&options, &["filename", "f"], 0)
+                .is_some_and(|file| !is_subtitle_source(file, inputs));
+        }
+        false
+    })
+}
+
+/// The filters of `graph` as names, without an `@instance` suffix, and
+/// their arguments, read the way ffmpeg's graph parser reads them.
+fn filters(graph: &str) -> Vec<(String, String)> {
+    let mut filters = Vec::new();
+    let mut rest = graph;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == ';');
+        if let Some(label) = rest.strip_prefix('[') {
+            rest = label.split_once(']').map_or("", |(_, after)| after);
+            continue;
+        }
+        if rest.is_empty() {
+            return filters;
+        }
+        let (name, after) = get_token(rest, &['=', ',', ';', '[']);
+        let (args, after) = match after.strip_prefix('=') {
+            Some(args) => get_token(args, &['[', ']', ',', ';']),
+            None => (String::new(), after),
+        };
+        let name = name.split('@').next().unwrap_or_default().to_string();
+        filters.push((name, args));
+        rest = after;
+    }
+}
+
+/// A filter's options as `(name, value)` pairs; options given by position
+/// have no name.
+fn filter_options(args: &str) -> Vec<(Option<&str>, String)> {
+    let mut options = Vec::new();
+    let mut rest = args;
+    while !rest.is_empty() {
+        let key_len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/' | '.')))
+            .unwrap_or(rest.len());
+        let key = (key_len > 0 && rest[key_len..].starts_with('=')).then(|| &rest[..key_len]);
+        let start = key.map_or(0, |key| key.len() + 1);
+        let (value, after) = get_token(&rest[start..], &[':']);
+        options.push((key, value));
+        rest = after.strip_prefix(':').unwrap_or(after);
+    }
+    options
+}
+
+/// The value of the option called one of `names`, or given as the
+/// `position`th of the leading unnamed options.
+fn option<'a>(
+    options: &'a [(Option<&str>, String)],
+    names: &[&str],
+    position: usize,
+) -> Option<&'a str> {
+    options
+        .iter()
+        .find(|(key, _)| key.is_some_and(|key| names.contains(&key)))
+        .or_else(|| {
+            options
+                .iter()
+                .take_while(|(key, _)| key.is_none())
+                .nth(position)
+        })
+        .map(|(_, value)| value.as_str())
+}
+
+/// Reads a token the way ffmpeg's `av_get_token` does: up to the first
+/// unquoted, unescaped character of `terms`, dropping one level of `\`
+/// escapes and `'` quotes and the whitespace around it. Returns the token
+/// and the rest, starting at the terminator.
+fn get_token<'a>(input: &'a str, terms: &[char]) -> (String, &'a str) {
+    let input = input.trim_start();
+    let mut token = String::new();
+    // Length without trailing whitespace that wasn't quoted or escaped.
+    let mut kept = 0;
+    let mut chars = input.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if terms.contains(&c) {
+            token.truncate(kept);
+            return (token, &input[index..]);
+        }
+        match c {
+            '\\' => {
+                token.extend(chars.next().map(|(_, escaped)| escaped));
+                kept = token.len();
+            },
+            '\'' => {
+                token.extend(chars.by_ref().map(|(_, c)| c).take_while(|&c| c != '\''));
+                kept = token.len();
+            },
+            _ => {
+                token.push(c);
+                if !c.is_whitespace() {
+                    kept = token.len();
+                }
+            },
+        }
+    }
+    token.truncate(kept);
+    (token, "")
+}
+
+/// Whether a subtitle filter may open `file`: a local file that is one of
+/// the job's inputs or has a subtitle extension.
+fn is_subtitle_source(file: &str, inputs: &[&Path]) -> bool {
+    let path = without_file_protocol(OsStr::new(file));
+    let local = matches!(scheme(file), None | Some("file"));
+    let subtitle = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| SUBTITLE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    local && (subtitle || inputs.contains(&path))
+}
+
+/// Whether both paths name the same existing file.
+fn same_file(a: &Path, b: &Path) -> bool {
+    let (a, b) = (&long_path(a), &long_path(b));
+    let (Ok(a_meta), Ok(b_meta)) = (fs::metadata(a), fs::metadata(b)) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        a_meta.dev() == b_meta.dev() && a_meta.ino() == b_meta.ino()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a_meta, b_meta);
+        matches!(
+            (fs::canonicalize(a), fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+    }
+}
+
 impl Default for JobValidator {
     fn default() -> Self {
         Self::new()
@@ -100,24 +480,67 @@ mod tests {
     #[test]
     fn test_validate_args_empty() {
         let validator = JobValidator::new();
-        assert!(validator.validate_args(&[]).is_err());
+        assert!(validator.validate_args::<String>(&[]).is_err());
     }
 
     #[test]
     fn test_validate_args_injection() {
         let validator = JobValidator::new();
-        let dangerous = vec![
-            "arg;rm -rf /".to_string(),
-            "arg|cat".to_string(),
-            "$(whoami)".to_string(),
-            "`date`".to_string(),
+        let dangerous: Vec<&[&str]> = vec![
+            &["-i", "concat:a.ts|/etc/passwd"],
+            &["-i", "SUBFILE:,start,0,end,0,:secret.mov"],
+            &["-i", "subfile,,start,0,end,0,,:/etc/passwd"],
+            &["-i", "crypto+file:/x"],
+            &["-i", "ftp://example.com/a.mp4"],
+            &["-i", "tcp://10.0.0.2:9000"],
+            &["-f", "lavfi", "-i", "movie=/etc/passwd"],
+            &["-f", "concat", "-safe", "0", "-i", "list.txt"],
+            &["-i", "in.mp4", "-f", "tee", "a.mp4|/tmp/b.mp4"],
+            &[
+                "-i",
+                "in.mp4",
+                "-vf",
+                "scale=640:-2,movie=/etc/passwd[m];[0][m]overlay",
+            ],
+            &["-i", "in.mp4", "-filter_complex_script", "graph.txt"],
+            &["-i", "in.mp4", "-vf", "[in]movie@x=/etc/passwd"],
+            &["-i", "in.mp4", "-vf", "null,amovie@a='/etc/passwd'"],
+            &["-i", "in.mp4", "-vf", "subtitles=/etc/passwd"],
+            &["-i", "in.mp4", "-vf", "ass=f=concat\\:a.srt"],
+            &["-i", "in.mp4", "-vf", "drawtext=textfile=/etc/passwd"],
+            &["-i", "in.mp4", "-vf", "drawtext=Sans.ttf:hi:/etc/passwd"],
+            &["-i", "in.mp4", "-vf", "lut3d=file=grade.cube"],
+            &["-i", "in.mp4", "-af", "asendcmd=f=cmds.txt,volume"],
+            &["-i", "in.mp4", "-vf", "metadata=mode=print:file=/tmp/x"],
+            &["-i", "in.mp4", "-vf", "vidstabdetect=result=/tmp/x.trf"],
+            &["-i", "in.mp4", "-vstats_file", "/tmp/x"],
+            &["-i", "in.mp4", "-passlogfile", "/tmp/x"],
+            &["-i", "in.mp4", "-progress", "/tmp/progress.txt"],
+            &["-i", "in.mp4", "-/filter:v", "graph.txt"],
+            &["-i", "in\0.mp4"],
+            &["-i", "in.mp4", "-f", "segment", "/tmp/out%03d.mp4"],
+            &["-i", "in.mp4", "-f", "stream_segment", "out%03d.ts"],
+            &["-i", "in.mp4", "-segment_list", "/tmp/list.csv", "out.mp4"],
+            &["-i", "in.mp4", "-f", "hls", "out.m3u8"],
+            &[
+                "-i",
+                "in.mp4",
+                "-hls_segment_filename",
+                "/tmp/s%d.ts",
+                "o.m3u8",
+            ],
+            &["-i", "in.mp4", "-f", "dash", "out.mpd"],
+            &["-i", "in.mp4", "-f", "image2", "/tmp/frame%04d.png"],
+            &["-i", "in.mp4", "-f", "image2", "-y", "frame%d.jpg"],
+            &["-i", "in.mp4", "-strftime", "1", "-f", "image2", "%Y.png"],
         ];
 
-        for arg in dangerous {
+        for args in dangerous {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
             assert!(
-                validator.validate_args(std::slice::from_ref(&arg)).is_err(),
-                "Should reject: {}",
-                arg
+                validator.validate_args(&args).is_err(),
+                "Should reject: {:?}",
+                args
             );
         }
     }
@@ -132,6 +555,175 @@ mod tests {
         ];
 
         assert!(validator.validate_args(&safe).is_ok());
+        let thumbnail: Vec<String> = [
+            "-i",
+            "in.mp4",
+            "-frames:v",
+            "1",
+            "-f",
+            "image2",
+            "50% off.png",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        assert!(validator.validate_args(&thumbnail).is_ok());
+    }
+
+    #[test]
+    fn network_urls_are_accepted_as_inputs() {
+        let validator = JobValidator::new();
+        for url in [
+            "https://example.com/a.mp4",
+            "rtsp://camera.local/stream",
+            "srt://10.0.0.2:9000",
+        ] {
+            let args = ["-i", url, "-c", "copy"];
+            assert!(validator.validate_args(&args).is_ok(), "{url}");
+            assert!(validator.validate_inputs(&args).is_ok(), "{url}");
+        }
+    }
+
+    #[test]
+    fn filtergraphs_and_file_names_may_use_shell_characters() {
+        let args: Vec<String> = [
+            "-i",
+            "Tom & Jerry; `Pilot` | 1940.mov",
+            "-i",
+            "https://example.com/a.mp4",
+            "-filter_complex",
+            "[0:v]split[a][b];[a]scale=1280:-2[out];[b]nullsink",
+            "-vf",
+            "drawtext=text='$(date) & more'",
+            "-f",
+            "mp4",
+            "out.mp4",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        assert!(JobValidator::new().validate_args(&args).is_ok());
+    }
+
+    #[test]
+    fn subtitle_filters_may_open_inputs_and_subtitle_files() {
+        let validator = JobValidator::new();
+        for graph in [
+            "subtitles=filename=/media/movie.mkv:si=1",
+            "crop=1920:800:0:140,subtitles=filename=C\\\\\\:\\\\\\\\subs\\\\\\\\it\\\\\\'s.srt",
+            "subtitles='/media/Tom & Jerry.ass':fontsdir=/Library/Fonts",
+            "drawtext=fontfile=Sans.ttf:text='a\\:textfile=b'",
+            "metadata=mode=print",
+        ] {
+            let args = ["-i", "/media/movie.mkv", "-vf", graph, "out.mp4"];
+            assert!(validator.validate_args(&args).is_ok(), "{graph}");
+        }
+    }
+
+    #[test]
+    fn filters_are_read_like_ffmpeg_reads_them() {
+        assert_eq!(
+            filters("[0:v]split@s[a][b]; [a] scale = 1280:-2 [out],null"),
+            [
+                ("split".to_string(), String::new()),
+                ("scale".to_string(), "1280:-2".to_string()),
+                ("null".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(
+            filter_options("C\\:\\\\x.srt:si=1:'a:b'"),
+            [
+                (None, "C:\\x.srt".to_string()),
+                (Some("si"), "1".to_string()),
+                (None, "a:b".to_string()),
+            ]
+        );
+
+        let graph = filters(r"subtitles=filename=C\\\:\\\\subs\\\\it\\\'s.srt");
+        assert_eq!(
+            filter_options(&graph[0].1),
+            [(Some("filename"), r"C:\subs\it's.srt".to_string())]
+        );
+    }
+
+    #[test]
+    fn planned_subtitle_paths_read_back_unchanged() {
+        for (path, fonts) in [
+            (r"C:\subs\it's,[1].srt", r"C:\Windows\Fonts"),
+            ("/media/Tom & Jerry; Part=2.ass", "/Library/Fonts"),
+            (r"\\server\share\a'b'c.srt", r"D:\fonts [x]"),
+        ] {
+            let request = serde_json::from_value(serde_json::json!({
+                "crop": { "width": 1, "height": 1 },
+                "subtitles": { "path": path, "streamIndex": 0, "fontsDir": fonts },
+            }))
+            .unwrap();
+            let graph = crate::planning::build_filtergraph(&request)
+                .filters
+                .join(",");
+            let parsed = filters(&graph);
+            assert_eq!(parsed.len(), 2, "{graph}");
+            let options = filter_options(&parsed[1].1);
+            assert_eq!(option(&options, &["filename"], 0), Some(path), "{graph}");
+            assert_eq!(option(&options, &["fontsdir"], 0), Some(fonts), "{graph}");
+            assert_eq!(option(&options, &["si"], 0), Some("0"), "{graph}");
+        }
+    }
+
+    #[test]
+    fn outputs_may_not_overwrite_inputs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("clip.mov");
+        std::fs::write(&source, b"frames").unwrap();
+        let args = vec![
+            "-i".to_string(),
+            "/other/audio.wav".to_string(),
+            "-i".to_string(),
+            source.to_string_lossy().into_owned(),
+        ];
+        let validator = JobValidator::new();
+
+        let alias = dir.path().join(".").join("clip.mov");
+        let Err(err) = validator.validate_paths(&args, &alias) else {
+            panic!("an output aliasing the input must be rejected");
+        };
+        assert_eq!(err.code, "job_output_is_input");
+
+        let output = dir.path().join("clip.mp4");
+        assert!(validator.validate_paths(&args, &output).is_ok());
+    }
+
+    #[test]
+    fn inputs_must_be_readable_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("clip.mov");
+        let empty = dir.path().join("empty.mov");
+        std::fs::write(&source, b"frames").unwrap();
+        std::fs::write(&empty, b"").unwrap();
+        let code = |input: &std::path::Path| {
+            let args = vec!["-i".to_string(), input.to_string_lossy().into_owned()];
+            JobValidator::new()
+                .validate_inputs(&args)
+                .err()
+                .map(|err| err.code)
+        };
+
+        assert_eq!(code(&source), None);
+        assert_eq!(
+            code(&dir.path().join("gone.mov")),
+            Some("job_input_missing")
+        );
+        assert_eq!(code(dir.path()), Some("job_input_not_file"));
+        assert_eq!(code(&empty), Some("job_input_empty"));
+        for remote in [
+            "https://example.com/a.mp4",
+            "concat:a.ts|b.ts",
+            "-",
+            "frame_%04d.png",
+        ] {
+            assert_eq!(code(std::path::Path::new(remote)), None, "{remote}");
+        }
     }
 
     fn stub_process(exclusive: bool) -> Arc<RunningProcess> {
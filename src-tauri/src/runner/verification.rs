@@ -0,0 +1,210 @@
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::{
+    events::{SharedEmitter, VerificationPayload},
+    job_registry::JobRegistry,
+    progress_monitor::{ProgressMonitor, RunningProcess},
+};
+use crate::sync_utils::MutexExt;
+
+/// Decoder arguments for a verification pass: every frame is decoded and
+/// discarded, and only errors are written to stderr. `limit_sec` stops
+/// reading after that much of the input, for a quick spot check.
+pub fn verification_args(path: &str, limit_sec: Option<f64>) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostats", "-v", "error"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    if let Some(limit) = limit_sec {
+        args.extend(["-t".to_string(), format!("{limit:.3}")]);
+    }
+    args.extend(["-i", path, "-f", "null"].iter().map(|arg| arg.to_string()));
+    args
+}
+
+/// Watches a verification-only ffmpeg process and reports decode errors.
+pub struct VerificationMonitor;
+
+impl VerificationMonitor {
+    pub fn start(
+        emitter: SharedEmitter,
+        registry: Arc<JobRegistry>,
+        job_id: String,
+        process: Arc<RunningProcess>,
+        path: PathBuf,
+        checksum: bool,
+        on_exit: impl FnOnce() + Send + 'static,
+    ) {
+        tauri::async_runtime::spawn_blocking(move || {
+            let decode_errors = Self::collect_errors(&emitter, &job_id, &process);
+            let payload = Self::finish(&job_id, &process, &path, decode_errors, checksum);
+            emitter.emit_verification(&payload);
+            registry.remove(&job_id);
+            on_exit();
+        });
+    }
+
+    fn collect_errors(emitter: &SharedEmitter, job_id: &str, process: &RunningProcess) -> usize {
+        let mut child_guard = process.child.lock_or_recover();
+        let Some(stderr) = child_guard.as_mut().and_then(|child| child.stderr.take()) else {
+            return 0;
+        };
+        drop(child_guard);
+
+        let mut count = 0;
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            count += 1;
+            process.push_log(&line);
+            emitter.emit_stderr(job_id, &line);
+        }
+        count
+    }
+
+    fn finish(
+        job_id: &str,
+        process: &Arc<RunningProcess>,
+        path: &Path,
+        decode_errors: usize,
+        checksum: bool,
+    ) -> VerificationPayload {
+        let exit_status = ProgressMonitor::wait_for_exit(job_id, process);
+        let cancelled = process.is_cancelled();
+        let exit_code = exit_status.as_ref().ok().and_then(|status| status.code());
+        let exited_cleanly = exit_status
+            .as_ref()
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        if let Err(err) = &exit_status {
+            process.push_log(&format!("ffmpeg wait error: {}", err.message));
+        }
+
+        let checksum = if checksum && !cancelled {
+            match sha256_hex(path) {
+                Ok(digest) => Some(digest),
+                Err(err) => {
+                    process.push_log(&format!("checksum failed: {err}"));
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        VerificationPayload {
+            job_id: job_id.to_string(),
+            path: path.to_string_lossy().into_owned(),
+            success: exited_cleanly && !cancelled && decode_errors == 0,
+            cancelled,
+            exit_code,
+            decode_errors,
+            errors: process.drain_logs(),
+            checksum,
+        }
+    }
+}
+
+/// Streams the file through SHA-256 and returns the lowercase hex digest.
+pub fn sha256_hex(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+    use tempfile::TempDir;
+
+    fn finished_process(script: &str) -> Arc<RunningProcess> {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn sh");
+        Arc::new(RunningProcess::new(child, false))
+    }
+
+    #[test]
+    fn args_decode_to_null_muxer() {
+        let args = verification_args("/media/clip.mkv", None);
+        assert_eq!(args[args.len() - 3..], ["/media/clip.mkv", "-f", "null"]);
+        assert!(args.contains(&"error".to_string()));
+        assert!(!args.contains(&"-t".to_string()));
+    }
+
+    #[test]
+    fn limited_checks_stop_reading_the_input_early() {
+        let args = verification_args("/media/clip.mkv", Some(30.0));
+        let limit = args.iter().position(|arg| arg == "-t").unwrap();
+        assert_eq!(args[limit + 1], "30.000");
+        assert!(limit < args.iter().position(|arg| arg == "-i").unwrap());
+    }
+
+    #[test]
+    fn sha256_matches_known_digest() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("abc.bin");
+        std::fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            sha256_hex(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn decode_errors_fail_verification_and_checksum_is_attached() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("clip.mkv");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let process = finished_process("exit 0");
+        process.push_log("[h264] corrupt macroblock");
+        let payload = VerificationMonitor::finish("job", &process, &path, 1, true);
+
+        assert!(!payload.success);
+        assert_eq!(payload.exit_code, Some(0));
+        assert_eq!(payload.errors, vec!["[h264] corrupt macroblock"]);
+        assert!(payload.checksum.is_some());
+    }
+
+    #[test]
+    fn cancelled_verification_skips_checksum() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("clip.mkv");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let process = finished_process("exit 0");
+        process.mark_cancelled();
+        let payload = VerificationMonitor::finish("job", &process, &path, 0, true);
+
+        assert!(payload.cancelled);
+        assert!(!payload.success);
+        assert!(payload.checksum.is_none());
+    }
+}
@@ -0,0 +1,183 @@
+//! Stall detection for running conversions.
+//!
+//! Some encodes hang without exiting or printing progress. A watchdog runs
+//! beside each conversion and stops it once no progress line has arrived
+//! for the configured timeout; the job then fails with `job_stalled`.
+//! Like a cancel, ffmpeg is first asked to quit so it can close the output
+//! it has written, and is killed only if it keeps running.
+//!
+//! After `progress=end` ffmpeg reports nothing while it writes the trailer,
+//! which for `+faststart` rewrites the whole file, so that phase is exempt.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{process_spawner::ProcessSpawner, progress_monitor::RunningProcess};
+use crate::sync_utils::MutexExt;
+
+pub const JOB_STALLED: &str = "job_stalled";
+
+/// Default time without progress before a job counts as stalled.
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long a stalled job may take to quit before it is killed.
+const STALL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+const MIN_POLL: Duration = Duration::from_millis(100);
+const MAX_POLL: Duration = Duration::from_secs(5);
+
+/// Stall timeout shared by every clone of the coordinator; zero disables
+/// the watchdog.
+#[derive(Debug, Clone)]
+pub struct StallTimeout(Arc<AtomicU64>);
+
+impl Default for StallTimeout {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU64::new(
+            DEFAULT_STALL_TIMEOUT.as_millis() as u64
+        )))
+    }
+}
+
+impl StallTimeout {
+    pub fn get(&self) -> Option<Duration> {
+        match self.0.load(Ordering::SeqCst) {
+            0 => None,
+            millis => Some(Duration::from_millis(millis)),
+        }
+    }
+
+    pub fn set(&self, timeout: Option<Duration>) {
+        let millis = timeout.map_or(0, |timeout| timeout.as_millis().max(1) as u64);
+        self.0.store(millis, Ordering::SeqCst);
+    }
+}
+
+/// Watches `process` on its own thread until it exits.
+pub fn spawn(job_id: String, process: Arc<RunningProcess>, timeout: Duration) {
+    std::thread::spawn(move || watch(&job_id, &process, timeout));
+}
+
+fn watch(job_id: &str, process: &Arc<RunningProcess>, timeout: Duration) {
+    let poll = (timeout / 4).clamp(MIN_POLL, MAX_POLL);
+    loop {
+        std::thread::sleep(poll);
+        if process.is_cancelled() {
+            return;
+        }
+
+        // The monitor holds the lock only while waiting for exit, and
+        // takes the child once it has exited.
+        let Some(mut child_guard) = process.child.try_lock_or_recover() else {
+            continue;
+        };
+        let Some(child) = child_guard.as_mut() else {
+            return;
+        };
+        if !matches!(child.try_wait(), Ok(None)) {
+            return;
+        }
+        if process.is_paused() || process.is_finishing() {
+            // A suspended job makes no progress by design, and a finishing
+            // one reports none while writing the trailer.
+            process.touch_progress();
+            continue;
+        }
+        if process.idle_for() < timeout {
+            continue;
+        }
+        process.mark_stalled();
+        process.push_log(&format!(
+            "No progress for {} s; stopping ffmpeg.",
+            timeout.as_secs()
+        ));
+        if ProcessSpawner::request_quit(child).is_ok() {
+            drop(child_guard);
+            kill_after_grace(job_id.to_string(), Arc::clone(process), STALL_GRACE_PERIOD);
+        } else if let Err(err) = ProcessSpawner::kill_tree(child) {
+            eprintln!("[jobs] failed stopping stalled job {job_id}: {err}");
+        }
+        return;
+    }
+}
+
+/// Kills `process` if it is still running after `grace`, for a job that
+/// was asked to quit.
+pub(super) fn kill_after_grace(job_id: String, process: Arc<RunningProcess>, grace: Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(grace);
+        let mut child_guard = process.child.lock_or_recover();
+        let Some(child) = child_guard.as_mut() else {
+            return;
+        };
+        if matches!(child.try_wait(), Ok(None)) {
+            process.push_log("ffmpeg did not quit when asked; killing it.");
+            if let Err(err) = ProcessSpawner::kill_tree(child) {
+                eprintln!("[jobs] failed killing job {job_id}: {err}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn shell_process(script: &str, stdin: Stdio) -> Arc<RunningProcess> {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdin(stdin)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn shell");
+        Arc::new(RunningProcess::new(child, false))
+    }
+
+    #[test]
+    fn silent_processes_are_killed() {
+        let process = shell_process("sleep 5", Stdio::null());
+
+        watch("job", &process, Duration::from_millis(200));
+
+        assert!(process.is_stalled());
+        let status = process.child.lock().unwrap().as_mut().unwrap().wait();
+        assert!(!status.unwrap().success());
+    }
+
+    #[test]
+    fn stalled_processes_are_asked_to_quit_first() {
+        let process = shell_process("head -c 1 >/dev/null; exit 0", Stdio::piped());
+
+        watch("job", &process, Duration::from_millis(200));
+
+        assert!(process.is_stalled());
+        let status = process.child.lock().unwrap().as_mut().unwrap().wait();
+        assert!(status.unwrap().success(), "process should quit on its own");
+    }
+
+    #[test]
+    fn the_trailer_phase_is_exempt() {
+        let process = shell_process("sleep 1", Stdio::null());
+        process.mark_finishing();
+
+        watch("job", &process, Duration::from_millis(200));
+
+        assert!(!process.is_stalled());
+        let status = process.child.lock().unwrap().as_mut().unwrap().wait();
+        assert!(status.unwrap().success());
+    }
+
+    #[test]
+    fn zero_disables_the_timeout() {
+        let timeout = StallTimeout::default();
+        assert_eq!(timeout.get(), Some(DEFAULT_STALL_TIMEOUT));
+        timeout.set(None);
+        assert_eq!(timeout.get(), None);
+        timeout.set(Some(Duration::from_secs(30)));
+        assert_eq!(timeout.get(), Some(Duration::from_secs(30)));
+    }
+}
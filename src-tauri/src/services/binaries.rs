@@ -0,0 +1,78 @@
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+
+use crate::{binary_resolver, error::AppError};
+
+pub trait BinaryServiceApi: Send + Sync {
+    fn install_managed(&self, app: &AppHandle)
+        -> Result<binary_resolver::ManagedInstall, AppError>;
+    fn managed(&self, app: &AppHandle) -> Option<binary_resolver::ManagedInstall>;
+    fn set_preferred_ffmpeg(
+        &self,
+        app: &AppHandle,
+        path: Option<&Path>,
+    ) -> Result<binary_resolver::BinaryInfo, AppError>;
+    fn info(&self, app: &AppHandle) -> binary_resolver::BinaryInfo;
+    fn diagnose(&self, app: &AppHandle) -> binary_resolver::BinaryDiagnostics;
+    fn installations(&self, app: &AppHandle) -> binary_resolver::FfmpegInstallations;
+    fn register_installation(
+        &self,
+        app: &AppHandle,
+        label: &str,
+        path: &Path,
+    ) -> Result<binary_resolver::FfmpegInstallation, AppError>;
+    fn remove_installation(&self, app: &AppHandle, id: &str) -> Result<(), AppError>;
+}
+
+/// Chooses, installs and reports the FFmpeg binaries Honeymelon runs.
+#[derive(Clone, Default)]
+pub struct BinaryService;
+
+impl BinaryServiceApi for BinaryService {
+    fn install_managed(
+        &self,
+        app: &AppHandle,
+    ) -> Result<binary_resolver::ManagedInstall, AppError> {
+        binary_resolver::install_managed_ffmpeg(app, &mut |progress| {
+            app.emit(binary_resolver::DOWNLOAD_PROGRESS_EVENT, &progress)
+                .ok();
+        })
+    }
+
+    fn managed(&self, app: &AppHandle) -> Option<binary_resolver::ManagedInstall> {
+        binary_resolver::managed_install(app)
+    }
+
+    fn set_preferred_ffmpeg(
+        &self,
+        app: &AppHandle,
+        path: Option<&Path>,
+    ) -> Result<binary_resolver::BinaryInfo, AppError> {
+        binary_resolver::set_preferred_ffmpeg(app, path)
+    }
+
+    fn info(&self, app: &AppHandle) -> binary_resolver::BinaryInfo {
+        binary_resolver::binary_info(app)
+    }
+
+    fn diagnose(&self, app: &AppHandle) -> binary_resolver::BinaryDiagnostics {
+        binary_resolver::diagnose_binaries(app)
+    }
+
+    fn installations(&self, app: &AppHandle) -> binary_resolver::FfmpegInstallations {
+        binary_resolver::ffmpeg_installations(app)
+    }
+
+    fn register_installation(
+        &self,
+        app: &AppHandle,
+        label: &str,
+        path: &Path,
+    ) -> Result<binary_resolver::FfmpegInstallation, AppError> {
+        binary_resolver::register_ffmpeg_installation(app, label, path)
+    }
+
+    fn remove_installation(&self, app: &AppHandle, id: &str) -> Result<(), AppError> {
+        binary_resolver::remove_ffmpeg_installation(app, id)
+    }
+}
@@ -4,6 +4,18 @@ use crate::{error::AppError, ffmpeg_capabilities};
 
 pub trait CapabilityServiceApi: Send + Sync {
     fn load(&self, app: &AppHandle) -> Result<ffmpeg_capabilities::CapabilitySnapshot, AppError>;
+    fn refresh(&self, app: &AppHandle)
+        -> Result<ffmpeg_capabilities::CapabilitySnapshot, AppError>;
+    fn encoder_options(
+        &self,
+        app: &AppHandle,
+        name: &str,
+    ) -> Result<ffmpeg_capabilities::EncoderOptions, AppError>;
+    fn validate_preset(
+        &self,
+        app: &AppHandle,
+        preset: &ffmpeg_capabilities::PlannedPreset,
+    ) -> Result<ffmpeg_capabilities::PresetValidation, AppError>;
 }
 
 /// Service wrapper for FFmpeg capability discovery.
@@ -14,4 +26,28 @@ impl CapabilityServiceApi for CapabilityService {
     fn load(&self, app: &AppHandle) -> Result<ffmpeg_capabilities::CapabilitySnapshot, AppError> {
         ffmpeg_capabilities::load_capabilities(app)
     }
+
+    fn refresh(
+        &self,
+        app: &AppHandle,
+    ) -> Result<ffmpeg_capabilities::CapabilitySnapshot, AppError> {
+        ffmpeg_capabilities::refresh_capabilities(app)
+    }
+
+    fn encoder_options(
+        &self,
+        app: &AppHandle,
+        name: &str,
+    ) -> Result<ffmpeg_capabilities::EncoderOptions, AppError> {
+        ffmpeg_capabilities::encoder_options(app, name)
+    }
+
+    fn validate_preset(
+        &self,
+        app: &AppHandle,
+        preset: &ffmpeg_capabilities::PlannedPreset,
+    ) -> Result<ffmpeg_capabilities::PresetValidation, AppError> {
+        let snapshot = ffmpeg_capabilities::load_capabilities(app)?;
+        Ok(ffmpeg_capabilities::validate_preset(&snapshot, preset))
+    }
 }
@@ -0,0 +1,208 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+use crate::{app_mode::AppMode, catalog, error::AppError, ffmpeg_probe};
+
+/// Emitted with the `BatchPlan` of scheduled rules that matched files, for
+/// the user to review before anything is converted.
+pub const SCHEDULED_PLAN_EVENT: &str = "catalog://scheduled-plan";
+/// How often scheduled rules are checked for being due.
+const SCHEDULE_POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+pub trait CatalogServiceApi: Send + Sync {
+    fn libraries(&self, app: &AppHandle) -> Result<Vec<String>, AppError>;
+    fn set_libraries(
+        &self,
+        app: &AppHandle,
+        libraries: Vec<String>,
+    ) -> Result<Vec<String>, AppError>;
+    fn scan(&self, app: &AppHandle) -> Result<catalog::ScanSummary, AppError>;
+    fn query(
+        &self,
+        app: &AppHandle,
+        query: &catalog::CatalogQuery,
+    ) -> Result<Vec<catalog::CatalogEntry>, AppError>;
+    fn rules(&self, app: &AppHandle) -> Result<Vec<catalog::ConversionRule>, AppError>;
+    fn save_rule(
+        &self,
+        app: &AppHandle,
+        rule: catalog::ConversionRule,
+    ) -> Result<Vec<catalog::ConversionRule>, AppError>;
+    fn remove_rule(
+        &self,
+        app: &AppHandle,
+        id: &str,
+    ) -> Result<Vec<catalog::ConversionRule>, AppError>;
+    fn plan_batch(
+        &self,
+        app: &AppHandle,
+        rule_ids: Option<Vec<String>>,
+    ) -> Result<catalog::BatchPlan, AppError>;
+    /// Evaluates scheduled rules now and every `SCHEDULE_POLL_INTERVAL`,
+    /// unless the app is read-only.
+    fn start_scheduler(&self, app: AppHandle, mode: AppMode);
+}
+
+/// Maintains the library catalog and serialises scans against updates.
+#[derive(Clone, Default)]
+pub struct CatalogService {
+    lock: Arc<Mutex<()>>,
+}
+
+impl CatalogService {
+    fn guard(&self) -> Result<std::sync::MutexGuard<'_, ()>, AppError> {
+        self.lock
+            .lock()
+            .map_err(|_| AppError::new("catalog_state", "Catalog state poisoned"))
+    }
+
+    /// Re-scans the libraries and plans the rules that are due, recording
+    /// that they ran. Returns `None` when no rule was due.
+    fn run_scheduled(
+        &self,
+        app: &AppHandle,
+        now_secs: u64,
+    ) -> Result<Option<catalog::BatchPlan>, AppError> {
+        let _guard = self.guard()?;
+        let path = catalog::store_path(app)?;
+        let mut stored = catalog::load(&path)?;
+        let due = catalog::due_rules(&stored, now_secs);
+        if due.is_empty() {
+            return Ok(None);
+        }
+
+        catalog::scan(&mut stored, |file| probe(app, file))?;
+        let plan = catalog::plan_batch(&stored, Some(&due));
+        for rule in stored
+            .rules
+            .iter_mut()
+            .filter(|rule| due.contains(&rule.id))
+        {
+            rule.last_scheduled_secs = Some(now_secs);
+        }
+        catalog::persist(&path, &stored)?;
+        Ok(Some(plan))
+    }
+}
+
+fn probe(app: &AppHandle, file: &str) -> Result<catalog::CatalogMedia, AppError> {
+    ffmpeg_probe::probe_media(app, file)
+        .map(|response| catalog::CatalogMedia::from(&response.summary))
+}
+
+impl CatalogServiceApi for CatalogService {
+    fn libraries(&self, app: &AppHandle) -> Result<Vec<String>, AppError> {
+        Ok(catalog::load(&catalog::store_path(app)?)?.libraries)
+    }
+
+    fn set_libraries(
+        &self,
+        app: &AppHandle,
+        libraries: Vec<String>,
+    ) -> Result<Vec<String>, AppError> {
+        if let Some(missing) = libraries.iter().find(|path| !Path::new(path).is_dir()) {
+            return Err(AppError::new(
+                "catalog_library_missing",
+                format!("Library folder {missing} is not a directory."),
+            ));
+        }
+
+        let _guard = self.guard()?;
+        let path = catalog::store_path(app)?;
+        let mut stored = catalog::load(&path)?;
+        stored.libraries = libraries;
+        catalog::persist(&path, &stored)?;
+        Ok(stored.libraries)
+    }
+
+    fn scan(&self, app: &AppHandle) -> Result<catalog::ScanSummary, AppError> {
+        let _guard = self.guard()?;
+        let path = catalog::store_path(app)?;
+        let mut stored = catalog::load(&path)?;
+
+        let summary = catalog::scan(&mut stored, |file| probe(app, file))?;
+
+        catalog::persist(&path, &stored)?;
+        Ok(summary)
+    }
+
+    fn query(
+        &self,
+        app: &AppHandle,
+        query: &catalog::CatalogQuery,
+    ) -> Result<Vec<catalog::CatalogEntry>, AppError> {
+        Ok(catalog::load(&catalog::store_path(app)?)?.query(query))
+    }
+
+    fn rules(&self, app: &AppHandle) -> Result<Vec<catalog::ConversionRule>, AppError> {
+        Ok(catalog::load(&catalog::store_path(app)?)?.rules)
+    }
+
+    fn save_rule(
+        &self,
+        app: &AppHandle,
+        rule: catalog::ConversionRule,
+    ) -> Result<Vec<catalog::ConversionRule>, AppError> {
+        rule.validate()?;
+
+        let _guard = self.guard()?;
+        let path = catalog::store_path(app)?;
+        let mut stored = catalog::load(&path)?;
+        match stored
+            .rules
+            .iter_mut()
+            .find(|existing| existing.id == rule.id)
+        {
+            Some(existing) => *existing = rule,
+            None => stored.rules.push(rule),
+        }
+        catalog::persist(&path, &stored)?;
+        Ok(stored.rules)
+    }
+
+    fn remove_rule(
+        &self,
+        app: &AppHandle,
+        id: &str,
+    ) -> Result<Vec<catalog::ConversionRule>, AppError> {
+        let _guard = self.guard()?;
+        let path = catalog::store_path(app)?;
+        let mut stored = catalog::load(&path)?;
+        stored.rules.retain(|rule| rule.id != id);
+        catalog::persist(&path, &stored)?;
+        Ok(stored.rules)
+    }
+
+    fn plan_batch(
+        &self,
+        app: &AppHandle,
+        rule_ids: Option<Vec<String>>,
+    ) -> Result<catalog::BatchPlan, AppError> {
+        let stored = catalog::load(&catalog::store_path(app)?)?;
+        Ok(catalog::plan_batch(&stored, rule_ids.as_deref()))
+    }
+
+    fn start_scheduler(&self, app: AppHandle, mode: AppMode) {
+        // Scanning rewrites the catalog.
+        if mode.read_only {
+            return;
+        }
+        let service = self.clone();
+        std::thread::spawn(move || loop {
+            let now_secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default();
+            match service.run_scheduled(&app, now_secs) {
+                Ok(Some(plan)) if !plan.items.is_empty() => {
+                    app.emit(SCHEDULED_PLAN_EVENT, &plan).ok();
+                },
+                Ok(_) => {},
+                Err(err) => eprintln!("[catalog] scheduled rules failed: {}", err.message),
+            }
+            std::thread::sleep(SCHEDULE_POLL_INTERVAL);
+        });
+    }
+}
@@ -33,7 +33,7 @@ impl MediaFilter {
         }
     }
 
-    fn extensions(&self) -> &'static [&'static str] {
+    pub(crate) fn extensions(&self) -> &'static [&'static str] {
         match self {
             MediaFilter::Video => VIDEO_EXTENSIONS,
             MediaFilter::Audio => AUDIO_EXTENSIONS,
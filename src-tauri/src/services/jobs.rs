@@ -1,12 +1,22 @@
 use crate::{
     error::AppError,
     runner::{
-        coordinator::JobCoordinator,
-        events::TauriEmitter,
+        battery::BatteryPolicy,
+        coordinator::{JobActionResult, JobCoordinator, JobOptions, RestoredJob},
+        events::{
+            EncodingSetting, EventEncoding, ProgressInterval, TauriEmitter, ThrottledEmitter,
+        },
         external::{DefaultSpawnController, SpawnController},
+        history::HistoryEntry,
+        hooks::PostJobHook,
+        job_log::LogSink,
+        retry::RetryPolicy,
     },
 };
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::AppHandle;
 
 pub trait JobServiceApi: Send + Sync {
@@ -14,25 +24,71 @@ pub trait JobServiceApi: Send + Sync {
         &self,
         app: AppHandle,
         job_id: String,
-        args: Vec<String>,
-        output_path: String,
-        exclusive: bool,
+        args: Vec<OsString>,
+        output_path: PathBuf,
+        options: JobOptions,
     ) -> Result<(), AppError>;
     fn cancel_job(&self, job_id: &str) -> Result<bool, AppError>;
+    fn cancel_all_jobs(&self) -> Vec<JobActionResult>;
+    fn pause_all_jobs(&self) -> Vec<JobActionResult>;
+    fn resume_all_jobs(&self) -> Vec<JobActionResult>;
     fn set_max_concurrency(&self, limit: usize);
+    fn set_retry_policy(&self, policy: RetryPolicy);
+    fn load_post_job_hooks(&self, app: AppHandle);
+    fn set_post_job_hooks(&self, hooks: Vec<PostJobHook>);
+    fn set_stall_timeout(&self, timeout: Option<Duration>);
+    fn set_progress_interval(&self, interval: Duration);
+    fn set_low_priority(&self, enabled: bool);
+    fn set_default_threads(&self, threads: Option<u32>);
+    fn set_prevent_sleep(&self, enabled: bool);
+    fn set_battery_policy(&self, app: AppHandle, policy: BatteryPolicy);
+    fn restore_jobs(&self, app: AppHandle, resume: bool) -> Vec<RestoredJob>;
+    fn history(&self, app: AppHandle) -> Vec<HistoryEntry>;
+    fn clear_history(&self, app: AppHandle);
+    fn job_log_path(&self, app: AppHandle, job_id: &str) -> Result<Option<String>, AppError>;
+    fn export_job_logs(
+        &self,
+        app: AppHandle,
+        job_id: Option<String>,
+        destination: String,
+    ) -> Result<Vec<String>, AppError>;
+    fn start_verification(
+        &self,
+        app: AppHandle,
+        job_id: String,
+        path: String,
+        checksum: bool,
+        limit_sec: Option<f64>,
+    ) -> Result<(), AppError>;
+    fn set_verification_concurrency(&self, limit: usize);
+    fn negotiate_event_encoding(&self, preferred: &[EventEncoding]) -> EventEncoding;
+    fn active_job_count(&self) -> usize;
+    /// Kills running jobs as the app exits, leaving them restorable.
+    fn shutdown(&self);
 }
 
 #[derive(Clone)]
 pub struct JobService {
     coordinator: Arc<JobCoordinator>,
+    encoding: EncodingSetting,
+    progress_interval: ProgressInterval,
 }
 
 impl JobService {
     pub fn new_with_spawner(spawner: Arc<dyn SpawnController>) -> Self {
         Self {
             coordinator: Arc::new(JobCoordinator::with_spawner(spawner)),
+            encoding: EncodingSetting::default(),
+            progress_interval: ProgressInterval::default(),
         }
     }
+
+    fn emitter(&self, app: &AppHandle) -> Arc<ThrottledEmitter> {
+        Arc::new(ThrottledEmitter::new(
+            Arc::new(TauriEmitter::new(app.clone(), self.encoding.get())),
+            self.progress_interval.clone(),
+        ))
+    }
 }
 
 impl Default for JobService {
@@ -46,20 +102,129 @@ impl JobServiceApi for JobService {
         &self,
         app: AppHandle,
         job_id: String,
-        args: Vec<String>,
-        output_path: String,
-        exclusive: bool,
+        args: Vec<OsString>,
+        output_path: PathBuf,
+        options: JobOptions,
     ) -> Result<(), AppError> {
-        let emitter = Arc::new(TauriEmitter::new(app.clone()));
+        let emitter = self.emitter(&app);
         self.coordinator
-            .start_job(app, emitter, job_id, args, output_path, exclusive)
+            .start_job(app, emitter, job_id, args, output_path, options)
     }
 
     fn cancel_job(&self, job_id: &str) -> Result<bool, AppError> {
         self.coordinator.cancel_job(job_id)
     }
 
+    fn cancel_all_jobs(&self) -> Vec<JobActionResult> {
+        self.coordinator.cancel_all_jobs()
+    }
+
+    fn active_job_count(&self) -> usize {
+        self.coordinator.active_job_count()
+    }
+
+    fn shutdown(&self) {
+        self.coordinator.shutdown();
+    }
+
+    fn pause_all_jobs(&self) -> Vec<JobActionResult> {
+        self.coordinator.pause_all_jobs()
+    }
+
+    fn resume_all_jobs(&self) -> Vec<JobActionResult> {
+        self.coordinator.resume_all_jobs()
+    }
+
     fn set_max_concurrency(&self, limit: usize) {
         self.coordinator.set_max_concurrency(limit);
     }
+
+    fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.coordinator.set_retry_policy(policy);
+    }
+
+    fn load_post_job_hooks(&self, app: AppHandle) {
+        self.coordinator.load_post_job_hooks(&app);
+    }
+
+    fn set_post_job_hooks(&self, hooks: Vec<PostJobHook>) {
+        self.coordinator.set_post_job_hooks(hooks);
+    }
+
+    fn set_stall_timeout(&self, timeout: Option<Duration>) {
+        self.coordinator.set_stall_timeout(timeout);
+    }
+
+    fn set_progress_interval(&self, interval: Duration) {
+        self.progress_interval.set(interval);
+    }
+
+    fn set_low_priority(&self, enabled: bool) {
+        self.coordinator.set_low_priority(enabled);
+    }
+
+    fn set_default_threads(&self, threads: Option<u32>) {
+        self.coordinator.set_default_threads(threads);
+    }
+
+    fn set_prevent_sleep(&self, enabled: bool) {
+        self.coordinator.set_prevent_sleep(enabled);
+    }
+
+    fn set_battery_policy(&self, app: AppHandle, policy: BatteryPolicy) {
+        let emitter = self.emitter(&app);
+        self.coordinator.set_battery_policy(policy, emitter);
+    }
+
+    fn restore_jobs(&self, app: AppHandle, resume: bool) -> Vec<RestoredJob> {
+        let emitter = self.emitter(&app);
+        self.coordinator.restore_jobs(app, emitter, resume)
+    }
+
+    fn history(&self, app: AppHandle) -> Vec<HistoryEntry> {
+        self.coordinator.history(&app)
+    }
+
+    fn clear_history(&self, app: AppHandle) {
+        self.coordinator.clear_history(&app);
+    }
+
+    fn job_log_path(&self, app: AppHandle, job_id: &str) -> Result<Option<String>, AppError> {
+        let path = LogSink::for_app(&app)?.path(job_id);
+        Ok(path.is_file().then(|| path.to_string_lossy().into_owned()))
+    }
+
+    fn export_job_logs(
+        &self,
+        app: AppHandle,
+        job_id: Option<String>,
+        destination: String,
+    ) -> Result<Vec<String>, AppError> {
+        let copies = LogSink::for_app(&app)?.export(job_id.as_deref(), Path::new(&destination))?;
+        Ok(copies
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    fn start_verification(
+        &self,
+        app: AppHandle,
+        job_id: String,
+        path: String,
+        checksum: bool,
+        limit_sec: Option<f64>,
+    ) -> Result<(), AppError> {
+        let emitter = self.emitter(&app);
+        self.coordinator
+            .start_verification(app, emitter, job_id, path, checksum, limit_sec)
+    }
+
+    fn set_verification_concurrency(&self, limit: usize) {
+        self.coordinator.set_verification_concurrency(limit);
+    }
+
+    fn negotiate_event_encoding(&self, preferred: &[EventEncoding]) -> EventEncoding {
+        self.encoding.negotiate(preferred)
+    }
 }
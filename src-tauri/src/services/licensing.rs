@@ -1,17 +1,33 @@
+use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 
-use crate::{error::AppError, license};
+use crate::{app_mode::AppMode, error::AppError, license};
 
 pub trait LicenseServiceApi: Send + Sync {
     fn verify(&self, key: &str) -> Result<license::LicenseInfo, AppError>;
     fn activate(&self, app: &AppHandle, key: &str) -> Result<license::LicenseInfo, AppError>;
     fn current(&self, app: &AppHandle) -> Result<Option<license::LicenseInfo>, AppError>;
     fn remove(&self, app: &AppHandle) -> Result<(), AppError>;
+    fn deactivate(
+        &self,
+        app: &AppHandle,
+        notify_server: bool,
+    ) -> Result<license::Deactivation, AppError>;
+    fn trial_status(&self, app: &AppHandle) -> Result<license::TrialStatus, AppError>;
+    fn has_entitlement(&self, app: &AppHandle, feature: &str) -> Result<bool, AppError>;
+    fn start_revalidation(&self, app: AppHandle);
+    fn migrate_storage(&self, app: &AppHandle, mode: AppMode);
 }
 
+/// How often the stored license is confirmed with the license server.
+const REVALIDATION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
 /// Handles license verification, activation, and persistence flows.
 #[derive(Clone, Default)]
-pub struct LicenseService;
+pub struct LicenseService {
+    cache: Arc<license::LicenseCache>,
+}
 
 impl LicenseServiceApi for LicenseService {
     fn verify(&self, key: &str) -> Result<license::LicenseInfo, AppError> {
@@ -20,19 +36,93 @@ impl LicenseServiceApi for LicenseService {
 
     fn activate(&self, app: &AppHandle, key: &str) -> Result<license::LicenseInfo, AppError> {
         let mut info = license::verify(key)?;
-        info.activated_at = Some(license::activate_timestamp());
+        let now = license::activate_timestamp();
+        info.activated_at = Some(now);
+        info.machine_fingerprint = license::machine_fingerprint();
+        license::activate_online(&mut info, now)?;
         license::persist(app, &info)?;
+        self.cache.forget();
         app.emit("license://activated", &info).ok();
         Ok(info)
     }
 
     fn current(&self, app: &AppHandle) -> Result<Option<license::LicenseInfo>, AppError> {
-        license::load(app).map_err(Into::into)
+        self.cache.load(app).map_err(Into::into)
     }
 
     fn remove(&self, app: &AppHandle) -> Result<(), AppError> {
-        license::remove(app)?;
+        let removed = license::remove(app);
+        self.cache.forget();
+        removed?;
         app.emit("license://removed", &()).ok();
         Ok(())
     }
+
+    fn deactivate(
+        &self,
+        app: &AppHandle,
+        notify_server: bool,
+    ) -> Result<license::Deactivation, AppError> {
+        let deactivation = license::deactivate(app, notify_server, license::activate_timestamp());
+        self.cache.forget();
+        let deactivation = deactivation?;
+        app.emit("license://removed", &()).ok();
+        Ok(deactivation)
+    }
+
+    fn trial_status(&self, app: &AppHandle) -> Result<license::TrialStatus, AppError> {
+        license::trial_status(app, license::activate_timestamp()).map_err(Into::into)
+    }
+
+    /// Whether the stored license grants `feature`; `false` without one.
+    fn has_entitlement(&self, app: &AppHandle, feature: &str) -> Result<bool, AppError> {
+        Ok(self
+            .cache
+            .load(app)?
+            .is_some_and(|info| info.has_entitlement(feature)))
+    }
+
+    /// Confirms the stored license with the license server now and every
+    /// `REVALIDATION_INTERVAL`, when a server is configured.
+    fn start_revalidation(&self, app: AppHandle) {
+        if !license::server_configured() {
+            return;
+        }
+        let cache = self.cache.clone();
+        std::thread::spawn(move || loop {
+            revalidate(&app, &cache);
+            std::thread::sleep(REVALIDATION_INTERVAL);
+        });
+    }
+
+    /// Moves a license left in the license file into the credential store;
+    /// skipped in read-only mode, where the file keeps serving as fallback.
+    fn migrate_storage(&self, app: &AppHandle, mode: AppMode) {
+        if mode.read_only {
+            return;
+        }
+        if let Err(err) = license::migrate(app) {
+            eprintln!("[license] moving the license file failed: {err}");
+        }
+        self.cache.forget();
+    }
+}
+
+fn revalidate(app: &AppHandle, cache: &license::LicenseCache) {
+    let revalidation = license::revalidate_stored(app, license::activate_timestamp());
+    cache.forget();
+    match revalidation {
+        Ok(license::Revalidation::Confirmed(info)) => {
+            app.emit("license://revalidated", &info).ok();
+        },
+        Ok(license::Revalidation::Revoked(reason)) => {
+            eprintln!("[license] the license server revoked the license: {reason}");
+            app.emit("license://removed", &()).ok();
+        },
+        Ok(license::Revalidation::Offline { degraded: true }) => {
+            app.emit("license://degraded", &()).ok();
+        },
+        Ok(license::Revalidation::NoLicense | license::Revalidation::Offline { .. }) => {},
+        Err(err) => eprintln!("[license] revalidation failed: {err}"),
+    }
 }
@@ -0,0 +1,64 @@
+use tauri::AppHandle;
+
+use crate::{
+    error::AppError,
+    ffmpeg_probe,
+    loudness::{
+        self,
+        replaygain::{self, GainTagRequest, GainTagResult},
+        LoudnessMeasurement,
+    },
+};
+
+pub trait LoudnessServiceApi: Send + Sync {
+    fn analyze(&self, app: &AppHandle, path: &str) -> Result<LoudnessMeasurement, AppError>;
+    fn write_gain_tags(
+        &self,
+        app: &AppHandle,
+        request: GainTagRequest,
+    ) -> Result<Vec<GainTagResult>, AppError>;
+}
+
+/// Service running loudness analysis and the ReplayGain/R128 post-step.
+#[derive(Clone, Default)]
+pub struct LoudnessService;
+
+impl LoudnessServiceApi for LoudnessService {
+    fn analyze(&self, app: &AppHandle, path: &str) -> Result<LoudnessMeasurement, AppError> {
+        let mut measurement = loudness::analyze(app, path)?;
+        measurement.duration_sec = ffmpeg_probe::probe_media(app, path)
+            .ok()
+            .map(|response| response.summary.duration_sec)
+            .filter(|duration| *duration > 0.0);
+        Ok(measurement)
+    }
+
+    fn write_gain_tags(
+        &self,
+        app: &AppHandle,
+        request: GainTagRequest,
+    ) -> Result<Vec<GainTagResult>, AppError> {
+        if request.targets.is_empty() {
+            return Err(AppError::new(
+                "loudness_no_targets",
+                "Select at least one output to tag.",
+            ));
+        }
+
+        // Album gain depends on every track, so measure all before writing.
+        let measured = request
+            .targets
+            .into_iter()
+            .map(|target| {
+                let measurement = self.analyze(app, &target.path)?;
+                Ok((target, measurement))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let results = replaygain::plan_gain_tags(&measured, request.album_mode);
+        for result in &results {
+            replaygain::write_tags(app, &result.path, &result.tags)?;
+        }
+        Ok(results)
+    }
+}
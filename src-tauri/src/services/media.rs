@@ -1,17 +1,150 @@
-use tauri::AppHandle;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
 
-use crate::{error::AppError, ffmpeg_probe};
+use crate::{contact_sheet, error::AppError, ffmpeg_probe, probe_batch, probe_report, waveform};
+
+/// Event carrying each batch probe result, in input order.
+pub const PROBE_RESULT_EVENT: &str = "probe://result";
 
 pub trait MediaProbeServiceApi: Send + Sync {
     fn probe(&self, app: &AppHandle, path: &str) -> Result<ffmpeg_probe::ProbeResponse, AppError>;
+    /// Probes and also analyzes interlacing, which decodes part of the video.
+    fn deep_probe(
+        &self,
+        app: &AppHandle,
+        path: &str,
+    ) -> Result<ffmpeg_probe::ProbeResponse, AppError>;
+    /// Keyframe timestamps of the first video stream, for aligned cuts.
+    fn keyframes(&self, app: &AppHandle, path: &str) -> Result<Vec<f64>, AppError>;
+    fn export_report(
+        &self,
+        app: &AppHandle,
+        paths: &[String],
+        format: probe_report::ReportFormat,
+    ) -> Result<probe_report::ProbeReport, AppError>;
+    fn probe_batch(
+        &self,
+        app: &AppHandle,
+        batch_id: &str,
+        paths: &[String],
+        concurrency: Option<usize>,
+    ) -> probe_batch::BatchProbeSummary;
+    fn cancel_probe_batch(&self, batch_id: &str) -> bool;
+    fn contact_sheet(
+        &self,
+        app: &AppHandle,
+        request: &contact_sheet::ContactSheetRequest,
+    ) -> Result<contact_sheet::ContactSheet, AppError>;
+    fn waveform(
+        &self,
+        app: &AppHandle,
+        request: &waveform::WaveformRequest,
+    ) -> Result<waveform::Waveform, AppError>;
 }
 
 /// Service responsible for media probing/introspection.
 #[derive(Clone, Default)]
-pub struct MediaProbeService;
+pub struct MediaProbeService {
+    batches: Arc<probe_batch::BatchRegistry>,
+}
 
 impl MediaProbeServiceApi for MediaProbeService {
     fn probe(&self, app: &AppHandle, path: &str) -> Result<ffmpeg_probe::ProbeResponse, AppError> {
         ffmpeg_probe::probe_media(app, path)
     }
+
+    fn deep_probe(
+        &self,
+        app: &AppHandle,
+        path: &str,
+    ) -> Result<ffmpeg_probe::ProbeResponse, AppError> {
+        let mut response = self.probe(app, path)?;
+        if response.summary.vcodec.is_some() {
+            match ffmpeg_probe::detect_interlacing(app, path, response.summary.duration_sec) {
+                Ok(interlacing) => response.summary.interlacing = Some(interlacing),
+                Err(err) => eprintln!(
+                    "[probe] interlacing analysis of {path} failed: {}",
+                    err.message
+                ),
+            }
+        }
+        Ok(response)
+    }
+
+    fn keyframes(&self, app: &AppHandle, path: &str) -> Result<Vec<f64>, AppError> {
+        ffmpeg_probe::list_keyframes(app, path)
+    }
+
+    fn export_report(
+        &self,
+        app: &AppHandle,
+        paths: &[String],
+        format: probe_report::ReportFormat,
+    ) -> Result<probe_report::ProbeReport, AppError> {
+        if paths.is_empty() {
+            return Err(AppError::new(
+                "report_no_paths",
+                "Select at least one file to inspect.",
+            ));
+        }
+
+        let entries: Vec<probe_report::ReportEntry> = paths
+            .iter()
+            .map(|path| probe_report::ReportEntry {
+                path: path.clone(),
+                result: self.probe(app, path),
+            })
+            .collect();
+
+        probe_report::render_report(&entries, format)
+    }
+
+    fn probe_batch(
+        &self,
+        app: &AppHandle,
+        batch_id: &str,
+        paths: &[String],
+        concurrency: Option<usize>,
+    ) -> probe_batch::BatchProbeSummary {
+        let cancel = self.batches.begin(batch_id);
+        let summary = probe_batch::run_batch(
+            batch_id,
+            paths,
+            probe_batch::resolve_concurrency(concurrency),
+            &cancel,
+            |path| self.probe(app, path).map(|response| response.summary),
+            |item| {
+                let _ = app.emit(PROBE_RESULT_EVENT, &item);
+            },
+        );
+        self.batches.finish(batch_id, &cancel);
+        summary
+    }
+
+    fn cancel_probe_batch(&self, batch_id: &str) -> bool {
+        self.batches.cancel(batch_id)
+    }
+
+    fn contact_sheet(
+        &self,
+        app: &AppHandle,
+        request: &contact_sheet::ContactSheetRequest,
+    ) -> Result<contact_sheet::ContactSheet, AppError> {
+        let summary = self.probe(app, &request.path)?.summary;
+        if summary.vcodec.is_none() {
+            return Err(AppError::new(
+                "contact_sheet_no_video",
+                format!("{} has no video stream", request.path),
+            ));
+        }
+        contact_sheet::generate(app, request, summary.duration_sec)
+    }
+
+    fn waveform(
+        &self,
+        app: &AppHandle,
+        request: &waveform::WaveformRequest,
+    ) -> Result<waveform::Waveform, AppError> {
+        waveform::generate(app, request)
+    }
 }
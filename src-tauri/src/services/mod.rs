@@ -4,43 +4,75 @@
 //! validate input and delegate work. Trait-based indirection keeps
 //! responsibilities isolated and dramatically improves testability.
 
+mod binaries;
 mod capabilities;
+mod catalog;
 mod dialogs;
 mod jobs;
 mod licensing;
+mod loudness;
 mod media;
 mod paths;
+mod planning;
+mod watch_folders;
 
+pub use binaries::{BinaryService, BinaryServiceApi};
 pub use capabilities::{CapabilityService, CapabilityServiceApi};
+pub use catalog::{CatalogService, CatalogServiceApi};
 pub use dialogs::{DialogService, DialogServiceApi, MediaFilter};
 pub use jobs::{JobService, JobServiceApi};
 pub use licensing::{LicenseService, LicenseServiceApi};
+pub use loudness::{LoudnessService, LoudnessServiceApi};
 pub use media::{MediaProbeService, MediaProbeServiceApi};
 pub use paths::{PathService, PathServiceApi};
+pub use planning::{PlanningService, PlanningServiceApi};
+pub use watch_folders::{WatchFolderService, WatchFolderServiceApi};
 
 use std::sync::Arc;
 
+use crate::{
+    app_mode::AppMode,
+    metrics::{CommandMetrics, CommandTimeouts},
+};
+
 /// Shared registry that bundles the available services for dependency
 /// injection via `tauri::State`.
 #[derive(Clone)]
 pub struct ServiceRegistry {
+    pub binaries: Arc<dyn BinaryServiceApi>,
     pub capabilities: Arc<dyn CapabilityServiceApi>,
     pub media_probe: Arc<dyn MediaProbeServiceApi>,
     pub jobs: Arc<dyn JobServiceApi>,
     pub paths: Arc<dyn PathServiceApi>,
     pub dialogs: Arc<dyn DialogServiceApi>,
     pub licensing: Arc<dyn LicenseServiceApi>,
+    pub planning: Arc<dyn PlanningServiceApi>,
+    pub watch_folders: Arc<dyn WatchFolderServiceApi>,
+    pub catalog: Arc<dyn CatalogServiceApi>,
+    pub loudness: Arc<dyn LoudnessServiceApi>,
+    /// Launch mode gating mutating commands.
+    pub mode: AppMode,
+    pub metrics: Arc<CommandMetrics>,
+    pub timeouts: Arc<CommandTimeouts>,
 }
 
 impl Default for ServiceRegistry {
     fn default() -> Self {
         Self {
+            binaries: Arc::new(BinaryService),
             capabilities: Arc::new(CapabilityService),
-            media_probe: Arc::new(MediaProbeService),
+            media_probe: Arc::new(MediaProbeService::default()),
             jobs: Arc::new(JobService::default()),
             paths: Arc::new(PathService),
             dialogs: Arc::new(DialogService),
-            licensing: Arc::new(LicenseService),
+            licensing: Arc::new(LicenseService::default()),
+            planning: Arc::new(PlanningService),
+            watch_folders: Arc::new(WatchFolderService::default()),
+            catalog: Arc::new(CatalogService::default()),
+            loudness: Arc::new(LoudnessService),
+            mode: AppMode::default(),
+            metrics: Arc::new(CommandMetrics::default()),
+            timeouts: Arc::new(CommandTimeouts::default()),
         }
     }
 }
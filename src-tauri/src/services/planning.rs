@@ -0,0 +1,214 @@
+use tauri::AppHandle;
+
+use crate::{
+    error::AppError,
+    ffmpeg_capabilities, ffmpeg_probe, fonts, planning,
+    runner::{process_spawner::ProcessSpawner, triage, validator::JobValidator},
+};
+
+pub trait PlanningServiceApi: Send + Sync {
+    fn build_filtergraph(
+        &self,
+        app: &AppHandle,
+        request: planning::FilterGraphRequest,
+    ) -> Result<planning::FilterGraphPlan, AppError>;
+    fn diff_plans(&self, before: &[String], after: &[String]) -> planning::PlanDiff;
+    fn resolve_container(
+        &self,
+        app: &AppHandle,
+        request: planning::ContainerRequest,
+    ) -> Result<planning::ContainerPlan, AppError>;
+    fn plan_trim(&self, request: &planning::TrimRequest) -> Result<planning::TrimPlan, AppError>;
+    fn apply_remediation(&self, args: &[String], remediation: &triage::Remediation) -> Vec<String>;
+    fn plan_album(
+        &self,
+        app: &AppHandle,
+        request: planning::AlbumRequest,
+    ) -> Result<planning::AlbumPlan, AppError>;
+    fn finish_album(
+        &self,
+        app: &AppHandle,
+        request: planning::AlbumRequest,
+    ) -> Result<Vec<String>, AppError>;
+    fn preview_stream_selection(
+        &self,
+        app: &AppHandle,
+        path: &str,
+        rules: &planning::StreamSelectionRules,
+    ) -> Result<planning::StreamSelectionPreview, AppError>;
+    fn estimate_output_size(
+        &self,
+        app: &AppHandle,
+        request: planning::SizeEstimateRequest,
+    ) -> Result<planning::SizeEstimate, AppError>;
+}
+
+/// Service exposing backend planning helpers to the frontend.
+#[derive(Clone, Default)]
+pub struct PlanningService;
+
+impl PlanningServiceApi for PlanningService {
+    fn build_filtergraph(
+        &self,
+        app: &AppHandle,
+        mut request: planning::FilterGraphRequest,
+    ) -> Result<planning::FilterGraphPlan, AppError> {
+        let Some(subtitles) = request.subtitles.as_mut() else {
+            return Ok(planning::build_filtergraph(&request));
+        };
+
+        if subtitles.fonts_dir.is_none() {
+            subtitles.fonts_dir = fonts::preferred_fonts_dir(app)
+                .and_then(|dir| dir.to_str().map(|value| value.to_string()));
+        }
+
+        let mut plan = planning::build_filtergraph(&request);
+        if let Ok(snapshot) = ffmpeg_capabilities::load_capabilities(app) {
+            if !snapshot.filters.iter().any(|filter| filter == "subtitles") {
+                plan.warnings.push(
+                    "This FFmpeg build lacks the libass subtitles filter; burn-in will fail."
+                        .into(),
+                );
+            }
+        }
+        Ok(plan)
+    }
+
+    fn diff_plans(&self, before: &[String], after: &[String]) -> planning::PlanDiff {
+        planning::diff_plans(before, after)
+    }
+
+    fn resolve_container(
+        &self,
+        app: &AppHandle,
+        request: planning::ContainerRequest,
+    ) -> Result<planning::ContainerPlan, AppError> {
+        let formats = ffmpeg_capabilities::load_capabilities(app)
+            .map(|snapshot| snapshot.muxers)
+            .unwrap_or_default();
+        planning::resolve_container(&request, &formats)
+    }
+
+    fn plan_trim(&self, request: &planning::TrimRequest) -> Result<planning::TrimPlan, AppError> {
+        planning::plan_trim(request)
+    }
+
+    fn apply_remediation(&self, args: &[String], remediation: &triage::Remediation) -> Vec<String> {
+        triage::apply_remediation(args, remediation)
+    }
+
+    fn plan_album(
+        &self,
+        app: &AppHandle,
+        request: planning::AlbumRequest,
+    ) -> Result<planning::AlbumPlan, AppError> {
+        let mut probe_warnings = Vec::new();
+        let tracks = request
+            .paths
+            .iter()
+            .map(|path| match ffmpeg_probe::probe_media(app, path) {
+                Ok(response) => planning::AlbumTrack::from_probe(path, &response.raw),
+                Err(err) => {
+                    probe_warnings.push(format!("{path}: {}", err.message));
+                    planning::AlbumTrack {
+                        path: path.clone(),
+                        ..planning::AlbumTrack::default()
+                    }
+                },
+            })
+            .collect();
+
+        let mut plan = planning::plan_album(&request, tracks)?;
+        plan.warnings.extend(probe_warnings);
+        Ok(plan)
+    }
+
+    /// Re-plans from the request so the playlist and gapless values come
+    /// from the backend, not from the caller.
+    fn finish_album(
+        &self,
+        app: &AppHandle,
+        request: planning::AlbumRequest,
+    ) -> Result<Vec<String>, AppError> {
+        let plan = self.plan_album(app, request.clone())?;
+        planning::finish_album(&request, &plan)
+    }
+
+    fn preview_stream_selection(
+        &self,
+        app: &AppHandle,
+        path: &str,
+        rules: &planning::StreamSelectionRules,
+    ) -> Result<planning::StreamSelectionPreview, AppError> {
+        let response = ffmpeg_probe::probe_media(app, path)?;
+        Ok(planning::preview_stream_selection(&response.raw, rules))
+    }
+
+    fn estimate_output_size(
+        &self,
+        app: &AppHandle,
+        request: planning::SizeEstimateRequest,
+    ) -> Result<planning::SizeEstimate, AppError> {
+        let response = ffmpeg_probe::probe_media(app, &request.path)?;
+        let source = planning::SourceMedia::from_probe(&response.raw);
+        if !request.sample || !planning::benefits_from_sample(&request.plan, &source) {
+            return planning::estimate_size(&request.plan, &source);
+        }
+        let sample_bytes = encode_sample(app, &request, &source)?;
+        Ok(planning::from_sample(sample_bytes, &source))
+    }
+}
+
+/// Encodes a sample of the source with the job's arguments into a temporary
+/// file and returns its size.
+fn encode_sample(
+    app: &AppHandle,
+    request: &planning::SizeEstimateRequest,
+    source: &planning::SourceMedia,
+) -> Result<u64, AppError> {
+    JobValidator::new().validate_args(&request.args)?;
+    let preset = &request.plan.preset;
+    let extension = preset
+        .output_extension
+        .as_deref()
+        .unwrap_or(&preset.container);
+    let output = std::env::temp_dir().join(format!(
+        "honeymelon-estimate-{}.{extension}",
+        uuid::Uuid::new_v4()
+    ));
+    let output_path = output.to_string_lossy().into_owned();
+
+    let ffmpeg = ProcessSpawner::resolve_ffmpeg(app)?;
+    let args = planning::sample_args(&request.args, source);
+    let result = ProcessSpawner::spawn_decode(ffmpeg, &args, &output_path)
+        .and_then(|child| {
+            child.wait_with_output().map_err(|err| {
+                AppError::new(
+                    "estimate_sample_failed",
+                    format!("Sample encode failed: {err}"),
+                )
+            })
+        })
+        .and_then(|finished| {
+            if !finished.status.success() {
+                return Err(AppError::new(
+                    "estimate_sample_failed",
+                    format!(
+                        "Sample encode exited with {}: {}",
+                        finished.status,
+                        String::from_utf8_lossy(&finished.stderr).trim()
+                    ),
+                ));
+            }
+            std::fs::metadata(&output)
+                .map(|meta| meta.len())
+                .map_err(|err| {
+                    AppError::new(
+                        "estimate_sample_failed",
+                        format!("Sample output missing: {err}"),
+                    )
+                })
+        });
+    let _ = std::fs::remove_file(&output);
+    result
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use crate::{error::AppError, watch_folders};
+
+pub trait WatchFolderServiceApi: Send + Sync {
+    fn list(&self, app: &AppHandle) -> Result<Vec<watch_folders::WatchFolder>, AppError>;
+    fn save(
+        &self,
+        app: &AppHandle,
+        folder: watch_folders::WatchFolder,
+    ) -> Result<Vec<watch_folders::WatchFolder>, AppError>;
+    fn remove(
+        &self,
+        app: &AppHandle,
+        id: &str,
+    ) -> Result<Vec<watch_folders::WatchFolder>, AppError>;
+    fn poll(
+        &self,
+        app: &AppHandle,
+        id: &str,
+    ) -> Result<Vec<watch_folders::WatchCandidate>, AppError>;
+    fn resolve_output(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        output_path: &str,
+    ) -> Result<Option<String>, AppError>;
+}
+
+/// Persists watch-folder configuration and tracks file stability between
+/// polls.
+#[derive(Clone, Default)]
+pub struct WatchFolderService {
+    trackers: Arc<Mutex<HashMap<String, watch_folders::StabilityTracker>>>,
+}
+
+impl WatchFolderService {
+    fn find(&self, app: &AppHandle, id: &str) -> Result<watch_folders::WatchFolder, AppError> {
+        self.list(app)?
+            .into_iter()
+            .find(|folder| folder.id == id)
+            .ok_or_else(|| {
+                AppError::new(
+                    "watch_folder_not_found",
+                    format!("Unknown watch folder {id}"),
+                )
+            })
+    }
+}
+
+impl WatchFolderServiceApi for WatchFolderService {
+    fn list(&self, app: &AppHandle) -> Result<Vec<watch_folders::WatchFolder>, AppError> {
+        watch_folders::load(&watch_folders::store_path(app)?)
+    }
+
+    fn save(
+        &self,
+        app: &AppHandle,
+        folder: watch_folders::WatchFolder,
+    ) -> Result<Vec<watch_folders::WatchFolder>, AppError> {
+        folder.validate()?;
+
+        let path = watch_folders::store_path(app)?;
+        let mut folders = watch_folders::load(&path)?;
+        match folders.iter_mut().find(|existing| existing.id == folder.id) {
+            Some(existing) => *existing = folder,
+            None => folders.push(folder),
+        }
+
+        watch_folders::persist(&path, &folders)?;
+        Ok(folders)
+    }
+
+    fn remove(
+        &self,
+        app: &AppHandle,
+        id: &str,
+    ) -> Result<Vec<watch_folders::WatchFolder>, AppError> {
+        let path = watch_folders::store_path(app)?;
+        let mut folders = watch_folders::load(&path)?;
+        folders.retain(|folder| folder.id != id);
+        watch_folders::persist(&path, &folders)?;
+
+        if let Ok(mut trackers) = self.trackers.lock() {
+            trackers.remove(id);
+        }
+        Ok(folders)
+    }
+
+    fn poll(
+        &self,
+        app: &AppHandle,
+        id: &str,
+    ) -> Result<Vec<watch_folders::WatchCandidate>, AppError> {
+        let folder = self.find(app, id)?;
+        if !folder.enabled {
+            return Ok(Vec::new());
+        }
+
+        let mut trackers = self
+            .trackers
+            .lock()
+            .map_err(|_| AppError::new("watch_folder_state", "Watch folder state poisoned"))?;
+        let tracker = trackers.entry(folder.id.clone()).or_default();
+        watch_folders::scan(&folder, tracker, SystemTime::now())
+    }
+
+    fn resolve_output(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        output_path: &str,
+    ) -> Result<Option<String>, AppError> {
+        let folder = self.find(app, id)?;
+        Ok(folder
+            .policy
+            .resolve_output(Path::new(output_path))
+            .map(|path| path.to_string_lossy().into_owned()))
+    }
+}
@@ -0,0 +1,76 @@
+//! Lock helpers that survive panics.
+//!
+//! A thread that panics while holding a std lock poisons it, and every
+//! later `lock()` fails. For job state that turns one crashed monitor
+//! thread into a registry nobody can touch. The state behind these locks
+//! is only ever updated in single steps, so the guard of a poisoned lock
+//! is still consistent and is taken over instead.
+
+use std::sync::{
+    Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError,
+};
+
+pub trait MutexExt<T> {
+    /// Locks the mutex, recovering it if a panicking thread poisoned it.
+    fn lock_or_recover(&self) -> MutexGuard<'_, T>;
+
+    /// Locks the mutex if it's free, recovering it if it was poisoned.
+    fn try_lock_or_recover(&self) -> Option<MutexGuard<'_, T>>;
+}
+
+impl<T> MutexExt<T> for Mutex<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn try_lock_or_recover(&self) -> Option<MutexGuard<'_, T>> {
+        match self.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+pub trait RwLockExt<T> {
+    /// Read-locks the lock, recovering it if a panicking thread poisoned it.
+    fn read_or_recover(&self) -> RwLockReadGuard<'_, T>;
+
+    /// Write-locks the lock, recovering it if a panicking thread poisoned it.
+    fn write_or_recover(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_or_recover(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_or_recover(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn poisoned_locks_are_recovered() {
+        let mutex = Arc::new(Mutex::new(1));
+        let rwlock = Arc::new(RwLock::new(1));
+        let (m, r) = (Arc::clone(&mutex), Arc::clone(&rwlock));
+        let _ = std::thread::spawn(move || {
+            let _m = m.lock().unwrap();
+            let _r = r.write().unwrap();
+            panic!("poison both");
+        })
+        .join();
+        assert!(mutex.is_poisoned() && rwlock.is_poisoned());
+
+        *mutex.lock_or_recover() += 1;
+        *rwlock.write_or_recover() += 1;
+        assert_eq!(*mutex.try_lock_or_recover().unwrap(), 2);
+        assert_eq!(*rwlock.read_or_recover(), 2);
+    }
+}
@@ -0,0 +1,155 @@
+//! Watch-folder configuration and pickup policies.
+//!
+//! Camera offloads and network copies land in watch folders while they are
+//! still being written, so each folder carries a policy deciding when a file
+//! is old and stable enough to pick up, which names to ignore, and what to do
+//! when the output already exists.
+
+mod policy;
+mod storage;
+
+pub use policy::{PickupState, StabilityTracker, WatchFolderPolicy};
+pub use storage::{load, persist, store_path};
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::error::AppError;
+
+/// A folder monitored for new media along with its pickup policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolder {
+    pub id: String,
+    pub path: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub policy: WatchFolderPolicy,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WatchFolder {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.id.trim().is_empty() {
+            return Err(AppError::new(
+                "watch_folder_invalid",
+                "Watch folder id must not be empty.",
+            ));
+        }
+
+        if !Path::new(&self.path).is_dir() {
+            return Err(AppError::new(
+                "watch_folder_missing",
+                format!("Watch folder {} is not a directory.", self.path),
+            ));
+        }
+
+        self.policy.validate()
+    }
+}
+
+/// A file found in a watch folder and its pickup state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchCandidate {
+    pub path: String,
+    pub size: u64,
+    pub state: PickupState,
+}
+
+/// Lists the files directly inside `folder` and evaluates each against the
+/// folder policy, updating `tracker` with the observed sizes.
+pub fn scan(
+    folder: &WatchFolder,
+    tracker: &mut StabilityTracker,
+    now: SystemTime,
+) -> Result<Vec<WatchCandidate>, AppError> {
+    let entries = fs::read_dir(&folder.path)
+        .map_err(|err| AppError::new("watch_folder_read", err.to_string()))?;
+
+    let mut present: Vec<PathBuf> = Vec::new();
+    let mut candidates = Vec::new();
+
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let modified = metadata.modified().unwrap_or(now);
+        let stable_for = tracker.observe(&path, metadata.len(), now);
+
+        candidates.push(WatchCandidate {
+            path: path.to_string_lossy().into_owned(),
+            size: metadata.len(),
+            state: folder
+                .policy
+                .evaluate(&file_name, modified, stable_for, now),
+        });
+        present.push(path);
+    }
+
+    tracker.retain(&present);
+    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn folder(path: &Path) -> WatchFolder {
+        WatchFolder {
+            id: "offload".into(),
+            path: path.to_string_lossy().into_owned(),
+            enabled: true,
+            policy: WatchFolderPolicy {
+                min_file_age_secs: 0,
+                stability_window_secs: 5,
+                ..WatchFolderPolicy::default()
+            },
+        }
+    }
+
+    #[test]
+    fn validate_requires_existing_directory() {
+        let temp = TempDir::new().unwrap();
+        let mut watch = folder(temp.path());
+        assert!(watch.validate().is_ok());
+
+        watch.path = temp.path().join("missing").to_string_lossy().into_owned();
+        assert_eq!(watch.validate().unwrap_err().code, "watch_folder_missing");
+    }
+
+    #[test]
+    fn scan_reports_files_ready_after_stability_window() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("clip.mov"), b"data").unwrap();
+        fs::write(temp.path().join("clip.mov.part"), b"da").unwrap();
+        fs::create_dir(temp.path().join("nested")).unwrap();
+
+        let watch = folder(temp.path());
+        let mut tracker = StabilityTracker::default();
+        let now = SystemTime::now() + Duration::from_secs(1);
+
+        let first = scan(&watch, &mut tracker, now).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].state, PickupState::Settling);
+        assert_eq!(first[1].state, PickupState::Excluded);
+
+        let second = scan(&watch, &mut tracker, now + Duration::from_secs(5)).unwrap();
+        assert_eq!(second[0].state, PickupState::Ready);
+    }
+}
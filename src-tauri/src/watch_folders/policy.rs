@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::error::AppError;
+
+/// Upper bound for age and stability windows; anything longer is a typo.
+const MAX_WINDOW_SECS: u64 = 24 * 60 * 60;
+const MAX_PATTERN_LENGTH: usize = 256;
+
+/// What to do when the output for a picked-up file already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExistingOutputAction {
+    #[default]
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+/// Per-folder rules deciding when a file is safe to pick up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchFolderPolicy {
+    /// Minimum time since the last modification before pickup.
+    pub min_file_age_secs: u64,
+    /// How long the file size must stay unchanged before pickup.
+    pub stability_window_secs: u64,
+    /// Case-insensitive file name patterns (`*` and `?` wildcards) to ignore.
+    pub exclude_patterns: Vec<String>,
+    pub on_existing_output: ExistingOutputAction,
+}
+
+impl Default for WatchFolderPolicy {
+    fn default() -> Self {
+        Self {
+            min_file_age_secs: 5,
+            stability_window_secs: 10,
+            exclude_patterns: vec![
+                ".*".into(),
+                "*.part".into(),
+                "*.tmp".into(),
+                "*.crdownload".into(),
+            ],
+            on_existing_output: ExistingOutputAction::Skip,
+        }
+    }
+}
+
+impl WatchFolderPolicy {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.min_file_age_secs > MAX_WINDOW_SECS {
+            return Err(invalid_policy(format!(
+                "Minimum file age must not exceed {MAX_WINDOW_SECS} seconds."
+            )));
+        }
+
+        if self.stability_window_secs > MAX_WINDOW_SECS {
+            return Err(invalid_policy(format!(
+                "Stability window must not exceed {MAX_WINDOW_SECS} seconds."
+            )));
+        }
+
+        for pattern in &self.exclude_patterns {
+            if pattern.trim().is_empty() {
+                return Err(invalid_policy("Exclude patterns must not be empty."));
+            }
+            if pattern.len() > MAX_PATTERN_LENGTH {
+                return Err(invalid_policy(format!(
+                    "Exclude pattern exceeds {MAX_PATTERN_LENGTH} characters."
+                )));
+            }
+            if pattern.contains('/') || pattern.contains('\\') {
+                return Err(invalid_policy(format!(
+                    "Exclude pattern {pattern} must match file names, not paths."
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_excluded(&self, file_name: &str) -> bool {
+        let name = file_name.to_lowercase();
+        self.exclude_patterns
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_lowercase(), &name))
+    }
+
+    /// Evaluates a file observed in the folder against this policy.
+    pub fn evaluate(
+        &self,
+        file_name: &str,
+        modified: SystemTime,
+        stable_for: Duration,
+        now: SystemTime,
+    ) -> PickupState {
+        if self.is_excluded(file_name) {
+            return PickupState::Excluded;
+        }
+
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age < Duration::from_secs(self.min_file_age_secs) {
+            return PickupState::TooNew;
+        }
+
+        if stable_for < Duration::from_secs(self.stability_window_secs) {
+            return PickupState::Settling;
+        }
+
+        PickupState::Ready
+    }
+
+    /// Applies the existing-output action. Returns `None` when the file
+    /// should be skipped, otherwise the path the job should write to.
+    pub fn resolve_output(&self, output: &Path) -> Option<PathBuf> {
+        if !output.exists() {
+            return Some(output.to_path_buf());
+        }
+
+        match self.on_existing_output {
+            ExistingOutputAction::Skip => None,
+            ExistingOutputAction::Overwrite => Some(output.to_path_buf()),
+            ExistingOutputAction::Rename => Some(crate::fs_utils::next_available_path(output)),
+        }
+    }
+}
+
+/// Pickup readiness of a single file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PickupState {
+    Ready,
+    TooNew,
+    Settling,
+    Excluded,
+}
+
+/// Remembers file sizes between polls to measure how long each file has
+/// been stable.
+#[derive(Debug, Default)]
+pub struct StabilityTracker {
+    observations: HashMap<PathBuf, (u64, SystemTime)>,
+}
+
+impl StabilityTracker {
+    /// Records the current size and returns how long it has been unchanged.
+    pub fn observe(&mut self, path: &Path, size: u64, now: SystemTime) -> Duration {
+        let entry = self
+            .observations
+            .entry(path.to_path_buf())
+            .or_insert((size, now));
+
+        if entry.0 != size {
+            *entry = (size, now);
+        }
+
+        now.duration_since(entry.1).unwrap_or_default()
+    }
+
+    /// Drops observations for files that are no longer present.
+    pub fn retain(&mut self, present: &[PathBuf]) {
+        self.observations
+            .retain(|path, _| present.iter().any(|entry| entry == path));
+    }
+}
+
+fn invalid_policy(message: impl Into<String>) -> AppError {
+    AppError::new("watch_folder_invalid_policy", message)
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_matching_handles_stars_and_question_marks() {
+        assert!(wildcard_match("*.part", "clip.mov.part"));
+        assert!(wildcard_match(".*", ".ds_store"));
+        assert!(wildcard_match("c???.mov", "c001.mov"));
+        assert!(!wildcard_match("*.part", "clip.mov"));
+        assert!(!wildcard_match("c??.mov", "c001.mov"));
+    }
+
+    #[test]
+    fn default_policy_excludes_partial_downloads() {
+        let policy = WatchFolderPolicy::default();
+        assert!(policy.is_excluded("Clip.MOV.PART"));
+        assert!(policy.is_excluded(".hidden.mp4"));
+        assert!(!policy.is_excluded("clip.mov"));
+    }
+
+    #[test]
+    fn validation_rejects_path_patterns_and_huge_windows() {
+        let mut policy = WatchFolderPolicy {
+            exclude_patterns: vec!["sub/*.mov".into()],
+            ..WatchFolderPolicy::default()
+        };
+        assert_eq!(
+            policy.validate().unwrap_err().code,
+            "watch_folder_invalid_policy"
+        );
+
+        policy.exclude_patterns.clear();
+        policy.stability_window_secs = MAX_WINDOW_SECS + 1;
+        assert!(policy.validate().is_err());
+
+        policy.stability_window_secs = 30;
+        assert!(policy.validate().is_ok());
+    }
+
+    #[test]
+    fn evaluate_waits_for_age_and_stability() {
+        let policy = WatchFolderPolicy::default();
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(60);
+
+        assert_eq!(
+            policy.evaluate("a.mov", now, Duration::from_secs(60), now),
+            PickupState::TooNew
+        );
+        assert_eq!(
+            policy.evaluate("a.mov", old, Duration::from_secs(2), now),
+            PickupState::Settling
+        );
+        assert_eq!(
+            policy.evaluate("a.mov", old, Duration::from_secs(10), now),
+            PickupState::Ready
+        );
+        assert_eq!(
+            policy.evaluate("a.tmp", old, Duration::from_secs(10), now),
+            PickupState::Excluded
+        );
+    }
+
+    #[test]
+    fn tracker_resets_when_size_changes() {
+        let mut tracker = StabilityTracker::default();
+        let path = PathBuf::from("/watch/clip.mov");
+        let start = SystemTime::now();
+
+        assert_eq!(tracker.observe(&path, 10, start), Duration::ZERO);
+        let later = start + Duration::from_secs(5);
+        assert_eq!(tracker.observe(&path, 10, later), Duration::from_secs(5));
+        let grown = later + Duration::from_secs(5);
+        assert_eq!(tracker.observe(&path, 20, grown), Duration::ZERO);
+
+        tracker.retain(&[]);
+        assert!(tracker.observations.is_empty());
+    }
+
+    #[test]
+    fn existing_output_action_is_applied() {
+        let temp = tempfile::tempdir().unwrap();
+        let output = temp.path().join("clip.mp4");
+        let mut policy = WatchFolderPolicy::default();
+
+        assert_eq!(policy.resolve_output(&output), Some(output.clone()));
+
+        std::fs::File::create(&output).unwrap();
+        assert_eq!(policy.resolve_output(&output), None);
+
+        policy.on_existing_output = ExistingOutputAction::Overwrite;
+        assert_eq!(policy.resolve_output(&output), Some(output.clone()));
+
+        policy.on_existing_output = ExistingOutputAction::Rename;
+        assert_eq!(
+            policy.resolve_output(&output),
+            Some(temp.path().join("clip (1).mp4"))
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use super::WatchFolder;
+use crate::{
+    error::AppError,
+    persistence::{self, DocumentSchema},
+};
+
+const WATCH_FOLDERS_FILE_NAME: &str = "watch-folders.json";
+const WATCH_FOLDERS_SCHEMA: DocumentSchema = DocumentSchema {
+    name: "watch folders",
+    migrations: &[],
+};
+
+pub fn store_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(WATCH_FOLDERS_FILE_NAME))
+        .map_err(|err| AppError::new("watch_folder_store_path", err.to_string()))
+}
+
+pub fn load(path: &Path) -> Result<Vec<WatchFolder>, AppError> {
+    Ok(persistence::load(path, &WATCH_FOLDERS_SCHEMA)?.unwrap_or_default())
+}
+
+pub fn persist(path: &Path, folders: &[WatchFolder]) -> Result<(), AppError> {
+    persistence::persist(path, &WATCH_FOLDERS_SCHEMA, &folders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watch_folders::WatchFolderPolicy;
+    use tempfile::TempDir;
+
+    #[test]
+    fn persist_and_load_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config").join(WATCH_FOLDERS_FILE_NAME);
+        assert!(load(&path).unwrap().is_empty());
+
+        let folders = vec![WatchFolder {
+            id: "camera".into(),
+            path: temp.path().to_string_lossy().into_owned(),
+            enabled: true,
+            policy: WatchFolderPolicy {
+                stability_window_secs: 30,
+                ..WatchFolderPolicy::default()
+            },
+        }];
+        persist(&path, &folders).unwrap();
+
+        assert_eq!(load(&path).unwrap(), folders);
+    }
+}
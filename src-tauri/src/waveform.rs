@@ -0,0 +1,229 @@
+//! Waveform images for previewing audio.
+//!
+//! ffmpeg's `showwavespic` draws the whole first audio stream, downmixed to
+//! mono, into a single PNG under the app cache directory. Images are keyed
+//! by the source's path, size and modification time plus the requested
+//! look, so reopening a file reuses its waveform until the file changes.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::UNIX_EPOCH,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+const WAVEFORM_DIR: &str = "waveforms";
+/// Largest image side accepted, in pixels.
+pub const MAX_WAVEFORM_SIDE: u32 = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformRequest {
+    pub path: String,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+    /// Hex RGB color such as `#f59e0b`.
+    #[serde(default = "default_color")]
+    pub color: String,
+}
+
+fn default_width() -> u32 {
+    1200
+}
+
+fn default_height() -> u32 {
+    240
+}
+
+fn default_color() -> String {
+    "#f59e0b".into()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Waveform {
+    /// PNG in the app cache directory.
+    pub image_path: String,
+    pub width: u32,
+    pub height: u32,
+    /// Whether an earlier image was reused.
+    pub cached: bool,
+}
+
+/// Whether `color` is a `#rrggbb` hex color.
+pub fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Cache file name for `request`; changes whenever the source does.
+pub fn cache_key(request: &WaveformRequest, metadata: &fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|age| age.as_nanos())
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(request.path.as_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(modified.to_le_bytes());
+    hasher.update(format!("{}x{}:{}", request.width, request.height, request.color).as_bytes());
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{}.png", &digest[..32])
+}
+
+/// Arguments drawing the waveform of `request.path` into `output`.
+pub fn waveform_args(request: &WaveformRequest, output: &Path) -> Vec<String> {
+    let filter = format!(
+        "[0:a:0]aformat=channel_layouts=mono,showwavespic=s={}x{}:colors=0x{}[wave]",
+        request.width,
+        request.height,
+        request.color.trim_start_matches('#')
+    );
+    [
+        "-hide_banner",
+        "-nostdin",
+        "-loglevel",
+        "error",
+        "-y",
+        "-i",
+        &request.path,
+        "-filter_complex",
+        &filter,
+        "-map",
+        "[wave]",
+        "-frames:v",
+        "1",
+        "-c:v",
+        "png",
+        "-f",
+        "image2",
+        &output.to_string_lossy(),
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+fn cache_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join(WAVEFORM_DIR))
+        .map_err(|err| AppError::new("waveform_cache_dir", err.to_string()))
+}
+
+/// Renders the waveform, or returns the cached image for an unchanged file.
+pub fn generate(app: &AppHandle, request: &WaveformRequest) -> Result<Waveform, AppError> {
+    let metadata = fs::metadata(&request.path)
+        .map_err(|err| AppError::new("waveform_source", format!("{}: {err}", request.path)))?;
+    let dir = cache_dir(app)?;
+    let image = dir.join(cache_key(request, &metadata));
+    let waveform = |cached| Waveform {
+        image_path: image.to_string_lossy().into_owned(),
+        width: request.width,
+        height: request.height,
+        cached,
+    };
+    if image.is_file() {
+        return Ok(waveform(true));
+    }
+
+    fs::create_dir_all(&dir).map_err(|err| AppError::new("waveform_cache_dir", err.to_string()))?;
+    // Render next to the final name so a half-written image is never reused.
+    let partial = image.with_extension("png.part");
+    let args = waveform_args(request, &partial);
+    let mut last_err = None;
+
+    for candidate in crate::binary_resolver::resolve_ffmpeg_paths(app) {
+        match Command::new(&candidate).args(&args).output() {
+            Ok(output) if output.status.success() => {
+                fs::rename(&partial, &image)
+                    .map_err(|err| AppError::new("waveform_write", err.to_string()))?;
+                return Ok(waveform(false));
+            },
+            Ok(output) => {
+                last_err = Some(format!(
+                    "ffmpeg exited with status {} (stderr: {})",
+                    output
+                        .status
+                        .code()
+                        .map(|code| code.to_string())
+                        .unwrap_or_else(|| "unknown".into()),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            },
+            Err(error) => last_err = Some(error.to_string()),
+        }
+    }
+
+    let _ = fs::remove_file(&partial);
+    Err(AppError::new(
+        "waveform_exec",
+        last_err.unwrap_or_else(|| "Unable to execute ffmpeg".into()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn request(path: &str) -> WaveformRequest {
+        serde_json::from_value(serde_json::json!({ "path": path })).unwrap()
+    }
+
+    #[test]
+    fn waveform_is_drawn_from_the_first_audio_stream() {
+        let args = waveform_args(&request("/music/song.flac"), Path::new("/cache/w.png"));
+        let filter = args
+            .iter()
+            .position(|arg| arg == "-filter_complex")
+            .unwrap();
+        assert_eq!(
+            args[filter + 1],
+            "[0:a:0]aformat=channel_layouts=mono,showwavespic=s=1200x240:colors=0xf59e0b[wave]"
+        );
+        assert_eq!(args.last().unwrap(), "/cache/w.png");
+    }
+
+    #[test]
+    fn cache_keys_follow_the_source_and_look() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().to_string_lossy().into_owned();
+        let metadata = fs::metadata(file.path()).unwrap();
+        let base = request(&path);
+        let key = cache_key(&base, &metadata);
+
+        assert!(key.ends_with(".png"));
+        assert_eq!(key, cache_key(&base, &metadata));
+        let taller = WaveformRequest {
+            height: 480,
+            ..base.clone()
+        };
+        assert_ne!(key, cache_key(&taller, &metadata));
+
+        fs::write(file.path(), b"changed").unwrap();
+        assert_ne!(key, cache_key(&base, &fs::metadata(file.path()).unwrap()));
+    }
+
+    #[test]
+    fn colors_must_be_hex() {
+        assert!(is_hex_color("#F59E0B"));
+        assert!(!is_hex_color("f59e0b"));
+        assert!(!is_hex_color("#f59e0"));
+        assert!(!is_hex_color("#f59e0g"));
+    }
+}
@@ -1,4 +1,4 @@
-use honeymelon_lib::{CompletionPayload, ProgressMetrics, ProgressPayload};
+use honeymelon_lib::{CompletionPayload, ProgressMetrics, ProgressPayload, Remediation};
 use serde_json::{json, Value};
 
 #[test]
@@ -9,14 +9,19 @@ fn progress_payload_serializes_expected_fields() {
             processed_seconds: Some(42.0),
             fps: Some(29.97),
             speed: Some(1.25),
+            total_size: Some(1_048_576),
+            bitrate_kbps: Some(199.7),
         }),
         raw: "frame=100 fps=29.97 time=00:00:42.00 speed=1.25x".into(),
+        metadata: Default::default(),
     };
 
     let json = serde_json::to_value(&payload).expect("serialize payload");
     assert_eq!(json["jobId"], "job-1");
     assert_eq!(json["raw"], payload.raw);
     assert_eq!(json["progress"]["fps"], json!(29.97));
+    assert_eq!(json["progress"]["totalSize"], json!(1_048_576));
+    assert_eq!(json["progress"]["bitrateKbps"], json!(199.7));
 }
 
 #[test]
@@ -30,10 +35,20 @@ fn completion_payload_includes_logs_and_status() {
         code: "job_failed".into(),
         message: Some("ffmpeg exited with status 1".into()),
         logs: vec!["line 1".into(), "line 2".into()],
+        log_path: Some("/logs/jobs/job-1.log".into()),
+        suggestions: vec![Remediation::SoftwareDecode],
+        output_path: None,
+        verified: false,
+        fell_back_to_software: true,
+        trashed_source: None,
+        metadata: Default::default(),
     };
 
     let json: Value = serde_json::to_value(&payload).expect("serialize payload");
     assert_eq!(json["jobId"], "job-1");
     assert_eq!(json["code"], "job_failed");
     assert_eq!(json["logs"].as_array().unwrap().len(), 2);
+    assert_eq!(json["logPath"], "/logs/jobs/job-1.log");
+    assert_eq!(json["suggestions"][0]["action"], "softwareDecode");
+    assert_eq!(json["fellBackToSoftware"], true);
 }
@@ -46,6 +46,7 @@ vi.mock('@/composables/use-tauri-events', () => ({
 vi.mock('@/lib/capability', () => ({
   availablePresets: (...args: unknown[]) => availablePresetsMock(...args),
   loadCapabilities: (...args: unknown[]) => loadCapabilitiesMock(...args),
+  onCapabilitiesChanged: () => Promise.resolve(() => {}),
 }));
 
 vi.mock('@/stores/jobs', () => ({
@@ -17,6 +17,11 @@ const formatCompletionErrorMock = vi.fn();
 const executionStartMock = vi.fn();
 const executionCancelMock = vi.fn();
 const executionSetConcurrencyMock = vi.fn();
+const executionSetLowPriorityMock = vi.fn();
+const executionSetDefaultThreadsMock = vi.fn();
+const executionSetPostJobHooksMock = vi.fn();
+const executionSetPreventSleepMock = vi.fn();
+const executionSetBatteryPolicyMock = vi.fn();
 const probeMediaMock = vi.fn();
 const notificationModuleMock = {
   isPermissionGranted: vi.fn(),
@@ -44,6 +49,7 @@ vi.mock('pinia', () => ({
 vi.mock('@/lib/capability', () => ({
   availablePresets: (...args: unknown[]) => availablePresetsMock(...args),
   loadCapabilities: (...args: unknown[]) => loadCapabilitiesMock(...args),
+  onCapabilitiesChanged: () => Promise.resolve(() => {}),
 }));
 
 vi.mock('@/lib/constants', () => ({
@@ -82,6 +88,11 @@ vi.mock('@/services/execution-service', () => ({
     start: (...args: unknown[]) => executionStartMock(...args),
     cancel: (...args: unknown[]) => executionCancelMock(...args),
     setConcurrency: (...args: unknown[]) => executionSetConcurrencyMock(...args),
+    setLowPriority: (...args: unknown[]) => executionSetLowPriorityMock(...args),
+    setDefaultThreads: (...args: unknown[]) => executionSetDefaultThreadsMock(...args),
+    setPreventSleep: (...args: unknown[]) => executionSetPreventSleepMock(...args),
+    setBatteryPolicy: (...args: unknown[]) => executionSetBatteryPolicyMock(...args),
+    setPostJobHooks: (...args: unknown[]) => executionSetPostJobHooksMock(...args),
   },
 }));
 
@@ -226,6 +237,14 @@ function setupStores(jobOverrides: Partial<MockJob> = {}): SetupStoresResult {
     includePresetInName: ref(true),
     includeTierInName: ref(true),
     filenameSeparator: ref('-'),
+    lowPriority: ref(false),
+    segmentedEncoding: ref(false),
+    trashSourceAfterSuccess: ref(false),
+    preserveTimestamps: ref(false),
+    encoderThreads: ref(null),
+    preventSleep: ref(true),
+    batteryPolicy: ref('ignore'),
+    postJobHooks: ref([]),
   };
 
   const prefsStore: PrefsStore = {
@@ -51,6 +51,7 @@ describe('useTauriEvents', () => {
     const onDragLeave = vi.fn();
     const onMenuOpen = vi.fn().mockResolvedValue(undefined);
     const onMenuAbout = vi.fn();
+    const onQuitRequested = vi.fn();
 
     const unlistenSpies: Record<string, ReturnType<typeof vi.fn>> = {};
     const registeredHandlers: Record<string, (payload?: any) => unknown> = {};
@@ -75,6 +76,7 @@ describe('useTauriEvents', () => {
       onDragLeave,
       onMenuOpen,
       onMenuAbout,
+      onQuitRequested,
     });
 
     await setupEventListeners();
@@ -86,6 +88,7 @@ describe('useTauriEvents', () => {
         'tauri://drag-leave',
         'menu:open',
         'menu:about',
+        'app:quit-requested',
       ]),
     );
 
@@ -107,6 +110,9 @@ describe('useTauriEvents', () => {
     registeredHandlers['menu:about']();
     expect(onMenuAbout).toHaveBeenCalled();
 
+    await registeredHandlers['app:quit-requested']({ payload: { activeJobs: 2 } });
+    expect(onQuitRequested).toHaveBeenCalledWith(2);
+
     cleanupEventListeners();
 
     Object.values(unlistenSpies).forEach((spy) => {
@@ -98,6 +98,8 @@ describe('orchestrator planner/runner integration', () => {
       includePresetInName: ref(true),
       includeTierInName: ref(true),
       filenameSeparator: ref('-'),
+      segmentedEncoding: ref(true),
+      trashSourceAfterSuccess: ref(true),
       simulate: false,
       execution: new ExecutionService(),
     });
@@ -110,10 +112,17 @@ describe('orchestrator planner/runner integration', () => {
     expect(invokeMock).toHaveBeenCalledWith(
       'start_job',
       expect.objectContaining({
-        jobId: job.id,
-        args: expect.arrayContaining(['-i', job.path]),
-        outputPath: expect.stringContaining('.mp4'),
-        exclusive: false,
+        spec: expect.objectContaining({
+          jobId: job.id,
+          args: expect.arrayContaining(['-i', job.path]),
+          outputPath: expect.stringContaining('.mp4'),
+          exclusive: false,
+          durationSec: 64,
+          segmented: true,
+          trashSource: true,
+          metadata: expect.objectContaining({ presetId: 'video-to-mp4' }),
+          plan: expect.objectContaining({ preset: decision.preset }),
+        }),
       }),
     );
   });
@@ -128,6 +137,7 @@ describe('orchestrator planner/runner integration', () => {
       includePresetInName: ref(false),
       includeTierInName: ref(false),
       filenameSeparator: ref('_'),
+      segmentedEncoding: ref(false),
       simulate: false,
       execution: new ExecutionService(),
     });
@@ -182,8 +192,14 @@ describe('orchestrator planner/runner integration', () => {
       expect.objectContaining({ jobId: 'job-1', raw: 'progress-line' }),
     );
 
-    await handlers['ffmpeg://stderr']?.({ payload: { jobId: 'job-1', line: 'stderr-line' } });
-    expect(onStderr).toHaveBeenCalledWith({ jobId: 'job-1', line: 'stderr-line' });
+    await handlers['ffmpeg://stderr']?.({
+      payload: { jobId: 'job-1', line: 'stderr-line', severity: 'warning' },
+    });
+    expect(onStderr).toHaveBeenCalledWith({
+      jobId: 'job-1',
+      line: 'stderr-line',
+      severity: 'warning',
+    });
 
     await handlers['ffmpeg://completion']?.({
       payload: { jobId: 'job-1', success: true, cancelled: false, logs: [] },
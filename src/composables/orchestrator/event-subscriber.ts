@@ -1,8 +1,32 @@
+import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
+import type { NativePath } from '@/lib/api-types.generated';
+import type { BatteryPolicy } from '@/services/execution-service';
+
 const PROGRESS_EVENT = 'ffmpeg://progress';
 const COMPLETION_EVENT = 'ffmpeg://completion';
 const STDERR_EVENT = 'ffmpeg://stderr';
+const QUEUE_EVENT = 'ffmpeg://queue';
+const RETRYING_EVENT = 'ffmpeg://retrying';
+const QUEUE_SUSPENDED_EVENT = 'ffmpeg://queue-suspended';
+const COMPACT_PROGRESS_EVENT = 'ffmpeg://progress-compact';
+const COMPACT_STDERR_EVENT = 'ffmpeg://stderr-compact';
+
+export type EventEncoding = 'json' | 'compact';
+
+/** Positional progress tuple: `[jobId, processedSeconds, fps, speed]`. */
+type CompactProgress = [string, number | null, number | null, number | null];
+type CompactStderr = [string, string, StderrSeverity];
+
+/** Severity the backend assigns to each stderr line from known ffmpeg messages. */
+export type StderrSeverity = 'info' | 'warning' | 'error';
+
+export interface StderrEventPayload {
+  jobId?: string;
+  line?: string;
+  severity?: StderrSeverity;
+}
 
 export interface ProgressEventPayload {
   jobId: string;
@@ -10,10 +34,21 @@ export interface ProgressEventPayload {
     processedSeconds?: number;
     fps?: number;
     speed?: number;
+    /** Bytes written to the output so far */
+    totalSize?: number;
+    bitrateKbps?: number;
   };
   raw: string;
+  /** Labels given to the job when it started */
+  metadata?: Record<string, string>;
 }
 
+export type RemediationSuggestion =
+  | { action: 'softwareDecode' }
+  | { action: 'lowerThreads'; threads: number }
+  | { action: 'changeContainer'; container: string }
+  | { action: 'freeDisk' };
+
 export interface CompletionEventPayload {
   jobId: string;
   success: boolean;
@@ -23,13 +58,53 @@ export interface CompletionEventPayload {
   code?: string | null;
   message?: string | null;
   logs?: string[];
+  logPath?: string | null;
+  suggestions?: RemediationSuggestion[];
+  /** Final output location; differs from the requested path after an auto-rename */
+  outputPath?: NativePath | null;
+  /** Whether the output was probed after finalizing and matched its source */
+  verified?: boolean;
+  /** Whether the job was rerun with a software encoder after its hardware encoder failed */
+  fellBackToSoftware?: boolean;
+  /** Source moved to the trash after the job succeeded */
+  trashedSource?: string | null;
+  /** Labels given to the job when it started */
+  metadata?: Record<string, string>;
+}
+
+/** Queue position of a waiting job; position 0 means it has started. */
+export interface QueuePositionEventPayload {
+  jobId: string;
+  position: number;
+  queueLength: number;
+}
+
+/** A failed job that will be started again after `delayMs`. */
+export interface RetryingEventPayload {
+  jobId: string;
+  attempt: number;
+  maxRetries: number;
+  delayMs: number;
+  code: string;
+  message?: string | null;
+}
+
+/** The battery policy holding (`suspended`) or releasing the queue. */
+export interface QueueSuspendedEventPayload {
+  suspended: boolean;
+  policy: BatteryPolicy;
 }
 
 export interface RunnerEventSubscriberOptions {
   enabled: boolean;
+  /** Request positional event payloads; the backend may fall back to JSON. */
+  compact?: boolean;
   onProgress?: (payload: ProgressEventPayload | undefined) => void;
   onCompletion?: (payload: CompletionEventPayload | undefined) => void;
-  onStderr?: (payload: { jobId?: string; line?: string } | undefined) => void;
+  onStderr?: (payload: StderrEventPayload | undefined) => void;
+  onQueuePosition?: (payload: QueuePositionEventPayload | undefined) => void;
+  onRetrying?: (payload: RetryingEventPayload | undefined) => void;
+  onQueueSuspended?: (payload: QueueSuspendedEventPayload | undefined) => void;
 }
 
 export interface RunnerEventSubscriber {
@@ -37,6 +112,32 @@ export interface RunnerEventSubscriber {
   stop: () => void;
 }
 
+async function negotiateEncoding(compact: boolean): Promise<EventEncoding> {
+  if (!compact) {
+    return 'json';
+  }
+  try {
+    return await invoke<EventEncoding>('negotiate_event_encoding', {
+      preferred: ['compact', 'json'],
+    });
+  } catch (error) {
+    console.error('[orchestrator] Failed to negotiate event encoding:', error);
+    return 'json';
+  }
+}
+
+function expandProgress([jobId, processedSeconds, fps, speed]: CompactProgress): ProgressEventPayload {
+  return {
+    jobId,
+    progress: {
+      processedSeconds: processedSeconds ?? undefined,
+      fps: fps ?? undefined,
+      speed: speed ?? undefined,
+    },
+    raw: '',
+  };
+}
+
 export function createRunnerEventSubscriber(
   options: RunnerEventSubscriberOptions,
 ): RunnerEventSubscriber {
@@ -49,11 +150,18 @@ export function createRunnerEventSubscriber(
     }
     started = true;
 
+    const encoding = await negotiateEncoding(options.compact ?? false);
+
     if (options.onProgress) {
       try {
-        const unlistenProgress = await listen<ProgressEventPayload>(PROGRESS_EVENT, (event) => {
-          options.onProgress?.(event.payload);
-        });
+        const unlistenProgress =
+          encoding === 'compact'
+            ? await listen<CompactProgress>(COMPACT_PROGRESS_EVENT, (event) => {
+                options.onProgress?.(expandProgress(event.payload));
+              })
+            : await listen<ProgressEventPayload>(PROGRESS_EVENT, (event) => {
+                options.onProgress?.(event.payload);
+              });
         unlistenFns.push(unlistenProgress);
       } catch (error) {
         console.error('[orchestrator] Failed to setup progress listener:', error);
@@ -62,12 +170,15 @@ export function createRunnerEventSubscriber(
 
     if (options.onStderr) {
       try {
-        const unlistenStderr = await listen<{ jobId: string; line: string }>(
-          STDERR_EVENT,
-          (event) => {
-            options.onStderr?.(event.payload);
-          },
-        );
+        const unlistenStderr =
+          encoding === 'compact'
+            ? await listen<CompactStderr>(COMPACT_STDERR_EVENT, (event) => {
+                const [jobId, line, severity] = event.payload;
+                options.onStderr?.({ jobId, line, severity });
+              })
+            : await listen<StderrEventPayload>(STDERR_EVENT, (event) => {
+                options.onStderr?.(event.payload);
+              });
         unlistenFns.push(unlistenStderr);
       } catch (error) {
         console.error('[orchestrator] Failed to setup stderr listener:', error);
@@ -87,6 +198,42 @@ export function createRunnerEventSubscriber(
         console.error('[orchestrator] Failed to setup completion listener:', error);
       }
     }
+
+    if (options.onQueuePosition) {
+      try {
+        const unlistenQueue = await listen<QueuePositionEventPayload>(QUEUE_EVENT, (event) => {
+          options.onQueuePosition?.(event.payload);
+        });
+        unlistenFns.push(unlistenQueue);
+      } catch (error) {
+        console.error('[orchestrator] Failed to setup queue listener:', error);
+      }
+    }
+
+    if (options.onRetrying) {
+      try {
+        const unlistenRetrying = await listen<RetryingEventPayload>(RETRYING_EVENT, (event) => {
+          options.onRetrying?.(event.payload);
+        });
+        unlistenFns.push(unlistenRetrying);
+      } catch (error) {
+        console.error('[orchestrator] Failed to setup retrying listener:', error);
+      }
+    }
+
+    if (options.onQueueSuspended) {
+      try {
+        const unlistenSuspended = await listen<QueueSuspendedEventPayload>(
+          QUEUE_SUSPENDED_EVENT,
+          (event) => {
+            options.onQueueSuspended?.(event.payload);
+          },
+        );
+        unlistenFns.push(unlistenSuspended);
+      } catch (error) {
+        console.error('[orchestrator] Failed to setup queue suspension listener:', error);
+      }
+    }
   }
 
   function stop() {
@@ -13,6 +13,9 @@ interface RunnerClientOptions {
   includePresetInName: Ref<boolean>;
   includeTierInName: Ref<boolean>;
   filenameSeparator: Ref<string>;
+  segmentedEncoding: Ref<boolean>;
+  trashSourceAfterSuccess?: Ref<boolean>;
+  preserveTimestamps?: Ref<boolean>;
   simulate: boolean;
   execution: ExecutionService;
 }
@@ -30,6 +33,9 @@ export function createRunnerClient(options: RunnerClientOptions): RunnerClient {
     includePresetInName,
     includeTierInName,
     filenameSeparator,
+    segmentedEncoding,
+    trashSourceAfterSuccess,
+    preserveTimestamps,
     simulate,
     execution,
   } = options;
@@ -63,6 +69,12 @@ export function createRunnerClient(options: RunnerClientOptions): RunnerClient {
         decision,
         outputPath,
         exclusive: job.exclusive ?? false,
+        tier: job.tier,
+        durationSec: job.summary?.durationSec,
+        segmented: segmentedEncoding.value,
+        trashSource: trashSourceAfterSuccess?.value,
+        preserveTimestamps: preserveTimestamps?.value,
+        metadata: { presetId: job.presetId, tier: job.tier, label: pathBasename(job.path) },
       });
 
       if (!startResult.success) {
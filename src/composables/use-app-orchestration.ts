@@ -44,6 +44,7 @@ import { useCapabilityGate } from '@/composables/use-capability-gate';
 import { useDesktopBridge } from '@/composables/use-desktop-bridge';
 import { useFileHandler } from '@/composables/use-file-handler';
 import { useJobOrchestrator } from '@/composables/use-job-orchestrator';
+import { executionService } from '@/services/execution-service';
 import { useJobsStore } from '@/stores/jobs';
 
 /**
@@ -417,6 +418,24 @@ export function useAppOrchestration() {
     isAboutOpen.value = true;
   }
 
+  /**
+   * Asks before quitting while the backend reports running jobs.
+   *
+   * The backend holds the window close or quit and emits a request; on
+   * confirmation it stops the jobs, removes partial output and exits.
+   */
+  async function confirmQuit(activeJobCount: number) {
+    const confirmed = window.confirm(
+      `You have ${activeJobCount} job(s) currently running. Are you sure you want to quit? This will cancel all active jobs.`,
+    );
+    if (!confirmed) return;
+    try {
+      await executionService.confirmQuit();
+    } catch (error) {
+      console.error('[app] Failed to quit:', error);
+    }
+  }
+
   /**
    * Tauri event handlers for desktop-specific functionality.
    *
@@ -427,34 +446,19 @@ export function useAppOrchestration() {
     onDrop: handleFileDrop,
     onBrowseFiles: () => handleBrowse(),
     onOpenAbout: openAbout,
+    onQuitRequested: confirmQuit,
   });
   dragStateRef = isDragOver;
 
   /**
    * Initialization logic on component mount.
    *
-   * Loads system capabilities, sets up window close handling for active jobs,
-   * and prepares the application for use.
+   * Loads system capabilities and prepares the application for use.
    */
   const hasInstanceContext = !!getCurrentInstance();
 
   const initializeCapabilities = async () => {
     await loadCapabilitySnapshot();
-
-    if (fileHandler.isTauriRuntime()) {
-      const { getCurrentWindow } = await import('@tauri-apps/api/window');
-      const currentWindow = getCurrentWindow();
-      await currentWindow.onCloseRequested(async (event) => {
-        if (hasActiveJobs.value) {
-          const confirmed = window.confirm(
-            `You have ${activeJobs.value.length} job(s) currently running. Are you sure you want to quit? This will cancel all active jobs.`,
-          );
-          if (!confirmed) {
-            event.preventDefault();
-          }
-        }
-      });
-    }
   };
 
   if (hasInstanceContext) {
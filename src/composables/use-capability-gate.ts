@@ -1,13 +1,14 @@
-import { computed, ref, type ComputedRef, type Ref } from 'vue';
+import { computed, getCurrentScope, onScopeDispose, ref, type ComputedRef, type Ref } from 'vue';
 
-import { availablePresets, loadCapabilities } from '@/lib/capability';
+import { availablePresets, loadCapabilities, onCapabilitiesChanged } from '@/lib/capability';
 import { DEFAULT_PRESET_ID } from '@/lib/presets';
 import type { CapabilitySnapshot, Preset } from '@/lib/types';
 
 /**
  * Encapsulates FFmpeg capability loading and preset availability state.
  * Provides a single place for the app to await capability detection before
- * enabling preset-dependent flows.
+ * enabling preset-dependent flows. Once loaded, the snapshot follows backend
+ * capability changes, so preset availability updates live.
  */
 export function useCapabilityGate(): {
   capabilities: Ref<CapabilitySnapshot | undefined>;
@@ -22,8 +23,21 @@ export function useCapabilityGate(): {
   const presetOptions = computed<Preset[]>(() => availablePresets(capabilities.value));
   const presetsReady = computed(() => presetOptions.value.length > 0);
 
+  let stopListening: (() => void) | undefined;
+  let listening = false;
+
   async function loadCapabilitySnapshot() {
     capabilities.value = await loadCapabilities();
+    if (!listening) {
+      listening = true;
+      stopListening = await onCapabilitiesChanged((snapshot) => {
+        capabilities.value = snapshot;
+      });
+    }
+  }
+
+  if (getCurrentScope()) {
+    onScopeDispose(() => stopListening?.());
   }
 
   return {
@@ -6,14 +6,15 @@ import {
   createRunnerEventSubscriber,
   type CompletionEventPayload,
   type ProgressEventPayload,
+  type StderrEventPayload,
 } from '@/composables/orchestrator/event-subscriber';
 import { createPlannerClient } from '@/composables/orchestrator/planner-client';
 import { createRunnerClient } from '@/composables/orchestrator/runner-client';
-import { loadCapabilities } from '@/lib/capability';
+import { loadCapabilities, onCapabilitiesChanged } from '@/lib/capability';
 import { LIMITS } from '@/lib/constants';
 import { ErrorHandler } from '@/lib/error-handler';
 import type { CapabilitySnapshot, Tier } from '@/lib/types';
-import { pathBasename } from '@/lib/utils';
+import { displayPath, pathBasename } from '@/lib/utils';
 import { executionService } from '@/services/execution-service';
 import { useJobsStore } from '@/stores/jobs';
 import { usePrefsStore } from '@/stores/prefs';
@@ -50,6 +51,14 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
     includePresetInName,
     includeTierInName,
     filenameSeparator,
+    segmentedEncoding,
+    trashSourceAfterSuccess,
+    preserveTimestamps,
+    lowPriority,
+    encoderThreads,
+    preventSleep,
+    batteryPolicy,
+    postJobHooks,
   } = storeToRefs(prefs);
   const capabilities = ref<CapabilitySnapshot>();
   const e2eSimulation = import.meta.env.VITE_E2E_SIMULATION === 'true';
@@ -69,6 +78,9 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
     includePresetInName,
     includeTierInName,
     filenameSeparator,
+    segmentedEncoding,
+    trashSourceAfterSuccess,
+    preserveTimestamps,
     simulate,
     execution: executionService,
   });
@@ -88,6 +100,56 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
     { immediate: true },
   );
 
+  watch(
+    lowPriority,
+    (value) => {
+      if (!simulate) {
+        void executionService.setLowPriority(value);
+      }
+    },
+    { immediate: true },
+  );
+
+  watch(
+    encoderThreads,
+    (value) => {
+      if (!simulate) {
+        void executionService.setDefaultThreads(value);
+      }
+    },
+    { immediate: true },
+  );
+
+  watch(
+    preventSleep,
+    (value) => {
+      if (!simulate) {
+        void executionService.setPreventSleep(value);
+      }
+    },
+    { immediate: true },
+  );
+
+  watch(
+    batteryPolicy,
+    (value) => {
+      if (!simulate) {
+        void executionService.setBatteryPolicy(value);
+      }
+    },
+    { immediate: true },
+  );
+
+  watch(
+    postJobHooks,
+    (value) => {
+      if (!simulate) {
+        void executionService.setPostJobHooks(value);
+      }
+    },
+    { immediate: true, deep: true },
+  );
+
   loadCapabilities()
     .then((snapshot) => {
       capabilities.value = snapshot;
@@ -96,6 +158,15 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
       console.warn('[orchestrator] Failed to load capabilities:', error);
     });
 
+  let stopCapabilityUpdates: (() => void) | null = null;
+  if (!simulate) {
+    void onCapabilitiesChanged((snapshot) => {
+      capabilities.value = snapshot;
+    }).then((unlisten) => {
+      stopCapabilityUpdates = unlisten;
+    });
+  }
+
   const runnerEvents = createRunnerEventSubscriber({
     enabled: !simulate,
     onProgress: handleProgressEvent,
@@ -144,7 +215,7 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
     }
   }
 
-  function handleStderrEvent(payload?: { jobId?: string; line?: string }) {
+  function handleStderrEvent(payload?: StderrEventPayload) {
     try {
       if (!payload?.jobId) {
         console.warn('[orchestrator] Invalid stderr event - missing jobId:', payload);
@@ -156,7 +227,14 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
         return;
       }
 
-      console.error(`[ffmpeg][${payload.jobId}] ${payload.line}`);
+      const message = `[ffmpeg][${payload.jobId}] ${payload.line}`;
+      if (payload.severity === 'error') {
+        console.error(message);
+      } else if (payload.severity === 'warning') {
+        console.warn(message);
+      } else {
+        console.debug(message);
+      }
       jobs.appendLog(payload.jobId, payload.line);
     } catch (error) {
       console.error('[orchestrator] Error processing stderr event:', error, payload);
@@ -191,7 +269,8 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
 
       if (payload.success) {
         const job = jobs.getJob(payload.jobId);
-        jobs.markCompleted(payload.jobId, job?.outputPath ?? '');
+        const outputPath = payload.outputPath ? displayPath(payload.outputPath) : undefined;
+        jobs.markCompleted(payload.jobId, outputPath ?? job?.outputPath ?? '');
         void notifyJobResult(payload);
       } else {
         const errorMessage = ErrorHandler.formatCompletionError(payload);
@@ -211,6 +290,7 @@ export function useJobOrchestrator(options: OrchestratorOptions = {}) {
 
   const teardown = () => {
     runnerEvents.stop();
+    stopCapabilityUpdates?.();
     runner.shutdown();
   };
 
@@ -8,16 +8,18 @@ interface UseTauriEventsOptions {
   onDragLeave?: () => void;
   onMenuOpen?: () => void | Promise<void>;
   onMenuAbout?: () => void;
+  onQuitRequested?: (activeJobs: number) => void | Promise<void>;
 }
 
 export function useTauriEvents(options: UseTauriEventsOptions = {}) {
-  const { onDrop, onDragEnter, onDragLeave, onMenuOpen, onMenuAbout } = options;
+  const { onDrop, onDragEnter, onDragLeave, onMenuOpen, onMenuAbout, onQuitRequested } = options;
 
   const unlistenDrop = ref<UnlistenFn | null>(null);
   const unlistenEnter = ref<UnlistenFn | null>(null);
   const unlistenLeave = ref<UnlistenFn | null>(null);
   const unlistenMenuOpen = ref<UnlistenFn | null>(null);
   const unlistenMenuAbout = ref<UnlistenFn | null>(null);
+  const unlistenQuitRequested = ref<UnlistenFn | null>(null);
 
   function isTauriRuntime(): boolean {
     return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
@@ -91,6 +93,15 @@ export function useTauriEvents(options: UseTauriEventsOptions = {}) {
         onMenuAbout();
       });
     }
+
+    if (onQuitRequested) {
+      unlistenQuitRequested.value = await listen<{ activeJobs?: number }>(
+        'app:quit-requested',
+        async (event) => {
+          await onQuitRequested(event.payload?.activeJobs ?? 0);
+        },
+      );
+    }
   }
 
   function cleanupEventListeners() {
@@ -99,6 +110,7 @@ export function useTauriEvents(options: UseTauriEventsOptions = {}) {
     unlistenLeave.value?.();
     unlistenMenuOpen.value?.();
     unlistenMenuAbout.value?.();
+    unlistenQuitRequested.value?.();
   }
 
   if (getCurrentInstance()) {
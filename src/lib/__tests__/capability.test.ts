@@ -1,6 +1,16 @@
 import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest';
 
-import { loadCapabilities, presetIsAvailable, availablePresets } from '../capability';
+import {
+  loadCapabilities,
+  loadEncoderOptions,
+  onCapabilitiesChanged,
+  refreshCapabilities,
+  validatePreset,
+  supportsPixelFormat,
+  hasBitstreamFilter,
+  presetIsAvailable,
+  availablePresets,
+} from '../capability';
 import { PRESETS } from '../presets';
 import type { CapabilitySnapshot, Preset } from '../types';
 
@@ -9,7 +19,12 @@ vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
 }));
 
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(),
+}));
+
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 const baseVideoPreset: Preset = {
   id: 'test-video',
@@ -127,6 +142,7 @@ describe('loadCapabilities', () => {
       audioEncoders: ['aac', 'opus'],
       formats: ['mp4', 'mov', 'mkv'],
       filters: ['scale', 'crop'],
+      ffmpeg: { version: '7.1', release: [7, 1, 0], configuration: [], libraries: {} },
     };
 
     mockInvoke.mockResolvedValue(mockCapabilities);
@@ -134,6 +150,7 @@ describe('loadCapabilities', () => {
     const result = await loadCapabilities();
 
     expect(mockInvoke).toHaveBeenCalledWith('load_capabilities');
+    expect(result.ffmpeg?.release).toEqual([7, 1, 0]);
     expect(result.videoEncoders).toEqual(new Set(['h264_videotoolbox', 'hevc_videotoolbox']));
     expect(result.audioEncoders).toEqual(new Set(['aac', 'opus']));
     expect(result.formats).toEqual(new Set(['mp4', 'mov', 'mkv']));
@@ -226,3 +243,130 @@ describe('loadCapabilities', () => {
     expect(result1.videoEncoders).toBeInstanceOf(Set);
   });
 });
+
+describe('supportsPixelFormat', () => {
+  it('checks known pixel formats', () => {
+    const capabilities = { ...emptyCapabilities, pixelFormats: new Set(['yuv420p', 'nv12']) };
+    expect(supportsPixelFormat(capabilities, 'nv12')).toBe(true);
+    expect(supportsPixelFormat(capabilities, 'yuv420p10le')).toBe(false);
+  });
+
+  it('assumes support when pixel formats are unknown', () => {
+    expect(supportsPixelFormat(undefined, 'p010le')).toBe(true);
+    expect(supportsPixelFormat(emptyCapabilities, 'p010le')).toBe(true);
+  });
+});
+
+describe('hasBitstreamFilter', () => {
+  it('checks known bitstream filters', () => {
+    const capabilities = { ...emptyCapabilities, bitstreamFilters: new Set(['aac_adtstoasc']) };
+    expect(hasBitstreamFilter(capabilities, 'aac_adtstoasc')).toBe(true);
+    expect(hasBitstreamFilter(capabilities, 'hevc_mp4toannexb')).toBe(false);
+    expect(hasBitstreamFilter(undefined, 'hevc_mp4toannexb')).toBe(true);
+  });
+});
+
+describe('refreshCapabilities', () => {
+  const mockInvoke = vi.mocked(invoke);
+
+  it('should bypass the cache and serve later loads', async () => {
+    (globalThis.window as any) = { __TAURI_INTERNALS__: {} };
+    mockInvoke.mockReset();
+    mockInvoke.mockResolvedValue({
+      videoEncoders: ['libx265'],
+      audioEncoders: [],
+      formats: [],
+      filters: [],
+    });
+
+    const refreshed = await refreshCapabilities();
+    const loaded = await loadCapabilities();
+
+    expect(mockInvoke).toHaveBeenCalledTimes(1);
+    expect(mockInvoke).toHaveBeenCalledWith('refresh_capabilities');
+    expect(loaded).toBe(refreshed);
+    expect(loaded.videoEncoders).toEqual(new Set(['libx265']));
+  });
+});
+
+describe('onCapabilitiesChanged', () => {
+  it('should reload the snapshot and pass on the diff', async () => {
+    (globalThis.window as any) = { __TAURI_INTERNALS__: {} };
+    const mockInvoke = vi.mocked(invoke);
+    mockInvoke.mockReset();
+    mockInvoke.mockResolvedValue({
+      videoEncoders: ['libsvtav1'],
+      audioEncoders: [],
+      formats: [],
+      filters: [],
+    });
+    let emit: ((event: { payload: unknown }) => void) | undefined;
+    vi.mocked(listen).mockImplementation(async (_event, callback) => {
+      emit = callback as typeof emit;
+      return () => {};
+    });
+    const handler = vi.fn();
+
+    await onCapabilitiesChanged(handler);
+    const diff = { videoEncoders: { added: ['libsvtav1'], removed: ['libx265'] } };
+    emit?.({ payload: diff });
+    const loaded = await loadCapabilities();
+
+    expect(listen).toHaveBeenCalledWith('capabilities://changed', expect.any(Function));
+    expect(mockInvoke).toHaveBeenCalledWith('load_capabilities');
+    expect(handler).toHaveBeenCalledWith(loaded, diff);
+    expect(loaded.videoEncoders).toEqual(new Set(['libsvtav1']));
+  });
+});
+
+describe('loadEncoderOptions', () => {
+  const mockInvoke = vi.mocked(invoke);
+
+  beforeEach(() => {
+    mockInvoke.mockReset();
+  });
+
+  it('should cache options per encoder', async () => {
+    mockInvoke.mockResolvedValue({ name: 'libx265', presets: [], profiles: ['main'] });
+
+    await loadEncoderOptions('libx265');
+    const result = await loadEncoderOptions('libx265');
+
+    expect(mockInvoke).toHaveBeenCalledTimes(1);
+    expect(mockInvoke).toHaveBeenCalledWith('get_encoder_options', { name: 'libx265' });
+    expect(result.profiles).toEqual(['main']);
+  });
+
+  it('should retry after a failure', async () => {
+    mockInvoke.mockRejectedValueOnce(new Error('unknown encoder'));
+    mockInvoke.mockResolvedValueOnce({ name: 'libsvtav1', presets: [] });
+
+    await expect(loadEncoderOptions('libsvtav1')).rejects.toThrow('unknown encoder');
+    await expect(loadEncoderOptions('libsvtav1')).resolves.toMatchObject({ name: 'libsvtav1' });
+    expect(mockInvoke).toHaveBeenCalledTimes(2);
+  });
+});
+
+describe('validatePreset', () => {
+  const mockInvoke = vi.mocked(invoke);
+
+  beforeEach(() => {
+    mockInvoke.mockReset();
+  });
+
+  it('should pass the planned preset to the backend', async () => {
+    const issue = {
+      field: 'videoEncoder',
+      value: 'libx265',
+      message: 'The libx265 encoder is unavailable in this FFmpeg build.',
+      suggestion: 'hevc_videotoolbox',
+    };
+    mockInvoke.mockResolvedValue({ valid: false, issues: [issue] });
+    const preset = { container: 'mp4', videoEncoder: 'libx265', audioEncoder: 'aac' };
+
+    const result = await validatePreset(preset);
+
+    expect(mockInvoke).toHaveBeenCalledWith('validate_preset', { preset });
+    expect(result.issues[0]?.suggestion).toBe('hevc_videotoolbox');
+  });
+});
@@ -0,0 +1,108 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+import {
+  diagnoseBinaries,
+  getBinaryInfo,
+  listFfmpegInstallations,
+  registerFfmpegInstallation,
+  removeFfmpegInstallation,
+  setFfmpegPath,
+} from '../ffmpeg-binary';
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+vi.mock('../capability', () => ({
+  refreshCapabilities: vi.fn(() => Promise.resolve()),
+}));
+
+import { invoke } from '@tauri-apps/api/core';
+
+import { refreshCapabilities } from '../capability';
+
+describe('ffmpeg-binary', () => {
+  const mockInvoke = vi.mocked(invoke);
+  const info = {
+    preferredPath: '/opt/homebrew/bin/ffmpeg',
+    ffmpeg: { path: '/opt/homebrew/bin/ffmpeg', source: 'preference', version: '7.1.1' },
+    ffprobe: { path: '/opt/homebrew/bin/ffprobe', source: 'preference', version: '7.1.1' },
+  };
+
+  beforeEach(() => {
+    mockInvoke.mockReset();
+    vi.mocked(refreshCapabilities).mockClear();
+  });
+
+  it('reads the resolved binaries', async () => {
+    mockInvoke.mockResolvedValue(info);
+
+    await expect(getBinaryInfo()).resolves.toEqual(info);
+    expect(mockInvoke).toHaveBeenCalledWith('get_binary_info');
+  });
+
+  it('reads the binary diagnostics', async () => {
+    const diagnostics = {
+      ffmpeg: [
+        { source: 'bundled', path: '/app/bin/ffmpeg', status: 'checksumMismatch', selected: false },
+        {
+          source: 'systemPath',
+          path: '/usr/local/bin/ffmpeg',
+          status: 'ok',
+          version: '7.1.1',
+          selected: true,
+        },
+      ],
+      ffprobe: [{ source: 'systemPath', path: 'ffprobe', status: 'missing', selected: true }],
+      searchPath: '/usr/local/bin:/usr/bin',
+    };
+    mockInvoke.mockResolvedValue(diagnostics);
+
+    await expect(diagnoseBinaries()).resolves.toEqual(diagnostics);
+    expect(mockInvoke).toHaveBeenCalledWith('diagnose_binaries');
+  });
+
+  it('stores the path and re-detects capabilities', async () => {
+    mockInvoke.mockResolvedValue(info);
+
+    await expect(setFfmpegPath('/opt/homebrew/bin/ffmpeg')).resolves.toEqual(info);
+    expect(mockInvoke).toHaveBeenCalledWith('set_ffmpeg_path', {
+      path: '/opt/homebrew/bin/ffmpeg',
+    });
+    expect(refreshCapabilities).toHaveBeenCalled();
+  });
+
+  it('leaves capabilities alone when the path is rejected', async () => {
+    mockInvoke.mockRejectedValue(new Error('did not report an FFmpeg version'));
+
+    await expect(setFfmpegPath('/usr/bin/true')).rejects.toThrow('FFmpeg version');
+    expect(refreshCapabilities).not.toHaveBeenCalled();
+  });
+
+  it('manages registered installations', async () => {
+    const nightly = {
+      id: 'b1f0',
+      label: 'Nightly',
+      ffmpegPath: '/opt/ffmpeg-git/bin/ffmpeg',
+      version: 'N-118000',
+    };
+    mockInvoke.mockResolvedValueOnce(nightly);
+    await expect(
+      registerFfmpegInstallation('Nightly', '/opt/ffmpeg-git/bin/ffmpeg'),
+    ).resolves.toEqual(nightly);
+    expect(mockInvoke).toHaveBeenCalledWith('register_ffmpeg_installation', {
+      label: 'Nightly',
+      path: '/opt/ffmpeg-git/bin/ffmpeg',
+    });
+
+    mockInvoke.mockResolvedValueOnce({ preferredId: null, installations: [nightly] });
+    await expect(listFfmpegInstallations()).resolves.toEqual({
+      preferredId: null,
+      installations: [nightly],
+    });
+
+    mockInvoke.mockResolvedValueOnce(undefined);
+    await removeFfmpegInstallation('b1f0');
+    expect(mockInvoke).toHaveBeenLastCalledWith('remove_ffmpeg_installation', { id: 'b1f0' });
+  });
+});
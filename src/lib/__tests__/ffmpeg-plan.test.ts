@@ -52,7 +52,8 @@ describe('ffmpeg-plan', () => {
     expect(decision.remuxOnly).toBe(true);
     expect(decision.ffmpegArgs).toContain('-c:v');
     expect(decision.ffmpegArgs).toContain('copy');
-    expect(decision.ffmpegArgs).toContain('-progress');
+    // The runner adds its own progress reporting.
+    expect(decision.ffmpegArgs).not.toContain('-progress');
   });
 
   it('plans a transcode when target container requires different codecs', () => {
@@ -65,7 +66,21 @@ describe('ffmpeg-plan', () => {
     expect(decision.ffmpegArgs).toContain('libx264');
     expect(decision.ffmpegArgs).toContain('-c:a');
     expect(decision.ffmpegArgs).toContain('aac');
-    expect(decision.ffmpegArgs).toContain('-progress');
+    // The runner adds its own progress reporting.
+    expect(decision.ffmpegArgs).not.toContain('-progress');
+  });
+
+  it('warns when a transcoded stream has no decoder', () => {
+    const context: PlannerContext = {
+      presetId: 'video-to-mp4',
+      summary: { ...baseSummary, vcodec: 'prores_raw', acodec: 'opus' },
+      capabilities: { ...emptyCapabilities, decodableCodecs: new Set(['h264', 'opus']) },
+    };
+    const decision = planJob(context);
+    expect(decision.warnings).toContain(
+      "This file's prores_raw video can't be decoded by your FFmpeg.",
+    );
+    expect(decision.warnings.some((warning) => warning.includes('opus audio'))).toBe(false);
   });
 
   it('builds the GIF pipeline for gif outputs', () => {
@@ -91,7 +106,8 @@ describe('ffmpeg-plan', () => {
     const decision = planJob(context);
     expect(decision.ffmpegArgs).toContain('-c:a');
     expect(decision.ffmpegArgs).toContain('libmp3lame');
-    expect(decision.ffmpegArgs).toContain('-progress');
+    // The runner adds its own progress reporting.
+    expect(decision.ffmpegArgs).not.toContain('-progress');
   });
 
   it('falls back to default preset when unknown id is supplied', () => {
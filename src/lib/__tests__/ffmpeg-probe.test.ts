@@ -1,10 +1,16 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
-import { probeMedia, type ProbeResponse } from '../ffmpeg-probe';
+import {
+  generateContactSheet,
+  generateWaveform,
+  probeMedia,
+  type ProbeResponse,
+} from '../ffmpeg-probe';
 import type { ProbeSummary } from '../types';
 
 // Mock the Tauri invoke function
 vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
+  convertFileSrc: (path: string) => `asset://localhost${path}`,
 }));
 
 import { invoke } from '@tauri-apps/api/core';
@@ -173,4 +179,39 @@ describe('ffmpeg-probe', () => {
       expect(result).toEqual(mockProbeResponse);
     });
   });
+
+  describe('generateContactSheet', () => {
+    it('sends the normalized path with the grid options', async () => {
+      mockInvoke.mockResolvedValue({ dataUrl: 'data:image/jpeg;base64,', columns: 3, rows: 2 });
+
+      await generateContactSheet('file:///media/clip%20one.mov', { columns: 3, rows: 2 });
+
+      expect(mockInvoke).toHaveBeenCalledWith('generate_contact_sheet', {
+        request: { path: '/media/clip one.mov', columns: 3, rows: 2 },
+      });
+    });
+
+    it('rejects empty paths', async () => {
+      await expect(generateContactSheet('  ')).rejects.toThrow('missing or empty');
+      expect(mockInvoke).not.toHaveBeenCalled();
+    });
+  });
+
+  describe('generateWaveform', () => {
+    it('returns a loadable URL for the cached image', async () => {
+      mockInvoke.mockResolvedValue({
+        imagePath: '/cache/waveforms/abc.png',
+        width: 1200,
+        height: 240,
+        cached: false,
+      });
+
+      const waveform = await generateWaveform('/music/song.flac', { color: '#ffffff' });
+
+      expect(mockInvoke).toHaveBeenCalledWith('generate_waveform', {
+        request: { path: '/music/song.flac', color: '#ffffff' },
+      });
+      expect(waveform.src).toBe('asset://localhost/cache/waveforms/abc.png');
+    });
+  });
 });
@@ -0,0 +1,50 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { analyzeLoudness, loudnormFilter, type LoudnessMeasurement } from '../loudness';
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+import { invoke } from '@tauri-apps/api/core';
+
+describe('loudness', () => {
+  const mockInvoke = vi.mocked(invoke);
+
+  const measurement: LoudnessMeasurement = {
+    path: '/music/track.flac',
+    integratedLufs: -16.5,
+    truePeakDbtp: 0.4,
+    loudnessRangeLu: 6.3,
+    thresholdLufs: -26.8,
+  };
+
+  beforeEach(() => {
+    mockInvoke.mockReset();
+  });
+
+  it('invokes the analysis command', async () => {
+    mockInvoke.mockResolvedValue(measurement);
+
+    await expect(analyzeLoudness('/music/track.flac')).resolves.toEqual(measurement);
+    expect(mockInvoke).toHaveBeenCalledWith('analyze_loudness', { path: '/music/track.flac' });
+  });
+
+  it('builds a linear second-pass filter from the measurement', () => {
+    expect(loudnormFilter(measurement, { integratedLufs: -16 })).toBe(
+      'loudnorm=I=-16:TP=-1:LRA=7:measured_I=-16.5:measured_TP=0.4:' +
+        'measured_LRA=6.3:measured_thresh=-26.8:linear=true',
+    );
+  });
+
+  it('keeps the measured range and treats silence as the floor', () => {
+    const filter = loudnormFilter({
+      ...measurement,
+      integratedLufs: null,
+      truePeakDbtp: null,
+      loudnessRangeLu: 12,
+    });
+    expect(filter).toContain('LRA=12');
+    expect(filter).toContain('measured_I=-99');
+    expect(filter).toContain('measured_TP=-99');
+  });
+});
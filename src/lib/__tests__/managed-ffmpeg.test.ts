@@ -0,0 +1,63 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+import { installFfmpeg } from '../managed-ffmpeg';
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(),
+}));
+
+vi.mock('../capability', () => ({
+  refreshCapabilities: vi.fn(() => Promise.resolve()),
+}));
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+import { refreshCapabilities } from '../capability';
+
+describe('installFfmpeg', () => {
+  const mockInvoke = vi.mocked(invoke);
+  const unlisten = vi.fn();
+
+  beforeEach(() => {
+    mockInvoke.mockReset();
+    unlisten.mockReset();
+  });
+
+  it('forwards progress and refreshes capabilities once installed', async () => {
+    const install = {
+      version: '7.1.1',
+      ffmpegPath: '/data/ffmpeg',
+      ffprobePath: '/data/ffprobe',
+      ffmpegSha256: 'a'.repeat(64),
+      ffprobeSha256: 'b'.repeat(64),
+    };
+    const progress = { binary: 'ffmpeg', downloadedBytes: 1024, totalBytes: 4096 };
+    vi.mocked(listen).mockImplementation(async (_event, callback) => {
+      (callback as (event: { payload: unknown }) => void)({ payload: progress });
+      return unlisten;
+    });
+    mockInvoke.mockResolvedValue(install);
+    const onProgress = vi.fn();
+
+    await expect(installFfmpeg(onProgress)).resolves.toEqual(install);
+
+    expect(listen).toHaveBeenCalledWith('ffmpeg://download-progress', expect.any(Function));
+    expect(onProgress).toHaveBeenCalledWith(progress);
+    expect(mockInvoke).toHaveBeenCalledWith('install_ffmpeg');
+    expect(refreshCapabilities).toHaveBeenCalled();
+    expect(unlisten).toHaveBeenCalled();
+  });
+
+  it('stops listening when the install fails', async () => {
+    vi.mocked(listen).mockResolvedValue(unlisten);
+    mockInvoke.mockRejectedValue(new Error('checksum mismatch'));
+
+    await expect(installFfmpeg(() => {})).rejects.toThrow('checksum mismatch');
+    expect(unlisten).toHaveBeenCalled();
+  });
+});
@@ -6,8 +6,10 @@ import {
   stripExtension,
   joinPath,
   formatFileSize,
+  formatBitrate,
   formatDuration,
   getFileExtension,
+  displayPath,
 } from '../utils';
 
 describe('utils', () => {
@@ -97,6 +99,18 @@ describe('utils', () => {
     });
   });
 
+  describe('displayPath', () => {
+    it('should pass text paths through', () => {
+      expect(displayPath('/out/video.mp4')).toBe('/out/video.mp4');
+    });
+
+    it('should decode raw paths lossily', () => {
+      expect(displayPath({ bytes: [0x2f, 0x61, 0xff, 0x2e, 0x6d, 0x70, 0x34] })).toBe(
+        '/a\uFFFD.mp4',
+      );
+    });
+  });
+
   describe('stripExtension', () => {
     it('should remove file extension', () => {
       expect(stripExtension('/Users/test/file.txt')).toBe('/Users/test/file');
@@ -231,6 +245,17 @@ describe('utils', () => {
     });
   });
 
+  describe('formatBitrate', () => {
+    it('should format megabits with one decimal', () => {
+      expect(formatBitrate(8_200_000)).toBe('8.2 Mbps');
+      expect(formatBitrate(1_000_000)).toBe('1 Mbps');
+    });
+
+    it('should format lower rates in kilobits', () => {
+      expect(formatBitrate(192_000)).toBe('192 kbps');
+    });
+  });
+
   describe('formatDuration', () => {
     it('should format seconds', () => {
       expect(formatDuration(0)).toBe('0s');
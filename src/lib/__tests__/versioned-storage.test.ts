@@ -0,0 +1,82 @@
+import { describe, it, expect, beforeEach, vi } from 'vitest';
+
+import { loadVersioned, saveVersioned, type StorageSchema } from '../versioned-storage';
+
+function createStorageMock(): Storage {
+  let store: Record<string, string> = {};
+  return {
+    getItem: (key: string) => (key in store ? store[key] : null),
+    setItem: (key: string, value: string) => {
+      store[key] = value;
+    },
+    removeItem: (key: string) => {
+      delete store[key];
+    },
+    clear: () => {
+      store = {};
+    },
+    key: (index: number) => Object.keys(store)[index] ?? null,
+    get length() {
+      return Object.keys(store).length;
+    },
+  } as Storage;
+}
+
+const V1: StorageSchema = { key: 'settings', migrations: [] };
+
+const V2: StorageSchema = {
+  key: 'settings',
+  migrations: [
+    (payload) => {
+      const { title, ...rest } = payload as { title?: string };
+      return { ...rest, name: title };
+    },
+  ],
+};
+
+describe('versioned-storage', () => {
+  beforeEach(() => {
+    vi.stubGlobal('localStorage', createStorageMock());
+  });
+
+  it('wraps saved data with the schema version', () => {
+    saveVersioned(V1, { name: 'demo' });
+
+    expect(JSON.parse(localStorage.getItem('settings') ?? '')).toEqual({
+      schemaVersion: 1,
+      data: { name: 'demo' },
+    });
+    expect(loadVersioned(V1)).toEqual({ name: 'demo' });
+  });
+
+  it('backs up and envelopes legacy values', () => {
+    localStorage.setItem('settings', '{"name":"legacy"}');
+
+    expect(loadVersioned(V1)).toEqual({ name: 'legacy' });
+    expect(localStorage.getItem('settings.v1.bak')).toBe('{"name":"legacy"}');
+    expect(JSON.parse(localStorage.getItem('settings') ?? '').schemaVersion).toBe(1);
+  });
+
+  it('runs pending migrations', () => {
+    localStorage.setItem('settings', '{"schemaVersion":1,"data":{"title":"old"}}');
+
+    expect(loadVersioned(V2)).toEqual({ name: 'old' });
+    expect(localStorage.getItem('settings.v1.bak')).not.toBeNull();
+    expect(JSON.parse(localStorage.getItem('settings') ?? '').schemaVersion).toBe(2);
+  });
+
+  it('reads newer values leniently and backs them up on save', () => {
+    localStorage.setItem('settings', '{"schemaVersion":7,"data":{"name":"future"}}');
+
+    expect(loadVersioned(V1)).toEqual({ name: 'future' });
+    expect(localStorage.getItem('settings.v7.bak')).toBeNull();
+
+    saveVersioned(V1, { name: 'future' });
+    expect(localStorage.getItem('settings.v7.bak')).not.toBeNull();
+  });
+
+  it('treats unreadable values as missing', () => {
+    localStorage.setItem('settings', '{not json');
+    expect(loadVersioned(V1)).toBeNull();
+  });
+});
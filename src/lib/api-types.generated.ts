@@ -0,0 +1,113 @@
+// This file is generated from src-tauri/src/api. Do not edit it by hand;
+// run `UPDATE_API_BINDINGS=1 cargo test api::` in src-tauri instead.
+
+export type JobSpec = { jobId: string, 
+/**
+ * Raw ffmpeg arguments; empty when `command` is given instead.
+ */
+args?: Array<string>, 
+/**
+ * Structured alternative to `args`.
+ */
+command?: FfmpegCommand | null, outputPath: NativePath, exclusive?: boolean, 
+/**
+ * Run ffmpeg at background priority; unset follows the global
+ * preference.
+ */
+lowPriority?: boolean | null, 
+/**
+ * Jobs that must succeed before this one starts; if one fails, this
+ * job completes as `job_skipped`.
+ */
+dependsOn?: Array<string>, 
+/**
+ * What to do when the output file already exists; unset overwrites it.
+ */
+overwrite?: OverwritePolicy | null, 
+/**
+ * Source duration from the probe. With the plan's target bitrates it
+ * sizes the free-space check made before ffmpeg starts.
+ */
+durationSec?: number | null, 
+/**
+ * Encode long sources as keyframe-aligned segments in parallel
+ * ffmpeg processes; sources that don't qualify run as usual.
+ */
+segmented?: boolean, 
+/**
+ * Most threads the encoder may use, leaving the rest of the machine
+ * to other work; unset follows the global default.
+ */
+threads?: number | null, 
+/**
+ * Move the source to the trash once the output is finalized and
+ * verified against it.
+ */
+trashSource?: boolean, 
+/**
+ * Give the output the source's timestamps (and Finder tags on macOS).
+ */
+preserveTimestamps?: boolean, 
+/**
+ * Echoed back in the job's progress and completion events.
+ */
+metadata?: { [key in string]?: string }, 
+/**
+ * Id of the registered FFmpeg installation to run; unset uses the
+ * preferred FFmpeg.
+ */
+ffmpegInstallation?: string | null, 
+/**
+ * The plan that produced `args`, reported back in failure telemetry.
+ */
+plan?: PlanRequest | null, };
+
+export type FfmpegCommand = { inputs: Array<CommandInput>, 
+/**
+ * Stream selections such as `0:v:0?` or `-0:s`.
+ */
+maps?: Array<string>, codecs?: Array<StreamCodec>, filters?: Array<StreamFilter>, metadata?: Array<MetadataEntry>, 
+/**
+ * Other output options, e.g. `crf` with value `23`.
+ */
+options?: Array<CommandOption>, 
+/**
+ * Output muxer.
+ */
+format?: string | null, };
+
+export type CommandInput = { path: NativePath, 
+/**
+ * Options applying to this input, such as `ss`.
+ */
+options?: Array<CommandOption>, };
+
+export type StreamCodec = { stream: string, codec: string, };
+
+export type StreamFilter = { stream: string, graph: string, };
+
+export type MetadataEntry = { stream?: string | null, key: string, value: string, };
+
+export type CommandOption = { name: string, value?: string | null, };
+
+export type OverwritePolicy = "overwrite" | "skip" | "rename";
+
+export type PlanRequest = { preset: Preset, tier?: string | null, };
+
+export type Preset = { id: string, label: string, container: string, mediaKind: string, sourceContainers: Array<string>, description?: string | null, video: PresetVideo, audio: PresetAudio, subs?: PresetSubs | null, remuxOnly?: boolean | null, experimental?: boolean | null, outputExtension?: string | null, tags?: Array<string> | null, };
+
+export type PresetVideo = { codec: string, 
+/**
+ * Overrides keyed by tier name.
+ */
+tiers?: { [key in string]?: VideoTier } | null, copyColorMetadata?: boolean | null, };
+
+export type PresetAudio = { codec: string, bitrateK?: number | null, tiers?: { [key in string]?: AudioTier } | null, stereoOnly?: boolean | null, };
+
+export type PresetSubs = { mode: string, burnInAvailable?: boolean | null, notes?: string | null, };
+
+export type VideoTier = { bitrateK?: number | null, maxrateK?: number | null, bufsizeK?: number | null, crf?: number | null, profile?: string | null, };
+
+export type AudioTier = { bitrateK?: number | null, quality?: number | null, };
+
+export type NativePath = string | { bytes: Array<number>, };
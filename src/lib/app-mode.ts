@@ -0,0 +1,16 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** Run mode selected at launch; see `app_mode.rs` */
+export interface AppMode {
+  /** Set by `--read-only` or `HONEYMELON_READ_ONLY`: nothing may be written */
+  readOnly: boolean;
+}
+
+/** Reads the run mode, treating a failed call as the normal mode. */
+export async function loadAppMode(): Promise<AppMode> {
+  try {
+    return await invoke<AppMode>('app_mode');
+  } catch {
+    return { readOnly: false };
+  }
+}
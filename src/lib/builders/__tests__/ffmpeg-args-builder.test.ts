@@ -212,6 +212,45 @@ describe('FFmpegArgsBuilder', () => {
       expect(result.notes).toContain('Video dropped');
     });
 
+    it('should keep cover art when dropping video for an audio container', () => {
+      const builder = new FFmpegArgsBuilder();
+      const videoAction: VideoAction = {
+        action: 'drop',
+        encoder: null,
+        note: 'Video dropped',
+      };
+      const tierResult: VideoTierResult<TierDefaults['video']> = {
+        tier: 'balanced',
+        usedFallback: false,
+        value: undefined,
+      };
+      const summary: ProbeSummary = {
+        durationSec: 180,
+        acodec: 'flac',
+        hasCoverArt: true,
+        streams: [
+          { index: 0, type: 'audio', codec: 'flac', default: true, forced: false },
+          {
+            index: 1,
+            type: 'video',
+            codec: 'mjpeg',
+            default: false,
+            forced: false,
+            coverArt: true,
+          },
+        ],
+      };
+      const preset: Preset = { ...basePreset, container: 'mp3', mediaKind: 'audio' };
+
+      const result = builder.withVideo(videoAction, tierResult, preset, summary).build();
+
+      expect(result.ffmpegArgs).not.toContain('-vn');
+      expect(result.ffmpegArgs.join(' ')).toContain(
+        '-map 0:1 -c:v copy -disposition:v:0 attached_pic',
+      );
+      expect(result.notes).toContain('Video: cover art preserved.');
+    });
+
     it('should copy video codec', () => {
       const builder = new FFmpegArgsBuilder();
       const videoAction: VideoAction = {
@@ -228,7 +267,7 @@ describe('FFmpegArgsBuilder', () => {
       const result = builder.withVideo(videoAction, tierResult, basePreset).build();
 
       expect(result.ffmpegArgs).toContain('-map');
-      expect(result.ffmpegArgs).toContain('0:v:0?');
+      expect(result.ffmpegArgs).toContain('0:V:0?');
       expect(result.ffmpegArgs).toContain('-c:v');
       expect(result.ffmpegArgs).toContain('copy');
       expect(result.notes).toContain('Video copied');
@@ -348,6 +387,59 @@ describe('FFmpegArgsBuilder', () => {
       expect(result.ffmpegArgs).not.toContain('-colorspace');
     });
 
+    it('should reduce 10-bit sources to 8-bit for H.264', () => {
+      const builder = new FFmpegArgsBuilder();
+      const videoAction: VideoAction = {
+        action: 'transcode',
+        encoder: 'h264_videotoolbox',
+        note: 'Video transcoded',
+      };
+      const tierResult: VideoTierResult<TierDefaults['video']> = {
+        tier: 'balanced',
+        usedFallback: false,
+      };
+      const summary: ProbeSummary = {
+        durationSec: 120,
+        vcodec: 'hevc',
+        pixFmt: 'yuv420p10le',
+        bitDepth: 10,
+      };
+
+      const result = builder.withVideo(videoAction, tierResult, basePreset, summary).build();
+
+      expect(result.ffmpegArgs).toEqual(
+        expect.arrayContaining(['-c:v', 'h264_videotoolbox', '-pix_fmt', 'yuv420p']),
+      );
+      expect(result.notes).toContain('Video: 10-bit source reduced to 8-bit for H.264.');
+    });
+
+    it('should keep 10-bit sources at 10 bits for HEVC', () => {
+      const builder = new FFmpegArgsBuilder();
+      const videoAction: VideoAction = {
+        action: 'transcode',
+        encoder: 'hevc_videotoolbox',
+        note: 'Video transcoded',
+      };
+      const tierResult: VideoTierResult<TierDefaults['video']> = {
+        tier: 'balanced',
+        usedFallback: false,
+        value: { profile: 'main' },
+      };
+      const preset: Preset = { ...basePreset, video: { codec: 'hevc' } };
+      const summary: ProbeSummary = { durationSec: 120, vcodec: 'prores', bitDepth: 10 };
+
+      const args = builder.withVideo(videoAction, tierResult, preset, summary).build().ffmpegArgs;
+
+      expect(args[args.indexOf('-profile:v') + 1]).toBe('main10');
+      expect(args[args.indexOf('-pix_fmt') + 1]).toBe('p010le');
+
+      const p010Source: ProbeSummary = { ...summary, pixFmt: 'p010le' };
+      const direct = new FFmpegArgsBuilder()
+        .withVideo(videoAction, tierResult, preset, p010Source)
+        .build().ffmpegArgs;
+      expect(direct).not.toContain('-pix_fmt');
+    });
+
     it('should handle ProRes profile normalization', () => {
       const builder = new FFmpegArgsBuilder();
       const videoAction: VideoAction = {
@@ -571,6 +663,19 @@ describe('FFmpegArgsBuilder', () => {
       expect(result.ffmpegArgs).toContain('-0:s:m:codec:dvb_subtitle?');
       expect(result.ffmpegArgs).toContain('-0:s:m:codec:xsub?');
     });
+
+    it('should map attachments when fonts are kept', () => {
+      const builder = new FFmpegArgsBuilder();
+      const plan: SubtitlePlanDecision = {
+        mode: 'copy',
+        note: 'Subtitles copied',
+        keepAttachments: true,
+      };
+
+      const result = builder.withSubtitles(plan).build();
+
+      expect(result.ffmpegArgs.join(' ')).toContain('-map 0:t? -c:t copy');
+    });
   });
 
   describe('withContainer', () => {
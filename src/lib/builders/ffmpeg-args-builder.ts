@@ -16,6 +16,9 @@ const CONTAINER_TO_MUXER: Partial<Record<Container, string>> = {
   wav: 'wav',
 };
 
+/** Audio containers that store cover art as an attached picture */
+const COVER_ART_CONTAINERS: Container[] = ['mp3', 'm4a', 'flac'];
+
 function muxerForContainer(container: Container): string | undefined {
   return CONTAINER_TO_MUXER[container];
 }
@@ -68,10 +71,21 @@ export class FFmpegArgsBuilder {
     preset: Preset,
     summary?: ProbeSummary,
   ): this {
-    if (videoAction.action === 'drop') {
+    const coverArt = summary?.streams?.find((stream) => stream.coverArt);
+    if (
+      videoAction.action === 'drop' &&
+      coverArt &&
+      COVER_ART_CONTAINERS.includes(preset.container)
+    ) {
+      this.args.push('-map', `0:${coverArt.index}`, '-c:v', 'copy');
+      this.args.push('-disposition:v:0', 'attached_pic');
+      this.notes.push('Video: cover art preserved.');
+    } else if (videoAction.action === 'drop') {
       this.args.push('-vn');
     } else {
-      this.args.push('-map', '0:v:0?');
+      const sourceBitDepth = summary?.bitDepth ?? 8;
+      // `V` skips attached pictures, so cover art is never taken for the video.
+      this.args.push('-map', '0:V:0?');
       if (videoAction.encoder) {
         this.args.push('-c:v', videoAction.encoder);
       }
@@ -90,13 +104,32 @@ export class FFmpegArgsBuilder {
           this.args.push('-crf', tierDefaults.crf.toString());
         }
         if (tierDefaults.profile) {
-          const profile = resolveVideoProfile(preset.video.codec, tierDefaults.profile);
+          let profile = resolveVideoProfile(preset.video.codec, tierDefaults.profile);
+          // HEVC Main is 8-bit only; keep deeper sources at 10 bits.
+          if (preset.video.codec === 'hevc' && profile === 'main' && sourceBitDepth > 8) {
+            profile = 'main10';
+          }
           if (profile) {
             this.args.push('-profile:v', profile);
           }
         }
         this.notes.push(`Video tier ${videoTier.tier} applied.`);
       }
+      if (videoAction.action === 'transcode' && sourceBitDepth > 8) {
+        if (preset.video.codec === 'h264') {
+          // High 10 H.264 is rejected by hardware encoders and 8-bit libx264 builds.
+          this.args.push('-pix_fmt', 'yuv420p');
+          this.notes.push(`Video: ${sourceBitDepth}-bit source reduced to 8-bit for H.264.`);
+        } else if (
+          preset.video.codec === 'hevc' &&
+          videoAction.encoder?.includes('videotoolbox') &&
+          summary?.pixFmt !== 'p010le'
+        ) {
+          // VideoToolbox falls back to 8-bit unless handed a 10-bit surface.
+          this.args.push('-pix_fmt', 'p010le');
+          this.notes.push(`Video: ${sourceBitDepth}-bit source encoded as 10-bit HEVC.`);
+        }
+      }
       if (videoAction.action === 'transcode' && preset.video.copyColorMetadata && summary?.color) {
         const { primaries, trc, space } = summary.color;
         if (primaries) {
@@ -154,7 +187,7 @@ export class FFmpegArgsBuilder {
   }
 
   withSubtitles(plan: SubtitlePlanDecision): this {
-    const { mode, note, excludeImageStreams } = plan;
+    const { mode, note, excludeImageStreams, keepAttachments } = plan;
 
     if (mode === 'drop') {
       this.args.push('-sn');
@@ -175,6 +208,9 @@ export class FFmpegArgsBuilder {
           this.args.push('-map', `-0:s:m:codec:${codec}?`);
         }
       }
+      if (keepAttachments) {
+        this.args.push('-map', '0:t?', '-c:t', 'copy');
+      }
     }
 
     this.notes.push(note);
@@ -11,9 +11,10 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 import { PRESETS } from './presets';
-import type { CapabilitySnapshot, Preset } from './types';
+import type { CapabilitySnapshot, FfmpegVersion, Preset } from './types';
 
 /**
  * Empty capability snapshot for fallback scenarios.
@@ -49,6 +50,16 @@ interface RawCapabilitySnapshot {
   formats: string[];
   /** Array of available filter names */
   filters: string[];
+  /** Array of pixel formats FFmpeg can convert to */
+  pixelFormats?: string[];
+  /** Array of codec names some decoder handles */
+  decodableCodecs?: string[];
+  /** Array of hardware decoding methods */
+  hwaccels?: string[];
+  /** Array of bitstream filter names */
+  bitstreamFilters?: string[];
+  /** Parsed `ffmpeg -version` output */
+  ffmpeg?: FfmpegVersion | null;
 }
 
 /**
@@ -82,7 +93,9 @@ let capabilityPromise: Promise<CapabilitySnapshot> | undefined;
  *
  * @returns Promise resolving to capability snapshot
  */
-async function fetchCapabilities(): Promise<CapabilitySnapshot> {
+async function fetchCapabilities(
+  command: 'load_capabilities' | 'refresh_capabilities' = 'load_capabilities',
+): Promise<CapabilitySnapshot> {
   if (import.meta.env.VITE_E2E_SIMULATION === 'true') {
     return DEFAULT_E2E_CAPABILITIES;
   }
@@ -93,12 +106,17 @@ async function fetchCapabilities(): Promise<CapabilitySnapshot> {
   }
 
   try {
-    const raw = await invoke<RawCapabilitySnapshot>('load_capabilities');
+    const raw = await invoke<RawCapabilitySnapshot>(command);
     return {
       videoEncoders: new Set(raw.videoEncoders ?? []),
       audioEncoders: new Set(raw.audioEncoders ?? []),
       formats: new Set(raw.formats ?? []),
       filters: new Set(raw.filters ?? []),
+      pixelFormats: new Set(raw.pixelFormats ?? []),
+      decodableCodecs: new Set(raw.decodableCodecs ?? []),
+      hwaccels: new Set(raw.hwaccels ?? []),
+      bitstreamFilters: new Set(raw.bitstreamFilters ?? []),
+      ffmpeg: raw.ffmpeg ?? null,
     };
   } catch (error) {
     console.error('[capability] Failed to read capabilities:', error);
@@ -121,6 +139,199 @@ export function loadCapabilities(): Promise<CapabilitySnapshot> {
   return capabilityPromise;
 }
 
+/**
+ * Re-detects FFmpeg capabilities, bypassing the backend cache.
+ *
+ * The backend already re-detects when the FFmpeg binary changes on disk; this
+ * covers changes it cannot see. Later `loadCapabilities` calls get the result.
+ *
+ * @returns Promise resolving to the fresh capability snapshot
+ */
+export function refreshCapabilities(): Promise<CapabilitySnapshot> {
+  capabilityPromise = fetchCapabilities('refresh_capabilities');
+  return capabilityPromise;
+}
+
+const CAPABILITIES_CHANGED_EVENT = 'capabilities://changed';
+
+/** Names added to and removed from one capability list */
+export interface CapabilityListDiff {
+  added: string[];
+  removed: string[];
+}
+
+/** Payload of `capabilities://changed`: what a backend refresh changed */
+export interface CapabilityDiff {
+  videoEncoders: CapabilityListDiff;
+  audioEncoders: CapabilityListDiff;
+  formats: CapabilityListDiff;
+  filters: CapabilityListDiff;
+  pixelFormats: CapabilityListDiff;
+  decodableCodecs: CapabilityListDiff;
+  hwaccels: CapabilityListDiff;
+  bitstreamFilters: CapabilityListDiff;
+  /** Versions before and after, set only when the version changed */
+  previousVersion?: string | null;
+  version?: string | null;
+}
+
+/**
+ * Subscribes to capability changes, such as encoders gained or lost after an
+ * FFmpeg upgrade.
+ *
+ * On each change the cached snapshot is reloaded, so later `loadCapabilities`
+ * calls see it too, and `handler` receives the new snapshot with the diff.
+ *
+ * @param handler - Called with the new snapshot and what changed
+ * @returns Promise resolving to a function that stops listening
+ */
+export async function onCapabilitiesChanged(
+  handler: (capabilities: CapabilitySnapshot, diff: CapabilityDiff) => void,
+): Promise<UnlistenFn> {
+  if (!isTauriRuntime()) {
+    return () => {};
+  }
+  return await listen<CapabilityDiff>(CAPABILITIES_CHANGED_EVENT, (event) => {
+    capabilityPromise = fetchCapabilities();
+    void capabilityPromise.then((capabilities) => handler(capabilities, event.payload));
+  });
+}
+
+/**
+ * Checks whether FFmpeg can convert frames to a pixel format.
+ *
+ * Unknown capabilities count as support, so planning isn't blocked when the
+ * snapshot is missing or predates pixel format detection.
+ *
+ * @param capabilities - System capabilities, if loaded
+ * @param pixelFormat - Pixel format name, e.g. 'yuv420p10le'
+ * @returns false only when the format is known to be unsupported
+ */
+export function supportsPixelFormat(
+  capabilities: CapabilitySnapshot | undefined,
+  pixelFormat: string,
+): boolean {
+  const formats = capabilities?.pixelFormats;
+  return !formats || formats.size === 0 || formats.has(pixelFormat);
+}
+
+/**
+ * Checks whether FFmpeg has a decoder for a codec, as named by ffprobe.
+ *
+ * Like `supportsPixelFormat`, unknown capabilities count as support.
+ *
+ * @param capabilities - System capabilities, if loaded
+ * @param codec - Codec name from a probe summary, e.g. 'av1'
+ * @returns false only when no decoder is known to handle the codec
+ */
+export function canDecode(capabilities: CapabilitySnapshot | undefined, codec: string): boolean {
+  const codecs = capabilities?.decodableCodecs;
+  return !codecs || codecs.size === 0 || codecs.has(codec.toLowerCase());
+}
+
+/**
+ * Checks whether FFmpeg has a bitstream filter, such as 'hevc_mp4toannexb'
+ * for remuxing HEVC from MP4 into MPEG-TS.
+ *
+ * Like `supportsPixelFormat`, unknown capabilities count as support.
+ *
+ * @param capabilities - System capabilities, if loaded
+ * @param name - Bitstream filter name
+ * @returns false only when the filter is known to be missing
+ */
+export function hasBitstreamFilter(
+  capabilities: CapabilitySnapshot | undefined,
+  name: string,
+): boolean {
+  const filters = capabilities?.bitstreamFilters;
+  return !filters || filters.size === 0 || filters.has(name);
+}
+
+/**
+ * Settings one encoder accepts, as reported by `ffmpeg -h encoder=NAME`.
+ *
+ * `presets`, `profiles` and `tunes` are empty when the encoder takes free-form
+ * values (libx264's preset, for one); `options` shows whether the option exists.
+ */
+export interface EncoderOptions {
+  name: string;
+  description?: string | null;
+  presets: string[];
+  profiles: string[];
+  tunes: string[];
+  pixelFormats: string[];
+  sampleFormats: string[];
+  options: EncoderOption[];
+}
+
+/** One private option of an encoder */
+export interface EncoderOption {
+  name: string;
+  /** Value type as printed by FFmpeg ('int', 'string', 'boolean', ...) */
+  valueType: string;
+  description: string;
+  default?: string | null;
+  /** Named values the option accepts */
+  values: string[];
+}
+
+const encoderOptionPromises = new Map<string, Promise<EncoderOptions>>();
+
+/**
+ * Loads the presets, profiles, tunes and pixel formats of one encoder.
+ *
+ * Results are cached per encoder for the session; failures are not cached,
+ * so a later call retries.
+ *
+ * @param name - Encoder name, e.g. 'libx265' or 'hevc_videotoolbox'
+ * @returns Promise resolving to the encoder's options
+ */
+export function loadEncoderOptions(name: string): Promise<EncoderOptions> {
+  let promise = encoderOptionPromises.get(name);
+  if (!promise) {
+    promise = invoke<EncoderOptions>('get_encoder_options', { name });
+    promise.catch(() => encoderOptionPromises.delete(name));
+    encoderOptionPromises.set(name, promise);
+  }
+  return promise;
+}
+
+/** Container, encoders and filters of a planned preset, by FFmpeg name */
+export interface PlannedPreset {
+  container: string;
+  /** Encoder passed to `-c:v`, e.g. 'libx265'; omit or 'copy' when video isn't encoded */
+  videoEncoder?: string | null;
+  /** Encoder passed to `-c:a`, e.g. 'libopus' */
+  audioEncoder?: string | null;
+  /** Names of the filters the plan uses, e.g. 'zscale' */
+  filters?: string[];
+}
+
+/** One part of a planned preset the detected FFmpeg can't provide */
+export interface PresetIssue {
+  field: 'container' | 'videoEncoder' | 'audioEncoder' | 'filters';
+  /** The unavailable container, encoder or filter */
+  value: string;
+  message: string;
+  /** An available substitute, e.g. 'hevc_videotoolbox' for 'libx265' */
+  suggestion?: string | null;
+}
+
+export interface PresetValidation {
+  valid: boolean;
+  issues: PresetIssue[];
+}
+
+/**
+ * Checks a planned preset against the detected FFmpeg capabilities.
+ *
+ * @param preset - Container, encoders and filters the plan needs
+ * @returns Promise resolving to the issues found, each with a substitute when one exists
+ */
+export function validatePreset(preset: PlannedPreset): Promise<PresetValidation> {
+  return invoke<PresetValidation>('validate_preset', { preset });
+}
+
 /**
  * Checks if a preset is available based on system capabilities.
  *
@@ -57,4 +57,10 @@ export const EVENTS = {
 
   /** Raw stderr line event from FFmpeg */
   FFMPEG_STDERR: 'ffmpeg://stderr',
+
+  /** Queue position update for jobs waiting on a free slot */
+  FFMPEG_QUEUE: 'ffmpeg://queue',
+
+  /** A failed job is about to be retried */
+  FFMPEG_RETRYING: 'ffmpeg://retrying',
 } as const;
@@ -0,0 +1,123 @@
+import { invoke } from '@tauri-apps/api/core';
+
+import { refreshCapabilities } from './capability';
+
+/** Where a resolved binary came from, in the resolver's priority order */
+export type BinarySource =
+  | 'preference'
+  | 'environment'
+  | 'development'
+  | 'bundled'
+  | 'managed'
+  | 'systemPath';
+
+/** The binary the resolver currently picks for one tool */
+export interface ResolvedBinary {
+  /** Path, or the bare name when it is looked up on the PATH */
+  path: string;
+  source: BinarySource;
+  /** Version from `-version`; `null` when the binary doesn't run */
+  version?: string | null;
+}
+
+/** Which FFmpeg and FFprobe are in use, and why */
+export interface BinaryInfo {
+  /** Path chosen with `setFfmpegPath`, if any */
+  preferredPath?: string | null;
+  ffmpeg: ResolvedBinary;
+  ffprobe: ResolvedBinary;
+}
+
+/** Why a candidate path is used or skipped */
+export type CandidateStatus =
+  | 'missing'
+  | 'notExecutable'
+  | 'checksumMismatch'
+  | 'noVersion'
+  | 'ok';
+
+/** One path the resolver checks and what was found there */
+export interface CandidateDiagnosis {
+  source: BinarySource;
+  /** For the PATH tier, where the lookup found the binary, or the bare name */
+  path: string;
+  status: CandidateStatus;
+  version?: string | null;
+  /** Whether this is the candidate the resolver picks */
+  selected: boolean;
+}
+
+/** Every candidate for both tools, in the resolver's priority order */
+export interface BinaryDiagnostics {
+  ffmpeg: CandidateDiagnosis[];
+  ffprobe: CandidateDiagnosis[];
+  /** The PATH searched for the last tier */
+  searchPath?: string | null;
+}
+
+/** An FFmpeg build registered so jobs can select it by id */
+export interface FfmpegInstallation {
+  id: string;
+  label: string;
+  ffmpegPath: string;
+  /** Version reported when the build was registered */
+  version: string;
+}
+
+/** The registered builds and which of them is the preferred FFmpeg */
+export interface FfmpegInstallations {
+  /** Id of the build whose path was chosen with `setFfmpegPath` */
+  preferredId?: string | null;
+  installations: FfmpegInstallation[];
+}
+
+/** Reports the FFmpeg and FFprobe in use, where they were found and their versions. */
+export async function getBinaryInfo(): Promise<BinaryInfo> {
+  return await invoke<BinaryInfo>('get_binary_info');
+}
+
+/**
+ * Checks every path searched for FFmpeg and FFprobe, for working out why a
+ * binary isn't found. Runs each usable candidate, so it takes a moment.
+ */
+export async function diagnoseBinaries(): Promise<BinaryDiagnostics> {
+  return await invoke<BinaryDiagnostics>('diagnose_binaries');
+}
+
+/**
+ * Makes `path` the FFmpeg to use ahead of bundled and system builds, or goes
+ * back to automatic resolution with `null`. The backend rejects paths that
+ * don't run as FFmpeg; on success capabilities are re-detected for the new
+ * binary.
+ *
+ * @param path - Absolute path to an ffmpeg executable; ffprobe is taken from beside it
+ */
+export async function setFfmpegPath(path: string | null): Promise<BinaryInfo> {
+  const info = await invoke<BinaryInfo>('set_ffmpeg_path', { path });
+  await refreshCapabilities();
+  return info;
+}
+
+/** Lists the registered FFmpeg builds, with the preferred one marked. */
+export async function listFfmpegInstallations(): Promise<FfmpegInstallations> {
+  return await invoke<FfmpegInstallations>('list_ffmpeg_installations');
+}
+
+/**
+ * Registers the FFmpeg at `path` so jobs can run it by passing its id as
+ * `ffmpegInstallation`. Registering a path again updates its label.
+ *
+ * @param label - Name shown for the build, e.g. "Nightly"
+ * @param path - Absolute path to an ffmpeg executable
+ */
+export async function registerFfmpegInstallation(
+  label: string,
+  path: string,
+): Promise<FfmpegInstallation> {
+  return await invoke<FfmpegInstallation>('register_ffmpeg_installation', { label, path });
+}
+
+/** Forgets a registered build; the binary stays on disk. */
+export async function removeFfmpegInstallation(id: string): Promise<void> {
+  await invoke<void>('remove_ffmpeg_installation', { id });
+}
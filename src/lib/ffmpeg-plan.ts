@@ -1,4 +1,5 @@
 import { FFmpegArgsBuilder } from './builders/ffmpeg-args-builder';
+import { canDecode } from './capability';
 import { CONTAINER_RULES } from './container-rules';
 import type { ContainerRule } from './container-rules';
 import { AudioPlanner } from './planners/audio-planner';
@@ -89,6 +90,15 @@ export function planJob(context: PlannerContext): PlannerDecision {
 
   const subtitlePlan = subtitlePlanner.plan(preset, containerRule, context.summary, warnings);
 
+  // Stream copy never decodes, so only transcoded streams need a decoder.
+  const { vcodec, acodec } = context.summary;
+  if (videoAction.action === 'transcode' && vcodec && !canDecode(context.capabilities, vcodec)) {
+    warnings.push(`This file's ${vcodec} video can't be decoded by your FFmpeg.`);
+  }
+  if (audioAction.action === 'transcode' && acodec && !canDecode(context.capabilities, acodec)) {
+    warnings.push(`This file's ${acodec} audio can't be decoded by your FFmpeg.`);
+  }
+
   if (videoTier.usedFallback) {
     notes.push(`Video tier fallback applied: using ${videoTier.tier}.`);
   }
@@ -101,7 +111,6 @@ export function planJob(context: PlannerContext): PlannerDecision {
     .withAudio(audioAction, audioTier, preset)
     .withSubtitles(subtitlePlan)
     .withContainer(preset.container, containerRule?.requiresFaststart ?? false)
-    .withProgress()
     .build();
 
   notes.push(...builderNotes);
@@ -144,7 +153,7 @@ function planGifJob(context: PlannerContext, preset: Preset): PlannerDecision {
   notes.push('Subtitles: drop for GIF export.');
 
   const argsBuilder = new FFmpegArgsBuilder();
-  argsBuilder.withCustomArgs(['-progress', 'pipe:2', '-nostats', ...args]);
+  argsBuilder.withCustomArgs(args);
 
   const muxer = muxerForContainer(preset.container);
   if (muxer) {
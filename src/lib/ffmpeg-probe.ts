@@ -1,10 +1,24 @@
-import { invoke } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 import type { ProbeSummary } from './types';
 
 export interface ProbeResponse {
   raw: unknown;
   summary: ProbeSummary;
+  /** Chapter markers in file order */
+  chapters?: ProbeChapter[];
+  /** Container-level tags (title, artist, album, creation_time, ...), keys in lowercase */
+  tags?: Record<string, string>;
+  /** Keyframe timestamps of the first video stream in seconds, when requested */
+  keyframes?: number[] | null;
+}
+
+/** A chapter marker, for chapter-based trimming and splitting */
+export interface ProbeChapter {
+  startSec: number;
+  endSec: number;
+  title?: string | null;
 }
 
 /** Normalize anything (file:// URL, NFC, stray whitespace) to a real POSIX path */
@@ -24,7 +38,15 @@ function normalizePath(input: string): string {
   return p.normalize('NFC');
 }
 
-export async function probeMedia(rawInput: string): Promise<ProbeResponse> {
+/**
+ * Probes a media file. A deep probe also runs a short `idet` pass over the
+ * video to fill `summary.interlacing`, which takes a few seconds. Asking for
+ * `keyframes` lists keyframe timestamps for keyframe-aligned stream copy trims.
+ */
+export async function probeMedia(
+  rawInput: string,
+  options: { deep?: boolean; keyframes?: boolean } = {},
+): Promise<ProbeResponse> {
   // The user's code returns the parsed JSON directly, but the existing function signature
   // and consumers expect a ProbeResponse object. I will adapt the user's code to fit
   // the existing structure.
@@ -34,5 +56,119 @@ export async function probeMedia(rawInput: string): Promise<ProbeResponse> {
   if (!input) {
     throw new Error('Path to probe is missing or empty');
   }
-  return await invoke<ProbeResponse>('probe_media', { path: input });
+  return await invoke<ProbeResponse>('probe_media', {
+    path: input,
+    ...(options.deep ? { deep: true } : {}),
+    ...(options.keyframes ? { keyframes: true } : {}),
+  });
+}
+
+const PROBE_RESULT_EVENT = 'probe://result';
+
+/** One `probe://result` event; `index` points into the batch's paths */
+export interface BatchProbeItem {
+  batchId: string;
+  index: number;
+  path: string;
+  result?: ProbeSummary | null;
+  error?: { code: string; message: string } | null;
+}
+
+/** A tiled sheet of frames from across a file, as a JPEG data URL */
+export interface ContactSheet {
+  dataUrl: string;
+  columns: number;
+  rows: number;
+  /** Seconds between sampled frames; 0 when the duration is unknown */
+  intervalSec: number;
+}
+
+export interface ContactSheetOptions {
+  columns?: number;
+  rows?: number;
+  /** Tile width in pixels */
+  tileWidth?: number;
+}
+
+/** Renders a contact sheet so a source can be checked before converting it. */
+export async function generateContactSheet(
+  rawInput: string,
+  options: ContactSheetOptions = {},
+): Promise<ContactSheet> {
+  const path = normalizePath(rawInput);
+  if (!path) {
+    throw new Error('Path for contact sheet is missing or empty');
+  }
+  return await invoke<ContactSheet>('generate_contact_sheet', {
+    request: { path, ...options },
+  });
+}
+
+/** A waveform PNG rendered into the app cache directory */
+export interface Waveform {
+  imagePath: string;
+  width: number;
+  height: number;
+  /** Whether an image rendered earlier for the unchanged file was reused */
+  cached: boolean;
+  /** `imagePath` as a URL the webview can load */
+  src: string;
+}
+
+export interface WaveformOptions {
+  width?: number;
+  height?: number;
+  /** `#rrggbb` */
+  color?: string;
+}
+
+/** Renders (or reuses) the waveform image of an audio file. */
+export async function generateWaveform(
+  rawInput: string,
+  options: WaveformOptions = {},
+): Promise<Waveform> {
+  const path = normalizePath(rawInput);
+  if (!path) {
+    throw new Error('Path for waveform is missing or empty');
+  }
+  const waveform = await invoke<Omit<Waveform, 'src'>>('generate_waveform', {
+    request: { path, ...options },
+  });
+  return { ...waveform, src: convertFileSrc(waveform.imagePath) };
+}
+
+export interface BatchProbeSummary {
+  batchId: string;
+  total: number;
+  delivered: number;
+  failed: number;
+  cancelled: boolean;
+}
+
+/**
+ * Probes many files in the backend. Results arrive in input order as
+ * `probe://result` events; starting a new batch cancels the previous one.
+ */
+export async function probeMediaBatch(
+  batchId: string,
+  paths: string[],
+  concurrency?: number,
+): Promise<BatchProbeSummary> {
+  const inputs = paths.map(normalizePath).filter((path) => path.length > 0);
+  return await invoke<BatchProbeSummary>('probe_media_batch', {
+    batchId,
+    paths: inputs,
+    concurrency,
+  });
+}
+
+/** Subscribes to batch probe results, for every batch. */
+export async function onProbeResult(
+  handler: (item: BatchProbeItem) => void,
+): Promise<UnlistenFn> {
+  return await listen<BatchProbeItem>(PROBE_RESULT_EVENT, (event) => handler(event.payload));
+}
+
+export async function cancelProbeBatch(batchId: string): Promise<boolean> {
+  return await invoke<boolean>('cancel_probe_batch', { batchId });
 }
@@ -0,0 +1,49 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** Result of the `analyze_loudness` pass (EBU R128) for one file */
+export interface LoudnessMeasurement {
+  path: string;
+  /** `-Infinity` arrives as `null` for digital silence */
+  integratedLufs: number | null;
+  truePeakDbtp: number | null;
+  loudnessRangeLu: number;
+  /** Gating threshold of the integrated loudness */
+  thresholdLufs: number;
+  durationSec?: number | null;
+}
+
+/** Targets for a `loudnorm` pass, defaulting to the EBU R128 broadcast values */
+export interface LoudnormTarget {
+  integratedLufs?: number;
+  truePeakDbtp?: number;
+  loudnessRangeLu?: number;
+}
+
+/** Runs the analysis pass that measures integrated loudness, true peak and LRA. */
+export async function analyzeLoudness(path: string): Promise<LoudnessMeasurement> {
+  return await invoke<LoudnessMeasurement>('analyze_loudness', { path });
+}
+
+/**
+ * Builds the `loudnorm` filter for the second pass of a two-pass
+ * normalization. Feeding the measured values lets loudnorm apply a single
+ * linear gain instead of its dynamic mode.
+ */
+export function loudnormFilter(
+  measurement: LoudnessMeasurement,
+  target: LoudnormTarget = {},
+): string {
+  const { integratedLufs = -23, truePeakDbtp = -1, loudnessRangeLu = 7 } = target;
+  const measured = measurement.integratedLufs ?? -99;
+  const peak = measurement.truePeakDbtp ?? -99;
+  return [
+    `loudnorm=I=${integratedLufs}`,
+    `TP=${truePeakDbtp}`,
+    `LRA=${Math.max(loudnessRangeLu, measurement.loudnessRangeLu)}`,
+    `measured_I=${measured}`,
+    `measured_TP=${peak}`,
+    `measured_LRA=${measurement.loudnessRangeLu}`,
+    `measured_thresh=${measurement.thresholdLufs}`,
+    'linear=true',
+  ].join(':');
+}
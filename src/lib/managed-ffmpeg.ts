@@ -0,0 +1,50 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+import { refreshCapabilities } from './capability';
+
+const DOWNLOAD_PROGRESS_EVENT = 'ffmpeg://download-progress';
+
+/** One `ffmpeg://download-progress` event */
+export interface DownloadProgress {
+  binary: 'ffmpeg' | 'ffprobe';
+  downloadedBytes: number;
+  /** Archive size, when the server reports one */
+  totalBytes?: number | null;
+}
+
+/** The FFmpeg build Honeymelon downloaded into app data */
+export interface ManagedInstall {
+  version: string;
+  ffmpegPath: string;
+  ffprobePath: string;
+  /** SHA-256 pinned for the installed binaries in the manifest built into the app */
+  ffmpegSha256: string;
+  ffprobeSha256: string;
+}
+
+/** The installed managed FFmpeg build, or `null` when there is none. */
+export async function managedFfmpeg(): Promise<ManagedInstall | null> {
+  return await invoke<ManagedInstall | null>('managed_ffmpeg');
+}
+
+/**
+ * Downloads and installs the pinned, checksum-verified FFmpeg build for this
+ * platform, then re-detects capabilities so the new binary is used at once.
+ *
+ * @param onProgress - Called as each binary's archive downloads
+ */
+export async function installFfmpeg(
+  onProgress?: (progress: DownloadProgress) => void,
+): Promise<ManagedInstall> {
+  const unlisten = onProgress
+    ? await listen<DownloadProgress>(DOWNLOAD_PROGRESS_EVENT, (event) => onProgress(event.payload))
+    : undefined;
+  try {
+    const install = await invoke<ManagedInstall>('install_ffmpeg');
+    await refreshCapabilities();
+    return install;
+  } finally {
+    unlisten?.();
+  }
+}
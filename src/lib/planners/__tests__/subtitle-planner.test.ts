@@ -180,6 +180,39 @@ describe('SubtitlePlanner', () => {
       expect(result.mode).toBe('copy');
       expect(warnings).toHaveLength(0);
     });
+
+    describe('ASS subtitles with embedded fonts', () => {
+      const styledSummary: ProbeSummary = {
+        ...baseSummary,
+        hasTextSubs: true,
+        streams: [
+          { index: 0, type: 'video', codec: 'h264', default: true, forced: false },
+          { index: 1, type: 'subtitle', codec: 'ass', default: true, forced: false },
+        ],
+        attachments: [
+          { index: 2, filename: 'Roboto.ttf', mimeType: 'font/ttf', font: true },
+          { index: 3, filename: 'cover.jpg', mimeType: 'image/jpeg', font: false },
+        ],
+      };
+
+      it('should keep the fonts in MKV', () => {
+        const preset: Preset = { ...basePreset, container: 'mkv' };
+        const result = planner.plan(preset, mkvRule, styledSummary, warnings);
+
+        expect(result.keepAttachments).toBe(true);
+        expect(result.note).toBe('Subtitles: keep existing streams and 1 embedded font(s).');
+        expect(warnings).toHaveLength(0);
+      });
+
+      it('should warn when the container drops the fonts', () => {
+        const result = planner.plan(basePreset, undefined, styledSummary, warnings);
+
+        expect(result.keepAttachments).toBeUndefined();
+        expect(warnings).toEqual([
+          'ASS subtitles use 1 embedded font(s) that mp4 cannot carry; styling will fall back to system fonts.',
+        ]);
+      });
+    });
   });
 
   describe('Mode: convert', () => {
@@ -1,13 +1,17 @@
 import type { ContainerRule } from '../container-rules';
-import type { Preset, ProbeSummary } from '../types';
+import type { Container, Preset, ProbeSummary } from '../types';
 
 const SUBTITLE_CONVERT_CODEC = 'mov_text';
+/** Containers that can carry attached files such as fonts */
+const ATTACHMENT_CONTAINERS: Container[] = ['mkv'];
 
 export interface SubtitlePlanDecision {
   mode: 'drop' | 'copy' | 'convert';
   note: string;
   /** Whether image-based subtitles should be excluded from mapping */
   excludeImageStreams?: boolean;
+  /** Whether attached fonts should be mapped alongside the subtitles */
+  keepAttachments?: boolean;
 }
 
 function allowsAny(list: 'any' | string[] | undefined): boolean {
@@ -17,6 +21,17 @@ function allowsAny(list: 'any' | string[] | undefined): boolean {
   return list.length > 0;
 }
 
+/** Number of attached fonts, counted only when ASS/SSA subtitles may use them */
+function fontsForStyledSubtitles(summary: ProbeSummary): number {
+  const hasStyledSubs = (summary.streams ?? []).some(
+    (stream) => stream.type === 'subtitle' && (stream.codec === 'ass' || stream.codec === 'ssa'),
+  );
+  if (!hasStyledSubs) {
+    return 0;
+  }
+  return (summary.attachments ?? []).filter((attachment) => attachment.font).length;
+}
+
 function subtitleCodecAllowed(list: 'any' | string[] | undefined, codec: string): boolean {
   if (!list || list === 'any') {
     return true;
@@ -61,6 +76,19 @@ export class SubtitlePlanner {
         if (hasImage && rule && !allowsAny(rule.image)) {
           warnings.push(`${preset.container} does not permit image subtitles; consider burn-in.`);
         }
+        const fonts = fontsForStyledSubtitles(summary);
+        if (fonts > 0 && ATTACHMENT_CONTAINERS.includes(preset.container)) {
+          return {
+            mode: 'copy',
+            note: `Subtitles: keep existing streams and ${fonts} embedded font(s).`,
+            keepAttachments: true,
+          };
+        }
+        if (fonts > 0) {
+          warnings.push(
+            `ASS subtitles use ${fonts} embedded font(s) that ${preset.container} cannot carry; styling will fall back to system fonts.`,
+          );
+        }
         return {
           mode: 'copy',
           note: 'Subtitles: keep existing streams.',
@@ -212,7 +212,101 @@ export interface ProbeSummary {
     trc?: string;
     /** Color space (e.g., 'bt709', 'bt2020nc') */
     space?: string;
+    /** Mastering display color volume (SMPTE ST 2086); chromaticities as [x, y], luminance in cd/m² */
+    masteringDisplay?: {
+      red: [number, number];
+      green: [number, number];
+      blue: [number, number];
+      whitePoint: [number, number];
+      minLuminance: number;
+      maxLuminance: number;
+    } | null;
+    /** Content light levels in cd/m² */
+    contentLight?: { maxCll: number; maxFall: number } | null;
+    /** Whether the video carries HDR10+ dynamic metadata */
+    hdr10Plus?: boolean;
+    /** Dolby Vision configuration */
+    dolbyVision?: { profile: number; level?: number | null; compatibilityId?: number | null } | null;
   };
+  /** Pixel format of the first video stream (e.g., 'yuv420p10le') */
+  pixFmt?: string | null;
+  /** Bits per color component of the first video stream */
+  bitDepth?: number | null;
+  /** Codec profile of the first video stream (e.g., 'Main 10', 'High') */
+  profile?: string | null;
+  /** Codec level as ffprobe reports it (e.g., 41 for H.264 4.1) */
+  level?: number | null;
+  /** Overall bitrate of the container in bits per second */
+  bitRate?: number | null;
+  /** Bitrate of the first video stream in bits per second, reported or estimated */
+  videoBitRate?: number | null;
+  /** Bitrate of the first audio stream in bits per second */
+  audioBitRate?: number | null;
+  /** Whether the file carries cover art as an attached picture stream */
+  hasCoverArt?: boolean;
+  /** Files attached to the container (fonts, covers), as Matroska allows */
+  attachments?: ProbeAttachment[];
+  /** Every stream in the file, in file order, for track selection */
+  streams?: ProbeStream[];
+  /** Interlacing analysis, only present after a deep probe */
+  interlacing?: {
+    /** Frames analyzed */
+    frames: number;
+    /** Likelihood (0–1) that the video is interlaced */
+    interlaced: number;
+    /** Likelihood (0–1) that the video is telecined (3:2 pulldown) */
+    telecined: number;
+    /** Dominant field order of interlaced frames */
+    fieldOrder?: 'tff' | 'bff' | null;
+  } | null;
+}
+
+/**
+ * One stream of a probed media file.
+ */
+export interface ProbeStream {
+  /** Index within the file, as used by `-map 0:<index>` */
+  index: number;
+  /** Stream type ('video', 'audio', 'subtitle', 'data', ...) */
+  type: string;
+  /** Codec name in lowercase */
+  codec?: string | null;
+  /** Language tag (e.g., 'eng'); absent when untagged */
+  language?: string | null;
+  /** Track title */
+  title?: string | null;
+  /** Number of audio channels (audio only) */
+  channels?: number | null;
+  /** Width in pixels (video only) */
+  width?: number | null;
+  /** Height in pixels (video only) */
+  height?: number | null;
+  /** Whether the stream is the default of its type */
+  default: boolean;
+  /** Whether the stream is forced (forced subtitles) */
+  forced: boolean;
+  /** Whether the stream is an attached picture (cover art) rather than video */
+  coverArt?: boolean;
+  /** Bitrate in bits per second; apportioned from the container when `bitRateEstimated` */
+  bitRate?: number | null;
+  /** Whether `bitRate` was estimated rather than reported */
+  bitRateEstimated?: boolean;
+  /** Every stream tag (language, title, handler_name, ...), keys in lowercase */
+  tags?: Record<string, string>;
+}
+
+/**
+ * A file attached to a probed container, such as a font used by ASS subtitles.
+ */
+export interface ProbeAttachment {
+  /** Index within the file, as used by `-map 0:<index>` */
+  index: number;
+  /** Attached file name */
+  filename?: string | null;
+  /** MIME type (e.g., 'font/ttf', 'image/jpeg') */
+  mimeType?: string | null;
+  /** Whether the attachment is a font */
+  font: boolean;
 }
 
 /**
@@ -230,6 +324,30 @@ export interface CapabilitySnapshot {
   formats: Set<string>;
   /** Set of available filter names */
   filters: Set<string>;
+  /** Set of pixel formats FFmpeg can convert to; absent when unknown */
+  pixelFormats?: Set<string>;
+  /** Set of codec names some decoder handles; absent when unknown */
+  decodableCodecs?: Set<string>;
+  /** Set of hardware decoding methods (e.g., 'videotoolbox') */
+  hwaccels?: Set<string>;
+  /** Set of bitstream filters for stream copy (e.g., 'aac_adtstoasc') */
+  bitstreamFilters?: Set<string>;
+  /** Version and build details of the FFmpeg binary */
+  ffmpeg?: FfmpegVersion | null;
+}
+
+/**
+ * Version and build details parsed from `ffmpeg -version`.
+ */
+export interface FfmpegVersion {
+  /** Version as printed, e.g. '7.1', 'n6.1.1' or 'N-113447-g1a2b3c4' */
+  version: string;
+  /** Release number as [major, minor, patch]; absent for builds from git */
+  release?: [number, number, number] | null;
+  /** `./configure` flags the binary was built with */
+  configuration: string[];
+  /** Library versions keyed by library, e.g. libavcodec → '61.19.100' */
+  libraries: Record<string, string>;
 }
 
 /**
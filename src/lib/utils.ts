@@ -2,6 +2,8 @@ import type { ClassValue } from 'clsx';
 import { clsx } from 'clsx';
 import { twMerge } from 'tailwind-merge';
 
+import type { NativePath } from '@/lib/api-types.generated';
+
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
@@ -86,6 +88,14 @@ export function formatFileSize(bytes: number): string {
   return `${Math.round((bytes / Math.pow(k, i)) * 10) / 10} ${sizes[i]}`;
 }
 
+/** Formats bits per second for display, e.g. 8.2 Mbps or 192 kbps */
+export function formatBitrate(bitsPerSecond: number): string {
+  if (bitsPerSecond >= 1_000_000) {
+    return `${Math.round(bitsPerSecond / 100_000) / 10} Mbps`;
+  }
+  return `${Math.round(bitsPerSecond / 1000)} kbps`;
+}
+
 export function formatDuration(seconds: number): string {
   if (seconds < 60) return `${Math.floor(seconds)}s`;
   const mins = Math.floor(seconds / 60);
@@ -115,6 +125,12 @@ export function formatPath(path?: string): string {
   return formatted;
 }
 
+/** A path from the backend as text; raw paths, which aren't valid Unicode, are decoded lossily. */
+export function displayPath(path: NativePath): string {
+  if (typeof path === 'string') return path;
+  return new TextDecoder().decode(new Uint8Array(path.bytes));
+}
+
 export function extractFileName(name: string): string {
   const lastDot = name.lastIndexOf('.');
   if (lastDot === -1) return name;
@@ -0,0 +1,91 @@
+/**
+ * Versioned documents in `localStorage`, mirroring the backend's
+ * `persistence` module: every value is stored as `{ schemaVersion, data }`,
+ * values written before versioning count as version 1, and loading an older
+ * value backs it up under `<key>.v<version>.bak` before migrating it.
+ */
+
+/** Upgrades a payload by exactly one version. */
+export type Migration = (payload: unknown) => unknown;
+
+export interface StorageSchema {
+  key: string;
+  /** `migrations[i]` upgrades version `i + 1` to `i + 2` */
+  migrations: Migration[];
+}
+
+interface Envelope {
+  schemaVersion: number;
+  data: unknown;
+}
+
+export function currentVersion(schema: StorageSchema): number {
+  return schema.migrations.length + 1;
+}
+
+function isEnvelope(value: unknown): value is Envelope {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as Envelope).schemaVersion === 'number' &&
+    'data' in value
+  );
+}
+
+function backup(schema: StorageSchema, raw: string, version: number) {
+  const target = `${schema.key}.v${version}.bak`;
+  if (localStorage.getItem(target) === null) {
+    localStorage.setItem(target, raw);
+  }
+}
+
+/**
+ * Loads a value, migrating it in place when it predates `schema`. Values
+ * written by a newer release are returned as-is for lenient parsing and are
+ * backed up on the next save. Unreadable values load as `null`.
+ */
+export function loadVersioned<T>(schema: StorageSchema): T | null {
+  const raw = localStorage.getItem(schema.key);
+  if (raw === null) {
+    return null;
+  }
+
+  let parsed: unknown;
+  try {
+    parsed = JSON.parse(raw);
+  } catch {
+    return null;
+  }
+
+  const enveloped = isEnvelope(parsed);
+  const version = enveloped ? parsed.schemaVersion : 1;
+  let payload = enveloped ? parsed.data : parsed;
+
+  const current = currentVersion(schema);
+  if (version < current || !enveloped) {
+    backup(schema, raw, version);
+    for (const migration of schema.migrations.slice(Math.max(version - 1, 0))) {
+      payload = migration(payload);
+    }
+    saveVersioned(schema, payload);
+  }
+  return payload as T;
+}
+
+/** Writes a value tagged with the schema's current version. */
+export function saveVersioned(schema: StorageSchema, data: unknown) {
+  const raw = localStorage.getItem(schema.key);
+  if (raw !== null) {
+    try {
+      const parsed: unknown = JSON.parse(raw);
+      if (isEnvelope(parsed) && parsed.schemaVersion > currentVersion(schema)) {
+        backup(schema, raw, parsed.schemaVersion);
+      }
+    } catch {
+      // An unreadable value is simply replaced.
+    }
+  }
+
+  const envelope: Envelope = { schemaVersion: currentVersion(schema), data };
+  localStorage.setItem(schema.key, JSON.stringify(envelope));
+}
@@ -31,10 +31,12 @@ describe('ExecutionService ↔ Tauri bridge', () => {
 
     expect(result.success).toBe(true);
     expect(invokeMock).toHaveBeenCalledWith('start_job', {
-      jobId: 'job-123',
-      args: decision.ffmpegArgs,
-      outputPath: '/tmp/output.mp4',
-      exclusive: true,
+      spec: {
+        jobId: 'job-123',
+        args: decision.ffmpegArgs,
+        outputPath: '/tmp/output.mp4',
+        exclusive: true,
+      },
     });
   });
 
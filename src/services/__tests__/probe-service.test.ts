@@ -0,0 +1,70 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+import type { BatchProbeItem } from '@/lib/ffmpeg-probe';
+import { ProbeService } from '@/services/probe-service';
+
+const batch = vi.hoisted(() => ({
+  handler: undefined as ((item: BatchProbeItem) => void) | undefined,
+  unlisten: vi.fn(),
+}));
+
+vi.mock('@/lib/ffmpeg-probe', () => ({
+  probeMedia: vi.fn(),
+  onProbeResult: vi.fn(async (handler: (item: BatchProbeItem) => void) => {
+    batch.handler = handler;
+    return batch.unlisten;
+  }),
+  probeMediaBatch: vi.fn(),
+}));
+
+import { probeMediaBatch } from '@/lib/ffmpeg-probe';
+
+describe('ProbeService.probeMany', () => {
+  const mockBatch = vi.mocked(probeMediaBatch);
+
+  beforeEach(() => {
+    mockBatch.mockReset();
+    batch.unlisten.mockReset();
+  });
+
+  it('probes valid paths as one batch and maps results back', async () => {
+    mockBatch.mockImplementation(async (batchId, paths) => {
+      batch.handler?.({ batchId: 'other', index: 0, path: paths[0], result: { durationSec: 1 } });
+      batch.handler?.({ batchId, index: 0, path: paths[0], result: { durationSec: 5 } });
+      batch.handler?.({
+        batchId,
+        index: 1,
+        path: paths[1],
+        error: { code: 'probe_failed', message: 'bad file' },
+      });
+      return { batchId, total: paths.length, delivered: 2, failed: 1, cancelled: false };
+    });
+    const onResult = vi.fn();
+
+    const results = await new ProbeService().probeMany(['/a.mov', '', '/b.mov'], {
+      concurrency: 2,
+      onResult,
+    });
+
+    expect(mockBatch).toHaveBeenCalledWith(expect.any(String), ['/a.mov', '/b.mov'], 2);
+    expect(results[0]).toEqual({ success: true, summary: { durationSec: 5 } });
+    expect(results[1].success).toBe(false);
+    expect(results[2]).toEqual({ success: false, error: 'bad file' });
+    expect(onResult).toHaveBeenCalledTimes(2);
+    expect(batch.unlisten).toHaveBeenCalled();
+  });
+
+  it('marks undelivered paths as cancelled', async () => {
+    mockBatch.mockImplementation(async (batchId, paths) => ({
+      batchId,
+      total: paths.length,
+      delivered: 0,
+      failed: 0,
+      cancelled: true,
+    }));
+
+    const results = await new ProbeService().probeMany(['/a.mov']);
+
+    expect(results).toEqual([{ success: false, error: 'Probe cancelled' }]);
+  });
+});
@@ -7,7 +7,9 @@
 
 import { invoke } from '@tauri-apps/api/core';
 
+import type { JobSpec, NativePath } from '@/lib/api-types.generated';
 import type { PlannerDecision } from '@/lib/ffmpeg-plan';
+import type { Tier } from '@/lib/types';
 
 /**
  * Result of a job start operation
@@ -18,6 +20,23 @@ export interface ExecutionResult {
   code?: string;
 }
 
+/**
+ * Outcome of a decode-only check, delivered as a `verify://completion` event
+ */
+export interface VerificationResult {
+  jobId: string;
+  path: string;
+  success: boolean;
+  cancelled: boolean;
+  exitCode: number | null;
+  /** Number of decode errors ffmpeg reported */
+  decodeErrors: number;
+  /** Most recent error lines */
+  errors: string[];
+  /** Hex SHA-256 of the file when a checksum was requested */
+  checksum: string | null;
+}
+
 /**
  * Job execution options
  */
@@ -26,6 +45,107 @@ export interface ExecutionOptions {
   decision: PlannerDecision;
   outputPath: string;
   exclusive?: boolean;
+  /** Quality tier the decision was planned with, reported in failure telemetry */
+  tier?: Tier;
+  /** Run ffmpeg at background priority; unset follows the global preference */
+  lowPriority?: boolean;
+  /** Jobs that must succeed first; if one fails this job completes as `job_skipped` */
+  dependsOn?: string[];
+  /** What to do when the output already exists; unset overwrites it */
+  overwrite?: JobSpec['overwrite'];
+  /** Source duration, used to check free space on the output volume */
+  durationSec?: number;
+  /** Encode long sources in parallel segments */
+  segmented?: boolean;
+  /** Most encoder threads; unset follows the global default */
+  threads?: number;
+  /** Move the source to the trash once the output is verified */
+  trashSource?: boolean;
+  /** Give the output the source's timestamps and Finder tags */
+  preserveTimestamps?: boolean;
+  /** Labels echoed back in the job's progress and completion events */
+  metadata?: Record<string, string>;
+  /** Id of the registered FFmpeg installation to run; unset uses the preferred one */
+  ffmpegInstallation?: string;
+}
+
+/**
+ * A job interrupted by the last quit or crash
+ */
+export interface RestoredJob {
+  jobId: string;
+  args: NativePath[];
+  outputPath: NativePath;
+  exclusive: boolean;
+  savedAtMs: number;
+  /** Whether the backend started the job again */
+  resumed: boolean;
+  error?: { code: string; message: string } | null;
+}
+
+/**
+ * Outcome of a bulk action for one job
+ */
+export interface JobActionResult {
+  jobId: string;
+  success: boolean;
+  error?: { code: string; message: string } | null;
+}
+
+/**
+ * A finished conversion recorded by the backend
+ */
+export interface HistoryEntry {
+  jobId: string;
+  sourcePath: string | null;
+  outputPath: string;
+  presetId: string | null;
+  success: boolean;
+  cancelled: boolean;
+  /** Completion code, e.g. `job_complete` or `job_failed` */
+  code: string;
+  message: string | null;
+  durationSec: number | null;
+  elapsedMs: number | null;
+  sourceBytes: number | null;
+  outputBytes: number | null;
+  /** Media seconds converted per wall-clock second */
+  speed: number | null;
+  /** Milliseconds since the Unix epoch */
+  finishedAtMs: number;
+}
+
+/**
+ * What the queue does on battery power: keep going, hold new starts, or
+ * also pause running jobs
+ */
+export type BatteryPolicy = 'ignore' | 'defer' | 'pause';
+
+/**
+ * Runs after a job's final outcome. `{output}`, `{input}`, `{jobId}`,
+ * `{status}` and `{code}` are replaced in command arguments and URLs.
+ */
+export type PostJobHook = {
+  /** Outcomes that run the hook; defaults to every outcome */
+  on?: 'success' | 'failure' | 'always';
+} & (
+  | { kind: 'command'; program: string; args?: string[] }
+  | { kind: 'webhook'; url: string }
+);
+
+/**
+ * When failed jobs are started again automatically
+ */
+export interface RetryPolicy {
+  /** Retries after the first attempt; 0 disables retrying */
+  maxRetries: number;
+  /** Delay before the first retry, doubled for each one after it */
+  initialDelayMs: number;
+  maxDelayMs: number;
+  /** ffmpeg exit codes treated as transient */
+  exitCodes?: number[];
+  /** Whether failures to spawn ffmpeg are retried */
+  retrySpawnErrors?: boolean;
 }
 
 /**
@@ -82,12 +202,26 @@ export class ExecutionService {
       }
 
       // Start the job via Tauri command
-      await invoke<void>('start_job', {
+      const spec: JobSpec = {
         jobId: options.jobId,
         args: options.decision.ffmpegArgs,
         outputPath: options.outputPath,
         exclusive: options.exclusive ?? false,
-      });
+        lowPriority: options.lowPriority,
+        dependsOn: options.dependsOn,
+        overwrite: options.overwrite,
+        durationSec: options.durationSec,
+        segmented: options.segmented,
+        threads: options.threads,
+        trashSource: options.trashSource,
+        preserveTimestamps: options.preserveTimestamps,
+        metadata: options.metadata,
+        ffmpegInstallation: options.ffmpegInstallation,
+        plan: options.decision.preset
+          ? { preset: options.decision.preset, tier: options.tier }
+          : undefined,
+      };
+      await invoke<void>('start_job', { spec });
 
       return {
         success: true,
@@ -144,6 +278,43 @@ export class ExecutionService {
     }
   }
 
+  /**
+   * Cancels every queued, waiting and running job
+   *
+   * @returns Per-job results, including jobs that could not be stopped
+   */
+  async cancelAll(): Promise<JobActionResult[]> {
+    return invoke<JobActionResult[]>('cancel_all_jobs');
+  }
+
+  /**
+   * Quits after the user agreed to interrupt active jobs
+   *
+   * Running jobs are stopped and their partial output removed; the queue is
+   * kept so it can be restored on the next launch.
+   */
+  async confirmQuit(): Promise<void> {
+    await invoke<void>('confirm_quit');
+  }
+
+  /**
+   * Suspends every running job
+   *
+   * @returns Per-job results; pausing is unsupported on some platforms
+   */
+  async pauseAll(): Promise<JobActionResult[]> {
+    return invoke<JobActionResult[]>('pause_all_jobs');
+  }
+
+  /**
+   * Continues every paused job
+   *
+   * @returns Per-job results
+   */
+  async resumeAll(): Promise<JobActionResult[]> {
+    return invoke<JobActionResult[]>('resume_all_jobs');
+  }
+
   /**
    * Sets the maximum number of concurrent jobs
    *
@@ -161,6 +332,154 @@ export class ExecutionService {
     await invoke<void>('set_max_concurrency', { limit: actualLimit });
   }
 
+  /**
+   * Replaces the policy for retrying transient job failures
+   *
+   * @param policy - Retry limits and which failures count as transient
+   * @returns Promise that resolves when the policy is applied
+   */
+  async setRetryPolicy(policy: RetryPolicy): Promise<void> {
+    await invoke<void>('set_retry_policy', { policy });
+  }
+
+  /**
+   * Replaces the hooks run after each job finishes
+   *
+   * @param hooks - Programs to run or webhooks to notify
+   * @returns Promise that resolves when the hooks are applied
+   */
+  async setPostJobHooks(hooks: PostJobHook[]): Promise<void> {
+    await invoke<void>('set_post_job_hooks', { hooks });
+  }
+
+  /**
+   * Sets whether jobs run at background priority by default
+   *
+   * @param enabled - Lower ffmpeg's scheduling priority so the UI and other apps stay responsive
+   * @returns Promise that resolves when the preference is applied
+   */
+  async setLowPriority(enabled: boolean): Promise<void> {
+    await invoke<void>('set_low_priority', { enabled });
+  }
+
+  /**
+   * Sets the encoder thread limit for jobs that don't set their own
+   *
+   * @param threads - Most threads per job, or null to let ffmpeg use every core
+   * @returns Promise that resolves when the default is applied
+   */
+  async setDefaultThreads(threads: number | null): Promise<void> {
+    await invoke<void>('set_default_threads', { threads });
+  }
+
+  /**
+   * Sets whether running jobs keep the system from sleeping
+   *
+   * @param enabled - Prevent idle sleep while any job runs
+   * @returns Promise that resolves when the preference is applied
+   */
+  async setPreventSleep(enabled: boolean): Promise<void> {
+    await invoke<void>('set_prevent_sleep', { enabled });
+  }
+
+  /**
+   * Sets what the queue does while the machine runs on battery
+   *
+   * @param policy - Battery policy to apply
+   * @returns Promise that resolves when the policy is applied
+   */
+  async setBatteryPolicy(policy: BatteryPolicy): Promise<void> {
+    await invoke<void>('set_battery_policy', { policy });
+  }
+
+  /**
+   * Sets how long a job may go without progress before it is stopped
+   *
+   * @param timeoutMs - Milliseconds without progress, or null to disable the watchdog
+   * @returns Promise that resolves when the timeout is applied
+   */
+  async setStallTimeout(timeoutMs: number | null): Promise<void> {
+    await invoke<void>('set_stall_timeout', { timeoutMs });
+  }
+
+  /**
+   * Sets the minimum time between progress events of one job
+   *
+   * @param intervalMs - Milliseconds between events, or 0 to send every event
+   * @returns Promise that resolves when the interval is applied
+   */
+  async setProgressInterval(intervalMs: number): Promise<void> {
+    await invoke<void>('set_progress_interval', { intervalMs: Math.max(0, Math.round(intervalMs)) });
+  }
+
+  /**
+   * Collects jobs interrupted by the last quit or crash
+   *
+   * @param resume - Start them again from the beginning instead of only listing them
+   * @returns The interrupted jobs, oldest first
+   */
+  async restoreJobs(resume = false): Promise<RestoredJob[]> {
+    return invoke<RestoredJob[]>('restore_jobs', { resume });
+  }
+
+  /**
+   * Lists finished conversions
+   *
+   * @returns History entries, newest first
+   */
+  async getHistory(): Promise<HistoryEntry[]> {
+    return invoke<HistoryEntry[]>('get_history');
+  }
+
+  /**
+   * Removes every history entry
+   */
+  async clearHistory(): Promise<void> {
+    await invoke<void>('clear_history');
+  }
+
+  /**
+   * Finds the ffmpeg log of a job
+   *
+   * @param jobId - Job whose log to locate
+   * @returns Path of the current log file, or null if none was written
+   */
+  async getJobLogPath(jobId: string): Promise<string | null> {
+    return invoke<string | null>('get_job_log_path', { jobId });
+  }
+
+  /**
+   * Copies job logs, earlier attempts included, into a directory
+   *
+   * @param destination - Directory to copy the logs into
+   * @param jobId - Only export this job's logs
+   * @returns Paths of the copies
+   */
+  async exportJobLogs(destination: string, jobId?: string): Promise<string[]> {
+    return invoke<string[]>('export_job_logs', { destination, jobId: jobId ?? null });
+  }
+
+  /**
+   * Decodes a file without writing output to find corruption; the result
+   * arrives as a `verify://completion` event for `jobId`
+   *
+   * @param jobId - Id to track and cancel the check with
+   * @param path - File to decode
+   * @param options - Hash the file afterwards, or only decode its first seconds
+   */
+  async startVerification(
+    jobId: string,
+    path: string,
+    options: { checksum?: boolean; limitSec?: number } = {},
+  ): Promise<void> {
+    await invoke<void>('start_verification', {
+      jobId,
+      path,
+      checksum: options.checksum ?? false,
+      limitSec: options.limitSec ?? null,
+    });
+  }
+
   /**
    * Validates execution options
    *
@@ -5,7 +5,11 @@
  * validation, and transformation of probe results.
  */
 
-import { probeMedia as probeMediaCommand } from '@/lib/ffmpeg-probe';
+import {
+  onProbeResult,
+  probeMedia as probeMediaCommand,
+  probeMediaBatch,
+} from '@/lib/ffmpeg-probe';
 import type { ProbeSummary } from '@/lib/types';
 
 /**
@@ -73,10 +77,13 @@ export class ProbeService {
   }
 
   /**
-   * Probes multiple media files in parallel
+   * Probes multiple media files as one backend batch, which runs a bounded
+   * number of ffprobe processes at a time. Results are passed to `onResult`
+   * as they arrive, in input order.
    *
    * @param paths - Array of file paths to probe
-   * @returns Promise with array of probe results
+   * @param options - Worker count and a per-result callback
+   * @returns Promise with array of probe results, matching `paths`
    *
    * @example
    * ```ts
@@ -85,8 +92,58 @@ export class ProbeService {
    * const successful = results.filter(r => r.success);
    * ```
    */
-  async probeMany(paths: string[]): Promise<ProbeResult[]> {
-    return Promise.all(paths.map((path) => this.probe(path)));
+  async probeMany(
+    paths: string[],
+    options: {
+      concurrency?: number;
+      onResult?: (index: number, result: ProbeResult) => void;
+    } = {},
+  ): Promise<ProbeResult[]> {
+    const results: ProbeResult[] = paths.map(() => ({
+      success: false,
+      error: 'Invalid path: path must be a non-empty string',
+    }));
+    // The batch only receives valid paths; map its indexes back to ours.
+    const positions = paths.flatMap((path, index) => (this.validatePath(path) ? [index] : []));
+    if (positions.length === 0) {
+      return results;
+    }
+
+    const batchId = `probe-${Date.now().toString(36)}-${Math.random().toString(36).slice(2)}`;
+    const delivered = new Set<number>();
+    const unlisten = await onProbeResult((item) => {
+      const position = positions[item.index];
+      if (item.batchId !== batchId || position === undefined) {
+        return;
+      }
+      const result: ProbeResult = item.result
+        ? { success: true, summary: item.result }
+        : { success: false, error: item.error?.message ?? 'Probe failed' };
+      results[position] = result;
+      delivered.add(position);
+      options.onResult?.(position, result);
+    });
+
+    try {
+      await probeMediaBatch(batchId, positions.map((index) => paths[index]), options.concurrency);
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      for (const position of positions) {
+        if (!delivered.has(position)) {
+          results[position] = { success: false, error: message };
+        }
+      }
+      return results;
+    } finally {
+      unlisten();
+    }
+
+    for (const position of positions) {
+      if (!delivered.has(position)) {
+        results[position] = { success: false, error: 'Probe cancelled' };
+      }
+    }
+    return results;
   }
 
   /**
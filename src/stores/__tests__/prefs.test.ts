@@ -1,13 +1,83 @@
-import { describe, it, expect, beforeEach } from 'vitest';
+import { describe, it, expect, beforeEach, vi } from 'vitest';
 import { setActivePinia, createPinia } from 'pinia';
+import { nextTick } from 'vue';
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+}));
+
+import { invoke } from '@tauri-apps/api/core';
 import { usePrefsStore } from '../prefs';
 import { LIMITS, DEFAULTS } from '@/lib/constants';
 
+const mockInvoke = vi.mocked(invoke);
+
+function createStorageMock(): Storage {
+  let store: Record<string, string> = {};
+  return {
+    getItem: (key: string) => (key in store ? store[key] : null),
+    setItem: (key: string, value: string) => {
+      store[key] = value;
+    },
+    removeItem: (key: string) => {
+      delete store[key];
+    },
+    clear: () => {
+      store = {};
+    },
+    key: (index: number) => Object.keys(store)[index] ?? null,
+    get length() {
+      return Object.keys(store).length;
+    },
+  } as Storage;
+}
+
 describe('prefs store', () => {
   beforeEach(() => {
+    vi.stubGlobal('localStorage', createStorageMock());
+    mockInvoke.mockReset();
+    mockInvoke.mockResolvedValue({ readOnly: false });
     setActivePinia(createPinia());
   });
 
+  describe('persistence', () => {
+    it('saves changes as a versioned document', async () => {
+      const store = usePrefsStore();
+      store.setLowPriority(true);
+      await nextTick();
+
+      await vi.waitFor(() => expect(localStorage.getItem('prefs')).not.toBeNull());
+      const stored = JSON.parse(localStorage.getItem('prefs') ?? '{}');
+      expect(stored.schemaVersion).toBe(1);
+      expect(stored.data.lowPriority).toBe(true);
+    });
+
+    it('does not save in read-only mode', async () => {
+      mockInvoke.mockResolvedValue({ readOnly: true });
+      const store = usePrefsStore();
+      await vi.waitFor(() => expect(store.readOnly).toBe(true));
+
+      store.setLowPriority(true);
+      await nextTick();
+      await Promise.resolve();
+
+      expect(store.lowPriority).toBe(true);
+      expect(localStorage.getItem('prefs')).toBeNull();
+    });
+
+    it('restores saved preferences', () => {
+      localStorage.setItem(
+        'prefs',
+        JSON.stringify({ schemaVersion: 1, data: { filenameSeparator: '_', encoderThreads: 4 } }),
+      );
+
+      const store = usePrefsStore();
+      expect(store.filenameSeparator).toBe('_');
+      expect(store.encoderThreads).toBe(4);
+      expect(store.includePresetInName).toBe(true);
+    });
+  });
+
   describe('initial state', () => {
     it('should have default concurrency', () => {
       const store = usePrefsStore();
@@ -33,6 +103,65 @@ describe('prefs store', () => {
       const store = usePrefsStore();
       expect(store.filenameSeparator).toBe(DEFAULTS.FILENAME_SEPARATOR);
     });
+
+    it('should encode as one process by default', () => {
+      const store = usePrefsStore();
+      expect(store.segmentedEncoding).toBe(false);
+
+      store.setSegmentedEncoding(true);
+      expect(store.segmentedEncoding).toBe(true);
+    });
+
+    it('should keep sources after conversion by default', () => {
+      const store = usePrefsStore();
+      expect(store.trashSourceAfterSuccess).toBe(false);
+
+      store.setTrashSourceAfterSuccess(true);
+      expect(store.trashSourceAfterSuccess).toBe(true);
+    });
+
+    it('should not copy source timestamps by default', () => {
+      const store = usePrefsStore();
+      expect(store.preserveTimestamps).toBe(false);
+
+      store.setPreserveTimestamps(true);
+      expect(store.preserveTimestamps).toBe(true);
+    });
+
+    it('should leave encoder threads to ffmpeg by default', () => {
+      const store = usePrefsStore();
+      expect(store.encoderThreads).toBeNull();
+
+      store.setEncoderThreads(6.7);
+      expect(store.encoderThreads).toBe(6);
+
+      store.setEncoderThreads(0);
+      expect(store.encoderThreads).toBeNull();
+    });
+
+    it('should have no post-job hooks by default', () => {
+      const store = usePrefsStore();
+      expect(store.postJobHooks).toEqual([]);
+
+      store.setPostJobHooks([{ kind: 'webhook', url: 'https://example.com/done' }]);
+      expect(store.postJobHooks).toHaveLength(1);
+    });
+
+    it('should prevent sleep by default', () => {
+      const store = usePrefsStore();
+      expect(store.preventSleep).toBe(true);
+
+      store.setPreventSleep(false);
+      expect(store.preventSleep).toBe(false);
+    });
+
+    it('should ignore battery power by default', () => {
+      const store = usePrefsStore();
+      expect(store.batteryPolicy).toBe('ignore');
+
+      store.setBatteryPolicy('pause');
+      expect(store.batteryPolicy).toBe('pause');
+    });
   });
 
   describe('maxConcurrency computed', () => {
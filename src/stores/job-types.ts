@@ -37,7 +37,11 @@ export function createJobId(): JobId {
   if (typeof crypto !== 'undefined' && 'randomUUID' in crypto) {
     return crypto.randomUUID();
   }
-  return `job-${Math.random().toString(36).slice(2, 10)}`;
+  // The backend only accepts UUID job ids, so fall back to a v4-shaped id.
+  return 'xxxxxxxx-xxxx-4xxx-yxxx-xxxxxxxxxxxx'.replace(/[xy]/g, (char) => {
+    const nibble = Math.floor(Math.random() * 16);
+    return (char === 'x' ? nibble : (nibble & 0x3) | 0x8).toString(16);
+  });
 }
 
 export function readEnqueuedAt(state: JobState): number {
@@ -12,6 +12,42 @@ interface LicenseInfo {
   payload: string;
   signature: string;
   activatedAt: number | null;
+  /** Set when the license was activated against the license server */
+  serverActivation?: { token: string; validatedAt: number } | null;
+  /** Hashed id of the machine the license was activated on */
+  machineFingerprint?: string | null;
+  /** Per-feature grants; absent for version-1 licenses, which grant everything */
+  entitlements?: Entitlements | null;
+  /** Unix time a subscription license lapses; absent for perpetual licenses */
+  expiresAt?: number | null;
+  /** Whether the subscription had lapsed when the license was last verified */
+  expired?: boolean;
+}
+
+/** Features and limits a version-2 license grants */
+interface Entitlements {
+  features: string[];
+  /** Most files in one batch; absent means unlimited */
+  maxBatchSize?: number;
+}
+
+/** Outcome of deactivating the license so its seat can move to another machine */
+interface Deactivation {
+  licenseId: string;
+  /** Code to quote when activating elsewhere or contacting support */
+  confirmationCode: string;
+  deactivatedAt: number;
+  /** Whether the license server released the seat */
+  serverNotified: boolean;
+}
+
+/** The trial started on first run; times are Unix seconds */
+interface TrialStatus {
+  startedAt: number;
+  expiresAt: number;
+  /** Whole or partial days left; 0 once expired */
+  daysRemaining: number;
+  expired: boolean;
 }
 
 export const useLicenseStore = defineStore('license', () => {
@@ -29,6 +65,7 @@ export const useLicenseStore = defineStore('license', () => {
   };
 
   const current = ref<LicenseInfo | null>(null);
+  const trial = ref<TrialStatus | null>(null);
   const preview = ref<LicenseInfo | null>(null);
   const isLoading = ref(false);
   const isVerifying = ref(false);
@@ -55,9 +92,22 @@ export const useLicenseStore = defineStore('license', () => {
       promptOnInit.value = true;
     });
 
+    const unlistenRevalidated = await listen<LicenseInfo>('license://revalidated', (event) => {
+      current.value = event.payload;
+    });
+
+    // The license server was unreachable for longer than the offline grace period
+    const unlistenDegraded = await listen('license://degraded', () => {
+      current.value = null;
+      lastError.value = 'License could not be revalidated; connect to the internet to continue';
+      promptOnInit.value = true;
+    });
+
     stopListeners = () => {
       unlistenActivated();
       unlistenRemoved();
+      unlistenRevalidated();
+      unlistenDegraded();
     };
   }
 
@@ -75,7 +125,8 @@ export const useLicenseStore = defineStore('license', () => {
       }
 
       current.value = await invoke<LicenseInfo | null>('current_license');
-      promptOnInit.value = !current.value;
+      await refreshTrial();
+      promptOnInit.value = !current.value || Boolean(current.value.expired);
     } catch (error) {
       console.error('[licenseStore] Failed to load license', error);
       lastError.value = (error as Error).message;
@@ -85,6 +136,16 @@ export const useLicenseStore = defineStore('license', () => {
     }
   }
 
+  async function refreshTrial() {
+    try {
+      trial.value = await invoke<TrialStatus>('trial_status');
+    } catch (error) {
+      // A tampered trial record counts as no trial
+      console.error('[licenseStore] Failed to load trial', error);
+      trial.value = null;
+    }
+  }
+
   async function init() {
     await ensureListeners();
     await refresh();
@@ -166,6 +227,25 @@ export const useLicenseStore = defineStore('license', () => {
     }
   }
 
+  async function deactivate(notifyServer = true) {
+    try {
+      lastError.value = null;
+      if (isDev) {
+        return null;
+      }
+
+      const deactivation = await invoke<Deactivation>('deactivate_license', { notifyServer });
+      current.value = null;
+      preview.value = null;
+      promptOnInit.value = true;
+      return deactivation;
+    } catch (error) {
+      console.error('[licenseStore] Deactivation failed', error);
+      lastError.value = (error as Error).message;
+      return null;
+    }
+  }
+
   function clearError() {
     lastError.value = null;
   }
@@ -182,30 +262,59 @@ export const useLicenseStore = defineStore('license', () => {
     forcedDialogOpen.value = false;
   }
 
+  const trialActive = computed(() => Boolean(trial.value && !trial.value.expired));
+  const trialDaysRemaining = computed(() => trial.value?.daysRemaining ?? 0);
+  const hasPremiumAccess = computed(
+    () => Boolean(current.value && !current.value.expired) || trialActive.value,
+  );
+  /** Mirrors `LicenseInfo::has_entitlement`; the trial grants every feature */
+  function hasEntitlement(feature: string) {
+    if (!current.value) {
+      return trialActive.value;
+    }
+    if (current.value.expired) {
+      return false;
+    }
+    const entitlements = current.value.entitlements;
+    return !entitlements || entitlements.features.includes(feature);
+  }
+
   const needsActivation = computed(() => initialized.value && !current.value);
-  const shouldPrompt = computed(() => promptOnInit.value && needsActivation.value);
+  /** A subscription license that lapsed and needs a renewed key */
+  const needsRenewal = computed(() => Boolean(current.value?.expired));
+  const shouldPrompt = computed(
+    () => promptOnInit.value && (needsActivation.value || needsRenewal.value) && !trialActive.value,
+  );
 
   return {
     current,
+    trial,
     preview,
     isLoading,
     isVerifying,
     isActivating,
     lastError,
     initialized,
+    trialActive,
+    trialDaysRemaining,
+    hasPremiumAccess,
     needsActivation,
+    needsRenewal,
     shouldPrompt,
     forcedDialogOpen,
     init,
     refresh,
+    refreshTrial,
     verify,
     activate,
     remove,
+    deactivate,
     clearError,
     clearPrompt,
     requestActivationDialog,
     clearForcedDialog,
+    hasEntitlement,
   };
 });
 
-export type { LicenseInfo };
+export type { Deactivation, Entitlements, LicenseInfo, TrialStatus };
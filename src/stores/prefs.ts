@@ -1,18 +1,55 @@
 import { defineStore } from 'pinia';
-import { computed, ref } from 'vue';
+import { computed, ref, watch } from 'vue';
 
+import { loadAppMode } from '@/lib/app-mode';
 import { LIMITS, DEFAULTS } from '@/lib/constants';
+import { loadVersioned, saveVersioned, type StorageSchema } from '@/lib/versioned-storage';
+import type { BatteryPolicy, PostJobHook } from '@/services/execution-service';
 
 const MIN_CONCURRENCY = LIMITS.MIN_CONCURRENCY;
 const DEFAULT_CONCURRENCY = LIMITS.DEFAULT_CONCURRENCY;
 const DEFAULT_FILENAME_SEPARATOR = DEFAULTS.FILENAME_SEPARATOR;
 
+/** Stored preferences; add a migration when a field is renamed or reshaped. */
+export const PREFS_SCHEMA: StorageSchema = {
+  key: 'prefs',
+  migrations: [],
+};
+
+interface StoredPrefs {
+  preferredConcurrency?: number;
+  outputDirectory?: string | null;
+  includePresetInName?: boolean;
+  includeTierInName?: boolean;
+  filenameSeparator?: string;
+  lowPriority?: boolean;
+  segmentedEncoding?: boolean;
+  trashSourceAfterSuccess?: boolean;
+  preserveTimestamps?: boolean;
+  encoderThreads?: number | null;
+  preventSleep?: boolean;
+  batteryPolicy?: BatteryPolicy;
+}
+
 export const usePrefsStore = defineStore('prefs', () => {
   const preferredConcurrency = ref<number>(DEFAULT_CONCURRENCY);
   const outputDirectory = ref<string | null>(null);
   const includePresetInName = ref(true);
   const includeTierInName = ref(false);
   const filenameSeparator = ref(DEFAULT_FILENAME_SEPARATOR);
+  const lowPriority = ref(false);
+  const segmentedEncoding = ref(false);
+  const trashSourceAfterSuccess = ref(false);
+  const preserveTimestamps = ref(false);
+  const encoderThreads = ref<number | null>(null);
+  const preventSleep = ref(true);
+  const batteryPolicy = ref<BatteryPolicy>('ignore');
+  const postJobHooks = ref<PostJobHook[]>([]);
+  /** In read-only mode preferences apply to this session but aren't saved */
+  const readOnly = ref(false);
+  const modeLoaded = loadAppMode().then((mode) => {
+    readOnly.value = mode.readOnly;
+  });
 
   const maxConcurrency = computed(() =>
     Math.max(MIN_CONCURRENCY, Math.floor(preferredConcurrency.value || MIN_CONCURRENCY)),
@@ -41,6 +78,90 @@ export const usePrefsStore = defineStore('prefs', () => {
     filenameSeparator.value = value?.length ? value : DEFAULT_FILENAME_SEPARATOR;
   }
 
+  function setLowPriority(value: boolean) {
+    lowPriority.value = Boolean(value);
+  }
+
+  function setSegmentedEncoding(value: boolean) {
+    segmentedEncoding.value = Boolean(value);
+  }
+
+  function setTrashSourceAfterSuccess(value: boolean) {
+    trashSourceAfterSuccess.value = Boolean(value);
+  }
+
+  function setPreserveTimestamps(value: boolean) {
+    preserveTimestamps.value = Boolean(value);
+  }
+
+  function setEncoderThreads(value: number | null) {
+    const threads = Math.floor(value ?? 0);
+    encoderThreads.value = threads >= 1 ? threads : null;
+  }
+
+  function setPreventSleep(value: boolean) {
+    preventSleep.value = Boolean(value);
+  }
+
+  function setBatteryPolicy(value: BatteryPolicy) {
+    batteryPolicy.value = value;
+  }
+
+  function setPostJobHooks(value: PostJobHook[]) {
+    postJobHooks.value = [...value];
+  }
+
+  // Post-job hooks are stored by the backend, not here.
+  function restore(stored: StoredPrefs) {
+    if (stored.preferredConcurrency !== undefined) {
+      setPreferredConcurrency(stored.preferredConcurrency);
+    }
+    if (stored.outputDirectory !== undefined) setOutputDirectory(stored.outputDirectory);
+    if (stored.includePresetInName !== undefined) {
+      setIncludePresetInName(stored.includePresetInName);
+    }
+    if (stored.includeTierInName !== undefined) setIncludeTierInName(stored.includeTierInName);
+    if (stored.filenameSeparator !== undefined) setFilenameSeparator(stored.filenameSeparator);
+    if (stored.lowPriority !== undefined) setLowPriority(stored.lowPriority);
+    if (stored.segmentedEncoding !== undefined) setSegmentedEncoding(stored.segmentedEncoding);
+    if (stored.trashSourceAfterSuccess !== undefined) {
+      setTrashSourceAfterSuccess(stored.trashSourceAfterSuccess);
+    }
+    if (stored.preserveTimestamps !== undefined) setPreserveTimestamps(stored.preserveTimestamps);
+    if (stored.encoderThreads !== undefined) setEncoderThreads(stored.encoderThreads);
+    if (stored.preventSleep !== undefined) setPreventSleep(stored.preventSleep);
+    if (stored.batteryPolicy !== undefined) setBatteryPolicy(stored.batteryPolicy);
+  }
+
+  const stored = loadVersioned<StoredPrefs>(PREFS_SCHEMA);
+  if (stored && typeof stored === 'object') {
+    restore(stored);
+  }
+
+  watch(
+    (): StoredPrefs => ({
+      preferredConcurrency: preferredConcurrency.value,
+      outputDirectory: outputDirectory.value,
+      includePresetInName: includePresetInName.value,
+      includeTierInName: includeTierInName.value,
+      filenameSeparator: filenameSeparator.value,
+      lowPriority: lowPriority.value,
+      segmentedEncoding: segmentedEncoding.value,
+      trashSourceAfterSuccess: trashSourceAfterSuccess.value,
+      preserveTimestamps: preserveTimestamps.value,
+      encoderThreads: encoderThreads.value,
+      preventSleep: preventSleep.value,
+      batteryPolicy: batteryPolicy.value,
+    }),
+    (snapshot) => {
+      void modeLoaded.then(() => {
+        if (!readOnly.value) {
+          saveVersioned(PREFS_SCHEMA, snapshot);
+        }
+      });
+    },
+  );
+
   return {
     preferredConcurrency,
     maxConcurrency,
@@ -49,10 +170,27 @@ export const usePrefsStore = defineStore('prefs', () => {
     includePresetInName,
     includeTierInName,
     filenameSeparator,
+    lowPriority,
+    segmentedEncoding,
+    trashSourceAfterSuccess,
+    preserveTimestamps,
+    encoderThreads,
+    preventSleep,
+    batteryPolicy,
+    postJobHooks,
+    readOnly,
     setPreferredConcurrency,
     setOutputDirectory,
     setIncludePresetInName,
     setIncludeTierInName,
     setFilenameSeparator,
+    setLowPriority,
+    setSegmentedEncoding,
+    setTrashSourceAfterSuccess,
+    setPreserveTimestamps,
+    setEncoderThreads,
+    setPreventSleep,
+    setBatteryPolicy,
+    setPostJobHooks,
   };
 });